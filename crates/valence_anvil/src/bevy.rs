@@ -1,5 +1,6 @@
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU32;
 use std::path::PathBuf;
 use std::thread;
 
@@ -8,6 +9,7 @@ use bevy_ecs::prelude::*;
 use flume::{Receiver, Sender};
 use valence_server::client::{Client, OldView, View};
 use valence_server::entity::{EntityLayerId, OldEntityLayerId};
+use valence_server::layer::chunk::UnloadedChunk;
 use valence_server::layer::UpdateLayersPreClientSet;
 use valence_server::protocol::anyhow;
 use valence_server::registry::BiomeRegistry;
@@ -15,7 +17,20 @@ use valence_server::{ChunkLayer, ChunkPos};
 
 use crate::parsing::{DimensionFolder, ParsedChunk};
 
-type WorkerResult = anyhow::Result<Option<ParsedChunk>>;
+/// A request for the anvil worker.
+enum WorkerMsg {
+    Load(ChunkPos),
+    Save {
+        pos: ChunkPos,
+        chunk: UnloadedChunk,
+        min_y: i32,
+    },
+}
+
+enum WorkerResult {
+    Load(anyhow::Result<Option<ParsedChunk>>),
+    Save(anyhow::Result<()>),
+}
 
 /// The order in which chunks should be processed by the anvil worker. Smaller
 /// values are sent first.
@@ -30,11 +45,26 @@ pub struct AnvilLevel {
     ///
     /// This set is empty by default, but you can modify it at any time.
     pub ignored_chunks: HashSet<ChunkPos>,
+    /// Whether [dirty] chunks are written back to the region files when they
+    /// are unloaded and when autosaving.
+    ///
+    /// This is `false` by default.
+    ///
+    /// [dirty]: valence_server::layer::chunk::LoadedChunk::is_dirty
+    pub save_chunks: bool,
+    /// The number of ticks between autosaves, where all dirty chunks in the
+    /// layer are saved without being unloaded. Autosaving is disabled if this
+    /// is `None` or [`AnvilLevel::save_chunks`] is `false`.
+    ///
+    /// This is 6000 ticks (five minutes) by default.
+    pub autosave_interval: Option<NonZeroU32>,
+    /// The number of ticks since the last autosave.
+    ticks_since_autosave: u32,
     /// Chunks that need to be loaded. Chunks with `None` priority have already
     /// been sent to the anvil thread.
     pending: HashMap<ChunkPos, Option<Priority>>,
     /// Sender for the chunk worker thread.
-    sender: Sender<ChunkPos>,
+    sender: Sender<WorkerMsg>,
     /// Receiver for the chunk worker thread.
     receiver: Receiver<(ChunkPos, WorkerResult)>,
 }
@@ -51,6 +81,9 @@ impl AnvilLevel {
                 receiver: pending_receiver,
             }),
             ignored_chunks: HashSet::new(),
+            save_chunks: false,
+            autosave_interval: NonZeroU32::new(6000),
+            ticks_since_autosave: 0,
             pending: HashMap::new(),
             sender: pending_sender,
             receiver: finished_receiver,
@@ -77,6 +110,28 @@ impl AnvilLevel {
             }
         }
     }
+
+    /// Queues all [dirty] chunks in the given layer to be written to the
+    /// region files, and marks them as clean. A [`ChunkSaveEvent`] is sent
+    /// for each chunk once it is written.
+    ///
+    /// This ignores [`AnvilLevel::save_chunks`].
+    ///
+    /// [dirty]: valence_server::layer::chunk::LoadedChunk::is_dirty
+    pub fn save_dirty_chunks(&self, layer: &mut ChunkLayer) {
+        let min_y = layer.min_y();
+
+        for (pos, chunk) in layer.chunks_mut() {
+            if chunk.is_dirty() {
+                chunk.set_dirty(false);
+                self.save_chunk(pos, chunk.to_unloaded(), min_y);
+            }
+        }
+    }
+
+    fn save_chunk(&self, pos: ChunkPos, chunk: UnloadedChunk, min_y: i32) {
+        let _ = self.sender.send(WorkerMsg::Save { pos, chunk, min_y });
+    }
 }
 
 #[derive(Debug)]
@@ -87,7 +142,7 @@ struct ChunkWorkerState {
     /// Sender of finished chunks.
     sender: Sender<(ChunkPos, WorkerResult)>,
     /// Receiver of pending chunks.
-    receiver: Receiver<ChunkPos>,
+    receiver: Receiver<WorkerMsg>,
 }
 
 pub struct AnvilPlugin;
@@ -96,10 +151,16 @@ impl Plugin for AnvilPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ChunkLoadEvent>()
             .add_event::<ChunkUnloadEvent>()
+            .add_event::<ChunkSaveEvent>()
             .add_systems(PreUpdate, remove_unviewed_chunks)
             .add_systems(
                 PostUpdate,
-                (init_anvil, update_client_views, send_recv_chunks)
+                (
+                    init_anvil,
+                    update_client_views,
+                    send_recv_chunks,
+                    autosave_chunks,
+                )
                     .chain()
                     .before(UpdateLayersPreClientSet),
             );
@@ -114,7 +175,8 @@ fn init_anvil(mut query: Query<&mut AnvilLevel, (Added<AnvilLevel>, With<ChunkLa
     }
 }
 
-/// Removes all chunks no longer viewed by clients.
+/// Removes all chunks no longer viewed by clients, saving them first if
+/// enabled.
 ///
 /// This needs to run in `PreUpdate` where the chunk viewer counts have been
 /// updated from the previous tick.
@@ -123,10 +185,16 @@ fn remove_unviewed_chunks(
     mut unload_events: EventWriter<ChunkUnloadEvent>,
 ) {
     for (entity, mut layer, anvil) in &mut chunk_layers {
+        let min_y = layer.min_y();
+
         layer.retain_chunks(|pos, chunk| {
             if chunk.viewer_count_mut() > 0 || anvil.ignored_chunks.contains(&pos) {
                 true
             } else {
+                if anvil.save_chunks && chunk.is_dirty() {
+                    anvil.save_chunk(pos, chunk.to_unloaded(), min_y);
+                }
+
                 unload_events.send(ChunkUnloadEvent {
                     chunk_layer: entity,
                     pos,
@@ -182,18 +250,41 @@ fn send_recv_chunks(
     mut layers: Query<(Entity, &mut ChunkLayer, &mut AnvilLevel)>,
     mut to_send: Local<Vec<(Priority, ChunkPos)>>,
     mut load_events: EventWriter<ChunkLoadEvent>,
+    mut save_events: EventWriter<ChunkSaveEvent>,
 ) {
     for (entity, mut layer, anvil) in &mut layers {
         let anvil = anvil.into_inner();
 
         // Insert the chunks that are finished loading into the chunk layer and send
-        // load events.
+        // load and save events.
         for (pos, res) in anvil.receiver.drain() {
+            let res = match res {
+                WorkerResult::Load(res) => res,
+                WorkerResult::Save(res) => {
+                    save_events.send(ChunkSaveEvent {
+                        chunk_layer: entity,
+                        pos,
+                        status: match res {
+                            Ok(()) => ChunkSaveStatus::Success,
+                            Err(e) => ChunkSaveStatus::Failed(e),
+                        },
+                    });
+
+                    continue;
+                }
+            };
+
             anvil.pending.remove(&pos);
 
             let status = match res {
                 Ok(Some(ParsedChunk { chunk, timestamp })) => {
                     layer.insert_chunk(pos, chunk);
+
+                    // The chunk matches what is on disk.
+                    if let Some(chunk) = layer.chunk_mut(pos) {
+                        chunk.set_dirty(false);
+                    }
+
                     ChunkLoadStatus::Success { timestamp }
                 }
                 Ok(None) => ChunkLoadStatus::Empty,
@@ -219,17 +310,46 @@ fn send_recv_chunks(
 
         // Send the sorted chunks to be loaded.
         for (_, pos) in to_send.drain(..) {
-            let _ = anvil.sender.try_send(pos);
+            let _ = anvil.sender.try_send(WorkerMsg::Load(pos));
+        }
+    }
+}
+
+fn autosave_chunks(mut layers: Query<(&mut ChunkLayer, &mut AnvilLevel)>) {
+    for (mut layer, mut anvil) in &mut layers {
+        let Some(interval) = anvil.autosave_interval.filter(|_| anvil.save_chunks) else {
+            continue;
+        };
+
+        anvil.ticks_since_autosave += 1;
+
+        if anvil.ticks_since_autosave >= interval.get() {
+            anvil.ticks_since_autosave = 0;
+            anvil.save_dirty_chunks(&mut layer);
         }
     }
 }
 
 fn anvil_worker(mut state: ChunkWorkerState) {
-    while let Ok(pos) = state.receiver.recv() {
-        let res = state
-            .dimension_folder
-            .get_chunk(pos)
-            .map_err(anyhow::Error::from);
+    while let Ok(msg) = state.receiver.recv() {
+        let (pos, res) = match msg {
+            WorkerMsg::Load(pos) => {
+                let res = state
+                    .dimension_folder
+                    .get_chunk(pos)
+                    .map_err(anyhow::Error::from);
+
+                (pos, WorkerResult::Load(res))
+            }
+            WorkerMsg::Save { pos, chunk, min_y } => {
+                let res = state
+                    .dimension_folder
+                    .set_chunk(pos, &chunk, min_y)
+                    .map_err(anyhow::Error::from);
+
+                (pos, WorkerResult::Save(res))
+            }
+        };
 
         let _ = state.sender.send((pos, res));
     }
@@ -268,3 +388,21 @@ pub struct ChunkUnloadEvent {
     /// The position of the chunk that was unloaded.
     pub pos: ChunkPos,
 }
+
+/// An event sent by `valence_anvil` after an attempt to save a chunk is made.
+#[derive(Event, Debug)]
+pub struct ChunkSaveEvent {
+    /// The [`ChunkLayer`] the chunk was saved from.
+    pub chunk_layer: Entity,
+    /// The position of the chunk in the layer.
+    pub pos: ChunkPos,
+    pub status: ChunkSaveStatus,
+}
+
+#[derive(Debug)]
+pub enum ChunkSaveStatus {
+    /// The chunk was written to the region files.
+    Success,
+    /// An attempt was made to save the chunk, but something went wrong.
+    Failed(anyhow::Error),
+}
@@ -1,12 +1,13 @@
 use std::borrow::Cow;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
 use std::path::PathBuf;
 
 use thiserror::Error;
 use valence_server::block::{PropName, PropValue};
 use valence_server::layer::chunk::{Chunk, UnloadedChunk};
-use valence_server::nbt::{Compound, List, Value};
-use valence_server::protocol::BlockKind;
+use valence_server::nbt::{compound, Compound, List, Value};
+use valence_server::protocol::{BlockKind, BlockState};
 use valence_server::registry::biome::BiomeId;
 use valence_server::registry::BiomeRegistry;
 use valence_server::{ChunkPos, Ident};
@@ -18,6 +19,8 @@ pub struct DimensionFolder {
     region: RegionFolder,
     /// Mapping of biome names to their biome ID.
     biome_to_id: BTreeMap<Ident<String>, BiomeId>,
+    /// Mapping of biome IDs to their biome name.
+    id_to_biome: BTreeMap<BiomeId, Ident<String>>,
 }
 
 impl DimensionFolder {
@@ -31,6 +34,10 @@ impl DimensionFolder {
                 .iter()
                 .map(|(id, name, _)| (name.to_string_ident(), id))
                 .collect(),
+            id_to_biome: biomes
+                .iter()
+                .map(|(id, name, _)| (id, name.to_string_ident()))
+                .collect(),
         }
     }
 
@@ -50,6 +57,22 @@ impl DimensionFolder {
             timestamp: raw_chunk.timestamp,
         }))
     }
+
+    /// Writes a chunk to the region files at the given chunk position,
+    /// overwriting the old chunk if it exists.
+    ///
+    /// `min_y` is the minimum Y coordinate of the chunk's dimension. Biomes
+    /// that are not in the biome registry this folder was created with are
+    /// written as `minecraft:plains`.
+    pub fn set_chunk<C: Chunk>(
+        &mut self,
+        pos: ChunkPos,
+        chunk: &C,
+        min_y: i32,
+    ) -> Result<(), RegionError> {
+        let nbt = encode_chunk(chunk, pos, min_y, &self.id_to_biome);
+        self.region.set_chunk(pos.x, pos.z, &nbt)
+    }
 }
 
 /// A chunk parsed to show block information, biome information etc.
@@ -363,6 +386,239 @@ fn parse_chunk(
     Ok(chunk)
 }
 
+/// The data version of chunks written by [`DimensionFolder::set_chunk`].
+pub const DATA_VERSION: i32 = 3955;
+
+/// Converts a chunk to the NBT format used in region files. Light is not
+/// written, so the game will relight the chunk when it is loaded.
+fn encode_chunk<C: Chunk>(
+    chunk: &C,
+    pos: ChunkPos,
+    min_y: i32,
+    biome_names: &BTreeMap<BiomeId, Ident<String>>,
+) -> Compound {
+    let min_sect_y = min_y.div_euclid(16);
+
+    let mut sections = vec![];
+    let mut block_entities = vec![];
+
+    let mut block_palette = Palette::default();
+    let mut biome_palette = Palette::default();
+
+    for sect_y in 0..chunk.height() / 16 {
+        block_palette.clear();
+
+        for i in 0..BLOCKS_PER_SECTION as u32 {
+            let x = i % 16;
+            let z = i / 16 % 16;
+            let y = sect_y * 16 + i / (16 * 16);
+
+            let state = chunk.block_state(x, y, z);
+
+            block_palette.push(state);
+
+            if let Some(kind) = state.block_entity_kind() {
+                if let Some(nbt) = chunk.block_entity(x, y, z) {
+                    let mut nbt = nbt.clone();
+
+                    nbt.insert("id", kind.ident().to_string());
+                    nbt.insert("x", pos.x * 16 + x as i32);
+                    nbt.insert("y", min_sect_y * 16 + y as i32);
+                    nbt.insert("z", pos.z * 16 + z as i32);
+                    nbt.insert("keepPacked", false);
+
+                    block_entities.push(nbt);
+                }
+            }
+        }
+
+        biome_palette.clear();
+
+        for i in 0..BIOMES_PER_SECTION as u32 {
+            let x = i % 4;
+            let z = i / 4 % 4;
+            let y = sect_y * 4 + i / (4 * 4);
+
+            biome_palette.push(chunk.biome(x, y, z));
+        }
+
+        let mut block_states = compound! {
+            "palette" => List::Compound(
+                block_palette
+                    .values
+                    .iter()
+                    .map(|&state| encode_block_state(state))
+                    .collect(),
+            ),
+        };
+
+        if block_palette.values.len() > 1 {
+            let bits_per_idx = bit_width(block_palette.values.len() - 1).max(4);
+            block_states.insert("data", pack_idxs(&block_palette.idxs, bits_per_idx));
+        }
+
+        let mut biomes = compound! {
+            "palette" => List::String(
+                biome_palette
+                    .values
+                    .iter()
+                    .map(|biome| {
+                        biome_names
+                            .get(biome)
+                            .map_or("minecraft:plains", |name| name.as_str())
+                            .to_owned()
+                    })
+                    .collect(),
+            ),
+        };
+
+        if biome_palette.values.len() > 1 {
+            let bits_per_idx = bit_width(biome_palette.values.len() - 1);
+            biomes.insert("data", pack_idxs(&biome_palette.idxs, bits_per_idx));
+        }
+
+        sections.push(compound! {
+            "Y" => (min_sect_y + sect_y as i32) as i8,
+            "block_states" => block_states,
+            "biomes" => biomes,
+        });
+    }
+
+    compound! {
+        "DataVersion" => DATA_VERSION,
+        "xPos" => pos.x,
+        "zPos" => pos.z,
+        "yPos" => min_sect_y,
+        "Status" => "minecraft:full",
+        "LastUpdate" => 0_i64,
+        "InhabitedTime" => 0_i64,
+        "isLightOn" => false,
+        "sections" => List::Compound(sections),
+        "block_entities" => List::Compound(block_entities),
+        "Heightmaps" => encode_heightmaps(chunk),
+        "block_ticks" => List::End,
+        "fluid_ticks" => List::End,
+        "PostProcessing" => List::End,
+        "structures" => compound! {
+            "References" => Compound::new(),
+            "starts" => Compound::new(),
+        },
+    }
+}
+
+fn encode_block_state(state: BlockState) -> Compound {
+    let kind = state.to_kind();
+
+    let mut nbt = compound! {
+        "Name" => format!("minecraft:{}", kind.to_str()),
+    };
+
+    if !kind.props().is_empty() {
+        let properties = kind
+            .props()
+            .iter()
+            .filter_map(|&name| {
+                let value = state.get(name)?;
+                Some((name.to_str().to_owned(), value.to_str().into()))
+            })
+            .collect::<Compound>();
+
+        nbt.insert("Properties", properties);
+    }
+
+    nbt
+}
+
+/// Computes the `MOTION_BLOCKING` and `WORLD_SURFACE` heightmaps of a chunk.
+/// Each value is the height above the bottom of the chunk of the block above
+/// the highest matching block in the column, or zero if no block matches.
+fn encode_heightmaps<C: Chunk>(chunk: &C) -> Compound {
+    let mut motion_blocking = Vec::with_capacity(16 * 16);
+    let mut world_surface = Vec::with_capacity(16 * 16);
+
+    for z in 0..16 {
+        for x in 0..16 {
+            let mut motion_blocking_height = 0;
+            let mut world_surface_height = 0;
+
+            for y in (0..chunk.height()).rev() {
+                let state = chunk.block_state(x, y, z);
+
+                if world_surface_height == 0 && !state.is_air() {
+                    world_surface_height = y + 1;
+                }
+
+                if state.blocks_motion()
+                    || state.is_liquid()
+                    || state.get(PropName::Waterlogged) == Some(PropValue::True)
+                {
+                    motion_blocking_height = y + 1;
+                    break;
+                }
+            }
+
+            motion_blocking.push(u64::from(motion_blocking_height));
+            world_surface.push(u64::from(world_surface_height));
+        }
+    }
+
+    let bits_per_entry = bit_width(chunk.height() as usize);
+
+    compound! {
+        "MOTION_BLOCKING" => pack_idxs(&motion_blocking, bits_per_entry),
+        "WORLD_SURFACE" => pack_idxs(&world_surface, bits_per_entry),
+    }
+}
+
+/// A palette of values and the palette indices of a section's entries.
+struct Palette<T> {
+    values: Vec<T>,
+    value_to_idx: HashMap<T, u64>,
+    idxs: Vec<u64>,
+}
+
+impl<T> Default for Palette<T> {
+    fn default() -> Self {
+        Self {
+            values: vec![],
+            value_to_idx: HashMap::new(),
+            idxs: vec![],
+        }
+    }
+}
+
+impl<T: Copy + Eq + Hash> Palette<T> {
+    fn clear(&mut self) {
+        self.values.clear();
+        self.value_to_idx.clear();
+        self.idxs.clear();
+    }
+
+    fn push(&mut self, value: T) {
+        let idx = *self.value_to_idx.entry(value).or_insert_with(|| {
+            self.values.push(value);
+            self.values.len() as u64 - 1
+        });
+
+        self.idxs.push(idx);
+    }
+}
+
+/// Packs indices into longs the same way they are unpacked when parsing.
+/// Indices do not span across longs.
+fn pack_idxs(idxs: &[u64], bits_per_idx: usize) -> Vec<i64> {
+    let idxs_per_long = 64 / bits_per_idx;
+
+    idxs.chunks(idxs_per_long)
+        .map(|idxs| {
+            idxs.iter()
+                .enumerate()
+                .fold(0_u64, |long, (j, &idx)| long | (idx << (bits_per_idx * j)))
+                as i64
+        })
+        .collect()
+}
+
 const BLOCKS_PER_SECTION: usize = 16 * 16 * 16;
 const BIOMES_PER_SECTION: usize = 4 * 4 * 4;
 
@@ -378,3 +634,66 @@ fn ident_path(ident: &str) -> &str {
 const fn bit_width(n: usize) -> usize {
     (usize::BITS - n.leading_zeros()) as usize
 }
+
+#[cfg(test)]
+mod tests {
+    use valence_server::registry::RegistryIdx;
+
+    use super::*;
+
+    #[test]
+    fn encode_parse_roundtrip() {
+        let mut chunk = UnloadedChunk::with_height(64);
+
+        chunk.fill_block_states(BlockState::STONE);
+        chunk.fill_block_state_section(3, BlockState::AIR);
+        chunk.set_block_state(
+            1,
+            40,
+            2,
+            BlockState::OAK_LOG.set(PropName::Axis, PropValue::X),
+        );
+        chunk.set_block_state(5, 48, 5, BlockState::CHEST);
+        chunk.set_block_entity(5, 48, 5, Some(compound! { "Lock" => "secret" }));
+        chunk.fill_biomes(BiomeId::from_index(1));
+        chunk.set_biome(0, 0, 0, BiomeId::from_index(2));
+
+        let biome_names = BTreeMap::from([
+            (
+                BiomeId::from_index(1),
+                Ident::new("minecraft:plains".to_owned()).unwrap(),
+            ),
+            (
+                BiomeId::from_index(2),
+                Ident::new("minecraft:desert".to_owned()).unwrap(),
+            ),
+        ]);
+        let biome_ids = biome_names
+            .iter()
+            .map(|(id, name)| (name.clone(), *id))
+            .collect();
+
+        let nbt = encode_chunk(&chunk, ChunkPos::new(3, -2), -64, &biome_names);
+
+        assert_eq!(nbt.get("yPos"), Some(&Value::Int(-4)));
+
+        let parsed = parse_chunk(nbt, &biome_ids).unwrap();
+
+        assert_eq!(parsed.height(), chunk.height());
+
+        for y in 0..chunk.height() {
+            for z in 0..16 {
+                for x in 0..16 {
+                    assert_eq!(parsed.block_state(x, y, z), chunk.block_state(x, y, z));
+                }
+            }
+        }
+
+        assert_eq!(parsed.biome(0, 0, 0), BiomeId::from_index(2));
+        assert_eq!(parsed.biome(1, 0, 0), BiomeId::from_index(1));
+        assert_eq!(
+            parsed.block_entity(5, 48, 5),
+            Some(&compound! { "Lock" => "secret" })
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+
+use valence_server::protocol::FixedBitSet;
+
+/// The number of messages a client can acknowledge at once.
+pub(crate) const LAST_SEEN_WINDOW: usize = 20;
+
+/// The maximum number of unacknowledged messages a client may have before it
+/// is disconnected.
+pub(crate) const MAX_PENDING: usize = 4096;
+
+pub(crate) type Signature = Box<[u8; 256]>;
+
+/// Tracks the signed messages sent to a client and validates the
+/// acknowledgements the client sends back.
+#[derive(Debug)]
+pub(crate) struct LastSeenValidator {
+    /// The messages that can be acknowledged. The window is always the first
+    /// [`LAST_SEEN_WINDOW`] entries. Entries are `None` when the client
+    /// ignored the message.
+    tracked: VecDeque<Option<TrackedMessage>>,
+    last_pending: Option<Signature>,
+}
+
+#[derive(Debug)]
+struct TrackedMessage {
+    signature: Signature,
+    /// If the message was not yet acknowledged by the client.
+    pending: bool,
+}
+
+/// An acknowledgement that doesn't match the messages sent to the client.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct InvalidAcknowledgement;
+
+impl LastSeenValidator {
+    pub(crate) fn new() -> Self {
+        Self {
+            tracked: std::iter::repeat_with(|| None)
+                .take(LAST_SEEN_WINDOW)
+                .collect(),
+            last_pending: None,
+        }
+    }
+
+    /// Returns the number of messages sent to the client that were not moved
+    /// out of the window yet.
+    pub(crate) fn tracked_count(&self) -> usize {
+        self.tracked.len()
+    }
+
+    /// Tracks a signed message sent to the client.
+    pub(crate) fn add_pending(&mut self, signature: &[u8; 256]) {
+        if self.last_pending.as_deref() != Some(signature) {
+            self.tracked.push_back(Some(TrackedMessage {
+                signature: Box::new(*signature),
+                pending: true,
+            }));
+            self.last_pending = Some(Box::new(*signature));
+        }
+    }
+
+    /// Moves the window forward by `offset` messages.
+    pub(crate) fn apply_offset(&mut self, offset: i32) -> Result<(), InvalidAcknowledgement> {
+        let max_offset = self.tracked.len() - LAST_SEEN_WINDOW;
+
+        let offset = usize::try_from(offset)
+            .ok()
+            .filter(|&offset| offset <= max_offset)
+            .ok_or(InvalidAcknowledgement)?;
+
+        self.tracked.drain(..offset);
+
+        Ok(())
+    }
+
+    /// Moves the window forward by `offset` messages and applies the
+    /// acknowledgements of the messages in the window. Returns the signatures
+    /// of the acknowledged messages, oldest first.
+    pub(crate) fn apply_update(
+        &mut self,
+        offset: i32,
+        acknowledged: FixedBitSet<LAST_SEEN_WINDOW, 3>,
+    ) -> Result<Vec<Signature>, InvalidAcknowledgement> {
+        self.apply_offset(offset)?;
+
+        let mut last_seen = vec![];
+
+        for (i, entry) in self.tracked.iter_mut().take(LAST_SEEN_WINDOW).enumerate() {
+            if acknowledged.bit(i) {
+                // Acknowledging a message that was never sent or was already ignored.
+                let Some(message) = entry else {
+                    return Err(InvalidAcknowledgement);
+                };
+
+                message.pending = false;
+                last_seen.push(message.signature.clone());
+            } else {
+                // Ignoring a message that was already acknowledged.
+                if matches!(entry, Some(message) if !message.pending) {
+                    return Err(InvalidAcknowledgement);
+                }
+
+                *entry = None;
+            }
+        }
+
+        Ok(last_seen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(indices: &[usize]) -> FixedBitSet<LAST_SEEN_WINDOW, 3> {
+        let mut set = FixedBitSet([0; 3]);
+        for &i in indices {
+            set.set_bit(i, true);
+        }
+        set
+    }
+
+    #[test]
+    fn acknowledging_sent_messages() {
+        let mut validator = LastSeenValidator::new();
+
+        validator.add_pending(&[1; 256]);
+        validator.add_pending(&[2; 256]);
+        // Duplicates are only tracked once.
+        validator.add_pending(&[2; 256]);
+
+        assert_eq!(validator.tracked_count(), LAST_SEEN_WINDOW + 2);
+
+        let last_seen = validator.apply_update(2, bits(&[18, 19])).unwrap();
+
+        assert_eq!(last_seen, [Box::new([1; 256]), Box::new([2; 256])]);
+        assert_eq!(validator.tracked_count(), LAST_SEEN_WINDOW);
+    }
+
+    #[test]
+    fn invalid_acknowledgements() {
+        let mut validator = LastSeenValidator::new();
+
+        // Offset past the sent messages.
+        assert_eq!(validator.apply_offset(1), Err(InvalidAcknowledgement));
+
+        // Acknowledging a message that was never sent.
+        assert_eq!(
+            validator.apply_update(0, bits(&[0])),
+            Err(InvalidAcknowledgement)
+        );
+
+        validator.add_pending(&[1; 256]);
+        validator.apply_update(1, bits(&[19])).unwrap();
+
+        // Ignoring a message that was acknowledged before.
+        assert_eq!(
+            validator.apply_update(0, bits(&[])),
+            Err(InvalidAcknowledgement)
+        );
+    }
+}
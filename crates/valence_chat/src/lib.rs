@@ -0,0 +1,560 @@
+#![doc = include_str!("../README.md")]
+
+use std::borrow::Cow;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use rsa::RsaPublicKey;
+use tracing::warn;
+use valence_player_list::{PlayerListEntry, PlayerListSet};
+use valence_server::client::{
+    Client, DisconnectClient, FlushPacketsSet, SpawnClientsSet, Username,
+};
+use valence_server::event_loop::{EventLoopPreUpdate, PacketEvent};
+use valence_server::message::SendMessage;
+use valence_server::protocol::packets::play::chat_message_s2c::{
+    MessageFilterType, MessageSignature,
+};
+use valence_server::protocol::packets::play::player_list_s2c::{self, PlayerListActions};
+use valence_server::protocol::packets::play::{
+    ChatMessageC2s, ChatMessageS2c, MessageAcknowledgmentC2s, PlayerListS2c, PlayerSessionC2s,
+    ProfilelessChatMessageS2c,
+};
+use valence_server::protocol::{Bounded, VarInt, WritePacket};
+use valence_server::registry::RegistryCodec;
+use valence_server::text::{Color, IntoText};
+use valence_server::uuid::{Uuid, Version};
+use valence_server::{ident, Ident, Text, UniqueId};
+
+mod last_seen;
+mod session;
+
+use last_seen::{LastSeenValidator, MAX_PENDING};
+pub use session::ChatSession;
+
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatSettings>()
+            .add_event::<ChatMessageEvent>()
+            .add_event::<ChatSessionEvent>()
+            .add_event::<RelayChatEvent>()
+            .add_systems(PreUpdate, init_chat_states.after(SpawnClientsSet))
+            .add_systems(EventLoopPreUpdate, handle_chat_packets)
+            .add_systems(
+                PostUpdate,
+                (
+                    init_chat_sessions_for_clients,
+                    broadcast_chat_sessions,
+                    broadcast_chat_messages,
+                    relay_chat_messages,
+                )
+                    .chain()
+                    // Clients must know about players before receiving their chat sessions and
+                    // messages.
+                    .after(PlayerListSet)
+                    .before(FlushPacketsSet),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct ChatSettings {
+    /// If chat messages are relayed to all clients using [`Self::chat_type`].
+    /// Disable this to choose the receivers of messages yourself by sending
+    /// [`RelayChatEvent`]s.
+    ///
+    /// Defaults to `false`, so servers handling chat messages themselves, such
+    /// as chat based commands, do not relay them to everyone.
+    pub broadcast: bool,
+    /// The chat type from the `minecraft:chat_type` registry used for
+    /// broadcast messages.
+    ///
+    /// Defaults to `minecraft:chat`.
+    pub chat_type: Ident<String>,
+    /// If players without a valid chat session are prevented from chatting.
+    ///
+    /// Defaults to `false`.
+    pub enforce_secure_chat: bool,
+    /// The keys used to check that the public keys of players were issued by
+    /// Mojang. These are the `playerCertificateKeys` from
+    /// `https://api.minecraftservices.com/publickeys`.
+    ///
+    /// If empty, public keys are accepted without checking who issued them.
+    /// Clients still check the keys of other players themselves.
+    pub player_certificate_keys: Vec<RsaPublicKey>,
+}
+
+impl Default for ChatSettings {
+    fn default() -> Self {
+        Self {
+            broadcast: false,
+            chat_type: ident!("chat").into(),
+            enforce_secure_chat: false,
+            player_certificate_keys: vec![],
+        }
+    }
+}
+
+/// The chat state of a client.
+#[derive(Component, Debug)]
+pub struct ChatState {
+    session: Option<ChatSession>,
+    /// The index of the next message in the chain of signed messages of the
+    /// session.
+    next_index: i32,
+    /// The timestamp of the last message in milliseconds.
+    last_timestamp: u64,
+    /// The signed messages sent to this client.
+    last_seen: LastSeenValidator,
+}
+
+impl ChatState {
+    fn new() -> Self {
+        Self {
+            session: None,
+            next_index: 0,
+            last_timestamp: 0,
+            last_seen: LastSeenValidator::new(),
+        }
+    }
+
+    /// Returns the chat session of the client. This is `None` if the client
+    /// has not sent a valid session, which is always the case in offline
+    /// mode.
+    pub fn session(&self) -> Option<&ChatSession> {
+        self.session.as_ref()
+    }
+}
+
+/// A chat message sent by a client. Only messages that passed validation are
+/// sent as events.
+#[derive(Event, Clone, Debug)]
+pub struct ChatMessageEvent {
+    pub client: Entity,
+    pub message: Box<str>,
+    /// Unix timestamp in milliseconds.
+    pub timestamp: u64,
+    /// The signature of the message, or `None` if the client has no chat
+    /// session.
+    pub signature: Option<Box<SignedMessage>>,
+}
+
+/// The signature of a chat message and the data needed to relay it to other
+/// clients as signed player chat.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SignedMessage {
+    /// The UUID of the player that sent the message.
+    pub sender: Uuid,
+    /// The index of the message in the chain of messages sent in the
+    /// sender's chat session.
+    pub index: i32,
+    pub salt: u64,
+    pub signature: Box<[u8; 256]>,
+    /// The signatures of the messages the sender had seen, oldest first.
+    pub last_seen: Vec<Box<[u8; 256]>>,
+}
+
+/// Sent when a client starts a new chat session.
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ChatSessionEvent {
+    pub client: Entity,
+}
+
+/// Send this event to show a chat message to clients. Signed messages are sent
+/// as player chat, and unsigned messages as system chat.
+#[derive(Event, Clone, Debug)]
+pub struct RelayChatEvent {
+    pub message: ChatMessageEvent,
+    /// The chat type from the `minecraft:chat_type` registry used to decorate
+    /// the message.
+    pub chat_type: Ident<String>,
+    /// The target of the message, for chat types such as
+    /// `minecraft:msg_command_outgoing`.
+    pub target_name: Option<Text>,
+    /// The clients to send the message to, or `None` to send it to all
+    /// clients.
+    pub receivers: Option<Vec<Entity>>,
+}
+
+fn init_chat_states(clients: Query<Entity, Added<Client>>, mut commands: Commands) {
+    for entity in &clients {
+        commands.entity(entity).insert(ChatState::new());
+    }
+}
+
+fn disconnect(commands: &mut Commands, client: Entity, reason: &'static str) {
+    commands.add(DisconnectClient {
+        client,
+        reason: Text::translate(reason, []),
+    });
+}
+
+fn is_illegal_char(c: char) -> bool {
+    c == '§' || c < ' ' || c == '\x7f'
+}
+
+fn handle_chat_packets(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut Client, &mut ChatState, &UniqueId)>,
+    settings: Res<ChatSettings>,
+    mut message_events: EventWriter<ChatMessageEvent>,
+    mut session_events: EventWriter<ChatSessionEvent>,
+    mut commands: Commands,
+) {
+    for packet in packets.read() {
+        let Ok((mut client, state, uuid)) = clients.get_mut(packet.client) else {
+            continue;
+        };
+
+        let state = state.into_inner();
+
+        if let Some(pkt) = packet.decode::<PlayerSessionC2s>() {
+            // Offline players have name based UUIDs, which their keys were not issued for.
+            if uuid.0.get_version() != Some(Version::Random) {
+                continue;
+            }
+
+            let Some(session) = ChatSession::new(
+                pkt.session_id,
+                pkt.expires_at,
+                pkt.public_key_data.0,
+                pkt.key_signature.0,
+            ) else {
+                disconnect(
+                    &mut commands,
+                    packet.client,
+                    "multiplayer.disconnect.invalid_public_key",
+                );
+                continue;
+            };
+
+            if session.is_expired() {
+                disconnect(
+                    &mut commands,
+                    packet.client,
+                    "multiplayer.disconnect.expired_public_key",
+                );
+                continue;
+            }
+
+            if !settings.player_certificate_keys.is_empty()
+                && !session.is_issued_by(uuid.0, &settings.player_certificate_keys)
+            {
+                disconnect(
+                    &mut commands,
+                    packet.client,
+                    "multiplayer.disconnect.invalid_public_key_signature",
+                );
+                continue;
+            }
+
+            state.session = Some(session);
+            state.next_index = 0;
+
+            session_events.send(ChatSessionEvent {
+                client: packet.client,
+            });
+        } else if let Some(pkt) = packet.decode::<MessageAcknowledgmentC2s>() {
+            if state.last_seen.apply_offset(pkt.message_count.0).is_err() {
+                disconnect(
+                    &mut commands,
+                    packet.client,
+                    "multiplayer.disconnect.chat_validation_failed",
+                );
+            }
+        } else if let Some(pkt) = packet.decode::<ChatMessageC2s>() {
+            let message = pkt.message.0;
+
+            if message.chars().any(is_illegal_char) {
+                disconnect(
+                    &mut commands,
+                    packet.client,
+                    "multiplayer.disconnect.illegal_characters",
+                );
+                continue;
+            }
+
+            if pkt.timestamp < state.last_timestamp {
+                disconnect(
+                    &mut commands,
+                    packet.client,
+                    "multiplayer.disconnect.out_of_order_chat",
+                );
+                continue;
+            }
+
+            state.last_timestamp = pkt.timestamp;
+
+            let Ok(last_seen) = state
+                .last_seen
+                .apply_update(pkt.message_count.0, pkt.acknowledgement)
+            else {
+                disconnect(
+                    &mut commands,
+                    packet.client,
+                    "multiplayer.disconnect.chat_validation_failed",
+                );
+                continue;
+            };
+
+            let signature = match (&state.session, pkt.signature) {
+                (Some(session), Some(signature)) => {
+                    if session.is_expired() {
+                        client.send_chat_message(
+                            Text::translate("chat.disabled.expiredProfileKey", [])
+                                .color(Color::RED),
+                        );
+                        continue;
+                    }
+
+                    let index = state.next_index;
+
+                    if !session.verify_message(
+                        uuid.0,
+                        index,
+                        pkt.salt,
+                        pkt.timestamp,
+                        message,
+                        &last_seen,
+                        signature,
+                    ) {
+                        disconnect(
+                            &mut commands,
+                            packet.client,
+                            "multiplayer.disconnect.chat_validation_failed",
+                        );
+                        continue;
+                    }
+
+                    state.next_index += 1;
+
+                    Some(Box::new(SignedMessage {
+                        sender: uuid.0,
+                        index,
+                        salt: pkt.salt,
+                        signature: Box::new(*signature),
+                        last_seen,
+                    }))
+                }
+                // Clients with a chat session sign all of their messages.
+                (Some(_), None) => {
+                    disconnect(
+                        &mut commands,
+                        packet.client,
+                        "multiplayer.disconnect.chat_validation_failed",
+                    );
+                    continue;
+                }
+                (None, _) => {
+                    if settings.enforce_secure_chat {
+                        client.send_chat_message(
+                            Text::translate("chat.disabled.missingProfileKey", [])
+                                .color(Color::RED),
+                        );
+                        continue;
+                    }
+
+                    None
+                }
+            };
+
+            message_events.send(ChatMessageEvent {
+                client: packet.client,
+                message: message.into(),
+                timestamp: pkt.timestamp,
+                signature,
+            });
+        }
+    }
+}
+
+fn chat_session_entry(uuid: Uuid, session: &ChatSession) -> player_list_s2c::PlayerListEntry {
+    player_list_s2c::PlayerListEntry {
+        player_uuid: uuid,
+        chat_data: Some(session.chat_data()),
+        ..Default::default()
+    }
+}
+
+fn init_chat_sessions_for_clients(
+    mut clients: Query<&mut Client, Added<Client>>,
+    sessions: Query<(&UniqueId, &ChatState), With<PlayerListEntry>>,
+) {
+    if clients.is_empty() {
+        return;
+    }
+
+    let entries: Vec<_> = sessions
+        .iter()
+        .filter_map(|(uuid, state)| Some(chat_session_entry(uuid.0, state.session.as_ref()?)))
+        .collect();
+
+    if entries.is_empty() {
+        return;
+    }
+
+    for mut client in &mut clients {
+        client.write_packet(&PlayerListS2c {
+            actions: PlayerListActions::new().with_initialize_chat(true),
+            entries: Cow::Borrowed(&entries),
+        });
+    }
+}
+
+fn broadcast_chat_sessions(
+    mut events: EventReader<ChatSessionEvent>,
+    sessions: Query<(&UniqueId, &ChatState), With<PlayerListEntry>>,
+    mut clients: Query<&mut Client>,
+) {
+    for event in events.read() {
+        let Ok((uuid, state)) = sessions.get(event.client) else {
+            continue;
+        };
+
+        let Some(session) = &state.session else {
+            continue;
+        };
+
+        let entries = [chat_session_entry(uuid.0, session)];
+
+        for mut client in &mut clients {
+            client.write_packet(&PlayerListS2c {
+                actions: PlayerListActions::new().with_initialize_chat(true),
+                entries: Cow::Borrowed(&entries),
+            });
+        }
+    }
+}
+
+fn broadcast_chat_messages(
+    settings: Res<ChatSettings>,
+    mut messages: EventReader<ChatMessageEvent>,
+    mut relay_events: EventWriter<RelayChatEvent>,
+) {
+    for message in messages.read() {
+        if settings.broadcast {
+            relay_events.send(RelayChatEvent {
+                message: message.clone(),
+                chat_type: settings.chat_type.clone(),
+                target_name: None,
+                receivers: None,
+            });
+        }
+    }
+}
+
+fn relay_chat_messages(
+    mut events: EventReader<RelayChatEvent>,
+    senders: Query<(&Username, Has<PlayerListEntry>)>,
+    mut receivers: Query<(Entity, &mut Client, &mut ChatState)>,
+    codec: Res<RegistryCodec>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let Some(chat_type) =
+            codec.entry_index(ident!("chat_type"), event.chat_type.as_str_ident())
+        else {
+            warn!(
+                "attempt to relay chat message with unknown chat type {}",
+                event.chat_type
+            );
+            continue;
+        };
+
+        let Ok((username, listed)) = senders.get(event.message.client) else {
+            continue;
+        };
+
+        let relay = ChatRelay {
+            message: &event.message,
+            // Clients disconnect when receiving signed messages from players missing from their
+            // player list.
+            signed: event.message.signature.as_deref().filter(|_| listed),
+            // Chat types are sent as registry references, which are offset by one.
+            chat_type: VarInt(chat_type as i32 + 1),
+            sender_name: Text::text(username.0.clone()),
+            target_name: event.target_name.as_ref(),
+        };
+
+        match &event.receivers {
+            Some(entities) => {
+                for &entity in entities {
+                    if let Ok((entity, mut client, mut state)) = receivers.get_mut(entity) {
+                        relay.send(entity, &mut client, &mut state, &mut commands);
+                    }
+                }
+            }
+            None => {
+                for (entity, mut client, mut state) in &mut receivers {
+                    relay.send(entity, &mut client, &mut state, &mut commands);
+                }
+            }
+        }
+    }
+}
+
+struct ChatRelay<'a> {
+    message: &'a ChatMessageEvent,
+    signed: Option<&'a SignedMessage>,
+    chat_type: VarInt,
+    sender_name: Text,
+    target_name: Option<&'a Text>,
+}
+
+impl ChatRelay<'_> {
+    fn send(
+        &self,
+        entity: Entity,
+        client: &mut Client,
+        state: &mut ChatState,
+        commands: &mut Commands,
+    ) {
+        let Some(signed) = self.signed else {
+            client.write_packet(&ProfilelessChatMessageS2c {
+                message: Cow::Owned(Text::text(self.message.message.to_string())),
+                chat_type: self.chat_type,
+                chat_type_name: Cow::Borrowed(&self.sender_name),
+                target_name: self.target_name.map(Cow::Borrowed),
+            });
+
+            return;
+        };
+
+        client.write_packet(&ChatMessageS2c {
+            sender: signed.sender,
+            index: VarInt(signed.index),
+            message_signature: Some(&*signed.signature),
+            message: Bounded(&*self.message.message),
+            timestamp: self.message.timestamp,
+            salt: signed.salt,
+            // Always send the full signatures, so there is no need to track which signatures
+            // the client has cached.
+            previous_messages: signed
+                .last_seen
+                .iter()
+                .map(|signature| MessageSignature {
+                    message_id: -1,
+                    signature: Some(&**signature),
+                })
+                .collect(),
+            unsigned_content: None,
+            filter_type: MessageFilterType::PassThrough,
+            filter_type_bits: None,
+            chat_type: self.chat_type,
+            network_name: Cow::Borrowed(&self.sender_name),
+            network_target_name: self.target_name.map(Cow::Borrowed),
+        });
+
+        state.last_seen.add_pending(&signed.signature);
+
+        if state.last_seen.tracked_count() > MAX_PENDING {
+            disconnect(
+                commands,
+                entity,
+                "multiplayer.disconnect.too_many_pending_chats",
+            );
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rsa::pkcs8::DecodePublicKey;
+use rsa::{Pkcs1v15Sign, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use valence_server::protocol::packets::play::player_list_s2c::ChatData;
+use valence_server::uuid::Uuid;
+
+/// The chat session of a player, containing the public key their messages are
+/// signed with.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ChatSession {
+    session_id: Uuid,
+    expires_at: i64,
+    public_key: RsaPublicKey,
+    public_key_der: Box<[u8]>,
+    key_signature: Box<[u8]>,
+}
+
+impl ChatSession {
+    /// Parses a chat session from the data sent by the client. Returns `None`
+    /// if the public key is malformed.
+    pub(crate) fn new(
+        session_id: Uuid,
+        expires_at: i64,
+        public_key_der: &[u8],
+        key_signature: &[u8],
+    ) -> Option<Self> {
+        Some(Self {
+            session_id,
+            expires_at,
+            public_key: RsaPublicKey::from_public_key_der(public_key_der).ok()?,
+            public_key_der: public_key_der.into(),
+            key_signature: key_signature.into(),
+        })
+    }
+
+    pub fn session_id(&self) -> Uuid {
+        self.session_id
+    }
+
+    /// The time the public key expires at as a Unix timestamp in milliseconds.
+    pub fn expires_at(&self) -> i64 {
+        self.expires_at
+    }
+
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+
+    /// Returns if the public key has expired.
+    pub fn is_expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as i64);
+
+        self.expires_at < now
+    }
+
+    /// Returns if the public key was issued to the player with `uuid` by the
+    /// owner of one of the given keys.
+    pub(crate) fn is_issued_by(&self, uuid: Uuid, issuer_keys: &[RsaPublicKey]) -> bool {
+        let mut hasher = Sha1::new();
+        hasher.update(uuid.as_bytes());
+        hasher.update(self.expires_at.to_be_bytes());
+        hasher.update(&self.public_key_der);
+        let hash = hasher.finalize();
+
+        issuer_keys.iter().any(|key| {
+            key.verify(Pkcs1v15Sign::new::<Sha1>(), &hash, &self.key_signature)
+                .is_ok()
+        })
+    }
+
+    /// Returns if `signature` is a valid signature of a chat message sent in
+    /// this session.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn verify_message(
+        &self,
+        sender: Uuid,
+        index: i32,
+        salt: u64,
+        timestamp: u64,
+        message: &str,
+        last_seen: &[Box<[u8; 256]>],
+        signature: &[u8; 256],
+    ) -> bool {
+        let hash = self.message_hash(sender, index, salt, timestamp, message, last_seen);
+
+        self.public_key
+            .verify(Pkcs1v15Sign::new::<Sha256>(), &hash, signature)
+            .is_ok()
+    }
+
+    /// Returns the hash of a chat message that is signed by the client.
+    fn message_hash(
+        &self,
+        sender: Uuid,
+        index: i32,
+        salt: u64,
+        timestamp: u64,
+        message: &str,
+        last_seen: &[Box<[u8; 256]>],
+    ) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+
+        // Signature version.
+        hasher.update(1_i32.to_be_bytes());
+
+        // Message link.
+        hasher.update(sender.as_bytes());
+        hasher.update(self.session_id.as_bytes());
+        hasher.update(index.to_be_bytes());
+
+        // Message body. The timestamp is signed in seconds.
+        hasher.update(salt.to_be_bytes());
+        hasher.update((timestamp / 1000).to_be_bytes());
+        hasher.update((message.len() as i32).to_be_bytes());
+        hasher.update(message.as_bytes());
+        hasher.update((last_seen.len() as i32).to_be_bytes());
+
+        for seen in last_seen {
+            hasher.update(seen.as_slice());
+        }
+
+        hasher.finalize().to_vec()
+    }
+
+    pub(crate) fn chat_data(&self) -> ChatData {
+        ChatData {
+            session_id: self.session_id,
+            key_expiry_time: self.expires_at,
+            public_key: &self.public_key_der,
+            public_key_signature: &self.key_signature,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rsa::pkcs8::EncodePublicKey;
+    use rsa::RsaPrivateKey;
+
+    use super::*;
+
+    fn session(key: &RsaPrivateKey) -> ChatSession {
+        let der = key.to_public_key().to_public_key_der().unwrap();
+
+        ChatSession::new(Uuid::from_u128(1), i64::MAX, der.as_bytes(), &[]).unwrap()
+    }
+
+    fn sign(key: &RsaPrivateKey, session: &ChatSession, sender: Uuid, message: &str) -> [u8; 256] {
+        let hash = session.message_hash(sender, 0, 42, 1_000_000, message, &[]);
+
+        key.sign(Pkcs1v15Sign::new::<Sha256>(), &hash)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn verify_message_accepts_valid_signature() {
+        let key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+        let session = session(&key);
+        let sender = Uuid::from_u128(2);
+
+        let signature = sign(&key, &session, sender, "hello");
+
+        assert!(session.verify_message(sender, 0, 42, 1_000_000, "hello", &[], &signature));
+    }
+
+    #[test]
+    fn verify_message_rejects_tampered_signature() {
+        let key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+        let session = session(&key);
+        let sender = Uuid::from_u128(2);
+
+        let mut signature = sign(&key, &session, sender, "hello");
+
+        // The signature does not match a different message.
+        assert!(!session.verify_message(sender, 0, 42, 1_000_000, "goodbye", &[], &signature));
+
+        signature[100] ^= 1;
+
+        assert!(!session.verify_message(sender, 0, 42, 1_000_000, "hello", &[], &signature));
+    }
+}
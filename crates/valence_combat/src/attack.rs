@@ -0,0 +1,130 @@
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use valence_equipment::Equipment;
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::entity::Flags;
+use valence_server::entity::living::Health;
+use valence_server::entity::OnGround;
+use valence_server::interact_entity::{EntityInteraction, InteractEntityEvent};
+use valence_server::registry::RegistryCodec;
+use valence_server::{ident, GameMode, Server};
+
+use crate::{enchantment_level, CombatSettings, CombatState, DamageEvent};
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct AttackerQuery {
+    state: &'static mut CombatState,
+    health: &'static Health,
+    attributes: Option<&'static EntityAttributes>,
+    equipment: Option<&'static Equipment>,
+    flags: Option<&'static Flags>,
+    on_ground: Option<&'static OnGround>,
+    game_mode: Option<&'static GameMode>,
+}
+
+/// Turns the attacks of players into [`DamageEvent`]s.
+pub(crate) fn handle_player_attacks(
+    mut events: EventReader<InteractEntityEvent>,
+    mut attackers: Query<AttackerQuery>,
+    victims: Query<&Health, With<CombatState>>,
+    mut damage_events: EventWriter<DamageEvent>,
+    codec: Res<RegistryCodec>,
+    server: Res<Server>,
+    settings: Res<CombatSettings>,
+) {
+    for event in events.read() {
+        if !settings.player_attacks
+            || event.interact != EntityInteraction::Attack
+            || event.client == event.entity
+        {
+            continue;
+        }
+
+        if !victims.get(event.entity).is_ok_and(|health| health.0 > 0.0) {
+            continue;
+        }
+
+        let Ok(mut attacker) = attackers.get_mut(event.client) else {
+            continue;
+        };
+
+        if attacker.health.0 <= 0.0 || attacker.game_mode == Some(&GameMode::Spectator) {
+            continue;
+        }
+
+        let attribute = |attribute: EntityAttribute| {
+            attacker
+                .attributes
+                .and_then(|a| a.get_compute_value(attribute))
+                .unwrap_or_else(|| attribute.default_value())
+        };
+
+        let progress = attack_cooldown_progress(
+            server.current_tick() - attacker.state.last_attack_tick,
+            attribute(EntityAttribute::GenericAttackSpeed),
+        );
+
+        let main_hand = attacker.equipment.map(Equipment::main_hand);
+        let enchantment =
+            |name| main_hand.map_or(0, |stack| enchantment_level(stack, &codec, name));
+
+        let sharpness = enchantment(ident!("sharpness"));
+        let sharpness_damage = if sharpness > 0 {
+            0.5 * sharpness as f32 + 0.5
+        } else {
+            0.0
+        };
+
+        let mut damage = attribute(EntityAttribute::GenericAttackDamage) as f32;
+        damage *= 0.2 + progress * progress * 0.8;
+
+        let strong = progress > 0.9;
+        let sprinting = attacker.flags.is_some_and(Flags::sprinting);
+        let on_ground = attacker.on_ground.map_or(true, |on_ground| on_ground.0);
+
+        // Vanilla also requires the player to be falling, which the server
+        // doesn't know.
+        if strong && !sprinting && !on_ground {
+            damage *= 1.5;
+        }
+
+        damage += sharpness_damage * progress;
+
+        let mut knockback = attribute(EntityAttribute::GenericAttackKnockback) as f32
+            + enchantment(ident!("knockback")) as f32;
+
+        if strong && sprinting {
+            knockback += 1.0;
+        }
+
+        attacker.state.last_attack_tick = server.current_tick();
+
+        damage_events.send(
+            DamageEvent::new(event.entity, ident!("player_attack"), damage)
+                .with_source(event.client)
+                .with_bonus_knockback(knockback * 0.5),
+        );
+    }
+}
+
+/// Returns how far the attack cooldown has progressed, from 0 to 1, after the
+/// given number of ticks.
+fn attack_cooldown_progress(ticks: i64, attack_speed: f64) -> f32 {
+    let cooldown_ticks = 20.0 / attack_speed;
+
+    ((ticks as f64 + 0.5) / cooldown_ticks).clamp(0.0, 1.0) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attack_cooldown() {
+        // Swords have an attack speed of 1.6, so the cooldown is 12.5 ticks.
+        assert_eq!(attack_cooldown_progress(0, 1.6), 0.04);
+        assert_eq!(attack_cooldown_progress(12, 1.6), 1.0);
+        assert_eq!(attack_cooldown_progress(i64::MAX, 4.0), 1.0);
+    }
+}
@@ -0,0 +1,423 @@
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use tracing::warn;
+use valence_equipment::Equipment;
+use valence_server::client::Client;
+use valence_server::entity::active_status_effects::ActiveStatusEffects;
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::living::{Absorption, Health};
+use valence_server::entity::{EntityId, EntityLayerId, Look, OnGround, Position, Velocity};
+use valence_server::math::{DVec2, DVec3};
+use valence_server::protocol::packets::play::{DamageTiltS2c, EntityDamageS2c};
+use valence_server::protocol::status_effects::StatusEffect;
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::registry::{RegistryCodec, TagsRegistry};
+use valence_server::{ident, EntityLayer, GameMode, Layer};
+
+use crate::{
+    enchantment_level, CombatSettings, CombatState, DamageAppliedEvent, DamageEvent, LastDamage,
+    PendingDamage,
+};
+
+/// The base strength of the knockback of damage that has a direction.
+const BASE_KNOCKBACK: f64 = 0.4;
+
+/// The number of game ticks in a second. [`Velocity`] is measured in meters
+/// per second while knockback is calculated in blocks per tick.
+const TICKS_PER_SECOND: f32 = 20.0;
+
+pub(crate) fn tick_hurt_cooldown(mut entities: Query<&mut CombatState>) {
+    for mut state in &mut entities {
+        if state.hurt_cooldown > 0 {
+            state.hurt_cooldown -= 1;
+        }
+    }
+}
+
+pub(crate) fn collect_damage_events(
+    mut events: EventReader<DamageEvent>,
+    mut pending: ResMut<PendingDamage>,
+) {
+    pending.extend(events.read().cloned());
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct VictimQuery {
+    entity: Entity,
+    id: &'static EntityId,
+    layer: &'static EntityLayerId,
+    pos: &'static Position,
+    health: &'static mut Health,
+    absorption: Option<&'static mut Absorption>,
+    state: &'static mut CombatState,
+    attributes: Option<&'static EntityAttributes>,
+    effects: Option<&'static ActiveStatusEffects>,
+    equipment: Option<&'static Equipment>,
+    game_mode: Option<&'static GameMode>,
+    look: Option<&'static Look>,
+    on_ground: Option<&'static OnGround>,
+    velocity: Option<&'static mut Velocity>,
+    client: Option<&'static mut Client>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_pending_damage(
+    mut pending: ResMut<PendingDamage>,
+    mut victims: Query<VictimQuery>,
+    sources: Query<(&EntityId, &Position)>,
+    mut layers: Query<&mut EntityLayer>,
+    codec: Res<RegistryCodec>,
+    tags: Res<TagsRegistry>,
+    settings: Res<CombatSettings>,
+    server: Res<valence_server::Server>,
+    mut applied_events: EventWriter<DamageAppliedEvent>,
+) {
+    for damage in pending.drain(..) {
+        let Ok(mut victim) = victims.get_mut(damage.victim) else {
+            continue;
+        };
+
+        if victim.health.0 <= 0.0 {
+            // Dead entities can't be damaged again.
+            continue;
+        }
+
+        let Some(type_id) =
+            codec.entry_index(ident!("damage_type"), damage.damage_type.as_str_ident())
+        else {
+            warn!("unknown damage type `{}`", damage.damage_type);
+            continue;
+        };
+
+        let has_tag = |tag: &str| tags.contains("minecraft:damage_type", tag, type_id as i32);
+
+        if matches!(
+            victim.game_mode,
+            Some(GameMode::Creative | GameMode::Spectator)
+        ) && !has_tag("minecraft:bypasses_invulnerability")
+        {
+            continue;
+        }
+
+        if damage.amount.is_nan() || damage.amount <= 0.0 {
+            continue;
+        }
+
+        if has_tag("minecraft:is_fire")
+            && victim
+                .effects
+                .is_some_and(|e| e.has_effect(StatusEffect::FireResistance))
+        {
+            continue;
+        }
+
+        // The cooldown counts down, so this is the first half of the
+        // invulnerability. Damage is only applied during it if it's larger than
+        // the damage that started it.
+        let mut amount = damage.amount;
+        let hurt = if victim.state.hurt_cooldown > settings.invulnerability_ticks / 2 {
+            if amount <= victim.state.hurt_amount {
+                continue;
+            }
+
+            amount -= victim.state.hurt_amount;
+            victim.state.hurt_amount = damage.amount;
+            false
+        } else {
+            victim.state.hurt_amount = damage.amount;
+            victim.state.hurt_cooldown = settings.invulnerability_ticks;
+            true
+        };
+
+        if !has_tag("minecraft:bypasses_armor") {
+            let attribute = |attribute: EntityAttribute| {
+                victim
+                    .attributes
+                    .and_then(|a| a.get_compute_value(attribute))
+                    .unwrap_or_default() as f32
+            };
+
+            amount = armor_reduction(
+                amount,
+                attribute(EntityAttribute::GenericArmor),
+                attribute(EntityAttribute::GenericArmorToughness),
+            );
+        }
+
+        if !has_tag("minecraft:bypasses_effects") {
+            if !has_tag("minecraft:bypasses_resistance") {
+                if let Some(resistance) = victim
+                    .effects
+                    .and_then(|e| e.get_current_effect(StatusEffect::Resistance))
+                {
+                    amount = resistance_reduction(amount, resistance.amplifier());
+                }
+            }
+
+            if amount > 0.0 && !has_tag("minecraft:bypasses_enchantments") {
+                if let Some(equipment) = victim.equipment {
+                    let protection = protection_points(equipment, &codec, has_tag);
+                    amount = protection_reduction(amount, protection);
+                }
+            }
+        }
+
+        let mut to_health = amount;
+
+        if let Some(absorption) = &mut victim.absorption {
+            let absorbed = to_health.min(absorption.0).max(0.0);
+
+            if absorbed > 0.0 {
+                absorption.0 -= absorbed;
+                to_health -= absorbed;
+            }
+        }
+
+        if to_health > 0.0 {
+            victim.health.0 = (victim.health.0 - to_health).max(0.0);
+        }
+
+        victim.state.last_damage = Some(LastDamage {
+            damage_type: damage.damage_type.clone(),
+            source: damage.source,
+        });
+
+        if let Some(source) = damage.source {
+            victim.state.attacker = Some((source, server.current_tick()));
+        }
+
+        if hurt {
+            send_damage_packets(&mut victim, &damage, type_id, &sources, &mut layers);
+
+            if !has_tag("minecraft:no_knockback") {
+                apply_knockback(&mut victim, &damage, &sources);
+            }
+        }
+
+        applied_events.send(DamageAppliedEvent { damage, amount });
+    }
+}
+
+fn send_damage_packets(
+    victim: &mut VictimQueryItem,
+    damage: &DamageEvent,
+    type_id: usize,
+    sources: &Query<(&EntityId, &Position)>,
+    layers: &mut Query<&mut EntityLayer>,
+) {
+    let victim_entity = victim.entity;
+
+    // Entity IDs are sent plus one, with zero meaning no entity.
+    let source_id = |source: Option<Entity>, self_id: i32| {
+        VarInt(source.map_or(0, |source| {
+            if source == victim_entity {
+                self_id + 1
+            } else {
+                sources.get(source).map_or(0, |(id, _)| id.get() + 1)
+            }
+        }))
+    };
+
+    let mut pkt = EntityDamageS2c {
+        entity_id: VarInt(victim.id.get()),
+        source_type_id: VarInt(type_id as i32),
+        source_cause_id: source_id(damage.source, victim.id.get()),
+        source_direct_id: source_id(damage.direct_source, victim.id.get()),
+        source_pos: damage.position,
+    };
+
+    if let Ok(mut layer) = layers.get_mut(victim.layer.0) {
+        layer
+            .view_except_writer(victim.pos.0, victim.entity)
+            .write_packet(&pkt);
+    }
+
+    if let Some(client) = &mut victim.client {
+        // Clients see themselves with the entity ID 0.
+        pkt.entity_id = VarInt(0);
+        pkt.source_cause_id = source_id(damage.source, 0);
+        pkt.source_direct_id = source_id(damage.direct_source, 0);
+
+        client.write_packet(&pkt);
+    }
+}
+
+fn apply_knockback(
+    victim: &mut VictimQueryItem,
+    damage: &DamageEvent,
+    sources: &Query<(&EntityId, &Position)>,
+) {
+    let from = damage
+        .direct_source
+        .or(damage.source)
+        .and_then(|source| sources.get(source).ok())
+        .map(|(_, pos)| pos.0)
+        .or(damage.position);
+
+    // Unlike vanilla, damage without a direction doesn't knock back in a random
+    // direction.
+    let Some(from) = from else {
+        return;
+    };
+
+    let dir = DVec2::new(from.x - victim.pos.0.x, from.z - victim.pos.0.z);
+
+    if dir.length_squared() < 1.0e-5 {
+        return;
+    }
+
+    let resistance = victim
+        .attributes
+        .and_then(|a| a.get_compute_value(EntityAttribute::GenericKnockbackResistance))
+        .unwrap_or_default();
+
+    let on_ground = victim.on_ground.map_or(true, |on_ground| on_ground.0);
+
+    // The server doesn't know the velocity of clients, so it's assumed to be
+    // zero.
+    let mut vel = victim
+        .velocity
+        .as_ref()
+        .map_or(DVec3::ZERO, |vel| (vel.0 / TICKS_PER_SECOND).as_dvec3());
+
+    for strength in [BASE_KNOCKBACK, f64::from(damage.bonus_knockback)] {
+        vel = knockback_velocity(vel, strength * (1.0 - resistance), dir, on_ground);
+    }
+
+    let vel = vel.as_vec3() * TICKS_PER_SECOND;
+
+    if let Some(velocity) = &mut victim.velocity {
+        velocity.0 = vel;
+    }
+
+    if let Some(client) = &mut victim.client {
+        client.set_velocity(vel);
+
+        let yaw = victim.look.map_or(0.0, |look| look.yaw);
+
+        client.write_packet(&DamageTiltS2c {
+            entity_id: VarInt(0),
+            yaw: dir.y.atan2(dir.x).to_degrees() as f32 - yaw,
+        });
+    }
+}
+
+/// Returns the velocity of an entity in blocks per tick after it is knocked
+/// away from the direction `dir` with `strength`.
+fn knockback_velocity(vel: DVec3, strength: f64, dir: DVec2, on_ground: bool) -> DVec3 {
+    if strength <= 0.0 {
+        return vel;
+    }
+
+    let push = dir.normalize() * strength;
+
+    DVec3::new(
+        vel.x / 2.0 - push.x,
+        if on_ground {
+            (vel.y / 2.0 + strength).min(0.4)
+        } else {
+            vel.y
+        },
+        vel.z / 2.0 - push.y,
+    )
+}
+
+/// Reduces damage by the armor and armor toughness of the victim.
+fn armor_reduction(amount: f32, armor: f32, toughness: f32) -> f32 {
+    let toughness_factor = 2.0 + toughness / 4.0;
+    let effective_armor = (armor - amount / toughness_factor).clamp(armor * 0.2, 20.0);
+
+    amount * (1.0 - effective_armor / 25.0)
+}
+
+/// Reduces damage by the resistance effect with the given amplifier.
+fn resistance_reduction(amount: f32, amplifier: u8) -> f32 {
+    let reduction = (f32::from(amplifier) + 1.0) * 5.0;
+
+    (amount * (25.0 - reduction) / 25.0).max(0.0)
+}
+
+/// Reduces damage by the protection points of the victim's enchantments.
+fn protection_reduction(amount: f32, protection: f32) -> f32 {
+    amount * (1.0 - protection.clamp(0.0, 20.0) / 25.0)
+}
+
+/// Returns the protection points of the armor in `equipment` against a damage
+/// type with the tags tested by `has_tag`.
+fn protection_points<F: Fn(&str) -> bool>(
+    equipment: &Equipment,
+    codec: &RegistryCodec,
+    has_tag: F,
+) -> f32 {
+    let mut points = 0;
+
+    for slot in Equipment::FEET_IDX..=Equipment::HEAD_IDX {
+        let stack = equipment.slot(slot);
+
+        if stack.is_empty() {
+            continue;
+        }
+
+        let level = |name| enchantment_level(stack, codec, name);
+
+        if !has_tag("minecraft:bypasses_invulnerability") {
+            points += level(ident!("protection"));
+        }
+
+        if has_tag("minecraft:is_fire") {
+            points += level(ident!("fire_protection")) * 2;
+        }
+
+        if has_tag("minecraft:is_explosion") {
+            points += level(ident!("blast_protection")) * 2;
+        }
+
+        if has_tag("minecraft:is_projectile") {
+            points += level(ident!("projectile_protection")) * 2;
+        }
+
+        if has_tag("minecraft:is_fall") {
+            points += level(ident!("feather_falling")) * 3;
+        }
+    }
+
+    points as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn armor_reduces_damage() {
+        // Full diamond armor.
+        assert_eq!(armor_reduction(10.0, 20.0, 8.0), 10.0 * (1.0 - 17.5 / 25.0));
+        // Armor always blocks at least a fifth of its points.
+        assert_eq!(
+            armor_reduction(100.0, 10.0, 0.0),
+            100.0 * (1.0 - 2.0 / 25.0)
+        );
+        assert_eq!(armor_reduction(5.0, 0.0, 0.0), 5.0);
+    }
+
+    #[test]
+    fn resistance_and_protection_reduce_damage() {
+        assert_eq!(resistance_reduction(10.0, 0), 8.0);
+        assert_eq!(resistance_reduction(10.0, 4), 0.0);
+        assert_eq!(protection_reduction(10.0, 4.0), 10.0 * (1.0 - 4.0 / 25.0));
+        // Protection is capped at 20 points.
+        assert_eq!(protection_reduction(10.0, 64.0), 2.0);
+    }
+
+    #[test]
+    fn knockback_pushes_away_from_source() {
+        let vel = knockback_velocity(DVec3::ZERO, 0.4, DVec2::new(1.0, 0.0), true);
+
+        assert_eq!(vel, DVec3::new(-0.4, 0.4, 0.0));
+
+        let vel = knockback_velocity(DVec3::ZERO, 0.4, DVec2::new(0.0, 2.0), false);
+
+        assert_eq!(vel, DVec3::new(0.0, 0.0, -0.4));
+    }
+}
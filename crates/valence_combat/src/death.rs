@@ -0,0 +1,317 @@
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use valence_server::client::{Client, Username, VisibleChunkLayer};
+use valence_server::entity::active_status_effects::ActiveStatusEffects;
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::entity::CustomName;
+use valence_server::entity::living::{Absorption, Health};
+use valence_server::entity::player::{Food, Saturation};
+use valence_server::entity::{EntityKind, EntityStatus, EntityStatuses, Look, Position};
+use valence_server::ident::Ident;
+use valence_server::message::SendMessage;
+use valence_server::nbt::Value;
+use valence_server::registry::RegistryCodec;
+use valence_server::spawn::RespawnPosition;
+use valence_server::status::RequestRespawnEvent;
+use valence_server::text::Text;
+use valence_server::{ident, Despawned, Server};
+
+use crate::{CombatSettings, CombatState, LastDamage};
+
+/// The number of ticks a previous attacker is still credited with a death
+/// caused by damage without a source, such as falling after being hit.
+const ATTACKER_MEMORY_TICKS: i64 = 100;
+
+/// The number of ticks the death animation of non-player entities lasts
+/// before they are despawned.
+const DEATH_ANIMATION_TICKS: i64 = 20;
+
+/// Marks a living entity that has died.
+///
+/// Players stay dead until they respawn. Other entities are despawned once
+/// their death animation has finished.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Dead {
+    /// The tick the entity died.
+    pub tick: i64,
+}
+
+/// Sent when a living entity dies.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    /// The damage type of the damage that killed the entity, if any.
+    pub damage_type: Option<Ident<String>>,
+    /// The entity credited with the death, if any.
+    pub killer: Option<Entity>,
+    /// The death message, as shown on the death screen of players.
+    pub message: Text,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn detect_deaths(
+    entities: Query<(Entity, &Health, &CombatState, Has<Client>), (Changed<Health>, Without<Dead>)>,
+    mut statuses: Query<&mut EntityStatuses>,
+    mut clients: Query<&mut Client>,
+    names: Query<(Option<&Username>, Option<&CustomName>, &EntityKind)>,
+    codec: Res<RegistryCodec>,
+    server: Res<Server>,
+    settings: Res<CombatSettings>,
+    mut death_events: EventWriter<DeathEvent>,
+    mut commands: Commands,
+) {
+    let mut player_deaths = vec![];
+
+    for (entity, health, state, is_client) in &entities {
+        if health.0 > 0.0 {
+            continue;
+        }
+
+        let killer = state
+            .last_damage
+            .as_ref()
+            .and_then(|damage| damage.source)
+            .or_else(|| {
+                state
+                    .attacker
+                    .filter(|&(_, tick)| server.current_tick() - tick <= ATTACKER_MEMORY_TICKS)
+                    .map(|(attacker, _)| attacker)
+            });
+
+        let name = |entity| names.get(entity).map_or(Text::text("?"), display_name);
+
+        let message = death_message(
+            name(entity),
+            state.last_damage.as_ref(),
+            killer.map(name),
+            &codec,
+        );
+
+        if is_client {
+            if let Ok(mut client) = clients.get_mut(entity) {
+                client.kill(message.clone());
+            }
+
+            player_deaths.push(message.clone());
+        } else if let Ok(mut statuses) = statuses.get_mut(entity) {
+            statuses.trigger(EntityStatus::PlayDeathSoundOrAddProjectileHitParticles);
+        }
+
+        commands.entity(entity).insert(Dead {
+            tick: server.current_tick(),
+        });
+
+        death_events.send(DeathEvent {
+            entity,
+            damage_type: state
+                .last_damage
+                .as_ref()
+                .map(|damage| damage.damage_type.clone()),
+            killer,
+            message,
+        });
+    }
+
+    if settings.show_death_messages {
+        for message in player_deaths {
+            for mut client in &mut clients {
+                client.send_chat_message(message.clone());
+            }
+        }
+    }
+}
+
+/// Despawns dead non-player entities after their death animation.
+pub(crate) fn remove_dead_entities(
+    mut entities: Query<(Entity, &Dead, Option<&mut EntityStatuses>), Without<Client>>,
+    server: Res<Server>,
+    mut commands: Commands,
+) {
+    for (entity, dead, statuses) in &mut entities {
+        if server.current_tick() - dead.tick >= DEATH_ANIMATION_TICKS {
+            if let Some(mut statuses) = statuses {
+                statuses.trigger(EntityStatus::AddDeathParticles);
+            }
+
+            commands.entity(entity).insert(Despawned);
+        }
+    }
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct RespawnQuery {
+    health: &'static mut Health,
+    state: &'static mut CombatState,
+    pos: &'static mut Position,
+    look: &'static mut Look,
+    respawn_pos: &'static RespawnPosition,
+    visible_chunk_layer: &'static mut VisibleChunkLayer,
+    attributes: Option<&'static EntityAttributes>,
+    absorption: Option<&'static mut Absorption>,
+    food: Option<&'static mut Food>,
+    saturation: Option<&'static mut Saturation>,
+    effects: Option<&'static mut ActiveStatusEffects>,
+}
+
+/// Respawns dead players at their [`RespawnPosition`] when they click the
+/// respawn button.
+pub(crate) fn respawn_players(
+    mut events: EventReader<RequestRespawnEvent>,
+    mut clients: Query<RespawnQuery, With<Dead>>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let Ok(mut client) = clients.get_mut(event.client) else {
+            continue;
+        };
+
+        client.health.0 = client
+            .attributes
+            .and_then(|a| a.get_compute_value(EntityAttribute::GenericMaxHealth))
+            .unwrap_or_else(|| EntityAttribute::GenericMaxHealth.default_value())
+            as f32;
+
+        *client.state = CombatState::default();
+
+        let pos = client.respawn_pos.pos;
+        client.pos.set([
+            f64::from(pos.x) + 0.5,
+            f64::from(pos.y),
+            f64::from(pos.z) + 0.5,
+        ]);
+        client.look.yaw = client.respawn_pos.yaw;
+        client.look.pitch = 0.0;
+
+        if let Some(absorption) = &mut client.absorption {
+            absorption.0 = 0.0;
+        }
+
+        if let Some(food) = &mut client.food {
+            food.0 = 20;
+        }
+
+        if let Some(saturation) = &mut client.saturation {
+            saturation.0 = 5.0;
+        }
+
+        if let Some(effects) = &mut client.effects {
+            effects.remove_all();
+        }
+
+        // Sends the respawn packet, which closes the death screen.
+        client.visible_chunk_layer.set_changed();
+
+        commands.entity(event.client).remove::<Dead>();
+    }
+}
+
+fn display_name(
+    (username, custom_name, kind): (Option<&Username>, Option<&CustomName>, &EntityKind),
+) -> Text {
+    if let Some(username) = username {
+        return Text::text(username.0.clone());
+    }
+
+    if let Some(CustomName(Some(name))) = custom_name {
+        return name.clone();
+    }
+
+    kind.translation_key()
+        .map_or(Text::text("?"), |key| Text::translate(key, []))
+}
+
+/// Builds the vanilla death message of an entity that died from `damage`.
+fn death_message(
+    victim: Text,
+    damage: Option<&LastDamage>,
+    killer: Option<Text>,
+    codec: &RegistryCodec,
+) -> Text {
+    let element = damage.and_then(|damage| {
+        let registry = codec.registry(ident!("damage_type"));
+        let idx = codec.entry_index(ident!("damage_type"), damage.damage_type.as_str_ident())?;
+
+        Some(&registry[idx].element)
+    });
+
+    let string = |key| match element.and_then(|element| element.get(key)) {
+        Some(Value::String(value)) => Some(value.as_str()),
+        _ => None,
+    };
+
+    let message_id = string("message_id").unwrap_or("generic");
+    let key = format!("death.attack.{message_id}");
+
+    match (string("death_message_type"), killer) {
+        (Some("intentional_game_design"), _) => Text::translate(
+            format!("{key}.message"),
+            [victim, Text::translate(format!("{key}.link"), [])],
+        ),
+        (Some("fall_variants"), Some(killer)) => {
+            Text::translate("death.fell.assist", [victim, killer])
+        }
+        (Some("fall_variants"), None) => Text::translate("death.fell.accident.generic", [victim]),
+        // Damage without a source that was preceded by an attack.
+        (_, Some(killer)) if damage.is_some_and(|damage| damage.source.is_none()) => {
+            Text::translate(format!("{key}.player"), [victim, killer])
+        }
+        (_, Some(killer)) => Text::translate(key, [victim, killer]),
+        (_, None) => Text::translate(key, [victim]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn death_messages() {
+        let codec = RegistryCodec::default();
+        let damage = |damage_type: &str, source| LastDamage {
+            damage_type: Ident::new(damage_type).unwrap().to_string_ident(),
+            source,
+        };
+
+        assert_eq!(
+            death_message(Text::text("a"), None, None, &codec),
+            Text::translate("death.attack.generic", [Text::text("a")])
+        );
+
+        assert_eq!(
+            death_message(
+                Text::text("a"),
+                Some(&damage(
+                    "minecraft:player_attack",
+                    Some(Entity::PLACEHOLDER)
+                )),
+                Some(Text::text("b")),
+                &codec
+            ),
+            Text::translate("death.attack.player", [Text::text("a"), Text::text("b")])
+        );
+
+        assert_eq!(
+            death_message(
+                Text::text("a"),
+                Some(&damage("minecraft:drown", None)),
+                Some(Text::text("b")),
+                &codec
+            ),
+            Text::translate(
+                "death.attack.drown.player",
+                [Text::text("a"), Text::text("b")]
+            )
+        );
+
+        assert_eq!(
+            death_message(
+                Text::text("a"),
+                Some(&damage("minecraft:fall", None)),
+                None,
+                &codec
+            ),
+            Text::translate("death.fell.accident.generic", [Text::text("a")])
+        );
+    }
+}
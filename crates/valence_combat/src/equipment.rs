@@ -0,0 +1,199 @@
+//! Attribute modifiers granted by equipped armor and weapons.
+
+use bevy_ecs::prelude::*;
+use valence_equipment::Equipment;
+use valence_server::entity::attributes::{
+    EntityAttribute, EntityAttributeOperation, EntityAttributes,
+};
+use valence_server::protocol::item::component::{
+    AttributeOperation, EquipmentSlotGroup, ItemComponent, ItemComponentKind,
+};
+use valence_server::uuid::Uuid;
+use valence_server::ItemStack;
+
+/// The attribute modifier UUIDs of each equipment slot, indexed like the slots
+/// of [`Equipment`]. The main hand and armor UUIDs are the ones vanilla used
+/// before modifiers were identified by name.
+const SLOT_MODIFIER_UUIDS: [Uuid; Equipment::SLOT_COUNT] = [
+    Uuid::from_u128(0xcb3f55d3_645c_4f38_a497_9c13a33db5cf),
+    Uuid::from_u128(0x3c5b6b1e_2a44_4c4b_9a9c_0f4d4f2a9c01),
+    Uuid::from_u128(0x845db27c_c624_495f_8c9f_6020a9a58b6b),
+    Uuid::from_u128(0xd8499b04_0e66_4726_ab29_64469d734e0d),
+    Uuid::from_u128(0x9f3d476d_c118_4544_8365_64846904b48e),
+    Uuid::from_u128(0x2ad3f246_fee1_4e67_b886_69fd380bb150),
+];
+
+/// The attributes the equipment of an entity currently modifies.
+#[derive(Component, Default, Debug)]
+pub(crate) struct EquipmentModifiers(Vec<EntityAttribute>);
+
+/// Updates the attribute modifiers of entities when their equipment changes.
+pub(crate) fn update_equipment_modifiers(
+    mut entities: Query<
+        (&Equipment, &mut EntityAttributes, &mut EquipmentModifiers),
+        Changed<Equipment>,
+    >,
+) {
+    for (equipment, mut attributes, mut applied) in &mut entities {
+        for attribute in applied.0.drain(..) {
+            for uuid in SLOT_MODIFIER_UUIDS {
+                attributes.remove_modifier(attribute, uuid);
+            }
+        }
+
+        for (idx, uuid) in SLOT_MODIFIER_UUIDS.into_iter().enumerate() {
+            let stack = equipment.slot(idx as u8);
+
+            if stack.is_empty() {
+                continue;
+            }
+
+            // Modifiers with the same attribute and operation are summed since
+            // each slot only has a single UUID.
+            let mut modifiers: Vec<(EntityAttribute, EntityAttributeOperation, f64)> = vec![];
+
+            for (attribute, operation, amount) in item_modifiers(stack, idx as u8) {
+                match modifiers
+                    .iter_mut()
+                    .find(|(a, o, _)| *a == attribute && *o == operation)
+                {
+                    Some((_, _, total)) => *total += amount,
+                    None => modifiers.push((attribute, operation, amount)),
+                }
+            }
+
+            for (attribute, operation, amount) in modifiers {
+                attributes.set_modifier(attribute, uuid, amount, operation);
+
+                if !applied.0.contains(&attribute) {
+                    applied.0.push(attribute);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the attribute modifiers `stack` grants when equipped in the
+/// equipment slot `slot`.
+///
+/// Modifiers set with the [`ItemComponent::AttributeModifiers`] component
+/// replace the default modifiers of the item.
+fn item_modifiers(
+    stack: &ItemStack,
+    slot: u8,
+) -> Vec<(EntityAttribute, EntityAttributeOperation, f64)> {
+    if stack
+        .components
+        .is_removed(ItemComponentKind::AttributeModifiers)
+    {
+        return vec![];
+    }
+
+    if let Some(ItemComponent::AttributeModifiers(modifiers)) =
+        stack.component(ItemComponentKind::AttributeModifiers)
+    {
+        return modifiers
+            .modifiers
+            .iter()
+            .filter(|modifier| slot_group_matches(modifier.slot, slot))
+            .filter_map(|modifier| {
+                let attribute =
+                    EntityAttribute::from_raw(u8::try_from(modifier.attribute.0).ok()?)?;
+
+                let operation = match modifier.operation {
+                    AttributeOperation::AddValue => EntityAttributeOperation::Add,
+                    AttributeOperation::AddMultipliedBase => EntityAttributeOperation::MultiplyBase,
+                    AttributeOperation::AddMultipliedTotal => {
+                        EntityAttributeOperation::MultiplyTotal
+                    }
+                };
+
+                Some((attribute, operation, modifier.amount))
+            })
+            .collect();
+    }
+
+    stack
+        .item
+        .attribute_modifiers()
+        .iter()
+        .filter(|modifier| slot_group_matches(modifier.slot.into(), slot))
+        .map(|modifier| (modifier.attribute, modifier.operation, modifier.amount))
+        .collect()
+}
+
+fn slot_group_matches(group: EquipmentSlotGroup, slot: u8) -> bool {
+    match group {
+        EquipmentSlotGroup::Any => true,
+        EquipmentSlotGroup::MainHand => slot == Equipment::MAIN_HAND_IDX,
+        EquipmentSlotGroup::OffHand => slot == Equipment::OFF_HAND_IDX,
+        EquipmentSlotGroup::Hand => {
+            slot == Equipment::MAIN_HAND_IDX || slot == Equipment::OFF_HAND_IDX
+        }
+        EquipmentSlotGroup::Feet => slot == Equipment::FEET_IDX,
+        EquipmentSlotGroup::Legs => slot == Equipment::LEGS_IDX,
+        EquipmentSlotGroup::Chest => slot == Equipment::CHEST_IDX,
+        EquipmentSlotGroup::Head => slot == Equipment::HEAD_IDX,
+        EquipmentSlotGroup::Armor => (Equipment::FEET_IDX..=Equipment::HEAD_IDX).contains(&slot),
+        // Body armor is only worn by animals, which don't have a slot for it.
+        EquipmentSlotGroup::Body => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_server::ItemKind;
+
+    use super::*;
+
+    #[test]
+    fn armor_only_applies_in_its_slot() {
+        let chestplate = ItemStack::new(ItemKind::DiamondChestplate, 1);
+
+        assert_eq!(
+            item_modifiers(&chestplate, Equipment::CHEST_IDX),
+            [
+                (
+                    EntityAttribute::GenericArmor,
+                    EntityAttributeOperation::Add,
+                    8.0
+                ),
+                (
+                    EntityAttribute::GenericArmorToughness,
+                    EntityAttributeOperation::Add,
+                    2.0
+                ),
+            ]
+        );
+        assert!(item_modifiers(&chestplate, Equipment::MAIN_HAND_IDX).is_empty());
+    }
+
+    #[test]
+    fn weapon_modifiers_only_apply_in_main_hand() {
+        let axe = ItemStack::new(ItemKind::NetheriteAxe, 1);
+
+        let modifiers = item_modifiers(&axe, Equipment::MAIN_HAND_IDX);
+
+        assert_eq!(modifiers.len(), 2);
+        assert_eq!(
+            modifiers[0],
+            (
+                EntityAttribute::GenericAttackDamage,
+                EntityAttributeOperation::Add,
+                9.0
+            )
+        );
+        assert_eq!(modifiers[1].0, EntityAttribute::GenericAttackSpeed);
+        assert!((modifiers[1].2 - -3.0).abs() < 1e-6);
+
+        assert!(item_modifiers(&axe, Equipment::OFF_HAND_IDX).is_empty());
+    }
+
+    #[test]
+    fn removed_modifiers_component_disables_defaults() {
+        let sword = ItemStack::new(ItemKind::DiamondSword, 1)
+            .without_component(ItemComponentKind::AttributeModifiers);
+
+        assert!(item_modifiers(&sword, Equipment::MAIN_HAND_IDX).is_empty());
+    }
+}
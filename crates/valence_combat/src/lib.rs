@@ -0,0 +1,267 @@
+#![doc = include_str!("../README.md")]
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use derive_more::{Deref, DerefMut};
+use valence_server::client::SpawnClientsSet;
+use valence_server::entity::living::LivingEntity;
+use valence_server::entity::{InitEntitiesSet, UpdateTrackedDataSet};
+use valence_server::ident::Ident;
+use valence_server::math::DVec3;
+use valence_server::protocol::item::component::{ItemComponent, ItemComponentKind};
+use valence_server::registry::RegistryCodec;
+use valence_server::{ident, ItemStack};
+
+mod attack;
+mod damage;
+mod death;
+mod equipment;
+
+pub use death::{Dead, DeathEvent};
+
+pub struct CombatPlugin;
+
+/// The system sets of the [`CombatPlugin`], in the order they run.
+///
+/// These sets live in [`PostUpdate`].
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum CombatSet {
+    /// Player attacks and [`DamageEvent`]s are collected into
+    /// [`PendingDamage`].
+    CollectDamage,
+    /// Systems that modify or cancel [`PendingDamage`] should run in this
+    /// set.
+    ModifyDamage,
+    /// Pending damage is applied, and entities that ran out of health die.
+    ApplyDamage,
+}
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CombatSettings>()
+            .init_resource::<PendingDamage>()
+            .add_event::<DamageEvent>()
+            .add_event::<DamageAppliedEvent>()
+            .add_event::<DeathEvent>()
+            .configure_sets(
+                PostUpdate,
+                (
+                    CombatSet::CollectDamage,
+                    CombatSet::ModifyDamage,
+                    CombatSet::ApplyDamage,
+                )
+                    .chain()
+                    .after(InitEntitiesSet)
+                    .before(UpdateTrackedDataSet),
+            )
+            .add_systems(PreUpdate, init_combat_state.after(SpawnClientsSet))
+            .add_systems(
+                PostUpdate,
+                (
+                    equipment::update_equipment_modifiers,
+                    damage::tick_hurt_cooldown,
+                    attack::handle_player_attacks,
+                    damage::collect_damage_events,
+                )
+                    .chain()
+                    .in_set(CombatSet::CollectDamage),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    damage::apply_pending_damage,
+                    death::detect_deaths,
+                    death::remove_dead_entities,
+                    death::respawn_players,
+                )
+                    .chain()
+                    .in_set(CombatSet::ApplyDamage),
+            );
+    }
+}
+
+/// Global settings for the [`CombatPlugin`].
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct CombatSettings {
+    /// The number of ticks an entity is invulnerable for after being hurt.
+    /// During the first half of this time, damage is only applied if it is
+    /// larger than the damage that started the invulnerability, and only the
+    /// difference is applied. Damage during the second half is applied as
+    /// usual.
+    pub invulnerability_ticks: u32,
+    /// If players damage the entities they attack. When disabled, attacks have
+    /// to be handled with [`InteractEntityEvent`]s.
+    ///
+    /// [`InteractEntityEvent`]: valence_server::interact_entity::InteractEntityEvent
+    pub player_attacks: bool,
+    /// If the death messages of players are sent to all clients.
+    pub show_death_messages: bool,
+}
+
+impl Default for CombatSettings {
+    fn default() -> Self {
+        Self {
+            invulnerability_ticks: 20,
+            player_attacks: true,
+            show_death_messages: true,
+        }
+    }
+}
+
+/// An event to damage a living entity.
+///
+/// The damage is reduced by the armor, protection enchantments and resistance
+/// effect of the victim, depending on the damage type.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct DamageEvent {
+    /// The entity taking damage.
+    pub victim: Entity,
+    /// The damage type in the `minecraft:damage_type` registry, such as
+    /// `minecraft:player_attack`.
+    pub damage_type: Ident<String>,
+    /// The amount of damage before it is reduced.
+    pub amount: f32,
+    /// The entity responsible for the damage, such as the player that shot an
+    /// arrow.
+    pub source: Option<Entity>,
+    /// The entity that directly dealt the damage, such as the arrow.
+    pub direct_source: Option<Entity>,
+    /// The position the damage came from when there is no source entity, such
+    /// as the center of an explosion.
+    pub position: Option<DVec3>,
+    /// Knockback strength added on top of the base knockback of `0.4`.
+    pub bonus_knockback: f32,
+}
+
+impl DamageEvent {
+    pub fn new<I: Into<Ident<String>>>(victim: Entity, damage_type: I, amount: f32) -> Self {
+        Self {
+            victim,
+            damage_type: damage_type.into(),
+            amount,
+            source: None,
+            direct_source: None,
+            position: None,
+            bonus_knockback: 0.0,
+        }
+    }
+
+    /// Sets the entity that both caused and directly dealt the damage.
+    #[must_use]
+    pub fn with_source(mut self, source: Entity) -> Self {
+        self.source = Some(source);
+        self.direct_source = Some(source);
+        self
+    }
+
+    /// Sets the entity that directly dealt the damage, such as a projectile.
+    #[must_use]
+    pub fn with_direct_source(mut self, direct_source: Entity) -> Self {
+        self.direct_source = Some(direct_source);
+        self
+    }
+
+    #[must_use]
+    pub fn with_position<P: Into<DVec3>>(mut self, position: P) -> Self {
+        self.position = Some(position.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_bonus_knockback(mut self, bonus_knockback: f32) -> Self {
+        self.bonus_knockback = bonus_knockback;
+        self
+    }
+}
+
+/// The damage that will be applied this tick.
+///
+/// Systems in [`CombatSet::ModifyDamage`] can change the damage in here or
+/// remove entries to cancel them.
+#[derive(Resource, Default, Debug, Deref, DerefMut)]
+pub struct PendingDamage(pub Vec<DamageEvent>);
+
+/// Sent after damage has been applied to an entity.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct DamageAppliedEvent {
+    pub damage: DamageEvent,
+    /// The health and absorption the victim lost, after all reductions.
+    pub amount: f32,
+}
+
+/// The combat state of a living entity. Added to all living entities by the
+/// [`CombatPlugin`].
+#[derive(Component, Clone, Default, Debug)]
+pub struct CombatState {
+    /// The remaining ticks of invulnerability.
+    hurt_cooldown: u32,
+    /// The damage taken when the invulnerability started.
+    hurt_amount: f32,
+    /// The damage that most recently hurt the entity.
+    last_damage: Option<LastDamage>,
+    /// The entity that last damaged this entity and the tick it did so.
+    attacker: Option<(Entity, i64)>,
+    /// The tick the entity last attacked.
+    last_attack_tick: i64,
+}
+
+#[derive(Clone, Debug)]
+struct LastDamage {
+    damage_type: Ident<String>,
+    source: Option<Entity>,
+}
+
+impl CombatState {
+    /// Returns the remaining ticks the entity is invulnerable for.
+    pub fn hurt_cooldown(&self) -> u32 {
+        self.hurt_cooldown
+    }
+
+    /// Makes the entity invulnerable for the given number of ticks.
+    pub fn set_hurt_cooldown(&mut self, ticks: u32) {
+        self.hurt_cooldown = ticks;
+    }
+
+    /// Returns the entity that last damaged this entity, if any.
+    pub fn last_attacker(&self) -> Option<Entity> {
+        self.attacker.map(|(attacker, _)| attacker)
+    }
+}
+
+fn init_combat_state(
+    entities: Query<Entity, (Added<LivingEntity>, Without<CombatState>)>,
+    mut commands: Commands,
+) {
+    for entity in &entities {
+        commands.entity(entity).insert((
+            CombatState::default(),
+            equipment::EquipmentModifiers::default(),
+        ));
+    }
+}
+
+/// Returns the level of the enchantment `name` on `stack`, or 0 if the stack
+/// is not enchanted with it.
+pub(crate) fn enchantment_level(
+    stack: &ItemStack,
+    codec: &RegistryCodec,
+    name: Ident<&str>,
+) -> i32 {
+    let Some(ItemComponent::Enchantments(enchantments)) =
+        stack.component(ItemComponentKind::Enchantments)
+    else {
+        return 0;
+    };
+
+    let Some(id) = codec.entry_index(ident!("enchantment"), name) else {
+        return 0;
+    };
+
+    enchantments
+        .enchantments
+        .iter()
+        .filter(|enchantment| enchantment.id.0 as usize == id)
+        .map(|enchantment| enchantment.level.0)
+        .max()
+        .unwrap_or(0)
+}
@@ -76,13 +76,14 @@ use std::fmt::{Display, Formatter};
 use petgraph::dot::Dot;
 use petgraph::prelude::*;
 use valence_server::protocol::packets::play::command_tree_s2c::{
-    Node, NodeData, Parser, StringArg,
+    Node, NodeData, Parser, StringArg, Suggestion,
 };
 use valence_server::protocol::packets::play::CommandTreeS2c;
 use valence_server::protocol::VarInt;
 
 use crate::modifier_value::ModifierValue;
-use crate::parsers::{CommandArg, ParseInput};
+use crate::parsers::{CommandArg, CommandArgParseError, ParseInput};
+use crate::suggestions::SuggestionProvider;
 use crate::{CommandRegistry, CommandScopeRegistry};
 
 /// This struct is used to store the command graph. (see module level docs for
@@ -118,6 +119,116 @@ impl CommandGraph {
 
         Self { graph, root }
     }
+
+    /// Returns the usage of each child of `node` that passes `can_use`, in the
+    /// same format as the vanilla `/help` command. For example, the
+    /// `teleport` node in the module level example has the usages
+    /// `<destination:entity>`,
+    /// `<target:entity> (<destination:location>|<destination:entity>)` and
+    /// `<destination:location>`.
+    ///
+    /// * Children of executable nodes are optional and wrapped in `[]`.
+    /// * Alternatives are listed as `(a|b)`.
+    /// * Redirects are shown as `-> target`, or `...` if they redirect to the
+    ///   root node.
+    pub fn usage<F: Fn(&CommandNode) -> bool>(&self, node: NodeIndex, can_use: F) -> Vec<String> {
+        let optional = self.graph[node].executable;
+
+        self.children(node, &can_use)
+            .into_iter()
+            .map(|child| self.smart_usage(child, &can_use, optional, false))
+            .collect()
+    }
+
+    fn smart_usage(
+        &self,
+        node: NodeIndex,
+        can_use: &impl Fn(&CommandNode) -> bool,
+        optional: bool,
+        deep: bool,
+    ) -> String {
+        let this = self.usage_text(node);
+        let this = if optional { format!("[{this}]") } else { this };
+
+        if deep {
+            return this;
+        }
+
+        if let Some(redirect) = self.redirect(node) {
+            return if redirect == self.root {
+                format!("{this} ...")
+            } else {
+                format!("{this} -> {}", self.usage_text(redirect))
+            };
+        }
+
+        let child_optional = self.graph[node].executable;
+        let children = self.children(node, can_use);
+
+        match children.as_slice() {
+            [] => this,
+            [child] => {
+                let usage = self.smart_usage(*child, can_use, child_optional, child_optional);
+                format!("{this} {usage}")
+            }
+            _ => {
+                let mut usages = Vec::new();
+                for &child in &children {
+                    let usage = self.smart_usage(child, can_use, false, true);
+                    if !usages.contains(&usage) {
+                        usages.push(usage);
+                    }
+                }
+
+                let (open, close) = if child_optional {
+                    ("[", "]")
+                } else {
+                    ("(", ")")
+                };
+
+                if let [usage] = usages.as_slice() {
+                    if child_optional {
+                        format!("{this} {open}{usage}{close}")
+                    } else {
+                        format!("{this} {usage}")
+                    }
+                } else {
+                    format!("{this} {open}{}{close}", usages.join("|"))
+                }
+            }
+        }
+    }
+
+    /// The children of `node` that pass `can_use`, in the order they were
+    /// added.
+    fn children(&self, node: NodeIndex, can_use: &impl Fn(&CommandNode) -> bool) -> Vec<NodeIndex> {
+        // petgraph iterates the edges of a node from newest to oldest
+        let mut children: Vec<_> = self
+            .graph
+            .edges_directed(node, Direction::Outgoing)
+            .filter(|edge| *edge.weight() == CommandEdgeType::Child)
+            .map(|edge| edge.target())
+            .filter(|&child| can_use(&self.graph[child]))
+            .collect();
+
+        children.reverse();
+        children
+    }
+
+    fn redirect(&self, node: NodeIndex) -> Option<NodeIndex> {
+        self.graph
+            .edges_directed(node, Direction::Outgoing)
+            .find(|edge| *edge.weight() == CommandEdgeType::Redirect)
+            .map(|edge| edge.target())
+    }
+
+    fn usage_text(&self, node: NodeIndex) -> String {
+        match &self.graph[node].data {
+            NodeData::Root => String::new(),
+            NodeData::Literal { name } => name.clone(),
+            NodeData::Argument { name, .. } => format!("<{name}>"),
+        }
+    }
 }
 
 /// Data for the nodes in the graph (see module level docs for more info)
@@ -258,8 +369,9 @@ pub struct CommandGraphBuilder<'a, T> {
     graph: &'a mut CommandGraph,
     current_node: NodeIndex,
     executables: &'a mut HashMap<NodeIndex, fn(&mut ParseInput) -> T>,
-    parsers: &'a mut HashMap<NodeIndex, fn(&mut ParseInput) -> bool>,
+    parsers: &'a mut HashMap<NodeIndex, fn(&mut ParseInput) -> Result<(), CommandArgParseError>>,
     modifiers: &'a mut HashMap<NodeIndex, fn(String, &mut HashMap<ModifierValue, ModifierValue>)>,
+    suggestions: &'a mut HashMap<NodeIndex, SuggestionProvider>,
     scopes_added: Vec<String>, /* we need to keep track of added scopes so we can add them to
                                 * the registry later */
 }
@@ -274,7 +386,10 @@ impl<'a, T> CommandGraphBuilder<'a, T> {
     pub fn new(
         registry: &'a mut CommandRegistry,
         executables: &'a mut HashMap<NodeIndex, fn(&mut ParseInput) -> T>,
-        parsers: &'a mut HashMap<NodeIndex, fn(&mut ParseInput) -> bool>,
+        parsers: &'a mut HashMap<
+            NodeIndex,
+            fn(&mut ParseInput) -> Result<(), CommandArgParseError>,
+        >,
         modifiers: &'a mut HashMap<
             NodeIndex,
             fn(String, &mut HashMap<ModifierValue, ModifierValue>),
@@ -286,6 +401,7 @@ impl<'a, T> CommandGraphBuilder<'a, T> {
             executables,
             parsers,
             modifiers,
+            suggestions: &mut registry.suggestions,
             scopes_added: Vec::new(),
         }
     }
@@ -327,7 +443,9 @@ impl<'a, T> CommandGraphBuilder<'a, T> {
     /// * executable - `false`
     /// * scopes - `Vec::new()`
     /// * parser - `StringArg::SingleWord`
-    /// * suggestion - `None`
+    /// * suggestion - `None` (set by [`with_parser`](Self::with_parser) if the
+    ///   parser has suggestions, or by
+    ///   [`with_suggestions`](Self::with_suggestions))
     pub fn argument<A: Into<String>>(&mut self, argument: A) -> &mut Self {
         let graph = &mut self.graph.graph;
         let current_node = &mut self.current_node;
@@ -499,7 +617,7 @@ impl<'a, T> CommandGraphBuilder<'a, T> {
 
         let node = graph.node_weight_mut(current_node).unwrap();
         self.parsers
-            .insert(current_node, |input| P::parse_arg(input).is_ok());
+            .insert(current_node, |input| P::parse_arg(input).map(|_| ()));
 
         let parser = P::display();
 
@@ -515,6 +633,66 @@ impl<'a, T> CommandGraphBuilder<'a, T> {
             NodeData::Root => NodeData::Root,
         };
 
+        // A provider set on the node itself takes precedence over the one from
+        // the parser.
+        if let Some(provider) = P::suggestions() {
+            if !self.suggestions.contains_key(&current_node) {
+                self.with_suggestions(provider);
+            }
+        }
+
+        self
+    }
+
+    /// Sets the suggestion provider for the current node. The client will ask
+    /// the server for suggestions while the argument is being typed, and the
+    /// provider is used to answer. The node should be an argument node or
+    /// nothing will happen.
+    ///
+    /// # Arguments
+    /// * provider - the provider to use for the current node (see
+    ///   [`suggestions`](crate::suggestions))
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use valence_command::graph::CommandGraphBuilder;
+    /// use valence_command::suggestions::{SuggestionProvider, PLAYER_NAMES};
+    /// use valence_command::CommandRegistry;
+    ///
+    /// struct MsgCommand;
+    ///
+    /// let mut command_graph = CommandRegistry::default();
+    /// let mut executable_map = HashMap::new();
+    /// let mut parser_map = HashMap::new();
+    /// let mut modifier_map = HashMap::new();
+    /// let mut command_graph_builder = CommandGraphBuilder::<MsgCommand>::new(
+    ///     &mut command_graph,
+    ///     &mut executable_map,
+    ///     &mut parser_map,
+    ///     &mut modifier_map,
+    /// );
+    ///
+    /// command_graph_builder
+    ///     .root()
+    ///     .literal("msg")
+    ///     .argument("target")
+    ///     .with_parser::<String>()
+    ///     .with_suggestions(SuggestionProvider::Dynamic(PLAYER_NAMES))
+    ///     .with_executable(|_| MsgCommand);
+    /// ```
+    pub fn with_suggestions(&mut self, provider: SuggestionProvider) -> &mut Self {
+        let graph = &mut self.graph.graph;
+        let current_node = self.current_node;
+
+        let node = graph.node_weight_mut(current_node).unwrap();
+
+        if let NodeData::Argument { suggestion, .. } = &mut node.data {
+            *suggestion = Some(Suggestion::AskServer);
+            self.suggestions.insert(current_node, provider);
+        }
+
         self
     }
 
@@ -4,20 +4,27 @@ pub mod manager;
 mod modifier_value;
 pub mod parsers;
 pub mod scopes;
+pub mod selector;
+pub mod source;
+pub mod suggestions;
 
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 
 use bevy_app::App;
 use bevy_ecs::prelude::{Resource, SystemSet};
-pub use manager::{CommandExecutionEvent, CommandProcessedEvent};
+pub use manager::{
+    CommandError, CommandExecutionEvent, CommandFailedEvent, CommandFeedbackSettings,
+    CommandProcessedEvent,
+};
 pub use modifier_value::ModifierValue;
 use petgraph::prelude::NodeIndex;
 pub use scopes::CommandScopeRegistry;
 
 use crate::graph::{CommandGraph, CommandGraphBuilder};
 use crate::handler::CommandHandlerPlugin;
-use crate::parsers::ParseInput;
+use crate::parsers::{CommandArgParseError, ParseInput};
+use crate::suggestions::SuggestionProvider;
 
 #[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct CommandSystemSet;
@@ -26,9 +33,10 @@ pub struct CommandSystemSet;
 #[allow(clippy::type_complexity)]
 pub struct CommandRegistry {
     pub graph: CommandGraph,
-    pub parsers: HashMap<NodeIndex, fn(&mut ParseInput) -> bool>,
+    pub parsers: HashMap<NodeIndex, fn(&mut ParseInput) -> Result<(), CommandArgParseError>>,
     pub modifiers: HashMap<NodeIndex, fn(String, &mut HashMap<ModifierValue, ModifierValue>)>,
     pub executables: HashSet<NodeIndex>,
+    pub suggestions: HashMap<NodeIndex, SuggestionProvider>,
 }
 
 pub trait Command {
@@ -1,25 +1,32 @@
 use std::collections::{HashMap, HashSet};
 
-use bevy_app::{App, Plugin, PreUpdate};
+use bevy_app::{App, Plugin, PostUpdate, PreUpdate};
 use bevy_ecs::entity::Entity;
 use bevy_ecs::prelude::{
     Added, Changed, Commands, DetectChanges, Event, EventReader, EventWriter, IntoSystemConfigs,
-    Mut, Or, Query, Res,
+    Mut, Or, Query, Res, Resource, With,
 };
 use petgraph::graph::NodeIndex;
 use petgraph::prelude::EdgeRef;
 use petgraph::{Direction, Graph};
+use thiserror::Error;
 use tracing::{debug, info, trace, warn};
-use valence_server::client::{Client, SpawnClientsSet};
+use valence_lang::keys;
+use valence_server::client::{Client, FlushPacketsSet, SpawnClientsSet};
 use valence_server::event_loop::PacketEvent;
 use valence_server::protocol::packets::play::command_tree_s2c::NodeData;
 use valence_server::protocol::packets::play::{CommandExecutionC2s, CommandTreeS2c};
 use valence_server::protocol::WritePacket;
 use valence_server::EventLoopPreUpdate;
+use valence_text::{Color, IntoText, Text};
 
 use crate::graph::{CommandEdgeType, CommandGraph, CommandNode};
-use crate::parsers::ParseInput;
+use crate::parsers::{CommandArgParseError, ParseInput};
 use crate::scopes::{CommandScopePlugin, CommandScopes};
+use crate::source::{send_replies, CommandReplyEvent, CommandSource};
+use crate::suggestions::{
+    answer_completion_requests, suggest_player_names, AddSuggestionProvider, PLAYER_NAMES,
+};
 use crate::{CommandRegistry, CommandScopeRegistry, CommandSystemSet, ModifierValue};
 
 pub struct CommandPlugin;
@@ -29,7 +36,10 @@ impl Plugin for CommandPlugin {
         app.add_plugins(CommandScopePlugin)
             .add_event::<CommandExecutionEvent>()
             .add_event::<CommandProcessedEvent>()
-            .add_systems(PreUpdate, insert_scope_component.after(SpawnClientsSet))
+            .add_event::<CommandFailedEvent>()
+            .add_event::<CommandReplyEvent>()
+            .init_resource::<CommandFeedbackSettings>()
+            .add_systems(PreUpdate, insert_executor_components.after(SpawnClientsSet))
             .add_systems(
                 EventLoopPreUpdate,
                 (
@@ -37,19 +47,25 @@ impl Plugin for CommandPlugin {
                     command_tree_update_with_client,
                     read_incoming_packets.before(CommandSystemSet),
                     parse_incoming_commands.in_set(CommandSystemSet),
+                    send_command_errors.after(CommandSystemSet),
+                    answer_completion_requests,
                 ),
-            );
+            )
+            .add_systems(PostUpdate, send_replies.before(FlushPacketsSet))
+            .add_suggestion_provider(PLAYER_NAMES, suggest_player_names);
 
         let graph: CommandGraph = CommandGraph::new();
         let modifiers = HashMap::new();
         let parsers = HashMap::new();
         let executables = HashSet::new();
+        let suggestions = HashMap::new();
 
         app.insert_resource(CommandRegistry {
             graph,
             parsers,
             modifiers,
             executables,
+            suggestions,
         });
     }
 }
@@ -80,9 +96,158 @@ pub struct CommandProcessedEvent {
     pub node: NodeIndex,
 }
 
-fn insert_scope_component(mut clients: Query<Entity, Added<Client>>, mut commands: Commands) {
+/// This is sent instead of [`CommandProcessedEvent`] when a command could not
+/// be parsed. Unless [`CommandFeedbackSettings::send_errors`] is disabled, the
+/// [`feedback`](Self::feedback) is sent to the executor.
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub struct CommandFailedEvent {
+    /// the command that failed eg. "teleport @p 0 ~"
+    pub command: String,
+    /// usually the Client entity but it could be a command block or something
+    /// (whatever the library user wants)
+    pub executor: Entity,
+    /// why the command failed
+    pub error: CommandError,
+    /// the byte offset in `command` where parsing failed
+    pub cursor: usize,
+    /// the usages of the command from the last node that was parsed eg.
+    /// "teleport <destination:entity>". Empty if not even the first literal
+    /// of the command could be parsed.
+    pub usage: Vec<String>,
+}
+
+impl CommandFailedEvent {
+    /// The feedback vanilla gives for the error: the error in red, the command
+    /// up to where it failed with a `<--[HERE]` marker, then the usages of the
+    /// command.
+    pub fn feedback(&self) -> Vec<Text> {
+        let (context_before, after) = self.context();
+
+        let mut context = Text::default()
+            .color(Color::GRAY)
+            .on_click_suggest_command(format!("/{}", self.command));
+
+        context += context_before;
+
+        if !after.is_empty() {
+            context += after.to_owned().color(Color::RED).underlined();
+        }
+        context += Text::translate(keys::COMMAND_CONTEXT_HERE, [])
+            .color(Color::RED)
+            .italic();
+
+        let mut feedback = vec![self.error.text().color(Color::RED), context];
+
+        feedback.extend(self.usage.iter().map(|usage| {
+            format!("/{usage}")
+                .color(Color::GRAY)
+                .on_click_suggest_command(format!("/{usage}"))
+        }));
+
+        feedback
+    }
+
+    /// The same as [`feedback`](Self::feedback) without formatting or
+    /// translations, for executors that aren't clients. The rest of the
+    /// command after the error is left out.
+    pub fn plain_feedback(&self) -> Vec<String> {
+        let (context, _) = self.context();
+
+        let mut feedback = vec![self.error.to_string(), format!("{context}<--[HERE]")];
+        feedback.extend(self.usage.iter().map(|usage| format!("/{usage}")));
+
+        feedback
+    }
+
+    /// Splits the command at the error. Only the last 10 characters before the
+    /// error are kept, like in vanilla.
+    fn context(&self) -> (String, &str) {
+        let (before, after) = self.command.split_at(self.cursor.min(self.command.len()));
+
+        let context = match before.char_indices().rev().nth(9) {
+            Some((start, _)) if start > 0 => format!("...{}", &before[start..]),
+            _ => before.to_owned(),
+        };
+
+        (context, after)
+    }
+}
+
+/// The reason a command could not be parsed.
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
+pub enum CommandError {
+    /// The command doesn't exist, is incomplete, or the executor doesn't have
+    /// the scopes to use it.
+    #[error("unknown or incomplete command")]
+    UnknownCommand,
+    /// Nothing matches the input after the command.
+    #[error("incorrect argument for command")]
+    UnknownArgument,
+    /// An argument was rejected by its parser.
+    #[error(transparent)]
+    InvalidArgument(#[from] CommandArgParseError),
+}
+
+impl CommandError {
+    /// The error as shown to players.
+    pub fn text(&self) -> Text {
+        match self {
+            CommandError::UnknownCommand => Text::translate(keys::COMMAND_UNKNOWN_COMMAND, []),
+            CommandError::UnknownArgument => Text::translate(keys::COMMAND_UNKNOWN_ARGUMENT, []),
+            CommandError::InvalidArgument(error) => error.to_string().into_text(),
+        }
+    }
+}
+
+/// Settings for how the [`CommandPlugin`] reports errors.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct CommandFeedbackSettings {
+    /// If the [`feedback`](CommandFailedEvent::feedback) of each
+    /// [`CommandFailedEvent`] is sent to the executor as
+    /// [`CommandReplyEvent`]s. Disable this to present errors yourself.
+    ///
+    /// Defaults to `true`.
+    pub send_errors: bool,
+}
+
+impl Default for CommandFeedbackSettings {
+    fn default() -> Self {
+        Self { send_errors: true }
+    }
+}
+
+fn insert_executor_components(mut clients: Query<Entity, Added<Client>>, mut commands: Commands) {
     for client in &mut clients {
-        commands.entity(client).insert(CommandScopes::new());
+        commands
+            .entity(client)
+            .insert((CommandScopes::new(), CommandSource::Player));
+    }
+}
+
+fn send_command_errors(
+    mut events: EventReader<CommandFailedEvent>,
+    settings: Res<CommandFeedbackSettings>,
+    clients: Query<(), With<Client>>,
+    mut replies: EventWriter<CommandReplyEvent>,
+) {
+    if !settings.send_errors {
+        return;
+    }
+
+    for event in events.read() {
+        let feedback = if clients.contains(event.executor) {
+            event.feedback()
+        } else {
+            event
+                .plain_feedback()
+                .into_iter()
+                .map(IntoText::into_text)
+                .collect()
+        };
+
+        for line in feedback {
+            replies.send(CommandReplyEvent::new(event.executor, line));
+        }
     }
 }
 
@@ -216,6 +381,7 @@ fn update_client_command_tree(
 fn parse_incoming_commands(
     mut event_reader: EventReader<CommandExecutionEvent>,
     mut event_writer: EventWriter<CommandProcessedEvent>,
+    mut failed_writer: EventWriter<CommandFailedEvent>,
     command_registry: Res<CommandRegistry>,
     scope_registry: Res<CommandScopeRegistry>,
     entity_scopes: Query<&CommandScopes>,
@@ -238,6 +404,7 @@ fn parse_incoming_commands(
 
         let mut args = Vec::new();
         let mut modifiers_to_be_executed = Vec::new();
+        let mut failures = ParseFailures::new(command_input.len(), root);
 
         parse_command_args(
             &mut args,
@@ -252,8 +419,61 @@ fn parse_incoming_commands(
             &entity_scopes,
             scope_registry.as_ref(),
             false,
+            (root, 0),
+            &mut failures,
         );
 
+        if to_be_executed.is_empty() {
+            let failure = failures.furthest.unwrap_or(ParseFailure {
+                cursor: 0,
+                error: CommandError::UnknownCommand,
+                node: root,
+                node_end: 0,
+            });
+
+            let usage = if failure.node == root {
+                Vec::new()
+            } else {
+                let default_scopes = CommandScopes::new();
+                let client_scopes: Vec<&str> = entity_scopes
+                    .get(executor)
+                    .unwrap_or(&default_scopes)
+                    .0
+                    .iter()
+                    .map(|scope| scope.as_str())
+                    .collect();
+
+                let prefix = command_input[..failure.node_end].trim();
+
+                command_registry
+                    .graph
+                    .usage(failure.node, |node| {
+                        node.scopes.is_empty()
+                            || node
+                                .scopes
+                                .iter()
+                                .any(|scope| scope_registry.any_grants(&client_scopes, scope))
+                    })
+                    .into_iter()
+                    .map(|usage| format!("{prefix} {usage}"))
+                    .collect()
+            };
+
+            debug!(
+                "Command failed: /{} ({} at {})",
+                command_event.command, failure.error, failure.cursor
+            );
+
+            failed_writer.send(CommandFailedEvent {
+                command: command_event.command.clone(),
+                executor,
+                error: failure.error,
+                cursor: failure.cursor,
+                usage,
+            });
+            continue;
+        }
+
         let mut modifiers = HashMap::new();
         for (node, modifier) in modifiers_to_be_executed {
             command_registry.modifiers[&node](modifier, &mut modifiers);
@@ -276,6 +496,74 @@ fn parse_incoming_commands(
     }
 }
 
+/// Where parsing of a command failed and the last node that was parsed before
+/// it.
+#[derive(Debug)]
+struct ParseFailure {
+    cursor: usize,
+    error: CommandError,
+    node: NodeIndex,
+    /// the byte offset in the command where the text of `node` ends
+    node_end: usize,
+}
+
+/// Keeps track of the failure that got the furthest into the command, which
+/// is the one reported to the executor.
+struct ParseFailures {
+    command_len: usize,
+    root: NodeIndex,
+    furthest: Option<ParseFailure>,
+}
+
+impl ParseFailures {
+    fn new(command_len: usize, root: NodeIndex) -> Self {
+        Self {
+            command_len,
+            root,
+            furthest: None,
+        }
+    }
+
+    /// Records a failure at the current position of `input`. Parser errors
+    /// take precedence over other failures at the same position.
+    fn record(
+        &mut self,
+        input: &ParseInput,
+        error: CommandError,
+        (node, node_end): (NodeIndex, usize),
+    ) {
+        let cursor = self.command_len - input.len();
+
+        let replace = match &self.furthest {
+            None => true,
+            Some(furthest) => {
+                cursor > furthest.cursor
+                    || (cursor == furthest.cursor
+                        && matches!(error, CommandError::InvalidArgument(_))
+                        && !matches!(furthest.error, CommandError::InvalidArgument(_)))
+            }
+        };
+
+        if replace {
+            self.furthest = Some(ParseFailure {
+                cursor,
+                error,
+                node,
+                node_end,
+            });
+        }
+    }
+
+    /// The error for input that doesn't match any child of `node`.
+    fn unknown(&self, input: &ParseInput, node: NodeIndex) -> CommandError {
+        if node == self.root || input.is_done() {
+            CommandError::UnknownCommand
+        } else {
+            CommandError::UnknownArgument
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 /// recursively parse the command args.
 fn parse_command_args(
@@ -291,6 +579,8 @@ fn parse_command_args(
     scopes: &Query<&CommandScopes>,
     scope_registry: &CommandScopeRegistry,
     coming_from_redirect: bool,
+    parent: (NodeIndex, usize),
+    failures: &mut ParseFailures,
 ) -> bool {
     let node_scopes = &graph[current_node].scopes;
     let default_scopes = CommandScopes::new();
@@ -311,6 +601,8 @@ fn parse_command_args(
             }
         }
         if !has_scope {
+            // nodes the executor can't use are reported as if they don't exist
+            failures.record(&input, failures.unknown(&input, parent.0), parent);
             return false;
         }
     }
@@ -328,59 +620,80 @@ fn parse_command_args(
             // if the node is a literal, we want to match the name of the literal
             // to the input
             NodeData::Literal { name } => {
+                let pre_input = input.clone();
                 if input.match_next(name) {
                     if !input.match_next(" ") && !input.is_done() {
+                        failures.record(&pre_input, failures.unknown(&pre_input, parent.0), parent);
                         return false;
                     } // we want to pop the whitespace after the literal
                     if command_registry.modifiers.contains_key(&current_node) {
                         modifiers_to_be_executed.push((current_node, String::new()));
                     }
                 } else {
+                    failures.record(&input, failures.unknown(&input, parent.0), parent);
                     return false;
                 }
             }
             // if the node is an argument, we want to parse the argument
             NodeData::Argument { .. } => {
                 let Some(parser) = command_registry.parsers.get(&current_node) else {
+                    failures.record(&input, failures.unknown(&input, parent.0), parent);
                     return false;
                 };
 
                 // we want to save the input before and after parsing
                 // this is so we can save the argument to the command args
                 let pre_input = input.clone().into_inner();
-                let valid = parser(&mut input);
-                if valid {
-                    // If input.len() > pre_input.len() the parser replaced the input
-                    let Some(arg) = pre_input
-                        .get(..pre_input.len().wrapping_sub(input.len()))
-                        .map(|s| s.to_owned())
-                    else {
-                        panic!(
-                            "Parser replaced input with another string. This is not allowed. \
-                             Attempting to parse: {}",
-                            input.into_inner()
-                        );
+                let pre_parse = input.clone();
+                if let Err(error) = parser(&mut input) {
+                    // a missing argument makes the command incomplete rather than invalid
+                    let error = if pre_parse.is_done() {
+                        CommandError::UnknownCommand
+                    } else {
+                        CommandError::InvalidArgument(error)
                     };
-
-                    if command_registry.modifiers.contains_key(&current_node) {
-                        modifiers_to_be_executed.push((current_node, arg.clone()));
-                    }
-                    command_args.push(arg);
-                } else {
+                    failures.record(&pre_parse, error, parent);
                     return false;
                 }
+
+                // If input.len() > pre_input.len() the parser replaced the input
+                let Some(arg) = pre_input
+                    .get(..pre_input.len().wrapping_sub(input.len()))
+                    .map(|s| s.to_owned())
+                else {
+                    panic!(
+                        "Parser replaced input with another string. This is not allowed. \
+                         Attempting to parse: {}",
+                        input.into_inner()
+                    );
+                };
+
+                if command_registry.modifiers.contains_key(&current_node) {
+                    modifiers_to_be_executed.push((current_node, arg.clone()));
+                }
+                command_args.push(arg);
             }
         }
     } else {
         command_args.clear();
     }
 
+    let node_end = failures.command_len - input.len();
+
     input.skip_whitespace();
     if input.is_done() && executable_leafs.contains(&&current_node) {
         to_be_executed.push(current_node);
         return true;
     }
 
+    if input.is_done() {
+        failures.record(
+            &input,
+            CommandError::UnknownCommand,
+            (current_node, node_end),
+        );
+    }
+
     let mut all_invalid = true;
     for neighbor in graph.neighbors(current_node) {
         let pre_input = input.clone();
@@ -402,6 +715,8 @@ fn parse_command_args(
                 let edge = graph.find_edge(current_node, neighbor).unwrap();
                 matches!(&graph[edge], CommandEdgeType::Redirect)
             },
+            (current_node, node_end),
+            failures,
         );
         if valid {
             *command_args = args;
@@ -412,6 +727,14 @@ fn parse_command_args(
         }
     }
     if all_invalid {
+        if !input.is_done() {
+            // nothing can follow this node, eg. an extra argument at the end
+            failures.record(
+                &input,
+                failures.unknown(&input, current_node),
+                (current_node, node_end),
+            );
+        }
         return false;
     }
     true
@@ -35,6 +35,8 @@ pub(crate) use valence_server::protocol::packets::play::command_tree_s2c::Parser
 pub use vec2::Vec2;
 pub use vec3::Vec3;
 
+use crate::suggestions::SuggestionProvider;
+
 pub trait CommandArg: Sized {
     fn arg_from_str(string: &str) -> Result<Self, CommandArgParseError> {
         Self::parse_arg(&mut ParseInput::new(string))
@@ -43,6 +45,11 @@ pub trait CommandArg: Sized {
     fn parse_arg(input: &mut ParseInput) -> Result<Self, CommandArgParseError>;
     /// what will the client be sent
     fn display() -> Parser;
+    /// the suggestions offered while the argument is being typed. Nodes using
+    /// this parser will ask the server for suggestions if this is `Some`.
+    fn suggestions() -> Option<SuggestionProvider> {
+        None
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -175,7 +182,7 @@ impl<'a> ParseInput<'a> {
     }
 }
 
-#[derive(Debug, Error)]
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
 pub enum CommandArgParseError {
     // these should be player facing and not disclose internal information
     #[error("invalid argument, expected {expected} got {got}")] // e.g. "integer" number
@@ -1,10 +1,18 @@
+use std::str::FromStr;
+
+use valence_nbt::snbt::SnbtReader;
+use valence_nbt::{Compound, Value};
+use valence_server::GameMode;
+
 use super::Parser;
-use crate::parsers::{CommandArg, CommandArgParseError, ParseInput};
+use crate::parsers::{CommandArg, CommandArgParseError, ParseInput, QuotableString};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// An entity selector. Use [`SelectorQuery`](crate::selector::SelectorQuery)
+/// to find the entities it selects.
+#[derive(Debug, Clone, PartialEq)]
 pub enum EntitySelector {
     SimpleSelector(EntitySelectors),
-    ComplexSelector(EntitySelectors, String),
+    ComplexSelector(EntitySelectors, SelectorArguments),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -18,6 +26,113 @@ pub enum EntitySelectors {
     RandomPlayer,
 }
 
+/// The arguments between the brackets of a selector, e.g. the
+/// `type=minecraft:zombie,limit=2` in `@e[type=minecraft:zombie,limit=2]`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SelectorArguments {
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub z: Option<f64>,
+    pub distance: Option<SelectorRange<f64>>,
+    pub dx: Option<f64>,
+    pub dy: Option<f64>,
+    pub dz: Option<f64>,
+    pub limit: Option<usize>,
+    pub sort: Option<SelectorSort>,
+    /// Entity type identifiers, always with a namespace (e.g.
+    /// `minecraft:zombie`).
+    pub entity_type: Vec<Negatable<String>>,
+    pub name: Vec<Negatable<String>>,
+    pub tag: Vec<Negatable<String>>,
+    pub team: Vec<Negatable<String>>,
+    pub gamemode: Vec<Negatable<GameMode>>,
+    /// Objective names and the range the score must be in.
+    pub scores: Option<Vec<(String, SelectorRange<i32>)>>,
+    pub nbt: Vec<Negatable<Compound>>,
+}
+
+impl SelectorArguments {
+    /// Whether the selector only selects entities in the same layer as the
+    /// executor. This is the case when any of the position arguments are used.
+    pub fn is_layer_limited(&self) -> bool {
+        self.x.is_some()
+            || self.y.is_some()
+            || self.z.is_some()
+            || self.distance.is_some()
+            || self.dx.is_some()
+            || self.dy.is_some()
+            || self.dz.is_some()
+    }
+}
+
+/// A selector argument value that may be prefixed with `!`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Negatable<T> {
+    pub value: T,
+    pub negated: bool,
+}
+
+impl<T> Negatable<T> {
+    /// Checks the value with `predicate` and inverts the result if negated.
+    pub fn matches<F: FnOnce(&T) -> bool>(&self, predicate: F) -> bool {
+        predicate(&self.value) != self.negated
+    }
+}
+
+/// An inclusive range like `1..5`, `..5`, `1..` or `3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SelectorRange<T> {
+    pub min: Option<T>,
+    pub max: Option<T>,
+}
+
+impl<T: PartialOrd> SelectorRange<T> {
+    pub fn contains(&self, value: &T) -> bool {
+        self.min.as_ref().map_or(true, |min| min <= value)
+            && self.max.as_ref().map_or(true, |max| value <= max)
+    }
+}
+
+impl<T: FromStr + Clone> SelectorRange<T> {
+    fn parse(input: &mut ParseInput) -> Result<Self, CommandArgParseError> {
+        let range = read_unquoted(input);
+
+        let parse_bound = |bound: &str| {
+            if bound.is_empty() {
+                Ok(None)
+            } else {
+                bound
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| invalid_argument("range", range))
+            }
+        };
+
+        match range.split_once("..") {
+            Some(("", "")) => Err(invalid_argument("range", range)),
+            Some((min, max)) => Ok(Self {
+                min: parse_bound(min)?,
+                max: parse_bound(max)?,
+            }),
+            None => match parse_bound(range)? {
+                Some(exact) => Ok(Self {
+                    min: Some(exact.clone()),
+                    max: Some(exact),
+                }),
+                None => Err(invalid_argument("range", range)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorSort {
+    Nearest,
+    Furthest,
+    Random,
+    Arbitrary,
+}
+
 impl CommandArg for EntitySelector {
     // we want to get either a simple string [`@e`, `@a`, `@p`, `@r`,
     // `<player_name>`] or a full selector: [`@e[<selector>]`, `@a[<selector>]`,
@@ -25,57 +140,41 @@ impl CommandArg for EntitySelector {
     // them, so we need to be careful
     fn parse_arg(input: &mut ParseInput) -> Result<Self, CommandArgParseError> {
         input.skip_whitespace();
-        let mut simple_selector = None;
-        while let Some(c) = input.peek() {
-            match c {
-                '@' => {
-                    input.pop(); // pop the '@'
-                    match input.pop() {
-                        Some('e') => simple_selector = Some(EntitySelectors::AllEntities),
-                        Some('a') => simple_selector = Some(EntitySelectors::AllPlayers),
-                        Some('p') => simple_selector = Some(EntitySelectors::NearestPlayer),
-                        Some('r') => simple_selector = Some(EntitySelectors::RandomPlayer),
-                        Some('s') => simple_selector = Some(EntitySelectors::SelfPlayer),
-                        _ => {
-                            return Err(CommandArgParseError::InvalidArgument {
-                                expected: "entity selector".to_owned(),
-                                got: c.to_string(),
-                            })
-                        }
-                    }
-                    if input.peek() != Some('[') {
-                        // if there's no complex selector, we're done
-                        return Ok(EntitySelector::SimpleSelector(simple_selector.unwrap()));
-                    }
-                }
-                '[' => {
-                    input.pop();
-                    if simple_selector.is_none() {
-                        return Err(CommandArgParseError::InvalidArgument {
-                            expected: "entity selector".to_owned(),
-                            got: c.to_string(),
-                        });
-                    }
-                    let mut s = String::new();
-                    while let Some(c) = input.pop() {
-                        if c == ']' {
-                            return Ok(EntitySelector::ComplexSelector(
-                                simple_selector.unwrap(),
-                                s.trim().to_owned(),
-                            ));
-                        }
-
-                        s.push(c);
-                    }
-                }
-                _ => {
-                    return Ok(EntitySelector::SimpleSelector(
-                        EntitySelectors::SinglePlayer(String::parse_arg(input)?),
-                    ))
-                }
+
+        if input.peek() != Some('@') {
+            if input.is_done() {
+                return Err(CommandArgParseError::InvalidArgLength);
             }
+
+            return Ok(EntitySelector::SimpleSelector(
+                EntitySelectors::SinglePlayer(String::parse_arg(input)?),
+            ));
         }
-        Err(CommandArgParseError::InvalidArgLength)
+
+        input.pop(); // pop the '@'
+        let selector = match input.pop() {
+            Some('e') => EntitySelectors::AllEntities,
+            Some('a') => EntitySelectors::AllPlayers,
+            Some('p') => EntitySelectors::NearestPlayer,
+            Some('r') => EntitySelectors::RandomPlayer,
+            Some('s') => EntitySelectors::SelfPlayer,
+            c => {
+                return Err(invalid_argument(
+                    "entity selector",
+                    c.map(String::from).unwrap_or_default(),
+                ))
+            }
+        };
+
+        if input.peek() != Some('[') {
+            // if there's no complex selector, we're done
+            return Ok(EntitySelector::SimpleSelector(selector));
+        }
+        input.pop();
+
+        let arguments = SelectorArguments::parse(input, &selector)?;
+
+        Ok(EntitySelector::ComplexSelector(selector, arguments))
     }
 
     fn display() -> Parser {
@@ -86,10 +185,268 @@ impl CommandArg for EntitySelector {
     }
 }
 
+impl SelectorArguments {
+    /// Parses the arguments after the opening bracket up to and including the
+    /// closing bracket.
+    fn parse(
+        input: &mut ParseInput,
+        selector: &EntitySelectors,
+    ) -> Result<Self, CommandArgParseError> {
+        let mut arguments = Self::default();
+
+        loop {
+            input.skip_whitespace();
+            if input.peek() == Some(']') {
+                input.pop();
+                return Ok(arguments);
+            }
+
+            let option = read_unquoted(input);
+            input.skip_whitespace();
+            expect(input, '=')?;
+            input.skip_whitespace();
+
+            arguments.parse_option(option, input, selector)?;
+
+            input.skip_whitespace();
+            match input.pop() {
+                Some(',') => {}
+                Some(']') => return Ok(arguments),
+                Some(c) => return Err(invalid_argument("',' or ']'", c)),
+                None => return Err(CommandArgParseError::InvalidArgLength),
+            }
+        }
+    }
+
+    fn parse_option(
+        &mut self,
+        option: &str,
+        input: &mut ParseInput,
+        selector: &EntitySelectors,
+    ) -> Result<(), CommandArgParseError> {
+        let selects_players = matches!(
+            selector,
+            EntitySelectors::AllPlayers
+                | EntitySelectors::NearestPlayer
+                | EntitySelectors::RandomPlayer
+        );
+        let is_sorted = matches!(
+            selector,
+            EntitySelectors::NearestPlayer | EntitySelectors::RandomPlayer
+        );
+        let is_self = *selector == EntitySelectors::SelfPlayer;
+
+        match option {
+            "x" => set_once(&mut self.x, option, parse_number(input)?),
+            "y" => set_once(&mut self.y, option, parse_number(input)?),
+            "z" => set_once(&mut self.z, option, parse_number(input)?),
+            "dx" => set_once(&mut self.dx, option, parse_number(input)?),
+            "dy" => set_once(&mut self.dy, option, parse_number(input)?),
+            "dz" => set_once(&mut self.dz, option, parse_number(input)?),
+            "distance" => set_once(&mut self.distance, option, SelectorRange::parse(input)?),
+            "limit" if !is_self => {
+                let limit: usize = parse_number(input)?;
+                if limit == 0 {
+                    return Err(invalid_argument("limit of at least 1", limit));
+                }
+                set_once(&mut self.limit, option, limit)
+            }
+            "sort" if !is_self && !is_sorted => {
+                let sort = match read_unquoted(input) {
+                    "nearest" => SelectorSort::Nearest,
+                    "furthest" => SelectorSort::Furthest,
+                    "random" => SelectorSort::Random,
+                    "arbitrary" => SelectorSort::Arbitrary,
+                    sort => return Err(invalid_argument("selector sort", sort)),
+                };
+                set_once(&mut self.sort, option, sort)
+            }
+            "type" if !selects_players => {
+                let negated = parse_negation(input);
+                let entity_type = read_unquoted(input);
+                if entity_type.is_empty() || entity_type.starts_with('#') {
+                    return Err(invalid_argument("entity type", entity_type));
+                }
+                let entity_type = if entity_type.contains(':') {
+                    entity_type.to_owned()
+                } else {
+                    format!("minecraft:{entity_type}")
+                };
+                push_single(&mut self.entity_type, option, entity_type, negated)
+            }
+            "name" => {
+                let negated = parse_negation(input);
+                let name = match input.peek() {
+                    Some('"') => QuotableString::parse_arg(input)?.0,
+                    _ => read_unquoted(input).to_owned(),
+                };
+                push_single(&mut self.name, option, name, negated)
+            }
+            "team" => {
+                let negated = parse_negation(input);
+                let team = read_unquoted(input).to_owned();
+                push_single(&mut self.team, option, team, negated)
+            }
+            "gamemode" => {
+                let negated = parse_negation(input);
+                let game_mode = GameMode::parse_arg(input)?;
+                push_single(&mut self.gamemode, option, game_mode, negated)
+            }
+            "tag" => {
+                let negated = parse_negation(input);
+                let tag = read_unquoted(input).to_owned();
+                self.tag.push(Negatable {
+                    value: tag,
+                    negated,
+                });
+                Ok(())
+            }
+            "nbt" => {
+                let negated = parse_negation(input);
+                let nbt = parse_compound(input)?;
+                self.nbt.push(Negatable {
+                    value: nbt,
+                    negated,
+                });
+                Ok(())
+            }
+            "scores" => {
+                let scores = parse_scores(input)?;
+                set_once(&mut self.scores, option, scores)
+            }
+            _ => Err(not_applicable(option)),
+        }
+    }
+}
+
+fn invalid_argument(expected: &str, got: impl ToString) -> CommandArgParseError {
+    CommandArgParseError::InvalidArgument {
+        expected: expected.to_owned(),
+        got: got.to_string(),
+    }
+}
+
+fn not_applicable(option: &str) -> CommandArgParseError {
+    invalid_argument("selector option applicable here", option)
+}
+
+fn expect(input: &mut ParseInput, expected: char) -> Result<(), CommandArgParseError> {
+    match input.pop() {
+        Some(c) if c == expected => Ok(()),
+        Some(c) => Err(invalid_argument(&format!("'{expected}'"), c)),
+        None => Err(CommandArgParseError::InvalidArgLength),
+    }
+}
+
+/// Pops everything up to the next character that ends a value.
+fn read_unquoted<'a>(input: &mut ParseInput<'a>) -> &'a str {
+    let rest = input.0;
+    let end = rest
+        .find(|c: char| c.is_whitespace() || matches!(c, ',' | '=' | '[' | ']' | '{' | '}'))
+        .unwrap_or(rest.len());
+
+    input.advance_n_bytes(end);
+    &rest[..end]
+}
+
+fn parse_number<T: FromStr>(input: &mut ParseInput) -> Result<T, CommandArgParseError> {
+    let number = read_unquoted(input);
+    number
+        .parse()
+        .map_err(|_| invalid_argument("number", number))
+}
+
+fn parse_negation(input: &mut ParseInput) -> bool {
+    let negated = input.peek() == Some('!');
+    if negated {
+        input.pop();
+        input.skip_whitespace();
+    }
+    negated
+}
+
+fn parse_compound(input: &mut ParseInput) -> Result<Compound, CommandArgParseError> {
+    let mut reader = SnbtReader::new(input.0);
+
+    match reader.parse_element() {
+        Ok(Value::Compound(compound)) => {
+            input.advance_n_bytes(reader.bytes_read());
+            Ok(compound)
+        }
+        _ => Err(invalid_argument("nbt compound", input.peek_word())),
+    }
+}
+
+fn parse_scores(
+    input: &mut ParseInput,
+) -> Result<Vec<(String, SelectorRange<i32>)>, CommandArgParseError> {
+    let mut scores = vec![];
+
+    expect(input, '{')?;
+
+    loop {
+        input.skip_whitespace();
+        if input.peek() == Some('}') {
+            input.pop();
+            return Ok(scores);
+        }
+
+        let objective = read_unquoted(input).to_owned();
+        input.skip_whitespace();
+        expect(input, '=')?;
+        input.skip_whitespace();
+
+        scores.push((objective, SelectorRange::parse(input)?));
+
+        input.skip_whitespace();
+        match input.pop() {
+            Some(',') => {}
+            Some('}') => return Ok(scores),
+            Some(c) => return Err(invalid_argument("',' or '}'", c)),
+            None => return Err(CommandArgParseError::InvalidArgLength),
+        }
+    }
+}
+
+fn set_once<T>(slot: &mut Option<T>, option: &str, value: T) -> Result<(), CommandArgParseError> {
+    if slot.is_some() {
+        return Err(not_applicable(option));
+    }
+
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Adds a value that may be negated any number of times, but only used once
+/// without negation.
+fn push_single<T>(
+    values: &mut Vec<Negatable<T>>,
+    option: &str,
+    value: T,
+    negated: bool,
+) -> Result<(), CommandArgParseError> {
+    if values.iter().any(|value| !value.negated) || (!negated && !values.is_empty()) {
+        return Err(not_applicable(option));
+    }
+
+    values.push(Negatable { value, negated });
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn distance_up_to_5() -> SelectorArguments {
+        SelectorArguments {
+            distance: Some(SelectorRange {
+                min: None,
+                max: Some(5.0),
+            }),
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_entity_selector() {
         let mut input = ParseInput::new("@e");
@@ -102,10 +459,7 @@ mod tests {
         let mut input = ParseInput::new("@e[distance=..5]");
         assert_eq!(
             EntitySelector::parse_arg(&mut input).unwrap(),
-            EntitySelector::ComplexSelector(
-                EntitySelectors::AllEntities,
-                "distance=..5".to_owned()
-            )
+            EntitySelector::ComplexSelector(EntitySelectors::AllEntities, distance_up_to_5())
         );
         assert!(input.is_done());
 
@@ -116,41 +470,138 @@ mod tests {
         let mut input = ParseInput::new("@r[distance=..5] hello");
         assert_eq!(
             EntitySelector::parse_arg(&mut input).unwrap(),
-            EntitySelector::ComplexSelector(
-                EntitySelectors::RandomPlayer,
-                "distance=..5".to_owned()
-            )
+            EntitySelector::ComplexSelector(EntitySelectors::RandomPlayer, distance_up_to_5())
         );
         assert!(!input.is_done());
 
         let mut input = ParseInput::new("@p[distance=..5]hello");
         assert_eq!(
             EntitySelector::parse_arg(&mut input).unwrap(),
-            EntitySelector::ComplexSelector(
-                EntitySelectors::NearestPlayer,
-                "distance=..5".to_owned()
-            )
+            EntitySelector::ComplexSelector(EntitySelectors::NearestPlayer, distance_up_to_5())
         );
         assert!(!input.is_done());
 
         let mut input = ParseInput::new("@e[distance=..5] hello world");
         assert_eq!(
             EntitySelector::parse_arg(&mut input).unwrap(),
-            EntitySelector::ComplexSelector(
-                EntitySelectors::AllEntities,
-                "distance=..5".to_owned()
-            )
+            EntitySelector::ComplexSelector(EntitySelectors::AllEntities, distance_up_to_5())
         );
         assert!(!input.is_done());
 
         let mut input = ParseInput::new("@e[distance=..5]hello world");
         assert_eq!(
             EntitySelector::parse_arg(&mut input).unwrap(),
-            EntitySelector::ComplexSelector(
-                EntitySelectors::AllEntities,
-                "distance=..5".to_owned()
-            )
+            EntitySelector::ComplexSelector(EntitySelectors::AllEntities, distance_up_to_5())
         );
         assert!(!input.is_done());
     }
+
+    #[test]
+    fn test_selector_arguments() {
+        let mut input = ParseInput::new(
+            "@e[ type = !zombie, x=1.5,y=-2,z=3, dx=4,dy=5,dz=6, limit=2, sort=nearest, \
+             name=\"Some Name\", tag=a, tag=!b, team=, gamemode=!creative, \
+             scores={kills=1..,deaths=..2}, nbt={Health:20f}] rest",
+        );
+
+        let EntitySelector::ComplexSelector(EntitySelectors::AllEntities, arguments) =
+            EntitySelector::parse_arg(&mut input).unwrap()
+        else {
+            panic!("expected a complex selector");
+        };
+        assert_eq!(input.into_inner(), " rest");
+
+        assert_eq!(
+            arguments.entity_type,
+            [Negatable {
+                value: "minecraft:zombie".to_owned(),
+                negated: true
+            }]
+        );
+        assert_eq!(
+            (arguments.x, arguments.y, arguments.z),
+            (Some(1.5), Some(-2.0), Some(3.0))
+        );
+        assert_eq!(
+            (arguments.dx, arguments.dy, arguments.dz),
+            (Some(4.0), Some(5.0), Some(6.0))
+        );
+        assert_eq!(arguments.limit, Some(2));
+        assert_eq!(arguments.sort, Some(SelectorSort::Nearest));
+        assert_eq!(arguments.name[0].value, "Some Name");
+        assert_eq!(arguments.tag.len(), 2);
+        assert!(arguments.tag[1].negated);
+        assert_eq!(arguments.team[0].value, "");
+        assert_eq!(
+            arguments.gamemode,
+            [Negatable {
+                value: GameMode::Creative,
+                negated: true
+            }]
+        );
+        assert_eq!(
+            arguments.scores,
+            Some(vec![
+                (
+                    "kills".to_owned(),
+                    SelectorRange {
+                        min: Some(1),
+                        max: None
+                    }
+                ),
+                (
+                    "deaths".to_owned(),
+                    SelectorRange {
+                        min: None,
+                        max: Some(2)
+                    }
+                ),
+            ])
+        );
+        assert_eq!(
+            arguments.nbt[0].value.get("Health"),
+            Some(&Value::Float(20.0))
+        );
+        assert!(arguments.is_layer_limited());
+    }
+
+    #[test]
+    fn test_invalid_selector_arguments() {
+        for selector in [
+            "@e[limit=0]",
+            "@e[limit=1,limit=2]",
+            "@e[type=zombie,type=skeleton]",
+            "@e[unknown=1]",
+            "@e[distance=..]",
+            "@s[limit=1]",
+            "@p[sort=random]",
+            "@a[type=player]",
+            "@e[name=a,name=b]",
+            "@e[nbt=1]",
+            "@e[scores={a=1]",
+        ] {
+            assert!(
+                EntitySelector::parse_arg(&mut ParseInput::new(selector)).is_err(),
+                "{selector} should not parse"
+            );
+        }
+
+        assert!(
+            EntitySelector::parse_arg(&mut ParseInput::new("@e[type=!zombie,type=!skeleton]"))
+                .is_ok()
+        );
+        assert!(EntitySelector::parse_arg(&mut ParseInput::new("@e[]")).is_ok());
+    }
+
+    #[test]
+    fn test_selector_range() {
+        let range = SelectorRange::<i32>::parse(&mut ParseInput::new("3")).unwrap();
+        assert!(range.contains(&3));
+        assert!(!range.contains(&4));
+
+        let range = SelectorRange::<f64>::parse(&mut ParseInput::new("0.5..1.5")).unwrap();
+        assert!(range.contains(&0.5));
+        assert!(range.contains(&1.5));
+        assert!(!range.contains(&2.0));
+    }
 }
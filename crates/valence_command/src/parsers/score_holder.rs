@@ -2,7 +2,7 @@ use super::Parser;
 use crate::parsers::entity_selector::EntitySelector;
 use crate::parsers::{CommandArg, CommandArgParseError, ParseInput};
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum ScoreHolder {
     Entity(EntitySelector),
     #[default]
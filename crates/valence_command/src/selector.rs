@@ -0,0 +1,340 @@
+//! # Entity selector resolution
+//!
+//! [`SelectorQuery`] is a system param that finds the entities an
+//! [`EntitySelector`] selects, so command handlers don't need to implement
+//! the selector rules themselves.
+//!
+//! ```
+//! use bevy_ecs::prelude::*;
+//! use valence_command::parsers::EntitySelector;
+//! use valence_command::selector::SelectorQuery;
+//!
+//! // called from a command handler with the parsed `targets` argument
+//! fn kill(
+//!     targets: &EntitySelector,
+//!     executor: Entity,
+//!     selector: &SelectorQuery,
+//!     commands: &mut Commands,
+//! ) {
+//!     for target in selector.resolve(targets, executor) {
+//!         commands.entity(target).despawn();
+//!     }
+//! }
+//! ```
+//!
+//! Selectors are resolved the same way as in vanilla with a few differences:
+//! * Selectors only select entities in the same layer as the executor if any of
+//!   the position arguments (`x`, `y`, `z`, `distance`, `dx`, `dy` or `dz`) are
+//!   used.
+//! * The `dx`, `dy` and `dz` arguments check the position of the entity instead
+//!   of its hitbox.
+//! * Entities other than players are named by their
+//!   [`CustomName`](valence_server::entity::entity::CustomName).
+//! * Tags are stored in the [`EntityTags`] component.
+//! * Teams and scores are read from the teams and objectives of
+//!   `valence_scoreboard` in the layer of each entity.
+//! * The `nbt` argument is not checked, since entities have no NBT to compare
+//!   against. Handlers that support it can check
+//!   [`SelectorArguments::nbt`](crate::parsers::entity_selector::SelectorArguments::nbt)
+//!   themselves.
+
+use std::collections::BTreeSet;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use bevy_ecs::system::SystemParam;
+use valence_scoreboard::{Objective, ObjectiveScores, Team, TeamMembers};
+use valence_server::client::Username;
+use valence_server::entity::entity::CustomName;
+use valence_server::entity::{EntityKind, EntityLayerId, Position};
+use valence_server::math::DVec3;
+use valence_server::rand::seq::SliceRandom;
+use valence_server::{Despawned, GameMode, UniqueId};
+
+use crate::parsers::entity_selector::{
+    EntitySelector, EntitySelectors, SelectorArguments, SelectorSort,
+};
+
+/// The tags of an entity checked by the `tag` selector argument.
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct EntityTags(pub BTreeSet<String>);
+
+/// The components of an entity checked by a [`SelectorQuery`].
+#[derive(QueryData)]
+pub struct SelectorEntity {
+    entity: Entity,
+    kind: &'static EntityKind,
+    position: &'static Position,
+    layer: &'static EntityLayerId,
+    uuid: &'static UniqueId,
+    username: Option<&'static Username>,
+    custom_name: Option<&'static CustomName>,
+    game_mode: Option<&'static GameMode>,
+    tags: Option<&'static EntityTags>,
+}
+
+impl SelectorEntityItem<'_> {
+    /// The name the entity is known by on the scoreboard.
+    fn score_holder(&self) -> String {
+        match self.username {
+            Some(username) => username.0.clone(),
+            None => self.uuid.0.to_string(),
+        }
+    }
+
+    fn is_player(&self) -> bool {
+        *self.kind == EntityKind::PLAYER
+    }
+}
+
+/// Finds the entities selected by an [`EntitySelector`]. See the
+/// [module level documentation](self) for more info.
+#[derive(SystemParam)]
+pub struct SelectorQuery<'w, 's> {
+    entities: Query<'w, 's, SelectorEntity, Without<Despawned>>,
+    teams: Query<'w, 's, (&'static Team, &'static TeamMembers, &'static EntityLayerId)>,
+    objectives: Query<
+        'w,
+        's,
+        (
+            &'static Objective,
+            &'static ObjectiveScores,
+            &'static EntityLayerId,
+        ),
+    >,
+}
+
+impl SelectorQuery<'_, '_> {
+    /// Returns the entities selected by `selector` when used by `executor`.
+    /// The executor doesn't need to be an entity with a position, in which case
+    /// positions are relative to the origin.
+    pub fn resolve(&self, selector: &EntitySelector, executor: Entity) -> Vec<Entity> {
+        let default_arguments = SelectorArguments::default();
+        let (selector, arguments) = match selector {
+            EntitySelector::SimpleSelector(selector) => (selector, &default_arguments),
+            EntitySelector::ComplexSelector(selector, arguments) => (selector, arguments),
+        };
+
+        let executor = self.entities.get(executor).ok();
+
+        let executor_pos = executor.as_ref().map_or(DVec3::ZERO, |e| e.position.0);
+        let origin = DVec3::new(
+            arguments.x.unwrap_or(executor_pos.x),
+            arguments.y.unwrap_or(executor_pos.y),
+            arguments.z.unwrap_or(executor_pos.z),
+        );
+
+        let layer = arguments
+            .is_layer_limited()
+            .then(|| executor.as_ref().map(|e| e.layer.0))
+            .flatten();
+
+        let mut selected: Vec<_> = match selector {
+            EntitySelectors::SinglePlayer(name) => {
+                return self
+                    .entities
+                    .iter()
+                    .find(|e| e.username.is_some_and(|u| u.0.eq_ignore_ascii_case(name)))
+                    .map(|e| e.entity)
+                    .into_iter()
+                    .collect();
+            }
+            EntitySelectors::SelfPlayer => executor
+                .into_iter()
+                .filter(|e| self.matches(e, arguments, origin, layer))
+                .map(|e| (e.entity, e.position.distance_squared(origin)))
+                .collect(),
+            EntitySelectors::AllEntities => self
+                .entities
+                .iter()
+                .filter(|e| self.matches(e, arguments, origin, layer))
+                .map(|e| (e.entity, e.position.distance_squared(origin)))
+                .collect(),
+            EntitySelectors::AllPlayers
+            | EntitySelectors::NearestPlayer
+            | EntitySelectors::RandomPlayer => self
+                .entities
+                .iter()
+                .filter(|e| e.is_player() && self.matches(e, arguments, origin, layer))
+                .map(|e| (e.entity, e.position.distance_squared(origin)))
+                .collect(),
+        };
+
+        let (default_sort, default_limit) = match selector {
+            EntitySelectors::NearestPlayer => (SelectorSort::Nearest, Some(1)),
+            EntitySelectors::RandomPlayer => (SelectorSort::Random, Some(1)),
+            _ => (SelectorSort::Arbitrary, None),
+        };
+
+        match arguments.sort.unwrap_or(default_sort) {
+            SelectorSort::Nearest => selected.sort_by(|(_, a), (_, b)| a.total_cmp(b)),
+            SelectorSort::Furthest => selected.sort_by(|(_, a), (_, b)| b.total_cmp(a)),
+            SelectorSort::Random => selected.shuffle(&mut valence_server::rand::thread_rng()),
+            SelectorSort::Arbitrary => {}
+        }
+
+        if let Some(limit) = arguments.limit.or(default_limit) {
+            selected.truncate(limit);
+        }
+
+        selected.into_iter().map(|(entity, _)| entity).collect()
+    }
+
+    fn matches(
+        &self,
+        entity: &SelectorEntityItem,
+        arguments: &SelectorArguments,
+        origin: DVec3,
+        layer: Option<Entity>,
+    ) -> bool {
+        if layer.is_some_and(|layer| entity.layer.0 != layer) {
+            return false;
+        }
+
+        let position = entity.position.0;
+
+        if let Some(distance) = &arguments.distance {
+            let distance_squared = position.distance_squared(origin);
+            let squared = |bound: Option<f64>| bound.map(|bound| bound * bound);
+            let min = squared(distance.min);
+            let max = squared(distance.max);
+
+            if min.is_some_and(|min| distance_squared < min)
+                || max.is_some_and(|max| distance_squared > max)
+            {
+                return false;
+            }
+        }
+
+        if arguments.dx.is_some() || arguments.dy.is_some() || arguments.dz.is_some() {
+            let size = DVec3::new(
+                arguments.dx.unwrap_or(0.0),
+                arguments.dy.unwrap_or(0.0),
+                arguments.dz.unwrap_or(0.0),
+            );
+            let min = origin + size.min(DVec3::ZERO);
+            let max = origin + size.max(DVec3::ZERO) + DVec3::ONE;
+
+            if position.cmplt(min).any() || position.cmpgt(max).any() {
+                return false;
+            }
+        }
+
+        if !arguments.entity_type.iter().all(|entity_type| {
+            entity_type.matches(|entity_type| {
+                let key = format!("entity.{}", entity_type.replacen(':', ".", 1));
+                entity.kind.translation_key() == Some(key.as_str())
+            })
+        }) {
+            return false;
+        }
+
+        let name = match (entity.username, entity.custom_name) {
+            (Some(username), _) => Some(username.0.clone()),
+            (None, Some(CustomName(Some(name)))) => Some(plain_text(&name.to_legacy_lossy())),
+            _ => None,
+        };
+
+        if !arguments
+            .name
+            .iter()
+            .all(|n| n.matches(|n| name.as_deref() == Some(n.as_str())))
+        {
+            return false;
+        }
+
+        let tags = entity.tags.map(|tags| &tags.0);
+
+        if !arguments.tag.iter().all(|tag| {
+            tag.matches(|tag| match tags {
+                // `tag=` selects entities without tags
+                _ if tag.is_empty() => tags.map_or(true, BTreeSet::is_empty),
+                Some(tags) => tags.contains(tag),
+                None => false,
+            })
+        }) {
+            return false;
+        }
+
+        // game modes only apply to players, even when negated
+        if !arguments.gamemode.is_empty()
+            && !entity.game_mode.is_some_and(|game_mode| {
+                arguments
+                    .gamemode
+                    .iter()
+                    .all(|g| g.matches(|g| g == game_mode))
+            })
+        {
+            return false;
+        }
+
+        if arguments.team.is_empty() && arguments.scores.is_none() {
+            return true;
+        }
+
+        let holder = entity.score_holder();
+
+        if !arguments.team.is_empty() {
+            let team = self
+                .teams
+                .iter()
+                .find(|(_, members, layer)| layer.0 == entity.layer.0 && members.contains(&holder))
+                .map(|(team, ..)| team.name());
+
+            if !arguments.team.iter().all(|t| {
+                t.matches(|t| match team {
+                    // `team=` selects entities that aren't on a team
+                    _ if t.is_empty() => team.is_none(),
+                    Some(team) => team == t.as_str(),
+                    None => false,
+                })
+            }) {
+                return false;
+            }
+        }
+
+        if let Some(scores) = &arguments.scores {
+            for (objective, range) in scores {
+                let score = self
+                    .objectives
+                    .iter()
+                    .find(|(o, _, layer)| {
+                        layer.0 == entity.layer.0 && o.name() == objective.as_str()
+                    })
+                    .and_then(|(_, scores, _)| scores.get(&holder));
+
+                if !score.is_some_and(|score| range.contains(score)) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// Removes the `§` formatting codes from legacy text.
+pub(crate) fn plain_text(legacy: &str) -> String {
+    let mut plain = String::with_capacity(legacy.len());
+    let mut chars = legacy.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            plain.push(c);
+        }
+    }
+
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(plain_text("§a§lHello§r world"), "Hello world");
+    }
+}
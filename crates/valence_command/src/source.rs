@@ -0,0 +1,238 @@
+//! # Command sources
+//!
+//! Any entity can execute commands by sending a
+//! [`CommandExecutionEvent`](crate::CommandExecutionEvent) with itself as the
+//! executor. The [`CommandSource`] component tells handlers what kind of
+//! executor an entity is. It is added to clients automatically and should be
+//! added to other executors such as command blocks.
+//!
+//! Handlers reply to an executor by sending a [`CommandReplyEvent`], which is
+//! delivered depending on the executor:
+//! * Clients receive the reply as a chat message.
+//! * Executors with a [`ReplyChannel`] receive the reply through the channel.
+//!   This is how sources running on other threads get their replies.
+//! * The console prints the reply to the log.
+//!
+//! The [`CommandExecutor`] query gives handlers the permission scopes and
+//! position of an executor regardless of what kind it is.
+//!
+//! ```
+//! use bevy_ecs::prelude::*;
+//! use valence_command::source::{CommandExecutor, CommandReplyEvent};
+//! use valence_command::CommandScopeRegistry;
+//!
+//! // called from a command handler
+//! fn where_am_i(
+//!     executor: Entity,
+//!     executors: &Query<CommandExecutor>,
+//!     scope_registry: &CommandScopeRegistry,
+//!     replies: &mut EventWriter<CommandReplyEvent>,
+//! ) {
+//!     let Ok(executor) = executors.get(executor) else {
+//!         return;
+//!     };
+//!
+//!     let message = match executor.position {
+//!         Some(pos) if executor.has_scope("valence.command.whereami", scope_registry) => {
+//!             format!("You are at {}", pos.0)
+//!         }
+//!         Some(_) => "You can't use this command".to_owned(),
+//!         None => "You are nowhere".to_owned(),
+//!     };
+//!
+//!     replies.send(CommandReplyEvent::new(executor.entity, message));
+//! }
+//! ```
+//!
+//! The [`ConsoleCommandPlugin`] adds the server console as a command source.
+
+use std::collections::BTreeSet;
+use std::io::BufRead;
+use std::thread;
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use flume::{Receiver, Sender};
+use tracing::{debug, info, warn};
+use valence_server::client::Client;
+use valence_server::entity::{EntityLayerId, Position};
+use valence_server::message::SendMessage;
+use valence_server::EventLoopPreUpdate;
+use valence_text::{IntoText, Text};
+
+use crate::scopes::CommandScopes;
+use crate::selector::plain_text;
+use crate::{CommandExecutionEvent, CommandScopeRegistry, CommandSystemSet};
+
+/// The kind of entity that executes a command.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum CommandSource {
+    /// A client. Added to clients by the
+    /// [`CommandPlugin`](crate::manager::CommandPlugin).
+    Player,
+    /// The server console. See [`ConsoleCommandPlugin`].
+    Console,
+    /// A command block, or anything else in the world that runs commands.
+    CommandBlock,
+    /// A remote console connection.
+    Rcon,
+}
+
+/// Sends the replies to an executor through a channel. Use this for command
+/// sources that live outside of the ECS, such as a network connection
+/// handled on another thread.
+#[derive(Component, Clone, Debug)]
+pub struct ReplyChannel(pub Sender<Text>);
+
+/// A message sent back to the executor of a command.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct CommandReplyEvent {
+    pub executor: Entity,
+    pub message: Text,
+}
+
+impl CommandReplyEvent {
+    pub fn new<'a, M: IntoText<'a>>(executor: Entity, message: M) -> Self {
+        Self {
+            executor,
+            message: message.into_text(),
+        }
+    }
+}
+
+/// The components of an executor that are useful to command handlers. All of
+/// them are optional, since any entity can execute commands.
+#[derive(QueryData)]
+pub struct CommandExecutor {
+    pub entity: Entity,
+    pub source: Option<&'static CommandSource>,
+    pub scopes: Option<&'static CommandScopes>,
+    pub position: Option<&'static Position>,
+    pub layer: Option<&'static EntityLayerId>,
+}
+
+impl CommandExecutorItem<'_> {
+    /// Checks if the executor has a scope that grants `scope`.
+    pub fn has_scope(&self, scope: &str, registry: &CommandScopeRegistry) -> bool {
+        self.scopes.is_some_and(|scopes| {
+            registry.any_grants(&scopes.iter().map(String::as_str).collect(), scope)
+        })
+    }
+}
+
+pub(crate) fn send_replies(
+    mut replies: EventReader<CommandReplyEvent>,
+    mut executors: Query<(
+        Option<&mut Client>,
+        Option<&ReplyChannel>,
+        Option<&CommandSource>,
+    )>,
+) {
+    for reply in replies.read() {
+        match executors.get_mut(reply.executor) {
+            Ok((Some(mut client), _, _)) => client.send_chat_message(reply.message.clone()),
+            Ok((None, Some(channel), _)) => {
+                // the receiver is gone when the source disconnected
+                let _ = channel.0.send(reply.message.clone());
+            }
+            Ok((None, None, Some(CommandSource::Console))) => {
+                info!("{}", to_plain_text(&reply.message));
+            }
+            _ => debug!(
+                "dropped reply to {:?}: {}",
+                reply.executor,
+                to_plain_text(&reply.message)
+            ),
+        }
+    }
+}
+
+/// Converts a reply to plain text for sources that can't show formatting,
+/// such as the console.
+pub fn to_plain_text(text: &Text) -> String {
+    plain_text(&text.to_legacy_lossy())
+}
+
+/// Reads commands from the standard input on a background thread and executes
+/// them as the console. Replies to the console are printed to the log.
+///
+/// The console has the root scope, which grants every other scope.
+pub struct ConsoleCommandPlugin;
+
+impl Plugin for ConsoleCommandPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = flume::unbounded();
+
+        let spawned = thread::Builder::new()
+            .name("console commands".into())
+            .spawn(move || read_stdin(&sender));
+
+        if let Err(e) = spawned {
+            warn!("failed to spawn the console command thread: {e}");
+        }
+
+        let console = app
+            .world_mut()
+            .spawn((
+                CommandSource::Console,
+                CommandScopes(BTreeSet::from(["root".to_owned()])),
+            ))
+            .id();
+
+        app.insert_resource(Console {
+            entity: console,
+            commands: receiver,
+        })
+        .add_systems(
+            EventLoopPreUpdate,
+            read_console_commands.before(CommandSystemSet),
+        );
+    }
+}
+
+/// The console command source added by [`ConsoleCommandPlugin`].
+#[derive(Resource, Debug)]
+pub struct Console {
+    entity: Entity,
+    commands: Receiver<String>,
+}
+
+impl Console {
+    /// The entity that executes commands entered in the console.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+}
+
+fn read_stdin(sender: &Sender<String>) {
+    for line in std::io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("failed to read console input: {e}");
+                return;
+            }
+        };
+
+        if sender.send(line).is_err() {
+            // the app was dropped
+            return;
+        }
+    }
+}
+
+fn read_console_commands(console: Res<Console>, mut events: EventWriter<CommandExecutionEvent>) {
+    for line in console.commands.try_iter() {
+        // the slash is optional in the console
+        let command = line.trim();
+        let command = command.strip_prefix('/').unwrap_or(command);
+
+        if !command.is_empty() {
+            events.send(CommandExecutionEvent {
+                command: command.to_owned(),
+                executor: console.entity,
+            });
+        }
+    }
+}
@@ -0,0 +1,558 @@
+//! # Command suggestions
+//!
+//! Argument nodes can offer suggestions to the client while they are being
+//! typed. When a node has a [`SuggestionProvider`] the client asks the server
+//! for suggestions, and the provider is used to answer with a list of
+//! [`CommandSuggestion`]s.
+//!
+//! Providers can be attached to every node using a parser by implementing
+//! [`CommandArg::suggestions`](crate::parsers::CommandArg::suggestions), or to
+//! a single node with
+//! [`CommandGraphBuilder::with_suggestions`](crate::graph::CommandGraphBuilder::with_suggestions).
+//! A provider set on the node takes precedence over the one from the parser.
+//!
+//! There are two kinds of providers:
+//! * [`SuggestionProvider::Static`] - a plain function that is given the
+//!   argument typed so far. Useful for a fixed set of values, like the variants
+//!   of an enum.
+//! * [`SuggestionProvider::Dynamic`] - the name of a system registered with
+//!   [`AddSuggestionProvider::add_suggestion_provider`]. The system is given a
+//!   [`SuggestionContext`] and can query the world, which is needed for values
+//!   that change at runtime like player names or IDs.
+//!
+//! Suggestions that don't start with the typed argument (ignoring case) are
+//! filtered out, so providers are free to return every possible value. Literal
+//! nodes are always suggested by the server when the client asks for
+//! suggestions, so they don't need a provider.
+//!
+//! A provider for player names is registered by the
+//! [`CommandPlugin`](crate::manager::CommandPlugin) under [`PLAYER_NAMES`].
+//!
+//! # Example
+//! ```
+//! use bevy_app::App;
+//! use bevy_ecs::prelude::*;
+//! use valence_command::graph::CommandGraphBuilder;
+//! use valence_command::suggestions::{
+//!     AddSuggestionProvider, CommandSuggestion, SuggestionContext, SuggestionProvider,
+//! };
+//!
+//! #[derive(Component)]
+//! struct Warp(String);
+//!
+//! fn warp_names(In(_ctx): In<SuggestionContext>, warps: Query<&Warp>) -> Vec<CommandSuggestion> {
+//!     warps
+//!         .iter()
+//!         .map(|warp| CommandSuggestion::new(&warp.0))
+//!         .collect()
+//! }
+//!
+//! fn colors(_input: &str) -> Vec<CommandSuggestion> {
+//!     ["red", "green", "blue"]
+//!         .into_iter()
+//!         .map(CommandSuggestion::new)
+//!         .collect()
+//! }
+//!
+//! let mut app = App::new();
+//! app.add_suggestion_provider("my_plugin:warps", warp_names);
+//!
+//! // in `Command::assemble_graph`
+//! fn assemble_graph(graph: &mut CommandGraphBuilder<()>) {
+//!     graph
+//!         .root()
+//!         .literal("warp")
+//!         .argument("name")
+//!         .with_parser::<String>()
+//!         .with_suggestions(SuggestionProvider::Dynamic("my_plugin:warps"));
+//!
+//!     graph
+//!         .root()
+//!         .literal("paint")
+//!         .argument("color")
+//!         .with_parser::<String>()
+//!         .with_suggestions(SuggestionProvider::Static(colors));
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use bevy_app::App;
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::{SystemId, SystemState};
+use petgraph::prelude::{EdgeRef, NodeIndex};
+use petgraph::Direction;
+use tracing::warn;
+use valence_server::client::{Client, Username};
+use valence_server::event_loop::PacketEvent;
+use valence_server::protocol::packets::play::command_suggestions_s2c::CommandSuggestionsMatch;
+use valence_server::protocol::packets::play::command_tree_s2c::NodeData;
+use valence_server::protocol::packets::play::{
+    CommandSuggestionsS2c, RequestCommandCompletionsC2s,
+};
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_text::{IntoText, Text};
+
+use crate::graph::CommandEdgeType;
+use crate::parsers::ParseInput;
+use crate::scopes::CommandScopes;
+use crate::{CommandRegistry, CommandScopeRegistry};
+
+/// The name of the built-in provider suggesting the usernames of all
+/// connected clients.
+pub const PLAYER_NAMES: &str = "valence:player_names";
+
+/// A single suggestion sent to the client.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CommandSuggestion {
+    /// The text that replaces the argument when the suggestion is picked.
+    pub text: String,
+    /// Shown when hovering over the suggestion.
+    pub tooltip: Option<Text>,
+}
+
+impl CommandSuggestion {
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        Self {
+            text: text.into(),
+            tooltip: None,
+        }
+    }
+
+    pub fn with_tooltip<'a, T: IntoText<'a>>(mut self, tooltip: T) -> Self {
+        self.tooltip = Some(tooltip.into_text());
+        self
+    }
+}
+
+/// Produces the suggestions for an argument node.
+#[derive(Clone, Copy, Debug)]
+pub enum SuggestionProvider {
+    /// A function called with the argument typed so far.
+    Static(fn(&str) -> Vec<CommandSuggestion>),
+    /// The name of a system registered with
+    /// [`AddSuggestionProvider::add_suggestion_provider`].
+    Dynamic(&'static str),
+}
+
+/// The input of a dynamic suggestion provider.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SuggestionContext {
+    /// The entity asking for suggestions (usually a client).
+    pub executor: Entity,
+    /// The argument node being completed.
+    pub node: NodeIndex,
+    /// The argument typed so far. This can be empty.
+    pub input: String,
+}
+
+type SuggestionSystemId = SystemId<SuggestionContext, Vec<CommandSuggestion>>;
+
+/// The registered dynamic suggestion providers by name.
+#[derive(Resource, Default)]
+pub(crate) struct SuggestionProviders(HashMap<&'static str, SuggestionSystemId>);
+
+pub trait AddSuggestionProvider {
+    /// Registers a system as a dynamic suggestion provider that can be used
+    /// with [`SuggestionProvider::Dynamic`]. Registering a provider under an
+    /// existing name replaces it.
+    fn add_suggestion_provider<M, S>(&mut self, name: &'static str, system: S) -> &mut Self
+    where
+        S: IntoSystem<SuggestionContext, Vec<CommandSuggestion>, M> + 'static;
+}
+
+impl AddSuggestionProvider for App {
+    fn add_suggestion_provider<M, S>(&mut self, name: &'static str, system: S) -> &mut Self
+    where
+        S: IntoSystem<SuggestionContext, Vec<CommandSuggestion>, M> + 'static,
+    {
+        let id = self.world_mut().register_system(system);
+
+        self.world_mut()
+            .get_resource_or_insert_with(SuggestionProviders::default)
+            .0
+            .insert(name, id);
+
+        self
+    }
+}
+
+pub(crate) fn suggest_player_names(
+    In(_): In<SuggestionContext>,
+    usernames: Query<&Username>,
+) -> Vec<CommandSuggestion> {
+    usernames
+        .iter()
+        .map(|username| CommandSuggestion::new(&username.0))
+        .collect()
+}
+
+/// A completion request waiting for its dynamic providers to run. All offsets
+/// are byte offsets into `text`.
+struct CompletionRequest {
+    client: Entity,
+    transaction_id: VarInt,
+    text: String,
+    suggestions: Vec<(usize, Vec<CommandSuggestion>)>,
+    dynamic: Vec<(usize, SuggestionSystemId, SuggestionContext)>,
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn answer_completion_requests(
+    world: &mut World,
+    state: &mut SystemState<(
+        EventReader<PacketEvent>,
+        Res<CommandRegistry>,
+        Res<CommandScopeRegistry>,
+        Res<SuggestionProviders>,
+        Query<&CommandScopes>,
+    )>,
+) {
+    let mut requests = vec![];
+
+    {
+        let (mut packets, registry, scope_registry, providers, scopes) = state.get_mut(world);
+
+        for packet in packets.read() {
+            let Some(pkt) = packet.decode::<RequestCommandCompletionsC2s>() else {
+                continue;
+            };
+
+            let text = pkt.text.0;
+            let offset = usize::from(text.starts_with('/'));
+
+            let client_scopes: Vec<&str> = scopes
+                .get(packet.client)
+                .map(|scopes| scopes.0.iter().map(String::as_str).collect())
+                .unwrap_or_default();
+
+            let can_use = |node: NodeIndex| {
+                let node_scopes = &registry.graph.graph[node].scopes;
+                // if empty, we assume the node is global
+                node_scopes.is_empty()
+                    || node_scopes
+                        .iter()
+                        .any(|scope| scope_registry.any_grants(&client_scopes, scope))
+            };
+
+            let mut request = CompletionRequest {
+                client: packet.client,
+                transaction_id: pkt.transaction_id,
+                text: text.to_owned(),
+                suggestions: vec![],
+                dynamic: vec![],
+            };
+
+            for (node, start) in find_completions(&registry, &text[offset..], &can_use) {
+                let start = start + offset;
+                let input = &text[start..];
+
+                match &registry.graph.graph[node].data {
+                    NodeData::Literal { name } => request
+                        .suggestions
+                        .push((start, vec![CommandSuggestion::new(name)])),
+                    NodeData::Argument { .. } => match registry.suggestions.get(&node) {
+                        Some(SuggestionProvider::Static(provider)) => {
+                            request.suggestions.push((start, provider(input)));
+                        }
+                        Some(SuggestionProvider::Dynamic(name)) => match providers.0.get(name) {
+                            Some(id) => request.dynamic.push((
+                                start,
+                                *id,
+                                SuggestionContext {
+                                    executor: packet.client,
+                                    node,
+                                    input: input.to_owned(),
+                                },
+                            )),
+                            None => warn!("no suggestion provider registered under \"{name}\""),
+                        },
+                        None => {}
+                    },
+                    NodeData::Root => {}
+                }
+            }
+
+            requests.push(request);
+        }
+    }
+
+    for mut request in requests {
+        for (start, id, context) in request.dynamic {
+            match world.run_system_with_input(id, context) {
+                Ok(suggestions) => request.suggestions.push((start, suggestions)),
+                Err(e) => warn!("failed to run suggestion provider: {e}"),
+            }
+        }
+
+        let (start, matches) = merge_suggestions(&request.text, request.suggestions);
+
+        let Some(mut client) = world.get_mut::<Client>(request.client) else {
+            continue;
+        };
+
+        client.write_packet(&CommandSuggestionsS2c {
+            id: request.transaction_id,
+            // the client counts in UTF-16 code units
+            start: VarInt(request.text[..start].encode_utf16().count() as i32),
+            length: VarInt(request.text[start..].encode_utf16().count() as i32),
+            matches: matches
+                .iter()
+                .map(|suggestion| CommandSuggestionsMatch {
+                    suggested_match: &suggestion.text,
+                    tooltip: suggestion.tooltip.as_ref().map(Cow::Borrowed),
+                })
+                .collect(),
+        });
+    }
+}
+
+/// Finds the nodes that could complete the end of `command`, along with the
+/// byte offset where the text for each node starts.
+fn find_completions(
+    registry: &CommandRegistry,
+    command: &str,
+    can_use: &impl Fn(NodeIndex) -> bool,
+) -> Vec<(NodeIndex, usize)> {
+    let mut completions = vec![];
+    let mut visited = HashSet::new();
+
+    collect_completions(
+        registry,
+        registry.graph.root,
+        command,
+        0,
+        can_use,
+        &mut visited,
+        &mut completions,
+    );
+
+    completions
+}
+
+/// recursively walk the children of `node` with the command from `pos`
+/// onwards.
+fn collect_completions(
+    registry: &CommandRegistry,
+    node: NodeIndex,
+    command: &str,
+    pos: usize,
+    can_use: &impl Fn(NodeIndex) -> bool,
+    visited: &mut HashSet<(NodeIndex, usize)>,
+    completions: &mut Vec<(NodeIndex, usize)>,
+) {
+    let graph = &registry.graph.graph;
+
+    for edge in graph.edges_directed(node, Direction::Outgoing) {
+        let child = edge.target();
+
+        if !can_use(child) {
+            continue;
+        }
+
+        // redirects continue from the target without matching it
+        if matches!(edge.weight(), CommandEdgeType::Redirect) {
+            if visited.insert((child, pos)) {
+                collect_completions(registry, child, command, pos, can_use, visited, completions);
+            }
+            continue;
+        }
+
+        let mut input = ParseInput::new(&command[pos..]);
+        input.skip_whitespace();
+
+        let start = command.len() - input.len();
+        let rest = input.clone().into_inner();
+
+        let matched = match &graph[child].data {
+            NodeData::Root => continue,
+            NodeData::Literal { name } => input.match_next(name),
+            NodeData::Argument { .. } => registry
+                .parsers
+                .get(&child)
+                .is_some_and(|parser| parser(&mut input).is_ok()),
+        };
+
+        if matched && input.peek().is_some_and(char::is_whitespace) {
+            let next = command.len() - input.len();
+
+            if visited.insert((child, next)) {
+                collect_completions(
+                    registry,
+                    child,
+                    command,
+                    next,
+                    can_use,
+                    visited,
+                    completions,
+                );
+            }
+        } else if matches!(graph[child].data, NodeData::Argument { .. })
+            || !rest.contains(char::is_whitespace)
+        {
+            // literals are a single word, but arguments can span several
+            completions.push((child, start));
+        }
+    }
+}
+
+/// Merges the suggestions for nodes starting at different offsets into one
+/// range starting at the earliest offset. Suggestions are filtered by what has
+/// been typed, sorted and deduplicated.
+fn merge_suggestions(
+    text: &str,
+    suggestions: Vec<(usize, Vec<CommandSuggestion>)>,
+) -> (usize, Vec<CommandSuggestion>) {
+    let start = suggestions
+        .iter()
+        .map(|(start, _)| *start)
+        .min()
+        .unwrap_or(text.len());
+
+    let typed = text[start..].to_lowercase();
+
+    let mut matches: Vec<CommandSuggestion> = suggestions
+        .into_iter()
+        .flat_map(|(node_start, suggestions)| {
+            let prefix = &text[start..node_start];
+
+            suggestions.into_iter().map(move |mut suggestion| {
+                suggestion.text.insert_str(0, prefix);
+                suggestion
+            })
+        })
+        .filter(|suggestion| suggestion.text.to_lowercase().starts_with(&typed))
+        .collect();
+
+    matches.sort_by(|a, b| {
+        a.text
+            .to_lowercase()
+            .cmp(&b.text.to_lowercase())
+            .then_with(|| a.text.cmp(&b.text))
+    });
+    matches.dedup_by(|a, b| a.text == b.text);
+
+    (start, matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::CommandGraphBuilder;
+
+    fn test_registry() -> CommandRegistry {
+        let mut registry = CommandRegistry::default();
+        let mut executables = HashMap::new();
+        let mut parsers = HashMap::new();
+        let mut modifiers = HashMap::new();
+        let mut builder = CommandGraphBuilder::<()>::new(
+            &mut registry,
+            &mut executables,
+            &mut parsers,
+            &mut modifiers,
+        );
+
+        let teleport = builder.root().literal("teleport").id();
+        builder
+            .argument("x")
+            .with_parser::<i32>()
+            .with_suggestions(SuggestionProvider::Static(|_| {
+                vec![CommandSuggestion::new("10"), CommandSuggestion::new("20")]
+            }));
+        builder.root().literal("tp").redirect_to(teleport);
+        builder
+            .root()
+            .literal("test")
+            .with_scopes(vec!["test:admin"]);
+
+        registry.parsers.extend(parsers);
+        registry
+    }
+
+    fn names(registry: &CommandRegistry, completions: &[(NodeIndex, usize)]) -> Vec<String> {
+        completions
+            .iter()
+            .map(|(node, _)| match &registry.graph.graph[*node].data {
+                NodeData::Literal { name } | NodeData::Argument { name, .. } => name.clone(),
+                NodeData::Root => String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_completes_literals() {
+        let registry = test_registry();
+
+        let completions = find_completions(&registry, "te", &|_| true);
+        let mut found = names(&registry, &completions);
+        found.sort();
+        assert_eq!(found, ["teleport", "test", "tp"]);
+        assert!(completions.iter().all(|(_, start)| *start == 0));
+
+        let completions = find_completions(&registry, "te", &|node| {
+            registry.graph.graph[node].scopes.is_empty()
+        });
+        assert!(!names(&registry, &completions).contains(&"test".to_owned()));
+    }
+
+    #[test]
+    fn test_completes_arguments_through_redirects() {
+        let registry = test_registry();
+
+        let completions = find_completions(&registry, "tp 1", &|_| true);
+        assert_eq!(names(&registry, &completions), ["x"]);
+        assert_eq!(completions[0].1, 3);
+
+        assert!(find_completions(&registry, "tp 1 ", &|_| true).is_empty());
+        assert!(find_completions(&registry, "unknown ", &|_| true).is_empty());
+    }
+
+    #[test]
+    fn test_merge_suggestions() {
+        let (start, matches) = merge_suggestions(
+            "/give Ste",
+            vec![(
+                6,
+                vec![
+                    CommandSuggestion::new("steve"),
+                    CommandSuggestion::new("Steve"),
+                    CommandSuggestion::new("alex"),
+                    CommandSuggestion::new("steve"),
+                ],
+            )],
+        );
+
+        assert_eq!(start, 6);
+        assert_eq!(
+            matches,
+            [
+                CommandSuggestion::new("Steve"),
+                CommandSuggestion::new("steve")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_suggestions_with_different_starts() {
+        let (start, matches) = merge_suggestions(
+            "/a b ",
+            vec![
+                (
+                    3,
+                    vec![CommandSuggestion::new("b c"), CommandSuggestion::new("d")],
+                ),
+                (5, vec![CommandSuggestion::new("x").with_tooltip("tooltip")]),
+            ],
+        );
+
+        assert_eq!(start, 3);
+        assert_eq!(
+            matches,
+            [
+                CommandSuggestion::new("b c"),
+                CommandSuggestion::new("b x").with_tooltip("tooltip")
+            ]
+        );
+    }
+}
@@ -0,0 +1,514 @@
+#![doc = include_str!("../README.md")]
+
+use std::collections::HashMap;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use valence_combat::{CombatSet, DamageEvent};
+use valence_server::entity::active_status_effects::{ActiveStatusEffect, ActiveStatusEffects};
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::entity::Flags;
+use valence_server::entity::living::{Absorption, Health, LivingEntity};
+use valence_server::entity::player::{Food, Saturation};
+use valence_server::entity::InitEntitiesSet;
+use valence_server::ident;
+use valence_server::ident::Ident;
+use valence_server::protocol::status_effects::{AttributeModifier, StatusEffect};
+use valence_server::status_effect::{StatusEffectAdded, StatusEffectRemoved};
+
+pub struct EffectPlugin;
+
+impl Plugin for EffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectBehaviors>()
+            .add_event::<EffectTickEvent>()
+            .add_systems(PreUpdate, init_effect_flags)
+            .add_systems(
+                PostUpdate,
+                (
+                    start_effects,
+                    stop_effects,
+                    tick_effects,
+                    update_effect_flags,
+                )
+                    .chain()
+                    .after(InitEntitiesSet)
+                    .before(CombatSet::CollectDamage),
+            );
+    }
+}
+
+/// What a status effect does while it is active.
+#[derive(Clone, Debug)]
+pub struct EffectBehavior {
+    /// Attribute modifiers added while the effect is active. The amount of
+    /// each modifier is multiplied by the amplifier plus one.
+    pub modifiers: Vec<AttributeModifier>,
+    /// The number of ticks between the applications of the `action` for an
+    /// amplifier. `None` if the action is only applied when the effect
+    /// starts, as for instant effects.
+    pub interval: Option<fn(u8) -> u32>,
+    /// What happens when the effect is applied.
+    pub action: EffectAction,
+}
+
+impl EffectBehavior {
+    /// A behavior that only adds the vanilla attribute modifiers of `effect`.
+    pub fn new(effect: StatusEffect) -> Self {
+        Self {
+            modifiers: effect.attribute_modifiers(),
+            interval: None,
+            action: EffectAction::None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_interval(mut self, interval: fn(u8) -> u32) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    #[must_use]
+    pub fn with_action(mut self, action: EffectAction) -> Self {
+        self.action = action;
+        self
+    }
+}
+
+/// What happens when a status effect is applied. The functions get the
+/// amplifier of the effect.
+#[derive(Copy, Clone, Debug)]
+pub enum EffectAction {
+    /// Nothing happens, apart from the [`EffectTickEvent`] that is sent for
+    /// every application. Custom effects can react to that event.
+    None,
+    /// Heals the entity.
+    Heal(fn(u8) -> f32),
+    /// Damages the entity with a [`DamageEvent`].
+    Damage {
+        damage_type: Ident<&'static str>,
+        amount: fn(u8) -> f32,
+        /// The damage is only dealt when the health of the entity is above
+        /// this.
+        min_health: f32,
+    },
+    /// Adds exhaustion to players. Exhaustion is tracked by the `HungerPlugin`
+    /// of `valence_hunger`, which applies this action when it reads the
+    /// [`EffectTickEvent`].
+    Exhaust(fn(u8) -> f32),
+    /// Feeds players the given nutrition and saturation modifier, like food.
+    Feed(fn(u8) -> (i32, f32)),
+    /// Raises the absorption of the entity to at least this amount.
+    Absorb(fn(u8) -> f32),
+}
+
+/// The behaviors of the status effects, which can be changed to alter vanilla
+/// effects or give effects without vanilla behavior a custom one.
+#[derive(Resource, Clone, Debug)]
+pub struct EffectBehaviors(HashMap<StatusEffect, EffectBehavior>);
+
+impl EffectBehaviors {
+    /// Returns the behavior of `effect`, if any.
+    pub fn get(&self, effect: StatusEffect) -> Option<&EffectBehavior> {
+        self.0.get(&effect)
+    }
+
+    /// Sets the behavior of `effect`, returning the previous behavior.
+    pub fn insert(
+        &mut self,
+        effect: StatusEffect,
+        behavior: EffectBehavior,
+    ) -> Option<EffectBehavior> {
+        self.0.insert(effect, behavior)
+    }
+
+    /// Removes the behavior of `effect`, so that it does nothing.
+    pub fn remove(&mut self, effect: StatusEffect) -> Option<EffectBehavior> {
+        self.0.remove(&effect)
+    }
+}
+
+impl Default for EffectBehaviors {
+    /// The vanilla behaviors of the effects that can be simulated on the
+    /// server.
+    fn default() -> Self {
+        let mut behaviors = HashMap::new();
+
+        // Effects that only modify attributes.
+        for effect in StatusEffect::ALL {
+            if !effect.attribute_modifiers().is_empty() {
+                behaviors.insert(effect, EffectBehavior::new(effect));
+            }
+        }
+
+        behaviors.insert(
+            StatusEffect::Regeneration,
+            EffectBehavior::new(StatusEffect::Regeneration)
+                .with_interval(|amplifier| 50 >> amplifier.min(31))
+                .with_action(EffectAction::Heal(|_| 1.0)),
+        );
+
+        behaviors.insert(
+            StatusEffect::Poison,
+            EffectBehavior::new(StatusEffect::Poison)
+                .with_interval(|amplifier| 25 >> amplifier.min(31))
+                .with_action(EffectAction::Damage {
+                    damage_type: ident!("magic"),
+                    amount: |_| 1.0,
+                    min_health: 1.0,
+                }),
+        );
+
+        behaviors.insert(
+            StatusEffect::Wither,
+            EffectBehavior::new(StatusEffect::Wither)
+                .with_interval(|amplifier| 40 >> amplifier.min(31))
+                .with_action(EffectAction::Damage {
+                    damage_type: ident!("wither"),
+                    amount: |_| 1.0,
+                    min_health: 0.0,
+                }),
+        );
+
+        behaviors.insert(
+            StatusEffect::Hunger,
+            EffectBehavior::new(StatusEffect::Hunger)
+                .with_interval(|_| 1)
+                .with_action(EffectAction::Exhaust(|amplifier| {
+                    0.005 * (f32::from(amplifier) + 1.0)
+                })),
+        );
+
+        behaviors.insert(
+            StatusEffect::InstantHealth,
+            EffectBehavior::new(StatusEffect::InstantHealth).with_action(EffectAction::Heal(
+                |amplifier| (4 << amplifier.min(28)) as f32,
+            )),
+        );
+
+        behaviors.insert(
+            StatusEffect::InstantDamage,
+            EffectBehavior::new(StatusEffect::InstantDamage).with_action(EffectAction::Damage {
+                damage_type: ident!("magic"),
+                amount: |amplifier| (6 << amplifier.min(28)) as f32,
+                min_health: 0.0,
+            }),
+        );
+
+        behaviors.insert(
+            StatusEffect::Saturation,
+            EffectBehavior::new(StatusEffect::Saturation).with_action(EffectAction::Feed(
+                |amplifier| (i32::from(amplifier) + 1, 1.0),
+            )),
+        );
+
+        behaviors.insert(
+            StatusEffect::Absorption,
+            EffectBehavior::new(StatusEffect::Absorption).with_action(EffectAction::Absorb(
+                |amplifier| 4.0 * (f32::from(amplifier) + 1.0),
+            )),
+        );
+
+        Self(behaviors)
+    }
+}
+
+/// Sent every time the action of a status effect is applied to an entity,
+/// including effects with [`EffectAction::None`].
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct EffectTickEvent {
+    pub entity: Entity,
+    pub effect: StatusEffect,
+    pub amplifier: u8,
+}
+
+/// Status effects of a living entity that other systems may want to check,
+/// kept up to date by the [`EffectPlugin`].
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct EffectFlags {
+    invisible: bool,
+    glowing: bool,
+    levitating: bool,
+    slow_falling: bool,
+}
+
+impl EffectFlags {
+    pub fn invisible(&self) -> bool {
+        self.invisible
+    }
+
+    pub fn glowing(&self) -> bool {
+        self.glowing
+    }
+
+    pub fn levitating(&self) -> bool {
+        self.levitating
+    }
+
+    pub fn slow_falling(&self) -> bool {
+        self.slow_falling
+    }
+}
+
+fn init_effect_flags(
+    entities: Query<Entity, (Added<LivingEntity>, Without<EffectFlags>)>,
+    mut commands: Commands,
+) {
+    for entity in &entities {
+        commands.entity(entity).insert(EffectFlags::default());
+    }
+}
+
+fn update_effect_flags(
+    mut entities: Query<(&ActiveStatusEffects, &mut EffectFlags), Changed<ActiveStatusEffects>>,
+) {
+    for (effects, mut flags) in &mut entities {
+        flags.set_if_neq(EffectFlags {
+            invisible: effects.has_effect(StatusEffect::Invisibility),
+            glowing: effects.has_effect(StatusEffect::Glowing),
+            levitating: effects.has_effect(StatusEffect::Levitation),
+            slow_falling: effects.has_effect(StatusEffect::SlowFalling),
+        });
+    }
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct EffectQuery {
+    effects: &'static ActiveStatusEffects,
+    health: Option<&'static mut Health>,
+    absorption: Option<&'static mut Absorption>,
+    attributes: Option<&'static mut EntityAttributes>,
+    flags: Option<&'static mut Flags>,
+    food: Option<&'static mut Food>,
+    saturation: Option<&'static mut Saturation>,
+}
+
+/// Adds the modifiers of started effects and applies their action once if
+/// they have no interval.
+fn start_effects(
+    mut events: EventReader<StatusEffectAdded>,
+    mut entities: Query<EffectQuery>,
+    behaviors: Res<EffectBehaviors>,
+    mut tick_events: EventWriter<EffectTickEvent>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for event in events.read() {
+        let Ok(mut entity) = entities.get_mut(event.entity) else {
+            continue;
+        };
+
+        let Some(amplifier) = entity
+            .effects
+            .get_current_effect(event.status_effect)
+            .map(ActiveStatusEffect::amplifier)
+        else {
+            continue;
+        };
+
+        set_flags(&mut entity, event.status_effect, true);
+
+        let Some(behavior) = behaviors.get(event.status_effect) else {
+            continue;
+        };
+
+        if let Some(attributes) = &mut entity.attributes {
+            for modifier in &behavior.modifiers {
+                attributes.set_modifier(
+                    modifier.attribute,
+                    modifier.uuid,
+                    modifier.base_value * (f64::from(amplifier) + 1.0),
+                    modifier.operation,
+                );
+            }
+        }
+
+        clamp_health(&mut entity);
+
+        if behavior.interval.is_none() {
+            apply_action(
+                event.entity,
+                &mut entity,
+                behavior.action,
+                amplifier,
+                &mut damage_events,
+            );
+
+            tick_events.send(EffectTickEvent {
+                entity: event.entity,
+                effect: event.status_effect,
+                amplifier,
+            });
+        }
+    }
+}
+
+/// Removes the modifiers of effects that ended.
+fn stop_effects(
+    mut events: EventReader<StatusEffectRemoved>,
+    mut entities: Query<EffectQuery>,
+    behaviors: Res<EffectBehaviors>,
+) {
+    for event in events.read() {
+        let Ok(mut entity) = entities.get_mut(event.entity) else {
+            continue;
+        };
+
+        let effect = event.status_effect.status_effect();
+
+        set_flags(&mut entity, effect, false);
+
+        if let (Some(behavior), Some(attributes)) = (behaviors.get(effect), &mut entity.attributes)
+        {
+            for modifier in &behavior.modifiers {
+                attributes.remove_modifier(modifier.attribute, modifier.uuid);
+            }
+        }
+
+        clamp_health(&mut entity);
+        clamp_absorption(&mut entity);
+    }
+}
+
+/// Applies the actions of effects with an interval.
+fn tick_effects(
+    mut entities: Query<(Entity, EffectQuery)>,
+    behaviors: Res<EffectBehaviors>,
+    mut tick_events: EventWriter<EffectTickEvent>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity_id, mut entity) in &mut entities {
+        if entity.effects.no_effects() {
+            continue;
+        }
+
+        let ticking = entity
+            .effects
+            .get_current_effects()
+            .into_iter()
+            .filter_map(|effect| {
+                let behavior = behaviors.get(effect.status_effect())?;
+                let interval = (behavior.interval?)(effect.amplifier());
+
+                (interval == 0 || effect.active_ticks() % interval as i32 == 0).then_some((
+                    effect.status_effect(),
+                    effect.amplifier(),
+                    behavior.action,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        for (effect, amplifier, action) in ticking {
+            apply_action(
+                entity_id,
+                &mut entity,
+                action,
+                amplifier,
+                &mut damage_events,
+            );
+
+            tick_events.send(EffectTickEvent {
+                entity: entity_id,
+                effect,
+                amplifier,
+            });
+        }
+    }
+}
+
+fn apply_action(
+    entity_id: Entity,
+    entity: &mut EffectQueryItem,
+    action: EffectAction,
+    amplifier: u8,
+    damage_events: &mut EventWriter<DamageEvent>,
+) {
+    match action {
+        EffectAction::None => {}
+        EffectAction::Heal(amount) => {
+            let max_health = max_health(entity);
+
+            if let Some(health) = &mut entity.health {
+                if health.0 > 0.0 && health.0 < max_health {
+                    health.0 = (health.0 + amount(amplifier)).min(max_health);
+                }
+            }
+        }
+        EffectAction::Damage {
+            damage_type,
+            amount,
+            min_health,
+        } => {
+            if entity.health.as_ref().is_some_and(|h| h.0 > min_health) {
+                damage_events.send(DamageEvent::new(entity_id, damage_type, amount(amplifier)));
+            }
+        }
+        // Applied by the `HungerPlugin`, see the docs of the variant.
+        EffectAction::Exhaust(_) => {}
+        EffectAction::Feed(food) => {
+            let (nutrition, saturation_modifier) = food(amplifier);
+
+            if let (Some(food), Some(saturation)) = (&mut entity.food, &mut entity.saturation) {
+                food.0 = (food.0 + nutrition).clamp(0, 20);
+                saturation.0 = (saturation.0 + nutrition as f32 * saturation_modifier * 2.0)
+                    .clamp(0.0, food.0 as f32);
+            }
+        }
+        EffectAction::Absorb(amount) => {
+            if let Some(absorption) = &mut entity.absorption {
+                let amount = amount(amplifier);
+
+                if absorption.0 < amount {
+                    absorption.0 = amount;
+                }
+            }
+        }
+    }
+}
+
+/// Shows the invisibility and glowing effects through the entity flags.
+fn set_flags(entity: &mut EffectQueryItem, effect: StatusEffect, active: bool) {
+    if let Some(flags) = &mut entity.flags {
+        match effect {
+            StatusEffect::Invisibility => flags.set_invisible(active),
+            StatusEffect::Glowing => flags.set_glowing(active),
+            _ => {}
+        }
+    }
+}
+
+fn max_health(entity: &EffectQueryItem) -> f32 {
+    entity
+        .attributes
+        .as_ref()
+        .and_then(|a| a.get_compute_value(EntityAttribute::GenericMaxHealth))
+        .unwrap_or_else(|| EntityAttribute::GenericMaxHealth.default_value()) as f32
+}
+
+/// Keeps the health within the maximum after attribute changes, such as
+/// Health Boost ending.
+fn clamp_health(entity: &mut EffectQueryItem) {
+    let max_health = max_health(entity);
+
+    if let Some(health) = &mut entity.health {
+        if health.0 > max_health {
+            health.0 = max_health;
+        }
+    }
+}
+
+/// Removes the absorption left over from an ended Absorption effect.
+fn clamp_absorption(entity: &mut EffectQueryItem) {
+    let max_absorption = entity
+        .attributes
+        .as_ref()
+        .and_then(|a| a.get_compute_value(EntityAttribute::GenericMaxAbsorption))
+        .unwrap_or_default() as f32;
+
+    if let Some(absorption) = &mut entity.absorption {
+        if absorption.0 > max_absorption {
+            absorption.0 = max_absorption;
+        }
+    }
+}
@@ -461,11 +461,13 @@ fn build_entities() -> anyhow::Result<TokenStream> {
                                 bundle_fields.extend([quote! {
                                     pub player_food: super::player::Food,
                                     pub player_saturation: super::player::Saturation,
+                                    pub player_experience: super::player::Experience,
                                 }]);
 
                                 bundle_init_fields.extend([quote! {
                                     player_food: Default::default(),
                                     player_saturation: Default::default(),
+                                    player_experience: Default::default(),
                                 }]);
                             }
                             _ => {}
@@ -634,6 +636,19 @@ fn build_entities() -> anyhow::Result<TokenStream> {
                     #[doc = "Special untracked component for `PlayerEntity` entities."]
                     #[derive(bevy_ecs::component::Component, Copy, Clone, Default, Debug)]
                     pub struct Saturation(pub f32);
+
+                    #[doc = "Special untracked component for `PlayerEntity` entities."]
+                    #[doc = ""]
+                    #[doc = "The experience of a player, shown in the experience bar."]
+                    #[derive(bevy_ecs::component::Component, Copy, Clone, PartialEq, Default, Debug)]
+                    pub struct Experience {
+                        #[doc = "The experience level."]
+                        pub level: i32,
+                        #[doc = "The progress towards the next level, from `0.0` to `1.0`."]
+                        pub progress: f32,
+                        #[doc = "The total amount of experience points collected."]
+                        pub total: i32,
+                    }
                 }]);
             }
             _ => {}
@@ -22,9 +22,16 @@ struct Block {
     name: String,
     properties: Vec<Property>,
     default_state_id: u16,
+    /// How long the block takes to break. `-1.0` for unbreakable blocks.
+    hardness: f32,
     states: Vec<State>,
 }
 
+/// Strips the namespace from an item identifier.
+fn item_name(item_id: &str) -> &str {
+    item_id.strip_prefix("minecraft:").unwrap_or(item_id)
+}
+
 impl Block {
     pub(crate) fn min_state_id(&self) -> u16 {
         self.states.iter().map(|s| s.id).min().unwrap()
@@ -56,7 +63,9 @@ struct State {
     replaceable: bool,
     #[serde(default)]
     blocks_motion: bool,
+    tool_required: bool,
     collision_shapes: Vec<u16>,
+    outline_shapes: Vec<u16>,
     block_entity_type: Option<u32>,
 }
 
@@ -163,6 +172,30 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
         })
         .collect::<TokenStream>();
 
+    let state_to_tool_required_arms = blocks
+        .iter()
+        .flat_map(|b| {
+            b.states.iter().filter(|s| s.tool_required).map(|s| {
+                let id = s.id;
+                quote! {
+                    #id => true,
+                }
+            })
+        })
+        .collect::<TokenStream>();
+
+    let kind_to_hardness_arms = blocks
+        .iter()
+        .filter(|b| b.hardness != 0.0)
+        .map(|b| {
+            let kind = ident(b.name.to_pascal_case());
+            let hardness = b.hardness;
+            quote! {
+                Self::#kind => #hardness,
+            }
+        })
+        .collect::<TokenStream>();
+
     let shapes = shapes.iter().map(|s| {
         let min_x = s.min_x;
         let min_y = s.min_y;
@@ -193,6 +226,19 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
         })
         .collect::<TokenStream>();
 
+    let state_to_outline_shapes_arms = blocks
+        .iter()
+        .flat_map(|b| {
+            b.states.iter().map(|s| {
+                let id = s.id;
+                let outline_shapes = &s.outline_shapes;
+                quote! {
+                    #id => &[#(#outline_shapes),*],
+                }
+            })
+        })
+        .collect::<TokenStream>();
+
     let get_arms = blocks
         .iter()
         .filter(|&b| !b.properties.is_empty())
@@ -393,10 +439,10 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
         .iter()
         .map(|block| {
             let name = ident(block.name.to_pascal_case());
-            let item_id = &block.item_id;
+            let item_name = ident(item_name(&block.item_id).to_pascal_case());
 
             quote! {
-                BlockKind::#name => #item_id,
+                BlockKind::#name => ItemKind::#item_name,
             }
         })
         .collect::<TokenStream>();
@@ -406,10 +452,10 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
         .filter(|block| block.item_id != "minecraft:air")
         .map(|block| {
             let name = ident(block.name.to_pascal_case());
-            let item_id = &block.item_id;
+            let item_name = ident(item_name(&block.item_id).to_pascal_case());
 
             quote! {
-                #item_id => Some(BlockKind::#name),
+                ItemKind::#item_name => Some(BlockKind::#name),
             }
         })
         .collect::<TokenStream>();
@@ -693,6 +739,22 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                 }
             }
 
+            #[doc = "Returns whether this block state only drops items when broken"]
+            #[doc = "with the correct tool, which also makes it slower to break"]
+            #[doc = "without one."]
+            pub const fn requires_tool(self) -> bool {
+                match self.0 {
+                    #state_to_tool_required_arms
+                    _ => false,
+                }
+            }
+
+            #[doc = "Returns the hardness of the block kind of this state. See"]
+            #[doc = "[`BlockKind::hardness`]."]
+            pub const fn hardness(self) -> f32 {
+                self.to_kind().hardness()
+            }
+
             #[allow(clippy::large_stack_arrays)]
             const SHAPES: [Aabb; #shape_count] = [
                 #(#shapes,)*
@@ -707,6 +769,18 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                 shape_idxs.into_iter().map(|idx| Self::SHAPES[*idx as usize])
             }
 
+            /// Returns the shapes of the outline drawn around the block when a
+            /// player looks at it. These are the shapes players aim at when
+            /// breaking and placing blocks.
+            pub fn outline_shapes(self) -> impl ExactSizeIterator<Item = Aabb> + FusedIterator + Clone {
+                let shape_idxs: &'static [u16] = match self.0 {
+                    #state_to_outline_shapes_arms
+                    _ => &[],
+                };
+
+                shape_idxs.into_iter().map(|idx| Self::SHAPES[*idx as usize])
+            }
+
             pub const fn luminance(self) -> u8 {
                 match self.0 {
                     #state_to_luminance_arms
@@ -772,18 +846,23 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                 }
             }
 
+            #[doc = "Returns the hardness of this block kind, which determines how"]
+            #[doc = "long it takes to break."]
+            #[doc = ""]
+            #[doc = "Unbreakable blocks such as bedrock have a hardness of `-1.0`."]
+            pub const fn hardness(self) -> f32 {
+                match self {
+                    #kind_to_hardness_arms
+                    _ => 0.0,
+                }
+            }
+
             #[doc = "Converts a block kind to its corresponding item kind."]
             #[doc = ""]
             #[doc = "[`ItemKind::Air`] is used to indicate the absence of an item."]
             pub const fn to_item_kind(self) -> ItemKind {
-                let id = match self {
+                match self {
                     #block_kind_to_item_kind_arms
-                };
-
-                // TODO: unwrap() is not const yet.
-                match ItemKind::from_raw(id) {
-                    Some(k) => k,
-                    None => unreachable!(),
                 }
             }
 
@@ -795,7 +874,7 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                 // For instance, `torch` comes before `wall_torch` so this match
                 // should do the correct thing.
                 #[allow(unreachable_patterns)]
-                match item.to_raw() {
+                match item {
                     #block_kind_from_item_kind_arms
                     _ => None,
                 }
@@ -1,48 +1,160 @@
-use anyhow::Ok;
 use heck::ToPascalCase;
 use proc_macro2::TokenStream;
 use quote::quote;
 use serde::Deserialize;
 use valence_build_utils::{ident, rerun_if_changed};
 
-use std::sync::atomic::{AtomicU32, Ordering};
-
-static ID_COUNTER: AtomicU32 = AtomicU32::new(0);
-
-fn next_id() -> u32 {
-    ID_COUNTER.fetch_add(1, Ordering::Relaxed)
-}
-
-static ID_COUNTER: AtomicU32 = AtomicU32::new(0);
-
-fn next_id() -> u32 {
-    ID_COUNTER.fetch_add(1, Ordering::Relaxed)
-}
-
 #[derive(Deserialize, Clone, Debug)]
 struct Item {
-    id: u32,
+    id: u16,
     name: String,
     translation_key: String,
     max_stack: i8,
     max_durability: u16,
     enchantability: u8,
     fireproof: bool,
+    components: ItemComponents,
+    /// The default attribute modifiers of the item. Unlike the
+    /// `minecraft:attribute_modifiers` component, this includes the modifiers
+    /// of armor. Missing from data extracted before the extractor wrote it.
+    attribute_modifiers: Option<AttributeModifiers>,
+}
+
+impl Item {
+    /// Returns the default attribute modifiers of the item, falling back to
+    /// the `minecraft:attribute_modifiers` component.
+    fn attribute_modifiers(&self) -> &[AttributeModifier] {
+        self.attribute_modifiers
+            .as_ref()
+            .or(self.components.attribute_modifiers.as_ref())
+            .map_or(&[], |modifiers| &modifiers.modifiers)
+    }
+}
+
+/// The default components of an item. Only the components we generate code
+/// for are deserialized.
+#[derive(Deserialize, Clone, Debug)]
+struct ItemComponents {
+    #[serde(rename = "minecraft:food")]
     food: Option<FoodComponent>,
+    #[serde(rename = "minecraft:tool")]
+    tool: Option<ToolComponent>,
+    #[serde(rename = "minecraft:attribute_modifiers")]
+    attribute_modifiers: Option<AttributeModifiers>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 struct FoodComponent {
-    hunger: u16,
+    nutrition: i32,
     saturation: f32,
-    always_edible: bool,
-    meat: bool,
-    snack: bool,
-    // TODO: effects
+    #[serde(default)]
+    can_always_eat: bool,
+    #[serde(default = "default_eat_seconds")]
+    eat_seconds: f32,
+    using_converts_to: Option<ItemId>,
+    #[serde(default)]
+    effects: Vec<FoodEffect>,
+}
+
+fn default_eat_seconds() -> f32 {
+    1.6
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct ItemId {
+    id: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct FoodEffect {
+    effect: FoodEffectInstance,
+    #[serde(default = "default_probability")]
+    probability: f32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct FoodEffectInstance {
+    id: String,
+    #[serde(default)]
+    amplifier: u8,
+    #[serde(default)]
+    duration: i32,
+    #[serde(default)]
+    ambient: bool,
+    #[serde(default = "default_true")]
+    show_particles: bool,
+    #[serde(default = "default_true")]
+    show_icon: bool,
+}
+
+fn default_probability() -> f32 {
+    1.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct ToolComponent {
+    rules: Vec<ToolRule>,
+    #[serde(default = "default_mining_speed")]
+    default_mining_speed: f32,
+    #[serde(default = "default_damage_per_block")]
+    damage_per_block: i32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct ToolRule {
+    blocks: ToolRuleBlocks,
+    speed: Option<f32>,
+    correct_for_drops: Option<bool>,
+}
+
+/// A block tag (prefixed with `#`), a single block or a list of blocks.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+enum ToolRuleBlocks {
+    One(String),
+    Many(Vec<String>),
+}
+
+fn default_mining_speed() -> f32 {
+    1.0
+}
+
+fn default_damage_per_block() -> i32 {
+    1
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct AttributeModifiers {
+    modifiers: Vec<AttributeModifier>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct AttributeModifier {
+    #[serde(rename = "type")]
+    attribute: String,
+    id: String,
+    amount: f64,
+    operation: String,
+    slot: String,
+}
+
+/// Strips the namespace from an identifier.
+fn strip_namespace(ident: &str) -> &str {
+    ident.strip_prefix("minecraft:").unwrap_or(ident)
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct ItemComponentType {
+    id: u16,
+    name: String,
 }
 
 pub(crate) fn build() -> anyhow::Result<TokenStream> {
-    rerun_if_changed(["extracted/items.json"]);
+    rerun_if_changed(["extracted/items.json", "extracted/item_components.json"]);
 
     let items = serde_json::from_str::<Vec<Item>>(include_str!("../extracted/items.json"))?;
 
@@ -51,7 +163,7 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
     let item_kind_from_raw_id_arms = items
         .iter()
         .map(|item| {
-            let id = next_id();
+            let id = item.id;
             let name = ident(item.name.to_pascal_case());
 
             quote! {
@@ -60,158 +172,377 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
         })
         .collect::<TokenStream>();
 
-        let item_kind_to_raw_id_arms = items
-            .iter()
-            .map(|item| {
-                let id = next_id();
-                let name = ident(item.name.to_pascal_case());
+    let item_kind_to_raw_id_arms = items
+        .iter()
+        .map(|item| {
+            let id = item.id;
+            let name = ident(item.name.to_pascal_case());
 
-                quote! {
-                    Self::#name => #id,
-                }
-            })
-            .collect::<TokenStream>();
+            quote! {
+                Self::#name => #id,
+            }
+        })
+        .collect::<TokenStream>();
 
-        let item_kind_from_str_arms = items
-            .iter()
-            .map(|item| {
-                let str_name = &item.name;
-                let name = ident(str_name.to_pascal_case());
-                quote! {
-                    #str_name => Some(Self::#name),
-                }
-            })
-            .collect::<TokenStream>();
+    let item_kind_from_str_arms = items
+        .iter()
+        .map(|item| {
+            let str_name = &item.name;
+            let name = ident(str_name.to_pascal_case());
+            quote! {
+                #str_name => Some(Self::#name),
+            }
+        })
+        .collect::<TokenStream>();
 
-        let item_kind_to_str_arms = items
-            .iter()
-            .map(|item| {
-                let str_name = &item.name;
-                let name = ident(str_name.to_pascal_case());
-                quote! {
-                    Self::#name => #str_name,
-                }
-            })
-            .collect::<TokenStream>();
+    let item_kind_to_str_arms = items
+        .iter()
+        .map(|item| {
+            let str_name = &item.name;
+            let name = ident(str_name.to_pascal_case());
+            quote! {
+                Self::#name => #str_name,
+            }
+        })
+        .collect::<TokenStream>();
 
-        let item_kind_to_translation_key_arms = items
-            .iter()
-            .map(|item| {
-                let name = ident(item.name.to_pascal_case());
-                let translation_key = &item.translation_key;
-                quote! {
-                    Self::#name => #translation_key,
-                }
-            })
-            .collect::<TokenStream>();
+    let item_kind_to_translation_key_arms = items
+        .iter()
+        .map(|item| {
+            let name = ident(item.name.to_pascal_case());
+            let translation_key = &item.translation_key;
+            quote! {
+                Self::#name => #translation_key,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let item_kind_variants = items
+        .iter()
+        .map(|item| ident(item.name.to_pascal_case()))
+        .collect::<Vec<_>>();
 
-        let item_kind_variants = items
-            .iter()
-            .map(|item| ident(item.name.to_pascal_case()))
-            .collect::<Vec<_>>();
+    let item_kind_to_max_stack_arms = items
+        .iter()
+        .map(|item| {
+            let name = ident(item.name.to_pascal_case());
+            let max_stack = item.max_stack;
 
-        let item_kind_to_max_stack_arms = items
-            .iter()
-            .map(|item| {
-                let name = ident(item.name.to_pascal_case());
-                let max_stack = item.max_stack;
+            quote! {
+                Self::#name => #max_stack,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let item_kind_to_food_component_arms = items
+        .iter()
+        .filter_map(|item| {
+            let food = item.components.food.as_ref()?;
+            let name = ident(item.name.to_pascal_case());
+            let nutrition = food.nutrition;
+            let saturation = food.saturation;
+            let can_always_eat = food.can_always_eat;
+            let eat_seconds = food.eat_seconds;
+
+            let using_converts_to = match &food.using_converts_to {
+                Some(item) => {
+                    let item = ident(strip_namespace(&item.id).to_pascal_case());
+                    quote!(Some(Self::#item))
+                }
+                None => quote!(None),
+            };
+
+            let effects = food.effects.iter().map(|effect| {
+                let probability = effect.probability;
+                let FoodEffectInstance {
+                    id,
+                    amplifier,
+                    duration,
+                    ambient,
+                    show_particles,
+                    show_icon,
+                } = &effect.effect;
+                let id = ident(strip_namespace(id).to_pascal_case());
 
                 quote! {
-                    Self::#name => #max_stack,
+                    FoodComponentEffect {
+                        effect: StatusEffect::#id,
+                        amplifier: #amplifier,
+                        duration: #duration,
+                        ambient: #ambient,
+                        show_particles: #show_particles,
+                        show_icon: #show_icon,
+                        probability: #probability,
+                    }
                 }
+            });
+
+            Some(quote! {
+                Self::#name => Some(FoodComponent {
+                    nutrition: #nutrition,
+                    saturation: #saturation,
+                    can_always_eat: #can_always_eat,
+                    eat_seconds: #eat_seconds,
+                    using_converts_to: #using_converts_to,
+                    effects: &[#(#effects,)*],
+                }),
             })
-            .collect::<TokenStream>();
-
-        let item_kind_to_food_component_arms = items
-            .iter()
-            .map(|item| match &item.food {
-                Some(food_component) => {
-                    let name = ident(item.name.to_pascal_case());
-                    let hunger = food_component.hunger;
-                    let saturation = food_component.saturation;
-                    let always_edible = food_component.always_edible;
-                    let meat = food_component.meat;
-                    let snack = food_component.snack;
-
-                    quote! {
-                        Self::#name => Some(FoodComponent {
-                            hunger: #hunger,
-                            saturation: #saturation,
-                            always_edible: #always_edible,
-                            meat: #meat,
-                            snack: #snack,
+        })
+        .collect::<TokenStream>();
+
+    let item_kind_to_tool_component_arms = items
+        .iter()
+        .filter_map(|item| {
+            let tool = item.components.tool.as_ref()?;
+            let name = ident(item.name.to_pascal_case());
+            let default_mining_speed = tool.default_mining_speed;
+            let damage_per_block = tool.damage_per_block;
+
+            let rules = tool.rules.iter().map(|rule| {
+                let blocks = match &rule.blocks {
+                    ToolRuleBlocks::One(blocks) => {
+                        if let Some(tag) = blocks.strip_prefix('#') {
+                            quote!(ToolRuleBlocks::Tag(#tag))
+                        } else {
+                            let block = ident(strip_namespace(blocks).to_pascal_case());
+                            quote!(ToolRuleBlocks::Blocks(&[BlockKind::#block]))
                         }
-                    ),
                     }
-                }
-                None => quote! {},
-            })
-            .collect::<TokenStream>();
+                    ToolRuleBlocks::Many(blocks) => {
+                        let blocks = blocks
+                            .iter()
+                            .map(|b| ident(strip_namespace(b).to_pascal_case()));
+                        quote!(ToolRuleBlocks::Blocks(&[#(BlockKind::#blocks),*]))
+                    }
+                };
 
-        let item_kind_to_max_durability_arms = items
-            .iter()
-            .filter(|item| item.max_durability != 0)
-            .map(|item| {
-                let name = ident(item.name.to_pascal_case());
-                let max_durability = item.max_durability;
+                let speed = match rule.speed {
+                    Some(speed) => quote!(Some(#speed)),
+                    None => quote!(None),
+                };
+
+                let correct_for_drops = match rule.correct_for_drops {
+                    Some(correct) => quote!(Some(#correct)),
+                    None => quote!(None),
+                };
 
                 quote! {
-                    Self::#name => #max_durability,
+                    ToolRule {
+                        blocks: #blocks,
+                        speed: #speed,
+                        correct_for_drops: #correct_for_drops,
+                    }
                 }
+            });
+
+            Some(quote! {
+                Self::#name => Some(ToolComponent {
+                    rules: &[#(#rules),*],
+                    default_mining_speed: #default_mining_speed,
+                    damage_per_block: #damage_per_block,
+                }),
             })
-            .collect::<TokenStream>();
+        })
+        .collect::<TokenStream>();
 
-        let item_kind_to_enchantability_arms = items
-            .iter()
-            .filter(|item| item.enchantability != 0)
-            .map(|item| {
-                let name = ident(item.name.to_pascal_case());
-                let ench = item.enchantability;
+    let item_kind_to_attribute_modifiers_arms = items
+        .iter()
+        .filter(|item| !item.attribute_modifiers().is_empty())
+        .map(|item| {
+            let name = ident(item.name.to_pascal_case());
 
-                quote! {
-                    Self::#name => #ench,
-                }
+            let modifiers = item
+                .attribute_modifiers()
+                .iter()
+                .map(|modifier| {
+                    let attribute = ident(strip_namespace(&modifier.attribute).to_pascal_case());
+                    let id = &modifier.id;
+                    let amount = modifier.amount;
+
+                    let operation = match modifier.operation.as_str() {
+                        "add_value" => quote!(EntityAttributeOperation::Add),
+                        "add_multiplied_base" => quote!(EntityAttributeOperation::MultiplyBase),
+                        "add_multiplied_total" => quote!(EntityAttributeOperation::MultiplyTotal),
+                        other => anyhow::bail!("unknown attribute modifier operation {other}"),
+                    };
+
+                    let slot = match modifier.slot.as_str() {
+                        "any" => quote!(AttributeModifierSlot::Any),
+                        "mainhand" => quote!(AttributeModifierSlot::MainHand),
+                        "offhand" => quote!(AttributeModifierSlot::OffHand),
+                        "hand" => quote!(AttributeModifierSlot::Hand),
+                        "feet" => quote!(AttributeModifierSlot::Feet),
+                        "legs" => quote!(AttributeModifierSlot::Legs),
+                        "chest" => quote!(AttributeModifierSlot::Chest),
+                        "head" => quote!(AttributeModifierSlot::Head),
+                        "armor" => quote!(AttributeModifierSlot::Armor),
+                        "body" => quote!(AttributeModifierSlot::Body),
+                        other => anyhow::bail!("unknown attribute modifier slot {other}"),
+                    };
+
+                    Ok(quote! {
+                        ItemAttributeModifier {
+                            attribute: EntityAttribute::#attribute,
+                            id: #id,
+                            amount: #amount,
+                            operation: #operation,
+                            slot: #slot,
+                        }
+                    })
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            Ok(quote! {
+                Self::#name => &[#(#modifiers),*],
             })
-            .collect::<TokenStream>();
+        })
+        .collect::<anyhow::Result<TokenStream>>()?;
 
-        let item_kind_to_fireproof_arms = items
-            .iter()
-            .filter(|item| item.fireproof)
-            .map(|item| {
-                let name = ident(item.name.to_pascal_case());
+    let item_kind_to_max_durability_arms = items
+        .iter()
+        .filter(|item| item.max_durability != 0)
+        .map(|item| {
+            let name = ident(item.name.to_pascal_case());
+            let max_durability = item.max_durability;
 
-                quote! {
-                    Self::#name => true,
-                }
-            })
-            .collect::<TokenStream>();
-
-        Ok(quote! {
-            #[doc = "Represents an item from the game"]
-            #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
-            #[repr(u16)]
-            pub enum ItemKind {
-                #[default]
-                #(#item_kind_variants,)*
+            quote! {
+                Self::#name => #max_durability,
             }
+        })
+        .collect::<TokenStream>();
 
-            #[doc = "Contains food information about an item."]
-            #[doc = ""]
-            #[doc = "Only food items have a food component."]
-            #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
-            pub struct FoodComponent {
-                pub hunger: u16,
+    let item_kind_to_enchantability_arms = items
+        .iter()
+        .filter(|item| item.enchantability != 0)
+        .map(|item| {
+            let name = ident(item.name.to_pascal_case());
+            let ench = item.enchantability;
+
+            quote! {
+                Self::#name => #ench,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let item_kind_to_fireproof_arms = items
+        .iter()
+        .filter(|item| item.fireproof)
+        .map(|item| {
+            let name = ident(item.name.to_pascal_case());
+
+            quote! {
+                Self::#name => true,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let item_components = build_item_components()?;
+
+    Ok(quote! {
+        use crate::attributes::{EntityAttribute, EntityAttributeOperation};
+        use crate::block::BlockKind;
+        use crate::status_effects::StatusEffect;
+
+        #[doc = "Represents an item from the game"]
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+        #[repr(u16)]
+        pub enum ItemKind {
+            #[default]
+            #(#item_kind_variants,)*
+        }
+
+        #[doc = "Contains food information about an item."]
+        #[doc = ""]
+        #[doc = "Only food items have a food component."]
+        #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+        pub struct FoodComponent {
+            pub nutrition: i32,
             pub saturation: f32,
-            pub always_edible: bool,
-            pub meat: bool,
-            pub snack: bool,
+            pub can_always_eat: bool,
+            pub eat_seconds: f32,
+            #[doc = "The item left behind after eating, such as a bowl."]
+            pub using_converts_to: Option<ItemKind>,
+            #[doc = "The status effects that may be applied after eating."]
+            pub effects: &'static [FoodComponentEffect],
+        }
+
+        #[doc = "A status effect applied with some probability after eating."]
+        #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+        pub struct FoodComponentEffect {
+            pub effect: StatusEffect,
+            pub amplifier: u8,
+            #[doc = "The duration of the effect in ticks."]
+            pub duration: i32,
+            pub ambient: bool,
+            pub show_particles: bool,
+            pub show_icon: bool,
+            #[doc = "The probability of the effect being applied, from 0 to 1."]
+            pub probability: f32,
+        }
+
+        #[doc = "Describes how fast an item breaks blocks and whether the blocks"]
+        #[doc = "drop items when broken with it."]
+        #[doc = ""]
+        #[doc = "Only tools have a tool component."]
+        #[derive(Clone, Copy, PartialEq, Debug)]
+        pub struct ToolComponent {
+            #[doc = "The rules of the tool. The first rule matching a block is used."]
+            pub rules: &'static [ToolRule],
+            #[doc = "The mining speed for blocks that don't match any rule."]
+            pub default_mining_speed: f32,
+            #[doc = "The durability lost when breaking a block."]
+            pub damage_per_block: i32,
+        }
+
+        #[derive(Clone, Copy, PartialEq, Debug)]
+        pub struct ToolRule {
+            pub blocks: ToolRuleBlocks,
+            #[doc = "Overrides the mining speed for matching blocks."]
+            pub speed: Option<f32>,
+            #[doc = "Overrides whether matching blocks drop items."]
+            pub correct_for_drops: Option<bool>,
+        }
+
+        #[doc = "The blocks a [`ToolRule`] applies to."]
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum ToolRuleBlocks {
+            #[doc = "A block tag, such as `minecraft:mineable/pickaxe`."]
+            Tag(&'static str),
+            Blocks(&'static [BlockKind]),
+        }
+
+        #[doc = "An attribute modifier an item applies while it is equipped."]
+        #[derive(Clone, Copy, PartialEq, Debug)]
+        pub struct ItemAttributeModifier {
+            pub attribute: EntityAttribute,
+            #[doc = "The name of the modifier, such as `minecraft:base_attack_damage`."]
+            pub id: &'static str,
+            pub amount: f64,
+            pub operation: EntityAttributeOperation,
+            #[doc = "The equipment slots the modifier is active in."]
+            pub slot: AttributeModifierSlot,
+        }
+
+        #[doc = "The equipment slots an [`ItemAttributeModifier`] is active in."]
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum AttributeModifierSlot {
+            Any,
+            MainHand,
+            OffHand,
+            Hand,
+            Feet,
+            Legs,
+            Chest,
+            Head,
+            Armor,
+            Body,
         }
 
         impl ItemKind {
             #[doc = "Constructs a item kind from a raw item ID."]
             #[doc = ""]
             #[doc = "If the given ID is invalid, `None` is returned."]
-            pub const fn from_raw(id: &str) -> Option<Self> {
+            pub const fn from_raw(id: u16) -> Option<Self> {
                 match id {
                     #item_kind_from_raw_id_arms
                     _ => None
@@ -219,7 +550,7 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
             }
 
             #[doc = "Gets the raw item ID from the item kind"]
-            pub const fn to_raw(self) -> &str {
+            pub const fn to_raw(self) -> u16 {
                 match self {
                     #item_kind_to_raw_id_arms
                 }
@@ -257,7 +588,8 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                 }
             }
 
-            #[doc = "Returns a food component which stores hunger, saturation etc."]
+            #[doc = "Returns the default food component of this item kind, which"]
+            #[doc = "stores nutrition, saturation etc."]
             #[doc = ""]
             #[doc = "If the item kind can't be eaten, `None` will be returned."]
             pub const fn food_component(self) -> Option<FoodComponent> {
@@ -267,6 +599,26 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                 }
             }
 
+            #[doc = "Returns the default tool component of this item kind, which"]
+            #[doc = "determines how fast it breaks blocks."]
+            #[doc = ""]
+            #[doc = "If the item kind isn't a tool, `None` will be returned."]
+            pub const fn tool_component(self) -> Option<ToolComponent> {
+                match self {
+                    #item_kind_to_tool_component_arms
+                    _ => None
+                }
+            }
+
+            #[doc = "Returns the default attribute modifiers of this item kind, such"]
+            #[doc = "as the attack damage of weapons and the armor of armor pieces."]
+            pub const fn attribute_modifiers(self) -> &'static [ItemAttributeModifier] {
+                match self {
+                    #item_kind_to_attribute_modifiers_arms
+                    _ => &[],
+                }
+            }
+
             #[doc = "Returns the maximum durability before the item will break."]
             #[doc = ""]
             #[doc = "If the item doesn't have durability, `0` is returned."]
@@ -314,5 +666,116 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
             #[doc = "An array of all item kinds."]
             pub const ALL: [Self; #item_kind_count] = [#(Self::#item_kind_variants,)*];
         }
+
+        #item_components
+    })
+}
+
+fn build_item_components() -> anyhow::Result<TokenStream> {
+    let components = serde_json::from_str::<Vec<ItemComponentType>>(include_str!(
+        "../extracted/item_components.json"
+    ))?;
+
+    let component_count = components.len();
+
+    let component_variants = components
+        .iter()
+        .map(|component| ident(component.name.to_pascal_case()))
+        .collect::<Vec<_>>();
+
+    let component_from_raw_id_arms = components
+        .iter()
+        .map(|component| {
+            let id = component.id;
+            let name = ident(component.name.to_pascal_case());
+
+            quote! {
+                #id => Some(Self::#name),
+            }
+        })
+        .collect::<TokenStream>();
+
+    let component_to_raw_id_arms = components
+        .iter()
+        .map(|component| {
+            let id = component.id;
+            let name = ident(component.name.to_pascal_case());
+
+            quote! {
+                Self::#name => #id,
+            }
+        })
+        .collect::<TokenStream>();
+
+    let component_from_str_arms = components
+        .iter()
+        .map(|component| {
+            let str_name = &component.name;
+            let name = ident(str_name.to_pascal_case());
+
+            quote! {
+                #str_name => Some(Self::#name),
+            }
+        })
+        .collect::<TokenStream>();
+
+    let component_to_str_arms = components
+        .iter()
+        .map(|component| {
+            let str_name = &component.name;
+            let name = ident(str_name.to_pascal_case());
+
+            quote! {
+                Self::#name => #str_name,
+            }
+        })
+        .collect::<TokenStream>();
+
+    Ok(quote! {
+        #[doc = "The type of a data component attached to an item stack."]
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum ItemComponentKind {
+            #(#component_variants,)*
+        }
+
+        impl ItemComponentKind {
+            #[doc = "Constructs a component kind from a raw component type ID."]
+            #[doc = ""]
+            #[doc = "If the given ID is invalid, `None` is returned."]
+            pub const fn from_raw(id: u16) -> Option<Self> {
+                match id {
+                    #component_from_raw_id_arms
+                    _ => None
+                }
+            }
+
+            #[doc = "Gets the raw component type ID from the component kind."]
+            pub const fn to_raw(self) -> u16 {
+                match self {
+                    #component_to_raw_id_arms
+                }
+            }
+
+            #[doc = "Construct a component kind from its snake_case name."]
+            #[doc = ""]
+            #[doc = "Returns `None` if the name is invalid."]
+            #[allow(clippy::should_implement_trait)]
+            pub fn from_str(name: &str) -> Option<Self> {
+                match name {
+                    #component_from_str_arms
+                    _ => None
+                }
+            }
+
+            #[doc = "Gets the snake_case name of this component kind."]
+            pub const fn to_str(self) -> &'static str {
+                match self {
+                    #component_to_str_arms
+                }
+            }
+
+            #[doc = "An array of all component kinds."]
+            pub const ALL: [Self; #component_count] = [#(Self::#component_variants,)*];
+        }
     })
 }
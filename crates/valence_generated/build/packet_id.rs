@@ -18,10 +18,33 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
     let packets: Vec<Packet> = serde_json::from_str(include_str!("../extracted/packets.json"))?;
 
     let mut consts = TokenStream::new();
+    let mut configuration_consts = TokenStream::new();
 
     for packet in packets {
         let stripped_name = packet.name.strip_suffix("Packet").unwrap_or(&packet.name);
 
+        // Configuration packets reuse the names of their play counterparts, so they
+        // get their own module and are suffixed with the side they are sent to.
+        if packet.state == "configuration" {
+            let suffix = if packet.side == "clientbound" {
+                "S2C"
+            } else {
+                "C2S"
+            };
+
+            let name_ident = ident(format!("{}_{suffix}", stripped_name.to_shouty_snake_case()));
+            let id = packet.id;
+
+            let doc = format!("Side: {}\n\nState: {}", packet.side, packet.state);
+
+            configuration_consts.extend([quote! {
+                #[doc = #doc]
+                pub const #name_ident: i32 = #id;
+            }]);
+
+            continue;
+        }
+
         let name_ident = ident(stripped_name.to_shouty_snake_case());
         let id = packet.id;
 
@@ -33,5 +56,12 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
         }]);
     }
 
-    Ok(consts)
+    Ok(quote! {
+        #consts
+
+        /// Packet IDs used in the configuration state.
+        pub mod configuration {
+            #configuration_consts
+        }
+    })
 }
@@ -16,7 +16,26 @@ pub(crate) struct AttributeModifiers {
     attribute: String,
     operation: u8,
     base_value: f64,
-    uuid: String,
+    /// Since 1.21, modifiers are identified by a resource location such as
+    /// `minecraft:effect.speed` instead of a UUID. Data extracted before the
+    /// field was renamed stores it as `uuid`, in the form of a translation key.
+    #[serde(alias = "uuid")]
+    id: String,
+}
+
+/// Derives a stable UUID from the ID of an attribute modifier using 128-bit
+/// FNV-1a.
+///
+/// `EntityAttributes` still keys its modifiers by UUID, so the generated
+/// `AttributeModifier`s need one. Hashing the ID keeps it the same across
+/// builds, so a modifier added by one effect can be found and removed again.
+fn modifier_uuid(id: &str) -> u128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    id.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u128::from(byte)).wrapping_mul(PRIME)
+    })
 }
 
 #[derive(Deserialize, Debug)]
@@ -144,14 +163,14 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
                     let attribute = &modifier.attribute;
                     let operation = &modifier.operation;
                     let base_value = &modifier.base_value;
-                    let uuid = &modifier.uuid;
+                    let uuid = modifier_uuid(&modifier.id);
 
                     quote! {
                         AttributeModifier {
                             attribute: EntityAttribute::from_id(#attribute).unwrap(),
                             operation: EntityAttributeOperation::from_raw(#operation).unwrap(),
                             base_value: #base_value,
-                            uuid: Uuid::parse_str(#uuid).unwrap(),
+                            uuid: Uuid::from_u128(#uuid),
                         }
                     }
                 });
@@ -182,7 +201,7 @@ pub(crate) fn build() -> anyhow::Result<TokenStream> {
             pub operation: EntityAttributeOperation,
             #[doc = "The base_value of this modifier."]
             pub base_value: f64,
-            #[doc = "The UUID of this modifier."]
+            #[doc = "The UUID of this modifier, derived from its resource location."]
             pub uuid: Uuid,
         }
 
@@ -0,0 +1,89 @@
+use valence_server::layer::chunk::{Chunk, UnloadedChunk};
+use valence_server::registry::biome::BiomeId;
+use valence_server::{BlockState, ChunkPos};
+
+use crate::ChunkGenerator;
+
+/// Generates chunks filled with air.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct VoidGenerator {
+    /// The biome of the generated chunks.
+    pub biome: BiomeId,
+}
+
+impl ChunkGenerator for VoidGenerator {
+    fn generate(&self, _pos: ChunkPos, height: u32, _min_y: i32) -> Option<UnloadedChunk> {
+        let mut chunk = UnloadedChunk::with_height(height);
+        chunk.fill_biomes(self.biome);
+
+        Some(chunk)
+    }
+}
+
+/// Generates superflat chunks.
+#[derive(Clone, Default, Debug)]
+pub struct FlatGenerator {
+    /// The block and thickness of each layer, starting from the bottom of the
+    /// world. Layers above the top of the world are ignored.
+    pub layers: Vec<(BlockState, u32)>,
+    /// The biome of the generated chunks.
+    pub biome: BiomeId,
+}
+
+impl ChunkGenerator for FlatGenerator {
+    fn generate(&self, _pos: ChunkPos, height: u32, _min_y: i32) -> Option<UnloadedChunk> {
+        let mut chunk = UnloadedChunk::with_height(height);
+        chunk.fill_biomes(self.biome);
+
+        let mut y = 0;
+
+        for &(block, thickness) in &self.layers {
+            let top = y.saturating_add(thickness).min(height);
+
+            while y < top {
+                // Fill whole sections at once where possible.
+                if y % 16 == 0 && top - y >= 16 {
+                    chunk.fill_block_state_section(y / 16, block);
+                    y += 16;
+                    continue;
+                }
+
+                for z in 0..16 {
+                    for x in 0..16 {
+                        chunk.set_block_state(x, y, z, block);
+                    }
+                }
+
+                y += 1;
+            }
+        }
+
+        Some(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_generator_layers() {
+        let generator = FlatGenerator {
+            layers: vec![
+                (BlockState::BEDROCK, 1),
+                (BlockState::STONE, 40),
+                (BlockState::GRASS_BLOCK, 1),
+            ],
+            ..Default::default()
+        };
+
+        let chunk = generator.generate(ChunkPos::new(0, 0), 64, 0).unwrap();
+
+        assert_eq!(chunk.height(), 64);
+        assert_eq!(chunk.block_state(3, 0, 7), BlockState::BEDROCK);
+        assert_eq!(chunk.block_state(3, 1, 7), BlockState::STONE);
+        assert_eq!(chunk.block_state(15, 40, 15), BlockState::STONE);
+        assert_eq!(chunk.block_state(0, 41, 0), BlockState::GRASS_BLOCK);
+        assert_eq!(chunk.block_state(0, 42, 0), BlockState::AIR);
+    }
+}
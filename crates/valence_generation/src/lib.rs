@@ -0,0 +1,342 @@
+#![doc = include_str!("../README.md")]
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use flume::{Receiver, Sender};
+use valence_server::client::{Client, OldView, View};
+use valence_server::entity::{EntityLayerId, OldEntityLayerId};
+use valence_server::layer::chunk::UnloadedChunk;
+use valence_server::layer::UpdateLayersPreClientSet;
+use valence_server::{ChunkLayer, ChunkPos, ChunkView};
+
+mod generators;
+
+pub use generators::{FlatGenerator, VoidGenerator};
+
+/// Produces the chunks of a [`GeneratedLevel`].
+///
+/// Generators are called from a thread pool, so many chunks may be generated
+/// at the same time.
+pub trait ChunkGenerator: Send + Sync + 'static {
+    /// Generates the chunk at `pos`. `height` and `min_y` are the height and
+    /// minimum Y coordinate of the layer's dimension.
+    ///
+    /// If `None` is returned, no chunk is inserted at the position.
+    fn generate(&self, pos: ChunkPos, height: u32, min_y: i32) -> Option<UnloadedChunk>;
+}
+
+impl<F> ChunkGenerator for F
+where
+    F: Fn(ChunkPos, u32, i32) -> Option<UnloadedChunk> + Send + Sync + 'static,
+{
+    fn generate(&self, pos: ChunkPos, height: u32, min_y: i32) -> Option<UnloadedChunk> {
+        self(pos, height, min_y)
+    }
+}
+
+/// The order in which chunks should be generated. Smaller values are
+/// generated first.
+type Priority = u64;
+
+/// A [`Component`] for chunk layers whose chunks are produced by a
+/// [`ChunkGenerator`].
+///
+/// Missing chunks in view of clients are generated, and chunks no longer in
+/// view of any client are removed from the layer.
+#[derive(Component)]
+pub struct GeneratedLevel {
+    generator: Arc<dyn ChunkGenerator>,
+    /// The set of chunk positions that should not be generated or unloaded by
+    /// the generation systems.
+    ///
+    /// This set is empty by default, but you can modify it at any time.
+    pub ignored_chunks: HashSet<ChunkPos>,
+    /// Whether chunks no longer in view of any client are removed from the
+    /// layer.
+    ///
+    /// This is `true` by default.
+    pub unload_unviewed: bool,
+    /// The maximum number of chunks being generated at the same time. Queued
+    /// chunks are only handed to the thread pool when there is room, so that
+    /// chunks near clients aren't stuck behind chunks queued earlier.
+    ///
+    /// This is twice the number of threads in the thread pool by default.
+    pub max_in_flight: usize,
+    /// Chunks that need to be generated.
+    pending: HashMap<ChunkPos, Priority>,
+    /// Pending chunks queued by [`GeneratedLevel::force_chunk_generate`],
+    /// which are kept even when no client can see them.
+    forced: HashSet<ChunkPos>,
+    /// Chunks currently being generated on the thread pool.
+    in_flight: HashSet<ChunkPos>,
+    /// Sender for finished chunks, cloned into each generation task.
+    sender: Sender<(ChunkPos, Option<UnloadedChunk>)>,
+    /// Receiver for finished chunks.
+    receiver: Receiver<(ChunkPos, Option<UnloadedChunk>)>,
+}
+
+impl GeneratedLevel {
+    /// Creates a new level that generates its chunks with `generator`.
+    pub fn new<G: ChunkGenerator>(generator: G) -> Self {
+        let (sender, receiver) = flume::unbounded();
+
+        Self {
+            generator: Arc::new(generator),
+            ignored_chunks: HashSet::new(),
+            unload_unviewed: true,
+            max_in_flight: rayon::current_num_threads() * 2,
+            pending: HashMap::new(),
+            forced: HashSet::new(),
+            in_flight: HashSet::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Returns the generator of this level.
+    pub fn generator(&self) -> &dyn ChunkGenerator {
+        &*self.generator
+    }
+
+    /// Queues the chunk at the given position to be generated with the highest
+    /// priority, bypassing [`GeneratedLevel::ignored_chunks`].
+    ///
+    /// Note that the chunk will be unloaded after it is generated unless it
+    /// has been added to [`GeneratedLevel::ignored_chunks`] or it is in view
+    /// of a client.
+    ///
+    /// This has no effect if the chunk is already being generated.
+    pub fn force_chunk_generate(&mut self, pos: ChunkPos) {
+        if !self.in_flight.contains(&pos) {
+            self.pending.insert(pos, 0);
+            self.forced.insert(pos);
+        }
+    }
+
+    /// Returns whether the chunk at the given position is queued or being
+    /// generated.
+    pub fn is_generating(&self, pos: ChunkPos) -> bool {
+        self.pending.contains_key(&pos) || self.in_flight.contains(&pos)
+    }
+}
+
+impl fmt::Debug for GeneratedLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeneratedLevel")
+            .field("ignored_chunks", &self.ignored_chunks)
+            .field("unload_unviewed", &self.unload_unviewed)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("pending", &self.pending)
+            .field("forced", &self.forced)
+            .field("in_flight", &self.in_flight)
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct ChunkGenerationPlugin;
+
+impl Plugin for ChunkGenerationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ChunkGenerateEvent>()
+            .add_systems(PreUpdate, remove_unviewed_chunks)
+            .add_systems(
+                PostUpdate,
+                (
+                    update_client_views,
+                    prioritize_pending_chunks,
+                    send_recv_chunks,
+                )
+                    .chain()
+                    .before(UpdateLayersPreClientSet),
+            );
+    }
+}
+
+/// Removes all chunks no longer viewed by clients.
+///
+/// This needs to run in `PreUpdate` where the chunk viewer counts have been
+/// updated from the previous tick.
+fn remove_unviewed_chunks(mut layers: Query<(&mut ChunkLayer, &GeneratedLevel)>) {
+    for (mut layer, level) in &mut layers {
+        if level.unload_unviewed {
+            layer.retain_chunks(|pos, chunk| {
+                chunk.viewer_count_mut() > 0 || level.ignored_chunks.contains(&pos)
+            });
+        }
+    }
+}
+
+fn update_client_views(
+    clients: Query<(&EntityLayerId, Ref<OldEntityLayerId>, View, OldView), With<Client>>,
+    mut layers: Query<(&ChunkLayer, &mut GeneratedLevel)>,
+) {
+    for (loc, old_loc, view, old_view) in &clients {
+        let view = view.get();
+        let old_view = old_view.get();
+
+        let Ok((layer, mut level)) = layers.get_mut(loc.0) else {
+            continue;
+        };
+
+        // Newly added levels need the views of the clients already in the layer.
+        let is_new = old_loc.is_added() || level.is_added();
+
+        if loc != &*old_loc || view != old_view || is_new {
+            let mut queue_pos = |pos| {
+                if !level.ignored_chunks.contains(&pos)
+                    && !level.in_flight.contains(&pos)
+                    && layer.chunk(pos).is_none()
+                {
+                    // Chunks closer to clients are prioritized.
+                    let dist = view.pos.distance_squared(pos);
+
+                    match level.pending.entry(pos) {
+                        Entry::Occupied(mut oe) => {
+                            let priority = oe.get_mut();
+                            *priority = (*priority).min(dist);
+                        }
+                        Entry::Vacant(ve) => {
+                            ve.insert(dist);
+                        }
+                    }
+                }
+            };
+
+            // Queue all the new chunks in the view to be generated.
+            if is_new || loc != &*old_loc {
+                view.iter().for_each(&mut queue_pos);
+            } else {
+                view.diff(old_view).for_each(&mut queue_pos);
+            }
+        }
+    }
+}
+
+/// Drops queued chunks that are no longer in view of any client and
+/// recomputes the priority of the others, since clients may have moved since
+/// the chunks were queued.
+fn prioritize_pending_chunks(
+    clients: Query<(&EntityLayerId, View), With<Client>>,
+    mut layers: Query<(Entity, &mut GeneratedLevel)>,
+    mut views: Local<Vec<ChunkView>>,
+) {
+    for (entity, mut level) in &mut layers {
+        if level.pending.is_empty() {
+            continue;
+        }
+
+        views.extend(
+            clients
+                .iter()
+                .filter(|(loc, _)| loc.0 == entity)
+                .map(|(_, view)| view.get()),
+        );
+
+        let level = level.into_inner();
+
+        level.pending.retain(|pos, priority| {
+            if level.forced.contains(pos) {
+                return true;
+            }
+
+            let nearest = views
+                .iter()
+                .filter(|view| view.contains(*pos))
+                .map(|view| view.pos.distance_squared(*pos))
+                .min();
+
+            match nearest {
+                Some(dist) => {
+                    *priority = dist;
+                    true
+                }
+                None => false,
+            }
+        });
+
+        views.clear();
+    }
+}
+
+fn send_recv_chunks(
+    mut layers: Query<(Entity, &mut ChunkLayer, &mut GeneratedLevel)>,
+    mut to_send: Local<Vec<(Priority, ChunkPos)>>,
+    mut generate_events: EventWriter<ChunkGenerateEvent>,
+) {
+    for (entity, mut layer, level) in &mut layers {
+        let level = level.into_inner();
+
+        // Insert the chunks that are finished generating into the chunk layer and
+        // send generate events.
+        for (pos, chunk) in level.receiver.drain() {
+            level.in_flight.remove(&pos);
+
+            // Don't overwrite a chunk that was inserted while this one was being
+            // generated.
+            if let Some(chunk) = chunk.filter(|_| layer.chunk(pos).is_none()) {
+                layer.insert_chunk(pos, chunk);
+
+                generate_events.send(ChunkGenerateEvent {
+                    chunk_layer: entity,
+                    pos,
+                });
+            }
+        }
+
+        let available = level.max_in_flight.saturating_sub(level.in_flight.len());
+
+        if available == 0 || level.pending.is_empty() {
+            continue;
+        }
+
+        // Collect the chunks that still need to be generated. Chunks may have been
+        // inserted by something else since they were queued.
+        level.pending.retain(|pos, priority| {
+            if layer.chunk(*pos).is_some() {
+                level.forced.remove(pos);
+                false
+            } else {
+                to_send.push((*priority, *pos));
+                true
+            }
+        });
+
+        // Sort chunks by ascending priority.
+        to_send.sort_unstable_by_key(|(pri, _)| *pri);
+
+        let height = layer.height();
+        let min_y = layer.min_y();
+
+        // Hand the most important chunks to the thread pool.
+        for (_, pos) in to_send.drain(..).take(available) {
+            level.pending.remove(&pos);
+            level.forced.remove(&pos);
+            level.in_flight.insert(pos);
+
+            let generator = level.generator.clone();
+            let sender = level.sender.clone();
+
+            rayon::spawn(move || {
+                let chunk = generator.generate(pos, height, min_y);
+                let _ = sender.send((pos, chunk));
+            });
+        }
+
+        to_send.clear();
+    }
+}
+
+/// An event sent by `valence_generation` after a chunk is generated and
+/// inserted into a layer.
+#[derive(Event, Debug)]
+pub struct ChunkGenerateEvent {
+    /// The [`ChunkLayer`] where the chunk is located.
+    pub chunk_layer: Entity,
+    /// The position of the chunk in the layer.
+    pub pos: ChunkPos,
+}
@@ -0,0 +1,612 @@
+#![doc = include_str!("../README.md")]
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use rand::Rng;
+use valence_combat::{CombatSet, DamageAppliedEvent, DamageEvent, Dead};
+use valence_effect::{EffectAction, EffectBehaviors, EffectTickEvent};
+use valence_inventory::player_inventory::PlayerInventory;
+use valence_inventory::{DropItemStackEvent, HeldItem, Inventory};
+use valence_server::client::{Client, SpawnClientsSet};
+use valence_server::difficulty::WorldDifficulty;
+use valence_server::entity::active_status_effects::{ActiveStatusEffect, ActiveStatusEffects};
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::entity::Flags;
+use valence_server::entity::living::{Health, LivingFlags};
+use valence_server::entity::player::{Food, Saturation};
+use valence_server::entity::{EntityStatus, EntityStatuses, UpdateTrackedDataSet};
+use valence_server::event_loop::{EventLoopPreUpdate, EventLoopUpdate, PacketEvent};
+use valence_server::interact_item::InteractItemEvent;
+use valence_server::math::Vec3Swizzles;
+use valence_server::movement::MovementEvent;
+use valence_server::nbt::Value;
+use valence_server::protocol::item::component::ItemComponentKind;
+use valence_server::protocol::packets::play::player_action_c2s::PlayerAction;
+use valence_server::protocol::packets::play::PlayerActionC2s;
+use valence_server::registry::RegistryCodec;
+use valence_server::{
+    ident, Difficulty, GameMode, Hand, ItemComponent, ItemKind, ItemStack, Server,
+};
+
+/// The maximum food level.
+const MAX_FOOD: i32 = 20;
+/// Exhaustion is capped at this value.
+const MAX_EXHAUSTION: f32 = 40.0;
+/// The exhaustion needed to lose a point of saturation or food.
+const EXHAUSTION_PER_FOOD: f32 = 4.0;
+/// The exhaustion caused by healing a point of health with natural
+/// regeneration.
+const REGENERATION_EXHAUSTION: f32 = 6.0;
+
+const SPRINT_EXHAUSTION_PER_BLOCK: f32 = 0.1;
+const JUMP_EXHAUSTION: f32 = 0.05;
+const SPRINT_JUMP_EXHAUSTION: f32 = 0.2;
+const ATTACK_EXHAUSTION: f32 = 0.1;
+
+pub struct HungerPlugin;
+
+impl Plugin for HungerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HungerSettings>()
+            .add_event::<FoodEatenEvent>()
+            .add_systems(PreUpdate, init_hunger_state.after(SpawnClientsSet))
+            .add_systems(EventLoopPreUpdate, handle_release_use_item)
+            .add_systems(EventLoopUpdate, (start_eating, add_movement_exhaustion))
+            .add_systems(
+                PostUpdate,
+                (
+                    add_damage_exhaustion,
+                    add_effect_exhaustion.run_if(resource_exists::<EffectBehaviors>),
+                    tick_eating,
+                    tick_food,
+                )
+                    .chain()
+                    .after(CombatSet::ApplyDamage)
+                    .before(UpdateTrackedDataSet),
+            );
+    }
+}
+
+/// Global settings for the [`HungerPlugin`].
+///
+/// How far players starve depends on the [`WorldDifficulty`]. On peaceful,
+/// players don't lose food and slowly regain food and health instead.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct HungerSettings {
+    /// If players heal when their food level is high. This is the
+    /// `naturalRegeneration` game rule.
+    pub natural_regeneration: bool,
+}
+
+impl Default for HungerSettings {
+    fn default() -> Self {
+        Self {
+            natural_regeneration: true,
+        }
+    }
+}
+
+/// The hunger state of a player. Added to all clients by the
+/// [`HungerPlugin`].
+#[derive(Component, Clone, Default, Debug)]
+pub struct HungerState {
+    /// Exhaustion drains saturation, and then food, once it reaches 4.
+    exhaustion: f32,
+    /// Ticks since the player last healed or starved.
+    food_timer: u32,
+    eating: Option<Eating>,
+}
+
+#[derive(Clone, Debug)]
+struct Eating {
+    hand: Hand,
+    slot: u16,
+    item: ItemKind,
+    remaining_ticks: u32,
+}
+
+impl HungerState {
+    pub fn exhaustion(&self) -> f32 {
+        self.exhaustion
+    }
+
+    /// Adds exhaustion, such as from mining blocks. The exhaustion is capped
+    /// at 40.
+    pub fn add_exhaustion(&mut self, exhaustion: f32) {
+        self.exhaustion = (self.exhaustion + exhaustion).min(MAX_EXHAUSTION);
+    }
+
+    /// Returns if the player is currently eating.
+    pub fn is_eating(&self) -> bool {
+        self.eating.is_some()
+    }
+}
+
+/// Sent when a player finishes eating an item.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct FoodEatenEvent {
+    pub client: Entity,
+    /// The eaten item, with a count of one.
+    pub item: ItemStack,
+}
+
+fn init_hunger_state(
+    clients: Query<Entity, (Added<Client>, Without<HungerState>)>,
+    mut commands: Commands,
+) {
+    for client in &clients {
+        commands.entity(client).insert(HungerState::default());
+    }
+}
+
+/// Starts eating when a player uses a food item they are able to eat.
+fn start_eating(
+    mut events: EventReader<InteractItemEvent>,
+    mut clients: Query<
+        (
+            &mut HungerState,
+            &Inventory,
+            &HeldItem,
+            &Food,
+            &GameMode,
+            &mut LivingFlags,
+        ),
+        Without<Dead>,
+    >,
+) {
+    for event in events.read() {
+        let Ok((mut state, inventory, held_item, food, game_mode, mut flags)) =
+            clients.get_mut(event.client)
+        else {
+            continue;
+        };
+
+        let slot = match event.hand {
+            Hand::Main => held_item.slot(),
+            Hand::Off => PlayerInventory::SLOT_OFFHAND,
+        };
+
+        let stack = inventory.slot(slot);
+
+        let Some(properties) = FoodProperties::of(stack) else {
+            continue;
+        };
+
+        let can_eat = properties.can_always_eat
+            || food.0 < MAX_FOOD
+            || matches!(game_mode, GameMode::Creative | GameMode::Spectator);
+
+        if !can_eat {
+            continue;
+        }
+
+        state.eating = Some(Eating {
+            hand: event.hand,
+            slot,
+            item: stack.item,
+            remaining_ticks: (properties.eat_seconds * 20.0) as u32,
+        });
+
+        flags.set_using_item(true);
+        flags.set_off_hand_active(event.hand == Hand::Off);
+    }
+}
+
+/// Stops eating when a player lets go of the use button.
+fn handle_release_use_item(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut HungerState, &mut LivingFlags)>,
+) {
+    for packet in packets.read() {
+        if let Some(pkt) = packet.decode::<PlayerActionC2s>() {
+            if pkt.action != PlayerAction::ReleaseUseItem {
+                continue;
+            }
+
+            if let Ok((mut state, mut flags)) = clients.get_mut(packet.client) {
+                if state.eating.take().is_some() {
+                    flags.set_using_item(false);
+                }
+            }
+        }
+    }
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct EaterQuery {
+    entity: Entity,
+    state: &'static mut HungerState,
+    inventory: &'static mut Inventory,
+    held_item: &'static HeldItem,
+    food: &'static mut Food,
+    saturation: &'static mut Saturation,
+    game_mode: &'static GameMode,
+    flags: &'static mut LivingFlags,
+    statuses: &'static mut EntityStatuses,
+    effects: Option<&'static mut ActiveStatusEffects>,
+    dead: Has<Dead>,
+}
+
+/// Finishes eating once the use duration of the food has passed, or stops
+/// eating when the food is no longer held.
+fn tick_eating(
+    mut clients: Query<EaterQuery>,
+    mut events: EventWriter<FoodEatenEvent>,
+    mut drop_events: EventWriter<DropItemStackEvent>,
+) {
+    for mut client in &mut clients {
+        let Some(eating) = &mut client.state.eating else {
+            continue;
+        };
+
+        let still_held = match eating.hand {
+            Hand::Main => client.held_item.slot() == eating.slot,
+            Hand::Off => true,
+        } && client.inventory.slot(eating.slot).item == eating.item;
+
+        if client.dead || !still_held {
+            client.state.eating = None;
+            client.flags.set_using_item(false);
+            continue;
+        }
+
+        eating.remaining_ticks = eating.remaining_ticks.saturating_sub(1);
+
+        if eating.remaining_ticks > 0 {
+            continue;
+        }
+
+        let slot = eating.slot;
+        client.state.eating = None;
+        client.flags.set_using_item(false);
+
+        let stack = client.inventory.slot(slot).clone();
+
+        let Some(properties) = FoodProperties::of(&stack) else {
+            continue;
+        };
+
+        let food = (client.food.0 + properties.nutrition).clamp(0, MAX_FOOD);
+        let saturation = (client.saturation.0 + properties.saturation).clamp(0.0, food as f32);
+        client.food.0 = food;
+        client.saturation.0 = saturation;
+
+        if let Some(effects) = &mut client.effects {
+            let mut rng = rand::thread_rng();
+
+            for (effect, probability) in properties.effects {
+                if rng.gen::<f32>() < probability {
+                    effects.apply(effect);
+                }
+            }
+        }
+
+        if *client.game_mode != GameMode::Creative {
+            let remaining = stack.count - 1;
+
+            if remaining > 0 {
+                client.inventory.set_slot_amount(slot, remaining);
+            } else {
+                client.inventory.set_slot(slot, ItemStack::EMPTY);
+            }
+
+            // Items like bowls take the place of the eaten stack when it is used up.
+            // Otherwise they are added to the inventory, or dropped if it is full.
+            if let Some(converts_to) = properties.using_converts_to {
+                if remaining <= 0 {
+                    client.inventory.set_slot(slot, converts_to);
+                } else {
+                    let left = PlayerInventory::insert_stack(
+                        &mut client.inventory,
+                        client.held_item,
+                        converts_to.clone(),
+                    );
+
+                    if left > 0 {
+                        drop_events.send(DropItemStackEvent {
+                            client: client.entity,
+                            from_slot: None,
+                            stack: converts_to.with_count(left),
+                        });
+                    }
+                }
+            }
+        }
+
+        client.statuses.trigger(EntityStatus::ConsumeItem);
+
+        events.send(FoodEatenEvent {
+            client: client.entity,
+            item: stack.with_count(1),
+        });
+    }
+}
+
+/// Adds the exhaustion of sprinting and jumping.
+fn add_movement_exhaustion(
+    mut events: EventReader<MovementEvent>,
+    mut clients: Query<(&mut HungerState, &Flags, &GameMode)>,
+) {
+    for event in events.read() {
+        let Ok((mut state, flags, game_mode)) = clients.get_mut(event.client) else {
+            continue;
+        };
+
+        if !takes_exhaustion(*game_mode) {
+            continue;
+        }
+
+        let sprinting = flags.sprinting();
+
+        if sprinting && event.on_ground {
+            let distance = event.position.xz().distance(event.old_position.xz()) as f32;
+            state.add_exhaustion(distance * SPRINT_EXHAUSTION_PER_BLOCK);
+        }
+
+        let jumped =
+            event.old_on_ground && !event.on_ground && event.position.y > event.old_position.y;
+
+        if jumped {
+            state.add_exhaustion(if sprinting {
+                SPRINT_JUMP_EXHAUSTION
+            } else {
+                JUMP_EXHAUSTION
+            });
+        }
+    }
+}
+
+/// Adds the exhaustion of taking damage and attacking.
+fn add_damage_exhaustion(
+    mut events: EventReader<DamageAppliedEvent>,
+    mut clients: Query<(&mut HungerState, &GameMode)>,
+    codec: Res<RegistryCodec>,
+) {
+    for event in events.read() {
+        let damage = &event.damage;
+
+        if let Ok((mut state, game_mode)) = clients.get_mut(damage.victim) {
+            if takes_exhaustion(*game_mode) {
+                state.add_exhaustion(damage_exhaustion(&codec, damage));
+            }
+        }
+
+        if damage.damage_type.as_str() != "minecraft:player_attack" {
+            continue;
+        }
+
+        if let Some(Ok((mut state, game_mode))) =
+            damage.source.map(|source| clients.get_mut(source))
+        {
+            if takes_exhaustion(*game_mode) {
+                state.add_exhaustion(ATTACK_EXHAUSTION);
+            }
+        }
+    }
+}
+
+/// Adds the exhaustion of status effects with an [`EffectAction::Exhaust`]
+/// action, such as Hunger.
+fn add_effect_exhaustion(
+    mut events: EventReader<EffectTickEvent>,
+    mut clients: Query<(&mut HungerState, &GameMode)>,
+    behaviors: Res<EffectBehaviors>,
+) {
+    for event in events.read() {
+        let Some(EffectAction::Exhaust(amount)) =
+            behaviors.get(event.effect).map(|behavior| behavior.action)
+        else {
+            continue;
+        };
+
+        if let Ok((mut state, game_mode)) = clients.get_mut(event.entity) {
+            if takes_exhaustion(*game_mode) {
+                state.add_exhaustion(amount(event.amplifier));
+            }
+        }
+    }
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct HungryQuery {
+    entity: Entity,
+    state: &'static mut HungerState,
+    food: &'static mut Food,
+    saturation: &'static mut Saturation,
+    health: &'static mut Health,
+    attributes: Option<&'static EntityAttributes>,
+}
+
+/// Drains saturation and food, and applies natural regeneration and
+/// starvation.
+fn tick_food(
+    mut clients: Query<HungryQuery, Without<Dead>>,
+    settings: Res<HungerSettings>,
+    difficulty: Res<WorldDifficulty>,
+    server: Res<Server>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let peaceful = difficulty.difficulty == Difficulty::Peaceful;
+
+    for mut client in &mut clients {
+        if client.health.0 <= 0.0 {
+            continue;
+        }
+
+        let max_health = client
+            .attributes
+            .and_then(|a| a.get_compute_value(EntityAttribute::GenericMaxHealth))
+            .unwrap_or_else(|| EntityAttribute::GenericMaxHealth.default_value())
+            as f32;
+
+        let hurt = client.health.0 < max_health;
+
+        if peaceful && settings.natural_regeneration {
+            let tick = server.current_tick();
+
+            if hurt && tick % 20 == 0 {
+                heal(&mut client.health, 1.0, max_health);
+            }
+
+            if client.food.0 < MAX_FOOD && tick % 10 == 0 {
+                client.food.0 += 1;
+            }
+        }
+
+        if client.state.exhaustion > EXHAUSTION_PER_FOOD {
+            client.state.exhaustion -= EXHAUSTION_PER_FOOD;
+
+            if client.saturation.0 > 0.0 {
+                client.saturation.0 = (client.saturation.0 - 1.0).max(0.0);
+            } else if !peaceful {
+                client.food.0 = (client.food.0 - 1).max(0);
+            }
+        }
+
+        let food = client.food.0;
+        let saturation = client.saturation.0;
+        let regenerates = settings.natural_regeneration && hurt;
+
+        if regenerates && saturation > 0.0 && food >= MAX_FOOD {
+            // Fast regeneration while saturated.
+            client.state.food_timer += 1;
+
+            if client.state.food_timer >= 10 {
+                let amount = saturation.min(REGENERATION_EXHAUSTION);
+                heal(
+                    &mut client.health,
+                    amount / REGENERATION_EXHAUSTION,
+                    max_health,
+                );
+                client.state.add_exhaustion(amount);
+                client.state.food_timer = 0;
+            }
+        } else if regenerates && food >= 18 {
+            client.state.food_timer += 1;
+
+            if client.state.food_timer >= 80 {
+                heal(&mut client.health, 1.0, max_health);
+                client.state.add_exhaustion(REGENERATION_EXHAUSTION);
+                client.state.food_timer = 0;
+            }
+        } else if food <= 0 {
+            client.state.food_timer += 1;
+
+            if client.state.food_timer >= 80 {
+                let starves = match difficulty.difficulty {
+                    Difficulty::Peaceful => false,
+                    Difficulty::Easy => client.health.0 > 10.0,
+                    Difficulty::Normal => client.health.0 > 1.0,
+                    Difficulty::Hard => true,
+                };
+
+                if starves {
+                    damage_events.send(DamageEvent::new(client.entity, ident!("starve"), 1.0));
+                }
+
+                client.state.food_timer = 0;
+            }
+        } else {
+            client.state.food_timer = 0;
+        }
+    }
+}
+
+fn heal(health: &mut Health, amount: f32, max_health: f32) {
+    health.0 = (health.0 + amount).min(max_health);
+}
+
+/// Creative and spectator players don't get exhausted.
+fn takes_exhaustion(game_mode: GameMode) -> bool {
+    matches!(game_mode, GameMode::Survival | GameMode::Adventure)
+}
+
+/// Returns the exhaustion caused by `damage`, as set in the damage type
+/// registry.
+fn damage_exhaustion(codec: &RegistryCodec, damage: &DamageEvent) -> f32 {
+    let Some(idx) = codec.entry_index(ident!("damage_type"), damage.damage_type.as_str_ident())
+    else {
+        return 0.0;
+    };
+
+    match codec.registry(ident!("damage_type"))[idx]
+        .element
+        .get("exhaustion")
+    {
+        Some(Value::Float(exhaustion)) => *exhaustion,
+        Some(Value::Double(exhaustion)) => *exhaustion as f32,
+        _ => 0.0,
+    }
+}
+
+/// The food data of an item stack, either from its `food` component or the
+/// default of its item kind.
+struct FoodProperties {
+    nutrition: i32,
+    saturation: f32,
+    can_always_eat: bool,
+    eat_seconds: f32,
+    using_converts_to: Option<ItemStack>,
+    /// Status effects and the probability they are applied.
+    effects: Vec<(ActiveStatusEffect, f32)>,
+}
+
+impl FoodProperties {
+    fn of(stack: &ItemStack) -> Option<Self> {
+        if stack.is_empty() || stack.components.is_removed(ItemComponentKind::Food) {
+            return None;
+        }
+
+        if let Some(ItemComponent::Food(food)) = stack.component(ItemComponentKind::Food) {
+            return Some(Self {
+                nutrition: food.nutrition.0,
+                saturation: food.saturation,
+                can_always_eat: food.can_always_eat,
+                eat_seconds: food.eat_seconds,
+                using_converts_to: food.using_converts_to.clone(),
+                effects: food
+                    .effects
+                    .iter()
+                    .map(|effect| {
+                        let details = &effect.effect.details;
+                        let active = ActiveStatusEffect::from_effect(effect.effect.id)
+                            .with_amplifier(details.amplifier.0.clamp(0, 255) as u8)
+                            .with_duration(details.duration.0)
+                            .with_ambient(details.ambient)
+                            .with_show_particles(details.show_particles)
+                            .with_show_icon(details.show_icon);
+
+                        (active, effect.probability)
+                    })
+                    .collect(),
+            });
+        }
+
+        let food = stack.item.food_component()?;
+
+        Some(Self {
+            nutrition: food.nutrition,
+            saturation: food.saturation,
+            can_always_eat: food.can_always_eat,
+            eat_seconds: food.eat_seconds,
+            using_converts_to: food.using_converts_to.map(|item| ItemStack::new(item, 1)),
+            effects: food
+                .effects
+                .iter()
+                .map(|effect| {
+                    let active = ActiveStatusEffect::from_effect(effect.effect)
+                        .with_amplifier(effect.amplifier)
+                        .with_duration(effect.duration)
+                        .with_ambient(effect.ambient)
+                        .with_show_particles(effect.show_particles)
+                        .with_show_icon(effect.show_icon);
+
+                    (active, effect.probability)
+                })
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,891 @@
+//! Server-authoritative block placement.
+//!
+//! [`BlockPlacementPlugin`] is not part of the default plugins. Once added,
+//! clients that use a block item on a block place that block in their
+//! [`VisibleChunkLayer`]. The placed state is computed by [`placement_state`]
+//! from the item, the clicked face, the cursor position and the direction the
+//! player is facing.
+//!
+//! Placements are rejected when the target is not replaceable, when the new
+//! block would intersect a living entity, or when the target is out of reach.
+//! Accepted placements are collected into [`PendingBlockPlacements`], where
+//! they can be changed or cancelled before they are applied. A
+//! [`BlockPlaceEvent`] is sent for every placement that was applied.
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use derive_more::{Deref, DerefMut};
+use valence_server::block::{BlockKind, PropName, PropValue};
+use valence_server::client::{Client, VisibleChunkLayer, VisibleEntityLayers};
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::hitbox::Hitbox;
+use valence_server::entity::living::{Health, LivingEntity};
+use valence_server::entity::{entity, EntityLayerId, Look, Pose, Position};
+use valence_server::event_loop::EventLoopUpdate;
+use valence_server::interact_block::InteractBlockEvent;
+use valence_server::math::{Aabb, DVec3};
+use valence_server::{BlockPos, BlockState, ChunkLayer, Direction, GameMode, Hand, ItemStack};
+
+use crate::player_inventory::PlayerInventory;
+use crate::{HeldItem, Inventory};
+
+pub struct BlockPlacementPlugin;
+
+/// The system sets of the [`BlockPlacementPlugin`], in the order they run.
+///
+/// These sets live in [`EventLoopUpdate`].
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum BlockPlacementSet {
+    /// Block interactions of clients are turned into
+    /// [`PendingBlockPlacements`].
+    Collect,
+    /// Systems that modify or cancel [`PendingBlockPlacements`] should run in
+    /// this set.
+    Modify,
+    /// Pending placements are applied and [`BlockPlaceEvent`]s are sent.
+    Apply,
+}
+
+impl Plugin for BlockPlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlockPlacementSettings>()
+            .init_resource::<PendingBlockPlacements>()
+            .add_event::<BlockPlaceEvent>()
+            .configure_sets(
+                EventLoopUpdate,
+                (
+                    BlockPlacementSet::Collect,
+                    BlockPlacementSet::Modify,
+                    BlockPlacementSet::Apply,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                EventLoopUpdate,
+                collect_block_placements.in_set(BlockPlacementSet::Collect),
+            )
+            .add_systems(
+                EventLoopUpdate,
+                apply_block_placements.in_set(BlockPlacementSet::Apply),
+            );
+    }
+}
+
+/// Global settings for the [`BlockPlacementPlugin`].
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct BlockPlacementSettings {
+    /// Whether clients are prevented from placing blocks out of their reach.
+    pub check_reach: bool,
+    /// The distance in blocks added to the block interaction range of the
+    /// client when checking reach.
+    pub reach_leniency: f64,
+}
+
+impl Default for BlockPlacementSettings {
+    fn default() -> Self {
+        Self {
+            check_reach: true,
+            reach_leniency: 1.0,
+        }
+    }
+}
+
+/// A block placed by a client.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct BlockPlaceEvent {
+    pub client: Entity,
+    /// The hand holding the placed item.
+    pub hand: Hand,
+    /// The position of the placed block.
+    pub position: BlockPos,
+    /// The state of the placed block.
+    pub state: BlockState,
+    /// The block that was replaced by the placed block, such as air or water.
+    pub replaced: BlockState,
+    /// The position of the block that was clicked.
+    pub against: BlockPos,
+    /// The face of the block that was clicked.
+    pub face: Direction,
+    /// The item the block was placed with, before it was consumed.
+    pub item: ItemStack,
+}
+
+/// The block placements that will be applied this tick.
+///
+/// Systems in [`BlockPlacementSet::Modify`] can change the placements in here
+/// or remove entries to cancel them. Blocks that take up two positions, such
+/// as doors and beds, place their other half when they are applied.
+#[derive(Resource, Default, Debug, Deref, DerefMut)]
+pub struct PendingBlockPlacements(pub Vec<BlockPlaceEvent>);
+
+/// Everything about a placement that affects the placed block state.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PlacementContext {
+    /// The position of the placed block.
+    pub position: BlockPos,
+    /// The face of the block that was clicked.
+    pub face: Direction,
+    /// The position that was clicked, relative to [`Self::position`].
+    pub cursor_pos: DVec3,
+    /// The yaw of the player in degrees.
+    pub yaw: f32,
+    /// The pitch of the player in degrees.
+    pub pitch: f32,
+    /// The block that will be replaced.
+    pub replaced: BlockState,
+}
+
+impl PlacementContext {
+    /// Returns the horizontal direction the player is facing.
+    pub fn horizontal_facing(&self) -> Direction {
+        match (self.yaw.rem_euclid(360.0) / 90.0).round() as i32 % 4 {
+            0 => Direction::South,
+            1 => Direction::West,
+            2 => Direction::North,
+            _ => Direction::East,
+        }
+    }
+
+    /// Returns the direction the player is looking in, including up and down.
+    pub fn looking_direction(&self) -> Direction {
+        if self.pitch < -45.0 {
+            Direction::Up
+        } else if self.pitch > 45.0 {
+            Direction::Down
+        } else {
+            self.horizontal_facing()
+        }
+    }
+
+    /// Whether the upper half of the position was clicked, used to decide
+    /// between top and bottom slabs, stairs and trapdoors.
+    fn clicked_upper_half(&self) -> bool {
+        match self.face {
+            Direction::Down => true,
+            Direction::Up => false,
+            _ => self.cursor_pos.y > 0.5,
+        }
+    }
+}
+
+/// Computes the state of a block of `kind` placed in the given context.
+///
+/// This covers the properties that depend on how the block was placed, like
+/// the facing of stairs and furnaces, the half of slabs and doors, the axis of
+/// logs and the wall variants of torches and signs. Properties that depend on
+/// neighboring blocks, like the shape of stairs and the connections of fences,
+/// keep their default values.
+pub fn placement_state(kind: BlockKind, ctx: &PlacementContext) -> BlockState {
+    let name = kind.to_str();
+    let mut state = kind.to_state();
+
+    if name.ends_with("_slab") {
+        if ctx.replaced.to_kind() == kind {
+            return state
+                .set(PropName::Type, PropValue::Double)
+                .set(PropName::Waterlogged, PropValue::False);
+        }
+
+        let half = if ctx.clicked_upper_half() {
+            PropValue::Top
+        } else {
+            PropValue::Bottom
+        };
+
+        state = state.set(PropName::Type, half);
+    } else if name.ends_with("_stairs") {
+        let half = if ctx.clicked_upper_half() {
+            PropValue::Top
+        } else {
+            PropValue::Bottom
+        };
+
+        state = state
+            .set(PropName::Facing, direction_value(ctx.horizontal_facing()))
+            .set(PropName::Half, half);
+    } else if name.ends_with("_trapdoor") {
+        let (facing, half) = match ctx.face {
+            Direction::Up => (opposite(ctx.horizontal_facing()), PropValue::Bottom),
+            Direction::Down => (opposite(ctx.horizontal_facing()), PropValue::Top),
+            face if ctx.cursor_pos.y > 0.5 => (face, PropValue::Top),
+            face => (face, PropValue::Bottom),
+        };
+
+        state = state
+            .set(PropName::Facing, direction_value(facing))
+            .set(PropName::Half, half);
+    } else if name.ends_with("_door") {
+        let facing = ctx.horizontal_facing();
+
+        state = state
+            .set(PropName::Facing, direction_value(facing))
+            .set(PropName::Half, PropValue::Lower)
+            .set(PropName::Hinge, door_hinge(facing, ctx.cursor_pos));
+    } else if name.ends_with("_bed") {
+        state = state
+            .set(PropName::Facing, direction_value(ctx.horizontal_facing()))
+            .set(PropName::Part, PropValue::Foot);
+    } else if state.get(PropName::Face).is_some() {
+        // Buttons, levers and grindstones.
+        let (face, facing) = match ctx.face {
+            Direction::Up => (PropValue::Floor, ctx.horizontal_facing()),
+            Direction::Down => (PropValue::Ceiling, ctx.horizontal_facing()),
+            side => (PropValue::Wall, side),
+        };
+
+        state = state
+            .set(PropName::Face, face)
+            .set(PropName::Facing, direction_value(facing));
+    } else if state.get(PropName::Facing).is_some() {
+        let can_face_vertically = state
+            .set(PropName::Facing, PropValue::Up)
+            .get(PropName::Facing)
+            == Some(PropValue::Up);
+
+        let facing = if kind == BlockKind::Observer {
+            ctx.looking_direction()
+        } else if can_face_vertically {
+            opposite(ctx.looking_direction())
+        } else if name.ends_with("_fence_gate") {
+            ctx.horizontal_facing()
+        } else {
+            // Furnaces, chests and the like face the player.
+            opposite(ctx.horizontal_facing())
+        };
+
+        state = state.set(PropName::Facing, direction_value(facing));
+    }
+
+    if state.get(PropName::Axis).is_some() {
+        let axis = match ctx.face {
+            Direction::Down | Direction::Up => PropValue::Y,
+            Direction::North | Direction::South => PropValue::Z,
+            Direction::West | Direction::East => PropValue::X,
+        };
+
+        state = state.set(PropName::Axis, axis);
+    }
+
+    if state.get(PropName::Rotation).is_some() {
+        // Skulls face the player, while signs and banners face away.
+        let yaw = if name.ends_with("_skull") || name.ends_with("_head") {
+            ctx.yaw
+        } else {
+            ctx.yaw + 180.0
+        };
+
+        let rotation = ((yaw * 16.0 / 360.0 + 0.5).floor() as i32).rem_euclid(16) as u16;
+
+        if let Some(rotation) = PropValue::from_u16(rotation) {
+            state = state.set(PropName::Rotation, rotation);
+        }
+
+        if !matches!(ctx.face, Direction::Up | Direction::Down) {
+            if let Some(wall) = state.wall_block_id() {
+                state = wall.set(PropName::Facing, direction_value(ctx.face));
+            }
+        }
+    } else if !matches!(ctx.face, Direction::Up | Direction::Down) {
+        // Torches have a wall variant but no rotation.
+        if let Some(wall) = state.wall_block_id() {
+            state = wall.set(PropName::Facing, direction_value(ctx.face));
+        }
+    }
+
+    if state.get(PropName::Waterlogged).is_some() {
+        state = state.set(
+            PropName::Waterlogged,
+            PropValue::from_bool(is_water_source(ctx.replaced)),
+        );
+    }
+
+    state
+}
+
+/// Returns the other half of blocks that take up two positions, such as the
+/// upper half of doors and tall plants and the head of beds.
+pub fn other_half(position: BlockPos, state: BlockState) -> Option<(BlockPos, BlockState)> {
+    if state.get(PropName::Half) == Some(PropValue::Lower) {
+        return Some((
+            position.get_in_direction(Direction::Up),
+            state
+                .set(PropName::Half, PropValue::Upper)
+                .set(PropName::Waterlogged, PropValue::False),
+        ));
+    }
+
+    if state.get(PropName::Part) == Some(PropValue::Foot) {
+        let facing = direction_from_value(state.get(PropName::Facing)?)?;
+
+        return Some((
+            position.get_in_direction(facing),
+            state.set(PropName::Part, PropValue::Head),
+        ));
+    }
+
+    None
+}
+
+/// Returns whether `state` is a single slab of `kind`, which becomes a double
+/// slab when another slab of the same kind is placed in it.
+fn is_single_slab(state: BlockState, kind: BlockKind) -> bool {
+    state.to_kind() == kind
+        && kind.to_str().ends_with("_slab")
+        && matches!(
+            state.get(PropName::Type),
+            Some(PropValue::Bottom | PropValue::Top)
+        )
+}
+
+/// Returns whether clicking the single slab `state` on `face` completes it,
+/// instead of placing the new slab next to it.
+fn completes_slab(state: BlockState, face: Direction, cursor_y: f64) -> bool {
+    match state.get(PropName::Type) {
+        Some(PropValue::Bottom) => {
+            face == Direction::Up || (face != Direction::Down && cursor_y > 0.5)
+        }
+        Some(PropValue::Top) => {
+            face == Direction::Down || (face != Direction::Up && cursor_y <= 0.5)
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether right clicking the block opens a screen or changes its
+/// state, which takes precedence over placing a block unless the player is
+/// sneaking.
+///
+/// Only blocks that react to being used with any item are listed. Blocks like
+/// composters and flower pots only react to some items, which are placed
+/// against them as usual.
+fn is_interactable(state: BlockState) -> bool {
+    matches!(
+        state.to_kind(),
+        BlockKind::OakDoor
+            | BlockKind::SpruceDoor
+            | BlockKind::BirchDoor
+            | BlockKind::JungleDoor
+            | BlockKind::AcaciaDoor
+            | BlockKind::CherryDoor
+            | BlockKind::DarkOakDoor
+            | BlockKind::MangroveDoor
+            | BlockKind::BambooDoor
+            | BlockKind::CrimsonDoor
+            | BlockKind::WarpedDoor
+            | BlockKind::CopperDoor
+            | BlockKind::ExposedCopperDoor
+            | BlockKind::WeatheredCopperDoor
+            | BlockKind::OxidizedCopperDoor
+            | BlockKind::WaxedCopperDoor
+            | BlockKind::WaxedExposedCopperDoor
+            | BlockKind::WaxedWeatheredCopperDoor
+            | BlockKind::WaxedOxidizedCopperDoor
+            | BlockKind::OakTrapdoor
+            | BlockKind::SpruceTrapdoor
+            | BlockKind::BirchTrapdoor
+            | BlockKind::JungleTrapdoor
+            | BlockKind::AcaciaTrapdoor
+            | BlockKind::CherryTrapdoor
+            | BlockKind::DarkOakTrapdoor
+            | BlockKind::MangroveTrapdoor
+            | BlockKind::BambooTrapdoor
+            | BlockKind::CrimsonTrapdoor
+            | BlockKind::WarpedTrapdoor
+            | BlockKind::CopperTrapdoor
+            | BlockKind::ExposedCopperTrapdoor
+            | BlockKind::WeatheredCopperTrapdoor
+            | BlockKind::OxidizedCopperTrapdoor
+            | BlockKind::WaxedCopperTrapdoor
+            | BlockKind::WaxedExposedCopperTrapdoor
+            | BlockKind::WaxedWeatheredCopperTrapdoor
+            | BlockKind::WaxedOxidizedCopperTrapdoor
+            | BlockKind::OakFenceGate
+            | BlockKind::SpruceFenceGate
+            | BlockKind::BirchFenceGate
+            | BlockKind::JungleFenceGate
+            | BlockKind::AcaciaFenceGate
+            | BlockKind::CherryFenceGate
+            | BlockKind::DarkOakFenceGate
+            | BlockKind::MangroveFenceGate
+            | BlockKind::BambooFenceGate
+            | BlockKind::CrimsonFenceGate
+            | BlockKind::WarpedFenceGate
+            | BlockKind::StoneButton
+            | BlockKind::PolishedBlackstoneButton
+            | BlockKind::OakButton
+            | BlockKind::SpruceButton
+            | BlockKind::BirchButton
+            | BlockKind::JungleButton
+            | BlockKind::AcaciaButton
+            | BlockKind::CherryButton
+            | BlockKind::DarkOakButton
+            | BlockKind::MangroveButton
+            | BlockKind::BambooButton
+            | BlockKind::CrimsonButton
+            | BlockKind::WarpedButton
+            | BlockKind::WhiteBed
+            | BlockKind::OrangeBed
+            | BlockKind::MagentaBed
+            | BlockKind::LightBlueBed
+            | BlockKind::YellowBed
+            | BlockKind::LimeBed
+            | BlockKind::PinkBed
+            | BlockKind::GrayBed
+            | BlockKind::LightGrayBed
+            | BlockKind::CyanBed
+            | BlockKind::PurpleBed
+            | BlockKind::BlueBed
+            | BlockKind::BrownBed
+            | BlockKind::GreenBed
+            | BlockKind::RedBed
+            | BlockKind::BlackBed
+            | BlockKind::ShulkerBox
+            | BlockKind::WhiteShulkerBox
+            | BlockKind::OrangeShulkerBox
+            | BlockKind::MagentaShulkerBox
+            | BlockKind::LightBlueShulkerBox
+            | BlockKind::YellowShulkerBox
+            | BlockKind::LimeShulkerBox
+            | BlockKind::PinkShulkerBox
+            | BlockKind::GrayShulkerBox
+            | BlockKind::LightGrayShulkerBox
+            | BlockKind::CyanShulkerBox
+            | BlockKind::PurpleShulkerBox
+            | BlockKind::BlueShulkerBox
+            | BlockKind::BrownShulkerBox
+            | BlockKind::GreenShulkerBox
+            | BlockKind::RedShulkerBox
+            | BlockKind::BlackShulkerBox
+            | BlockKind::Chest
+            | BlockKind::TrappedChest
+            | BlockKind::EnderChest
+            | BlockKind::Barrel
+            | BlockKind::Furnace
+            | BlockKind::BlastFurnace
+            | BlockKind::Smoker
+            | BlockKind::BrewingStand
+            | BlockKind::Hopper
+            | BlockKind::Dispenser
+            | BlockKind::Dropper
+            | BlockKind::Crafter
+            | BlockKind::CraftingTable
+            | BlockKind::EnchantingTable
+            | BlockKind::CartographyTable
+            | BlockKind::SmithingTable
+            | BlockKind::Anvil
+            | BlockKind::ChippedAnvil
+            | BlockKind::DamagedAnvil
+            | BlockKind::Loom
+            | BlockKind::Stonecutter
+            | BlockKind::Grindstone
+            | BlockKind::Beacon
+            | BlockKind::Lectern
+            | BlockKind::Lever
+            | BlockKind::NoteBlock
+            | BlockKind::Repeater
+            | BlockKind::Comparator
+            | BlockKind::DaylightDetector
+            | BlockKind::Bell
+            | BlockKind::Cake
+    )
+}
+
+fn is_water_source(state: BlockState) -> bool {
+    state.to_kind() == BlockKind::Water && state.get(PropName::Level) == Some(PropValue::_0)
+}
+
+/// The hinge of a door, following the vanilla rules for doors without
+/// neighbors.
+fn door_hinge(facing: Direction, cursor_pos: DVec3) -> PropValue {
+    let right = match facing {
+        Direction::North => cursor_pos.x > 0.5,
+        Direction::South => cursor_pos.x < 0.5,
+        Direction::West => cursor_pos.z < 0.5,
+        Direction::East => cursor_pos.z > 0.5,
+        Direction::Up | Direction::Down => false,
+    };
+
+    if right {
+        PropValue::Right
+    } else {
+        PropValue::Left
+    }
+}
+
+fn opposite(dir: Direction) -> Direction {
+    match dir {
+        Direction::Down => Direction::Up,
+        Direction::Up => Direction::Down,
+        Direction::North => Direction::South,
+        Direction::South => Direction::North,
+        Direction::West => Direction::East,
+        Direction::East => Direction::West,
+    }
+}
+
+fn direction_value(dir: Direction) -> PropValue {
+    match dir {
+        Direction::Down => PropValue::Down,
+        Direction::Up => PropValue::Up,
+        Direction::North => PropValue::North,
+        Direction::South => PropValue::South,
+        Direction::West => PropValue::West,
+        Direction::East => PropValue::East,
+    }
+}
+
+fn direction_from_value(value: PropValue) -> Option<Direction> {
+    Some(match value {
+        PropValue::Down => Direction::Down,
+        PropValue::Up => Direction::Up,
+        PropValue::North => Direction::North,
+        PropValue::South => Direction::South,
+        PropValue::West => Direction::West,
+        PropValue::East => Direction::East,
+        _ => return None,
+    })
+}
+
+fn block_pos_to_vec(pos: BlockPos) -> DVec3 {
+    DVec3::new(pos.x.into(), pos.y.into(), pos.z.into())
+}
+
+fn block_aabbs(pos: BlockPos, state: BlockState) -> impl Iterator<Item = Aabb> {
+    let offset = block_pos_to_vec(pos);
+    state.collision_shapes().map(move |shape| shape + offset)
+}
+
+fn held_slot(hand: Hand, held_item: &HeldItem) -> u16 {
+    match hand {
+        Hand::Main => held_item.slot(),
+        Hand::Off => PlayerInventory::SLOT_OFFHAND,
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn collect_block_placements(
+    mut events: EventReader<InteractBlockEvent>,
+    mut clients: Query<
+        (
+            &mut Inventory,
+            &HeldItem,
+            &GameMode,
+            &Position,
+            &Look,
+            Option<&entity::Pose>,
+            Option<&EntityAttributes>,
+            &VisibleChunkLayer,
+            &VisibleEntityLayers,
+        ),
+        With<Client>,
+    >,
+    entities: Query<
+        (&Hitbox, &EntityLayerId, Option<&GameMode>, Option<&Health>),
+        With<LivingEntity>,
+    >,
+    layers: Query<&ChunkLayer>,
+    settings: Res<BlockPlacementSettings>,
+    mut pending: ResMut<PendingBlockPlacements>,
+) {
+    for event in events.read() {
+        let Ok((
+            mut inventory,
+            held_item,
+            game_mode,
+            pos,
+            look,
+            pose,
+            attributes,
+            chunk_layer,
+            entity_layers,
+        )) = clients.get_mut(event.client)
+        else {
+            continue;
+        };
+
+        if matches!(game_mode, GameMode::Adventure | GameMode::Spectator) {
+            continue;
+        }
+
+        let slot = held_slot(event.hand, held_item);
+        let item = inventory.slot(slot).clone();
+
+        if item.is_empty() {
+            continue;
+        }
+
+        let Some(kind) = BlockKind::from_item_kind(item.item) else {
+            continue;
+        };
+
+        let Ok(layer) = layers.get(chunk_layer.0) else {
+            continue;
+        };
+
+        let Some(clicked) = layer.block(event.position).map(|b| b.state) else {
+            continue;
+        };
+
+        let sneaking = pose.is_some_and(|pose| pose.0 == Pose::Sneaking);
+
+        if !sneaking && is_interactable(clicked) {
+            continue;
+        }
+
+        // The client predicted the placement and consumed the item, so resend
+        // the slot in case the placement is rejected or cancelled.
+        inventory.changed |= 1 << slot;
+
+        let clicked_pos = block_pos_to_vec(event.position);
+
+        if settings.check_reach {
+            let eye_height = if sneaking { 1.27 } else { 1.62 };
+            let eye_pos = pos.0 + DVec3::new(0.0, eye_height, 0.0);
+            let block_box = Aabb::from_bottom_size(clicked_pos, DVec3::ONE);
+
+            let range = attributes
+                .and_then(|a| a.get_compute_value(EntityAttribute::PlayerBlockInteractionRange))
+                .unwrap_or_else(|| EntityAttribute::PlayerBlockInteractionRange.default_value());
+
+            if block_box.distance_to_point(eye_pos) > range + settings.reach_leniency {
+                continue;
+            }
+        }
+
+        let cursor_y = f64::from(event.cursor_pos.y);
+
+        // Replaceable blocks like grass are replaced by the placed block, and
+        // clicking a slab with the same slab can complete it.
+        let position = if (clicked.is_replaceable() && clicked.to_kind() != kind)
+            || (is_single_slab(clicked, kind) && completes_slab(clicked, event.face, cursor_y))
+        {
+            event.position
+        } else {
+            event.position.get_in_direction(event.face)
+        };
+
+        let Some(replaced) = layer.block(position).map(|b| b.state) else {
+            continue;
+        };
+
+        if !replaced.is_replaceable() && !is_single_slab(replaced, kind) {
+            continue;
+        }
+
+        let ctx = PlacementContext {
+            position,
+            face: event.face,
+            cursor_pos: clicked_pos + event.cursor_pos.as_dvec3() - block_pos_to_vec(position),
+            yaw: look.yaw,
+            pitch: look.pitch,
+            replaced,
+        };
+
+        let state = placement_state(kind, &ctx);
+
+        let other = other_half(position, state);
+
+        if let Some((other_pos, _)) = other {
+            if !layer
+                .block(other_pos)
+                .is_some_and(|b| b.state.is_replaceable())
+            {
+                continue;
+            }
+        }
+
+        let obstructed = entities
+            .iter()
+            .filter(|(_, layer_id, game_mode, health)| {
+                entity_layers.0.contains(&layer_id.0)
+                    && *game_mode != Some(&GameMode::Spectator)
+                    && health.map_or(true, |health| health.0 > 0.0)
+            })
+            .any(|(hitbox, ..)| {
+                let hitbox = hitbox.get();
+
+                block_aabbs(position, state)
+                    .chain(
+                        other
+                            .into_iter()
+                            .flat_map(|(pos, state)| block_aabbs(pos, state)),
+                    )
+                    .any(|aabb| aabb.intersects(hitbox))
+            });
+
+        if obstructed {
+            continue;
+        }
+
+        pending.push(BlockPlaceEvent {
+            client: event.client,
+            hand: event.hand,
+            position,
+            state,
+            replaced,
+            against: event.position,
+            face: event.face,
+            item,
+        });
+    }
+}
+
+fn apply_block_placements(
+    mut clients: Query<(&mut Inventory, &HeldItem, &GameMode, &VisibleChunkLayer)>,
+    mut layers: Query<&mut ChunkLayer>,
+    mut pending: ResMut<PendingBlockPlacements>,
+    mut events: EventWriter<BlockPlaceEvent>,
+) {
+    for placement in pending.drain(..) {
+        let Ok((mut inventory, held_item, game_mode, chunk_layer)) =
+            clients.get_mut(placement.client)
+        else {
+            continue;
+        };
+
+        let Ok(mut layer) = layers.get_mut(chunk_layer.0) else {
+            continue;
+        };
+
+        // Another placement in the same tick may have taken the position.
+        if layer.block(placement.position).map(|b| b.state) != Some(placement.replaced) {
+            continue;
+        }
+
+        let other = other_half(placement.position, placement.state);
+
+        if let Some((other_pos, other_state)) = other {
+            if !layer
+                .block(other_pos)
+                .is_some_and(|b| b.state.is_replaceable())
+            {
+                continue;
+            }
+
+            layer.set_block(other_pos, other_state);
+        }
+
+        layer.set_block(placement.position, placement.state);
+
+        if *game_mode != GameMode::Creative {
+            let slot = held_slot(placement.hand, held_item);
+            let stack = inventory.slot(slot);
+
+            if stack.count > 1 {
+                let amount = stack.count - 1;
+                inventory.set_slot_amount(slot, amount);
+            } else {
+                inventory.set_slot(slot, ItemStack::EMPTY);
+            }
+        }
+
+        events.send(placement);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(face: Direction, cursor_pos: [f64; 3], yaw: f32) -> PlacementContext {
+        PlacementContext {
+            position: BlockPos::new(0, 64, 0),
+            face,
+            cursor_pos: cursor_pos.into(),
+            yaw,
+            pitch: 0.0,
+            replaced: BlockState::AIR,
+        }
+    }
+
+    #[test]
+    fn slab_half_follows_cursor() {
+        let top = context(Direction::North, [0.5, 0.75, 1.0], 0.0);
+        let bottom = context(Direction::North, [0.5, 0.25, 1.0], 0.0);
+
+        assert_eq!(
+            placement_state(BlockKind::OakSlab, &top).get(PropName::Type),
+            Some(PropValue::Top)
+        );
+        assert_eq!(
+            placement_state(BlockKind::OakSlab, &bottom).get(PropName::Type),
+            Some(PropValue::Bottom)
+        );
+
+        let double = PlacementContext {
+            replaced: BlockState::OAK_SLAB,
+            ..context(Direction::Up, [0.5, 0.5, 0.5], 0.0)
+        };
+
+        assert_eq!(
+            placement_state(BlockKind::OakSlab, &double).get(PropName::Type),
+            Some(PropValue::Double)
+        );
+    }
+
+    #[test]
+    fn facing_follows_player() {
+        // Looking north.
+        let ctx = context(Direction::Up, [0.5, 0.0, 0.5], 180.0);
+
+        assert_eq!(
+            placement_state(BlockKind::OakStairs, &ctx).get(PropName::Facing),
+            Some(PropValue::North)
+        );
+        assert_eq!(
+            placement_state(BlockKind::Furnace, &ctx).get(PropName::Facing),
+            Some(PropValue::South)
+        );
+    }
+
+    #[test]
+    fn log_axis_follows_face() {
+        let ctx = context(Direction::East, [0.0, 0.5, 0.5], 0.0);
+
+        assert_eq!(
+            placement_state(BlockKind::OakLog, &ctx).get(PropName::Axis),
+            Some(PropValue::X)
+        );
+    }
+
+    #[test]
+    fn torch_on_wall() {
+        let ctx = context(Direction::West, [1.0, 0.5, 0.5], 0.0);
+
+        let state = placement_state(BlockKind::Torch, &ctx);
+
+        assert_eq!(state.to_kind(), BlockKind::WallTorch);
+        assert_eq!(state.get(PropName::Facing), Some(PropValue::West));
+    }
+
+    #[test]
+    fn door_and_bed_halves() {
+        let ctx = context(Direction::Up, [0.5, 0.0, 0.5], 0.0);
+        let pos = BlockPos::new(0, 64, 0);
+
+        let door = placement_state(BlockKind::OakDoor, &ctx);
+        let (upper_pos, upper) = other_half(pos, door).unwrap();
+
+        assert_eq!(upper_pos, BlockPos::new(0, 65, 0));
+        assert_eq!(upper.get(PropName::Half), Some(PropValue::Upper));
+
+        // Looking south, so the head of the bed is south of the foot.
+        let bed = placement_state(BlockKind::RedBed, &ctx);
+        let (head_pos, head) = other_half(pos, bed).unwrap();
+
+        assert_eq!(head_pos, BlockPos::new(0, 64, 1));
+        assert_eq!(head.get(PropName::Part), Some(PropValue::Head));
+    }
+
+    #[test]
+    fn interactable_blocks() {
+        assert!(is_interactable(BlockState::CHEST));
+        assert!(is_interactable(BlockState::SHULKER_BOX));
+        assert!(is_interactable(BlockState::OAK_DOOR));
+        assert!(is_interactable(BlockState::COPPER_TRAPDOOR));
+
+        assert!(!is_interactable(BlockState::IRON_DOOR));
+        assert!(!is_interactable(BlockState::FLETCHING_TABLE));
+        assert!(!is_interactable(BlockState::STONE));
+    }
+}
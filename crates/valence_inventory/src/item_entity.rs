@@ -0,0 +1,432 @@
+//! Item entities for dropped item stacks.
+//!
+//! [`ItemEntityPlugin`] is not part of the default plugins. Once added, item
+//! stacks dropped by players (see [`DropItemStackEvent`]) are spawned as item
+//! entities that can be picked up by other players after a short delay.
+//! Nearby identical item entities are merged into a single entity, and item
+//! entities that are not picked up despawn after five minutes.
+//!
+//! Item entities only fall and slide when the
+//! [`PhysicsPlugin`](valence_server::physics::PhysicsPlugin) is added as well.
+
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use rand::Rng;
+use valence_server::client::Client;
+use valence_server::entity::item::{ItemEntityBundle, Stack};
+use valence_server::entity::living::Health;
+use valence_server::entity::{
+    EntityId, EntityLayerId, InitEntitiesSet, Look, Position, UpdateTrackedDataSet, Velocity,
+};
+use valence_server::event_loop::EventLoopUpdate;
+use valence_server::math::{Aabb, DVec3};
+use valence_server::protocol::packets::play::ItemPickupAnimationS2c;
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::{Despawned, EntityLayer, GameMode, ItemStack, Layer};
+
+use crate::player_inventory::PlayerInventory;
+use crate::{update_player_inventories, DropItemStackEvent, HeldItem, Inventory};
+
+/// The pickup delay of item entities that were not dropped by a player.
+pub const DEFAULT_PICKUP_DELAY: u32 = 10;
+
+/// The pickup delay of item entities dropped by a player.
+pub const DROPPED_PICKUP_DELAY: u32 = 40;
+
+/// The age in ticks at which item entities despawn.
+pub const DESPAWN_AGE: u32 = 6000;
+
+/// The height of a standing player's eyes above their feet.
+const PLAYER_EYE_HEIGHT: f64 = 1.62;
+
+/// The size of the hitbox of item entities.
+const ITEM_SIZE: f64 = 0.25;
+
+/// The size of the hitbox of players.
+const PLAYER_SIZE: DVec3 = DVec3::new(0.6, 1.8, 0.6);
+
+/// How far the hitbox of a player is grown in each direction to find the
+/// item entities it touches.
+const PICKUP_REACH: DVec3 = DVec3::new(1.0, 0.5, 1.0);
+
+/// How far the hitbox of an item entity is grown horizontally to find the
+/// item entities it can merge with.
+const MERGE_REACH: f64 = 0.5;
+
+pub struct ItemEntityPlugin;
+
+impl Plugin for ItemEntityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, init_item_entities)
+            .add_systems(EventLoopUpdate, spawn_dropped_item_stacks)
+            .add_systems(
+                PostUpdate,
+                (
+                    tick_item_entities,
+                    merge_item_entities,
+                    pickup_item_entities,
+                )
+                    .chain()
+                    .after(InitEntitiesSet)
+                    .before(UpdateTrackedDataSet)
+                    .before(update_player_inventories),
+            )
+            .add_event::<PickupItemEvent>();
+    }
+}
+
+/// The number of ticks before an item entity can be picked up.
+///
+/// Added to item entities without one when they are spawned.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PickupDelay(pub u32);
+
+/// The number of ticks an item entity has existed for. The item entity is
+/// despawned once this reaches [`DESPAWN_AGE`].
+///
+/// Added to item entities without one when they are spawned.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct ItemAge(pub u32);
+
+/// Sent when a player picks up an item entity.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct PickupItemEvent {
+    pub client: Entity,
+    pub item_entity: Entity,
+    /// The part of the item stack that was moved into the inventory of the
+    /// client.
+    pub stack: ItemStack,
+}
+
+fn init_item_entities(
+    items: Query<(Entity, Has<PickupDelay>, Has<ItemAge>), Added<Stack>>,
+    mut commands: Commands,
+) {
+    for (entity, has_delay, has_age) in &items {
+        let mut entity = commands.entity(entity);
+
+        if !has_delay {
+            entity.insert(PickupDelay(DEFAULT_PICKUP_DELAY));
+        }
+
+        if !has_age {
+            entity.insert(ItemAge::default());
+        }
+    }
+}
+
+/// Spawns item stacks dropped by players in front of their eyes.
+fn spawn_dropped_item_stacks(
+    mut events: EventReader<DropItemStackEvent>,
+    clients: Query<(&Position, &Look, &EntityLayerId)>,
+    mut commands: Commands,
+) {
+    let mut rng = rand::thread_rng();
+
+    for event in events.read() {
+        if event.stack.is_empty() {
+            continue;
+        }
+
+        let Ok((pos, look, layer)) = clients.get(event.client) else {
+            continue;
+        };
+
+        let (yaw_sin, yaw_cos) = f64::from(look.yaw).to_radians().sin_cos();
+        let (pitch_sin, pitch_cos) = f64::from(look.pitch).to_radians().sin_cos();
+
+        // A small random push in a random direction, so that stacks dropped
+        // in quick succession spread out.
+        let (spread_sin, spread_cos) = (rng.gen::<f64>() * TAU).sin_cos();
+        let spread = rng.gen::<f64>() * 0.02;
+
+        // In blocks per tick.
+        let velocity = DVec3::new(
+            -yaw_sin * pitch_cos * 0.3 + spread_cos * spread,
+            -pitch_sin * 0.3 + 0.1 + (rng.gen::<f64>() - rng.gen::<f64>()) * 0.1,
+            yaw_cos * pitch_cos * 0.3 + spread_sin * spread,
+        );
+
+        commands.spawn((
+            ItemEntityBundle {
+                position: Position(pos.0 + DVec3::new(0.0, PLAYER_EYE_HEIGHT - 0.3, 0.0)),
+                velocity: Velocity(velocity.as_vec3() * 20.0),
+                layer: *layer,
+                item_stack: Stack(event.stack.clone()),
+                ..Default::default()
+            },
+            PickupDelay(DROPPED_PICKUP_DELAY),
+            ItemAge::default(),
+        ));
+    }
+}
+
+/// Counts down the pickup delay and despawns old item entities.
+fn tick_item_entities(
+    mut items: Query<(Entity, &mut PickupDelay, &mut ItemAge), Without<Despawned>>,
+    mut commands: Commands,
+) {
+    for (entity, mut delay, mut age) in &mut items {
+        if delay.0 > 0 {
+            delay.0 -= 1;
+        }
+
+        age.0 += 1;
+
+        if age.0 >= DESPAWN_AGE {
+            commands.entity(entity).insert(Despawned);
+        }
+    }
+}
+
+/// Merges item entities with identical items that are close to each other.
+/// The larger stack takes in as much of the smaller stack as it can.
+fn merge_item_entities(
+    mut items: Query<
+        (
+            Entity,
+            &EntityLayerId,
+            &Position,
+            &mut Stack,
+            &mut PickupDelay,
+            &mut ItemAge,
+        ),
+        Without<Despawned>,
+    >,
+    mut commands: Commands,
+) {
+    // Item entities that can still grow, grouped by their layer and block
+    // column.
+    let mut columns: HashMap<(Entity, i64, i64), Vec<Entity>> = HashMap::new();
+
+    for (entity, layer, pos, stack, ..) in &items {
+        if stack.0.count < stack.0.item.max_stack() {
+            columns
+                .entry((layer.0, pos.0.x.floor() as i64, pos.0.z.floor() as i64))
+                .or_default()
+                .push(entity);
+        }
+    }
+
+    for (&(layer, x, z), entities) in &columns {
+        // The merge reach is smaller than a block, so only the surrounding
+        // columns need to be searched.
+        let neighbors = (-1..=1)
+            .flat_map(|dx| (-1..=1).map(move |dz| (layer, x + dx, z + dz)))
+            .filter_map(|column| columns.get(&column))
+            .flatten();
+
+        for &a in entities {
+            for &b in neighbors.clone() {
+                // Visit each pair only once.
+                if a >= b {
+                    continue;
+                }
+
+                let Ok([a, b]) = items.get_many_mut([a, b]) else {
+                    continue;
+                };
+
+                let (a_entity, _, a_pos, mut a_stack, mut a_delay, mut a_age) = a;
+                let (b_entity, _, b_pos, mut b_stack, mut b_delay, mut b_age) = b;
+
+                let reach = grow(
+                    item_aabb(a_pos.0),
+                    DVec3::new(MERGE_REACH, 0.0, MERGE_REACH),
+                );
+
+                if !can_merge(&a_stack.0, &b_stack.0) || !reach.intersects(item_aabb(b_pos.0)) {
+                    continue;
+                }
+
+                let (source, target) = if a_stack.0.count < b_stack.0.count {
+                    (&mut a_stack.0, &mut b_stack.0)
+                } else {
+                    (&mut b_stack.0, &mut a_stack.0)
+                };
+
+                let moved = (target.item.max_stack() - target.count).min(source.count);
+                target.count += moved;
+                source.count -= moved;
+
+                // Both entities take on the longer delay and the younger age.
+                a_delay.0 = a_delay.0.max(b_delay.0);
+                b_delay.0 = a_delay.0;
+                a_age.0 = a_age.0.min(b_age.0);
+                b_age.0 = a_age.0;
+
+                for (entity, stack) in [(a_entity, &a_stack), (b_entity, &b_stack)] {
+                    if stack.0.is_empty() {
+                        commands.entity(entity).insert(Despawned);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn can_merge(a: &ItemStack, b: &ItemStack) -> bool {
+    !a.is_empty()
+        && !b.is_empty()
+        && a.item == b.item
+        && a.components == b.components
+        && a.count < a.item.max_stack()
+        && b.count < b.item.max_stack()
+}
+
+/// Moves the item entities players touch into their inventories.
+#[allow(clippy::type_complexity)]
+fn pickup_item_entities(
+    mut clients: Query<(
+        Entity,
+        &mut Client,
+        &EntityId,
+        &EntityLayerId,
+        &Position,
+        &GameMode,
+        Option<&Health>,
+        &mut Inventory,
+        &HeldItem,
+    )>,
+    mut items: Query<
+        (
+            Entity,
+            &EntityId,
+            &EntityLayerId,
+            &Position,
+            &mut Stack,
+            &PickupDelay,
+        ),
+        Without<Despawned>,
+    >,
+    mut layers: Query<&mut EntityLayer>,
+    mut pickup_events: EventWriter<PickupItemEvent>,
+    mut commands: Commands,
+) {
+    for (
+        client_entity,
+        mut client,
+        client_id,
+        client_layer,
+        client_pos,
+        game_mode,
+        health,
+        mut inventory,
+        held_item,
+    ) in &mut clients
+    {
+        if *game_mode == GameMode::Spectator || health.is_some_and(|health| health.0 <= 0.0) {
+            continue;
+        }
+
+        let reach = grow(
+            Aabb::from_bottom_size(client_pos.0, PLAYER_SIZE),
+            PICKUP_REACH,
+        );
+
+        for (item_entity, item_id, item_layer, item_pos, mut stack, delay) in &mut items {
+            if delay.0 > 0
+                || item_layer != client_layer
+                || stack.0.is_empty()
+                || !reach.intersects(item_aabb(item_pos.0))
+            {
+                continue;
+            }
+
+            let remaining =
+                PlayerInventory::insert_stack(&mut inventory, held_item, stack.0.clone());
+            let picked_up = stack.0.count - remaining;
+
+            if picked_up <= 0 {
+                continue;
+            }
+
+            let mut pkt = ItemPickupAnimationS2c {
+                collected_entity_id: VarInt(item_id.get()),
+                collector_entity_id: VarInt(client_id.get()),
+                pickup_item_count: VarInt(picked_up.into()),
+            };
+
+            if let Ok(mut layer) = layers.get_mut(item_layer.0) {
+                layer
+                    .view_except_writer(item_pos.0, client_entity)
+                    .write_packet(&pkt);
+            }
+
+            // Clients see themselves with the entity ID 0.
+            pkt.collector_entity_id = VarInt(0);
+            client.write_packet(&pkt);
+
+            pickup_events.send(PickupItemEvent {
+                client: client_entity,
+                item_entity,
+                stack: stack.0.clone().with_count(picked_up),
+            });
+
+            if remaining == 0 {
+                stack.0.count = 0;
+                commands.entity(item_entity).insert(Despawned);
+            } else {
+                stack.0.count = remaining;
+            }
+        }
+    }
+}
+
+fn item_aabb(pos: DVec3) -> Aabb {
+    Aabb::from_bottom_size(pos, DVec3::splat(ITEM_SIZE))
+}
+
+/// Grows a bounding box by `amount` in both directions of each axis.
+fn grow(aabb: Aabb, amount: DVec3) -> Aabb {
+    Aabb::new(aabb.min() - amount, aabb.max() + amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_server::ItemKind;
+
+    use super::*;
+
+    #[test]
+    fn insert_prefers_existing_stacks() {
+        let mut inventory = Inventory::new(crate::InventoryKind::Player);
+        let held_item = HeldItem { held_item_slot: 36 };
+
+        inventory.set_slot(20, ItemStack::new(ItemKind::Stone, 60));
+
+        let remaining = PlayerInventory::insert_stack(
+            &mut inventory,
+            &held_item,
+            ItemStack::new(ItemKind::Stone, 10),
+        );
+
+        assert_eq!(remaining, 0);
+        assert_eq!(inventory.slot(20), &ItemStack::new(ItemKind::Stone, 64));
+        assert_eq!(inventory.slot(36), &ItemStack::new(ItemKind::Stone, 6));
+    }
+
+    #[test]
+    fn insert_into_full_inventory() {
+        let mut inventory = Inventory::new(crate::InventoryKind::Player);
+        let held_item = HeldItem { held_item_slot: 36 };
+
+        for slot in PlayerInventory::SLOTS_MAIN {
+            inventory.set_slot(slot, ItemStack::new(ItemKind::Dirt, 64));
+        }
+
+        inventory.set_slot(40, ItemStack::new(ItemKind::Stone, 62));
+
+        let remaining = PlayerInventory::insert_stack(
+            &mut inventory,
+            &held_item,
+            ItemStack::new(ItemKind::Stone, 5),
+        );
+
+        assert_eq!(remaining, 3);
+        assert_eq!(inventory.slot(40), &ItemStack::new(ItemKind::Stone, 64));
+    }
+}
@@ -9,7 +9,9 @@ use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use derive_more::{Deref, DerefMut};
 use player_inventory::PlayerInventory;
+use simulate::DragState;
 use tracing::{debug, warn};
+use valence_server::action::DiggingEquipment;
 use valence_server::client::{Client, FlushPacketsSet, SpawnClientsSet};
 use valence_server::event_loop::{EventLoopPreUpdate, PacketEvent};
 use valence_server::interact_block::InteractBlockEvent;
@@ -25,7 +27,10 @@ use valence_server::protocol::{VarInt, WritePacket};
 use valence_server::text::IntoText;
 use valence_server::{GameMode, Hand, ItemKind, ItemStack, Text};
 
+pub mod block_placement;
+pub mod item_entity;
 pub mod player_inventory;
+mod simulate;
 mod validate;
 
 pub struct InventoryPlugin;
@@ -44,6 +49,7 @@ impl Plugin for InventoryPlugin {
                 update_open_inventories,
                 update_player_inventories,
                 update_cursor_item,
+                update_digging_equipment,
             )
                 .before(FlushPacketsSet),
         )
@@ -112,7 +118,7 @@ impl Inventory {
     /// # use valence_inventory::*;
     /// # use valence_server::item::{ItemStack, ItemKind};
     /// let mut inv = Inventory::new(InventoryKind::Generic9x1);
-    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1, None));
+    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1));
     /// assert_eq!(inv.slot(0).item, ItemKind::Diamond);
     /// ```
     #[track_caller]
@@ -130,8 +136,8 @@ impl Inventory {
     /// # use valence_inventory::*;
     /// # use valence_server::item::{ItemStack, ItemKind};
     /// let mut inv = Inventory::new(InventoryKind::Generic9x1);
-    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1, None));
-    /// let old = inv.replace_slot(0, ItemStack::new(ItemKind::IronIngot, 1, None));
+    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1));
+    /// let old = inv.replace_slot(0, ItemStack::new(ItemKind::IronIngot, 1));
     /// assert_eq!(old.item, ItemKind::Diamond);
     /// ```
     #[track_caller]
@@ -156,7 +162,7 @@ impl Inventory {
     /// # use valence_inventory::*;
     /// # use valence_server::item::{ItemStack, ItemKind};
     /// let mut inv = Inventory::new(InventoryKind::Generic9x1);
-    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1, None));
+    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1));
     /// assert!(inv.slot(1).is_empty());
     /// inv.swap_slot(0, 1);
     /// assert_eq!(inv.slot(1).item, ItemKind::Diamond);
@@ -191,7 +197,7 @@ impl Inventory {
     /// # use valence_inventory::*;
     /// # use valence_server::item::{ItemStack, ItemKind};
     /// let mut inv = Inventory::new(InventoryKind::Generic9x1);
-    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1, None));
+    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1));
     /// inv.set_slot_amount(0, 64);
     /// assert_eq!(inv.slot(0).count, 64);
     /// ```
@@ -271,9 +277,9 @@ impl Inventory {
     /// # use valence_inventory::*;
     /// # use valence_server::item::*;
     /// let mut inv = Inventory::new(InventoryKind::Generic9x1);
-    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1, None));
-    /// inv.set_slot(2, ItemStack::new(ItemKind::GoldIngot, 1, None));
-    /// inv.set_slot(3, ItemStack::new(ItemKind::IronIngot, 1, None));
+    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1));
+    /// inv.set_slot(2, ItemStack::new(ItemKind::GoldIngot, 1));
+    /// inv.set_slot(3, ItemStack::new(ItemKind::IronIngot, 1));
     /// assert_eq!(inv.first_empty_slot_in(0..6), Some(1));
     /// assert_eq!(inv.first_empty_slot_in(2..6), Some(4));
     /// ```
@@ -295,9 +301,9 @@ impl Inventory {
     /// # use valence_inventory::*;
     /// # use valence_server::item::*;
     /// let mut inv = Inventory::new(InventoryKind::Generic9x1);
-    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1, None));
-    /// inv.set_slot(2, ItemStack::new(ItemKind::GoldIngot, 1, None));
-    /// inv.set_slot(3, ItemStack::new(ItemKind::IronIngot, 1, None));
+    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1));
+    /// inv.set_slot(2, ItemStack::new(ItemKind::GoldIngot, 1));
+    /// inv.set_slot(3, ItemStack::new(ItemKind::IronIngot, 1));
     /// assert_eq!(inv.first_empty_slot(), Some(1));
     /// ```
     #[inline]
@@ -311,10 +317,10 @@ impl Inventory {
     /// # use valence_inventory::*;
     /// # use valence_server::item::*;
     /// let mut inv = Inventory::new(InventoryKind::Generic9x1);
-    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1, None));
-    /// inv.set_slot(2, ItemStack::new(ItemKind::GoldIngot, 64, None));
-    /// inv.set_slot(3, ItemStack::new(ItemKind::IronIngot, 1, None));
-    /// inv.set_slot(4, ItemStack::new(ItemKind::GoldIngot, 1, None));
+    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1));
+    /// inv.set_slot(2, ItemStack::new(ItemKind::GoldIngot, 64));
+    /// inv.set_slot(3, ItemStack::new(ItemKind::IronIngot, 1));
+    /// inv.set_slot(4, ItemStack::new(ItemKind::GoldIngot, 1));
     /// assert_eq!(
     ///     inv.first_slot_with_item_in(ItemKind::GoldIngot, 64, 0..5),
     ///     Some(4)
@@ -345,10 +351,10 @@ impl Inventory {
     /// # use valence_inventory::*;
     /// # use valence_server::item::*;
     /// let mut inv = Inventory::new(InventoryKind::Generic9x1);
-    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1, None));
-    /// inv.set_slot(2, ItemStack::new(ItemKind::GoldIngot, 64, None));
-    /// inv.set_slot(3, ItemStack::new(ItemKind::IronIngot, 1, None));
-    /// inv.set_slot(4, ItemStack::new(ItemKind::GoldIngot, 1, None));
+    /// inv.set_slot(0, ItemStack::new(ItemKind::Diamond, 1));
+    /// inv.set_slot(2, ItemStack::new(ItemKind::GoldIngot, 64));
+    /// inv.set_slot(3, ItemStack::new(ItemKind::IronIngot, 1));
+    /// inv.set_slot(4, ItemStack::new(ItemKind::GoldIngot, 1));
     /// assert_eq!(inv.first_slot_with_item(ItemKind::GoldIngot, 64), Some(4));
     /// ```
     #[inline]
@@ -372,6 +378,8 @@ pub struct ClientInventoryState {
     /// This is so we can inform the user of the update through change detection
     /// when they differ in a given tick
     client_updated_cursor_item: Option<ItemStack>,
+    /// The drag the client is in the middle of, if any.
+    drag: DragState,
 }
 
 impl ClientInventoryState {
@@ -451,12 +459,12 @@ impl OpenInventory {
 /// # use valence_inventory::*;
 /// # use valence_server::item::*;
 /// let mut player_inventory = Inventory::new(InventoryKind::Player);
-/// player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 1, None));
+/// player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 1));
 ///
 /// let target_inventory = Inventory::new(InventoryKind::Generic9x3);
 /// let window = InventoryWindow::new(&player_inventory, Some(&target_inventory));
 ///
-/// assert_eq!(window.slot(54), &ItemStack::new(ItemKind::Diamond, 1, None));
+/// assert_eq!(window.slot(54), &ItemStack::new(ItemKind::Diamond, 1));
 /// ```
 pub struct InventoryWindow<'a> {
     player_inventory: &'a Inventory,
@@ -509,11 +517,11 @@ impl<'a> InventoryWindow<'a> {
 /// let mut target_inventory = Inventory::new(InventoryKind::Generic9x3);
 /// let mut window = InventoryWindowMut::new(&mut player_inventory, Some(&mut target_inventory));
 ///
-/// window.set_slot(54, ItemStack::new(ItemKind::Diamond, 1, None));
+/// window.set_slot(54, ItemStack::new(ItemKind::Diamond, 1));
 ///
 /// assert_eq!(
 ///     player_inventory.slot(36),
-///     &ItemStack::new(ItemKind::Diamond, 1, None)
+///     &ItemStack::new(ItemKind::Diamond, 1)
 /// );
 /// ```
 pub struct InventoryWindowMut<'a> {
@@ -591,6 +599,7 @@ fn init_new_client_inventories(clients: Query<Entity, Added<Client>>, mut comman
                 state_id: Wrapping(0),
                 slots_changed: 0,
                 client_updated_cursor_item: None,
+                drag: DragState::default(),
             },
             HeldItem {
                 // First slot of the hotbar.
@@ -834,6 +843,15 @@ fn update_client_on_close_inventory(
 }
 
 // TODO: make this event user friendly.
+/// Sent when a client clicks a slot in a way the server agrees with. Clicks
+/// whose predicted result differs from the server's simulation are resynced
+/// instead.
+///
+/// `slot_changes` and `carried_item` are the result of the click as computed by
+/// the server, or as claimed by the client for shift clicks the server doesn't
+/// simulate. Claimed results may only move items around without changing
+/// their components. Clicks that would modify a readonly [`Inventory`] are
+/// not applied, but the event is still sent.
 #[derive(Event, Clone, Debug)]
 pub struct ClickSlotEvent {
     pub client: Entity,
@@ -861,6 +879,7 @@ fn handle_click_slot(
         &mut ClientInventoryState,
         Option<&mut OpenInventory>,
         &mut CursorItem,
+        &GameMode,
     )>,
     mut inventories: Query<&mut Inventory, Without<Client>>,
     mut drop_item_stack_events: EventWriter<DropItemStackEvent>,
@@ -872,8 +891,14 @@ fn handle_click_slot(
             continue;
         };
 
-        let Ok((mut client, mut client_inv, mut inv_state, open_inventory, mut cursor_item)) =
-            clients.get_mut(packet.client)
+        let Ok((
+            mut client,
+            mut client_inv,
+            mut inv_state,
+            mut open_inventory,
+            mut cursor_item,
+            game_mode,
+        )) = clients.get_mut(packet.client)
         else {
             // The client does not exist, ignore.
             continue;
@@ -913,7 +938,13 @@ fn handle_click_slot(
         if pkt.slot_idx == -999 && pkt.mode == ClickMode::Click {
             // The client is dropping the cursor item by clicking outside the window.
 
-            let stack = std::mem::take(&mut cursor_item.0);
+            let stack = if pkt.button == 1 && cursor_item.count > 1 {
+                // Right clicking only drops a single item.
+                cursor_item.count -= 1;
+                cursor_item.0.clone().with_count(1)
+            } else {
+                std::mem::take(&mut cursor_item.0)
+            };
 
             if !stack.is_empty() {
                 drop_item_stack_events.send(DropItemStackEvent {
@@ -1084,138 +1115,120 @@ fn handle_click_slot(
                 continue;
             }
 
-            if let Some(mut open_inventory) = open_inventory {
-                // The player is interacting with an inventory that is
-                // open or has an inventory open while interacting with their own inventory.
-
-                let Ok(mut target_inventory) = inventories.get_mut(open_inventory.entity) else {
+            let mut target_inventory = match &open_inventory {
+                Some(open_inventory) => match inventories.get_mut(open_inventory.entity) {
+                    Ok(target_inventory) => Some(target_inventory),
                     // The inventory does not exist, ignore.
-                    continue;
-                };
-
-                if inv_state.state_id.0 != pkt.state_id.0 {
-                    // Client is out of sync. Resync and ignore click.
-
-                    debug!("Client state id mismatch, resyncing");
-
-                    inv_state.state_id += 1;
-
-                    client.write_packet(&InventoryS2c {
-                        window_id: inv_state.window_id,
-                        state_id: VarInt(inv_state.state_id.0),
-                        slots: Cow::Borrowed(target_inventory.slot_slice()),
-                        carried_item: Cow::Borrowed(&cursor_item.0),
-                    });
-
-                    continue;
-                }
+                    Err(_) => continue,
+                },
+                None => None,
+            };
 
-                let mut new_cursor = pkt.carried_item.clone();
+            if inv_state.state_id.0 != pkt.state_id.0 {
+                // Client is out of sync. Resync and ignore the click.
 
-                for slot in pkt.slot_changes.iter() {
-                    let transferred_between_inventories =
-                        ((0_i16..target_inventory.slot_count() as i16).contains(&pkt.slot_idx)
-                            && pkt.mode == ClickMode::Hotbar)
-                            || pkt.mode == ClickMode::ShiftClick;
+                debug!("Client state id mismatch, resyncing");
 
-                    if (0_i16..target_inventory.slot_count() as i16).contains(&slot.idx) {
-                        if (client_inv.readonly && transferred_between_inventories)
-                            || target_inventory.readonly
-                        {
-                            new_cursor = cursor_item.0.clone();
-                            continue;
-                        }
+                inv_state.state_id += 1;
 
-                        target_inventory.set_slot(slot.idx as u16, slot.stack.clone());
-                        open_inventory.client_changed |= 1 << slot.idx;
-                    } else {
-                        if (target_inventory.readonly && transferred_between_inventories)
-                            || client_inv.readonly
-                        {
-                            new_cursor = cursor_item.0.clone();
-                            continue;
-                        }
+                client.write_packet(&InventoryS2c {
+                    window_id: inv_state.window_id,
+                    state_id: VarInt(inv_state.state_id.0),
+                    slots: Cow::Borrowed(
+                        target_inventory
+                            .as_deref()
+                            .unwrap_or(&*client_inv)
+                            .slot_slice(),
+                    ),
+                    carried_item: Cow::Borrowed(&cursor_item.0),
+                });
 
-                        // The client is interacting with a slot in their own inventory.
-                        let slot_id =
-                            convert_to_player_slot_id(target_inventory.kind, slot.idx as u16);
-                        client_inv.set_slot(slot_id, slot.stack.clone());
-                        inv_state.slots_changed |= 1 << slot_id;
-                    }
-                }
+                continue;
+            }
 
-                cursor_item.set_if_neq(CursorItem(new_cursor.clone()));
-                inv_state.client_updated_cursor_item = Some(new_cursor);
+            let sim = simulate::simulate_click(
+                &pkt,
+                &InventoryWindow::new(&client_inv, target_inventory.as_deref()),
+                &cursor_item.0,
+                &mut inv_state.drag,
+                *game_mode == GameMode::Creative,
+            );
 
-                if target_inventory.readonly || client_inv.readonly {
-                    // resync the target inventory
-                    client.write_packet(&InventoryS2c {
-                        window_id: inv_state.window_id,
-                        state_id: VarInt(inv_state.state_id.0),
-                        slots: Cow::Borrowed(target_inventory.slot_slice()),
-                        carried_item: Cow::Borrowed(&cursor_item.0),
-                    });
+            if !sim.matches(&pkt) {
+                // The client's prediction differs from the server's, for example
+                // because it changed the components of an item. Resync and ignore
+                // the click.
 
-                    // resync the client inventory
-                    client.write_packet(&InventoryS2c {
-                        window_id: 0,
-                        state_id: VarInt(inv_state.state_id.0),
-                        slots: Cow::Borrowed(client_inv.slot_slice()),
-                        carried_item: Cow::Borrowed(&cursor_item.0),
-                    });
-                }
-            } else {
-                // The client is interacting with their own inventory.
+                debug!(
+                    "click slot packet for client {:#?} does not match the simulated click, \
+                     resyncing: {pkt:#?}",
+                    packet.client
+                );
 
-                if inv_state.state_id.0 != pkt.state_id.0 {
-                    // Client is out of sync. Resync and ignore the click.
+                inv_state.drag.reset();
 
-                    debug!("Client state id mismatch, resyncing");
+                resync_click_window(
+                    &mut client,
+                    &inv_state,
+                    &client_inv,
+                    target_inventory.as_deref(),
+                    &cursor_item,
+                );
 
-                    inv_state.state_id += 1;
+                continue;
+            }
 
-                    client.write_packet(&InventoryS2c {
-                        window_id: inv_state.window_id,
-                        state_id: VarInt(inv_state.state_id.0),
-                        slots: Cow::Borrowed(client_inv.slot_slice()),
-                        carried_item: Cow::Borrowed(&cursor_item.0),
-                    });
+            let slot_changes = sim.slot_changes();
 
-                    continue;
-                }
+            let modifies_readonly = slot_changes.iter().any(|change| match &target_inventory {
+                Some(target) if (change.idx as u16) < target.slot_count() => target.readonly,
+                _ => client_inv.readonly,
+            }) || (sim.hidden_offhand_change().is_some()
+                && client_inv.readonly);
 
-                let mut new_cursor = pkt.carried_item.clone();
+            if modifies_readonly {
+                // The click is legal, but it is not applied. The resync below reverts
+                // it on the client.
+                inv_state.client_updated_cursor_item = Some(cursor_item.0.clone());
+            } else {
+                for change in &slot_changes {
+                    let idx = change.idx as u16;
 
-                for slot in pkt.slot_changes.iter() {
-                    if (0_i16..client_inv.slot_count() as i16).contains(&slot.idx) {
-                        if client_inv.readonly {
-                            new_cursor = cursor_item.0.clone();
-                            continue;
+                    match (&mut target_inventory, &mut open_inventory) {
+                        (Some(target), Some(open_inventory)) if idx < target.slot_count() => {
+                            target.set_slot(idx, change.stack.clone());
+                            open_inventory.client_changed |= 1 << idx;
+                        }
+                        (target, _) => {
+                            // The client is interacting with a slot in their own inventory.
+                            let slot_id = match target {
+                                Some(target) => convert_to_player_slot_id(target.kind, idx),
+                                None => idx,
+                            };
+
+                            client_inv.set_slot(slot_id, change.stack.clone());
+                            inv_state.slots_changed |= 1 << slot_id;
                         }
-                        client_inv.set_slot(slot.idx as u16, slot.stack.clone());
-                        inv_state.slots_changed |= 1 << slot.idx;
-                    } else {
-                        // The client is trying to interact with a slot that does not exist,
-                        // ignore.
-                        warn!(
-                            "Client attempted to interact with slot {} which does not exist",
-                            slot.idx
-                        );
                     }
                 }
 
-                cursor_item.set_if_neq(CursorItem(new_cursor.clone()));
-                inv_state.client_updated_cursor_item = Some(new_cursor);
-
-                if client_inv.readonly {
-                    // resync the client inventory
-                    client.write_packet(&InventoryS2c {
-                        window_id: 0,
-                        state_id: VarInt(inv_state.state_id.0),
-                        slots: Cow::Borrowed(client_inv.slot_slice()),
-                        carried_item: Cow::Borrowed(&cursor_item.0),
-                    });
+                if let Some(offhand) = sim.hidden_offhand_change() {
+                    client_inv.set_slot(PlayerInventory::SLOT_OFFHAND, offhand.clone());
+                    inv_state.slots_changed |= 1 << PlayerInventory::SLOT_OFFHAND;
                 }
+
+                cursor_item.set_if_neq(CursorItem(sim.carried_item.clone()));
+                inv_state.client_updated_cursor_item = Some(sim.carried_item.clone());
+            }
+
+            if client_inv.readonly || target_inventory.as_ref().is_some_and(|t| t.readonly) {
+                resync_click_window(
+                    &mut client,
+                    &inv_state,
+                    &client_inv,
+                    target_inventory.as_deref(),
+                    &cursor_item,
+                );
             }
 
             click_slot_events.send(ClickSlotEvent {
@@ -1225,13 +1238,40 @@ fn handle_click_slot(
                 slot_id: pkt.slot_idx,
                 button: pkt.button,
                 mode: pkt.mode,
-                slot_changes: pkt.slot_changes.into(),
-                carried_item: pkt.carried_item,
+                slot_changes,
+                carried_item: sim.carried_item,
             });
         }
     }
 }
 
+/// Sends the contents of the window a click happened in, undoing whatever the
+/// client predicted. The player inventory is always resent because it is part
+/// of every window.
+fn resync_click_window(
+    client: &mut Client,
+    inv_state: &ClientInventoryState,
+    player_inventory: &Inventory,
+    open_inventory: Option<&Inventory>,
+    cursor_item: &CursorItem,
+) {
+    if let Some(open_inventory) = open_inventory {
+        client.write_packet(&InventoryS2c {
+            window_id: inv_state.window_id,
+            state_id: VarInt(inv_state.state_id.0),
+            slots: Cow::Borrowed(open_inventory.slot_slice()),
+            carried_item: Cow::Borrowed(&cursor_item.0),
+        });
+    }
+
+    client.write_packet(&InventoryS2c {
+        window_id: 0,
+        state_id: VarInt(inv_state.state_id.0),
+        slots: Cow::Borrowed(player_inventory.slot_slice()),
+        carried_item: Cow::Borrowed(&cursor_item.0),
+    });
+}
+
 fn handle_player_actions(
     mut packets: EventReader<PacketEvent>,
     mut clients: Query<(
@@ -1455,6 +1495,27 @@ fn update_player_selected_slot(mut clients: Query<(&mut Client, &HeldItem), Chan
     }
 }
 
+/// Copies the held item and helmet of each client to its [`DiggingEquipment`],
+/// which is used to validate how fast the client breaks blocks.
+fn update_digging_equipment(
+    mut clients: Query<(&Inventory, &HeldItem, &mut DiggingEquipment), With<Client>>,
+) {
+    for (inv, held_item, mut equipment) in &mut clients {
+        let main_hand = inv.slot(held_item.slot());
+        let head = inv.slot(PlayerInventory::SLOT_HEAD);
+
+        // `HeldItem` is changed by clients without change detection, so compare
+        // every tick instead.
+        if equipment.main_hand != *main_hand {
+            equipment.main_hand = main_hand.clone();
+        }
+
+        if equipment.head != *head {
+            equipment.head = head.clone();
+        }
+    }
+}
+
 /// Client to Server `HeldItem` Slot
 fn handle_update_selected_slot(
     mut packets: EventReader<PacketEvent>,
@@ -1,5 +1,9 @@
 use std::ops::RangeInclusive;
 
+use valence_server::ItemStack;
+
+use crate::{HeldItem, Inventory};
+
 pub struct PlayerInventory;
 
 impl PlayerInventory {
@@ -21,4 +25,58 @@ impl PlayerInventory {
     pub const fn slot_to_hotbar(slot: u16) -> u8 {
         (slot - *Self::SLOTS_HOTBAR.start()) as u8
     }
+
+    /// Adds an item stack to a player inventory the way vanilla does and
+    /// returns the number of items that did not fit.
+    ///
+    /// The stack is first added to matching stacks in the held slot, the
+    /// offhand, the hotbar and the main inventory, in that order. What is
+    /// left is put into the first empty slot of the hotbar or the main
+    /// inventory.
+    pub fn insert_stack(inventory: &mut Inventory, held_item: &HeldItem, stack: ItemStack) -> i8 {
+        let max_stack = stack.item.max_stack();
+        let mut remaining = stack.count;
+
+        let storage_slots =
+            || Self::SLOTS_HOTBAR.chain(*Self::SLOTS_MAIN.start()..*Self::SLOTS_HOTBAR.start());
+
+        let existing_slots = [held_item.slot(), Self::SLOT_OFFHAND]
+            .into_iter()
+            .chain(storage_slots());
+
+        for slot in existing_slots {
+            if remaining == 0 {
+                return 0;
+            }
+
+            let existing = inventory.slot(slot);
+
+            if existing.is_empty()
+                || existing.item != stack.item
+                || existing.components != stack.components
+                || existing.count >= max_stack
+            {
+                continue;
+            }
+
+            let moved = (max_stack - existing.count).min(remaining);
+            let count = existing.count + moved;
+            inventory.set_slot_amount(slot, count);
+            remaining -= moved;
+        }
+
+        for slot in storage_slots() {
+            if remaining == 0 {
+                return 0;
+            }
+
+            if inventory.slot(slot).is_empty() {
+                let moved = remaining.min(max_stack);
+                inventory.set_slot(slot, stack.clone().with_count(moved));
+                remaining -= moved;
+            }
+        }
+
+        remaining
+    }
 }
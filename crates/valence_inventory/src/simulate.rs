@@ -0,0 +1,1137 @@
+//! Server-side simulation of inventory clicks.
+//!
+//! Clients predict the result of their clicks and send the slots they think
+//! changed. Instead of trusting those, the server replays the click on a copy
+//! of the window with the same rules the vanilla client uses and only accepts
+//! the click if both results agree, item components included.
+//!
+//! Shift clicks in windows whose rules depend on recipes, like furnaces and
+//! brewing stands, are not simulated. The result claimed by the client is
+//! taken instead, which has already been checked to neither create nor
+//! destroy items.
+
+use std::ops::Range;
+
+use valence_server::protocol::packets::play::click_slot_c2s::{ClickMode, SlotChange};
+use valence_server::protocol::packets::play::ClickSlotC2s;
+use valence_server::{ItemKind, ItemStack};
+
+use crate::player_inventory::PlayerInventory;
+use crate::{InventoryKind, InventoryWindow};
+
+/// The state of a drag (`ClickMode::Drag`) that is in progress. Drags are
+/// spread over several packets, so the slots that were dragged over have to
+/// be remembered between them.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct DragState {
+    /// 0 when no drag is in progress, 1 while slots are being added.
+    status: u8,
+    /// 0 for splitting evenly, 1 for placing one item per slot and 2 for
+    /// cloning stacks in creative mode.
+    kind: u8,
+    slots: Vec<u16>,
+}
+
+impl DragState {
+    pub(crate) fn reset(&mut self) {
+        self.status = 0;
+        self.slots.clear();
+    }
+}
+
+/// A copy of the window a client clicked in, with the click applied to it.
+pub(super) struct SimulatedClick {
+    /// The kind of the open inventory, or `None` if the client is looking at
+    /// its own inventory.
+    open_kind: Option<InventoryKind>,
+    original: Vec<ItemStack>,
+    slots: Vec<ItemStack>,
+    /// The offhand is not part of the window while another inventory is open,
+    /// but it can still be swapped with the hotbar key for it.
+    original_offhand: ItemStack,
+    offhand: ItemStack,
+    pub(super) carried_item: ItemStack,
+}
+
+/// Where the hotbar key of a [`ClickMode::Hotbar`] click points to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HotbarTarget {
+    Slot(usize),
+    HiddenOffhand,
+}
+
+/// Simulates the click in `packet` on `window`.
+///
+/// [`ClickMode::DropKey`] and clicks outside the window are not simulated
+/// here, because they drop items instead of moving them around.
+pub(super) fn simulate_click(
+    packet: &ClickSlotC2s,
+    window: &InventoryWindow,
+    cursor_item: &ItemStack,
+    drag: &mut DragState,
+    creative: bool,
+) -> SimulatedClick {
+    let slots: Vec<ItemStack> = (0..window.slot_count())
+        .map(|idx| window.slot(idx).clone())
+        .collect();
+
+    let offhand = window
+        .player_inventory
+        .slot(PlayerInventory::SLOT_OFFHAND)
+        .clone();
+
+    let mut sim = SimulatedClick {
+        open_kind: window.open_inventory.map(|inv| inv.kind()),
+        original: slots.clone(),
+        slots,
+        original_offhand: offhand.clone(),
+        offhand,
+        carried_item: cursor_item.clone(),
+    };
+
+    let idx = usize::try_from(packet.slot_idx)
+        .ok()
+        .filter(|&idx| idx < sim.slots.len());
+
+    if packet.mode != ClickMode::Drag {
+        drag.reset();
+    }
+
+    match (packet.mode, idx) {
+        (ClickMode::Click, Some(idx)) => sim.click(idx, packet.button),
+        (ClickMode::ShiftClick, Some(idx)) => match sim.quick_move_targets(idx) {
+            Some(targets) => sim.shift_click(idx, &targets),
+            None => sim.accept_client_result(idx, packet),
+        },
+        (ClickMode::Hotbar, Some(idx)) => sim.hotbar_swap(idx, packet.button),
+        (ClickMode::CreativeMiddleClick, Some(idx)) => {
+            if creative && sim.carried_item.is_empty() && !sim.slots[idx].is_empty() {
+                let stack = &sim.slots[idx];
+                sim.carried_item = stack.clone().with_count(stack.item.max_stack());
+            }
+        }
+        (ClickMode::Drag, _) => sim.drag(idx, packet.button, drag, creative),
+        (ClickMode::DoubleClick, Some(idx)) => sim.pick_up_all(idx, packet.button),
+        _ => {}
+    }
+
+    sim.normalize();
+    sim
+}
+
+impl SimulatedClick {
+    /// The window slots that were changed by the click, in ascending order.
+    pub(super) fn slot_changes(&self) -> Vec<SlotChange> {
+        self.slots
+            .iter()
+            .zip(&self.original)
+            .enumerate()
+            .filter(|(_, (new, old))| !same_stack(new, old))
+            .map(|(idx, (new, _))| SlotChange {
+                idx: idx as i16,
+                stack: new.clone(),
+            })
+            .collect()
+    }
+
+    /// The new offhand item, if the click changed the offhand while it wasn't
+    /// part of the window.
+    pub(super) fn hidden_offhand_change(&self) -> Option<&ItemStack> {
+        (!same_stack(&self.offhand, &self.original_offhand)).then_some(&self.offhand)
+    }
+
+    /// Returns whether the slot changes and carried item claimed by the client
+    /// are exactly what the server computed.
+    pub(super) fn matches(&self, packet: &ClickSlotC2s) -> bool {
+        if !same_stack(&packet.carried_item, &self.carried_item) {
+            return false;
+        }
+
+        let claimed_correctly = packet.slot_changes.iter().all(|change| {
+            usize::try_from(change.idx)
+                .ok()
+                .and_then(|idx| self.slots.get(idx))
+                .is_some_and(|stack| same_stack(stack, &change.stack))
+        });
+
+        // Every slot that changed has to be reported by the client, otherwise
+        // its view of the window is out of date.
+        let reported_everything = self.slot_changes().iter().all(|change| {
+            packet
+                .slot_changes
+                .iter()
+                .any(|claimed| claimed.idx == change.idx)
+        });
+
+        claimed_correctly && reported_everything
+    }
+
+    fn normalize(&mut self) {
+        for stack in self
+            .slots
+            .iter_mut()
+            .chain([&mut self.offhand, &mut self.carried_item])
+        {
+            if stack.is_empty() {
+                *stack = ItemStack::EMPTY;
+            }
+        }
+    }
+
+    /// The number of slots belonging to the open inventory. The player's main
+    /// inventory follows them.
+    fn container_len(&self) -> usize {
+        self.open_kind.map_or(0, |kind| kind.slot_count())
+    }
+
+    fn is_result_slot(&self, idx: usize) -> bool {
+        match self.open_kind {
+            None | Some(InventoryKind::Crafting) => idx == 0,
+            Some(
+                InventoryKind::Furnace
+                | InventoryKind::BlastFurnace
+                | InventoryKind::Smoker
+                | InventoryKind::Grindstone
+                | InventoryKind::Cartography
+                | InventoryKind::Merchant,
+            ) => idx == 2,
+            Some(InventoryKind::Stonecutter) => idx == 1,
+            Some(InventoryKind::Loom) => idx == 3,
+            Some(_) => false,
+        }
+    }
+
+    fn is_armor_slot(&self, idx: usize) -> bool {
+        self.open_kind.is_none() && (5..=8).contains(&idx)
+    }
+
+    /// Whether `stack` may be put into the slot. Restrictions that depend on
+    /// recipes or other data the server doesn't have, like the fuel slot of a
+    /// furnace, are not checked.
+    fn may_place(&self, idx: usize, stack: &ItemStack) -> bool {
+        if self.is_result_slot(idx) {
+            return false;
+        }
+
+        if self.is_armor_slot(idx) {
+            return equipment_slot(stack.item) == Some(idx as u16);
+        }
+
+        if idx >= self.container_len() {
+            return true;
+        }
+
+        match (self.open_kind, idx) {
+            (Some(InventoryKind::ShulkerBox), _) => !is_shulker_box(stack.item),
+            (Some(InventoryKind::Beacon), 0) => matches!(
+                stack.item,
+                ItemKind::IronIngot
+                    | ItemKind::GoldIngot
+                    | ItemKind::Diamond
+                    | ItemKind::Emerald
+                    | ItemKind::NetheriteIngot
+            ),
+            (Some(InventoryKind::BrewingStand), 0..=2) => matches!(
+                stack.item,
+                ItemKind::Potion
+                    | ItemKind::SplashPotion
+                    | ItemKind::LingeringPotion
+                    | ItemKind::GlassBottle
+            ),
+            (Some(InventoryKind::BrewingStand), 4) => stack.item == ItemKind::BlazePowder,
+            (Some(InventoryKind::Enchantment), 1) => stack.item == ItemKind::LapisLazuli,
+            _ => true,
+        }
+    }
+
+    /// The maximum number of items of `stack` that fit into the slot.
+    fn slot_max(&self, idx: usize, stack: &ItemStack) -> i32 {
+        let single_item = self.is_armor_slot(idx)
+            || matches!(
+                (self.open_kind, idx),
+                (Some(InventoryKind::Beacon), 0)
+                    | (Some(InventoryKind::BrewingStand), 0..=2)
+                    | (Some(InventoryKind::Enchantment), 0)
+            );
+
+        if single_item {
+            1
+        } else {
+            i32::from(stack.item.max_stack())
+        }
+    }
+
+    /// Left (`button == 0`) or right click on a slot.
+    fn click(&mut self, idx: usize, button: i8) {
+        let right = button == 1;
+        let slot = self.slots[idx].clone();
+        let cursor = self.carried_item.clone();
+
+        if slot.is_empty() {
+            if !cursor.is_empty() && self.may_place(idx, &cursor) {
+                let amount = if right { 1 } else { i32::from(cursor.count) };
+                let placed = amount.min(self.slot_max(idx, &cursor));
+
+                self.slots[idx] = cursor.clone().with_count(placed as i8);
+                self.carried_item.count -= placed as i8;
+            }
+        } else if cursor.is_empty() {
+            let amount = if right {
+                (slot.count + 1) / 2
+            } else {
+                slot.count
+            };
+
+            self.carried_item = slot.clone().with_count(amount);
+            self.slots[idx].count -= amount;
+        } else if self.may_place(idx, &cursor) {
+            if same_item_and_components(&slot, &cursor) {
+                let amount = if right { 1 } else { i32::from(cursor.count) };
+                let space = self.slot_max(idx, &cursor) - i32::from(slot.count);
+                let moved = amount.min(space).max(0) as i8;
+
+                self.slots[idx].count += moved;
+                self.carried_item.count -= moved;
+            } else if i32::from(cursor.count) <= self.slot_max(idx, &cursor) {
+                self.slots[idx] = cursor;
+                self.carried_item = slot;
+            }
+        } else if same_item_and_components(&slot, &cursor)
+            && i32::from(slot.count) + i32::from(cursor.count) <= i32::from(cursor.item.max_stack())
+        {
+            // Taking the output of a result slot, which is only possible if all
+            // of it fits on the cursor.
+            self.carried_item.count += slot.count;
+            self.slots[idx] = ItemStack::EMPTY;
+        }
+    }
+
+    /// Moves the stack in a slot to the other part of the window, repeating
+    /// until nothing moves anymore.
+    fn shift_click(&mut self, idx: usize, targets: &[(Range<usize>, bool)]) {
+        for _ in 0..self.slots.len() {
+            if self.slots[idx].is_empty() {
+                break;
+            }
+
+            // Like in vanilla, a target is only tried if nothing could be moved
+            // into the ones before it.
+            let moved = targets
+                .iter()
+                .any(|(range, reverse)| self.move_stack_to(idx, range.clone(), *reverse));
+
+            if !moved {
+                break;
+            }
+        }
+    }
+
+    /// The slots a shift click on `idx` moves items into, in the order they
+    /// are tried, and whether they are filled from the end.
+    ///
+    /// Returns `None` for windows whose shift click rules are not modelled,
+    /// such as furnaces, which move items depending on their smelting recipes
+    /// and fuel values.
+    fn quick_move_targets(&self, idx: usize) -> Option<Vec<(Range<usize>, bool)>> {
+        let container_len = self.container_len();
+        let len = self.slots.len();
+
+        let Some(kind) = self.open_kind else {
+            return Some(vec![self.player_quick_move_target(idx)]);
+        };
+
+        let targets = match kind {
+            InventoryKind::Generic9x1
+            | InventoryKind::Generic9x2
+            | InventoryKind::Generic9x3
+            | InventoryKind::Generic9x4
+            | InventoryKind::Generic9x5
+            | InventoryKind::Generic9x6
+            | InventoryKind::Generic3x3
+            | InventoryKind::Hopper
+            | InventoryKind::ShulkerBox
+            | InventoryKind::Player => {
+                if idx < container_len {
+                    vec![(container_len..len, true)]
+                } else {
+                    vec![(0..container_len, false)]
+                }
+            }
+            InventoryKind::Crafting => {
+                // The main inventory takes window slots 10 to 36 and the hotbar
+                // 37 to 45.
+                let hotbar_start = container_len + 27;
+
+                match idx {
+                    0 => vec![(container_len..len, true)],
+                    1..=9 => vec![(container_len..len, false)],
+                    _ if idx < hotbar_start => {
+                        vec![(1..container_len, false), (hotbar_start..len, false)]
+                    }
+                    _ => vec![
+                        (1..container_len, false),
+                        (container_len..hotbar_start, false),
+                    ],
+                }
+            }
+            // Only moving items out of a furnace is modelled. Which of its slots
+            // an item moves into depends on the recipes and fuels.
+            InventoryKind::Furnace | InventoryKind::BlastFurnace | InventoryKind::Smoker
+                if idx < container_len =>
+            {
+                vec![(container_len..len, idx == 2)]
+            }
+            _ => return None,
+        };
+
+        Some(targets)
+    }
+
+    /// The shift click target of a slot of the player's own inventory.
+    fn player_quick_move_target(&self, idx: usize) -> (Range<usize>, bool) {
+        match idx {
+            0 => return (9..45, true),
+            1..=8 => return (9..45, false),
+            _ => {}
+        }
+
+        if let Some(slot) = equipment_slot(self.slots[idx].item)
+            .map(usize::from)
+            .filter(|&slot| self.slots[slot].is_empty())
+        {
+            return (slot..slot + 1, false);
+        }
+
+        match idx {
+            9..=35 => (36..45, false),
+            36..=44 => (9..36, false),
+            _ => (9..45, false),
+        }
+    }
+
+    /// Takes the slot changes and carried item claimed by the client as the
+    /// result of shift clicking `idx`. Used for clicks that are not modelled.
+    /// The click has already been checked to neither create nor destroy items.
+    ///
+    /// Only the counts of items are taken from the client. Every claimed
+    /// stack needs the same item and components as the clicked stack or the
+    /// stack it replaces, otherwise nothing is taken and the client is
+    /// resynced.
+    fn accept_client_result(&mut self, idx: usize, packet: &ClickSlotC2s) {
+        let source = &self.slots[idx];
+        let keeps_components = |stack: &ItemStack, previous: &ItemStack| {
+            stack.is_empty()
+                || same_item_and_components(stack, source)
+                || same_item_and_components(stack, previous)
+        };
+
+        let slots_keep_components = packet.slot_changes.iter().all(|change| {
+            match usize::try_from(change.idx)
+                .ok()
+                .and_then(|idx| self.slots.get(idx))
+            {
+                Some(previous) => keeps_components(&change.stack, previous),
+                // Changes of slots outside the window are ignored.
+                None => true,
+            }
+        });
+
+        if !slots_keep_components || !keeps_components(&packet.carried_item, &self.carried_item) {
+            return;
+        }
+
+        for change in packet.slot_changes.iter() {
+            if let Some(slot) = usize::try_from(change.idx)
+                .ok()
+                .and_then(|idx| self.slots.get_mut(idx))
+            {
+                *slot = change.stack.clone();
+            }
+        }
+
+        self.carried_item = packet.carried_item.clone();
+    }
+
+    /// Moves as much of the stack in `from` into `range` as possible, first
+    /// topping up matching stacks and then using the first empty slot.
+    /// Returns whether anything was moved.
+    fn move_stack_to(&mut self, from: usize, range: Range<usize>, reverse: bool) -> bool {
+        let mut stack = self.slots[from].clone();
+        let mut moved = false;
+
+        let order: Vec<usize> = if reverse {
+            range.rev().collect()
+        } else {
+            range.collect()
+        };
+
+        if stack.item.max_stack() > 1 {
+            for &idx in &order {
+                if stack.is_empty() {
+                    break;
+                }
+
+                let target = &self.slots[idx];
+
+                if target.is_empty() || !same_item_and_components(target, &stack) {
+                    continue;
+                }
+
+                let space = self.slot_max(idx, &stack) - i32::from(target.count);
+                let amount = i32::from(stack.count).min(space);
+
+                if amount > 0 {
+                    self.slots[idx].count += amount as i8;
+                    stack.count -= amount as i8;
+                    moved = true;
+                }
+            }
+        }
+
+        if !stack.is_empty() {
+            for &idx in &order {
+                if self.slots[idx].is_empty() && self.may_place(idx, &stack) {
+                    let amount = i32::from(stack.count).min(self.slot_max(idx, &stack)) as i8;
+
+                    self.slots[idx] = stack.clone().with_count(amount);
+                    stack.count -= amount;
+                    moved = true;
+                    break;
+                }
+            }
+        }
+
+        self.slots[from] = stack;
+        moved
+    }
+
+    fn hotbar_target(&self, button: i8) -> Option<HotbarTarget> {
+        let hotbar_start = if self.open_kind.is_some() {
+            // The hotbar is the last row of the player's main inventory.
+            self.container_len() + 27
+        } else {
+            usize::from(*PlayerInventory::SLOTS_HOTBAR.start())
+        };
+
+        match button {
+            0..=8 => Some(HotbarTarget::Slot(hotbar_start + button as usize)),
+            40 if self.open_kind.is_some() => Some(HotbarTarget::HiddenOffhand),
+            40 => Some(HotbarTarget::Slot(usize::from(
+                PlayerInventory::SLOT_OFFHAND,
+            ))),
+            _ => None,
+        }
+    }
+
+    /// Swaps a slot with a hotbar slot or the offhand.
+    fn hotbar_swap(&mut self, idx: usize, button: i8) {
+        let Some(target) = self.hotbar_target(button) else {
+            return;
+        };
+
+        if target == HotbarTarget::Slot(idx) {
+            return;
+        }
+
+        let hotbar = match target {
+            HotbarTarget::Slot(hotbar_idx) => self.slots[hotbar_idx].clone(),
+            HotbarTarget::HiddenOffhand => self.offhand.clone(),
+        };
+        let slot = self.slots[idx].clone();
+
+        let (new_hotbar, new_slot) = if hotbar.is_empty() {
+            (slot, ItemStack::EMPTY)
+        } else if !self.may_place(idx, &hotbar) {
+            return;
+        } else if i32::from(hotbar.count) > self.slot_max(idx, &hotbar) {
+            if !slot.is_empty() {
+                // The displaced item would have to be inserted elsewhere, which
+                // clients don't predict reliably.
+                return;
+            }
+
+            let max = self.slot_max(idx, &hotbar) as i8;
+            let remaining = hotbar.count - max;
+
+            (hotbar.clone().with_count(remaining), hotbar.with_count(max))
+        } else {
+            (slot, hotbar)
+        };
+
+        self.slots[idx] = new_slot;
+
+        match target {
+            HotbarTarget::Slot(hotbar_idx) => self.slots[hotbar_idx] = new_hotbar,
+            HotbarTarget::HiddenOffhand => self.offhand = new_hotbar,
+        }
+    }
+
+    /// Whether items from `stack` can be dragged into or gathered from the
+    /// slot.
+    fn can_quick_replace(&self, idx: usize, stack: &ItemStack) -> bool {
+        let slot = &self.slots[idx];
+
+        slot.is_empty()
+            || (same_item_and_components(slot, stack) && slot.count <= stack.item.max_stack())
+    }
+
+    /// Handles one packet of a drag. `button` holds the stage of the drag in
+    /// its lower two bits and the kind of drag in the two bits above them.
+    fn drag(&mut self, idx: Option<usize>, button: i8, state: &mut DragState, creative: bool) {
+        let previous_status = state.status;
+        let stage = (button & 3) as u8;
+        let kind = ((button >> 2) & 3) as u8;
+
+        state.status = stage;
+
+        if (previous_status != 1 || stage != 2) && previous_status != stage {
+            state.reset();
+            return;
+        }
+
+        if self.carried_item.is_empty() {
+            state.reset();
+            return;
+        }
+
+        match stage {
+            0 => {
+                if kind == 0 || kind == 1 || (kind == 2 && creative) {
+                    state.kind = kind;
+                    state.status = 1;
+                    state.slots.clear();
+                } else {
+                    state.reset();
+                }
+            }
+            1 => {
+                let Some(idx) = idx else {
+                    return;
+                };
+
+                let cursor = &self.carried_item;
+
+                if self.can_quick_replace(idx, cursor)
+                    && self.may_place(idx, cursor)
+                    && (state.kind == 2 || usize::from(cursor.count as u8) > state.slots.len())
+                    && !state.slots.contains(&(idx as u16))
+                {
+                    state.slots.push(idx as u16);
+                }
+            }
+            _ => {
+                let slots = std::mem::take(&mut state.slots);
+                let kind = state.kind;
+
+                state.reset();
+
+                if let [idx] = slots[..] {
+                    // Dragging over a single slot is the same as clicking it.
+                    self.click(usize::from(idx), kind as i8);
+                    return;
+                }
+
+                self.finish_drag(&slots, kind);
+            }
+        }
+    }
+
+    fn finish_drag(&mut self, slots: &[u16], kind: u8) {
+        let cursor = self.carried_item.clone();
+        let mut remaining = i32::from(cursor.count);
+
+        for idx in slots.iter().map(|&idx| usize::from(idx)) {
+            if !self.can_quick_replace(idx, &cursor)
+                || !self.may_place(idx, &cursor)
+                || (kind != 2 && usize::from(cursor.count as u8) < slots.len())
+            {
+                continue;
+            }
+
+            let before = if self.slots[idx].is_empty() {
+                0
+            } else {
+                i32::from(self.slots[idx].count)
+            };
+
+            let per_slot = match kind {
+                0 => i32::from(cursor.count) / slots.len() as i32,
+                1 => 1,
+                _ => i32::from(cursor.item.max_stack()),
+            };
+
+            let after = (per_slot + before).min(self.slot_max(idx, &cursor));
+
+            remaining -= after - before;
+            self.slots[idx] = cursor.clone().with_count(after as i8);
+        }
+
+        self.carried_item.count = remaining.max(0) as i8;
+    }
+
+    /// Double click: gathers stacks matching the cursor into the cursor.
+    fn pick_up_all(&mut self, idx: usize, button: i8) {
+        if self.carried_item.is_empty() || !self.slots[idx].is_empty() {
+            return;
+        }
+
+        let order: Vec<usize> = if button == 0 {
+            (0..self.slots.len()).collect()
+        } else {
+            (0..self.slots.len()).rev().collect()
+        };
+
+        let max = self.carried_item.item.max_stack();
+
+        // Partial stacks are taken first, full stacks only if there is still
+        // room afterwards.
+        for take_full_stacks in [false, true] {
+            for &idx in &order {
+                if self.carried_item.count >= max {
+                    return;
+                }
+
+                let slot = &self.slots[idx];
+
+                if slot.is_empty()
+                    || self.is_result_slot(idx)
+                    || !self.can_quick_replace(idx, &self.carried_item)
+                    || (!take_full_stacks && slot.count == slot.item.max_stack())
+                {
+                    continue;
+                }
+
+                let amount = slot.count.min(max - self.carried_item.count);
+
+                self.slots[idx].count -= amount;
+                self.carried_item.count += amount;
+            }
+        }
+    }
+}
+
+/// Compares two stacks, treating all empty stacks as equal.
+fn same_stack(a: &ItemStack, b: &ItemStack) -> bool {
+    (a.is_empty() && b.is_empty()) || a == b
+}
+
+fn same_item_and_components(a: &ItemStack, b: &ItemStack) -> bool {
+    a.item == b.item && a.components == b.components
+}
+
+fn is_shulker_box(item: ItemKind) -> bool {
+    matches!(
+        item,
+        ItemKind::ShulkerBox
+            | ItemKind::WhiteShulkerBox
+            | ItemKind::OrangeShulkerBox
+            | ItemKind::MagentaShulkerBox
+            | ItemKind::LightBlueShulkerBox
+            | ItemKind::YellowShulkerBox
+            | ItemKind::LimeShulkerBox
+            | ItemKind::PinkShulkerBox
+            | ItemKind::GrayShulkerBox
+            | ItemKind::LightGrayShulkerBox
+            | ItemKind::CyanShulkerBox
+            | ItemKind::PurpleShulkerBox
+            | ItemKind::BlueShulkerBox
+            | ItemKind::BrownShulkerBox
+            | ItemKind::GreenShulkerBox
+            | ItemKind::RedShulkerBox
+            | ItemKind::BlackShulkerBox
+    )
+}
+
+/// The slot of the player inventory an item is equipped in when it is shift
+/// clicked, if any.
+fn equipment_slot(item: ItemKind) -> Option<u16> {
+    let name = item.to_str();
+
+    if name.ends_with("_helmet")
+        || name.ends_with("_head")
+        || name.ends_with("_skull")
+        || item == ItemKind::CarvedPumpkin
+    {
+        Some(PlayerInventory::SLOT_HEAD)
+    } else if name.ends_with("_chestplate") || item == ItemKind::Elytra {
+        Some(PlayerInventory::SLOT_CHEST)
+    } else if name.ends_with("_leggings") {
+        Some(PlayerInventory::SLOT_LEGS)
+    } else if name.ends_with("_boots") {
+        Some(PlayerInventory::SLOT_FEET)
+    } else if item == ItemKind::Shield {
+        Some(PlayerInventory::SLOT_OFFHAND)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_server::protocol::VarInt;
+    use valence_server::ItemComponent;
+
+    use super::*;
+    use crate::Inventory;
+
+    fn packet(mode: ClickMode, slot_idx: i16, button: i8) -> ClickSlotC2s<'static> {
+        ClickSlotC2s {
+            window_id: 0,
+            state_id: VarInt(0),
+            slot_idx,
+            button,
+            mode,
+            slot_changes: vec![].into(),
+            carried_item: ItemStack::EMPTY,
+        }
+    }
+
+    fn simulate(
+        packet: &ClickSlotC2s,
+        player_inventory: &Inventory,
+        open_inventory: Option<&Inventory>,
+        cursor_item: &ItemStack,
+    ) -> SimulatedClick {
+        let window = InventoryWindow::new(player_inventory, open_inventory);
+        simulate_click(
+            packet,
+            &window,
+            cursor_item,
+            &mut DragState::default(),
+            false,
+        )
+    }
+
+    #[test]
+    fn left_click_merges_into_stack() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::Diamond, 60));
+
+        let sim = simulate(
+            &packet(ClickMode::Click, 9, 0),
+            &player_inventory,
+            None,
+            &ItemStack::new(ItemKind::Diamond, 10),
+        );
+
+        assert_eq!(sim.slots[9], ItemStack::new(ItemKind::Diamond, 64));
+        assert_eq!(sim.carried_item, ItemStack::new(ItemKind::Diamond, 6));
+    }
+
+    #[test]
+    fn stacks_with_different_components_are_swapped() {
+        let damaged =
+            ItemStack::new(ItemKind::IronIngot, 1).with_component(ItemComponent::Damage(1));
+
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::IronIngot, 1));
+
+        let sim = simulate(
+            &packet(ClickMode::Click, 9, 0),
+            &player_inventory,
+            None,
+            &damaged,
+        );
+
+        assert_eq!(sim.slots[9], damaged);
+        assert_eq!(sim.carried_item, ItemStack::new(ItemKind::IronIngot, 1));
+    }
+
+    #[test]
+    fn right_click_picks_up_half() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::Diamond, 5));
+
+        let sim = simulate(
+            &packet(ClickMode::Click, 9, 1),
+            &player_inventory,
+            None,
+            &ItemStack::EMPTY,
+        );
+
+        assert_eq!(sim.slots[9], ItemStack::new(ItemKind::Diamond, 2));
+        assert_eq!(sim.carried_item, ItemStack::new(ItemKind::Diamond, 3));
+    }
+
+    #[test]
+    fn shift_click_tops_up_partial_stacks_first() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        let mut open_inventory = Inventory::new(InventoryKind::Generic9x3);
+        open_inventory.set_slot(4, ItemStack::new(ItemKind::Diamond, 60));
+        // Slot 36 of the player inventory is window slot 54.
+        player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 64));
+        player_inventory.set_slot(37, ItemStack::new(ItemKind::Diamond, 10));
+
+        let sim = simulate(
+            &packet(ClickMode::ShiftClick, 55, 0),
+            &player_inventory,
+            Some(&open_inventory),
+            &ItemStack::EMPTY,
+        );
+
+        let changes = sim.slot_changes();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(sim.slots[4], ItemStack::new(ItemKind::Diamond, 64));
+        assert_eq!(sim.slots[0], ItemStack::new(ItemKind::Diamond, 6));
+        assert!(sim.slots[55].is_empty());
+    }
+
+    #[test]
+    fn shift_click_equips_armor() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(20, ItemStack::new(ItemKind::IronHelmet, 1));
+
+        let sim = simulate(
+            &packet(ClickMode::ShiftClick, 20, 0),
+            &player_inventory,
+            None,
+            &ItemStack::EMPTY,
+        );
+
+        assert_eq!(
+            sim.slots[usize::from(PlayerInventory::SLOT_HEAD)],
+            ItemStack::new(ItemKind::IronHelmet, 1)
+        );
+        assert!(sim.slots[20].is_empty());
+    }
+
+    #[test]
+    fn hotbar_swap_with_hidden_offhand() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(
+            PlayerInventory::SLOT_OFFHAND,
+            ItemStack::new(ItemKind::Shield, 1),
+        );
+        let open_inventory = Inventory::new(InventoryKind::Generic9x1);
+
+        let sim = simulate(
+            &packet(ClickMode::Hotbar, 3, 40),
+            &player_inventory,
+            Some(&open_inventory),
+            &ItemStack::EMPTY,
+        );
+
+        assert_eq!(sim.slots[3], ItemStack::new(ItemKind::Shield, 1));
+        assert_eq!(sim.hidden_offhand_change(), Some(&ItemStack::EMPTY));
+    }
+
+    #[test]
+    fn drag_splits_cursor_evenly() {
+        let player_inventory = Inventory::new(InventoryKind::Player);
+        let window = InventoryWindow::new(&player_inventory, None);
+        let cursor = ItemStack::new(ItemKind::Diamond, 10);
+        let mut drag = DragState::default();
+
+        for (slot_idx, button) in [(-999, 0), (9, 1), (10, 1), (11, 1)] {
+            let sim = simulate_click(
+                &packet(ClickMode::Drag, slot_idx, button),
+                &window,
+                &cursor,
+                &mut drag,
+                false,
+            );
+            assert!(sim.slot_changes().is_empty());
+        }
+
+        let sim = simulate_click(
+            &packet(ClickMode::Drag, -999, 2),
+            &window,
+            &cursor,
+            &mut drag,
+            false,
+        );
+
+        assert_eq!(sim.slot_changes().len(), 3);
+        assert_eq!(sim.slots[10], ItemStack::new(ItemKind::Diamond, 3));
+        assert_eq!(sim.carried_item, ItemStack::new(ItemKind::Diamond, 1));
+    }
+
+    #[test]
+    fn double_click_gathers_partial_stacks_first() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::Diamond, 64));
+        player_inventory.set_slot(10, ItemStack::new(ItemKind::Diamond, 5));
+
+        let sim = simulate(
+            &packet(ClickMode::DoubleClick, 11, 0),
+            &player_inventory,
+            None,
+            &ItemStack::new(ItemKind::Diamond, 1),
+        );
+
+        assert_eq!(sim.carried_item, ItemStack::new(ItemKind::Diamond, 64));
+        assert!(sim.slots[10].is_empty());
+        assert_eq!(sim.slots[9], ItemStack::new(ItemKind::Diamond, 6));
+    }
+
+    #[test]
+    fn claimed_components_must_match() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::IronSword, 1));
+
+        let mut click = packet(ClickMode::Click, 9, 0);
+        click.slot_changes = vec![SlotChange {
+            idx: 9,
+            stack: ItemStack::EMPTY,
+        }]
+        .into();
+        click.carried_item =
+            ItemStack::new(ItemKind::IronSword, 1).with_component(ItemComponent::Damage(0));
+
+        let sim = simulate(&click, &player_inventory, None, &ItemStack::EMPTY);
+        assert!(!sim.matches(&click));
+
+        click.carried_item = ItemStack::new(ItemKind::IronSword, 1);
+        assert!(sim.matches(&click));
+    }
+
+    #[test]
+    fn shift_click_furnace_result_into_hotbar() {
+        let player_inventory = Inventory::new(InventoryKind::Player);
+        let mut furnace = Inventory::new(InventoryKind::Furnace);
+        furnace.set_slot(2, ItemStack::new(ItemKind::IronIngot, 5));
+
+        let sim = simulate(
+            &packet(ClickMode::ShiftClick, 2, 0),
+            &player_inventory,
+            Some(&furnace),
+            &ItemStack::EMPTY,
+        );
+
+        // The result is moved to the last hotbar slot first, which is window
+        // slot 38.
+        assert!(sim.slots[2].is_empty());
+        assert_eq!(sim.slots[38], ItemStack::new(ItemKind::IronIngot, 5));
+    }
+
+    #[test]
+    fn shift_click_into_furnace_takes_client_result() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::Coal, 10));
+        let furnace = Inventory::new(InventoryKind::Furnace);
+
+        // Player slot 9 is window slot 3. Coal goes into the fuel slot, which
+        // the server can't know without the fuel values.
+        let mut click = packet(ClickMode::ShiftClick, 3, 0);
+        click.slot_changes = vec![
+            SlotChange {
+                idx: 1,
+                stack: ItemStack::new(ItemKind::Coal, 10),
+            },
+            SlotChange {
+                idx: 3,
+                stack: ItemStack::EMPTY,
+            },
+        ]
+        .into();
+
+        let sim = simulate(&click, &player_inventory, Some(&furnace), &ItemStack::EMPTY);
+
+        assert!(sim.matches(&click));
+        assert_eq!(sim.slots[1], ItemStack::new(ItemKind::Coal, 10));
+        assert!(sim.slots[3].is_empty());
+    }
+
+    #[test]
+    fn shift_click_with_changed_components_is_resynced() {
+        let player_inventory = Inventory::new(InventoryKind::Player);
+        let mut anvil = Inventory::new(InventoryKind::Anvil);
+        anvil.set_slot(0, ItemStack::new(ItemKind::IronSword, 1));
+
+        // Anvil slot 0 is moved to the first main inventory slot, which is
+        // window slot 4.
+        let mut click = packet(ClickMode::ShiftClick, 0, 0);
+        click.slot_changes = vec![
+            SlotChange {
+                idx: 0,
+                stack: ItemStack::EMPTY,
+            },
+            SlotChange {
+                idx: 4,
+                stack: ItemStack::new(ItemKind::IronSword, 1)
+                    .with_component(ItemComponent::Damage(0)),
+            },
+        ]
+        .into();
+
+        let sim = simulate(&click, &player_inventory, Some(&anvil), &ItemStack::EMPTY);
+
+        assert!(!sim.matches(&click));
+        assert_eq!(sim.slots[0], ItemStack::new(ItemKind::IronSword, 1));
+        assert!(sim.slots[4].is_empty());
+
+        click.slot_changes = vec![
+            SlotChange {
+                idx: 0,
+                stack: ItemStack::EMPTY,
+            },
+            SlotChange {
+                idx: 4,
+                stack: ItemStack::new(ItemKind::IronSword, 1),
+            },
+        ]
+        .into();
+
+        let sim = simulate(&click, &player_inventory, Some(&anvil), &ItemStack::EMPTY);
+
+        assert!(sim.matches(&click));
+    }
+
+    #[test]
+    fn shift_click_in_crafting_table_fills_grid_first() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::OakPlanks, 10));
+        let mut crafting_table = Inventory::new(InventoryKind::Crafting);
+        crafting_table.set_slot(5, ItemStack::new(ItemKind::OakPlanks, 1));
+
+        // Player slot 9 is window slot 10.
+        let sim = simulate(
+            &packet(ClickMode::ShiftClick, 10, 0),
+            &player_inventory,
+            Some(&crafting_table),
+            &ItemStack::EMPTY,
+        );
+
+        assert_eq!(sim.slots[5], ItemStack::new(ItemKind::OakPlanks, 11));
+        assert!(sim.slots[10].is_empty());
+        assert_eq!(sim.slot_changes().len(), 2);
+    }
+
+    #[test]
+    fn shift_click_in_full_crafting_table_moves_to_hotbar() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::OakPlanks, 10));
+        let mut crafting_table = Inventory::new(InventoryKind::Crafting);
+
+        for slot in 1..10 {
+            crafting_table.set_slot(slot, ItemStack::new(ItemKind::Stone, 64));
+        }
+
+        let sim = simulate(
+            &packet(ClickMode::ShiftClick, 10, 0),
+            &player_inventory,
+            Some(&crafting_table),
+            &ItemStack::EMPTY,
+        );
+
+        // The first hotbar slot is window slot 37.
+        assert_eq!(sim.slots[37], ItemStack::new(ItemKind::OakPlanks, 10));
+        assert!(sim.slots[10].is_empty());
+    }
+
+    #[test]
+    fn shulker_boxes_stay_out_of_shulker_boxes() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::RedShulkerBox, 1));
+        let shulker_box = Inventory::new(InventoryKind::ShulkerBox);
+
+        let sim = simulate(
+            &packet(ClickMode::ShiftClick, 27, 0),
+            &player_inventory,
+            Some(&shulker_box),
+            &ItemStack::EMPTY,
+        );
+
+        assert!(sim.slot_changes().is_empty());
+    }
+}
@@ -6,6 +6,10 @@ use super::{CursorItem, Inventory, InventoryWindow};
 use crate::player_inventory::PlayerInventory;
 
 /// Validates a click slot packet enforcing that all fields are valid.
+///
+/// This only checks that the packet is well formed and doesn't create or
+/// destroy items. Whether the claimed result is what the click actually does
+/// is checked by simulating it.
 pub(super) fn validate_click_slot_packet(
     packet: &ClickSlotC2s,
     player_inventory: &Inventory,
@@ -181,9 +185,8 @@ pub(super) fn validate_click_slot_packet(
                     );
 
                     let old_slot = window.slot(packet.slot_changes[0].idx as u16);
-                    // TODO: make sure NBT is the same.
-                    //       Sometimes, the client will add nbt data to an item if it's missing,
-                    // like       "Damage" to a sword.
+                    // Components are not compared here. The click is replayed by
+                    // `simulate::simulate_click` afterwards, which does.
                     let should_swap: bool = packet.button == 0
                         && match (!old_slot.is_empty(), !cursor_item.is_empty()) {
                             (true, true) => old_slot.item != cursor_item.item,
@@ -195,7 +198,7 @@ pub(super) fn validate_click_slot_packet(
                     if should_swap {
                         // assert that a swap occurs
                         ensure!(
-                            // There are some cases where the client will add NBT data that
+                            // There are some cases where the client will add components that
                             // did not previously exist.
                             old_slot.item == packet.carried_item.item
                                 && old_slot.count == packet.carried_item.count
@@ -225,8 +228,8 @@ pub(super) fn validate_click_slot_packet(
                 );
             } else {
                 ensure!(
-                    (2..=3).contains(&packet.slot_changes.len()),
-                    "shift click must modify 2 or 3 slots, got {}",
+                    packet.slot_changes.len() >= 2,
+                    "shift click must modify at least 2 slots, got {}",
                     packet.slot_changes.len()
                 );
 
@@ -291,7 +294,7 @@ pub(super) fn validate_click_slot_packet(
                     window.slot(packet.slot_changes[0].idx as u16),
                     window.slot(packet.slot_changes[1].idx as u16),
                 ];
-                // There are some cases where the client will add NBT data that did not
+                // There are some cases where the client will add components that did not
                 // previously exist.
                 ensure!(
                     old_slots
@@ -327,10 +330,9 @@ pub(super) fn validate_click_slot_packet(
 
                 let old_slot = window.slot(packet.slot_idx as u16);
                 let new_slot = &packet.slot_changes[0].stack;
+                // The drop itself is performed by the server, so the components the client
+                // claims for the remaining stack are never applied.
                 let is_transmuting = match (!old_slot.is_empty(), !new_slot.is_empty()) {
-                    // TODO: make sure NBT is the same.
-                    // Sometimes, the client will add nbt data to an item if it's missing, like
-                    // "Damage" to a sword.
                     (true, true) => old_slot.item != new_slot.item,
                     (_, false) => false,
                     (false, true) => true,
@@ -419,11 +421,9 @@ fn calculate_net_item_delta(
 
 #[cfg(test)]
 mod tests {
-    use valence_server::nbt::Compound;
-    use valence_server::nbt::Value::Int;
     use valence_server::protocol::packets::play::click_slot_c2s::SlotChange;
     use valence_server::protocol::VarInt;
-    use valence_server::{ItemKind, ItemStack};
+    use valence_server::{ItemComponent, ItemKind, ItemStack};
 
     use super::*;
     use crate::InventoryKind;
@@ -439,25 +439,25 @@ mod tests {
             slot_changes: vec![
                 SlotChange {
                     idx: 4,
-                    stack: ItemStack::new(ItemKind::Diamond, 21, None),
+                    stack: ItemStack::new(ItemKind::Diamond, 21),
                 },
                 SlotChange {
                     idx: 3,
-                    stack: ItemStack::new(ItemKind::Diamond, 21, None),
+                    stack: ItemStack::new(ItemKind::Diamond, 21),
                 },
                 SlotChange {
                     idx: 5,
-                    stack: ItemStack::new(ItemKind::Diamond, 21, None),
+                    stack: ItemStack::new(ItemKind::Diamond, 21),
                 },
             ]
             .into(),
-            carried_item: ItemStack::new(ItemKind::Diamond, 1, None),
+            carried_item: ItemStack::new(ItemKind::Diamond, 1),
         };
 
         let player_inventory = Inventory::new(InventoryKind::Player);
         let inventory = Inventory::new(InventoryKind::Generic9x1);
         let window = InventoryWindow::new(&player_inventory, Some(&inventory));
-        let cursor_item = CursorItem(ItemStack::new(ItemKind::Diamond, 64, None));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Diamond, 64));
 
         assert_eq!(
             calculate_net_item_delta(&drag_packet, &window, &cursor_item),
@@ -476,23 +476,23 @@ mod tests {
             slot_changes: vec![
                 SlotChange {
                     idx: 2,
-                    stack: ItemStack::new(ItemKind::Diamond, 2, None),
+                    stack: ItemStack::new(ItemKind::Diamond, 2),
                 },
                 SlotChange {
                     idx: 3,
-                    stack: ItemStack::new(ItemKind::IronIngot, 2, None),
+                    stack: ItemStack::new(ItemKind::IronIngot, 2),
                 },
                 SlotChange {
                     idx: 4,
-                    stack: ItemStack::new(ItemKind::GoldIngot, 2, None),
+                    stack: ItemStack::new(ItemKind::GoldIngot, 2),
                 },
                 SlotChange {
                     idx: 5,
-                    stack: ItemStack::new(ItemKind::Emerald, 2, None),
+                    stack: ItemStack::new(ItemKind::Emerald, 2),
                 },
             ]
             .into(),
-            carried_item: ItemStack::new(ItemKind::OakWood, 2, None),
+            carried_item: ItemStack::new(ItemKind::OakWood, 2),
         };
 
         let player_inventory = Inventory::new(InventoryKind::Player);
@@ -510,7 +510,7 @@ mod tests {
     fn click_filled_slot_with_empty_cursor_success() {
         let player_inventory = Inventory::new(InventoryKind::Player);
         let mut inventory = Inventory::new(InventoryKind::Generic9x1);
-        inventory.set_slot(0, ItemStack::new(ItemKind::Diamond, 20, None));
+        inventory.set_slot(0, ItemStack::new(ItemKind::Diamond, 20));
         let cursor_item = CursorItem::default();
         let packet = ClickSlotC2s {
             window_id: 1,
@@ -531,17 +531,13 @@ mod tests {
     }
 
     #[test]
-    fn click_filled_slot_with_incorrect_nbt_and_empty_cursor_success() {
+    fn click_filled_slot_with_incorrect_components_and_empty_cursor_success() {
         let player_inventory = Inventory::new(InventoryKind::Player);
         let cursor_item = CursorItem(ItemStack::EMPTY);
 
         let mut inventory = Inventory::new(InventoryKind::Generic9x1);
-        // Insert an item with no NBT data that should have NBT Data.
-        inventory.set_slot(0, ItemStack::new(ItemKind::DiamondPickaxe, 1, None));
-
-        // Proper NBT Compound
-        let mut compound = Compound::new();
-        compound.insert("Damage", Int(1));
+        // Insert an item without the damage component the client will add.
+        inventory.set_slot(0, ItemStack::new(ItemKind::DiamondPickaxe, 1));
 
         let packet = ClickSlotC2s {
             window_id: 1,
@@ -554,11 +550,8 @@ mod tests {
                 stack: ItemStack::EMPTY,
             }]
             .into(),
-            carried_item: ItemStack {
-                item: ItemKind::DiamondPickaxe,
-                count: 1,
-                nbt: Some(compound),
-            },
+            carried_item: ItemStack::new(ItemKind::DiamondPickaxe, 1)
+                .with_component(ItemComponent::Damage(1)),
         };
 
         validate_click_slot_packet(&packet, &player_inventory, Some(&inventory), &cursor_item)
@@ -570,8 +563,8 @@ mod tests {
         let player_inventory = Inventory::new(InventoryKind::Player);
         let inventory1 = Inventory::new(InventoryKind::Generic9x1);
         let mut inventory2 = Inventory::new(InventoryKind::Generic9x1);
-        inventory2.set_slot(0, ItemStack::new(ItemKind::Diamond, 10, None));
-        let cursor_item = CursorItem(ItemStack::new(ItemKind::Diamond, 20, None));
+        inventory2.set_slot(0, ItemStack::new(ItemKind::Diamond, 10));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Diamond, 20));
         let packet1 = ClickSlotC2s {
             window_id: 1,
             button: 0,
@@ -580,7 +573,7 @@ mod tests {
             slot_idx: 0,
             slot_changes: vec![SlotChange {
                 idx: 0,
-                stack: ItemStack::new(ItemKind::Diamond, 20, None),
+                stack: ItemStack::new(ItemKind::Diamond, 20),
             }]
             .into(),
             carried_item: ItemStack::EMPTY,
@@ -593,7 +586,7 @@ mod tests {
             slot_idx: 0,
             slot_changes: vec![SlotChange {
                 idx: 0,
-                stack: ItemStack::new(ItemKind::Diamond, 30, None),
+                stack: ItemStack::new(ItemKind::Diamond, 30),
             }]
             .into(),
             carried_item: ItemStack::EMPTY,
@@ -610,8 +603,8 @@ mod tests {
     fn click_filled_slot_with_filled_cursor_stack_overflow_success() {
         let player_inventory = Inventory::new(InventoryKind::Player);
         let mut inventory = Inventory::new(InventoryKind::Generic9x1);
-        inventory.set_slot(0, ItemStack::new(ItemKind::Diamond, 20, None));
-        let cursor_item = CursorItem(ItemStack::new(ItemKind::Diamond, 64, None));
+        inventory.set_slot(0, ItemStack::new(ItemKind::Diamond, 20));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Diamond, 64));
         let packet = ClickSlotC2s {
             window_id: 1,
             button: 0,
@@ -620,10 +613,10 @@ mod tests {
             slot_idx: 0,
             slot_changes: vec![SlotChange {
                 idx: 0,
-                stack: ItemStack::new(ItemKind::Diamond, 64, None),
+                stack: ItemStack::new(ItemKind::Diamond, 64),
             }]
             .into(),
-            carried_item: ItemStack::new(ItemKind::Diamond, 20, None),
+            carried_item: ItemStack::new(ItemKind::Diamond, 20),
         };
 
         validate_click_slot_packet(&packet, &player_inventory, Some(&inventory), &cursor_item)
@@ -634,8 +627,8 @@ mod tests {
     fn click_filled_slot_with_filled_cursor_different_item_success() {
         let player_inventory = Inventory::new(InventoryKind::Player);
         let mut inventory = Inventory::new(InventoryKind::Generic9x1);
-        inventory.set_slot(0, ItemStack::new(ItemKind::IronIngot, 2, None));
-        let cursor_item = CursorItem(ItemStack::new(ItemKind::Diamond, 2, None));
+        inventory.set_slot(0, ItemStack::new(ItemKind::IronIngot, 2));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Diamond, 2));
         let packet = ClickSlotC2s {
             window_id: 1,
             button: 0,
@@ -644,10 +637,10 @@ mod tests {
             slot_idx: 0,
             slot_changes: vec![SlotChange {
                 idx: 0,
-                stack: ItemStack::new(ItemKind::Diamond, 2, None),
+                stack: ItemStack::new(ItemKind::Diamond, 2),
             }]
             .into(),
-            carried_item: ItemStack::new(ItemKind::IronIngot, 2, None),
+            carried_item: ItemStack::new(ItemKind::IronIngot, 2),
         };
 
         validate_click_slot_packet(&packet, &player_inventory, Some(&inventory), &cursor_item)
@@ -659,8 +652,8 @@ mod tests {
         let player_inventory = Inventory::new(InventoryKind::Player);
         let inventory1 = Inventory::new(InventoryKind::Generic9x1);
         let mut inventory2 = Inventory::new(InventoryKind::Generic9x1);
-        inventory2.set_slot(0, ItemStack::new(ItemKind::Diamond, 10, None));
-        let cursor_item = CursorItem(ItemStack::new(ItemKind::Diamond, 20, None));
+        inventory2.set_slot(0, ItemStack::new(ItemKind::Diamond, 10));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Diamond, 20));
         let packet1 = ClickSlotC2s {
             window_id: 1,
             button: 0,
@@ -669,7 +662,7 @@ mod tests {
             slot_idx: 0,
             slot_changes: vec![SlotChange {
                 idx: 0,
-                stack: ItemStack::new(ItemKind::Diamond, 22, None),
+                stack: ItemStack::new(ItemKind::Diamond, 22),
             }]
             .into(),
             carried_item: ItemStack::EMPTY,
@@ -682,7 +675,7 @@ mod tests {
             slot_idx: 0,
             slot_changes: vec![SlotChange {
                 idx: 0,
-                stack: ItemStack::new(ItemKind::Diamond, 32, None),
+                stack: ItemStack::new(ItemKind::Diamond, 32),
             }]
             .into(),
             carried_item: ItemStack::EMPTY,
@@ -696,11 +689,11 @@ mod tests {
             slot_changes: vec![
                 SlotChange {
                     idx: 0,
-                    stack: ItemStack::new(ItemKind::Diamond, 22, None),
+                    stack: ItemStack::new(ItemKind::Diamond, 22),
                 },
                 SlotChange {
                     idx: 1,
-                    stack: ItemStack::new(ItemKind::Diamond, 22, None),
+                    stack: ItemStack::new(ItemKind::Diamond, 22),
                 },
             ]
             .into(),
@@ -722,7 +715,7 @@ mod tests {
         // no alchemy allowed - make sure that lead can't be turned into gold
 
         let mut player_inventory = Inventory::new(InventoryKind::Player);
-        player_inventory.set_slot(9, ItemStack::new(ItemKind::Lead, 2, None));
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::Lead, 2));
         let cursor_item = CursorItem::default();
 
         let packets = vec![
@@ -739,7 +732,7 @@ mod tests {
                     },
                     SlotChange {
                         idx: 36,
-                        stack: ItemStack::new(ItemKind::GoldIngot, 2, None),
+                        stack: ItemStack::new(ItemKind::GoldIngot, 2),
                     },
                 ]
                 .into(),
@@ -758,7 +751,7 @@ mod tests {
                     },
                     SlotChange {
                         idx: 36,
-                        stack: ItemStack::new(ItemKind::GoldIngot, 2, None),
+                        stack: ItemStack::new(ItemKind::GoldIngot, 2),
                     },
                 ]
                 .into(),
@@ -775,7 +768,7 @@ mod tests {
                     stack: ItemStack::EMPTY,
                 }]
                 .into(),
-                carried_item: ItemStack::new(ItemKind::GoldIngot, 2, None),
+                carried_item: ItemStack::new(ItemKind::GoldIngot, 2),
             },
             ClickSlotC2s {
                 window_id: 0,
@@ -785,7 +778,7 @@ mod tests {
                 slot_idx: 9,
                 slot_changes: vec![SlotChange {
                     idx: 9,
-                    stack: ItemStack::new(ItemKind::GoldIngot, 1, None),
+                    stack: ItemStack::new(ItemKind::GoldIngot, 1),
                 }]
                 .into(),
                 carried_item: ItemStack::EMPTY,
@@ -802,8 +795,8 @@ mod tests {
     #[test]
     fn allow_shift_click_overflow_to_new_stack() {
         let mut player_inventory = Inventory::new(InventoryKind::Player);
-        player_inventory.set_slot(9, ItemStack::new(ItemKind::Diamond, 64, None));
-        player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 32, None));
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::Diamond, 64));
+        player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 32));
         let cursor_item = CursorItem::default();
 
         let packet = ClickSlotC2s {
@@ -815,11 +808,11 @@ mod tests {
             slot_changes: vec![
                 SlotChange {
                     idx: 37,
-                    stack: ItemStack::new(ItemKind::Diamond, 32, None),
+                    stack: ItemStack::new(ItemKind::Diamond, 32),
                 },
                 SlotChange {
                     idx: 36,
-                    stack: ItemStack::new(ItemKind::Diamond, 64, None),
+                    stack: ItemStack::new(ItemKind::Diamond, 64),
                 },
                 SlotChange {
                     idx: 9,
@@ -837,7 +830,7 @@ mod tests {
     #[test]
     fn allow_pickup_overfull_stack_click() {
         let mut player_inventory = Inventory::new(InventoryKind::Player);
-        player_inventory.set_slot(9, ItemStack::new(ItemKind::Apple, 100, None));
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::Apple, 100));
         let cursor_item = CursorItem::default();
 
         let packet = ClickSlotC2s {
@@ -851,7 +844,7 @@ mod tests {
                 stack: ItemStack::EMPTY,
             }]
             .into(),
-            carried_item: ItemStack::new(ItemKind::Apple, 100, None),
+            carried_item: ItemStack::new(ItemKind::Apple, 100),
         };
 
         validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
@@ -861,7 +854,7 @@ mod tests {
     #[test]
     fn allow_place_overfull_stack_click() {
         let player_inventory = Inventory::new(InventoryKind::Player);
-        let cursor_item = CursorItem(ItemStack::new(ItemKind::Apple, 100, None));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Apple, 100));
 
         let packet = ClickSlotC2s {
             window_id: 0,
@@ -871,10 +864,10 @@ mod tests {
             mode: ClickMode::Click,
             slot_changes: vec![SlotChange {
                 idx: 9,
-                stack: ItemStack::new(ItemKind::Apple, 64, None),
+                stack: ItemStack::new(ItemKind::Apple, 64),
             }]
             .into(),
-            carried_item: ItemStack::new(ItemKind::Apple, 36, None),
+            carried_item: ItemStack::new(ItemKind::Apple, 36),
         };
 
         validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
@@ -883,7 +876,7 @@ mod tests {
     #[test]
     fn allow_clicking_outside_inventory_when_not_holding_anything_success() {
         let player_inventory = Inventory::new(InventoryKind::Player);
-        let cursor_item = CursorItem(ItemStack::new(ItemKind::Air, 0, None));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Air, 0));
 
         let packet = ClickSlotC2s {
             window_id: 0,
@@ -893,7 +886,7 @@ mod tests {
             mode: ClickMode::DropKey, // when not holding an item and clicking outside the user
             // interface the client sends this kind of packet
             slot_changes: vec![].into(),
-            carried_item: ItemStack::new(ItemKind::Air, 0, None),
+            carried_item: ItemStack::new(ItemKind::Air, 0),
         };
 
         validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
@@ -902,7 +895,7 @@ mod tests {
     #[test]
     fn allow_clicking_outside_inventory_when_holding_something_success() {
         let player_inventory = Inventory::new(InventoryKind::Player);
-        let cursor_item = CursorItem(ItemStack::new(ItemKind::Air, 0, None));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Air, 0));
 
         // This is in the notchian server a stack drop
         let packet = ClickSlotC2s {
@@ -912,7 +905,7 @@ mod tests {
             button: 0,
             mode: ClickMode::Click, // when holding an item its a click
             slot_changes: vec![].into(),
-            carried_item: ItemStack::new(ItemKind::Air, 0, None),
+            carried_item: ItemStack::new(ItemKind::Air, 0),
         };
 
         validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
@@ -921,7 +914,7 @@ mod tests {
     #[test]
     fn allow_clicking_on_the_margin_area_in_inventory_success() {
         let player_inventory = Inventory::new(InventoryKind::Player);
-        let cursor_item = CursorItem(ItemStack::new(ItemKind::Air, 0, None));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Air, 0));
 
         let packet = ClickSlotC2s {
             window_id: 0,
@@ -930,7 +923,7 @@ mod tests {
             button: 0,
             mode: ClickMode::Click,
             slot_changes: vec![].into(),
-            carried_item: ItemStack::new(ItemKind::Air, 0, None),
+            carried_item: ItemStack::new(ItemKind::Air, 0),
         };
 
         validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
@@ -939,7 +932,7 @@ mod tests {
     #[test]
     fn allow_clicking_on_an_empty_slot_with_empty_carried_item_success() {
         let player_inventory = Inventory::new(InventoryKind::Player);
-        let cursor_item = CursorItem(ItemStack::new(ItemKind::Air, 0, None));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Air, 0));
 
         let packet = ClickSlotC2s {
             window_id: 0,
@@ -948,7 +941,7 @@ mod tests {
             button: 0,
             mode: ClickMode::Click,
             slot_changes: vec![].into(),
-            carried_item: ItemStack::new(ItemKind::Air, 0, None),
+            carried_item: ItemStack::new(ItemKind::Air, 0),
         };
 
         validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
@@ -957,7 +950,7 @@ mod tests {
     #[test]
     fn allow_clicking_hotbar_keybinds_when_both_source_and_target_are_empty() {
         let player_inventory = Inventory::new(InventoryKind::Player);
-        let cursor_item = CursorItem(ItemStack::new(ItemKind::Air, 0, None));
+        let cursor_item = CursorItem(ItemStack::new(ItemKind::Air, 0));
 
         let packet = ClickSlotC2s {
             window_id: 0,
@@ -966,7 +959,7 @@ mod tests {
             button: 0,
             mode: ClickMode::Hotbar,
             slot_changes: vec![].into(),
-            carried_item: ItemStack::new(ItemKind::Air, 0, None),
+            carried_item: ItemStack::new(ItemKind::Air, 0),
         };
 
         validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
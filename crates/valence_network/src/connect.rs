@@ -22,16 +22,21 @@ use valence_lang::keys;
 use valence_protocol::profile::Property;
 use valence_protocol::Decode;
 use valence_server::client::Properties;
+use valence_server::protocol::decode::PacketFrame;
+use valence_server::protocol::packets::configuration::{
+    ClientInformationC2s, CustomPayloadC2s, CustomPayloadS2c, FinishConfigurationC2s,
+    FinishConfigurationS2c, SelectKnownPacksC2s,
+};
 use valence_server::protocol::packets::handshaking::handshake_c2s::HandshakeNextState;
 use valence_server::protocol::packets::handshaking::HandshakeC2s;
 use valence_server::protocol::packets::login::{
-    LoginCompressionS2c, LoginDisconnectS2c, LoginHelloC2s, LoginHelloS2c, LoginKeyC2s,
-    LoginQueryRequestS2c, LoginQueryResponseC2s, LoginSuccessS2c,
+    LoginAcknowledgedC2s, LoginCompressionS2c, LoginDisconnectS2c, LoginHelloC2s, LoginHelloS2c,
+    LoginKeyC2s, LoginQueryRequestS2c, LoginQueryResponseC2s, LoginSuccessS2c,
 };
 use valence_server::protocol::packets::status::{
     QueryPingC2s, QueryPongS2c, QueryRequestC2s, QueryResponseS2c,
 };
-use valence_server::protocol::{PacketDecoder, PacketEncoder, RawBytes, VarInt};
+use valence_server::protocol::{Bounded, Packet, PacketDecoder, PacketEncoder, RawBytes, VarInt};
 use valence_server::text::{Color, IntoText};
 use valence_server::{ident, Text, MINECRAFT_VERSION, PROTOCOL_VERSION};
 
@@ -158,8 +163,13 @@ async fn handle_handshake(
                 .context("handling login")?
             {
                 Some((info, cleanup)) => {
+                    let pending = handle_configuration(&shared, &mut io, &info)
+                        .await
+                        .context("handling configuration")?;
+
                     let client = io.into_client_args(
                         info,
+                        pending,
                         shared.0.incoming_byte_limit,
                         shared.0.outgoing_byte_limit,
                         cleanup,
@@ -309,12 +319,73 @@ async fn handle_login(
         uuid: info.uuid,
         username: info.username.as_str().into(),
         properties: Default::default(),
+        strict_error_handling: false,
     })
     .await?;
 
+    io.recv_packet::<LoginAcknowledgedC2s>().await?;
+
     Ok(Some((info, cleanup)))
 }
 
+/// Sends the client through the configuration state. Returns the client
+/// settings and custom payloads received along the way, which are handled once
+/// the client is spawned.
+async fn handle_configuration(
+    shared: &SharedNetworkState,
+    io: &mut PacketIo,
+    info: &NewClientInfo,
+) -> anyhow::Result<Vec<PacketFrame>> {
+    let configuration = shared.0.configuration_packets.read().unwrap().clone();
+
+    let mut pending = vec![];
+
+    io.send_bytes(&configuration.known_packs).await?;
+
+    loop {
+        let frame = io.recv_frame().await?;
+
+        match frame.id {
+            SelectKnownPacksC2s::ID => {
+                frame.decode::<SelectKnownPacksC2s>()?;
+                break;
+            }
+            ClientInformationC2s::ID | CustomPayloadC2s::ID => pending.push(frame),
+            id => trace!("ignoring packet with ID {id} in the configuration state"),
+        }
+    }
+
+    io.send_bytes(&configuration.packets).await?;
+
+    for payload in shared
+        .0
+        .callbacks
+        .inner
+        .configuration_payloads(shared, info)
+        .await
+    {
+        io.send_packet(&CustomPayloadS2c {
+            channel: payload.channel.into(),
+            data: Bounded(RawBytes(&payload.data)),
+        })
+        .await?;
+    }
+
+    io.send_packet(&FinishConfigurationS2c).await?;
+
+    loop {
+        let frame = io.recv_frame().await?;
+
+        match frame.id {
+            FinishConfigurationC2s::ID => break,
+            ClientInformationC2s::ID | CustomPayloadC2s::ID => pending.push(frame),
+            id => trace!("ignoring packet with ID {id} in the configuration state"),
+        }
+    }
+
+    Ok(pending)
+}
+
 /// Login procedure for online mode.
 async fn login_online(
     shared: &SharedNetworkState,
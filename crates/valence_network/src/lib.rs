@@ -4,11 +4,13 @@ mod byte_channel;
 mod connect;
 mod legacy_ping;
 mod packet_io;
+#[cfg(feature = "rcon")]
+mod rcon;
 
 use std::borrow::Cow;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use anyhow::Context;
@@ -20,6 +22,8 @@ pub use connect::HandshakeData;
 use flume::{Receiver, Sender};
 pub use legacy_ping::{ServerListLegacyPingPayload, ServerListLegacyPingResponse};
 use rand::rngs::OsRng;
+#[cfg(feature = "rcon")]
+pub use rcon::RconSettings;
 use rsa::traits::PublicKeyParts;
 use rsa::RsaPrivateKey;
 use serde::Serialize;
@@ -31,7 +35,10 @@ use tracing::error;
 use uuid::Uuid;
 use valence_protocol::text::IntoText;
 use valence_server::client::{ClientBundle, ClientBundleArgs, Properties, SpawnClientsSet};
-use valence_server::{CompressionThreshold, Server, Text, MINECRAFT_VERSION, PROTOCOL_VERSION};
+use valence_server::configuration::ConfigurationSettings;
+use valence_server::{
+    CompressionThreshold, Ident, Server, Text, MINECRAFT_VERSION, PROTOCOL_VERSION,
+};
 
 pub struct NetworkPlugin;
 
@@ -54,6 +61,9 @@ fn build_plugin(app: &mut App) -> anyhow::Result<()> {
         .world_mut()
         .get_resource_or_insert_with(NetworkSettings::default);
 
+    #[cfg(feature = "rcon")]
+    let rcon = settings.rcon.clone();
+
     let (new_clients_send, new_clients_recv) = flume::bounded(64);
 
     let rsa_key = RsaPrivateKey::new(&mut OsRng, 1024)?;
@@ -86,6 +96,7 @@ fn build_plugin(app: &mut App) -> anyhow::Result<()> {
         max_players: settings.max_players,
         connection_mode: settings.connection_mode.clone(),
         threshold,
+        configuration_packets: RwLock::default(),
         tokio_handle,
         _tokio_runtime: runtime,
         new_clients_send,
@@ -97,6 +108,11 @@ fn build_plugin(app: &mut App) -> anyhow::Result<()> {
 
     app.insert_resource(shared.clone());
 
+    #[cfg(feature = "rcon")]
+    if let Some(rcon) = rcon {
+        rcon::build(app, rcon);
+    }
+
     // System for starting the accept loop.
     let start_accept_loop = move |shared: Res<SharedNetworkState>| {
         let _guard = shared.0.tokio_handle.enter();
@@ -111,6 +127,17 @@ fn build_plugin(app: &mut App) -> anyhow::Result<()> {
         tokio::spawn(do_broadcast_to_lan_loop(shared.clone()));
     };
 
+    // System for sharing the configuration packets with the login tasks.
+    let update_configuration_packets =
+        |shared: Res<SharedNetworkState>, settings: Res<ConfigurationSettings>| {
+            if settings.is_changed() {
+                *shared.0.configuration_packets.write().unwrap() = ConfigurationPackets {
+                    known_packs: settings.known_packs_packet().into(),
+                    packets: settings.configuration_packets().into(),
+                };
+            }
+        };
+
     // System for spawning new clients.
     let spawn_new_clients = move |world: &mut World| {
         for _ in 0..shared.0.new_clients_recv.len() {
@@ -131,6 +158,8 @@ fn build_plugin(app: &mut App) -> anyhow::Result<()> {
     // Spawn new clients before the event loop starts.
     app.add_systems(PreUpdate, spawn_new_clients.in_set(SpawnClientsSet));
 
+    app.add_systems(Last, update_configuration_packets);
+
     Ok(())
 }
 
@@ -163,6 +192,9 @@ struct SharedNetworkStateInner {
     max_players: usize,
     connection_mode: ConnectionMode,
     threshold: CompressionThreshold,
+    /// Copy of the packets cached in [`ConfigurationSettings`], sent to
+    /// clients in the configuration state.
+    configuration_packets: RwLock<ConfigurationPackets>,
     tokio_handle: Handle,
     // Holding a runtime handle is not enough to keep tokio working. We need
     // to store the runtime here so we don't drop it.
@@ -180,6 +212,12 @@ struct SharedNetworkStateInner {
     http_client: reqwest::Client,
 }
 
+#[derive(Clone, Default)]
+struct ConfigurationPackets {
+    known_packs: Arc<[u8]>,
+    packets: Arc<[u8]>,
+}
+
 /// Contains information about a new client joining the server.
 #[derive(Debug)]
 #[non_exhaustive]
@@ -258,6 +296,15 @@ pub struct NetworkSettings {
     ///
     /// The default value is left unspecified and may change in future versions.
     pub outgoing_byte_limit: usize,
+    /// The settings for the RCON listener, or `None` to not start one.
+    /// Commands received over RCON are executed by the `CommandPlugin` from
+    /// `valence_command`, which needs to be added to the app.
+    ///
+    /// # Default Value
+    ///
+    /// `None`
+    #[cfg(feature = "rcon")]
+    pub rcon: Option<RconSettings>,
 }
 
 impl Default for NetworkSettings {
@@ -273,6 +320,8 @@ impl Default for NetworkSettings {
             },
             incoming_byte_limit: 2097152, // 2 MiB
             outgoing_byte_limit: 8388608, // 8 MiB
+            #[cfg(feature = "rcon")]
+            rcon: None,
         }
     }
 }
@@ -451,6 +500,25 @@ pub trait NetworkCallbacks: Send + Sync + 'static {
         }
     }
 
+    /// Called for each client in the configuration state after the registries
+    /// and tags were sent. The returned payloads are sent to the client before
+    /// it enters the play state.
+    ///
+    /// This method is called from within a tokio runtime.
+    ///
+    /// # Default Implementation
+    ///
+    /// No payloads are sent.
+    async fn configuration_payloads(
+        &self,
+        shared: &SharedNetworkState,
+        info: &NewClientInfo,
+    ) -> Vec<ConfigurationPayload> {
+        #![allow(unused_variables)]
+
+        vec![]
+    }
+
     /// Called upon every client login to obtain the full URL to use for session
     /// server requests. This is done to authenticate player accounts. This
     /// method is not called unless [online mode] is enabled.
@@ -487,6 +555,16 @@ pub trait NetworkCallbacks: Send + Sync + 'static {
     }
 }
 
+/// A custom payload sent to a client in the configuration state. See
+/// [`NetworkCallbacks::configuration_payloads`].
+#[derive(Clone, Debug)]
+pub struct ConfigurationPayload {
+    /// The plugin channel of the payload.
+    pub channel: Ident<String>,
+    /// The content of the payload.
+    pub data: Vec<u8>,
+}
+
 /// A callback function called when the associated client is dropped. See
 /// [`NetworkCallbacks::login`] for more information.
 pub type CleanupFn = Box<dyn FnOnce() + Send + Sync + 'static>;
@@ -10,9 +10,11 @@ use tokio::net::TcpStream;
 use tokio::sync::Semaphore;
 use tokio::task::JoinHandle;
 use tracing::{debug, warn};
-use valence_protocol::CompressionThreshold;
+use valence_protocol::{CompressionThreshold, PacketState};
 use valence_server::client::{ClientBundleArgs, ClientConnection, ReceivedPacket};
 use valence_server::protocol::decode::PacketFrame;
+use valence_server::protocol::packets::configuration::FinishConfigurationC2s;
+use valence_server::protocol::packets::play::ConfigurationAcknowledgedC2s;
 use valence_server::protocol::{Decode, Encode, Packet, PacketDecoder, PacketEncoder};
 
 use crate::byte_channel::{byte_channel, ByteSender, TrySendError};
@@ -50,15 +52,27 @@ impl PacketIo {
         Ok(())
     }
 
+    /// Writes already encoded packets, such as a cached packet from the
+    /// server, to the stream.
+    pub(crate) async fn send_bytes(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        self.enc.append_bytes(bytes);
+        let bytes = self.enc.take();
+        self.stream.write_all(&bytes).await?;
+        Ok(())
+    }
+
     pub(crate) async fn recv_packet<'a, P>(&'a mut self) -> anyhow::Result<P>
     where
         P: Packet + Decode<'a>,
     {
+        self.frame = self.recv_frame().await?;
+        self.frame.decode()
+    }
+
+    pub(crate) async fn recv_frame(&mut self) -> anyhow::Result<PacketFrame> {
         loop {
             if let Some(frame) = self.dec.try_next_packet()? {
-                self.frame = frame;
-
-                return self.frame.decode();
+                return Ok(frame);
             }
 
             self.dec.reserve(READ_BUF_SIZE);
@@ -85,9 +99,13 @@ impl PacketIo {
         self.dec.enable_encryption(key);
     }
 
+    /// Turns this connection into the arguments for a new client in the play
+    /// state. `pending` contains packets received during the configuration
+    /// state which are handed to the client before anything else.
     pub(crate) fn into_client_args(
         mut self,
         info: NewClientInfo,
+        pending: Vec<PacketFrame>,
         incoming_byte_limit: usize,
         outgoing_byte_limit: usize,
         cleanup: CleanupOnDrop,
@@ -99,11 +117,34 @@ impl PacketIo {
         let recv_sem = Arc::new(Semaphore::new(incoming_byte_limit));
         let recv_sem_clone = recv_sem.clone();
 
+        let timestamp = Instant::now();
+
+        for frame in pending {
+            let cost = mem::size_of::<ReceivedPacket>() + frame.body.len();
+
+            let Ok(permits) = recv_sem.try_acquire_many(cost as u32) else {
+                break;
+            };
+
+            permits.forget();
+
+            let _ = incoming_sender.send(ReceivedPacket {
+                timestamp,
+                state: PacketState::Configuration,
+                id: frame.id,
+                body: frame.body.freeze(),
+            });
+        }
+
         let (mut reader, mut writer) = self.stream.into_split();
 
         let reader_task = tokio::spawn(async move {
             let mut buf = BytesMut::new();
 
+            // The client switches between the play and configuration state on its own
+            // after it's told to, so we track the state from the acknowledgements.
+            let mut state = PacketState::Play;
+
             loop {
                 let frame = match self.dec.try_next_packet() {
                     Ok(Some(frame)) => frame,
@@ -157,10 +198,21 @@ impl PacketIo {
 
                 let packet = ReceivedPacket {
                     timestamp,
+                    state,
                     id: frame.id,
                     body: frame.body.freeze(),
                 };
 
+                match (state, packet.id) {
+                    (PacketState::Play, ConfigurationAcknowledgedC2s::ID) => {
+                        state = PacketState::Configuration;
+                    }
+                    (PacketState::Configuration, FinishConfigurationC2s::ID) => {
+                        state = PacketState::Play;
+                    }
+                    _ => {}
+                }
+
                 if incoming_sender.try_send(packet).is_err() {
                     // Channel closed.
                     break;
@@ -0,0 +1,524 @@
+//! The remote console (RCON) listener.
+//!
+//! RCON clients authenticate with a password and then send commands, which
+//! are executed as [`CommandExecutionEvent`]s by a new executor with the
+//! [`CommandSource::Rcon`] source. Everything replied to the executor in the
+//! same tick is sent back to the client as the response.
+
+use std::collections::BTreeSet;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{ensure, Context};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use flume::{Receiver, Sender};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tracing::{debug, error, info};
+use valence_command::scopes::CommandScopes;
+use valence_command::source::{to_plain_text, CommandSource, ReplyChannel};
+use valence_command::{CommandExecutionEvent, CommandSystemSet};
+use valence_server::{EventLoopPreUpdate, Text};
+
+use crate::SharedNetworkState;
+
+/// Settings for the RCON listener. See [`NetworkSettings::rcon`].
+///
+/// [`NetworkSettings::rcon`]: crate::NetworkSettings::rcon
+#[derive(Clone, Debug)]
+pub struct RconSettings {
+    /// The socket address the RCON listener will be bound to.
+    ///
+    /// # Default Value
+    ///
+    /// `0.0.0.0:25575`
+    pub address: SocketAddr,
+    /// The password clients need to authenticate with. Like in vanilla, the
+    /// listener isn't started if this is empty.
+    ///
+    /// # Default Value
+    ///
+    /// Empty.
+    pub password: String,
+    /// How long the listener waits for the next packet of a client before
+    /// closing the connection.
+    ///
+    /// # Default Value
+    ///
+    /// 60 seconds.
+    pub read_timeout: Duration,
+}
+
+impl Default for RconSettings {
+    fn default() -> Self {
+        Self {
+            address: SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 25575).into(),
+            password: String::new(),
+            read_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+const TYPE_RESPONSE: i32 = 0;
+const TYPE_COMMAND: i32 = 2;
+const TYPE_AUTH_RESPONSE: i32 = 2;
+const TYPE_AUTH: i32 = 3;
+
+/// The request ID sent back when authentication fails.
+const AUTH_FAILURE_ID: i32 = -1;
+
+/// The largest packet length accepted from clients, the same as vanilla.
+const MAX_INCOMING_LEN: usize = 1460;
+/// The largest body of a response packet. Longer responses are split into
+/// multiple packets.
+const MAX_RESPONSE_BODY_LEN: usize = 4096;
+
+pub(crate) fn build(app: &mut App, settings: RconSettings) {
+    if settings.password.is_empty() {
+        error!("not starting the RCON listener because the password is empty");
+        return;
+    }
+
+    let (commands_send, commands_recv) = flume::unbounded();
+
+    let settings = Arc::new(settings);
+
+    // Start the listener in `PostStartup` like the game listener.
+    let start_rcon_loop = move |shared: Res<SharedNetworkState>| {
+        let _guard = shared.0.tokio_handle.enter();
+
+        tokio::spawn(do_rcon_accept_loop(settings.clone(), commands_send.clone()));
+    };
+
+    app.insert_resource(RconCommands(commands_recv))
+        .add_systems(PostStartup, start_rcon_loop)
+        .add_systems(
+            EventLoopPreUpdate,
+            execute_rcon_commands.before(CommandSystemSet),
+        )
+        .add_systems(Last, send_rcon_responses);
+}
+
+/// A command received from an RCON client.
+struct RconCommand {
+    command: String,
+    response: oneshot::Sender<String>,
+}
+
+#[derive(Resource)]
+struct RconCommands(Receiver<RconCommand>);
+
+/// The executor of a single RCON command.
+#[derive(Component)]
+struct RconExecutor {
+    replies: Receiver<Text>,
+    response: Option<oneshot::Sender<String>>,
+}
+
+fn execute_rcon_commands(
+    rcon_commands: Res<RconCommands>,
+    mut commands: Commands,
+    mut events: EventWriter<CommandExecutionEvent>,
+) {
+    for RconCommand { command, response } in rcon_commands.0.try_iter() {
+        let (replies_send, replies_recv) = flume::unbounded();
+
+        let executor = commands
+            .spawn((
+                CommandSource::Rcon,
+                // the root scope grants every other scope
+                CommandScopes(BTreeSet::from(["root".to_owned()])),
+                ReplyChannel(replies_send),
+                RconExecutor {
+                    replies: replies_recv,
+                    response: Some(response),
+                },
+            ))
+            .id();
+
+        let command = command.trim();
+
+        events.send(CommandExecutionEvent {
+            command: command.strip_prefix('/').unwrap_or(command).to_owned(),
+            executor,
+        });
+    }
+}
+
+fn send_rcon_responses(mut executors: Query<(Entity, &mut RconExecutor)>, mut commands: Commands) {
+    for (entity, mut executor) in &mut executors {
+        let response = executor
+            .replies
+            .try_iter()
+            .map(|reply| to_plain_text(&reply))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if let Some(sender) = executor.response.take() {
+            // the connection might have been closed in the meantime
+            let _ = sender.send(response);
+        }
+
+        commands.entity(entity).despawn();
+    }
+}
+
+async fn do_rcon_accept_loop(settings: Arc<RconSettings>, commands: Sender<RconCommand>) {
+    let listener = match TcpListener::bind(settings.address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("failed to start RCON listener: {e}");
+            return;
+        }
+    };
+
+    info!("RCON listening on {}", settings.address);
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, remote_addr)) => {
+                let settings = settings.clone();
+                let commands = commands.clone();
+
+                tokio::spawn(async move {
+                    if let Err(e) = handle_rcon_connection(stream, &settings, &commands).await {
+                        debug!("RCON connection with {remote_addr} closed: {e:#}");
+                    }
+                });
+            }
+            Err(e) => {
+                error!("failed to accept incoming RCON connection: {e}");
+            }
+        }
+    }
+}
+
+async fn handle_rcon_connection(
+    mut stream: impl AsyncRead + AsyncWrite + Unpin,
+    settings: &RconSettings,
+    commands: &Sender<RconCommand>,
+) -> anyhow::Result<()> {
+    let mut authenticated = false;
+    let mut buf = Vec::new();
+
+    while let Some(packet) = read_packet(&mut stream, settings.read_timeout).await? {
+        buf.clear();
+
+        match packet.kind {
+            TYPE_AUTH => {
+                authenticated = password_matches(&packet.body, &settings.password);
+
+                let id = if authenticated {
+                    packet.id
+                } else {
+                    AUTH_FAILURE_ID
+                };
+                encode_packet(id, TYPE_AUTH_RESPONSE, "", &mut buf);
+            }
+            TYPE_COMMAND if authenticated => {
+                let (response_send, response_recv) = oneshot::channel();
+
+                commands
+                    .send_async(RconCommand {
+                        command: packet.body,
+                        response: response_send,
+                    })
+                    .await
+                    .context("server stopped")?;
+
+                let response = response_recv.await.context("server stopped")?;
+                encode_response(packet.id, &response, &mut buf);
+            }
+            TYPE_COMMAND => encode_packet(AUTH_FAILURE_ID, TYPE_AUTH_RESPONSE, "", &mut buf),
+            kind => encode_response(packet.id, &format!("Unknown request {kind:x}"), &mut buf),
+        }
+
+        stream.write_all(&buf).await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct RconPacket {
+    id: i32,
+    kind: i32,
+    body: String,
+}
+
+/// Reads the next packet, or `None` if the client closed the connection.
+/// Fails if the whole packet doesn't arrive within `timeout`.
+async fn read_packet(
+    stream: &mut (impl AsyncRead + Unpin),
+    timeout: Duration,
+) -> anyhow::Result<Option<RconPacket>> {
+    tokio::time::timeout(timeout, read_packet_inner(stream))
+        .await
+        .context("timed out waiting for a packet")?
+}
+
+async fn read_packet_inner(
+    stream: &mut (impl AsyncRead + Unpin),
+) -> anyhow::Result<Option<RconPacket>> {
+    let len = match stream.read_i32_le().await {
+        Ok(len) => len,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let len = usize::try_from(len).unwrap_or(0);
+
+    // the ID, the type and the two null bytes ending the body
+    ensure!(
+        (10..=MAX_INCOMING_LEN).contains(&len),
+        "invalid RCON packet length of {len}"
+    );
+
+    let mut data = vec![0; len];
+    stream.read_exact(&mut data).await?;
+
+    Ok(Some(decode_packet(&data)))
+}
+
+/// Compares a password in constant time, so the time it takes to reject an
+/// attempt doesn't reveal how much of it was correct. Only the length of the
+/// password can be learned.
+fn password_matches(attempt: &str, password: &str) -> bool {
+    if attempt.len() != password.len() {
+        return false;
+    }
+
+    let diff = attempt
+        .bytes()
+        .zip(password.bytes())
+        .fold(0, |diff, (a, b)| std::hint::black_box(diff | (a ^ b)));
+
+    diff == 0
+}
+
+/// Decodes a packet without its length prefix.
+fn decode_packet(data: &[u8]) -> RconPacket {
+    let id = i32::from_le_bytes(data[0..4].try_into().unwrap());
+    let kind = i32::from_le_bytes(data[4..8].try_into().unwrap());
+
+    // the body ends at the first null byte
+    let body = &data[8..];
+    let body = body
+        .iter()
+        .position(|&b| b == 0)
+        .map_or(body, |end| &body[..end]);
+
+    RconPacket {
+        id,
+        kind,
+        body: String::from_utf8_lossy(body).into_owned(),
+    }
+}
+
+fn encode_packet(id: i32, kind: i32, body: &str, buf: &mut Vec<u8>) {
+    let len = 4 + 4 + body.len() + 2;
+
+    buf.extend_from_slice(&(len as i32).to_le_bytes());
+    buf.extend_from_slice(&id.to_le_bytes());
+    buf.extend_from_slice(&kind.to_le_bytes());
+    buf.extend_from_slice(body.as_bytes());
+    buf.extend_from_slice(&[0, 0]);
+}
+
+/// Encodes a response, split into multiple packets if the body is too long
+/// for one.
+fn encode_response(id: i32, mut body: &str, buf: &mut Vec<u8>) {
+    loop {
+        let mut end = body.len().min(MAX_RESPONSE_BODY_LEN);
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let (part, rest) = body.split_at(end);
+        encode_packet(id, TYPE_RESPONSE, part, buf);
+
+        if rest.is_empty() {
+            return;
+        }
+
+        body = rest;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::DuplexStream;
+    use tokio::task::JoinHandle;
+
+    use super::*;
+
+    const PASSWORD: &str = "hunter2";
+
+    /// Handles a connection on one end of an in-memory stream and returns the
+    /// other end, the commands received by the server and the connection task.
+    fn connect(
+        read_timeout: Duration,
+    ) -> (
+        DuplexStream,
+        Receiver<RconCommand>,
+        JoinHandle<anyhow::Result<()>>,
+    ) {
+        let (client, server) = tokio::io::duplex(MAX_INCOMING_LEN * 2);
+        let (commands_send, commands_recv) = flume::unbounded();
+
+        let settings = RconSettings {
+            password: PASSWORD.to_owned(),
+            read_timeout,
+            ..Default::default()
+        };
+
+        let task =
+            tokio::spawn(
+                async move { handle_rcon_connection(server, &settings, &commands_send).await },
+            );
+
+        (client, commands_recv, task)
+    }
+
+    async fn send(stream: &mut DuplexStream, id: i32, kind: i32, body: &str) {
+        let mut buf = vec![];
+        encode_packet(id, kind, body, &mut buf);
+        stream.write_all(&buf).await.unwrap();
+    }
+
+    async fn recv(stream: &mut DuplexStream) -> RconPacket {
+        read_packet(stream, Duration::from_secs(5))
+            .await
+            .unwrap()
+            .expect("the server closed the connection")
+    }
+
+    fn decode_all(mut buf: &[u8]) -> Vec<RconPacket> {
+        let mut packets = vec![];
+
+        while !buf.is_empty() {
+            let len = i32::from_le_bytes(buf[0..4].try_into().unwrap()) as usize;
+            packets.push(decode_packet(&buf[4..4 + len]));
+            buf = &buf[4 + len..];
+        }
+
+        packets
+    }
+
+    #[test]
+    fn packet_round_trip() {
+        let mut buf = vec![];
+        encode_packet(7, TYPE_COMMAND, "time set day", &mut buf);
+
+        assert_eq!(buf.len(), 4 + 10 + 12);
+        assert_eq!(
+            decode_all(&buf),
+            [RconPacket {
+                id: 7,
+                kind: TYPE_COMMAND,
+                body: "time set day".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn long_responses_are_split() {
+        let body = "é".repeat(MAX_RESPONSE_BODY_LEN);
+
+        let mut buf = vec![];
+        encode_response(3, &body, &mut buf);
+
+        let packets = decode_all(&buf);
+
+        assert_eq!(packets.len(), 2);
+        assert!(packets.iter().all(|p| p.id == 3 && p.kind == TYPE_RESPONSE));
+        assert_eq!(packets[0].body.len(), MAX_RESPONSE_BODY_LEN);
+        assert_eq!(packets[0].body.clone() + &packets[1].body, body);
+
+        let mut buf = vec![];
+        encode_response(4, "", &mut buf);
+
+        assert_eq!(decode_all(&buf).len(), 1);
+    }
+
+    #[test]
+    fn password_comparison() {
+        assert!(password_matches(PASSWORD, PASSWORD));
+        assert!(!password_matches("hunter3", PASSWORD));
+        assert!(!password_matches("hunter", PASSWORD));
+        assert!(!password_matches("", PASSWORD));
+    }
+
+    #[tokio::test]
+    async fn auth_success() {
+        let (mut client, _commands, _task) = connect(Duration::from_secs(5));
+
+        send(&mut client, 1, TYPE_AUTH, PASSWORD).await;
+
+        assert_eq!(
+            recv(&mut client).await,
+            RconPacket {
+                id: 1,
+                kind: TYPE_AUTH_RESPONSE,
+                body: String::new(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn auth_failure() {
+        let (mut client, _commands, _task) = connect(Duration::from_secs(5));
+
+        send(&mut client, 1, TYPE_AUTH, "wrong").await;
+
+        let response = recv(&mut client).await;
+        assert_eq!(response.id, AUTH_FAILURE_ID);
+        assert_eq!(response.kind, TYPE_AUTH_RESPONSE);
+    }
+
+    #[tokio::test]
+    async fn unauthenticated_command_is_rejected() {
+        let (mut client, commands, _task) = connect(Duration::from_secs(5));
+
+        send(&mut client, 2, TYPE_COMMAND, "stop").await;
+
+        let response = recv(&mut client).await;
+        assert_eq!(response.id, AUTH_FAILURE_ID);
+        assert_eq!(response.kind, TYPE_AUTH_RESPONSE);
+        assert!(commands.is_empty());
+    }
+
+    #[tokio::test]
+    async fn command_reply_reaches_client() {
+        let (mut client, commands, _task) = connect(Duration::from_secs(5));
+
+        send(&mut client, 1, TYPE_AUTH, PASSWORD).await;
+        recv(&mut client).await;
+
+        send(&mut client, 7, TYPE_COMMAND, "time set day").await;
+
+        let command = commands.recv_async().await.unwrap();
+        assert_eq!(command.command, "time set day");
+        command.response.send("Set the time".to_owned()).unwrap();
+
+        assert_eq!(
+            recv(&mut client).await,
+            RconPacket {
+                id: 7,
+                kind: TYPE_RESPONSE,
+                body: "Set the time".to_owned(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn idle_connection_times_out() {
+        let (_client, _commands, task) = connect(Duration::from_millis(10));
+
+        assert!(task.await.unwrap().is_err());
+    }
+}
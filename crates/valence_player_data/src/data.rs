@@ -0,0 +1,525 @@
+use valence_inventory::player_inventory::PlayerInventory;
+use valence_nbt::{Compound, List, Value};
+use valence_server::entity::active_status_effects::ActiveStatusEffect;
+use valence_server::entity::player::Experience;
+use valence_server::item::component::{EnchantmentLevel, Enchantments};
+use valence_server::item::ItemComponentKind;
+use valence_server::math::DVec3;
+use valence_server::protocol::status_effects::StatusEffect;
+use valence_server::protocol::VarInt;
+use valence_server::registry::RegistryCodec;
+use valence_server::{ident, GameMode, Ident, ItemComponent, ItemKind, ItemStack, Text};
+
+/// The data version written to saved player data. This is the data version of
+/// Minecraft 1.21.
+pub const DATA_VERSION: i32 = 3955;
+
+/// The state of a player that is persisted across connections.
+///
+/// Every field is optional so that partially written or foreign data can still
+/// be loaded. Missing fields leave the corresponding component untouched.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct PlayerData {
+    /// The name of the dimension type of the layer the player was in.
+    pub dimension: Option<Ident<String>>,
+    pub position: Option<DVec3>,
+    /// The yaw and pitch of the player in degrees.
+    pub look: Option<[f32; 2]>,
+    pub game_mode: Option<GameMode>,
+    /// The selected hotbar slot in `0..9`.
+    pub selected_slot: Option<u8>,
+    /// Non-empty slots of the player inventory, keyed by the slot index used
+    /// by [`PlayerInventory`].
+    pub inventory: Vec<(u16, ItemStack)>,
+    pub active_effects: Vec<ActiveStatusEffect>,
+    pub experience: Option<Experience>,
+    pub health: Option<f32>,
+    pub food: Option<i32>,
+    pub saturation: Option<f32>,
+}
+
+impl PlayerData {
+    /// Reads player data from the vanilla `playerdata` NBT format. Fields that
+    /// are missing or invalid are skipped.
+    ///
+    /// The codec is used to resolve enchantment names to their network IDs.
+    pub fn from_nbt(nbt: &Compound, codec: &RegistryCodec) -> Self {
+        let mut data = Self::default();
+
+        if let Some(Value::String(dimension)) = nbt.get("Dimension") {
+            data.dimension = Ident::try_from(dimension.as_str()).ok();
+        }
+
+        if let Some(Value::List(List::Double(pos))) = nbt.get("Pos") {
+            if let [x, y, z] = pos[..] {
+                if x.is_finite() && y.is_finite() && z.is_finite() {
+                    data.position = Some(DVec3::new(x, y, z));
+                }
+            }
+        }
+
+        if let Some(Value::List(List::Float(rot))) = nbt.get("Rotation") {
+            if let [yaw, pitch] = rot[..] {
+                data.look = Some([yaw, pitch]);
+            }
+        }
+
+        if let Some(Value::Int(mode)) = nbt.get("playerGameType") {
+            data.game_mode = match mode {
+                0 => Some(GameMode::Survival),
+                1 => Some(GameMode::Creative),
+                2 => Some(GameMode::Adventure),
+                3 => Some(GameMode::Spectator),
+                _ => None,
+            };
+        }
+
+        if let Some(Value::Int(slot)) = nbt.get("SelectedItemSlot") {
+            data.selected_slot = u8::try_from(*slot).ok().filter(|&s| s < 9);
+        }
+
+        if let Some(Value::List(List::Compound(items))) = nbt.get("Inventory") {
+            data.inventory = items
+                .iter()
+                .filter_map(|item| {
+                    let Some(Value::Byte(slot)) = item.get("Slot") else {
+                        return None;
+                    };
+
+                    Some((slot_from_nbt(*slot)?, item_from_nbt(item, codec)?))
+                })
+                .collect();
+        }
+
+        if let Some(Value::List(List::Compound(effects))) = nbt.get("active_effects") {
+            data.active_effects = effects.iter().filter_map(effect_from_nbt).collect();
+        }
+
+        if let (Some(Value::Int(level)), Some(Value::Float(progress)), Some(Value::Int(total))) =
+            (nbt.get("XpLevel"), nbt.get("XpP"), nbt.get("XpTotal"))
+        {
+            data.experience = Some(Experience {
+                level: *level,
+                progress: *progress,
+                total: *total,
+            });
+        }
+
+        if let Some(Value::Float(health)) = nbt.get("Health") {
+            data.health = Some(*health);
+        }
+
+        if let Some(Value::Int(food)) = nbt.get("foodLevel") {
+            data.food = Some(*food);
+        }
+
+        if let Some(Value::Float(saturation)) = nbt.get("foodSaturationLevel") {
+            data.saturation = Some(*saturation);
+        }
+
+        data
+    }
+
+    /// Writes this player data to `nbt` in the vanilla `playerdata` format.
+    /// Keys not written by this function are left as is, so data written by
+    /// other programs is preserved.
+    ///
+    /// The codec is used to resolve enchantment network IDs to their names.
+    pub fn write_nbt(&self, nbt: &mut Compound, codec: &RegistryCodec) {
+        nbt.insert("DataVersion", DATA_VERSION);
+
+        if let Some(dimension) = &self.dimension {
+            nbt.insert("Dimension", dimension.to_string());
+        }
+
+        if let Some(pos) = self.position {
+            nbt.insert("Pos", List::Double(vec![pos.x, pos.y, pos.z]));
+        }
+
+        if let Some([yaw, pitch]) = self.look {
+            nbt.insert("Rotation", List::Float(vec![yaw, pitch]));
+        }
+
+        if let Some(game_mode) = self.game_mode {
+            nbt.insert("playerGameType", game_mode as i32);
+        }
+
+        if let Some(slot) = self.selected_slot {
+            nbt.insert("SelectedItemSlot", i32::from(slot));
+        }
+
+        let items = self
+            .inventory
+            .iter()
+            .filter(|(_, stack)| !stack.is_empty())
+            .filter_map(|(slot, stack)| {
+                let mut item = item_to_nbt(stack, codec);
+                item.insert("Slot", slot_to_nbt(*slot)?);
+                Some(item)
+            })
+            .collect();
+
+        nbt.insert("Inventory", List::Compound(items));
+
+        let effects = self.active_effects.iter().map(effect_to_nbt).collect();
+
+        nbt.insert("active_effects", List::Compound(effects));
+
+        if let Some(experience) = self.experience {
+            nbt.insert("XpLevel", experience.level);
+            nbt.insert("XpP", experience.progress);
+            nbt.insert("XpTotal", experience.total);
+        }
+
+        if let Some(health) = self.health {
+            nbt.insert("Health", health);
+        }
+
+        if let Some(food) = self.food {
+            nbt.insert("foodLevel", food);
+        }
+
+        if let Some(saturation) = self.saturation {
+            nbt.insert("foodSaturationLevel", saturation);
+        }
+    }
+
+    /// Returns this player data as a new compound in the vanilla `playerdata`
+    /// format.
+    pub fn to_nbt(&self, codec: &RegistryCodec) -> Compound {
+        let mut nbt = Compound::new();
+        self.write_nbt(&mut nbt, codec);
+        nbt
+    }
+}
+
+/// Converts a vanilla inventory slot to a [`PlayerInventory`] slot.
+fn slot_from_nbt(slot: i8) -> Option<u16> {
+    match slot {
+        0..=8 => Some(PlayerInventory::SLOTS_HOTBAR.start() + slot as u16),
+        9..=35 => Some(slot as u16),
+        100..=103 => Some(PlayerInventory::SLOT_HEAD + (103 - slot) as u16),
+        -106 => Some(PlayerInventory::SLOT_OFFHAND),
+        _ => None,
+    }
+}
+
+/// Converts a [`PlayerInventory`] slot to a vanilla inventory slot. The
+/// crafting grid is not saved.
+fn slot_to_nbt(slot: u16) -> Option<i8> {
+    match slot {
+        PlayerInventory::SLOT_HEAD..=PlayerInventory::SLOT_FEET => {
+            Some(103 - (slot - PlayerInventory::SLOT_HEAD) as i8)
+        }
+        PlayerInventory::SLOT_OFFHAND => Some(-106),
+        9..=35 => Some(slot as i8),
+        _ if PlayerInventory::SLOTS_HOTBAR.contains(&slot) => {
+            Some((slot - PlayerInventory::SLOTS_HOTBAR.start()) as i8)
+        }
+        _ => None,
+    }
+}
+
+fn item_from_nbt(nbt: &Compound, codec: &RegistryCodec) -> Option<ItemStack> {
+    let Some(Value::String(id)) = nbt.get("id") else {
+        return None;
+    };
+
+    let kind = ItemKind::from_str(Ident::new(id.as_str()).ok()?.path())?;
+
+    let count = match nbt.get("count") {
+        Some(Value::Int(count)) => *count,
+        Some(Value::Byte(count)) => i32::from(*count),
+        _ => 1,
+    };
+
+    // Stacks larger than an `i8` can be written by vanilla and other servers.
+    // Keep as many items as fit instead of dropping the stack.
+    let count = count.clamp(0, i8::MAX.into()) as i8;
+
+    let mut stack = ItemStack::new(kind, count);
+
+    let Some(Value::Compound(components)) = nbt.get("components") else {
+        return Some(stack);
+    };
+
+    if let Some(Value::Int(damage)) = components.get("minecraft:damage") {
+        stack.components.insert(ItemComponent::Damage(*damage));
+    }
+
+    if let Some(Value::Int(cost)) = components.get("minecraft:repair_cost") {
+        stack.components.insert(ItemComponent::RepairCost(*cost));
+    }
+
+    if let Some(Value::Compound(data)) = components.get("minecraft:custom_data") {
+        stack
+            .components
+            .insert(ItemComponent::CustomData(data.clone()));
+    }
+
+    if let Some(Value::String(name)) = components.get("minecraft:custom_name") {
+        if let Ok(name) = name.parse::<Text>() {
+            stack.components.insert(ItemComponent::CustomName(name));
+        }
+    }
+
+    if let Some(Value::Compound(enchantments)) = components.get("minecraft:enchantments") {
+        stack
+            .components
+            .insert(ItemComponent::Enchantments(enchantments_from_nbt(
+                enchantments,
+                codec,
+            )));
+    }
+
+    Some(stack)
+}
+
+fn item_to_nbt(stack: &ItemStack, codec: &RegistryCodec) -> Compound {
+    let mut nbt = Compound::new();
+
+    nbt.insert("id", format!("minecraft:{}", stack.item.to_str()));
+    nbt.insert("count", i32::from(stack.count));
+
+    let mut components = Compound::new();
+
+    for component in &stack.components {
+        match component {
+            ItemComponent::Damage(damage) => {
+                components.insert("minecraft:damage", *damage);
+            }
+            ItemComponent::RepairCost(cost) => {
+                components.insert("minecraft:repair_cost", *cost);
+            }
+            ItemComponent::CustomData(data) => {
+                components.insert("minecraft:custom_data", data.clone());
+            }
+            ItemComponent::CustomName(name) => {
+                components.insert("minecraft:custom_name", name.to_string());
+            }
+            ItemComponent::Enchantments(enchantments) => {
+                components.insert(
+                    "minecraft:enchantments",
+                    enchantments_to_nbt(enchantments, codec),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    if stack.components.is_removed(ItemComponentKind::Enchantments) {
+        components.insert("!minecraft:enchantments", Compound::new());
+    }
+
+    if !components.is_empty() {
+        nbt.insert("components", components);
+    }
+
+    nbt
+}
+
+fn enchantments_from_nbt(nbt: &Compound, codec: &RegistryCodec) -> Enchantments {
+    let mut enchantments = vec![];
+
+    if let Some(Value::Compound(levels)) = nbt.get("levels") {
+        for (name, level) in levels {
+            let (Ok(name), Value::Int(level)) = (Ident::new(name.as_str()), level) else {
+                continue;
+            };
+
+            if let Some(id) = codec.entry_index(ident!("enchantment"), name.as_str_ident()) {
+                enchantments.push(EnchantmentLevel {
+                    id: VarInt(id as i32),
+                    level: VarInt(*level),
+                });
+            }
+        }
+    }
+
+    let show_in_tooltip = !matches!(nbt.get("show_in_tooltip"), Some(Value::Byte(0)));
+
+    Enchantments {
+        enchantments,
+        show_in_tooltip,
+    }
+}
+
+fn enchantments_to_nbt(enchantments: &Enchantments, codec: &RegistryCodec) -> Compound {
+    let registry = codec
+        .registries
+        .get(ident!("enchantment").as_str())
+        .map_or(&[][..], Vec::as_slice);
+
+    let mut levels = Compound::new();
+
+    for enchantment in &enchantments.enchantments {
+        if let Some(value) = usize::try_from(enchantment.id.0)
+            .ok()
+            .and_then(|id| registry.get(id))
+        {
+            levels.insert(value.name.as_str(), enchantment.level.0);
+        }
+    }
+
+    let mut nbt = Compound::new();
+    nbt.insert("levels", levels);
+
+    if !enchantments.show_in_tooltip {
+        nbt.insert("show_in_tooltip", false);
+    }
+
+    nbt
+}
+
+fn effect_from_nbt(nbt: &Compound) -> Option<ActiveStatusEffect> {
+    let Some(Value::String(id)) = nbt.get("id") else {
+        return None;
+    };
+
+    let effect = StatusEffect::from_ident(Ident::new(id.as_str()).ok()?.as_str_ident())?;
+
+    let mut active = ActiveStatusEffect::from_effect(effect);
+
+    if let Some(Value::Byte(amplifier)) = nbt.get("amplifier") {
+        active = active.with_amplifier(*amplifier as u8);
+    }
+
+    match nbt.get("duration") {
+        Some(Value::Int(-1)) => active = active.with_infinite(),
+        Some(Value::Int(duration)) => active = active.with_duration(*duration),
+        _ => {}
+    }
+
+    let flag = |key: &str, default: bool| match nbt.get(key) {
+        Some(Value::Byte(b)) => *b != 0,
+        _ => default,
+    };
+
+    Some(
+        active
+            .with_ambient(flag("ambient", false))
+            .with_show_particles(flag("show_particles", true))
+            .with_show_icon(flag("show_icon", true)),
+    )
+}
+
+fn effect_to_nbt(effect: &ActiveStatusEffect) -> Compound {
+    let mut nbt = Compound::new();
+
+    nbt.insert("id", effect.status_effect().to_ident().to_string());
+    nbt.insert("amplifier", effect.amplifier() as i8);
+    nbt.insert("duration", effect.remaining_duration().unwrap_or(-1));
+    nbt.insert("ambient", effect.ambient());
+    nbt.insert("show_particles", effect.show_particles());
+    nbt.insert("show_icon", effect.show_icon());
+
+    nbt
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_server::entity::active_status_effects::ActiveStatusEffect;
+
+    use super::*;
+
+    #[test]
+    fn player_data_round_trip() {
+        let codec = RegistryCodec::default();
+
+        let mut sword = ItemStack::new(ItemKind::DiamondSword, 1);
+        sword.components.insert(ItemComponent::Damage(42));
+        sword
+            .components
+            .insert(ItemComponent::CustomName("Excalibur".into()));
+
+        let data = PlayerData {
+            dimension: Some(ident!("the_nether").into()),
+            position: Some(DVec3::new(1.5, 64.0, -3.25)),
+            look: Some([90.0, -10.0]),
+            game_mode: Some(GameMode::Adventure),
+            selected_slot: Some(4),
+            inventory: vec![
+                (
+                    PlayerInventory::SLOT_HEAD,
+                    ItemStack::new(ItemKind::IronHelmet, 1),
+                ),
+                (*PlayerInventory::SLOTS_HOTBAR.start(), sword),
+                (20, ItemStack::new(ItemKind::Stone, 64)),
+                (
+                    PlayerInventory::SLOT_OFFHAND,
+                    ItemStack::new(ItemKind::Shield, 1),
+                ),
+            ],
+            active_effects: vec![
+                ActiveStatusEffect::from_effect(StatusEffect::Speed)
+                    .with_amplifier(1)
+                    .with_duration(200),
+                ActiveStatusEffect::from_effect(StatusEffect::NightVision).with_infinite(),
+            ],
+            experience: Some(Experience {
+                level: 5,
+                progress: 0.5,
+                total: 60,
+            }),
+            health: Some(12.0),
+            food: Some(15),
+            saturation: Some(2.5),
+        };
+
+        let nbt = data.to_nbt(&codec);
+
+        let Some(Value::List(List::Compound(items))) = nbt.get("Inventory") else {
+            panic!("missing inventory");
+        };
+
+        let slots: Vec<_> = items.iter().map(|item| item.get("Slot").cloned()).collect();
+
+        assert_eq!(
+            slots,
+            [
+                Some(Value::Byte(103)),
+                Some(Value::Byte(0)),
+                Some(Value::Byte(20)),
+                Some(Value::Byte(-106))
+            ]
+        );
+
+        assert_eq!(PlayerData::from_nbt(&nbt, &codec), data);
+    }
+
+    #[test]
+    fn invalid_fields_are_skipped() {
+        let codec = RegistryCodec::default();
+
+        let mut nbt = Compound::new();
+        nbt.insert("Pos", List::Double(vec![0.0, 1.0]));
+        nbt.insert("playerGameType", 7);
+        nbt.insert("Health", 20.0_f32);
+
+        let data = PlayerData::from_nbt(&nbt, &codec);
+
+        assert_eq!(data.position, None);
+        assert_eq!(data.game_mode, None);
+        assert_eq!(data.health, Some(20.0));
+    }
+
+    #[test]
+    fn oversized_stacks_are_clamped() {
+        let codec = RegistryCodec::default();
+
+        let mut item = Compound::new();
+        item.insert("id", "minecraft:stone");
+        item.insert("count", 200);
+        item.insert("Slot", 9_i8);
+
+        let mut nbt = Compound::new();
+        nbt.insert("Inventory", List::Compound(vec![item]));
+
+        let data = PlayerData::from_nbt(&nbt, &codec);
+
+        assert_eq!(
+            data.inventory,
+            vec![(9, ItemStack::new(ItemKind::Stone, 127))]
+        );
+    }
+}
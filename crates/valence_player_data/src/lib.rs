@@ -0,0 +1,442 @@
+#![doc = include_str!("../README.md")]
+
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::{fmt, thread};
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use flume::{Receiver, Sender};
+use tracing::warn;
+use valence_inventory::{HeldItem, Inventory};
+use valence_nbt::Compound;
+use valence_server::client::{ClientMarker, VisibleChunkLayer, VisibleEntityLayers};
+use valence_server::entity::active_status_effects::ActiveStatusEffects;
+use valence_server::entity::living::Health;
+use valence_server::entity::player::{Experience, Food, Saturation};
+use valence_server::entity::{EntityLayerId, HeadYaw, InitEntitiesSet, Look, Position};
+use valence_server::protocol::anyhow;
+use valence_server::registry::RegistryCodec;
+use valence_server::uuid::Uuid;
+use valence_server::{ChunkLayer, Despawned, EntityLayer, GameMode, Ident, UniqueId};
+
+mod data;
+mod store;
+
+pub use data::{PlayerData, DATA_VERSION};
+pub use store::{PlayerDataFolder, PlayerDataStore};
+
+/// Loads the player data of clients when they join and saves it when they
+/// leave, periodically, and when the app exits.
+///
+/// The plugin does nothing until the [`PlayerDataStorage`] resource is
+/// inserted. Loading and saving happens on a separate thread, so the data of a
+/// new client is applied in [`PlayerDataSet`] in [`PostUpdate`] of a later
+/// tick, once it has been loaded. It overrides the values set by systems
+/// initializing new clients in [`Update`]. Clients without saved data keep
+/// those values.
+pub struct PlayerDataPlugin;
+
+/// The [`SystemSet`] in [`PostUpdate`] where player data is requested for newly
+/// joined clients and applied once it has been loaded.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct PlayerDataSet;
+
+impl Plugin for PlayerDataPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(PostUpdate, PlayerDataSet.before(InitEntitiesSet))
+            .add_systems(
+                PostUpdate,
+                (
+                    (request_player_data, apply_player_data)
+                        .chain()
+                        .in_set(PlayerDataSet),
+                    autosave_player_data.after(PlayerDataSet),
+                )
+                    .run_if(resource_exists::<PlayerDataStorage>),
+            )
+            .add_systems(
+                Last,
+                save_player_data.run_if(resource_exists::<PlayerDataStorage>),
+            );
+    }
+}
+
+/// The resource controlling where and when player data is saved.
+///
+/// The store is accessed by a worker thread which handles the loads and saves
+/// of all players in the order they were requested, so saved data is always
+/// visible to later loads.
+#[derive(Resource)]
+pub struct PlayerDataStorage {
+    store: Arc<dyn PlayerDataStore>,
+    /// The number of ticks between autosaves, where the data of all connected
+    /// players is saved. `None` disables autosaving.
+    ///
+    /// Defaults to 6000 (five minutes).
+    pub autosave_interval: Option<NonZeroU32>,
+    /// The number of ticks since the last autosave.
+    ticks_since_autosave: u32,
+    /// Sender for the worker thread.
+    sender: Sender<WorkerMsg>,
+    /// Receiver of loaded player data from the worker thread.
+    receiver: Receiver<(Uuid, anyhow::Result<Option<Compound>>)>,
+}
+
+impl PlayerDataStorage {
+    pub fn new<S: PlayerDataStore>(store: S) -> Self {
+        let store: Arc<dyn PlayerDataStore> = Arc::new(store);
+
+        let (pending_sender, pending_receiver) = flume::unbounded();
+        let (finished_sender, finished_receiver) = flume::unbounded();
+
+        let worker_state = WorkerState {
+            store: store.clone(),
+            sender: finished_sender,
+            receiver: pending_receiver,
+        };
+
+        thread::spawn(move || player_data_worker(worker_state));
+
+        Self {
+            store,
+            autosave_interval: NonZeroU32::new(6000),
+            ticks_since_autosave: 0,
+            sender: pending_sender,
+            receiver: finished_receiver,
+        }
+    }
+
+    /// Returns the underlying store. This can be used to access the data of
+    /// players that are not connected.
+    ///
+    /// Note that loads and saves queued by the plugin may not have completed
+    /// yet. Call [`PlayerDataStorage::flush`] first to wait for them.
+    pub fn store(&self) -> &dyn PlayerDataStore {
+        &*self.store
+    }
+
+    /// Blocks until all loads and saves queued so far have completed.
+    pub fn flush(&self) {
+        let (sender, receiver) = flume::bounded(1);
+
+        if self.sender.send(WorkerMsg::Flush(sender)).is_ok() {
+            let _ = receiver.recv();
+        }
+    }
+
+    fn save(&self, uuid: Uuid, data: Compound) {
+        let _ = self.sender.send(WorkerMsg::Save(uuid, data));
+    }
+}
+
+impl fmt::Debug for PlayerDataStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PlayerDataStorage")
+            .field("autosave_interval", &self.autosave_interval)
+            .field("ticks_since_autosave", &self.ticks_since_autosave)
+            .finish_non_exhaustive()
+    }
+}
+
+enum WorkerMsg {
+    Load(Uuid),
+    Save(Uuid, Compound),
+    /// Sends `()` back once all previous messages have been handled.
+    Flush(Sender<()>),
+}
+
+struct WorkerState {
+    store: Arc<dyn PlayerDataStore>,
+    /// Sender of loaded player data.
+    sender: Sender<(Uuid, anyhow::Result<Option<Compound>>)>,
+    /// Receiver of pending loads and saves.
+    receiver: Receiver<WorkerMsg>,
+}
+
+/// Handles loads and saves one at a time until the [`PlayerDataStorage`] is
+/// dropped.
+fn player_data_worker(state: WorkerState) {
+    while let Ok(msg) = state.receiver.recv() {
+        match msg {
+            WorkerMsg::Load(uuid) => {
+                let _ = state.sender.send((uuid, state.store.load(uuid)));
+            }
+            WorkerMsg::Save(uuid, data) => {
+                if let Err(e) = state.store.save(uuid, &data) {
+                    warn!("failed to save player data of {uuid}: {e:#}");
+                }
+            }
+            WorkerMsg::Flush(sender) => {
+                let _ = sender.send(());
+            }
+        }
+    }
+}
+
+/// Marks clients whose player data is being loaded.
+#[derive(Component, Default, Debug)]
+struct LoadingPlayerData;
+
+/// The player data compound loaded for a client. Saved data is written on top
+/// of it so that keys unknown to Valence survive round trips.
+///
+/// Only clients whose data was loaded successfully have this component, so
+/// data that failed to load is never overwritten.
+#[derive(Component, Default, Debug)]
+struct LoadedPlayerData(Compound);
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct PlayerDataQuery {
+    uuid: &'static UniqueId,
+    layer_id: &'static mut EntityLayerId,
+    visible_chunk_layer: &'static mut VisibleChunkLayer,
+    visible_entity_layers: &'static mut VisibleEntityLayers,
+    position: &'static mut Position,
+    look: &'static mut Look,
+    head_yaw: &'static mut HeadYaw,
+    game_mode: &'static mut GameMode,
+    inventory: &'static mut Inventory,
+    held_item: &'static mut HeldItem,
+    effects: &'static mut ActiveStatusEffects,
+    experience: &'static mut Experience,
+    health: &'static mut Health,
+    food: &'static mut Food,
+    saturation: &'static mut Saturation,
+}
+
+fn request_player_data(
+    clients: Query<(Entity, &UniqueId), Added<ClientMarker>>,
+    storage: Res<PlayerDataStorage>,
+    mut commands: Commands,
+) {
+    for (entity, uuid) in &clients {
+        let _ = storage.sender.send(WorkerMsg::Load(uuid.0));
+        commands.entity(entity).insert(LoadingPlayerData);
+    }
+}
+
+fn apply_player_data(
+    mut clients: Query<(Entity, PlayerDataQuery), With<LoadingPlayerData>>,
+    layers: Query<(Entity, &ChunkLayer), With<EntityLayer>>,
+    storage: Res<PlayerDataStorage>,
+    codec: Res<RegistryCodec>,
+    mut commands: Commands,
+) {
+    // Results for clients that have left in the meantime are discarded.
+    let mut loaded: HashMap<_, _> = storage.receiver.drain().collect();
+
+    if loaded.is_empty() {
+        return;
+    }
+
+    for (entity, mut client) in &mut clients {
+        let uuid = client.uuid.0;
+
+        let Some(res) = loaded.remove(&uuid) else {
+            continue;
+        };
+
+        commands.entity(entity).remove::<LoadingPlayerData>();
+
+        let nbt = match res {
+            Ok(Some(nbt)) => nbt,
+            Ok(None) => Compound::new(),
+            Err(e) => {
+                warn!("failed to load player data of {uuid}: {e:#}");
+                continue;
+            }
+        };
+
+        apply_data(PlayerData::from_nbt(&nbt, &codec), &mut client, &layers);
+
+        commands.entity(entity).insert(LoadedPlayerData(nbt));
+    }
+}
+
+fn apply_data(
+    data: PlayerData,
+    client: &mut PlayerDataQueryItem,
+    layers: &Query<(Entity, &ChunkLayer), With<EntityLayer>>,
+) {
+    if let Some(dimension) = &data.dimension {
+        move_to_dimension(dimension, client, layers);
+    }
+
+    if let Some(pos) = data.position {
+        client.position.0 = pos;
+    }
+
+    if let Some([yaw, pitch]) = data.look {
+        client.look.yaw = yaw;
+        client.look.pitch = pitch;
+        client.head_yaw.0 = yaw;
+    }
+
+    if let Some(game_mode) = data.game_mode {
+        *client.game_mode = game_mode;
+    }
+
+    if let Some(slot) = data.selected_slot {
+        client.held_item.set_hotbar_idx(slot);
+    }
+
+    for (slot, stack) in data.inventory {
+        if slot < client.inventory.slot_count() {
+            client.inventory.set_slot(slot, stack);
+        }
+    }
+
+    for effect in data.active_effects {
+        client.effects.apply(effect);
+    }
+
+    if let Some(experience) = data.experience {
+        *client.experience = experience;
+    }
+
+    if let Some(health) = data.health {
+        client.health.0 = health;
+    }
+
+    if let Some(food) = data.food {
+        client.food.0 = food;
+    }
+
+    if let Some(saturation) = data.saturation {
+        client.saturation.0 = saturation;
+    }
+}
+
+/// Moves the client to the first layer using the given dimension type, unless
+/// the layer it is in already uses it. Layers are not persisted themselves, so
+/// the dimension type is the closest thing to vanilla's saved dimension.
+fn move_to_dimension(
+    dimension: &Ident<String>,
+    client: &mut PlayerDataQueryItem,
+    layers: &Query<(Entity, &ChunkLayer), With<EntityLayer>>,
+) {
+    let current = client.visible_chunk_layer.0;
+
+    if layers
+        .get(current)
+        .is_ok_and(|(_, layer)| layer.dimension_type_name() == *dimension)
+    {
+        return;
+    }
+
+    let Some((layer, _)) = layers
+        .iter()
+        .find(|(_, layer)| layer.dimension_type_name() == *dimension)
+    else {
+        warn!(
+            "no layer with dimension type {dimension} for player {}",
+            client.uuid.0
+        );
+        return;
+    };
+
+    let old_entity_layer = client.layer_id.0;
+
+    client.layer_id.0 = layer;
+    client.visible_chunk_layer.0 = layer;
+    client.visible_entity_layers.0.remove(&old_entity_layer);
+    client.visible_entity_layers.0.insert(layer);
+}
+
+fn capture_player_data(
+    client: &PlayerDataQueryReadOnlyItem,
+    layers: &Query<(Entity, &ChunkLayer), With<EntityLayer>>,
+) -> PlayerData {
+    PlayerData {
+        dimension: layers
+            .get(client.visible_chunk_layer.0)
+            .ok()
+            .map(|(_, layer)| layer.dimension_type_name().into()),
+        position: Some(client.position.0),
+        look: Some([client.look.yaw, client.look.pitch]),
+        game_mode: Some(*client.game_mode),
+        selected_slot: Some(client.held_item.hotbar_idx()),
+        inventory: client
+            .inventory
+            .slots()
+            .enumerate()
+            .filter(|(_, stack)| !stack.is_empty())
+            .map(|(slot, stack)| (slot as u16, stack.clone()))
+            .collect(),
+        active_effects: client
+            .effects
+            .get_current_effects()
+            .into_iter()
+            .cloned()
+            .collect(),
+        experience: Some(*client.experience),
+        health: Some(client.health.0),
+        food: Some(client.food.0),
+        saturation: Some(client.saturation.0),
+    }
+}
+
+fn save_client(
+    storage: &PlayerDataStorage,
+    codec: &RegistryCodec,
+    layers: &Query<(Entity, &ChunkLayer), With<EntityLayer>>,
+    client: &PlayerDataQueryReadOnlyItem,
+    loaded: &LoadedPlayerData,
+) {
+    let mut nbt = loaded.0.clone();
+    capture_player_data(client, layers).write_nbt(&mut nbt, codec);
+
+    storage.save(client.uuid.0, nbt);
+}
+
+fn autosave_player_data(
+    mut storage: ResMut<PlayerDataStorage>,
+    codec: Res<RegistryCodec>,
+    layers: Query<(Entity, &ChunkLayer), With<EntityLayer>>,
+    clients: Query<(PlayerDataQuery, &LoadedPlayerData), Without<Despawned>>,
+) {
+    let Some(interval) = storage.autosave_interval else {
+        return;
+    };
+
+    storage.ticks_since_autosave += 1;
+
+    if storage.ticks_since_autosave < interval.get() {
+        return;
+    }
+
+    storage.ticks_since_autosave = 0;
+
+    for (client, loaded) in clients.iter() {
+        save_client(&storage, &codec, &layers, &client, loaded);
+    }
+}
+
+/// Saves the data of clients that are about to be despawned, or of all clients
+/// if the app is exiting. This runs in [`Last`] because [`Despawned`] entities
+/// are only removed at the very end of the tick.
+///
+/// When the app is exiting, this blocks until all saves have been written.
+fn save_player_data(
+    storage: Res<PlayerDataStorage>,
+    codec: Res<RegistryCodec>,
+    mut exit_events: EventReader<AppExit>,
+    layers: Query<(Entity, &ChunkLayer), With<EntityLayer>>,
+    clients: Query<(PlayerDataQuery, &LoadedPlayerData, Has<Despawned>), With<ClientMarker>>,
+) {
+    let exiting = exit_events.read().count() > 0;
+
+    for (client, loaded, despawned) in clients.iter() {
+        if despawned || exiting {
+            save_client(&storage, &codec, &layers, &client, loaded);
+        }
+    }
+
+    if exiting {
+        storage.flush();
+    }
+}
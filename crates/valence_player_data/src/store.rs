@@ -0,0 +1,93 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::bufread::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use valence_nbt::Compound;
+use valence_server::protocol::anyhow;
+use valence_server::uuid::Uuid;
+
+/// A storage backend for player data.
+///
+/// Implement this trait to store player data somewhere other than the
+/// filesystem, such as a database. The compounds passed to and returned from
+/// the store are in the vanilla `playerdata` format.
+pub trait PlayerDataStore: Send + Sync + 'static {
+    /// Loads the player data of the player with the given UUID. Returns
+    /// `Ok(None)` if no data has been saved for the player.
+    fn load(&self, uuid: Uuid) -> anyhow::Result<Option<Compound>>;
+
+    /// Saves the player data of the player with the given UUID, replacing any
+    /// existing data.
+    fn save(&self, uuid: Uuid, data: &Compound) -> anyhow::Result<()>;
+}
+
+/// A [`PlayerDataStore`] that reads and writes gzip compressed
+/// `<uuid>.dat` files in a directory, like the `playerdata` directory of a
+/// vanilla world.
+#[derive(Clone, Debug)]
+pub struct PlayerDataFolder {
+    /// Path to the directory containing the player data files.
+    dir: PathBuf,
+}
+
+impl PlayerDataFolder {
+    /// Create a new player data folder. The directory is created when player
+    /// data is first saved if it does not exist.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the path to the directory containing the player data files.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, uuid: Uuid, extension: &str) -> PathBuf {
+        self.dir.join(format!("{uuid}.{extension}"))
+    }
+}
+
+impl PlayerDataStore for PlayerDataFolder {
+    fn load(&self, uuid: Uuid) -> anyhow::Result<Option<Compound>> {
+        let file = match File::open(self.path(uuid, "dat")) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut buf = vec![];
+        GzDecoder::new(BufReader::new(file)).read_to_end(&mut buf)?;
+
+        let (data, _) = valence_nbt::from_binary(&mut buf.as_slice())?;
+
+        Ok(Some(data))
+    }
+
+    fn save(&self, uuid: Uuid, data: &Compound) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        // Write to a temporary file first so that existing data is not lost if
+        // writing fails halfway through. The previous file is kept as a
+        // backup, like vanilla does.
+        let tmp_path = self.path(uuid, "dat_tmp");
+        let path = self.path(uuid, "dat");
+
+        let mut encoder = GzEncoder::new(
+            BufWriter::new(File::create(&tmp_path)?),
+            Compression::default(),
+        );
+        valence_nbt::to_binary(data, &mut encoder, "")?;
+        encoder.finish()?.flush()?;
+
+        if path.exists() {
+            fs::rename(&path, self.path(uuid, "dat_old"))?;
+        }
+
+        fs::rename(&tmp_path, &path)?;
+
+        Ok(())
+    }
+}
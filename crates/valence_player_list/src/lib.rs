@@ -19,8 +19,10 @@ use valence_server::{Despawned, GameMode, Server, Text, UniqueId};
 
 pub struct PlayerListPlugin;
 
+/// The [`SystemSet`] in [`PostUpdate`] where player list changes are sent to
+/// clients.
 #[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
-struct PlayerListSet;
+pub struct PlayerListSet;
 
 impl Plugin for PlayerListPlugin {
     fn build(&self, app: &mut App) {
@@ -3,7 +3,8 @@ use std::io::Write;
 use anyhow::Context;
 use uuid::Uuid;
 use valence_generated::block::{BlockEntityKind, BlockKind, BlockState};
-use valence_generated::item::ItemKind;
+use valence_generated::item::{ItemComponentKind, ItemKind};
+use valence_generated::status_effects::StatusEffect;
 use valence_ident::{Ident, IdentError};
 use valence_nbt::Compound;
 
@@ -136,3 +137,33 @@ impl Decode<'_> for ItemKind {
         ItemKind::from_raw(id.try_into().context(errmsg)?).context(errmsg)
     }
 }
+
+impl Encode for ItemComponentKind {
+    fn encode(&self, w: impl Write) -> anyhow::Result<()> {
+        VarInt(i32::from(self.to_raw())).encode(w)
+    }
+}
+
+impl Decode<'_> for ItemComponentKind {
+    fn decode(r: &mut &[u8]) -> anyhow::Result<Self> {
+        let id = VarInt::decode(r)?.0;
+        let errmsg = "invalid item component type ID";
+
+        ItemComponentKind::from_raw(id.try_into().context(errmsg)?).context(errmsg)
+    }
+}
+
+impl Encode for StatusEffect {
+    fn encode(&self, w: impl Write) -> anyhow::Result<()> {
+        VarInt(i32::from(self.to_raw())).encode(w)
+    }
+}
+
+impl Decode<'_> for StatusEffect {
+    fn decode(r: &mut &[u8]) -> anyhow::Result<Self> {
+        let id = VarInt::decode(r)?.0;
+        let errmsg = "invalid status effect ID";
+
+        StatusEffect::from_raw(id.try_into().context(errmsg)?).context(errmsg)
+    }
+}
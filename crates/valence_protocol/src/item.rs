@@ -1,28 +1,38 @@
+use std::collections::{btree_map, BTreeMap};
 use std::io::Write;
 
+use anyhow::{ensure, Context};
 pub use valence_generated::item::ItemKind;
-use valence_nbt::Compound;
 
-use crate::{Decode, Encode};
+use crate::{Decode, Encode, VarInt};
+
+pub mod component;
+
+pub use component::{ItemComponent, ItemComponentKind};
 
 /// A stack of items in an inventory.
 #[derive(Clone, PartialEq, Debug, Default)]
 pub struct ItemStack {
     pub item: ItemKind,
     pub count: i8,
-    pub nbt: Option<Compound>,
+    /// The changes made to the default components of the item.
+    pub components: ItemComponentPatch,
 }
 
 impl ItemStack {
     pub const EMPTY: ItemStack = ItemStack {
         item: ItemKind::Air,
         count: 0,
-        nbt: None,
+        components: ItemComponentPatch::new(),
     };
 
     #[must_use]
-    pub const fn new(item: ItemKind, count: i8, nbt: Option<Compound>) -> Self {
-        Self { item, count, nbt }
+    pub const fn new(item: ItemKind, count: i8) -> Self {
+        Self {
+            item,
+            count,
+            components: ItemComponentPatch::new(),
+        }
     }
 
     #[must_use]
@@ -37,52 +47,220 @@ impl ItemStack {
         self
     }
 
+    /// Sets a component on this item stack, replacing any previous component
+    /// of the same kind.
+    #[must_use]
+    pub fn with_component(mut self, component: ItemComponent) -> Self {
+        self.components.insert(component);
+        self
+    }
+
+    /// Removes a default component of the item from this item stack.
     #[must_use]
-    pub fn with_nbt<C: Into<Option<Compound>>>(mut self, nbt: C) -> Self {
-        self.nbt = nbt.into();
+    pub fn without_component(mut self, kind: ItemComponentKind) -> Self {
+        self.components.remove(kind);
         self
     }
 
+    /// Returns the component of the given kind set on this item stack. Default
+    /// components of the item are not included.
+    pub fn component(&self, kind: ItemComponentKind) -> Option<&ItemComponent> {
+        self.components.get(kind)
+    }
+
     pub const fn is_empty(&self) -> bool {
         matches!(self.item, ItemKind::Air) || self.count <= 0
     }
 }
 
+/// A set of changes to the default data components of an item.
+///
+/// Each component kind is either set to a new value, removed, or left
+/// untouched so that the item's default is used.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ItemComponentPatch {
+    /// `None` values are components removed from the defaults.
+    entries: BTreeMap<ItemComponentKind, Option<ItemComponent>>,
+}
+
+impl ItemComponentPatch {
+    pub const fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the component of the given kind if it was set by this patch.
+    pub fn get(&self, kind: ItemComponentKind) -> Option<&ItemComponent> {
+        self.entries.get(&kind)?.as_ref()
+    }
+
+    /// Returns a mutable reference to the component of the given kind if it
+    /// was set by this patch.
+    pub fn get_mut(&mut self, kind: ItemComponentKind) -> Option<&mut ItemComponent> {
+        self.entries.get_mut(&kind)?.as_mut()
+    }
+
+    /// Sets a component, returning the previously set component of the same
+    /// kind.
+    pub fn insert(&mut self, component: ItemComponent) -> Option<ItemComponent> {
+        self.entries
+            .insert(component.kind(), Some(component))
+            .flatten()
+    }
+
+    /// Removes the component of the given kind from the item, including the
+    /// item's default. Returns the previously set component.
+    pub fn remove(&mut self, kind: ItemComponentKind) -> Option<ItemComponent> {
+        self.entries.insert(kind, None).flatten()
+    }
+
+    /// Forgets any change to the component of the given kind so that the
+    /// item's default is used again. Returns the previously set component.
+    pub fn reset(&mut self, kind: ItemComponentKind) -> Option<ItemComponent> {
+        self.entries.remove(&kind).flatten()
+    }
+
+    /// Returns whether the component of the given kind is removed by this
+    /// patch.
+    pub fn is_removed(&self, kind: ItemComponentKind) -> bool {
+        matches!(self.entries.get(&kind), Some(None))
+    }
+
+    /// Returns `true` if this patch makes no changes.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the components set by this patch.
+    pub fn iter(&self) -> impl Iterator<Item = &ItemComponent> + '_ {
+        self.entries.values().flatten()
+    }
+
+    /// Returns an iterator over the kinds of the components removed by this
+    /// patch.
+    pub fn removed(&self) -> impl Iterator<Item = ItemComponentKind> + '_ {
+        self.entries
+            .iter()
+            .filter(|(_, component)| component.is_none())
+            .map(|(kind, _)| *kind)
+    }
+}
+
+impl Extend<ItemComponent> for ItemComponentPatch {
+    fn extend<T: IntoIterator<Item = ItemComponent>>(&mut self, iter: T) {
+        for component in iter {
+            self.insert(component);
+        }
+    }
+}
+
+impl FromIterator<ItemComponent> for ItemComponentPatch {
+    fn from_iter<T: IntoIterator<Item = ItemComponent>>(iter: T) -> Self {
+        let mut patch = Self::new();
+        patch.extend(iter);
+        patch
+    }
+}
+
+impl<'a> IntoIterator for &'a ItemComponentPatch {
+    type Item = &'a ItemComponent;
+
+    type IntoIter =
+        std::iter::Flatten<btree_map::Values<'a, ItemComponentKind, Option<ItemComponent>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.values().flatten()
+    }
+}
+
+impl Encode for ItemComponentPatch {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        let added = self.iter().count();
+        let removed = self.entries.len() - added;
+
+        VarInt(added as i32).encode(&mut w)?;
+        VarInt(removed as i32).encode(&mut w)?;
+
+        for component in self.iter() {
+            component.encode(&mut w)?;
+        }
+
+        for kind in self.removed() {
+            kind.encode(&mut w)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Decode<'_> for ItemComponentPatch {
+    fn decode(r: &mut &[u8]) -> anyhow::Result<Self> {
+        let added = VarInt::decode(r)?.0;
+        let removed = VarInt::decode(r)?.0;
+
+        ensure!(
+            added >= 0 && removed >= 0,
+            "invalid component patch lengths of {added} and {removed}"
+        );
+
+        let mut entries = BTreeMap::new();
+
+        for _ in 0..added {
+            let kind = ItemComponentKind::decode(r)?;
+            let component = ItemComponent::decode_data(kind, r)
+                .with_context(|| format!("decoding item component {}", kind.to_str()))?;
+
+            ensure!(
+                entries.insert(kind, Some(component)).is_none(),
+                "duplicate item component {}",
+                kind.to_str()
+            );
+        }
+
+        for _ in 0..removed {
+            let kind = ItemComponentKind::decode(r)?;
+
+            ensure!(
+                entries.insert(kind, None).is_none(),
+                "duplicate item component {}",
+                kind.to_str()
+            );
+        }
+
+        Ok(Self { entries })
+    }
+}
+
 impl Encode for ItemStack {
     fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
         if self.is_empty() {
-            false.encode(w)
+            VarInt(0).encode(w)
         } else {
-            true.encode(&mut w)?;
+            VarInt(i32::from(self.count)).encode(&mut w)?;
             self.item.encode(&mut w)?;
-            self.count.encode(&mut w)?;
-            match &self.nbt {
-                Some(n) => n.encode(w),
-                None => 0_u8.encode(w),
-            }
+            self.components.encode(w)
         }
     }
 }
 
 impl Decode<'_> for ItemStack {
     fn decode(r: &mut &[u8]) -> anyhow::Result<Self> {
-        let present = bool::decode(r)?;
-        if !present {
+        let count = VarInt::decode(r)?.0;
+        if count <= 0 {
             return Ok(ItemStack::EMPTY);
         };
 
+        let count = i8::try_from(count).context("item count is out of range")?;
         let item = ItemKind::decode(r)?;
-        let count = i8::decode(r)?;
+        let components = ItemComponentPatch::decode(r)?;
 
-        let nbt = if let [0, rest @ ..] = *r {
-            *r = rest;
-            None
-        } else {
-            Some(Compound::decode(r)?)
+        let stack = ItemStack {
+            item,
+            count,
+            components,
         };
 
-        let stack = ItemStack { item, count, nbt };
-
         // Normalize empty item stacks.
         if stack.is_empty() {
             Ok(ItemStack::EMPTY)
@@ -94,20 +272,48 @@ impl Decode<'_> for ItemStack {
 
 #[cfg(test)]
 mod tests {
+    use super::component::Rarity;
     use super::*;
+    use crate::text::IntoText;
 
     #[test]
     fn empty_item_stack_is_empty() {
-        let air_stack = ItemStack::new(ItemKind::Air, 10, None);
-        let less_then_one_stack = ItemStack::new(ItemKind::Stone, 0, None);
+        let air_stack = ItemStack::new(ItemKind::Air, 10);
+        let less_then_one_stack = ItemStack::new(ItemKind::Stone, 0);
 
         assert!(air_stack.is_empty());
         assert!(less_then_one_stack.is_empty());
 
         assert!(ItemStack::EMPTY.is_empty());
 
-        let not_empty_stack = ItemStack::new(ItemKind::Stone, 10, None);
+        let not_empty_stack = ItemStack::new(ItemKind::Stone, 10);
 
         assert!(!not_empty_stack.is_empty());
     }
+
+    #[test]
+    fn item_stack_components_roundtrip() {
+        let stack = ItemStack::new(ItemKind::DiamondSword, 1)
+            .with_component(ItemComponent::Damage(12))
+            .with_component(ItemComponent::CustomName("Excalibur".into_text()))
+            .with_component(ItemComponent::Lore(vec!["Pulled from a stone".into_text()]))
+            .with_component(ItemComponent::Rarity(Rarity::Epic))
+            .without_component(ItemComponentKind::AttributeModifiers);
+
+        let mut buf = vec![];
+        stack.encode(&mut buf).unwrap();
+
+        let mut r = buf.as_slice();
+        let decoded = ItemStack::decode(&mut r).unwrap();
+
+        assert!(r.is_empty());
+        assert_eq!(decoded, stack);
+        assert_eq!(
+            decoded.component(ItemComponentKind::Damage),
+            Some(&ItemComponent::Damage(12))
+        );
+        assert!(decoded
+            .components
+            .is_removed(ItemComponentKind::AttributeModifiers));
+    }
 }
@@ -0,0 +1,853 @@
+//! Typed data components attached to [`ItemStack`]s.
+//!
+//! Every component type in the game has a variant in [`ItemComponent`]. The
+//! numeric IDs of the component types are generated from the extractor data
+//! and exposed through [`ItemComponentKind`].
+
+use std::io::Write;
+
+use anyhow::{bail, ensure, Context};
+use uuid::Uuid;
+use valence_generated::item::AttributeModifierSlot;
+pub use valence_generated::item::ItemComponentKind;
+use valence_generated::status_effects::StatusEffect;
+use valence_ident::Ident;
+use valence_nbt::Compound;
+use valence_text::Text;
+
+use super::{ItemKind, ItemStack};
+use crate::profile::Property;
+use crate::{BlockPos, Decode, Encode, VarInt};
+
+/// A single data component of an item stack.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ItemComponent {
+    /// Arbitrary NBT data that is not used by the game.
+    CustomData(Compound),
+    MaxStackSize(i32),
+    MaxDamage(i32),
+    /// The amount of durability the item has lost.
+    Damage(i32),
+    Unbreakable {
+        show_in_tooltip: bool,
+    },
+    /// The name of the item, as set by an anvil. Shown in italics.
+    CustomName(Text),
+    /// The default name of the item. Not shown in italics.
+    ItemName(Text),
+    Lore(Vec<Text>),
+    Rarity(Rarity),
+    Enchantments(Enchantments),
+    CanPlaceOn(AdventurePredicate),
+    CanBreak(AdventurePredicate),
+    AttributeModifiers(AttributeModifiers),
+    CustomModelData(i32),
+    HideAdditionalTooltip,
+    HideTooltip,
+    RepairCost(i32),
+    CreativeSlotLock,
+    EnchantmentGlintOverride(bool),
+    IntangibleProjectile(Compound),
+    Food(Food),
+    FireResistant,
+    Tool(Tool),
+    StoredEnchantments(Enchantments),
+    DyedColor {
+        rgb: i32,
+        show_in_tooltip: bool,
+    },
+    MapColor(i32),
+    MapId(i32),
+    MapDecorations(Compound),
+    MapPostProcessing(MapPostProcessing),
+    ChargedProjectiles(Vec<ItemStack>),
+    BundleContents(Vec<ItemStack>),
+    PotionContents(PotionContents),
+    SuspiciousStewEffects(Vec<SuspiciousStewEffect>),
+    WritableBookContent(Vec<WritableBookPage>),
+    WrittenBookContent(WrittenBookContent),
+    Trim(ArmorTrim),
+    DebugStickState(Compound),
+    EntityData(Compound),
+    BucketEntityData(Compound),
+    BlockEntityData(Compound),
+    Instrument(IdOr<Instrument>),
+    OminousBottleAmplifier(i32),
+    JukeboxPlayable(JukeboxPlayable),
+    Recipes(Compound),
+    LodestoneTracker(LodestoneTracker),
+    FireworkExplosion(FireworkExplosion),
+    Fireworks(Fireworks),
+    Profile(ResolvableProfile),
+    NoteBlockSound(Ident<String>),
+    BannerPatterns(Vec<BannerLayer>),
+    BaseColor(DyeColor),
+    PotDecorations(Vec<ItemKind>),
+    Container(Vec<ItemStack>),
+    BlockState(Vec<BlockStateProperty>),
+    Bees(Vec<BeeData>),
+    Lock(String),
+    ContainerLoot(Compound),
+}
+
+impl ItemComponent {
+    /// Returns the type of this component.
+    pub const fn kind(&self) -> ItemComponentKind {
+        match self {
+            Self::CustomData(_) => ItemComponentKind::CustomData,
+            Self::MaxStackSize(_) => ItemComponentKind::MaxStackSize,
+            Self::MaxDamage(_) => ItemComponentKind::MaxDamage,
+            Self::Damage(_) => ItemComponentKind::Damage,
+            Self::Unbreakable { .. } => ItemComponentKind::Unbreakable,
+            Self::CustomName(_) => ItemComponentKind::CustomName,
+            Self::ItemName(_) => ItemComponentKind::ItemName,
+            Self::Lore(_) => ItemComponentKind::Lore,
+            Self::Rarity(_) => ItemComponentKind::Rarity,
+            Self::Enchantments(_) => ItemComponentKind::Enchantments,
+            Self::CanPlaceOn(_) => ItemComponentKind::CanPlaceOn,
+            Self::CanBreak(_) => ItemComponentKind::CanBreak,
+            Self::AttributeModifiers(_) => ItemComponentKind::AttributeModifiers,
+            Self::CustomModelData(_) => ItemComponentKind::CustomModelData,
+            Self::HideAdditionalTooltip => ItemComponentKind::HideAdditionalTooltip,
+            Self::HideTooltip => ItemComponentKind::HideTooltip,
+            Self::RepairCost(_) => ItemComponentKind::RepairCost,
+            Self::CreativeSlotLock => ItemComponentKind::CreativeSlotLock,
+            Self::EnchantmentGlintOverride(_) => ItemComponentKind::EnchantmentGlintOverride,
+            Self::IntangibleProjectile(_) => ItemComponentKind::IntangibleProjectile,
+            Self::Food(_) => ItemComponentKind::Food,
+            Self::FireResistant => ItemComponentKind::FireResistant,
+            Self::Tool(_) => ItemComponentKind::Tool,
+            Self::StoredEnchantments(_) => ItemComponentKind::StoredEnchantments,
+            Self::DyedColor { .. } => ItemComponentKind::DyedColor,
+            Self::MapColor(_) => ItemComponentKind::MapColor,
+            Self::MapId(_) => ItemComponentKind::MapId,
+            Self::MapDecorations(_) => ItemComponentKind::MapDecorations,
+            Self::MapPostProcessing(_) => ItemComponentKind::MapPostProcessing,
+            Self::ChargedProjectiles(_) => ItemComponentKind::ChargedProjectiles,
+            Self::BundleContents(_) => ItemComponentKind::BundleContents,
+            Self::PotionContents(_) => ItemComponentKind::PotionContents,
+            Self::SuspiciousStewEffects(_) => ItemComponentKind::SuspiciousStewEffects,
+            Self::WritableBookContent(_) => ItemComponentKind::WritableBookContent,
+            Self::WrittenBookContent(_) => ItemComponentKind::WrittenBookContent,
+            Self::Trim(_) => ItemComponentKind::Trim,
+            Self::DebugStickState(_) => ItemComponentKind::DebugStickState,
+            Self::EntityData(_) => ItemComponentKind::EntityData,
+            Self::BucketEntityData(_) => ItemComponentKind::BucketEntityData,
+            Self::BlockEntityData(_) => ItemComponentKind::BlockEntityData,
+            Self::Instrument(_) => ItemComponentKind::Instrument,
+            Self::OminousBottleAmplifier(_) => ItemComponentKind::OminousBottleAmplifier,
+            Self::JukeboxPlayable(_) => ItemComponentKind::JukeboxPlayable,
+            Self::Recipes(_) => ItemComponentKind::Recipes,
+            Self::LodestoneTracker(_) => ItemComponentKind::LodestoneTracker,
+            Self::FireworkExplosion(_) => ItemComponentKind::FireworkExplosion,
+            Self::Fireworks(_) => ItemComponentKind::Fireworks,
+            Self::Profile(_) => ItemComponentKind::Profile,
+            Self::NoteBlockSound(_) => ItemComponentKind::NoteBlockSound,
+            Self::BannerPatterns(_) => ItemComponentKind::BannerPatterns,
+            Self::BaseColor(_) => ItemComponentKind::BaseColor,
+            Self::PotDecorations(_) => ItemComponentKind::PotDecorations,
+            Self::Container(_) => ItemComponentKind::Container,
+            Self::BlockState(_) => ItemComponentKind::BlockState,
+            Self::Bees(_) => ItemComponentKind::Bees,
+            Self::Lock(_) => ItemComponentKind::Lock,
+            Self::ContainerLoot(_) => ItemComponentKind::ContainerLoot,
+        }
+    }
+
+    /// Decodes the data of a component whose type has already been read.
+    pub(super) fn decode_data(kind: ItemComponentKind, r: &mut &[u8]) -> anyhow::Result<Self> {
+        type K = ItemComponentKind;
+
+        Ok(match kind {
+            K::CustomData => Self::CustomData(Decode::decode(r)?),
+            K::MaxStackSize => Self::MaxStackSize(VarInt::decode(r)?.0),
+            K::MaxDamage => Self::MaxDamage(VarInt::decode(r)?.0),
+            K::Damage => Self::Damage(VarInt::decode(r)?.0),
+            K::Unbreakable => Self::Unbreakable {
+                show_in_tooltip: bool::decode(r)?,
+            },
+            K::CustomName => Self::CustomName(Decode::decode(r)?),
+            K::ItemName => Self::ItemName(Decode::decode(r)?),
+            K::Lore => Self::Lore(Decode::decode(r)?),
+            K::Rarity => Self::Rarity(Decode::decode(r)?),
+            K::Enchantments => Self::Enchantments(Decode::decode(r)?),
+            K::CanPlaceOn => Self::CanPlaceOn(Decode::decode(r)?),
+            K::CanBreak => Self::CanBreak(Decode::decode(r)?),
+            K::AttributeModifiers => Self::AttributeModifiers(Decode::decode(r)?),
+            K::CustomModelData => Self::CustomModelData(VarInt::decode(r)?.0),
+            K::HideAdditionalTooltip => Self::HideAdditionalTooltip,
+            K::HideTooltip => Self::HideTooltip,
+            K::RepairCost => Self::RepairCost(VarInt::decode(r)?.0),
+            K::CreativeSlotLock => Self::CreativeSlotLock,
+            K::EnchantmentGlintOverride => Self::EnchantmentGlintOverride(bool::decode(r)?),
+            K::IntangibleProjectile => Self::IntangibleProjectile(Decode::decode(r)?),
+            K::Food => Self::Food(Decode::decode(r)?),
+            K::FireResistant => Self::FireResistant,
+            K::Tool => Self::Tool(Decode::decode(r)?),
+            K::StoredEnchantments => Self::StoredEnchantments(Decode::decode(r)?),
+            K::DyedColor => Self::DyedColor {
+                rgb: i32::decode(r)?,
+                show_in_tooltip: bool::decode(r)?,
+            },
+            K::MapColor => Self::MapColor(i32::decode(r)?),
+            K::MapId => Self::MapId(VarInt::decode(r)?.0),
+            K::MapDecorations => Self::MapDecorations(Decode::decode(r)?),
+            K::MapPostProcessing => Self::MapPostProcessing(Decode::decode(r)?),
+            K::ChargedProjectiles => Self::ChargedProjectiles(Decode::decode(r)?),
+            K::BundleContents => Self::BundleContents(Decode::decode(r)?),
+            K::PotionContents => Self::PotionContents(Decode::decode(r)?),
+            K::SuspiciousStewEffects => Self::SuspiciousStewEffects(Decode::decode(r)?),
+            K::WritableBookContent => Self::WritableBookContent(Decode::decode(r)?),
+            K::WrittenBookContent => Self::WrittenBookContent(Decode::decode(r)?),
+            K::Trim => Self::Trim(Decode::decode(r)?),
+            K::DebugStickState => Self::DebugStickState(Decode::decode(r)?),
+            K::EntityData => Self::EntityData(Decode::decode(r)?),
+            K::BucketEntityData => Self::BucketEntityData(Decode::decode(r)?),
+            K::BlockEntityData => Self::BlockEntityData(Decode::decode(r)?),
+            K::Instrument => Self::Instrument(Decode::decode(r)?),
+            K::OminousBottleAmplifier => Self::OminousBottleAmplifier(VarInt::decode(r)?.0),
+            K::JukeboxPlayable => Self::JukeboxPlayable(Decode::decode(r)?),
+            K::Recipes => Self::Recipes(Decode::decode(r)?),
+            K::LodestoneTracker => Self::LodestoneTracker(Decode::decode(r)?),
+            K::FireworkExplosion => Self::FireworkExplosion(Decode::decode(r)?),
+            K::Fireworks => Self::Fireworks(Decode::decode(r)?),
+            K::Profile => Self::Profile(Decode::decode(r)?),
+            K::NoteBlockSound => Self::NoteBlockSound(Decode::decode(r)?),
+            K::BannerPatterns => Self::BannerPatterns(Decode::decode(r)?),
+            K::BaseColor => Self::BaseColor(Decode::decode(r)?),
+            K::PotDecorations => Self::PotDecorations(Decode::decode(r)?),
+            K::Container => Self::Container(Decode::decode(r)?),
+            K::BlockState => Self::BlockState(Decode::decode(r)?),
+            K::Bees => Self::Bees(Decode::decode(r)?),
+            K::Lock => Self::Lock(decode_nbt_string(r)?),
+            K::ContainerLoot => Self::ContainerLoot(Decode::decode(r)?),
+        })
+    }
+}
+
+impl Encode for ItemComponent {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        self.kind().encode(&mut w)?;
+
+        match self {
+            Self::CustomData(nbt)
+            | Self::IntangibleProjectile(nbt)
+            | Self::MapDecorations(nbt)
+            | Self::DebugStickState(nbt)
+            | Self::EntityData(nbt)
+            | Self::BucketEntityData(nbt)
+            | Self::BlockEntityData(nbt)
+            | Self::Recipes(nbt)
+            | Self::ContainerLoot(nbt) => nbt.encode(w),
+            Self::MaxStackSize(n)
+            | Self::MaxDamage(n)
+            | Self::Damage(n)
+            | Self::CustomModelData(n)
+            | Self::RepairCost(n)
+            | Self::MapId(n)
+            | Self::OminousBottleAmplifier(n) => VarInt(*n).encode(w),
+            Self::Unbreakable { show_in_tooltip } => show_in_tooltip.encode(w),
+            Self::CustomName(text) | Self::ItemName(text) => text.encode(w),
+            Self::Lore(lines) => lines.encode(w),
+            Self::Rarity(rarity) => rarity.encode(w),
+            Self::Enchantments(enchantments) | Self::StoredEnchantments(enchantments) => {
+                enchantments.encode(w)
+            }
+            Self::CanPlaceOn(predicate) | Self::CanBreak(predicate) => predicate.encode(w),
+            Self::AttributeModifiers(modifiers) => modifiers.encode(w),
+            Self::HideAdditionalTooltip
+            | Self::HideTooltip
+            | Self::CreativeSlotLock
+            | Self::FireResistant => Ok(()),
+            Self::EnchantmentGlintOverride(glint) => glint.encode(w),
+            Self::Food(food) => food.encode(w),
+            Self::Tool(tool) => tool.encode(w),
+            Self::DyedColor {
+                rgb,
+                show_in_tooltip,
+            } => {
+                rgb.encode(&mut w)?;
+                show_in_tooltip.encode(w)
+            }
+            Self::MapColor(rgb) => rgb.encode(w),
+            Self::MapPostProcessing(processing) => processing.encode(w),
+            Self::ChargedProjectiles(stacks)
+            | Self::BundleContents(stacks)
+            | Self::Container(stacks) => stacks.encode(w),
+            Self::PotionContents(contents) => contents.encode(w),
+            Self::SuspiciousStewEffects(effects) => effects.encode(w),
+            Self::WritableBookContent(pages) => pages.encode(w),
+            Self::WrittenBookContent(content) => content.encode(w),
+            Self::Trim(trim) => trim.encode(w),
+            Self::Instrument(instrument) => instrument.encode(w),
+            Self::JukeboxPlayable(playable) => playable.encode(w),
+            Self::LodestoneTracker(tracker) => tracker.encode(w),
+            Self::FireworkExplosion(explosion) => explosion.encode(w),
+            Self::Fireworks(fireworks) => fireworks.encode(w),
+            Self::Profile(profile) => profile.encode(w),
+            Self::NoteBlockSound(sound) => sound.encode(w),
+            Self::BannerPatterns(layers) => layers.encode(w),
+            Self::BaseColor(color) => color.encode(w),
+            Self::PotDecorations(decorations) => decorations.encode(w),
+            Self::BlockState(properties) => properties.encode(w),
+            Self::Bees(bees) => bees.encode(w),
+            Self::Lock(key) => encode_nbt_string(key, w),
+        }
+    }
+}
+
+impl Decode<'_> for ItemComponent {
+    fn decode(r: &mut &[u8]) -> anyhow::Result<Self> {
+        let kind = ItemComponentKind::decode(r)?;
+        Self::decode_data(kind, r)
+    }
+}
+
+const NBT_STRING_TAG: u8 = 8;
+
+/// Components without a dedicated network format are sent as unnamed NBT. The
+/// lock component is a single string tag.
+fn encode_nbt_string(s: &str, mut w: impl Write) -> anyhow::Result<()> {
+    let len = u16::try_from(s.len()).context("NBT string is too long")?;
+
+    NBT_STRING_TAG.encode(&mut w)?;
+    len.encode(&mut w)?;
+    Ok(w.write_all(s.as_bytes())?)
+}
+
+fn decode_nbt_string(r: &mut &[u8]) -> anyhow::Result<String> {
+    let tag = u8::decode(r)?;
+    ensure!(tag == NBT_STRING_TAG, "expected NBT string tag, got {tag}");
+
+    let len = u16::decode(r)? as usize;
+    ensure!(
+        r.len() >= len,
+        "not enough data remaining to decode NBT string"
+    );
+
+    let (bytes, rest) = r.split_at(len);
+    *r = rest;
+
+    Ok(std::str::from_utf8(bytes)?.to_owned())
+}
+
+/// Either a reference to an entry in a registry, or an inline definition of
+/// the entry.
+#[derive(Clone, PartialEq, Debug)]
+pub enum IdOr<T> {
+    /// The registry ID of the entry.
+    Id(i32),
+    Inline(T),
+}
+
+impl<T: Encode> Encode for IdOr<T> {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        match self {
+            IdOr::Id(id) => VarInt(id + 1).encode(w),
+            IdOr::Inline(value) => {
+                VarInt(0).encode(&mut w)?;
+                value.encode(w)
+            }
+        }
+    }
+}
+
+impl<'a, T: Decode<'a>> Decode<'a> for IdOr<T> {
+    fn decode(r: &mut &'a [u8]) -> anyhow::Result<Self> {
+        match VarInt::decode(r)?.0 {
+            0 => Ok(IdOr::Inline(T::decode(r)?)),
+            id if id > 0 => Ok(IdOr::Id(id - 1)),
+            id => bail!("invalid registry ID of {id}"),
+        }
+    }
+}
+
+/// A set of registry entries, given either by a tag or by a list of IDs.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum IdSet {
+    Tag(Ident<String>),
+    Ids(Vec<VarInt>),
+}
+
+impl Encode for IdSet {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        match self {
+            IdSet::Tag(tag) => {
+                VarInt(0).encode(&mut w)?;
+                tag.encode(w)
+            }
+            IdSet::Ids(ids) => {
+                let len = i32::try_from(ids.len()).context("too many IDs in ID set")?;
+                VarInt(len + 1).encode(&mut w)?;
+
+                for id in ids {
+                    id.encode(&mut w)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Decode<'_> for IdSet {
+    fn decode(r: &mut &[u8]) -> anyhow::Result<Self> {
+        match VarInt::decode(r)?.0 {
+            0 => Ok(IdSet::Tag(Decode::decode(r)?)),
+            len if len > 0 => (1..len)
+                .map(|_| VarInt::decode(r))
+                .collect::<anyhow::Result<_>>()
+                .map(IdSet::Ids),
+            len => bail!("invalid ID set length of {len}"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Encode, Decode)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub enum DyeColor {
+    White,
+    Orange,
+    Magenta,
+    LightBlue,
+    Yellow,
+    Lime,
+    Pink,
+    Gray,
+    LightGray,
+    Cyan,
+    Purple,
+    Blue,
+    Brown,
+    Green,
+    Red,
+    Black,
+}
+
+impl DyeColor {
+    /// Returns the name of the color, such as `light_blue`.
+    pub const fn to_str(self) -> &'static str {
+        match self {
+            Self::White => "white",
+            Self::Orange => "orange",
+            Self::Magenta => "magenta",
+            Self::LightBlue => "light_blue",
+            Self::Yellow => "yellow",
+            Self::Lime => "lime",
+            Self::Pink => "pink",
+            Self::Gray => "gray",
+            Self::LightGray => "light_gray",
+            Self::Cyan => "cyan",
+            Self::Purple => "purple",
+            Self::Blue => "blue",
+            Self::Brown => "brown",
+            Self::Green => "green",
+            Self::Red => "red",
+            Self::Black => "black",
+        }
+    }
+
+    /// Returns the color with the given name, such as `light_blue`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(name: &str) -> Option<Self> {
+        Some(match name {
+            "white" => Self::White,
+            "orange" => Self::Orange,
+            "magenta" => Self::Magenta,
+            "light_blue" => Self::LightBlue,
+            "yellow" => Self::Yellow,
+            "lime" => Self::Lime,
+            "pink" => Self::Pink,
+            "gray" => Self::Gray,
+            "light_gray" => Self::LightGray,
+            "cyan" => Self::Cyan,
+            "purple" => Self::Purple,
+            "blue" => Self::Blue,
+            "brown" => Self::Brown,
+            "green" => Self::Green,
+            "red" => Self::Red,
+            "black" => Self::Black,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct Enchantments {
+    pub enchantments: Vec<EnchantmentLevel>,
+    pub show_in_tooltip: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct EnchantmentLevel {
+    /// The ID of the enchantment in the enchantment registry.
+    pub id: VarInt,
+    pub level: VarInt,
+}
+
+/// The blocks an item can be placed on or break in adventure mode.
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct AdventurePredicate {
+    pub predicates: Vec<BlockPredicate>,
+    pub show_in_tooltip: bool,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct BlockPredicate {
+    pub blocks: Option<IdSet>,
+    pub properties: Option<Vec<PropertyMatcher>>,
+    pub nbt: Option<Compound>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct PropertyMatcher {
+    pub name: String,
+    pub value: PropertyValueMatcher,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PropertyValueMatcher {
+    Exact(String),
+    Range { min: String, max: String },
+}
+
+impl Encode for PropertyValueMatcher {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        match self {
+            PropertyValueMatcher::Exact(value) => {
+                true.encode(&mut w)?;
+                value.encode(w)
+            }
+            PropertyValueMatcher::Range { min, max } => {
+                false.encode(&mut w)?;
+                min.encode(&mut w)?;
+                max.encode(w)
+            }
+        }
+    }
+}
+
+impl Decode<'_> for PropertyValueMatcher {
+    fn decode(r: &mut &[u8]) -> anyhow::Result<Self> {
+        if bool::decode(r)? {
+            Ok(PropertyValueMatcher::Exact(Decode::decode(r)?))
+        } else {
+            Ok(PropertyValueMatcher::Range {
+                min: Decode::decode(r)?,
+                max: Decode::decode(r)?,
+            })
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct AttributeModifiers {
+    pub modifiers: Vec<AttributeModifier>,
+    pub show_in_tooltip: bool,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct AttributeModifier {
+    /// The ID of the attribute in the attribute registry.
+    pub attribute: VarInt,
+    pub id: Ident<String>,
+    pub amount: f64,
+    pub operation: AttributeOperation,
+    pub slot: EquipmentSlotGroup,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub enum AttributeOperation {
+    AddValue,
+    AddMultipliedBase,
+    AddMultipliedTotal,
+}
+
+/// The equipment slots an attribute modifier is active in.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub enum EquipmentSlotGroup {
+    Any,
+    MainHand,
+    OffHand,
+    Hand,
+    Feet,
+    Legs,
+    Chest,
+    Head,
+    Armor,
+    Body,
+}
+
+impl From<AttributeModifierSlot> for EquipmentSlotGroup {
+    fn from(slot: AttributeModifierSlot) -> Self {
+        match slot {
+            AttributeModifierSlot::Any => Self::Any,
+            AttributeModifierSlot::MainHand => Self::MainHand,
+            AttributeModifierSlot::OffHand => Self::OffHand,
+            AttributeModifierSlot::Hand => Self::Hand,
+            AttributeModifierSlot::Feet => Self::Feet,
+            AttributeModifierSlot::Legs => Self::Legs,
+            AttributeModifierSlot::Chest => Self::Chest,
+            AttributeModifierSlot::Head => Self::Head,
+            AttributeModifierSlot::Armor => Self::Armor,
+            AttributeModifierSlot::Body => Self::Body,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct Food {
+    pub nutrition: VarInt,
+    pub saturation: f32,
+    pub can_always_eat: bool,
+    pub eat_seconds: f32,
+    pub using_converts_to: Option<ItemStack>,
+    pub effects: Vec<FoodEffect>,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct FoodEffect {
+    pub effect: PotionEffect,
+    pub probability: f32,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct PotionEffect {
+    pub id: StatusEffect,
+    pub details: PotionEffectDetails,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct PotionEffectDetails {
+    pub amplifier: VarInt,
+    pub duration: VarInt,
+    pub ambient: bool,
+    pub show_particles: bool,
+    pub show_icon: bool,
+    pub hidden_effect: Option<Box<PotionEffectDetails>>,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct Tool {
+    pub rules: Vec<ToolRule>,
+    pub default_mining_speed: f32,
+    pub damage_per_block: VarInt,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct ToolRule {
+    pub blocks: IdSet,
+    pub speed: Option<f32>,
+    pub correct_for_drops: Option<bool>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub enum MapPostProcessing {
+    Lock,
+    Scale,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct PotionContents {
+    /// The ID of the potion in the potion registry.
+    pub potion: Option<VarInt>,
+    pub custom_color: Option<i32>,
+    pub custom_effects: Vec<PotionEffect>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct SuspiciousStewEffect {
+    pub id: StatusEffect,
+    pub duration: VarInt,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct WritableBookPage {
+    pub raw: String,
+    pub filtered: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct WrittenBookContent {
+    pub raw_title: String,
+    pub filtered_title: Option<String>,
+    pub author: String,
+    pub generation: VarInt,
+    pub pages: Vec<WrittenBookPage>,
+    pub resolved: bool,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct WrittenBookPage {
+    pub raw: Text,
+    pub filtered: Option<Text>,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct ArmorTrim {
+    pub material: IdOr<TrimMaterial>,
+    pub pattern: IdOr<TrimPattern>,
+    pub show_in_tooltip: bool,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct TrimMaterial {
+    pub asset_name: String,
+    pub ingredient: ItemKind,
+    pub item_model_index: f32,
+    pub override_armor_materials: Vec<ArmorMaterialOverride>,
+    pub description: Text,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct ArmorMaterialOverride {
+    /// The ID of the armor material in the armor material registry.
+    pub material: VarInt,
+    pub asset_name: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct TrimPattern {
+    pub asset_id: Ident<String>,
+    pub template_item: ItemKind,
+    pub description: Text,
+    pub decal: bool,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct SoundEvent {
+    pub id: Ident<String>,
+    pub fixed_range: Option<f32>,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct Instrument {
+    pub sound: IdOr<SoundEvent>,
+    pub use_duration: VarInt,
+    pub range: f32,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct JukeboxPlayable {
+    pub song: JukeboxSongRef,
+    pub show_in_tooltip: bool,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum JukeboxSongRef {
+    Song(IdOr<JukeboxSong>),
+    /// The name of a song in the jukebox song registry, which may not be
+    /// known to the client.
+    Name(Ident<String>),
+}
+
+impl Encode for JukeboxSongRef {
+    fn encode(&self, mut w: impl Write) -> anyhow::Result<()> {
+        match self {
+            JukeboxSongRef::Song(song) => {
+                true.encode(&mut w)?;
+                song.encode(w)
+            }
+            JukeboxSongRef::Name(name) => {
+                false.encode(&mut w)?;
+                name.encode(w)
+            }
+        }
+    }
+}
+
+impl Decode<'_> for JukeboxSongRef {
+    fn decode(r: &mut &[u8]) -> anyhow::Result<Self> {
+        if bool::decode(r)? {
+            Ok(JukeboxSongRef::Song(Decode::decode(r)?))
+        } else {
+            Ok(JukeboxSongRef::Name(Decode::decode(r)?))
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct JukeboxSong {
+    pub sound: IdOr<SoundEvent>,
+    pub description: Text,
+    pub length_in_seconds: f32,
+    pub comparator_output: VarInt,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct LodestoneTracker {
+    pub target: Option<LodestoneTarget>,
+    pub tracked: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct LodestoneTarget {
+    pub dimension: Ident<String>,
+    pub position: BlockPos,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct FireworkExplosion {
+    pub shape: FireworkShape,
+    pub colors: Vec<i32>,
+    pub fade_colors: Vec<i32>,
+    pub has_trail: bool,
+    pub has_twinkle: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub enum FireworkShape {
+    SmallBall,
+    LargeBall,
+    Star,
+    Creeper,
+    Burst,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct Fireworks {
+    pub flight_duration: VarInt,
+    pub explosions: Vec<FireworkExplosion>,
+}
+
+/// A player profile, such as the owner of a player head. Missing parts of the
+/// profile are resolved by the client.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Encode, Decode)]
+pub struct ResolvableProfile {
+    pub name: Option<String>,
+    pub uuid: Option<Uuid>,
+    pub properties: Vec<Property>,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct BannerLayer {
+    pub pattern: IdOr<BannerPattern>,
+    pub color: DyeColor,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct BannerPattern {
+    pub asset_id: Ident<String>,
+    pub translation_key: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct BlockStateProperty {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct BeeData {
+    pub entity_data: Compound,
+    pub ticks_in_hive: VarInt,
+    pub min_ticks_in_hive: VarInt,
+}
@@ -39,6 +39,7 @@ mod velocity;
 
 use std::io::Write;
 
+pub use anyhow;
 use anyhow::Context;
 pub use array::FixedArray;
 pub use biome_pos::BiomePos;
@@ -47,6 +48,7 @@ pub use block::{BlockKind, BlockState};
 pub use block_pos::BlockPos;
 pub use bounded::Bounded;
 pub use byte_angle::ByteAngle;
+pub use bytes;
 pub use chunk_pos::ChunkPos;
 pub use chunk_section_pos::ChunkSectionPos;
 pub use decode::PacketDecoder;
@@ -58,22 +60,23 @@ pub use game_mode::GameMode;
 pub use global_pos::GlobalPos;
 pub use hand::Hand;
 pub use ident::ident;
-pub use item::{ItemKind, ItemStack};
+pub use item::{ItemComponent, ItemKind, ItemStack};
 pub use packets::play::particle_s2c::Particle;
 pub use raw::RawBytes;
 use serde::{Deserialize, Serialize};
 pub use sound::Sound;
 pub use text::Text;
+pub use uuid;
 pub use valence_generated::{block, packet_id, status_effects};
+pub use valence_ident as ident;
 pub use valence_ident::Ident;
+pub use valence_math as math;
+pub use valence_nbt as nbt;
 pub use valence_protocol_macros::{Decode, Encode, Packet};
+pub use valence_text as text;
 pub use var_int::VarInt;
 pub use var_long::VarLong;
 pub use velocity::Velocity;
-pub use {
-    anyhow, bytes, uuid, valence_ident as ident, valence_math as math, valence_nbt as nbt,
-    valence_text as text,
-};
 
 /// The maximum number of bytes in a single Minecraft packet.
 pub const MAX_PACKET_SIZE: i32 = 2097152;
@@ -293,6 +296,7 @@ pub enum PacketState {
     Handshaking,
     Status,
     Login,
+    Configuration,
     Play,
 }
 
@@ -399,7 +403,7 @@ mod tests {
                 f: BlockPos::new(1, 2, 3),
                 g: Hand::Off,
                 h: Ident::new("minecraft:whatever").unwrap(),
-                i: ItemStack::new(ItemKind::WoodenSword, 12, None),
+                i: ItemStack::new(ItemKind::WoodenSword, 12),
                 j: "my ".into_text() + "fancy".italic() + " text",
                 k: VarInt(123),
                 l: VarLong(456),
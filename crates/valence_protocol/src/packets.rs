@@ -4,12 +4,49 @@
 //! used in. Names are derived from the `FabricMC` Yarn mappings for
 //! consistency.
 
+pub mod configuration {
+    pub mod client_information_c2s;
+    pub use client_information_c2s::ClientInformationC2s;
+    pub mod custom_payload_c2s;
+    pub use custom_payload_c2s::CustomPayloadC2s;
+    pub mod custom_payload_s2c;
+    pub use custom_payload_s2c::CustomPayloadS2c;
+    pub mod disconnect_s2c;
+    pub use disconnect_s2c::DisconnectS2c;
+    pub mod finish_configuration_c2s;
+    pub use finish_configuration_c2s::FinishConfigurationC2s;
+    pub mod finish_configuration_s2c;
+    pub use finish_configuration_s2c::FinishConfigurationS2c;
+    pub mod keep_alive_c2s;
+    pub use keep_alive_c2s::KeepAliveC2s;
+    pub mod keep_alive_s2c;
+    pub use keep_alive_s2c::KeepAliveS2c;
+    pub mod ping_s2c;
+    pub use ping_s2c::PingS2c;
+    pub mod pong_c2s;
+    pub use pong_c2s::PongC2s;
+    pub mod registry_data_s2c;
+    pub use registry_data_s2c::RegistryDataS2c;
+    pub mod reset_chat_s2c;
+    pub use reset_chat_s2c::ResetChatS2c;
+    pub mod select_known_packs_c2s;
+    pub use select_known_packs_c2s::SelectKnownPacksC2s;
+    pub mod select_known_packs_s2c;
+    pub use select_known_packs_s2c::SelectKnownPacksS2c;
+    pub mod update_enabled_features_s2c;
+    pub use update_enabled_features_s2c::UpdateEnabledFeaturesS2c;
+    pub mod update_tags_s2c;
+    pub use update_tags_s2c::UpdateTagsS2c;
+}
+
 pub mod handshaking {
     pub mod handshake_c2s;
     pub use handshake_c2s::HandshakeC2s;
 }
 
 pub mod login {
+    pub mod login_acknowledged_c2s;
+    pub use login_acknowledged_c2s::LoginAcknowledgedC2s;
     pub mod login_compression_s2c;
     pub use login_compression_s2c::LoginCompressionS2c;
     pub mod login_disconnect_s2c;
@@ -87,6 +124,8 @@ pub mod play {
     pub use command_suggestions_s2c::CommandSuggestionsS2c;
     pub mod command_tree_s2c;
     pub use command_tree_s2c::CommandTreeS2c;
+    pub mod configuration_acknowledged_c2s;
+    pub use configuration_acknowledged_c2s::ConfigurationAcknowledgedC2s;
     pub mod cooldown_update_s2c;
     pub use cooldown_update_s2c::CooldownUpdateS2c;
     pub mod craft_failed_response_s2c;
@@ -269,6 +308,8 @@ pub mod play {
     pub use scoreboard_objective_update_s2c::ScoreboardObjectiveUpdateS2c;
     pub mod scoreboard_player_update_s2c;
     pub use scoreboard_player_update_s2c::ScoreboardPlayerUpdateS2c;
+    pub mod scoreboard_score_reset_s2c;
+    pub use scoreboard_score_reset_s2c::ScoreboardScoreResetS2c;
     pub mod screen_handler_property_update_s2c;
     pub use screen_handler_property_update_s2c::ScreenHandlerPropertyUpdateS2c;
     pub mod screen_handler_slot_update_s2c;
@@ -289,6 +330,8 @@ pub mod play {
     pub use simulation_distance_s2c::SimulationDistanceS2c;
     pub mod spectator_teleport_c2s;
     pub use spectator_teleport_c2s::SpectatorTeleportC2s;
+    pub mod start_configuration_s2c;
+    pub use start_configuration_s2c::StartConfigurationS2c;
     pub mod statistics_s2c;
     pub use statistics_s2c::StatisticsS2c;
     pub mod stop_sound_s2c;
@@ -0,0 +1,35 @@
+use crate::packets::play::client_settings_c2s::{ChatMode, DisplayedSkinParts, MainArm};
+use crate::packets::play::ClientSettingsC2s;
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+/// The configuration state equivalent of [`ClientSettingsC2s`].
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::CLIENT_INFORMATION_C2S,
+    state = PacketState::Configuration
+)]
+pub struct ClientInformationC2s<'a> {
+    pub locale: &'a str,
+    pub view_distance: u8,
+    pub chat_mode: ChatMode,
+    pub chat_colors: bool,
+    pub displayed_skin_parts: DisplayedSkinParts,
+    pub main_arm: MainArm,
+    pub enable_text_filtering: bool,
+    pub allow_server_listings: bool,
+}
+
+impl<'a> From<ClientInformationC2s<'a>> for ClientSettingsC2s<'a> {
+    fn from(value: ClientInformationC2s<'a>) -> Self {
+        Self {
+            locale: value.locale,
+            view_distance: value.view_distance,
+            chat_mode: value.chat_mode,
+            chat_colors: value.chat_colors,
+            displayed_skin_parts: value.displayed_skin_parts,
+            main_arm: value.main_arm,
+            enable_text_filtering: value.enable_text_filtering,
+            allow_server_listings: value.allow_server_listings,
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use std::borrow::Cow;
+
+use valence_ident::Ident;
+
+use crate::packets::play::custom_payload_c2s::MAX_PAYLOAD_SIZE;
+use crate::{packet_id, Bounded, Decode, Encode, Packet, PacketState, RawBytes};
+
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::CUSTOM_PAYLOAD_C2S,
+    state = PacketState::Configuration
+)]
+pub struct CustomPayloadC2s<'a> {
+    pub channel: Ident<Cow<'a, str>>,
+    pub data: Bounded<RawBytes<'a>, MAX_PAYLOAD_SIZE>,
+}
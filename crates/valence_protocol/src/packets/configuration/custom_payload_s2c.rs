@@ -0,0 +1,17 @@
+use std::borrow::Cow;
+
+use valence_ident::Ident;
+
+use crate::{packet_id, Bounded, Decode, Encode, Packet, PacketState, RawBytes};
+
+const MAX_PAYLOAD_SIZE: usize = 0x100000;
+
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::CUSTOM_PAYLOAD_S2C,
+    state = PacketState::Configuration
+)]
+pub struct CustomPayloadS2c<'a> {
+    pub channel: Ident<Cow<'a, str>>,
+    pub data: Bounded<RawBytes<'a>, MAX_PAYLOAD_SIZE>,
+}
@@ -0,0 +1,14 @@
+use std::borrow::Cow;
+
+use valence_text::Text;
+
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::DISCONNECT_S2C,
+    state = PacketState::Configuration
+)]
+pub struct DisconnectS2c<'a> {
+    pub reason: Cow<'a, Text>,
+}
@@ -0,0 +1,12 @@
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+/// Sent by the client to acknowledge [`FinishConfigurationS2c`]. The client is
+/// in the play state after this packet.
+///
+/// [`FinishConfigurationS2c`]: super::FinishConfigurationS2c
+#[derive(Copy, Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::FINISH_CONFIGURATION_C2S,
+    state = PacketState::Configuration
+)]
+pub struct FinishConfigurationC2s;
@@ -0,0 +1,10 @@
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+/// Tells the client that configuration is done and that it should switch to
+/// the play state.
+#[derive(Copy, Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::FINISH_CONFIGURATION_S2C,
+    state = PacketState::Configuration
+)]
+pub struct FinishConfigurationS2c;
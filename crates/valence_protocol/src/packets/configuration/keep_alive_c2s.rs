@@ -0,0 +1,10 @@
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+#[derive(Copy, Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::KEEP_ALIVE_C2S,
+    state = PacketState::Configuration
+)]
+pub struct KeepAliveC2s {
+    pub id: u64,
+}
@@ -0,0 +1,10 @@
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+#[derive(Copy, Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::PING_S2C,
+    state = PacketState::Configuration
+)]
+pub struct PingS2c {
+    pub id: i32,
+}
@@ -0,0 +1,29 @@
+use std::borrow::Cow;
+
+use valence_ident::Ident;
+use valence_nbt::Compound;
+
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+/// Sends the contents of a single dynamic registry (biomes, dimension types,
+/// chat types, etc.) to the client.
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::REGISTRY_DATA_S2C,
+    state = PacketState::Configuration
+)]
+pub struct RegistryDataS2c<'a> {
+    pub registry_id: Ident<Cow<'a, str>>,
+    pub entries: Vec<RegistryDataEntry<'a>>,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct RegistryDataEntry<'a> {
+    pub id: Ident<Cow<'a, str>>,
+    /// The element of the registry entry. If this is `None`, the client uses
+    /// the data from one of the known packs agreed upon with
+    /// [`SelectKnownPacksS2c`].
+    ///
+    /// [`SelectKnownPacksS2c`]: super::SelectKnownPacksS2c
+    pub data: Option<Cow<'a, Compound>>,
+}
@@ -0,0 +1,10 @@
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+/// Clears the client's chat session state, such as the message chain and
+/// the last seen messages.
+#[derive(Copy, Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::RESET_CHAT_S2C,
+    state = PacketState::Configuration
+)]
+pub struct ResetChatS2c;
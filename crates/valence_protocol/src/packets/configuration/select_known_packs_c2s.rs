@@ -0,0 +1,14 @@
+use std::borrow::Cow;
+
+use super::select_known_packs_s2c::KnownPack;
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+/// The subset of the server's known packs that the client also knows about.
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::SELECT_KNOWN_PACKS_C2S,
+    state = PacketState::Configuration
+)]
+pub struct SelectKnownPacksC2s<'a> {
+    pub packs: Cow<'a, [KnownPack<'a>]>,
+}
@@ -0,0 +1,23 @@
+use std::borrow::Cow;
+
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+/// Informs the client of the data packs the server has loaded. The client
+/// responds with [`SelectKnownPacksC2s`].
+///
+/// [`SelectKnownPacksC2s`]: super::SelectKnownPacksC2s
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::SELECT_KNOWN_PACKS_S2C,
+    state = PacketState::Configuration
+)]
+pub struct SelectKnownPacksS2c<'a> {
+    pub packs: Cow<'a, [KnownPack<'a>]>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct KnownPack<'a> {
+    pub namespace: &'a str,
+    pub id: &'a str,
+    pub version: &'a str,
+}
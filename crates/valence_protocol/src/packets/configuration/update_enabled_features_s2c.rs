@@ -0,0 +1,15 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use valence_ident::Ident;
+
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::UPDATE_ENABLED_FEATURES_S2C,
+    state = PacketState::Configuration
+)]
+pub struct UpdateEnabledFeaturesS2c<'a> {
+    pub features: Cow<'a, BTreeSet<Ident<String>>>,
+}
@@ -0,0 +1,13 @@
+use std::borrow::Cow;
+
+use crate::packets::play::synchronize_tags_s2c::RegistryMap;
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(
+    id = packet_id::configuration::UPDATE_TAGS_S2C,
+    state = PacketState::Configuration
+)]
+pub struct UpdateTagsS2c<'a> {
+    pub groups: Cow<'a, RegistryMap>,
+}
@@ -0,0 +1,9 @@
+use crate::{packet_id, Decode, Encode, Packet, PacketState};
+
+/// Acknowledges [`LoginSuccessS2c`]. The client is in the configuration state
+/// after this packet.
+///
+/// [`LoginSuccessS2c`]: super::LoginSuccessS2c
+#[derive(Copy, Clone, Debug, Encode, Decode, Packet)]
+#[packet(id = packet_id::LOGIN_ACKNOWLEDGED, state = PacketState::Login)]
+pub struct LoginAcknowledgedC2s;
@@ -11,4 +11,7 @@ pub struct LoginSuccessS2c<'a> {
     pub uuid: Uuid,
     pub username: Bounded<&'a str, 16>,
     pub properties: Cow<'a, [Property<&'a str>]>,
+    /// Whether the client should disconnect on packets it fails to decode
+    /// instead of skipping them.
+    pub strict_error_handling: bool,
 }
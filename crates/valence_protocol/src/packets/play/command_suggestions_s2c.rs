@@ -2,9 +2,10 @@ use std::borrow::Cow;
 
 use valence_text::Text;
 
-use crate::{Decode, Encode, Packet, VarInt};
+use crate::{packet_id, Decode, Encode, Packet, VarInt};
 
 #[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(id = packet_id::COMMAND_SUGGESTIONS)]
 pub struct CommandSuggestionsS2c<'a> {
     pub id: VarInt,
     pub start: VarInt,
@@ -0,0 +1,9 @@
+use crate::{packet_id, Decode, Encode, Packet};
+
+/// Acknowledges [`StartConfigurationS2c`]. All packets sent by the client after
+/// this one are configuration packets.
+///
+/// [`StartConfigurationS2c`]: super::StartConfigurationS2c
+#[derive(Copy, Clone, Debug, Encode, Decode, Packet)]
+#[packet(id = packet_id::CONFIGURATION_ACKNOWLEDGED)]
+pub struct ConfigurationAcknowledgedC2s;
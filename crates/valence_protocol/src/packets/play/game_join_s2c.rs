@@ -2,7 +2,6 @@ use std::borrow::Cow;
 use std::collections::BTreeSet;
 
 use valence_ident::Ident;
-use valence_nbt::Compound;
 
 use crate::game_mode::OptGameMode;
 use crate::{Decode, Encode, GameMode, GlobalPos, Packet, VarInt};
@@ -11,20 +10,23 @@ use crate::{Decode, Encode, GameMode, GlobalPos, Packet, VarInt};
 pub struct GameJoinS2c<'a> {
     pub entity_id: i32,
     pub is_hardcore: bool,
-    pub game_mode: GameMode,
-    pub previous_game_mode: OptGameMode,
     pub dimension_names: Cow<'a, BTreeSet<Ident<Cow<'a, str>>>>,
-    pub registry_codec: Cow<'a, Compound>,
-    pub dimension_type_name: Ident<Cow<'a, str>>,
-    pub dimension_name: Ident<Cow<'a, str>>,
-    pub hashed_seed: i64,
     pub max_players: VarInt,
     pub view_distance: VarInt,
     pub simulation_distance: VarInt,
     pub reduced_debug_info: bool,
     pub enable_respawn_screen: bool,
+    pub do_limited_crafting: bool,
+    /// Index of the dimension type in the `minecraft:dimension_type` registry
+    /// sent during configuration.
+    pub dimension_type: VarInt,
+    pub dimension_name: Ident<Cow<'a, str>>,
+    pub hashed_seed: i64,
+    pub game_mode: GameMode,
+    pub previous_game_mode: OptGameMode,
     pub is_debug: bool,
     pub is_flat: bool,
     pub last_death_location: Option<GlobalPos<'a>>,
     pub portal_cooldown: VarInt,
+    pub enforces_secure_chat: bool,
 }
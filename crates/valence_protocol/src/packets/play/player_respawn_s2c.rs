@@ -7,14 +7,18 @@ use crate::{Decode, Encode, GameMode, GlobalPos, Packet, VarInt};
 
 #[derive(Clone, PartialEq, Debug, Encode, Decode, Packet)]
 pub struct PlayerRespawnS2c<'a> {
-    pub dimension_type_name: Ident<Cow<'a, str>>,
+    /// Index of the dimension type in the `minecraft:dimension_type` registry
+    /// sent during configuration.
+    pub dimension_type: VarInt,
     pub dimension_name: Ident<Cow<'a, str>>,
     pub hashed_seed: u64,
     pub game_mode: GameMode,
     pub previous_game_mode: OptGameMode,
     pub is_debug: bool,
     pub is_flat: bool,
-    pub copy_metadata: bool,
     pub last_death_location: Option<GlobalPos<'a>>,
     pub portal_cooldown: VarInt,
+    /// Bit mask of the data kept by the client. `0x01` keeps attributes and
+    /// `0x02` keeps entity metadata.
+    pub data_kept: u8,
 }
@@ -1,6 +1,7 @@
-use crate::{Bounded, Decode, Encode, Packet, VarInt};
+use crate::{packet_id, Bounded, Decode, Encode, Packet, VarInt};
 
 #[derive(Copy, Clone, Debug, Encode, Decode, Packet)]
+#[packet(id = packet_id::COMMAND_SUGGESTION)]
 pub struct RequestCommandCompletionsC2s<'a> {
     pub transaction_id: VarInt,
     pub text: Bounded<&'a str, 32500>,
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 
 use bevy_ecs::prelude::*;
+use valence_nbt::Compound;
 use valence_text::Text;
 
 use crate::{Decode, Encode, Packet};
@@ -16,11 +17,15 @@ pub enum ObjectiveMode<'a> {
     Create {
         objective_display_name: Cow<'a, Text>,
         render_type: ObjectiveRenderType,
+        /// The default number format of scores in this objective.
+        number_format: Option<NumberFormat<'a>>,
     },
     Remove,
     Update {
         objective_display_name: Cow<'a, Text>,
         render_type: ObjectiveRenderType,
+        /// The default number format of scores in this objective.
+        number_format: Option<NumberFormat<'a>>,
     },
 }
 
@@ -32,3 +37,14 @@ pub enum ObjectiveRenderType {
     /// Display the value as hearts.
     Hearts,
 }
+
+/// Controls how a score is displayed next to its entry.
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub enum NumberFormat<'a> {
+    /// Hide the score.
+    Blank,
+    /// Display the score as a number using the given style.
+    Styled { style: Cow<'a, Compound> },
+    /// Display the given text instead of the score.
+    Fixed { content: Cow<'a, Text> },
+}
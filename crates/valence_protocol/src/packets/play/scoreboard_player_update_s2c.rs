@@ -1,18 +1,17 @@
+use std::borrow::Cow;
+
+use valence_text::Text;
+
+use super::scoreboard_objective_update_s2c::NumberFormat;
 use crate::{Decode, Encode, Packet, VarInt};
 
 #[derive(Clone, Debug, Encode, Decode, Packet)]
 pub struct ScoreboardPlayerUpdateS2c<'a> {
     pub entity_name: &'a str,
-    pub action: ScoreboardPlayerUpdateAction<'a>,
-}
-
-#[derive(Clone, PartialEq, Debug, Encode, Decode)]
-pub enum ScoreboardPlayerUpdateAction<'a> {
-    Update {
-        objective_name: &'a str,
-        objective_score: VarInt,
-    },
-    Remove {
-        objective_name: &'a str,
-    },
+    pub objective_name: &'a str,
+    pub objective_score: VarInt,
+    /// Shown instead of the entity name if present.
+    pub display_name: Option<Cow<'a, Text>>,
+    /// Overrides the number format of the objective if present.
+    pub number_format: Option<NumberFormat<'a>>,
 }
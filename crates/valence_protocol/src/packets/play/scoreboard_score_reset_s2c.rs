@@ -0,0 +1,9 @@
+use crate::{packet_id, Decode, Encode, Packet};
+
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(id = packet_id::RESET_SCORE)]
+pub struct ScoreboardScoreResetS2c<'a> {
+    pub entity_name: &'a str,
+    /// The objective to remove the score from, or all objectives if `None`.
+    pub objective_name: Option<&'a str>,
+}
@@ -0,0 +1,9 @@
+use crate::{packet_id, Decode, Encode, Packet};
+
+/// Sends the client back to the configuration state. The client stops
+/// processing play packets and responds with [`ConfigurationAcknowledgedC2s`].
+///
+/// [`ConfigurationAcknowledgedC2s`]: super::ConfigurationAcknowledgedC2s
+#[derive(Copy, Clone, Debug, Encode, Decode, Packet)]
+#[packet(id = packet_id::START_CONFIGURATION)]
+pub struct StartConfigurationS2c;
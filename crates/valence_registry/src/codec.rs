@@ -1,10 +1,15 @@
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use tracing::error;
 use valence_ident::Ident;
-use valence_nbt::{compound, Compound, List, Value};
+use valence_nbt::{Compound, List, Value};
+use valence_protocol::encode::{PacketWriter, WritePacket};
+use valence_protocol::packets::configuration::registry_data_s2c::RegistryDataEntry;
+use valence_protocol::packets::configuration::RegistryDataS2c;
+use valence_server_common::Server;
 
 use crate::RegistrySet;
 
@@ -13,16 +18,17 @@ pub(super) fn build(app: &mut App) {
         .add_systems(PostUpdate, cache_registry_codec.in_set(RegistrySet));
 }
 
-/// Contains the registry codec sent to all players while joining. This contains
-/// information for biomes and dimensions among other things.
+/// Contains the registry codec sent to all players during the configuration
+/// state. This contains information for biomes and dimensions among other
+/// things.
 ///
 /// Generally, end users should not manipulate the registry codec directly. Use
 /// one of the other registry resources instead.
 #[derive(Resource, Debug)]
 pub struct RegistryCodec {
     pub registries: BTreeMap<Ident<String>, Vec<RegistryValue>>,
-    // TODO: store this in binary form?
-    cached_codec: Compound,
+    /// One [`RegistryDataS2c`] packet for every registry.
+    cached_packets: Vec<u8>,
 }
 
 #[derive(Clone, Debug)]
@@ -32,8 +38,10 @@ pub struct RegistryValue {
 }
 
 impl RegistryCodec {
-    pub fn cached_codec(&self) -> &Compound {
-        &self.cached_codec
+    /// Returns bytes of the cached [`RegistryDataS2c`] packets, one for each
+    /// registry in the codec.
+    pub fn registry_data_packets(&self) -> &[u8] {
+        &self.cached_packets
     }
 
     pub fn registry(&self, registry_key: Ident<&str>) -> &Vec<RegistryValue> {
@@ -47,6 +55,16 @@ impl RegistryCodec {
             .get_mut(registry_key.as_str())
             .unwrap_or_else(|| panic!("missing registry for {registry_key}"))
     }
+
+    /// Returns the network ID of the entry `name` in the registry
+    /// `registry_key`. This is the entry's index in the order the registry is
+    /// sent to clients.
+    pub fn entry_index(&self, registry_key: Ident<&str>, name: Ident<&str>) -> Option<usize> {
+        self.registries
+            .get(registry_key.as_str())?
+            .iter()
+            .position(|value| value.name == name)
+    }
 }
 
 impl Default for RegistryCodec {
@@ -104,34 +122,31 @@ impl Default for RegistryCodec {
         Self {
             registries,
             // Cache will be created later.
-            cached_codec: Compound::new(),
+            cached_packets: vec![],
         }
     }
 }
 
-fn cache_registry_codec(codec: ResMut<RegistryCodec>) {
+fn cache_registry_codec(server: Res<Server>, codec: ResMut<RegistryCodec>) {
     if codec.is_changed() {
         let codec = codec.into_inner();
 
-        codec.cached_codec.clear();
-
-        for (reg_name, reg) in &codec.registries {
-            let mut value = vec![];
-
-            for (id, v) in reg.iter().enumerate() {
-                value.push(compound! {
-                    "id" => id as i32,
-                    "name" => v.name.as_str(),
-                    "element" => v.element.clone(),
-                });
-            }
+        codec.cached_packets.clear();
 
-            let registry = compound! {
-                "type" => reg_name.as_str(),
-                "value" => List::Compound(value),
-            };
+        let mut writer =
+            PacketWriter::new(&mut codec.cached_packets, server.compression_threshold());
 
-            codec.cached_codec.insert(reg_name.as_str(), registry);
+        for (reg_name, reg) in &codec.registries {
+            writer.write_packet(&RegistryDataS2c {
+                registry_id: reg_name.as_str_ident().into(),
+                entries: reg
+                    .iter()
+                    .map(|v| RegistryDataEntry {
+                        id: v.name.as_str_ident().into(),
+                        data: Some(Cow::Borrowed(&v.element)),
+                    })
+                    .collect(),
+            });
         }
     }
 }
@@ -3,8 +3,9 @@ use std::borrow::Cow;
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use valence_protocol::encode::{PacketWriter, WritePacket};
+use valence_protocol::packets::configuration::UpdateTagsS2c;
 pub use valence_protocol::packets::play::synchronize_tags_s2c::RegistryMap;
-use valence_protocol::packets::play::SynchronizeTagsS2c;
+use valence_protocol::VarInt;
 use valence_server_common::Server;
 
 use crate::RegistrySet;
@@ -22,14 +23,27 @@ pub(super) fn build(app: &mut App) {
 }
 
 impl TagsRegistry {
-    fn build_synchronize_tags(&self) -> SynchronizeTagsS2c {
-        SynchronizeTagsS2c {
+    fn build_update_tags(&self) -> UpdateTagsS2c {
+        UpdateTagsS2c {
             groups: Cow::Borrowed(&self.registries),
         }
     }
 
-    /// Returns bytes of the cached [`SynchronizeTagsS2c`] packet.
-    pub fn sync_tags_packet(&self) -> &[u8] {
+    /// Returns whether the tag `tag` of the registry `registry` contains the
+    /// entry with the raw ID `id`.
+    ///
+    /// For instance, `contains("minecraft:block", "minecraft:mineable/pickaxe",
+    /// BlockKind::Stone.to_raw().into())` is `true`.
+    pub fn contains(&self, registry: &str, tag: &str, id: i32) -> bool {
+        self.registries
+            .get(registry)
+            .and_then(|tags| tags.get(tag))
+            .is_some_and(|ids| ids.contains(&VarInt(id)))
+    }
+
+    /// Returns bytes of the cached [`UpdateTagsS2c`] packet. This is sent to
+    /// clients in the configuration state.
+    pub fn update_tags_packet(&self) -> &[u8] {
         &self.cached_packet
     }
 }
@@ -44,7 +58,7 @@ fn init_tags_registry(mut tags: ResMut<TagsRegistry>) {
 pub(crate) fn cache_tags_packet(server: Res<Server>, tags: ResMut<TagsRegistry>) {
     if tags.is_changed() {
         let tags = tags.into_inner();
-        let packet = tags.build_synchronize_tags();
+        let packet = tags.build_update_tags();
         let mut bytes = vec![];
         let mut writer = PacketWriter::new(&mut bytes, server.compression_threshold());
 
@@ -64,7 +78,7 @@ mod tests {
         app.update();
 
         let tags_registry = app.world.get_resource::<TagsRegistry>().unwrap();
-        let packet = tags_registry.build_synchronize_tags();
+        let packet = tags_registry.build_update_tags();
         assert!(!packet.registries.is_empty());
         assert!(!tags_registry.cached_packet.is_empty());
     }
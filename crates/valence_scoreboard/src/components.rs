@@ -1,10 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use bevy_ecs::prelude::*;
 use derive_more::{Deref, DerefMut};
 use valence_server::entity::EntityLayerId;
 use valence_server::protocol::packets::play::scoreboard_display_s2c::ScoreboardPosition;
 use valence_server::protocol::packets::play::scoreboard_objective_update_s2c::ObjectiveRenderType;
+use valence_server::protocol::packets::play::team_s2c::{
+    CollisionRule, NameTagVisibility, TeamColor,
+};
 use valence_server::text::IntoText;
 use valence_server::Text;
 
@@ -119,3 +122,141 @@ impl Default for ObjectiveBundle {
         }
     }
 }
+
+/// A string that identifies a team. It's generally not safe to modify this
+/// after it's been created.
+///
+/// Directly analogous to a Team's Name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Component, Deref)]
+pub struct Team(pub(crate) String);
+
+impl Team {
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        Self(name.into())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The display name of a team.
+#[derive(Debug, Clone, PartialEq, Component, Deref, DerefMut)]
+pub struct TeamDisplay(pub Text);
+
+/// How the members of a team are shown and interact with each other.
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct TeamSettings {
+    /// Shown before the names of the members.
+    pub prefix: Text,
+    /// Shown after the names of the members.
+    pub suffix: Text,
+    /// The color of the names of the members. This is also the color of the
+    /// sidebar slot that is only visible to the members, see
+    /// [`TeamSettings::sidebar_position`].
+    pub color: TeamColor,
+    /// Whether members can attack each other.
+    pub friendly_fire: bool,
+    /// Whether members can see invisible teammates.
+    pub see_invisible_teammates: bool,
+    pub name_tag_visibility: NameTagVisibility,
+    pub collision_rule: CollisionRule,
+}
+
+impl TeamSettings {
+    /// The sidebar slot that is only visible to members of teams with this
+    /// color. Use it as the [`ScoreboardPosition`] of an objective.
+    pub fn sidebar_position(&self) -> ScoreboardPosition {
+        ScoreboardPosition::SidebarTeam(self.color)
+    }
+}
+
+impl Default for TeamSettings {
+    fn default() -> Self {
+        Self {
+            prefix: Text::default(),
+            suffix: Text::default(),
+            color: TeamColor::Reset,
+            friendly_fire: true,
+            see_invisible_teammates: true,
+            name_tag_visibility: NameTagVisibility::Always,
+            collision_rule: CollisionRule::Always,
+        }
+    }
+}
+
+/// The names of the entities in a team. Players are identified by their
+/// username and other entities by their UUID.
+#[derive(Debug, Clone, PartialEq, Eq, Component, Default)]
+pub struct TeamMembers(pub(crate) BTreeSet<String>);
+
+impl TeamMembers {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_members<I, M>(members: I) -> Self
+    where
+        I: IntoIterator<Item = M>,
+        M: Into<String>,
+    {
+        Self(members.into_iter().map(Into::into).collect())
+    }
+
+    pub fn contains(&self, member: &str) -> bool {
+        self.0.contains(member)
+    }
+
+    /// Adds a member to the team. Returns whether it was not already a
+    /// member.
+    pub fn insert<M: Into<String>>(&mut self, member: M) -> bool {
+        self.0.insert(member.into())
+    }
+
+    /// Removes a member from the team. Returns whether it was a member.
+    pub fn remove(&mut self, member: &str) -> bool {
+        self.0.remove(member)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Component)]
+pub struct OldTeamMembers(pub(crate) BTreeSet<String>);
+
+impl OldTeamMembers {
+    /// Returns the members that were added and removed since the old members
+    /// were recorded.
+    pub fn diff<'a>(&'a self, members: &'a TeamMembers) -> (Vec<&'a str>, Vec<&'a str>) {
+        let added = members.0.difference(&self.0).map(String::as_str).collect();
+
+        let removed = self.0.difference(&members.0).map(String::as_str).collect();
+
+        (added, removed)
+    }
+}
+
+#[derive(Bundle)]
+pub struct TeamBundle {
+    pub name: Team,
+    pub display: TeamDisplay,
+    pub settings: TeamSettings,
+    pub members: TeamMembers,
+    pub old_members: OldTeamMembers,
+    pub layer: EntityLayerId,
+}
+
+impl Default for TeamBundle {
+    fn default() -> Self {
+        Self {
+            name: Team::new(""),
+            display: TeamDisplay("".into_text()),
+            settings: Default::default(),
+            members: Default::default(),
+            old_members: Default::default(),
+            layer: Default::default(),
+        }
+    }
+}
@@ -1,22 +1,29 @@
 #![doc = include_str!("../README.md")]
 
 mod components;
+mod sidebar;
 use std::collections::BTreeSet;
 
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 pub use components::*;
+pub use sidebar::{Sidebar, SIDEBAR_OBJECTIVE_NAME};
 use tracing::{debug, warn};
 use valence_server::client::{Client, OldVisibleEntityLayers, VisibleEntityLayers};
 use valence_server::entity::EntityLayerId;
 use valence_server::layer::UpdateLayersPreClientSet;
 use valence_server::protocol::packets::play::scoreboard_display_s2c::ScoreboardPosition;
+pub use valence_server::protocol::packets::play::scoreboard_objective_update_s2c::NumberFormat;
 use valence_server::protocol::packets::play::scoreboard_objective_update_s2c::{
     ObjectiveMode, ObjectiveRenderType,
 };
-use valence_server::protocol::packets::play::scoreboard_player_update_s2c::ScoreboardPlayerUpdateAction;
+pub use valence_server::protocol::packets::play::team_s2c::{
+    CollisionRule, NameTagVisibility, TeamColor,
+};
+use valence_server::protocol::packets::play::team_s2c::{Mode as TeamMode, TeamFlags};
 use valence_server::protocol::packets::play::{
     ScoreboardDisplayS2c, ScoreboardObjectiveUpdateS2c, ScoreboardPlayerUpdateS2c,
+    ScoreboardScoreResetS2c, TeamS2c,
 };
 use valence_server::protocol::{VarInt, WritePacket};
 use valence_server::text::IntoText;
@@ -48,6 +55,22 @@ impl Plugin for ScoreboardPlugin {
                 .after(create_or_update_objectives)
                 .after(handle_new_clients)
                 .in_set(ScoreboardSet),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                create_or_update_teams,
+                update_team_members.after(create_or_update_teams),
+                remove_despawned_teams,
+                handle_new_clients_teams,
+            )
+                .in_set(ScoreboardSet),
+        )
+        .add_systems(
+            PostUpdate,
+            (sidebar::remove_sidebars, sidebar::update_sidebars)
+                .chain()
+                .in_set(ScoreboardSet),
         );
     }
 }
@@ -75,11 +98,13 @@ fn create_or_update_objectives(
             ObjectiveMode::Create {
                 objective_display_name: (&display.0).into_cow_text(),
                 render_type: *render_type,
+                number_format: None,
             }
         } else {
             ObjectiveMode::Update {
                 objective_display_name: (&display.0).into_cow_text(),
                 render_type: *render_type,
+                number_format: None,
             }
         };
 
@@ -207,6 +232,7 @@ fn handle_new_clients(
                 mode: ObjectiveMode::Create {
                     objective_display_name: (&display.0).into_cow_text(),
                     render_type: *render_type,
+                    number_format: None,
                 },
             });
             client.write_packet(&ScoreboardDisplayS2c {
@@ -217,10 +243,10 @@ fn handle_new_clients(
             for (key, score) in &scores.0 {
                 let packet = ScoreboardPlayerUpdateS2c {
                     entity_name: key,
-                    action: ScoreboardPlayerUpdateAction::Update {
-                        objective_name: &objective.0,
-                        objective_score: VarInt(*score),
-                    },
+                    objective_name: &objective.0,
+                    objective_score: VarInt(*score),
+                    display_name: None,
+                    number_format: None,
                 };
 
                 client.write_packet(&packet);
@@ -251,24 +277,192 @@ fn update_scores(
         };
 
         for changed_key in old_scores.diff(scores) {
-            let action = match scores.0.get(changed_key) {
-                Some(score) => ScoreboardPlayerUpdateAction::Update {
+            match scores.0.get(changed_key) {
+                Some(score) => layer.write_packet(&ScoreboardPlayerUpdateS2c {
+                    entity_name: changed_key,
                     objective_name: &objective.0,
                     objective_score: VarInt(*score),
-                },
-                None => ScoreboardPlayerUpdateAction::Remove {
-                    objective_name: &objective.0,
-                },
-            };
+                    display_name: None,
+                    number_format: None,
+                }),
+                None => layer.write_packet(&ScoreboardScoreResetS2c {
+                    entity_name: changed_key,
+                    objective_name: Some(&objective.0),
+                }),
+            }
+        }
 
-            let packet = ScoreboardPlayerUpdateS2c {
-                entity_name: changed_key,
-                action,
-            };
+        old_scores.0.clone_from(&scores.0);
+    }
+}
+
+fn create_team_mode<'a>(
+    display: &'a TeamDisplay,
+    settings: &'a TeamSettings,
+    members: &'a TeamMembers,
+) -> TeamMode<'a> {
+    TeamMode::CreateTeam {
+        team_display_name: (&display.0).into_cow_text(),
+        friendly_flags: team_flags(settings),
+        name_tag_visibility: settings.name_tag_visibility,
+        collision_rule: settings.collision_rule,
+        team_color: settings.color,
+        team_prefix: (&settings.prefix).into_cow_text(),
+        team_suffix: (&settings.suffix).into_cow_text(),
+        entities: members.iter().collect(),
+    }
+}
 
-            layer.write_packet(&packet);
+fn team_flags(settings: &TeamSettings) -> TeamFlags {
+    TeamFlags::new()
+        .with_friendly_fire(settings.friendly_fire)
+        .with_see_invisible_teammates(settings.see_invisible_teammates)
+}
+
+fn create_or_update_teams(
+    mut teams: Query<
+        (
+            Ref<Team>,
+            &TeamDisplay,
+            &TeamSettings,
+            &TeamMembers,
+            &mut OldTeamMembers,
+            &EntityLayerId,
+        ),
+        (
+            Or<(Changed<TeamDisplay>, Changed<TeamSettings>)>,
+            Without<Despawned>,
+        ),
+    >,
+    mut layers: Query<&mut EntityLayer>,
+) {
+    for (team, display, settings, members, mut old_members, entity_layer) in &mut teams {
+        if team.name().is_empty() {
+            warn!("Team name is empty");
         }
 
-        old_scores.0.clone_from(&scores.0);
+        let mode = if team.is_added() {
+            // The initial members are sent with the team.
+            old_members.0.clone_from(&members.0);
+
+            create_team_mode(display, settings, members)
+        } else {
+            TeamMode::UpdateTeamInfo {
+                team_display_name: (&display.0).into_cow_text(),
+                friendly_flags: team_flags(settings),
+                name_tag_visibility: settings.name_tag_visibility,
+                collision_rule: settings.collision_rule,
+                team_color: settings.color,
+                team_prefix: (&settings.prefix).into_cow_text(),
+                team_suffix: (&settings.suffix).into_cow_text(),
+            }
+        };
+
+        let Ok(mut layer) = layers.get_mut(entity_layer.0) else {
+            warn!(
+                "No layer found for entity layer ID {:?}, can't update team",
+                entity_layer
+            );
+            continue;
+        };
+
+        layer.write_packet(&TeamS2c {
+            team_name: &team.0,
+            mode,
+        });
+    }
+}
+
+/// Must occur after `create_or_update_teams`.
+fn update_team_members(
+    mut teams: Query<
+        (&Team, &TeamMembers, &mut OldTeamMembers, &EntityLayerId),
+        (Changed<TeamMembers>, Without<Despawned>),
+    >,
+    mut layers: Query<&mut EntityLayer>,
+) {
+    for (team, members, mut old_members, entity_layer) in &mut teams {
+        let Ok(mut layer) = layers.get_mut(entity_layer.0) else {
+            warn!(
+                "No layer found for entity layer ID {:?}, can't update team members",
+                entity_layer
+            );
+            continue;
+        };
+
+        let (added, removed) = old_members.diff(members);
+
+        if !added.is_empty() {
+            layer.write_packet(&TeamS2c {
+                team_name: &team.0,
+                mode: TeamMode::AddEntities { entities: added },
+            });
+        }
+
+        if !removed.is_empty() {
+            layer.write_packet(&TeamS2c {
+                team_name: &team.0,
+                mode: TeamMode::RemoveEntities { entities: removed },
+            });
+        }
+
+        old_members.0.clone_from(&members.0);
+    }
+}
+
+fn remove_despawned_teams(
+    teams: Query<(&Team, &EntityLayerId), With<Despawned>>,
+    mut layers: Query<&mut EntityLayer>,
+) {
+    for (team, entity_layer) in &teams {
+        let Ok(mut layer) = layers.get_mut(entity_layer.0) else {
+            warn!(
+                "No layer found for entity layer ID {:?}, can't remove team",
+                entity_layer
+            );
+            continue;
+        };
+
+        layer.write_packet(&TeamS2c {
+            team_name: &team.0,
+            mode: TeamMode::RemoveTeam,
+        });
+    }
+}
+
+fn handle_new_clients_teams(
+    mut clients: Query<
+        (&mut Client, &VisibleEntityLayers, &OldVisibleEntityLayers),
+        Or<(Added<Client>, Changed<VisibleEntityLayers>)>,
+    >,
+    teams: Query<
+        (
+            &Team,
+            &TeamDisplay,
+            &TeamSettings,
+            &TeamMembers,
+            &EntityLayerId,
+        ),
+        Without<Despawned>,
+    >,
+) {
+    for (mut client, visible_layers, old_visible_layers) in &mut clients {
+        let is_new = client.is_added();
+
+        for (team, display, settings, members, layer) in &teams {
+            let was_visible = !is_new && old_visible_layers.get().contains(&layer.0);
+            let is_visible = visible_layers.0.contains(&layer.0);
+
+            let mode = match (was_visible, is_visible) {
+                (true, false) => TeamMode::RemoveTeam,
+                (false, true) => create_team_mode(display, settings, members),
+                _ => continue,
+            };
+
+            client.write_packet(&TeamS2c {
+                team_name: &team.0,
+                mode,
+            });
+        }
     }
 }
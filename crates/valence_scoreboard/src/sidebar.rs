@@ -0,0 +1,172 @@
+use std::borrow::Cow;
+
+use bevy_ecs::prelude::*;
+use valence_server::client::Client;
+use valence_server::protocol::packets::play::scoreboard_display_s2c::ScoreboardPosition;
+use valence_server::protocol::packets::play::scoreboard_objective_update_s2c::{
+    NumberFormat, ObjectiveMode, ObjectiveRenderType,
+};
+use valence_server::protocol::packets::play::{
+    ScoreboardDisplayS2c, ScoreboardObjectiveUpdateS2c, ScoreboardPlayerUpdateS2c,
+    ScoreboardScoreResetS2c,
+};
+use valence_server::protocol::{VarInt, WritePacket};
+use valence_server::text::IntoText;
+use valence_server::Text;
+
+/// The name of the objective used for sidebars. Every client has its own copy
+/// of this objective, so the name only has to be unique among the objectives
+/// the client can see.
+pub const SIDEBAR_OBJECTIVE_NAME: &str = "valence:sidebar";
+
+/// A sidebar that is only shown to the client it is attached to.
+///
+/// Insert this component on a client entity to show the sidebar, and remove it
+/// to hide the sidebar again. Changes to the title or lines are diffed against
+/// what the client has already seen, so only the lines that changed are
+/// resent.
+///
+/// The lines are displayed from top to bottom and their scores are hidden.
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct Sidebar {
+    pub title: Text,
+    pub lines: Vec<Text>,
+}
+
+impl Sidebar {
+    pub fn new<'a, T: IntoText<'a>>(title: T) -> Self {
+        Self {
+            title: title.into_text(),
+            lines: vec![],
+        }
+    }
+
+    pub fn with_lines<'a, I>(mut self, lines: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: IntoText<'a>,
+    {
+        self.lines = lines.into_iter().map(IntoText::into_text).collect();
+        self
+    }
+
+    /// Sets the line at `index`, adding empty lines if there are fewer lines
+    /// than that.
+    pub fn set_line<'a, T: IntoText<'a>>(&mut self, index: usize, line: T) {
+        if index >= self.lines.len() {
+            self.lines.resize(index + 1, Text::default());
+        }
+
+        self.lines[index] = line.into_text();
+    }
+}
+
+/// The sidebar as it was last sent to the client.
+#[derive(Debug, Component)]
+pub(crate) struct OldSidebar(Sidebar);
+
+/// The name of the score holding the line at `index`. Player names can't
+/// contain `#`, so these never collide with a real player.
+fn line_entry(index: usize) -> String {
+    format!("#{index}")
+}
+
+fn write_line<W: WritePacket>(client: &mut W, index: usize, line: &Text) {
+    client.write_packet(&ScoreboardPlayerUpdateS2c {
+        entity_name: &line_entry(index),
+        objective_name: SIDEBAR_OBJECTIVE_NAME,
+        // The sidebar is sorted by descending score.
+        objective_score: VarInt(-(index as i32)),
+        display_name: Some(Cow::Borrowed(line)),
+        number_format: None,
+    });
+}
+
+fn objective_mode(title: &Text, created: bool) -> ObjectiveMode<'_> {
+    let objective_display_name = title.into_cow_text();
+    let render_type = ObjectiveRenderType::Integer;
+    let number_format = Some(NumberFormat::Blank);
+
+    if created {
+        ObjectiveMode::Create {
+            objective_display_name,
+            render_type,
+            number_format,
+        }
+    } else {
+        ObjectiveMode::Update {
+            objective_display_name,
+            render_type,
+            number_format,
+        }
+    }
+}
+
+pub(crate) fn update_sidebars(
+    mut clients: Query<(Entity, &mut Client, &Sidebar, Option<&mut OldSidebar>), Changed<Sidebar>>,
+    mut commands: Commands,
+) {
+    for (entity, mut client, sidebar, old_sidebar) in &mut clients {
+        let Some(mut old_sidebar) = old_sidebar else {
+            client.write_packet(&ScoreboardObjectiveUpdateS2c {
+                objective_name: SIDEBAR_OBJECTIVE_NAME,
+                mode: objective_mode(&sidebar.title, true),
+            });
+            client.write_packet(&ScoreboardDisplayS2c {
+                position: ScoreboardPosition::Sidebar,
+                score_name: SIDEBAR_OBJECTIVE_NAME,
+            });
+
+            for (index, line) in sidebar.lines.iter().enumerate() {
+                write_line(&mut *client, index, line);
+            }
+
+            commands.entity(entity).insert(OldSidebar(sidebar.clone()));
+            continue;
+        };
+
+        let old = &old_sidebar.0;
+
+        if old.title != sidebar.title {
+            client.write_packet(&ScoreboardObjectiveUpdateS2c {
+                objective_name: SIDEBAR_OBJECTIVE_NAME,
+                mode: objective_mode(&sidebar.title, false),
+            });
+        }
+
+        for (index, line) in sidebar.lines.iter().enumerate() {
+            if old.lines.get(index) != Some(line) {
+                write_line(&mut *client, index, line);
+            }
+        }
+
+        for index in sidebar.lines.len()..old.lines.len() {
+            client.write_packet(&ScoreboardScoreResetS2c {
+                entity_name: &line_entry(index),
+                objective_name: Some(SIDEBAR_OBJECTIVE_NAME),
+            });
+        }
+
+        old_sidebar.0.clone_from(sidebar);
+    }
+}
+
+pub(crate) fn remove_sidebars(
+    mut removed: RemovedComponents<Sidebar>,
+    mut clients: Query<&mut Client, (With<OldSidebar>, Without<Sidebar>)>,
+    mut commands: Commands,
+) {
+    for entity in removed.read() {
+        // A sidebar that was removed and inserted again is diffed as usual.
+        let Ok(mut client) = clients.get_mut(entity) else {
+            continue;
+        };
+
+        client.write_packet(&ScoreboardObjectiveUpdateS2c {
+            objective_name: SIDEBAR_OBJECTIVE_NAME,
+            mode: ObjectiveMode::Remove,
+        });
+
+        commands.entity(entity).remove::<OldSidebar>();
+    }
+}
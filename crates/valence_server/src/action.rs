@@ -1,18 +1,36 @@
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
 use derive_more::Deref;
+use valence_entity::active_status_effects::ActiveStatusEffects;
+use valence_entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_entity::{entity, OnGround, Pose, Position};
+use valence_generated::item::ToolRuleBlocks;
+use valence_math::{Aabb, DVec3};
+use valence_protocol::block::{PropName, PropValue};
+use valence_protocol::item::component::IdSet;
+use valence_protocol::item::ItemComponentKind;
 use valence_protocol::packets::play::player_action_c2s::PlayerAction;
-use valence_protocol::packets::play::{PlayerActionC2s, PlayerActionResponseS2c};
-use valence_protocol::{BlockPos, Direction, VarInt, WritePacket};
+use valence_protocol::packets::play::{BlockUpdateS2c, PlayerActionC2s, PlayerActionResponseS2c};
+use valence_protocol::status_effects::StatusEffect;
+use valence_protocol::{
+    ident, BlockKind, BlockPos, BlockState, Direction, GameMode, Ident, ItemComponent, ItemStack,
+    VarInt, WritePacket,
+};
+use valence_registry::{RegistryCodec, TagsRegistry};
+use valence_server_common::Server;
 
-use crate::client::{Client, UpdateClientsSet};
+use crate::client::{Client, UpdateClientsSet, VisibleChunkLayer};
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::ChunkLayer;
 
 pub struct ActionPlugin;
 
 impl Plugin for ActionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<DiggingEvent>()
+        app.init_resource::<DiggingSettings>()
+            .add_event::<DiggingEvent>()
+            .add_event::<DiggingViolationEvent>()
             .add_systems(EventLoopPreUpdate, handle_player_action)
             .add_systems(
                 PostUpdate,
@@ -36,6 +54,91 @@ pub enum DiggingState {
     Stop,
 }
 
+/// Configuration resource for validating block digging.
+///
+/// All checks are disabled by default. When a check fails, no
+/// [`DiggingEvent`] is sent for the action, the block is resent to the client
+/// and a [`DiggingViolationEvent`] is sent instead.
+#[derive(Resource, Clone, Debug)]
+pub struct DiggingSettings {
+    /// Whether clients are prevented from digging blocks out of their reach.
+    pub check_reach: bool,
+    /// Whether clients are prevented from breaking blocks faster than their
+    /// tool, enchantments and status effects allow.
+    ///
+    /// The held item is read from [`DiggingEquipment`], which is kept up to
+    /// date by `valence_inventory`.
+    pub check_break_speed: bool,
+    /// The distance in blocks added to the block interaction range of the
+    /// client when checking reach. Vanilla uses `1.0`.
+    pub reach_leniency: f64,
+    /// The fraction of the expected break time that must have passed when a
+    /// client finishes breaking a block. Vanilla accepts `0.7` to account for
+    /// latency.
+    pub min_break_progress: f32,
+}
+
+impl Default for DiggingSettings {
+    fn default() -> Self {
+        Self {
+            check_reach: false,
+            check_break_speed: false,
+            reach_leniency: 1.0,
+            min_break_progress: 0.7,
+        }
+    }
+}
+
+/// Event sent when a digging action of a client is rejected by one of the
+/// checks enabled in [`DiggingSettings`].
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct DiggingViolationEvent {
+    pub client: Entity,
+    pub position: BlockPos,
+    pub state: DiggingState,
+    pub violation: DiggingViolation,
+}
+
+/// The reason a digging action was rejected.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DiggingViolation {
+    /// The block is too far away from the eyes of the client.
+    Reach {
+        /// The distance from the eyes of the client to the block.
+        distance: f64,
+        /// The maximum allowed distance.
+        max_distance: f64,
+    },
+    /// The client finished breaking the block too early.
+    BreakSpeed {
+        /// How much of the block had been broken, where `1.0` is fully broken.
+        progress: f32,
+    },
+}
+
+/// The items that determine how fast a client breaks blocks.
+#[derive(Component, Clone, PartialEq, Default, Debug)]
+pub struct DiggingEquipment {
+    /// The item in the main hand, used as the tool.
+    pub main_hand: ItemStack,
+    /// The item on the head, which may have Aqua Affinity.
+    pub head: ItemStack,
+}
+
+/// The block a client is currently breaking.
+#[derive(Component, Clone, Default, Debug)]
+pub struct DiggingProgress {
+    /// The position of the block and the tick digging started on.
+    current: Option<(BlockPos, i64)>,
+}
+
+impl DiggingProgress {
+    /// Returns the position of the block the client is breaking.
+    pub fn position(&self) -> Option<BlockPos> {
+        self.current.map(|(pos, _)| pos)
+    }
+}
+
 #[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug, Deref)]
 pub struct ActionSequence(i32);
 
@@ -49,54 +152,368 @@ impl ActionSequence {
     }
 }
 
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct DiggingQuery {
+    action_seq: &'static mut ActionSequence,
+    client: Option<&'static mut Client>,
+    progress: Option<&'static mut DiggingProgress>,
+    pos: Option<&'static Position>,
+    pose: Option<&'static entity::Pose>,
+    on_ground: Option<&'static OnGround>,
+    game_mode: Option<&'static GameMode>,
+    attributes: Option<&'static EntityAttributes>,
+    effects: Option<&'static ActiveStatusEffects>,
+    equipment: Option<&'static DiggingEquipment>,
+    chunk_layer: Option<&'static VisibleChunkLayer>,
+}
+
+#[allow(clippy::too_many_arguments)]
 fn handle_player_action(
-    mut clients: Query<&mut ActionSequence>,
+    mut clients: Query<DiggingQuery>,
     mut packets: EventReader<PacketEvent>,
+    layers: Query<&ChunkLayer>,
+    settings: Res<DiggingSettings>,
+    server: Res<Server>,
+    tags: Res<TagsRegistry>,
+    codec: Res<RegistryCodec>,
     mut digging_events: EventWriter<DiggingEvent>,
+    mut violation_events: EventWriter<DiggingViolationEvent>,
 ) {
     for packet in packets.read() {
         if let Some(pkt) = packet.decode::<PlayerActionC2s>() {
-            if let Ok(mut seq) = clients.get_mut(packet.client) {
-                seq.update(pkt.sequence.0);
+            let Ok(mut client) = clients.get_mut(packet.client) else {
+                continue;
+            };
+
+            client.action_seq.update(pkt.sequence.0);
+
+            let state = match pkt.action {
+                PlayerAction::StartDestroyBlock => DiggingState::Start,
+                PlayerAction::AbortDestroyBlock => DiggingState::Abort,
+                PlayerAction::StopDestroyBlock => DiggingState::Stop,
+                PlayerAction::DropAllItems => continue,
+                PlayerAction::DropItem => continue,
+                PlayerAction::ReleaseUseItem => continue,
+                PlayerAction::SwapItemWithOffhand => continue,
+            };
+
+            let layer = client
+                .chunk_layer
+                .and_then(|layer| layers.get(layer.0).ok());
+
+            let violation = check_digging(
+                &client,
+                layer,
+                pkt.position,
+                state,
+                &settings,
+                &server,
+                &tags,
+                &codec,
+            );
+
+            if let Some(progress) = &mut client.progress {
+                progress.current = match state {
+                    DiggingState::Start if violation.is_none() => {
+                        Some((pkt.position, server.current_tick()))
+                    }
+                    _ => None,
+                };
             }
 
-            // TODO: check that digging is happening within configurable distance to client.
-            // TODO: check that blocks are being broken at the appropriate speeds.
-
-            match pkt.action {
-                PlayerAction::StartDestroyBlock => {
-                    digging_events.send(DiggingEvent {
-                        client: packet.client,
-                        position: pkt.position,
-                        direction: pkt.direction,
-                        state: DiggingState::Start,
-                    });
-                }
-                PlayerAction::AbortDestroyBlock => {
-                    digging_events.send(DiggingEvent {
-                        client: packet.client,
-                        position: pkt.position,
-                        direction: pkt.direction,
-                        state: DiggingState::Abort,
-                    });
+            if let Some(violation) = violation {
+                // Undo the client's prediction of the block being broken.
+                if let (Some(conn), Some(layer)) = (&mut client.client, layer) {
+                    if let Some(block) = layer.block(pkt.position) {
+                        conn.write_packet(&BlockUpdateS2c {
+                            position: pkt.position,
+                            block_id: block.state,
+                        });
+                    }
                 }
-                PlayerAction::StopDestroyBlock => {
-                    digging_events.send(DiggingEvent {
-                        client: packet.client,
-                        position: pkt.position,
-                        direction: pkt.direction,
-                        state: DiggingState::Stop,
-                    });
-                }
-                PlayerAction::DropAllItems => {}
-                PlayerAction::DropItem => {}
-                PlayerAction::ReleaseUseItem => {}
-                PlayerAction::SwapItemWithOffhand => {}
+
+                violation_events.send(DiggingViolationEvent {
+                    client: packet.client,
+                    position: pkt.position,
+                    state,
+                    violation,
+                });
+
+                continue;
             }
+
+            digging_events.send(DiggingEvent {
+                client: packet.client,
+                position: pkt.position,
+                direction: pkt.direction,
+                state,
+            });
         }
     }
 }
 
+/// Runs the checks enabled in the settings and returns the first violation.
+#[allow(clippy::too_many_arguments)]
+fn check_digging(
+    client: &DiggingQueryItem,
+    layer: Option<&ChunkLayer>,
+    position: BlockPos,
+    state: DiggingState,
+    settings: &DiggingSettings,
+    server: &Server,
+    tags: &TagsRegistry,
+    codec: &RegistryCodec,
+) -> Option<DiggingViolation> {
+    if state == DiggingState::Abort {
+        return None;
+    }
+
+    let eye_pos = client
+        .pos
+        .map(|pos| pos.0 + DVec3::new(0.0, eye_height(client), 0.0));
+
+    if settings.check_reach {
+        if let Some(eye_pos) = eye_pos {
+            let block_box = Aabb::from_bottom_size(
+                DVec3::new(position.x.into(), position.y.into(), position.z.into()),
+                DVec3::ONE,
+            );
+
+            let distance = block_box.distance_to_point(eye_pos);
+            let max_distance = block_interaction_range(client) + settings.reach_leniency;
+
+            if distance > max_distance {
+                return Some(DiggingViolation::Reach {
+                    distance,
+                    max_distance,
+                });
+            }
+        }
+    }
+
+    if settings.check_break_speed
+        && state == DiggingState::Stop
+        && client.game_mode != Some(&GameMode::Creative)
+    {
+        let layer = layer?;
+        let block = layer.block(position)?.state;
+
+        let submerged = eye_pos.is_some_and(|eye_pos| {
+            layer
+                .block(BlockPos::from(eye_pos))
+                .is_some_and(|b| is_water(b.state))
+        });
+
+        let delta = block_break_delta(client, block, submerged, tags, codec);
+
+        let ticks = match client.progress.as_deref().and_then(|p| p.current) {
+            Some((pos, start_tick)) if pos == position => server.current_tick() - start_tick,
+            // The client never started breaking this block.
+            _ => 0,
+        };
+
+        let progress = delta * (ticks + 1) as f32;
+
+        if progress < settings.min_break_progress {
+            return Some(DiggingViolation::BreakSpeed { progress });
+        }
+    }
+
+    None
+}
+
+/// Returns the fraction of the block broken per tick, following the vanilla
+/// formula.
+fn block_break_delta(
+    client: &DiggingQueryItem,
+    block: BlockState,
+    submerged: bool,
+    tags: &TagsRegistry,
+    codec: &RegistryCodec,
+) -> f32 {
+    let hardness = block.hardness();
+
+    if hardness < 0.0 {
+        // Unbreakable.
+        return 0.0;
+    }
+
+    if hardness == 0.0 {
+        return 1.0;
+    }
+
+    let empty = ItemStack::EMPTY;
+    let main_hand = client.equipment.map_or(&empty, |e| &e.main_hand);
+    let head = client.equipment.map_or(&empty, |e| &e.head);
+
+    let (mut speed, correct_tool) = tool_speed(main_hand, block.to_kind(), tags);
+
+    if speed > 1.0 {
+        let efficiency = enchantment_level(main_hand, codec, ident!("efficiency"));
+
+        speed += attribute_value(client, EntityAttribute::PlayerMiningEfficiency) as f32;
+
+        if efficiency > 0 {
+            speed += (efficiency * efficiency + 1) as f32;
+        }
+    }
+
+    if let Some(effects) = client.effects {
+        let haste = effects
+            .get_current_effect(StatusEffect::Haste)
+            .map(|e| e.amplifier())
+            .max(
+                effects
+                    .get_current_effect(StatusEffect::ConduitPower)
+                    .map(|e| e.amplifier()),
+            );
+
+        if let Some(amplifier) = haste {
+            speed *= 1.0 + (f32::from(amplifier) + 1.0) * 0.2;
+        }
+
+        if let Some(fatigue) = effects.get_current_effect(StatusEffect::MiningFatigue) {
+            speed *= match fatigue.amplifier() {
+                0 => 0.3,
+                1 => 0.09,
+                2 => 0.0027,
+                _ => 8.1e-4,
+            };
+        }
+    }
+
+    speed *= attribute_value(client, EntityAttribute::PlayerBlockBreakSpeed) as f32;
+
+    if submerged {
+        let aqua_affinity = enchantment_level(head, codec, ident!("aqua_affinity"));
+
+        if aqua_affinity == 0 {
+            speed *= attribute_value(client, EntityAttribute::PlayerSubmergedMiningSpeed) as f32;
+        }
+    }
+
+    if !client.on_ground.map_or(true, |on_ground| on_ground.0) {
+        speed /= 5.0;
+    }
+
+    let divisor = if !block.requires_tool() || correct_tool {
+        30.0
+    } else {
+        100.0
+    };
+
+    speed / hardness / divisor
+}
+
+/// Returns the mining speed of the item for the block and whether it is the
+/// correct tool to get drops from the block.
+fn tool_speed(item: &ItemStack, block: BlockKind, tags: &TagsRegistry) -> (f32, bool) {
+    if item.is_empty() || item.components.is_removed(ItemComponentKind::Tool) {
+        return (1.0, false);
+    }
+
+    let raw_id = i32::from(block.to_raw());
+    let in_tag = |tag: &str| tags.contains("minecraft:block", tag, raw_id);
+
+    if let Some(ItemComponent::Tool(tool)) = item.component(ItemComponentKind::Tool) {
+        let rules = tool.rules.iter().map(|rule| {
+            let matches = match &rule.blocks {
+                IdSet::Tag(tag) => in_tag(tag.as_str()),
+                IdSet::Ids(ids) => ids.contains(&VarInt(raw_id)),
+            };
+
+            (matches, rule.speed, rule.correct_for_drops)
+        });
+
+        return apply_tool_rules(rules, tool.default_mining_speed);
+    }
+
+    if let Some(tool) = item.item.tool_component() {
+        let rules = tool.rules.iter().map(|rule| {
+            let matches = match rule.blocks {
+                ToolRuleBlocks::Tag(tag) => in_tag(tag),
+                ToolRuleBlocks::Blocks(blocks) => blocks.contains(&block),
+            };
+
+            (matches, rule.speed, rule.correct_for_drops)
+        });
+
+        return apply_tool_rules(rules, tool.default_mining_speed);
+    }
+
+    (1.0, false)
+}
+
+/// Returns the speed of the first matching rule with a speed and the
+/// correctness of the first matching rule with a correctness.
+fn apply_tool_rules<I>(rules: I, default_speed: f32) -> (f32, bool)
+where
+    I: Iterator<Item = (bool, Option<f32>, Option<bool>)> + Clone,
+{
+    let speed = rules
+        .clone()
+        .find_map(|(matches, speed, _)| speed.filter(|_| matches))
+        .unwrap_or(default_speed);
+
+    let correct = rules
+        .filter_map(|(matches, _, correct)| correct.filter(|_| matches))
+        .next()
+        .unwrap_or(false);
+
+    (speed, correct)
+}
+
+/// Returns the level of the enchantment on the item, or `0` if the item
+/// doesn't have it.
+fn enchantment_level(item: &ItemStack, codec: &RegistryCodec, enchantment: Ident<&str>) -> i32 {
+    let Some(ItemComponent::Enchantments(enchantments)) =
+        item.component(ItemComponentKind::Enchantments)
+    else {
+        return 0;
+    };
+
+    let Some(id) = codec.entry_index(ident!("enchantment"), enchantment) else {
+        return 0;
+    };
+
+    enchantments
+        .enchantments
+        .iter()
+        .find(|e| usize::try_from(e.id.0) == Ok(id))
+        .map_or(0, |e| e.level.0)
+}
+
+fn attribute_value(client: &DiggingQueryItem, attribute: EntityAttribute) -> f64 {
+    client
+        .attributes
+        .and_then(|a| a.get_compute_value(attribute))
+        .unwrap_or_else(|| attribute.default_value())
+}
+
+fn block_interaction_range(client: &DiggingQueryItem) -> f64 {
+    let range = attribute_value(client, EntityAttribute::PlayerBlockInteractionRange);
+
+    // Creative mode players get extra reach.
+    if client.game_mode == Some(&GameMode::Creative) {
+        range + 0.5
+    } else {
+        range
+    }
+}
+
+fn eye_height(client: &DiggingQueryItem) -> f64 {
+    match client.pose.map_or(Pose::Standing, |pose| pose.0) {
+        Pose::Sneaking => 1.27,
+        Pose::FallFlying | Pose::Swimming | Pose::SpinAttack => 0.4,
+        _ => 1.62,
+    }
+}
+
+fn is_water(state: BlockState) -> bool {
+    state.to_kind() == BlockKind::Water || state.get(PropName::Waterlogged) == Some(PropValue::True)
+}
+
 fn acknowledge_player_actions(
     mut clients: Query<(&mut Client, &mut ActionSequence), Changed<ActionSequence>>,
 ) {
@@ -0,0 +1,703 @@
+//! Typed views of block entity data.
+//!
+//! Block entities are stored as NBT in chunks. The types in this module read
+//! the parts of that NBT that are commonly edited into plain Rust structs and
+//! write them back, leaving all other keys untouched. Use them with
+//! [`ChunkLayer::block_entity_as`], [`ChunkLayer::modify_block_entity`] or the
+//! methods of the same names on [`Chunk`].
+//!
+//! [`ChunkLayer::block_entity_as`]: crate::ChunkLayer::block_entity_as
+//! [`ChunkLayer::modify_block_entity`]: crate::ChunkLayer::modify_block_entity
+//! [`Chunk`]: crate::layer::chunk::Chunk
+
+use valence_nbt::{Compound, List, Value};
+use valence_protocol::block::BlockEntityKind;
+use valence_protocol::item::component::{DyeColor, ResolvableProfile};
+use valence_protocol::profile::Property;
+use valence_protocol::uuid::Uuid;
+use valence_protocol::{Ident, ItemKind, Text};
+
+/// A typed view of the NBT of one or more kinds of block entities.
+///
+/// Reading is lenient like in vanilla: missing or malformed keys take their
+/// default values.
+pub trait BlockEntity: Sized {
+    /// The kinds of block entities this type can be read from.
+    const KINDS: &'static [BlockEntityKind];
+
+    /// Reads the block entity from its NBT.
+    fn from_nbt(nbt: &Compound) -> Self;
+
+    /// Writes the block entity into `nbt`. Keys that are not part of this
+    /// type are left untouched.
+    fn write_nbt(&self, nbt: &mut Compound);
+
+    /// Writes the block entity into a new compound.
+    fn to_nbt(&self) -> Compound {
+        let mut nbt = Compound::new();
+        self.write_nbt(&mut nbt);
+        nbt
+    }
+}
+
+/// The text on both sides of a sign or hanging sign.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct Sign {
+    pub front_text: SignText,
+    pub back_text: SignText,
+    /// Whether the sign is waxed and can no longer be edited by players.
+    pub is_waxed: bool,
+}
+
+/// The text on one side of a sign.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SignText {
+    pub messages: [Text; 4],
+    pub color: DyeColor,
+    pub has_glowing_text: bool,
+}
+
+impl Default for SignText {
+    fn default() -> Self {
+        Self {
+            messages: Default::default(),
+            color: DyeColor::Black,
+            has_glowing_text: false,
+        }
+    }
+}
+
+impl SignText {
+    fn from_nbt(nbt: &Compound) -> Self {
+        let mut text = Self::default();
+
+        if let Some(Value::List(List::String(messages))) = nbt.get("messages") {
+            for (message, line) in messages.iter().zip(&mut text.messages) {
+                *line = message.parse().unwrap_or_default();
+            }
+        }
+
+        if let Some(color) = get_str(nbt, "color").and_then(DyeColor::from_str) {
+            text.color = color;
+        }
+
+        text.has_glowing_text = get_bool(nbt, "has_glowing_text");
+
+        text
+    }
+
+    fn write_nbt(&self, nbt: &mut Compound) {
+        nbt.insert(
+            "messages",
+            List::String(self.messages.iter().map(Text::to_string).collect()),
+        );
+        nbt.insert("color", self.color.to_str());
+        nbt.insert("has_glowing_text", self.has_glowing_text);
+    }
+}
+
+impl BlockEntity for Sign {
+    const KINDS: &'static [BlockEntityKind] =
+        &[BlockEntityKind::Sign, BlockEntityKind::HangingSign];
+
+    fn from_nbt(nbt: &Compound) -> Self {
+        let side = |key| match nbt.get(key) {
+            Some(Value::Compound(side)) => SignText::from_nbt(side),
+            _ => SignText::default(),
+        };
+
+        Self {
+            front_text: side("front_text"),
+            back_text: side("back_text"),
+            is_waxed: get_bool(nbt, "is_waxed"),
+        }
+    }
+
+    fn write_nbt(&self, nbt: &mut Compound) {
+        // Written into the existing compounds to keep keys like
+        // `filtered_messages`.
+        self.front_text
+            .write_nbt(get_compound_mut(nbt, "front_text"));
+        self.back_text.write_nbt(get_compound_mut(nbt, "back_text"));
+        nbt.insert("is_waxed", self.is_waxed);
+    }
+}
+
+/// An item in a slot of a block entity's inventory.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ContainerItem {
+    pub slot: u8,
+    pub item: ItemKind,
+    pub count: i8,
+    /// The data components of the item in their NBT form, keyed by the
+    /// component name. They are kept as NBT since converting some of them
+    /// requires the registries.
+    pub components: Compound,
+}
+
+impl ContainerItem {
+    pub fn new(slot: u8, item: ItemKind, count: i8) -> Self {
+        Self {
+            slot,
+            item,
+            count,
+            components: Compound::new(),
+        }
+    }
+}
+
+/// The contents of a block entity with an inventory, such as a chest, barrel,
+/// shulker box, hopper, dispenser or dropper.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct Container {
+    /// The non-empty slots of the container, sorted by slot.
+    pub items: Vec<ContainerItem>,
+    pub custom_name: Option<Text>,
+    /// The loot table that fills the container when it is first opened.
+    pub loot_table: Option<Ident<String>>,
+}
+
+impl Container {
+    /// Returns the item in the given slot, if any.
+    pub fn item(&self, slot: u8) -> Option<&ContainerItem> {
+        self.items.iter().find(|item| item.slot == slot)
+    }
+
+    /// Puts an item into its slot, replacing the item already in it. The
+    /// previous item in the slot is returned.
+    pub fn set_item(&mut self, item: ContainerItem) -> Option<ContainerItem> {
+        set_item(&mut self.items, item)
+    }
+
+    /// Removes the item in the given slot and returns it.
+    pub fn remove_item(&mut self, slot: u8) -> Option<ContainerItem> {
+        remove_item(&mut self.items, slot)
+    }
+}
+
+impl BlockEntity for Container {
+    const KINDS: &'static [BlockEntityKind] = &[
+        BlockEntityKind::Chest,
+        BlockEntityKind::TrappedChest,
+        BlockEntityKind::Barrel,
+        BlockEntityKind::ShulkerBox,
+        BlockEntityKind::Hopper,
+        BlockEntityKind::Dispenser,
+        BlockEntityKind::Dropper,
+    ];
+
+    fn from_nbt(nbt: &Compound) -> Self {
+        Self {
+            items: items_from_nbt(nbt),
+            custom_name: get_text(nbt, "CustomName"),
+            loot_table: get_ident(nbt, "LootTable"),
+        }
+    }
+
+    fn write_nbt(&self, nbt: &mut Compound) {
+        write_items(nbt, &self.items);
+        write_text(nbt, "CustomName", self.custom_name.as_ref());
+        write_ident(nbt, "LootTable", self.loot_table.as_ref());
+    }
+}
+
+/// The state of a furnace, blast furnace or smoker.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct Furnace {
+    /// The non-empty slots of the furnace, sorted by slot. Slot 0 is the
+    /// input, slot 1 the fuel and slot 2 the result.
+    pub items: Vec<ContainerItem>,
+    /// The number of ticks the current fuel keeps burning.
+    pub burn_time: i16,
+    /// The number of ticks the current input has been cooking for.
+    pub cook_time: i16,
+    /// The number of ticks it takes to cook the current input.
+    pub cook_time_total: i16,
+    pub custom_name: Option<Text>,
+}
+
+impl Furnace {
+    pub const SLOT_INPUT: u8 = 0;
+    pub const SLOT_FUEL: u8 = 1;
+    pub const SLOT_RESULT: u8 = 2;
+
+    /// Returns the item in the given slot, if any.
+    pub fn item(&self, slot: u8) -> Option<&ContainerItem> {
+        self.items.iter().find(|item| item.slot == slot)
+    }
+
+    /// Puts an item into its slot, replacing the item already in it. The
+    /// previous item in the slot is returned.
+    pub fn set_item(&mut self, item: ContainerItem) -> Option<ContainerItem> {
+        set_item(&mut self.items, item)
+    }
+
+    /// Removes the item in the given slot and returns it.
+    pub fn remove_item(&mut self, slot: u8) -> Option<ContainerItem> {
+        remove_item(&mut self.items, slot)
+    }
+}
+
+impl BlockEntity for Furnace {
+    const KINDS: &'static [BlockEntityKind] = &[
+        BlockEntityKind::Furnace,
+        BlockEntityKind::BlastFurnace,
+        BlockEntityKind::Smoker,
+    ];
+
+    fn from_nbt(nbt: &Compound) -> Self {
+        Self {
+            items: items_from_nbt(nbt),
+            burn_time: get_short(nbt, "BurnTime"),
+            cook_time: get_short(nbt, "CookTime"),
+            cook_time_total: get_short(nbt, "CookTimeTotal"),
+            custom_name: get_text(nbt, "CustomName"),
+        }
+    }
+
+    fn write_nbt(&self, nbt: &mut Compound) {
+        write_items(nbt, &self.items);
+        nbt.insert("BurnTime", self.burn_time);
+        nbt.insert("CookTime", self.cook_time);
+        nbt.insert("CookTimeTotal", self.cook_time_total);
+        write_text(nbt, "CustomName", self.custom_name.as_ref());
+    }
+}
+
+/// The patterns of a banner. The base color of a banner is part of its block
+/// state.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct Banner {
+    /// The pattern layers from bottom to top.
+    pub patterns: Vec<BannerPatternLayer>,
+    pub custom_name: Option<Text>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BannerPatternLayer {
+    /// The name of the pattern in the banner pattern registry, such as
+    /// `minecraft:stripe_top`.
+    pub pattern: Ident<String>,
+    pub color: DyeColor,
+}
+
+impl BlockEntity for Banner {
+    const KINDS: &'static [BlockEntityKind] = &[BlockEntityKind::Banner];
+
+    fn from_nbt(nbt: &Compound) -> Self {
+        let patterns = match nbt.get("patterns") {
+            Some(Value::List(List::Compound(patterns))) => patterns
+                .iter()
+                .filter_map(|layer| {
+                    Some(BannerPatternLayer {
+                        pattern: get_ident(layer, "pattern")?,
+                        color: get_str(layer, "color").and_then(DyeColor::from_str)?,
+                    })
+                })
+                .collect(),
+            _ => vec![],
+        };
+
+        Self {
+            patterns,
+            custom_name: get_text(nbt, "CustomName"),
+        }
+    }
+
+    fn write_nbt(&self, nbt: &mut Compound) {
+        if self.patterns.is_empty() {
+            nbt.remove("patterns");
+        } else {
+            let patterns = self
+                .patterns
+                .iter()
+                .map(|layer| {
+                    let mut nbt = Compound::new();
+                    nbt.insert("pattern", layer.pattern.as_str());
+                    nbt.insert("color", layer.color.to_str());
+                    nbt
+                })
+                .collect();
+
+            nbt.insert("patterns", List::Compound(patterns));
+        }
+
+        write_text(nbt, "CustomName", self.custom_name.as_ref());
+    }
+}
+
+/// A mob head or player head.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct Skull {
+    /// The profile of the player whose skin is shown on a player head.
+    pub profile: Option<ResolvableProfile>,
+    /// The sound played by a note block below the head.
+    pub note_block_sound: Option<Ident<String>>,
+    pub custom_name: Option<Text>,
+}
+
+impl BlockEntity for Skull {
+    const KINDS: &'static [BlockEntityKind] = &[BlockEntityKind::Skull];
+
+    fn from_nbt(nbt: &Compound) -> Self {
+        let profile = match nbt.get("profile") {
+            Some(Value::String(name)) => Some(ResolvableProfile {
+                name: Some(name.clone()),
+                ..Default::default()
+            }),
+            Some(Value::Compound(profile)) => Some(profile_from_nbt(profile)),
+            _ => None,
+        };
+
+        Self {
+            profile,
+            note_block_sound: get_ident(nbt, "note_block_sound"),
+            custom_name: get_text(nbt, "custom_name"),
+        }
+    }
+
+    fn write_nbt(&self, nbt: &mut Compound) {
+        match &self.profile {
+            Some(profile) => {
+                nbt.insert("profile", profile_to_nbt(profile));
+            }
+            None => {
+                nbt.remove("profile");
+            }
+        }
+
+        write_ident(nbt, "note_block_sound", self.note_block_sound.as_ref());
+        write_text(nbt, "custom_name", self.custom_name.as_ref());
+    }
+}
+
+macro_rules! impl_compound_conversions {
+    ($($ty:ty),*) => {
+        $(
+            impl From<&Compound> for $ty {
+                fn from(nbt: &Compound) -> Self {
+                    <$ty as BlockEntity>::from_nbt(nbt)
+                }
+            }
+
+            impl From<$ty> for Compound {
+                fn from(block_entity: $ty) -> Self {
+                    block_entity.to_nbt()
+                }
+            }
+        )*
+    };
+}
+
+impl_compound_conversions!(Sign, Container, Furnace, Banner, Skull);
+
+fn profile_from_nbt(nbt: &Compound) -> ResolvableProfile {
+    let uuid = match nbt.get("id") {
+        Some(Value::IntArray(id)) => <[i32; 4]>::try_from(id.as_slice()).ok().map(|id| {
+            Uuid::from_u128(
+                id.iter()
+                    .fold(0, |uuid, &part| (uuid << 32) | u128::from(part as u32)),
+            )
+        }),
+        _ => None,
+    };
+
+    let properties = match nbt.get("properties") {
+        Some(Value::List(List::Compound(properties))) => properties
+            .iter()
+            .filter_map(|property| {
+                Some(Property {
+                    name: get_str(property, "name")?.into(),
+                    value: get_str(property, "value")?.into(),
+                    signature: get_str(property, "signature").map(Into::into),
+                })
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    ResolvableProfile {
+        name: get_str(nbt, "name").map(Into::into),
+        uuid,
+        properties,
+    }
+}
+
+fn profile_to_nbt(profile: &ResolvableProfile) -> Compound {
+    let mut nbt = Compound::new();
+
+    if let Some(name) = &profile.name {
+        nbt.insert("name", name.as_str());
+    }
+
+    if let Some(uuid) = profile.uuid {
+        let uuid = uuid.as_u128();
+        let id = (0..4)
+            .rev()
+            .map(|i| (uuid >> (i * 32)) as u32 as i32)
+            .collect::<Vec<_>>();
+
+        nbt.insert("id", id);
+    }
+
+    if !profile.properties.is_empty() {
+        let properties = profile
+            .properties
+            .iter()
+            .map(|property| {
+                let mut nbt = Compound::new();
+                nbt.insert("name", property.name.as_str());
+                nbt.insert("value", property.value.as_str());
+
+                if let Some(signature) = &property.signature {
+                    nbt.insert("signature", signature.as_str());
+                }
+
+                nbt
+            })
+            .collect();
+
+        nbt.insert("properties", List::Compound(properties));
+    }
+
+    nbt
+}
+
+fn items_from_nbt(nbt: &Compound) -> Vec<ContainerItem> {
+    let Some(Value::List(List::Compound(items))) = nbt.get("Items") else {
+        return vec![];
+    };
+
+    let mut items = items
+        .iter()
+        .filter_map(|item| {
+            let Some(Value::Byte(slot)) = item.get("Slot") else {
+                return None;
+            };
+
+            let id = get_ident(item, "id")?;
+
+            let count = match item.get("count") {
+                Some(Value::Int(count)) => i8::try_from(*count).ok()?,
+                Some(Value::Byte(count)) => *count,
+                _ => 1,
+            };
+
+            let components = match item.get("components") {
+                Some(Value::Compound(components)) => components.clone(),
+                _ => Compound::new(),
+            };
+
+            Some(ContainerItem {
+                slot: *slot as u8,
+                item: ItemKind::from_str(id.path())?,
+                count,
+                components,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    items.sort_by_key(|item| item.slot);
+    items
+}
+
+fn write_items(nbt: &mut Compound, items: &[ContainerItem]) {
+    let items = items
+        .iter()
+        .filter(|item| item.item != ItemKind::Air && item.count > 0)
+        .map(|item| {
+            let mut nbt = Compound::new();
+            nbt.insert("Slot", item.slot as i8);
+            nbt.insert("id", format!("minecraft:{}", item.item.to_str()));
+            nbt.insert("count", i32::from(item.count));
+
+            if !item.components.is_empty() {
+                nbt.insert("components", item.components.clone());
+            }
+
+            nbt
+        })
+        .collect::<Vec<_>>();
+
+    if items.is_empty() {
+        nbt.remove("Items");
+    } else {
+        nbt.insert("Items", List::Compound(items));
+    }
+}
+
+fn set_item(items: &mut Vec<ContainerItem>, item: ContainerItem) -> Option<ContainerItem> {
+    match items.binary_search_by_key(&item.slot, |item| item.slot) {
+        Ok(idx) => Some(std::mem::replace(&mut items[idx], item)),
+        Err(idx) => {
+            items.insert(idx, item);
+            None
+        }
+    }
+}
+
+fn remove_item(items: &mut Vec<ContainerItem>, slot: u8) -> Option<ContainerItem> {
+    let idx = items.iter().position(|item| item.slot == slot)?;
+    Some(items.remove(idx))
+}
+
+fn get_str<'a>(nbt: &'a Compound, key: &str) -> Option<&'a str> {
+    match nbt.get(key) {
+        Some(Value::String(string)) => Some(string.as_str()),
+        _ => None,
+    }
+}
+
+fn get_bool(nbt: &Compound, key: &str) -> bool {
+    matches!(nbt.get(key), Some(Value::Byte(b)) if *b != 0)
+}
+
+fn get_short(nbt: &Compound, key: &str) -> i16 {
+    match nbt.get(key) {
+        Some(Value::Short(n)) => *n,
+        Some(Value::Byte(n)) => (*n).into(),
+        Some(Value::Int(n)) => *n as i16,
+        _ => 0,
+    }
+}
+
+fn get_text(nbt: &Compound, key: &str) -> Option<Text> {
+    get_str(nbt, key)?.parse().ok()
+}
+
+fn get_ident(nbt: &Compound, key: &str) -> Option<Ident<String>> {
+    Ident::new(get_str(nbt, key)?).ok().map(Into::into)
+}
+
+/// Returns the compound under `key`, replacing the value with an empty
+/// compound if it is missing or not a compound.
+fn get_compound_mut<'a>(nbt: &'a mut Compound, key: &str) -> &'a mut Compound {
+    if !matches!(nbt.get(key), Some(Value::Compound(_))) {
+        nbt.insert(key, Compound::new());
+    }
+
+    match nbt.get_mut(key) {
+        Some(Value::Compound(compound)) => compound,
+        _ => unreachable!(),
+    }
+}
+
+fn write_text(nbt: &mut Compound, key: &str, text: Option<&Text>) {
+    match text {
+        Some(text) => {
+            nbt.insert(key, text.to_string());
+        }
+        None => {
+            nbt.remove(key);
+        }
+    }
+}
+
+fn write_ident(nbt: &mut Compound, key: &str, ident: Option<&Ident<String>>) {
+    match ident {
+        Some(ident) => {
+            nbt.insert(key, ident.as_str());
+        }
+        None => {
+            nbt.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_nbt::compound;
+    use valence_protocol::text::IntoText;
+
+    use super::*;
+
+    #[test]
+    fn sign_roundtrip() {
+        let mut sign = Sign::default();
+        sign.front_text.messages[1] = "Hello".into_text();
+        sign.back_text.color = DyeColor::LightBlue;
+        sign.back_text.has_glowing_text = true;
+        sign.is_waxed = true;
+
+        assert_eq!(Sign::from_nbt(&sign.to_nbt()), sign);
+    }
+
+    #[test]
+    fn sign_keeps_filtered_messages() {
+        let filtered = List::String(vec![
+            "\"\"".into(),
+            "\"***\"".into(),
+            "\"\"".into(),
+            "\"\"".into(),
+        ]);
+
+        let mut nbt = compound! {
+            "front_text" => compound! {
+                "messages" => List::String(vec!["\"\"".into(); 4]),
+                "filtered_messages" => filtered.clone(),
+            },
+        };
+
+        let mut sign = Sign::from_nbt(&nbt);
+        sign.front_text.messages[0] = "Hello".into_text();
+        sign.write_nbt(&mut nbt);
+
+        let Some(Value::Compound(front_text)) = nbt.get("front_text") else {
+            panic!("front_text should be a compound");
+        };
+
+        assert_eq!(
+            front_text.get("filtered_messages"),
+            Some(&Value::List(filtered))
+        );
+        assert_eq!(Sign::from_nbt(&nbt), sign);
+    }
+
+    #[test]
+    fn unknown_keys_are_kept() {
+        let mut nbt = compound! {
+            "Lock" => "key",
+            "Items" => List::Compound(vec![compound! {
+                "Slot" => 3_i8,
+                "id" => "minecraft:diamond",
+                "count" => 5,
+            }]),
+        };
+
+        let mut container = Container::from_nbt(&nbt);
+
+        assert_eq!(
+            container.item(3),
+            Some(&ContainerItem::new(3, ItemKind::Diamond, 5))
+        );
+
+        container.remove_item(3);
+        container.set_item(ContainerItem::new(0, ItemKind::Stone, 1));
+        container.write_nbt(&mut nbt);
+
+        assert_eq!(nbt.get("Lock"), Some(&Value::String("key".into())));
+        assert_eq!(
+            Container::from_nbt(&nbt).items,
+            vec![ContainerItem::new(0, ItemKind::Stone, 1)]
+        );
+    }
+
+    #[test]
+    fn skull_profile_roundtrip() {
+        let skull = Skull {
+            profile: Some(ResolvableProfile {
+                name: Some("Notch".into()),
+                uuid: Some(Uuid::from_u128(0x069a79f444e94726a5befca90e38aaf5)),
+                properties: vec![Property {
+                    name: "textures".into(),
+                    value: "abc".into(),
+                    signature: None,
+                }],
+            }),
+            note_block_sound: None,
+            custom_name: None,
+        };
+
+        assert_eq!(Skull::from_nbt(&skull.to_nbt()), skull);
+    }
+}
@@ -15,7 +15,7 @@ use tracing::warn;
 use uuid::Uuid;
 use valence_entity::attributes::{EntityAttributes, TrackedEntityAttributes};
 use valence_entity::living::Health;
-use valence_entity::player::{Food, PlayerEntityBundle, Saturation};
+use valence_entity::player::{Experience, Food, PlayerEntityBundle, Saturation};
 use valence_entity::query::EntityInitQuery;
 use valence_entity::tracked_data::TrackedData;
 use valence_entity::{
@@ -23,20 +23,21 @@ use valence_entity::{
 };
 use valence_math::{DVec3, Vec3};
 use valence_protocol::encode::{PacketEncoder, WritePacket};
+use valence_protocol::packets::configuration;
 use valence_protocol::packets::play::chunk_biome_data_s2c::ChunkBiome;
 use valence_protocol::packets::play::game_state_change_s2c::GameEventKind;
 use valence_protocol::packets::play::particle_s2c::Particle;
 use valence_protocol::packets::play::{
     ChunkBiomeDataS2c, ChunkLoadDistanceS2c, ChunkRenderDistanceCenterS2c, DeathMessageS2c,
     DisconnectS2c, EntitiesDestroyS2c, EntityAttributesS2c, EntityStatusS2c,
-    EntityTrackerUpdateS2c, EntityVelocityUpdateS2c, GameStateChangeS2c, HealthUpdateS2c,
-    ParticleS2c, PlaySoundS2c, UnloadChunkS2c,
+    EntityTrackerUpdateS2c, EntityVelocityUpdateS2c, ExperienceBarUpdateS2c, GameStateChangeS2c,
+    HealthUpdateS2c, ParticleS2c, PlaySoundS2c, UnloadChunkS2c,
 };
 use valence_protocol::profile::Property;
 use valence_protocol::sound::{Sound, SoundCategory, SoundId};
 use valence_protocol::text::{IntoText, Text};
 use valence_protocol::var_int::VarInt;
-use valence_protocol::{BlockPos, ChunkPos, Encode, GameMode, Packet};
+use valence_protocol::{BlockPos, ChunkPos, Encode, GameMode, Packet, PacketState};
 use valence_registry::RegistrySet;
 use valence_server_common::{Despawned, UniqueId};
 
@@ -80,6 +81,7 @@ impl Plugin for ClientPlugin {
                     update_old_view_dist.after(update_view_and_layers),
                     update_game_mode,
                     update_food_saturation_health,
+                    update_experience,
                     update_tracked_data,
                     init_tracked_data,
                     update_tracked_attributes,
@@ -120,6 +122,8 @@ pub struct ClientBundle {
     pub respawn_pos: crate::spawn::RespawnPosition,
     pub op_level: crate::op_level::OpLevel,
     pub action_sequence: crate::action::ActionSequence,
+    pub digging_progress: crate::action::DiggingProgress,
+    pub digging_equipment: crate::action::DiggingEquipment,
     pub view_distance: ViewDistance,
     pub old_view_distance: OldViewDistance,
     pub visible_chunk_layer: VisibleChunkLayer,
@@ -129,6 +133,7 @@ pub struct ClientBundle {
     pub keepalive_state: crate::keepalive::KeepaliveState,
     pub ping: crate::keepalive::Ping,
     pub teleport_state: crate::teleport::TeleportState,
+    pub movement_state: crate::movement::MovementState,
     pub game_mode: GameMode,
     pub prev_game_mode: crate::spawn::PrevGameMode,
     pub death_location: crate::spawn::DeathLocation,
@@ -152,6 +157,8 @@ impl ClientBundle {
             client: Client {
                 conn: args.conn,
                 enc: args.enc,
+                state: PacketState::Play,
+                switching_state: false,
             },
             settings: Default::default(),
             entity_remove_buf: Default::default(),
@@ -161,6 +168,8 @@ impl ClientBundle {
             respawn_pos: Default::default(),
             op_level: Default::default(),
             action_sequence: Default::default(),
+            digging_progress: Default::default(),
+            digging_equipment: Default::default(),
             view_distance: Default::default(),
             old_view_distance: OldViewDistance(2),
             visible_chunk_layer: Default::default(),
@@ -170,6 +179,7 @@ impl ClientBundle {
             keepalive_state: crate::keepalive::KeepaliveState::new(),
             ping: Default::default(),
             teleport_state: crate::teleport::TeleportState::new(),
+            movement_state: Default::default(),
             game_mode: GameMode::default(),
             prev_game_mode: Default::default(),
             death_location: Default::default(),
@@ -221,10 +231,15 @@ pub struct ClientMarker;
 pub struct Client {
     conn: Box<dyn ClientConnection>,
     pub(crate) enc: PacketEncoder,
+    /// The state clientbound packets are currently written in.
+    state: PacketState,
+    /// Whether the client was told to switch states and hasn't acknowledged
+    /// it yet. No packets are written in the meantime.
+    switching_state: bool,
 }
 
 /// Represents the bidirectional packet channel between the server and a client
-/// in the "play" state.
+/// in the "play" or "configuration" state.
 pub trait ClientConnection: Send + Sync + 'static {
     /// Sends encoded clientbound packet data. This function must not block and
     /// the data should be sent as soon as possible.
@@ -246,6 +261,8 @@ pub struct ReceivedPacket {
     /// The moment in time this packet arrived. This is _not_ the instant this
     /// packet was returned from [`ClientConnection::try_recv`].
     pub timestamp: Instant,
+    /// The state the client was in when it sent this packet.
+    pub state: PacketState,
     /// This packet's ID.
     pub id: i32,
     /// The content of the packet, excluding the leading varint packet ID.
@@ -260,20 +277,50 @@ impl Drop for Client {
 
 /// Writes packets into this client's packet buffer. The buffer is flushed at
 /// the end of the tick.
+///
+/// Packets that don't belong to the client's current [`PacketState`] are
+/// silently discarded, as are all packets written while the client is
+/// switching states. Raw packet bytes are only written in the play state.
 impl WritePacket for Client {
     fn write_packet_fallible<P>(&mut self, packet: &P) -> anyhow::Result<()>
     where
         P: Packet + Encode,
     {
+        if self.switching_state || P::STATE != self.state {
+            return Ok(());
+        }
+
         self.enc.write_packet_fallible(packet)
     }
 
     fn write_packet_bytes(&mut self, bytes: &[u8]) {
-        self.enc.write_packet_bytes(bytes)
+        if !self.switching_state && self.state == PacketState::Play {
+            self.enc.write_packet_bytes(bytes)
+        }
     }
 }
 
 impl Client {
+    /// The state clientbound packets are currently written in. This is
+    /// [`PacketState::Configuration`] once a client being [reconfigured] has
+    /// acknowledged the switch and [`PacketState::Play`] otherwise.
+    ///
+    /// [reconfigured]: crate::configuration::ReconfigureClient
+    pub fn state(&self) -> PacketState {
+        self.state
+    }
+
+    /// Stops writing packets until [`Self::set_state`] is called with the
+    /// state the client acknowledged switching to.
+    pub(crate) fn begin_state_switch(&mut self) {
+        self.switching_state = true;
+    }
+
+    pub(crate) fn set_state(&mut self, state: PacketState) {
+        self.state = state;
+        self.switching_state = false;
+    }
+
     pub fn connection(&self) -> &dyn ClientConnection {
         self.conn.as_ref()
     }
@@ -392,9 +439,15 @@ impl Command for DisconnectClient {
     fn apply(self, world: &mut World) {
         if let Some(mut entity) = world.get_entity_mut(self.client) {
             if let Some(mut client) = entity.get_mut::<Client>() {
-                client.write_packet(&DisconnectS2c {
-                    reason: self.reason.into(),
-                });
+                if client.state() == PacketState::Configuration {
+                    client.write_packet(&configuration::DisconnectS2c {
+                        reason: self.reason.into(),
+                    });
+                } else {
+                    client.write_packet(&DisconnectS2c {
+                        reason: self.reason.into(),
+                    });
+                }
 
                 // Despawned will be removed at the end of the tick, this way, the packets have
                 // time to be sent.
@@ -1221,6 +1274,16 @@ fn update_food_saturation_health(
     }
 }
 
+fn update_experience(mut clients: Query<(&mut Client, &Experience), Changed<Experience>>) {
+    for (mut client, experience) in &mut clients {
+        client.write_packet(&ExperienceBarUpdateS2c {
+            bar: experience.progress,
+            level: VarInt(experience.level),
+            total_xp: VarInt(experience.total),
+        });
+    }
+}
+
 fn update_old_view_dist(
     mut clients: Query<(&mut OldViewDistance, &ViewDistance), Changed<ViewDistance>>,
 ) {
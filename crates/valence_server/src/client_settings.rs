@@ -1,6 +1,7 @@
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use valence_entity::player::{self, PlayerModelParts};
+use valence_protocol::packets::configuration::ClientInformationC2s;
 use valence_protocol::packets::play::client_settings_c2s::ChatMode;
 use valence_protocol::packets::play::ClientSettingsC2s;
 
@@ -35,7 +36,12 @@ fn handle_client_settings(
     )>,
 ) {
     for packet in packets.read() {
-        if let Some(pkt) = packet.decode::<ClientSettingsC2s>() {
+        // Clients send their settings in both the configuration and play state.
+        let pkt = packet
+            .decode::<ClientSettingsC2s>()
+            .or_else(|| packet.decode::<ClientInformationC2s>().map(Into::into));
+
+        if let Some(pkt) = pkt {
             if let Ok((mut view_dist, mut settings, mut model_parts, mut main_arm)) =
                 clients.get_mut(packet.client)
             {
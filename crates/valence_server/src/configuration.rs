@@ -0,0 +1,373 @@
+//! The configuration state, where clients receive registries, tags, and
+//! feature flags before entering the play state.
+//!
+//! Clients pass through the configuration state once while joining. This is
+//! handled by the networking layer using the packets cached in
+//! [`ConfigurationSettings`]. Clients in the play state can be sent back to
+//! the configuration state at any time with [`ReconfigureClient`].
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::world::Command;
+use valence_entity::attributes::EntityAttributes;
+use valence_entity::query::EntityInitQuery;
+use valence_entity::tracked_data::TrackedData;
+use valence_entity::Position;
+use valence_protocol::encode::{PacketWriter, WritePacket};
+use valence_protocol::packets::configuration::select_known_packs_s2c::KnownPack;
+use valence_protocol::packets::configuration::{
+    FinishConfigurationS2c, SelectKnownPacksC2s, SelectKnownPacksS2c, UpdateEnabledFeaturesS2c,
+};
+use valence_protocol::packets::play::{
+    ChunkRenderDistanceCenterS2c, ConfigurationAcknowledgedC2s, EntityAttributesS2c,
+    EntityTrackerUpdateS2c, StartConfigurationS2c,
+};
+use valence_protocol::{ident, Ident, PacketState, VarInt, MINECRAFT_VERSION};
+use valence_registry::{RegistryCodec, RegistrySet, TagsRegistry};
+use valence_server_common::Server;
+
+use crate::abilities::PlayerAbilitiesFlags;
+use crate::client::{
+    update_view_and_layers, Client, OldView, OldVisibleChunkLayer, OldVisibleEntityLayers,
+    UpdateClientsSet,
+};
+use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
+use crate::layer::{ChunkLayer, EntityLayer};
+use crate::spawn::{game_join_packet, ClientSpawnQueryReadOnly, RespawnPosition};
+use crate::teleport::TeleportState;
+
+pub struct ConfigurationPlugin;
+
+impl Plugin for ConfigurationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConfigurationSettings>()
+            .add_event::<ConfigureClientEvent>()
+            .add_systems(
+                PostUpdate,
+                (
+                    cache_configuration_packets.after(RegistrySet),
+                    finish_configuration
+                        .after(cache_configuration_packets)
+                        .after(crate::spawn::initial_join)
+                        .before(update_view_and_layers)
+                        .in_set(UpdateClientsSet),
+                ),
+            )
+            .add_systems(
+                EventLoopPreUpdate,
+                (handle_configuration_acknowledged, handle_select_known_packs).chain(),
+            );
+    }
+}
+
+/// Settings for the configuration state.
+#[derive(Resource, Debug)]
+pub struct ConfigurationSettings {
+    /// The feature flags enabled on clients.
+    ///
+    /// # Default Value
+    ///
+    /// `minecraft:vanilla`
+    pub enabled_features: BTreeSet<Ident<String>>,
+    /// The data packs the server shares with clients. Clients reply with the
+    /// subset of these packs they also know about.
+    ///
+    /// # Default Value
+    ///
+    /// The `minecraft:core` pack for the current [`MINECRAFT_VERSION`].
+    pub known_packs: Vec<KnownPackInfo>,
+    cached_known_packs: Vec<u8>,
+    cached_packets: Vec<u8>,
+}
+
+/// A data pack in [`ConfigurationSettings::known_packs`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct KnownPackInfo {
+    pub namespace: String,
+    pub id: String,
+    pub version: String,
+}
+
+impl ConfigurationSettings {
+    /// Returns bytes of the cached [`SelectKnownPacksS2c`] packet. This is the
+    /// first packet sent in the configuration state.
+    pub fn known_packs_packet(&self) -> &[u8] {
+        &self.cached_known_packs
+    }
+
+    /// Returns bytes of the cached registry data, enabled features, and tags
+    /// packets. These are sent once the client has replied with its known
+    /// packs.
+    pub fn configuration_packets(&self) -> &[u8] {
+        &self.cached_packets
+    }
+}
+
+impl Default for ConfigurationSettings {
+    fn default() -> Self {
+        Self {
+            enabled_features: BTreeSet::from([ident!("vanilla").into()]),
+            known_packs: vec![KnownPackInfo {
+                namespace: "minecraft".into(),
+                id: "core".into(),
+                version: MINECRAFT_VERSION.into(),
+            }],
+            // Cache will be created later.
+            cached_known_packs: vec![],
+            cached_packets: vec![],
+        }
+    }
+}
+
+/// Sends a client in the play state back to the configuration state. The
+/// client is shown a loading screen while the registries, tags, and feature
+/// flags are sent again. Afterwards, the client rejoins the world it was in.
+///
+/// A [`ConfigureClientEvent`] is sent while the client is configured. Packets
+/// written to the client before it acknowledges the switch are discarded.
+/// Afterwards, packets written before it leaves the configuration state must
+/// belong to [`PacketState::Configuration`]; play packets are discarded.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ReconfigureClient {
+    pub client: Entity,
+}
+
+impl Command for ReconfigureClient {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(self.client) else {
+            return;
+        };
+
+        if entity.contains::<Reconfiguring>() {
+            return;
+        }
+
+        let Some(mut client) = entity.get_mut::<Client>() else {
+            return;
+        };
+
+        client.write_packet(&StartConfigurationS2c);
+        client.begin_state_switch();
+
+        entity.insert(Reconfiguring::AwaitingAck);
+    }
+}
+
+/// Component present on clients being sent through the configuration state
+/// by [`ReconfigureClient`].
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Reconfiguring {
+    /// Waiting for the client to acknowledge the switch to the configuration
+    /// state. Play packets sent by the client until then are still handled as
+    /// usual.
+    AwaitingAck,
+    /// Waiting for the client to reply with its known packs.
+    AwaitingKnownPacks,
+    /// The configuration data was sent. The client returns to the play state
+    /// at the end of the tick.
+    Finishing,
+}
+
+/// Sent when a client being reconfigured has received the configuration data.
+/// Configuration packets such as custom payloads can still be written to the
+/// client until the end of the tick.
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ConfigureClientEvent {
+    pub client: Entity,
+}
+
+fn cache_configuration_packets(
+    server: Res<Server>,
+    codec: Res<RegistryCodec>,
+    tags: Res<TagsRegistry>,
+    settings: ResMut<ConfigurationSettings>,
+) {
+    if settings.is_changed() || codec.is_changed() || tags.is_changed() {
+        let settings = settings.into_inner();
+        let threshold = server.compression_threshold();
+
+        let packs: Vec<_> = settings
+            .known_packs
+            .iter()
+            .map(|pack| KnownPack {
+                namespace: &pack.namespace,
+                id: &pack.id,
+                version: &pack.version,
+            })
+            .collect();
+
+        settings.cached_known_packs.clear();
+        PacketWriter::new(&mut settings.cached_known_packs, threshold).write_packet(
+            &SelectKnownPacksS2c {
+                packs: Cow::Owned(packs),
+            },
+        );
+
+        settings.cached_packets.clear();
+        settings
+            .cached_packets
+            .extend_from_slice(codec.registry_data_packets());
+        PacketWriter::new(&mut settings.cached_packets, threshold).write_packet(
+            &UpdateEnabledFeaturesS2c {
+                features: Cow::Borrowed(&settings.enabled_features),
+            },
+        );
+        settings
+            .cached_packets
+            .extend_from_slice(tags.update_tags_packet());
+    }
+}
+
+fn handle_configuration_acknowledged(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut Client, &mut Reconfiguring)>,
+    settings: Res<ConfigurationSettings>,
+) {
+    for packet in packets.read() {
+        if packet.decode::<ConfigurationAcknowledgedC2s>().is_some() {
+            if let Ok((mut client, mut reconfiguring)) = clients.get_mut(packet.client) {
+                if *reconfiguring == Reconfiguring::AwaitingAck {
+                    client.set_state(PacketState::Configuration);
+                    client.enc.append_bytes(settings.known_packs_packet());
+                    *reconfiguring = Reconfiguring::AwaitingKnownPacks;
+                }
+            }
+        }
+    }
+}
+
+fn handle_select_known_packs(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut Client, &mut Reconfiguring)>,
+    settings: Res<ConfigurationSettings>,
+    mut events: EventWriter<ConfigureClientEvent>,
+) {
+    for packet in packets.read() {
+        if packet.decode::<SelectKnownPacksC2s>().is_some() {
+            if let Ok((mut client, mut reconfiguring)) = clients.get_mut(packet.client) {
+                if *reconfiguring == Reconfiguring::AwaitingKnownPacks {
+                    client.enc.append_bytes(settings.configuration_packets());
+                    *reconfiguring = Reconfiguring::Finishing;
+
+                    events.send(ConfigureClientEvent {
+                        client: packet.client,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Returns reconfigured clients to the play state and sends them everything
+/// they lost while leaving the world.
+///
+/// Chunks and entities are resent for the client's old view, which is what the
+/// viewer counts still refer to. Changes since then are handled by
+/// [`update_view_and_layers`] as usual.
+#[allow(clippy::type_complexity)]
+fn finish_configuration(
+    mut clients: Query<(
+        Entity,
+        &mut Client,
+        &Reconfiguring,
+        &OldVisibleChunkLayer,
+        &OldVisibleEntityLayers,
+        OldView,
+        ClientSpawnQueryReadOnly,
+        &TrackedData,
+        &EntityAttributes,
+        &mut TeleportState,
+        &mut RespawnPosition,
+        &mut PlayerAbilitiesFlags,
+    )>,
+    codec: Res<RegistryCodec>,
+    chunk_layers: Query<&ChunkLayer>,
+    entity_layers: Query<&EntityLayer>,
+    mut entities: ParamSet<(Query<(EntityInitQuery, &Position)>, Query<&mut Position>)>,
+    mut commands: Commands,
+) {
+    for (
+        self_entity,
+        mut client,
+        reconfiguring,
+        old_chunk_layer,
+        old_entity_layers,
+        old_view,
+        spawn,
+        tracked_data,
+        attributes,
+        mut teleport_state,
+        mut respawn_pos,
+        mut abilities,
+    ) in &mut clients
+    {
+        if *reconfiguring != Reconfiguring::Finishing {
+            continue;
+        }
+
+        let Ok(chunk_layer) = chunk_layers.get(old_chunk_layer.get()) else {
+            continue;
+        };
+
+        client.write_packet(&FinishConfigurationS2c);
+        client.set_state(PacketState::Play);
+
+        client.write_packet(&game_join_packet(&codec, chunk_layer, &spawn));
+
+        let view = old_view.get();
+
+        client.write_packet(&ChunkRenderDistanceCenterS2c {
+            chunk_x: VarInt(view.pos.x),
+            chunk_z: VarInt(view.pos.z),
+        });
+
+        for pos in view.iter() {
+            if let Some(chunk) = chunk_layer.chunk(pos) {
+                chunk.write_init_packets(&mut *client, pos, chunk_layer.info());
+            }
+        }
+
+        let entity_init = entities.p0();
+
+        for &layer in old_entity_layers.get() {
+            if let Ok(layer) = entity_layers.get(layer) {
+                for pos in view.iter() {
+                    for entity in layer.entities_at(pos) {
+                        if self_entity != entity {
+                            if let Ok((init, pos)) = entity_init.get(entity) {
+                                init.write_init_packets(pos.get(), &mut *client);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(init_data) = tracked_data.init_data() {
+            client.write_packet(&EntityTrackerUpdateS2c {
+                entity_id: VarInt(0),
+                tracked_values: init_data.into(),
+            });
+        }
+
+        client.write_packet(&EntityAttributesS2c {
+            entity_id: VarInt(0),
+            properties: attributes.to_properties(),
+        });
+
+        // Resync the position, spawn position, and abilities like we do when first
+        // joining.
+        teleport_state.reset_synced();
+        respawn_pos.set_changed();
+        abilities.set_changed();
+
+        if let Ok(mut pos) = entities.p1().get_mut(self_entity) {
+            pos.set_changed();
+        }
+
+        commands.entity(self_entity).remove::<Reconfiguring>();
+    }
+}
@@ -1,7 +1,7 @@
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
-use valence_protocol::packets::play::{CustomPayloadC2s, CustomPayloadS2c};
-use valence_protocol::{Bounded, Ident, WritePacket};
+use valence_protocol::packets::{configuration, play};
+use valence_protocol::{Bounded, Ident, PacketState, WritePacket};
 
 use crate::client::Client;
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
@@ -23,11 +23,20 @@ pub struct CustomPayloadEvent {
 }
 
 impl Client {
+    /// Sends a custom payload to the client. This works in both the play and
+    /// configuration state.
     pub fn send_custom_payload(&mut self, channel: Ident<&str>, data: &[u8]) {
-        self.write_packet(&CustomPayloadS2c {
-            channel: channel.into(),
-            data: Bounded(data.into()),
-        });
+        if self.state() == PacketState::Configuration {
+            self.write_packet(&configuration::CustomPayloadS2c {
+                channel: channel.into(),
+                data: Bounded(data.into()),
+            });
+        } else {
+            self.write_packet(&play::CustomPayloadS2c {
+                channel: channel.into(),
+                data: Bounded(data.into()),
+            });
+        }
     }
 }
 
@@ -36,7 +45,13 @@ fn handle_custom_payload(
     mut events: EventWriter<CustomPayloadEvent>,
 ) {
     for packet in packets.read() {
-        if let Some(pkt) = packet.decode::<CustomPayloadC2s>() {
+        if let Some(pkt) = packet.decode::<play::CustomPayloadC2s>() {
+            events.send(CustomPayloadEvent {
+                client: packet.client,
+                channel: pkt.channel.into(),
+                data: pkt.data.0 .0.into(),
+            });
+        } else if let Some(pkt) = packet.decode::<configuration::CustomPayloadC2s>() {
             events.send(CustomPayloadEvent {
                 client: packet.client,
                 channel: pkt.channel.into(),
@@ -0,0 +1,49 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_protocol::packets::play::DifficultyS2c;
+use valence_protocol::{Difficulty, WritePacket};
+
+use crate::client::{Client, UpdateClientsSet};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldDifficulty>().add_systems(
+            PostUpdate,
+            update_difficulty
+                .after(crate::spawn::initial_join)
+                .in_set(UpdateClientsSet),
+        );
+    }
+}
+
+/// The difficulty of the server. It is shown to clients and used by gameplay
+/// plugins, such as for starvation.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct WorldDifficulty {
+    /// Defaults to [`Difficulty::Normal`].
+    pub difficulty: Difficulty,
+    /// If clients are shown the difficulty as locked.
+    pub locked: bool,
+}
+
+impl Default for WorldDifficulty {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::Normal,
+            locked: false,
+        }
+    }
+}
+
+fn update_difficulty(difficulty: Res<WorldDifficulty>, mut clients: Query<&mut Client>) {
+    for mut client in &mut clients {
+        if difficulty.is_changed() || client.is_added() {
+            client.write_packet(&DifficultyS2c {
+                difficulty: difficulty.difficulty,
+                locked: difficulty.locked,
+            });
+        }
+    }
+}
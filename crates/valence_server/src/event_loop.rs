@@ -7,7 +7,7 @@ use bevy_ecs::schedule::ScheduleLabel;
 use bevy_ecs::system::SystemState;
 use bytes::Bytes;
 use tracing::{debug, warn};
-use valence_protocol::{Decode, Packet};
+use valence_protocol::{Decode, Packet, PacketState};
 
 use crate::client::Client;
 
@@ -50,6 +50,8 @@ pub struct PacketEvent {
     pub client: Entity,
     /// The moment in time this packet arrived.
     pub timestamp: Instant,
+    /// The state the client was in when it sent this packet.
+    pub state: PacketState,
     /// This packet's ID.
     pub id: i32,
     /// The content of the packet, excluding the leading varint packet ID.
@@ -59,14 +61,14 @@ pub struct PacketEvent {
 impl PacketEvent {
     /// Attempts to decode this packet as the packet `P`.
     ///
-    /// If the packet ID is mismatched or an error occurs, `None` is returned.
-    /// Otherwise, `Some` is returned containing the decoded packet.
+    /// If the packet ID or state is mismatched or an error occurs, `None` is
+    /// returned. Otherwise, `Some` is returned containing the decoded packet.
     #[inline]
     pub fn decode<'a, P>(&'a self) -> Option<P>
     where
         P: Packet + Decode<'a>,
     {
-        if self.id == P::ID {
+        if self.id == P::ID && self.state == P::STATE {
             let mut r = &self.data[..];
 
             match P::decode(&mut r) {
@@ -120,6 +122,7 @@ fn run_event_loop(
                 event_writer.send(PacketEvent {
                     client: entity,
                     timestamp: pkt.timestamp,
+                    state: pkt.state,
                     id: pkt.id,
                     data: pkt.body,
                 });
@@ -154,6 +157,7 @@ fn run_event_loop(
                         event_writer.send(PacketEvent {
                             client: *entity,
                             timestamp: pkt.timestamp,
+                            state: pkt.state,
                             id: pkt.id,
                             data: pkt.body,
                         });
@@ -16,6 +16,11 @@ impl Plugin for InteractBlockPlugin {
     }
 }
 
+/// Sent when a client uses a block.
+///
+/// The interaction is not validated, so the position can be out of reach or
+/// in an unloaded chunk. Systems reacting to this event check what they need,
+/// like the reach check of the block placement plugin.
 #[derive(Event, Copy, Clone, Debug)]
 pub struct InteractBlockEvent {
     pub client: Entity,
@@ -44,8 +49,6 @@ fn handle_interact_block(
                 action_seq.update(pkt.sequence.0);
             }
 
-            // TODO: check that the block interaction is valid.
-
             events.send(InteractBlockEvent {
                 client: packet.client,
                 hand: pkt.hand,
@@ -4,8 +4,8 @@ use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use derive_more::Deref;
 use tracing::warn;
-use valence_protocol::packets::play::{KeepAliveC2s, KeepAliveS2c};
-use valence_protocol::WritePacket;
+use valence_protocol::packets::{configuration, play};
+use valence_protocol::{PacketState, WritePacket};
 
 use crate::client::{Client, UpdateClientsSet};
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
@@ -77,7 +77,12 @@ fn send_keepalive(
         if now.duration_since(state.last_send) >= settings.period {
             if state.got_keepalive {
                 let id = rand::random();
-                client.write_packet(&KeepAliveS2c { id });
+
+                if client.state() == PacketState::Configuration {
+                    client.write_packet(&configuration::KeepAliveS2c { id });
+                } else {
+                    client.write_packet(&play::KeepAliveS2c { id });
+                }
 
                 state.got_keepalive = false;
                 state.last_keepalive_id = id;
@@ -97,21 +102,27 @@ fn handle_keepalive_response(
     mut commands: Commands,
 ) {
     for packet in packets.read() {
-        if let Some(pkt) = packet.decode::<KeepAliveC2s>() {
-            if let Ok((entity, mut state, mut ping)) = clients.get_mut(packet.client) {
-                if state.got_keepalive {
-                    warn!("unexpected keepalive from client {entity:?}");
-                    commands.entity(entity).remove::<Client>();
-                } else if pkt.id != state.last_keepalive_id {
-                    warn!(
-                        "keepalive IDs don't match for client {entity:?} (expected {}, got {})",
-                        state.last_keepalive_id, pkt.id,
-                    );
-                    commands.entity(entity).remove::<Client>();
-                } else {
-                    state.got_keepalive = true;
-                    ping.0 = state.last_send.elapsed().as_millis() as i32;
-                }
+        let id = if let Some(pkt) = packet.decode::<play::KeepAliveC2s>() {
+            pkt.id
+        } else if let Some(pkt) = packet.decode::<configuration::KeepAliveC2s>() {
+            pkt.id
+        } else {
+            continue;
+        };
+
+        if let Ok((entity, mut state, mut ping)) = clients.get_mut(packet.client) {
+            if state.got_keepalive {
+                warn!("unexpected keepalive from client {entity:?}");
+                commands.entity(entity).remove::<Client>();
+            } else if id != state.last_keepalive_id {
+                warn!(
+                    "keepalive IDs don't match for client {entity:?} (expected {}, got {id})",
+                    state.last_keepalive_id,
+                );
+                commands.entity(entity).remove::<Client>();
+            } else {
+                state.got_keepalive = true;
+                ping.0 = state.last_send.elapsed().as_millis() as i32;
             }
         }
     }
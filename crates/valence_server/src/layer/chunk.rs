@@ -1,5 +1,6 @@
 #[allow(clippy::module_inception)]
 mod chunk;
+mod light;
 pub mod loaded;
 mod paletted_container;
 pub mod unloaded;
@@ -11,6 +12,7 @@ use std::fmt;
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 pub use chunk::{MAX_HEIGHT, *};
+pub use light::{light_emission, light_opacity};
 pub use loaded::LoadedChunk;
 use rustc_hash::FxHashMap;
 pub use unloaded::UnloadedChunk;
@@ -20,7 +22,10 @@ use valence_protocol::encode::{PacketWriter, WritePacket};
 use valence_protocol::packets::play::particle_s2c::Particle;
 use valence_protocol::packets::play::{ParticleS2c, PlaySoundS2c};
 use valence_protocol::sound::{Sound, SoundCategory, SoundId};
-use valence_protocol::{BiomePos, BlockPos, ChunkPos, CompressionThreshold, Encode, Ident, Packet};
+use valence_protocol::{
+    BiomePos, BlockPos, BlockState, ChunkPos, CompressionThreshold, Direction, Encode, Ident,
+    Packet,
+};
 use valence_registry::biome::{BiomeId, BiomeRegistry};
 use valence_registry::DimensionTypeRegistry;
 use valence_server_common::Server;
@@ -28,6 +33,8 @@ use valence_server_common::Server;
 use super::bvh::GetChunkPos;
 use super::message::Messages;
 use super::{Layer, UpdateLayersPostClientSet, UpdateLayersPreClientSet};
+use crate::block_entity::BlockEntity;
+use crate::raycast::{ray_box_intersection, BlockRaycastHit, RaycastShape};
 
 /// A [`Component`] containing the [chunks](LoadedChunk) and [dimension
 /// information](valence_registry::dimension_type::DimensionTypeId) of a
@@ -46,6 +53,8 @@ pub(crate) struct ChunkLayerInfo {
     min_y: i32,
     biome_registry_len: usize,
     threshold: CompressionThreshold,
+    has_sky_light: bool,
+    lighting_enabled: bool,
 }
 
 impl fmt::Debug for ChunkLayerInfo {
@@ -56,7 +65,8 @@ impl fmt::Debug for ChunkLayerInfo {
             .field("min_y", &self.min_y)
             .field("biome_registry_len", &self.biome_registry_len)
             .field("threshold", &self.threshold)
-            // Ignore sky light mask and array.
+            .field("has_sky_light", &self.has_sky_light)
+            .field("lighting_enabled", &self.lighting_enabled)
             .finish()
     }
 }
@@ -151,6 +161,8 @@ impl ChunkLayer {
                 min_y: dim.min_y,
                 biome_registry_len: biomes.iter().len(),
                 threshold: server.compression_threshold(),
+                has_sky_light: dim.has_skylight,
+                lighting_enabled: false,
             },
         }
     }
@@ -170,6 +182,65 @@ impl ChunkLayer {
         self.info.min_y
     }
 
+    /// Whether light is computed for the chunks in this layer. Disabled by
+    /// default.
+    pub fn lighting_enabled(&self) -> bool {
+        self.info.lighting_enabled
+    }
+
+    /// Enables or disables light computation for the chunks in this layer.
+    ///
+    /// While lighting is disabled, chunks are sent without light data and
+    /// clients compute the light themselves. Lighting a large number of chunks
+    /// can be expensive, so only layers that need server-side light should
+    /// enable it.
+    pub fn set_lighting_enabled(&mut self, enabled: bool) {
+        if self.info.lighting_enabled != enabled {
+            self.info.lighting_enabled = enabled;
+
+            for chunk in self.chunks.values_mut() {
+                chunk.light.invalidate();
+                chunk.clear_init_packet_cache();
+            }
+        }
+    }
+
+    /// Returns the sky light level at the given position, or `None` if the
+    /// position is not in a loaded chunk.
+    pub fn sky_light<P: Into<BlockPos>>(&self, pos: P) -> Option<u8> {
+        let (chunk, x, y, z) = self.chunk_and_offsets(pos.into())?;
+        Some(chunk.sky_light(x, y, z))
+    }
+
+    /// Returns the block light level at the given position, or `None` if the
+    /// position is not in a loaded chunk.
+    pub fn block_light<P: Into<BlockPos>>(&self, pos: P) -> Option<u8> {
+        let (chunk, x, y, z) = self.chunk_and_offsets(pos.into())?;
+        Some(chunk.block_light(x, y, z))
+    }
+
+    fn chunk_and_offsets(&self, pos: BlockPos) -> Option<(&LoadedChunk, u32, u32, u32)> {
+        let (x, y, z) = self.chunk_local_pos(pos)?;
+        let chunk = self.chunk(pos)?;
+
+        Some((chunk, x, y, z))
+    }
+
+    /// Converts a block position to a position within its chunk, or `None` if
+    /// it is outside the height of the layer.
+    fn chunk_local_pos(&self, pos: BlockPos) -> Option<(u32, u32, u32)> {
+        let y = pos
+            .y
+            .checked_sub(self.info.min_y)
+            .and_then(|y| y.try_into().ok())?;
+
+        if y >= self.info.height {
+            return None;
+        }
+
+        Some((pos.x.rem_euclid(16) as u32, y, pos.z.rem_euclid(16) as u32))
+    }
+
     /// Get a reference to the chunk at the given position, if it is loaded.
     pub fn chunk<P: Into<ChunkPos>>(&self, pos: P) -> Option<&LoadedChunk> {
         self.chunks.get(&pos.into())
@@ -311,6 +382,111 @@ impl ChunkLayer {
         Some(chunk.set_block(x, y, z, block))
     }
 
+    /// Casts a ray through the blocks of this layer and returns the first block
+    /// hit, if any.
+    ///
+    /// The ray starts at `origin` and travels in `direction`, which does not
+    /// need to be normalized. Blocks are tested against the shapes selected by
+    /// `shape`. The ray stops after `max_distance` blocks or when it reaches
+    /// an unloaded chunk.
+    pub fn raycast_blocks(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_distance: f64,
+        shape: RaycastShape,
+    ) -> Option<BlockRaycastHit> {
+        let dir = direction.try_normalize()?;
+
+        let mut block_pos = BlockPos::from(origin);
+
+        // Amanatides & Woo voxel traversal. `t_max` is the distance along the ray
+        // to the next block boundary on each axis, and `t_delta` is the distance
+        // between boundaries.
+        let step = [dir.x, dir.y, dir.z].map(|d| if d > 0.0 { 1 } else { -1 });
+        let mut t_max = [0.0; 3];
+        let mut t_delta = [f64::INFINITY; 3];
+
+        for i in 0..3 {
+            if dir[i] != 0.0 {
+                let boundary = if dir[i] > 0.0 {
+                    origin[i].floor() + 1.0
+                } else {
+                    origin[i].floor()
+                };
+
+                t_max[i] = (boundary - origin[i]) / dir[i];
+                t_delta[i] = 1.0 / dir[i].abs();
+            } else {
+                t_max[i] = f64::INFINITY;
+            }
+        }
+
+        loop {
+            let state = match self.chunk_and_offsets(block_pos) {
+                Some((chunk, x, y, z)) => chunk.block_state(x, y, z),
+                None if self.chunk(block_pos).is_some() => {
+                    // Above or below the world. Stop if the ray is moving away from it.
+                    let above = block_pos.y >= self.min_y();
+
+                    if (above && dir.y >= 0.0) || (!above && dir.y <= 0.0) {
+                        return None;
+                    }
+
+                    BlockState::AIR
+                }
+                None => return None,
+            };
+
+            let offset = DVec3::new(block_pos.x.into(), block_pos.y.into(), block_pos.z.into());
+
+            let mut closest: Option<(f64, Direction)> = None;
+
+            for aabb in shape.shapes(state) {
+                if let Some((dist, face)) = ray_box_intersection(aabb + offset, origin, dir) {
+                    if dist <= max_distance && closest.map_or(true, |(d, _)| dist < d) {
+                        closest = Some((dist, face));
+                    }
+                }
+            }
+
+            if let Some((distance, face)) = closest {
+                return Some(BlockRaycastHit {
+                    block_pos,
+                    state,
+                    face,
+                    pos: origin + dir * distance,
+                    distance,
+                });
+            }
+
+            // Step to the next block along the axis with the nearest boundary.
+            let axis = if t_max[0] < t_max[1] {
+                if t_max[0] < t_max[2] {
+                    0
+                } else {
+                    2
+                }
+            } else if t_max[1] < t_max[2] {
+                1
+            } else {
+                2
+            };
+
+            if t_max[axis] > max_distance {
+                return None;
+            }
+
+            match axis {
+                0 => block_pos.x += step[0],
+                1 => block_pos.y += step[1],
+                _ => block_pos.z += step[2],
+            }
+
+            t_max[axis] += t_delta[axis];
+        }
+    }
+
     pub fn block_entity_mut<P: Into<BlockPos>>(&mut self, pos: P) -> Option<&mut Compound> {
         let pos = pos.into();
 
@@ -331,6 +507,39 @@ impl ChunkLayer {
         chunk.block_entity_mut(x, y, z)
     }
 
+    /// Reads the block entity at the given position as a typed
+    /// [`BlockEntity`]. Returns `None` if the chunk is not loaded, there is no
+    /// block entity at the position or its kind is not one of
+    /// [`BlockEntity::KINDS`].
+    pub fn block_entity_as<T, P>(&self, pos: P) -> Option<T>
+    where
+        T: BlockEntity,
+        P: Into<BlockPos>,
+    {
+        let (chunk, x, y, z) = self.chunk_and_offsets(pos.into())?;
+
+        chunk.block_entity_as(x, y, z)
+    }
+
+    /// Modifies the block entity at the given position through a typed
+    /// [`BlockEntity`]. The changes are written back to the block entity and
+    /// sent to clients once `f` returns.
+    ///
+    /// Returns `None` without calling `f` if the chunk is not loaded, there is
+    /// no block entity at the position or its kind is not one of
+    /// [`BlockEntity::KINDS`].
+    pub fn modify_block_entity<T, R, P, F>(&mut self, pos: P, f: F) -> Option<R>
+    where
+        T: BlockEntity,
+        P: Into<BlockPos>,
+        F: FnOnce(&mut T) -> R,
+    {
+        let pos = pos.into();
+        let (x, y, z) = self.chunk_local_pos(pos)?;
+
+        self.chunk_mut(pos)?.modify_block_entity(x, y, z, f)
+    }
+
     pub fn biome<P: Into<BiomePos>>(&self, pos: P) -> Option<BiomeId> {
         let pos = pos.into();
 
@@ -775,12 +984,25 @@ pub(super) fn build(app: &mut App) {
     app.add_systems(
         PostUpdate,
         (
+            update_chunk_layer_light
+                .in_set(UpdateLayersPreClientSet)
+                .before(update_chunk_layers_pre_client),
             update_chunk_layers_pre_client.in_set(UpdateLayersPreClientSet),
             update_chunk_layers_post_client.in_set(UpdateLayersPostClientSet),
         ),
     );
 }
 
+fn update_chunk_layer_light(mut layers: Query<&mut ChunkLayer>) {
+    for layer in &mut layers {
+        // Avoid triggering change detection on layers without lighting.
+        if layer.info.lighting_enabled {
+            let layer = layer.into_inner();
+            light::update_light(&mut layer.chunks, &layer.info);
+        }
+    }
+}
+
 fn update_chunk_layers_pre_client(mut layers: Query<&mut ChunkLayer>) {
     for layer in &mut layers {
         let layer = layer.into_inner();
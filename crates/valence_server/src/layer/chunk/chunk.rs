@@ -3,6 +3,7 @@ use valence_protocol::BlockState;
 use valence_registry::biome::BiomeId;
 
 use super::paletted_container::PalettedContainer;
+use crate::block_entity::BlockEntity;
 
 /// Common operations on chunks. Notable implementors are
 /// [`LoadedChunk`](super::loaded::LoadedChunk) and
@@ -126,6 +127,58 @@ pub trait Chunk {
     #[track_caller]
     fn block_entity_mut(&mut self, x: u32, y: u32, z: u32) -> Option<&mut Compound>;
 
+    /// Reads the block entity at the provided position in this chunk as a
+    /// typed [`BlockEntity`]. `x` and `z` are in the range `0..16` while `y`
+    /// is in the range `0..height`.
+    ///
+    /// Returns `None` if there is no block entity at the position or its kind
+    /// is not one of [`BlockEntity::KINDS`].
+    ///
+    /// # Panics
+    ///
+    /// May panic if the position is out of bounds.
+    #[track_caller]
+    fn block_entity_as<T: BlockEntity>(&self, x: u32, y: u32, z: u32) -> Option<T> {
+        let kind = self.block_state(x, y, z).block_entity_kind()?;
+
+        if !T::KINDS.contains(&kind) {
+            return None;
+        }
+
+        self.block_entity(x, y, z).map(T::from_nbt)
+    }
+
+    /// Modifies the block entity at the provided position in this chunk
+    /// through a typed [`BlockEntity`]. `x` and `z` are in the range `0..16`
+    /// while `y` is in the range `0..height`. The changes are written back to
+    /// the NBT of the block entity once `f` returns.
+    ///
+    /// Returns `None` without calling `f` if there is no block entity at the
+    /// position or its kind is not one of [`BlockEntity::KINDS`].
+    ///
+    /// # Panics
+    ///
+    /// May panic if the position is out of bounds.
+    #[track_caller]
+    fn modify_block_entity<T, R, F>(&mut self, x: u32, y: u32, z: u32, f: F) -> Option<R>
+    where
+        T: BlockEntity,
+        F: FnOnce(&mut T) -> R,
+    {
+        let kind = self.block_state(x, y, z).block_entity_kind()?;
+
+        if !T::KINDS.contains(&kind) {
+            return None;
+        }
+
+        let nbt = self.block_entity_mut(x, y, z)?;
+        let mut block_entity = T::from_nbt(nbt);
+        let res = f(&mut block_entity);
+        block_entity.write_nbt(nbt);
+
+        Some(res)
+    }
+
     /// Sets the block entity at the provided position in this chunk. `x` and
     /// `z` are in the range `0..16` while `y` is in the range `0..height`.
     /// The previous block entity at the position is returned.
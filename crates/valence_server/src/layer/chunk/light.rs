@@ -0,0 +1,731 @@
+//! Sky and block light for chunk layers.
+//!
+//! Light is stored per chunk section as arrays of 4-bit levels. Newly loaded
+//! chunks are lit from scratch, and block changes are relit incrementally by
+//! removing the light that could have come from the changed block and then
+//! spreading light back in from the surrounding blocks.
+
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+use rustc_hash::FxHashMap;
+use valence_generated::block::{BlockKind, PropName, PropValue};
+use valence_math::DVec3;
+use valence_protocol::{BlockState, ChunkPos, FixedArray};
+
+use super::{Chunk, ChunkLayerInfo, LoadedChunk};
+
+/// The number of bytes in the light array of a chunk section.
+const LIGHT_ARRAY_LEN: usize = 2048;
+
+/// The maximum light level.
+const MAX_LIGHT: u8 = 15;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum LightKind {
+    Sky,
+    Block,
+}
+
+/// The light levels of a chunk section. A missing array means every level in
+/// the section is zero.
+#[derive(Clone, Default, Debug)]
+struct LightArray(Option<Box<[u8; LIGHT_ARRAY_LEN]>>);
+
+impl LightArray {
+    fn get(&self, idx: usize) -> u8 {
+        match &self.0 {
+            Some(arr) => (arr[idx / 2] >> (idx % 2 * 4)) & 0xf,
+            None => 0,
+        }
+    }
+
+    /// Sets the light level at `idx`. Returns whether the level changed.
+    fn set(&mut self, idx: usize, level: u8) -> bool {
+        if self.get(idx) == level {
+            return false;
+        }
+
+        let arr = self.0.get_or_insert_with(|| Box::new([0; LIGHT_ARRAY_LEN]));
+        let shift = idx % 2 * 4;
+        arr[idx / 2] = (arr[idx / 2] & !(0xf << shift)) | (level << shift);
+
+        true
+    }
+
+    fn to_packet_array(&self) -> Option<FixedArray<u8, LIGHT_ARRAY_LEN>> {
+        self.0.as_deref().map(|arr| FixedArray(*arr))
+    }
+}
+
+/// The light stored in a [`LoadedChunk`].
+#[derive(Clone, Debug)]
+pub(super) struct ChunkLight {
+    sky: Box<[LightArray]>,
+    block: Box<[LightArray]>,
+    /// Bit 0 is set for sections with changed sky light and bit 1 is set for
+    /// sections with changed block light.
+    changed: Box<[u8]>,
+    /// If the light in the chunk needs to be computed from scratch.
+    needs_init: bool,
+    /// Block indices in the chunk whose light properties have changed.
+    pending: Vec<u32>,
+}
+
+impl ChunkLight {
+    pub(super) fn new(sect_count: usize) -> Self {
+        Self {
+            sky: vec![LightArray::default(); sect_count].into(),
+            block: vec![LightArray::default(); sect_count].into(),
+            changed: vec![0; sect_count].into(),
+            needs_init: true,
+            pending: vec![],
+        }
+    }
+
+    fn arrays(&self, kind: LightKind) -> &[LightArray] {
+        match kind {
+            LightKind::Sky => &self.sky,
+            LightKind::Block => &self.block,
+        }
+    }
+
+    pub(super) fn get(&self, kind: LightKind, x: u32, y: u32, z: u32) -> u8 {
+        self.arrays(kind)[y as usize / 16].get(light_idx(x, y, z))
+    }
+
+    /// Sets the light level at the given position. Returns whether the level
+    /// changed. If `track` is true, the section is marked as changed.
+    pub(super) fn set(
+        &mut self,
+        kind: LightKind,
+        x: u32,
+        y: u32,
+        z: u32,
+        level: u8,
+        track: bool,
+    ) -> bool {
+        let sect_y = y as usize / 16;
+
+        let arrays = match kind {
+            LightKind::Sky => &mut self.sky,
+            LightKind::Block => &mut self.block,
+        };
+
+        if !arrays[sect_y].set(light_idx(x, y, z), level) {
+            return false;
+        }
+
+        if track {
+            self.changed[sect_y] |= match kind {
+                LightKind::Sky => 0b01,
+                LightKind::Block => 0b10,
+            };
+        }
+
+        true
+    }
+
+    /// Sets all light levels to zero. Returns whether any level changed.
+    pub(super) fn reset(&mut self, track: bool) -> bool {
+        let mut any_changed = false;
+
+        for (kind, bit) in [(LightKind::Sky, 0b01), (LightKind::Block, 0b10)] {
+            let arrays = match kind {
+                LightKind::Sky => &mut self.sky,
+                LightKind::Block => &mut self.block,
+            };
+
+            for (sect_y, arr) in arrays.iter_mut().enumerate() {
+                if arr.0.take().is_some() {
+                    any_changed = true;
+
+                    if track {
+                        self.changed[sect_y] |= bit;
+                    }
+                }
+            }
+        }
+
+        any_changed
+    }
+
+    /// Returns whether the light in the chunk has yet to be computed.
+    pub(super) fn needs_init(&self) -> bool {
+        self.needs_init
+    }
+
+    /// Records a block whose light properties have changed.
+    pub(super) fn push_pending(&mut self, idx: u32) {
+        if !self.needs_init {
+            self.pending.push(idx);
+        }
+    }
+
+    /// Forgets the computed light so that it is computed from scratch later.
+    pub(super) fn invalidate(&mut self) {
+        self.needs_init = true;
+        self.pending.clear();
+    }
+
+    pub(super) fn has_changes(&self) -> bool {
+        self.changed.iter().any(|&c| c != 0)
+    }
+
+    pub(super) fn clear_changes(&mut self) {
+        self.changed.fill(0);
+    }
+
+    pub(super) fn shrink_to_fit(&mut self) {
+        self.pending.shrink_to_fit();
+    }
+
+    /// Returns the light data of every section for the chunk data packet.
+    pub(super) fn init_data(&self, has_sky_light: bool) -> LightData {
+        self.data(has_sky_light, true, |_| true)
+    }
+
+    /// Returns the light data of the sections changed this tick for the light
+    /// update packet.
+    pub(super) fn changed_data(&self, has_sky_light: bool) -> LightData {
+        self.data(has_sky_light, false, |(sect_y, kind)| {
+            self.changed[sect_y]
+                & match kind {
+                    LightKind::Sky => 0b01,
+                    LightKind::Block => 0b10,
+                }
+                != 0
+        })
+    }
+
+    /// Builds light masks and arrays. The sections in the packets are offset
+    /// by one since the client also stores light for the section below and
+    /// the section above the world.
+    fn data(
+        &self,
+        has_sky_light: bool,
+        include_edges: bool,
+        include: impl Fn((usize, LightKind)) -> bool,
+    ) -> LightData {
+        let mut data = LightData::new(self.sky.len() + 2);
+
+        for kind in [LightKind::Sky, LightKind::Block] {
+            if kind == LightKind::Sky && !has_sky_light {
+                continue;
+            }
+
+            if include_edges {
+                // Nothing is lit below the world.
+                data.push(kind, 0, None);
+            }
+
+            for (sect_y, arr) in self.arrays(kind).iter().enumerate() {
+                if include((sect_y, kind)) {
+                    data.push(kind, sect_y + 1, arr.to_packet_array());
+                }
+            }
+
+            if include_edges {
+                // The sky is fully lit above the world.
+                let above = match kind {
+                    LightKind::Sky => Some(FixedArray([0xff; LIGHT_ARRAY_LEN])),
+                    LightKind::Block => None,
+                };
+
+                data.push(kind, self.sky.len() + 1, above);
+            }
+        }
+
+        data
+    }
+}
+
+/// Light masks and arrays in the format of the chunk data and light update
+/// packets.
+#[derive(Default, Debug)]
+pub(super) struct LightData {
+    pub(super) sky_light_mask: Vec<u64>,
+    pub(super) block_light_mask: Vec<u64>,
+    pub(super) empty_sky_light_mask: Vec<u64>,
+    pub(super) empty_block_light_mask: Vec<u64>,
+    pub(super) sky_light_arrays: Vec<FixedArray<u8, LIGHT_ARRAY_LEN>>,
+    pub(super) block_light_arrays: Vec<FixedArray<u8, LIGHT_ARRAY_LEN>>,
+}
+
+impl LightData {
+    fn new(sect_count: usize) -> Self {
+        let mask_len = sect_count.div_ceil(64);
+
+        Self {
+            sky_light_mask: vec![0; mask_len],
+            block_light_mask: vec![0; mask_len],
+            empty_sky_light_mask: vec![0; mask_len],
+            empty_block_light_mask: vec![0; mask_len],
+            ..Default::default()
+        }
+    }
+
+    fn push(
+        &mut self,
+        kind: LightKind,
+        sect_idx: usize,
+        arr: Option<FixedArray<u8, LIGHT_ARRAY_LEN>>,
+    ) {
+        let (mask, empty_mask, arrays) = match kind {
+            LightKind::Sky => (
+                &mut self.sky_light_mask,
+                &mut self.empty_sky_light_mask,
+                &mut self.sky_light_arrays,
+            ),
+            LightKind::Block => (
+                &mut self.block_light_mask,
+                &mut self.empty_block_light_mask,
+                &mut self.block_light_arrays,
+            ),
+        };
+
+        let bit = 1 << (sect_idx % 64);
+
+        match arr {
+            Some(arr) => {
+                mask[sect_idx / 64] |= bit;
+                arrays.push(arr);
+            }
+            None => empty_mask[sect_idx / 64] |= bit,
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.sky_light_mask
+            .iter()
+            .chain(&self.block_light_mask)
+            .chain(&self.empty_sky_light_mask)
+            .chain(&self.empty_block_light_mask)
+            .all(|&bits| bits == 0)
+    }
+}
+
+fn light_idx(x: u32, y: u32, z: u32) -> usize {
+    (x + z * 16 + y % 16 * 16 * 16) as usize
+}
+
+/// Returns how much the given block state reduces light passing through it.
+///
+/// Full opaque blocks block light entirely. Liquids, waterlogged blocks,
+/// leaves, ice, and cobwebs reduce light by one level. All other blocks let
+/// light through unchanged.
+pub fn light_opacity(state: BlockState) -> u8 {
+    light_props(state).0
+}
+
+/// Returns the light level emitted by the given block state.
+pub fn light_emission(state: BlockState) -> u8 {
+    light_props(state).1
+}
+
+/// Returns whether replacing one block state with the other can change the
+/// light around it.
+pub(super) fn light_props_differ(a: BlockState, b: BlockState) -> bool {
+    light_props(a) != light_props(b)
+}
+
+fn light_props(state: BlockState) -> (u8, u8) {
+    static PROPS: OnceLock<Box<[(u8, u8)]>> = OnceLock::new();
+
+    let props = PROPS.get_or_init(|| {
+        (0..=BlockState::max_raw())
+            .map(|raw| {
+                let state = BlockState::from_raw(raw).unwrap();
+                (compute_opacity(state), state.luminance())
+            })
+            .collect()
+    });
+
+    props[state.to_raw() as usize]
+}
+
+fn compute_opacity(state: BlockState) -> u8 {
+    let mut shapes = state.collision_shapes();
+
+    let is_full_cube = shapes.len() == 1
+        && shapes
+            .next()
+            .is_some_and(|aabb| aabb.min() == DVec3::ZERO && aabb.max() == DVec3::ONE);
+
+    if state.is_opaque() && is_full_cube {
+        return MAX_LIGHT;
+    }
+
+    let kind = state.to_kind();
+
+    if state.is_liquid()
+        || state.get(PropName::Waterlogged) == Some(PropValue::True)
+        || kind.to_str().ends_with("_leaves")
+        || matches!(
+            kind,
+            BlockKind::Ice | BlockKind::FrostedIce | BlockKind::Cobweb
+        )
+    {
+        1
+    } else {
+        0
+    }
+}
+
+const DIRECTIONS: [(i32, i32, i32); 6] = [
+    (0, -1, 0),
+    (0, 1, 0),
+    (-1, 0, 0),
+    (1, 0, 0),
+    (0, 0, -1),
+    (0, 0, 1),
+];
+
+/// A block position with `y` relative to the bottom of the chunk layer.
+type Pos = (i32, i32, i32);
+
+/// Computes the light of newly loaded chunks and relights changed blocks in
+/// the given chunks.
+pub(super) fn update_light(chunks: &mut FxHashMap<ChunkPos, LoadedChunk>, info: &ChunkLayerInfo) {
+    let mut init = vec![];
+    let mut changed = vec![];
+
+    for (&pos, chunk) in chunks.iter_mut() {
+        if chunk.light.needs_init {
+            chunk.reset_light();
+            init.push(pos);
+        } else {
+            for idx in chunk.light.pending.drain(..) {
+                let x = pos.x * 16 + (idx % 16) as i32;
+                let z = pos.z * 16 + (idx / 16 % 16) as i32;
+                let y = (idx / 16 / 16) as i32;
+
+                changed.push((x, y, z));
+            }
+        }
+    }
+
+    if init.is_empty() && changed.is_empty() {
+        return;
+    }
+
+    let mut engine = LightEngine {
+        chunks,
+        height: info.height as i32,
+        increase: VecDeque::new(),
+        decrease: VecDeque::new(),
+    };
+
+    let mut kinds = vec![LightKind::Block];
+
+    if info.has_sky_light {
+        kinds.push(LightKind::Sky);
+    }
+
+    for kind in kinds {
+        for &pos in &changed {
+            engine.remove_light(kind, pos);
+        }
+
+        engine.propagate_decrease(kind);
+
+        for &pos in &init {
+            engine.init_chunk(kind, pos);
+        }
+
+        engine.propagate_increase(kind);
+    }
+
+    for pos in init {
+        if let Some(chunk) = engine.chunks.get_mut(&pos) {
+            chunk.light.needs_init = false;
+        }
+    }
+}
+
+struct LightEngine<'a> {
+    chunks: &'a mut FxHashMap<ChunkPos, LoadedChunk>,
+    height: i32,
+    /// Positions to spread light from.
+    increase: VecDeque<Pos>,
+    /// Positions that were darkened, along with their previous light level.
+    decrease: VecDeque<(Pos, u8)>,
+}
+
+impl LightEngine<'_> {
+    fn chunk(&self, (x, _, z): Pos) -> Option<&LoadedChunk> {
+        self.chunks
+            .get(&ChunkPos::new(x.div_euclid(16), z.div_euclid(16)))
+    }
+
+    fn light(&self, kind: LightKind, pos @ (x, y, z): Pos) -> u8 {
+        if y >= self.height {
+            return match kind {
+                LightKind::Sky => MAX_LIGHT,
+                LightKind::Block => 0,
+            };
+        }
+
+        if y < 0 {
+            return 0;
+        }
+
+        self.chunk(pos).map_or(0, |chunk| {
+            chunk.light.get(
+                kind,
+                x.rem_euclid(16) as u32,
+                y as u32,
+                z.rem_euclid(16) as u32,
+            )
+        })
+    }
+
+    fn set_light(&mut self, kind: LightKind, (x, y, z): Pos, level: u8) {
+        if !(0..self.height).contains(&y) {
+            return;
+        }
+
+        if let Some(chunk) = self
+            .chunks
+            .get_mut(&ChunkPos::new(x.div_euclid(16), z.div_euclid(16)))
+        {
+            chunk.set_light(
+                kind,
+                x.rem_euclid(16) as u32,
+                y as u32,
+                z.rem_euclid(16) as u32,
+                level,
+            );
+        }
+    }
+
+    /// Returns the opacity and emission of the block at the given position, or
+    /// `None` if the position is not in a loaded chunk.
+    fn props(&self, pos @ (x, y, z): Pos) -> Option<(u8, u8)> {
+        if !(0..self.height).contains(&y) {
+            return None;
+        }
+
+        let state = self.chunk(pos)?.block_state(
+            x.rem_euclid(16) as u32,
+            y as u32,
+            z.rem_euclid(16) as u32,
+        );
+
+        Some(light_props(state))
+    }
+
+    /// Removes the light at a changed block and queues the light the block
+    /// now emits.
+    fn remove_light(&mut self, kind: LightKind, pos: Pos) {
+        let Some((_, emission)) = self.props(pos) else {
+            return;
+        };
+
+        let old = self.light(kind, pos);
+
+        self.set_light(kind, pos, 0);
+        self.decrease.push_back((pos, old));
+
+        if kind == LightKind::Block && emission > 0 {
+            self.set_light(kind, pos, emission);
+            self.increase.push_back(pos);
+        }
+    }
+
+    /// Darkens the blocks that may have been lit by the positions in the
+    /// decrease queue. Blocks with light from elsewhere are queued so their
+    /// light spreads back into the darkened area.
+    fn propagate_decrease(&mut self, kind: LightKind) {
+        while let Some(((x, y, z), level)) = self.decrease.pop_front() {
+            for (dx, dy, dz) in DIRECTIONS {
+                let neighbor = (x + dx, y + dy, z + dz);
+
+                if neighbor.1 >= self.height {
+                    if kind == LightKind::Sky {
+                        self.increase.push_back(neighbor);
+                    }
+                    continue;
+                }
+
+                let Some((_, emission)) = self.props(neighbor) else {
+                    continue;
+                };
+
+                let neighbor_level = self.light(kind, neighbor);
+
+                if neighbor_level == 0 {
+                    continue;
+                }
+
+                let is_direct_sky = kind == LightKind::Sky
+                    && dy == -1
+                    && level == MAX_LIGHT
+                    && neighbor_level == MAX_LIGHT;
+
+                if neighbor_level < level || is_direct_sky {
+                    self.set_light(kind, neighbor, 0);
+                    self.decrease.push_back((neighbor, neighbor_level));
+
+                    if kind == LightKind::Block && emission > 0 {
+                        self.set_light(kind, neighbor, emission);
+                        self.increase.push_back(neighbor);
+                    }
+                } else {
+                    self.increase.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    /// Spreads light from the positions in the increase queue.
+    fn propagate_increase(&mut self, kind: LightKind) {
+        while let Some(pos @ (x, y, z)) = self.increase.pop_front() {
+            let level = self.light(kind, pos);
+
+            if level <= 1 {
+                continue;
+            }
+
+            for (dx, dy, dz) in DIRECTIONS {
+                let neighbor = (x + dx, y + dy, z + dz);
+
+                let Some((opacity, _)) = self.props(neighbor) else {
+                    continue;
+                };
+
+                // Sky light travels straight down without losing strength.
+                let new_level =
+                    if kind == LightKind::Sky && dy == -1 && level == MAX_LIGHT && opacity == 0 {
+                        MAX_LIGHT
+                    } else {
+                        level.saturating_sub(opacity.max(1))
+                    };
+
+                if new_level > self.light(kind, neighbor) {
+                    self.set_light(kind, neighbor, new_level);
+                    self.increase.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    /// Lights a newly loaded chunk and queues the positions to spread light
+    /// from, including light coming from neighboring chunks.
+    #[allow(clippy::needless_range_loop)]
+    fn init_chunk(&mut self, kind: LightKind, chunk_pos: ChunkPos) {
+        let Some(chunk) = self.chunks.get(&chunk_pos) else {
+            return;
+        };
+
+        let base_x = chunk_pos.x * 16;
+        let base_z = chunk_pos.z * 16;
+
+        match kind {
+            LightKind::Block => {
+                let mut emitters = vec![];
+
+                for sect_y in 0..chunk.height() / 16 {
+                    if chunk
+                        .uniform_block_state(sect_y)
+                        .is_some_and(|state| light_emission(state) == 0)
+                    {
+                        continue;
+                    }
+
+                    for y in sect_y * 16..sect_y * 16 + 16 {
+                        for z in 0..16 {
+                            for x in 0..16 {
+                                let emission = light_emission(chunk.block_state(x, y, z));
+
+                                if emission > 0 {
+                                    emitters.push((
+                                        (base_x + x as i32, y as i32, base_z + z as i32),
+                                        emission,
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                }
+
+                for (pos, emission) in emitters {
+                    self.set_light(kind, pos, emission);
+                    self.increase.push_back(pos);
+                }
+            }
+            LightKind::Sky => {
+                // The lowest position in each column lit directly by the sky.
+                let mut bottoms = [[self.height; 16]; 16];
+
+                for (z, row) in bottoms.iter_mut().enumerate() {
+                    for (x, bottom) in row.iter_mut().enumerate() {
+                        while *bottom > 0
+                            && light_opacity(chunk.block_state(
+                                x as u32,
+                                *bottom as u32 - 1,
+                                z as u32,
+                            )) == 0
+                        {
+                            *bottom -= 1;
+                        }
+                    }
+                }
+
+                for z in 0..16 {
+                    for x in 0..16 {
+                        let bottom = bottoms[z][x];
+                        let pos = (base_x + x as i32, bottom, base_z + z as i32);
+
+                        // The bottom of the column spreads light into the block below it.
+                        self.increase.push_back(pos);
+
+                        let on_edge = x == 0 || x == 15 || z == 0 || z == 15;
+
+                        for y in bottom..self.height {
+                            let pos = (base_x + x as i32, y, base_z + z as i32);
+
+                            self.set_light(kind, pos, MAX_LIGHT);
+
+                            // Only spread sideways from blocks next to a column that isn't lit
+                            // directly at this height.
+                            let next_to_shadow = on_edge
+                                || bottoms[z][x - 1] > y
+                                || bottoms[z][x + 1] > y
+                                || bottoms[z - 1][x] > y
+                                || bottoms[z + 1][x] > y;
+
+                            if next_to_shadow {
+                                self.increase.push_back(pos);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Spread light from the edges of neighboring chunks.
+        for (dx, dz) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let neighbor_pos = ChunkPos::new(chunk_pos.x + dx, chunk_pos.z + dz);
+
+            if !self.chunks.contains_key(&neighbor_pos) {
+                continue;
+            }
+
+            for i in 0..16 {
+                let (x, z) = match (dx, dz) {
+                    (-1, _) => (base_x - 1, base_z + i),
+                    (1, _) => (base_x + 16, base_z + i),
+                    (_, -1) => (base_x + i, base_z - 1),
+                    _ => (base_x + i, base_z + 16),
+                };
+
+                for y in 0..self.height {
+                    if self.light(kind, (x, y, z)) > 1 {
+                        self.increase.push_back((x, y, z));
+                    }
+                }
+            }
+        }
+    }
+}
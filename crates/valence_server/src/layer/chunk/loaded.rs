@@ -10,9 +10,9 @@ use valence_protocol::encode::{PacketWriter, WritePacket};
 use valence_protocol::packets::play::chunk_data_s2c::ChunkDataBlockEntity;
 use valence_protocol::packets::play::chunk_delta_update_s2c::ChunkDeltaUpdateEntry;
 use valence_protocol::packets::play::{
-    BlockEntityUpdateS2c, BlockUpdateS2c, ChunkDataS2c, ChunkDeltaUpdateS2c,
+    BlockEntityUpdateS2c, BlockUpdateS2c, ChunkDataS2c, ChunkDeltaUpdateS2c, LightUpdateS2c,
 };
-use valence_protocol::{BlockPos, BlockState, ChunkPos, ChunkSectionPos, Encode};
+use valence_protocol::{BlockPos, BlockState, ChunkPos, ChunkSectionPos, Encode, VarInt};
 use valence_registry::biome::BiomeId;
 use valence_registry::RegistryIdx;
 
@@ -20,6 +20,7 @@ use super::chunk::{
     bit_width, check_biome_oob, check_block_oob, check_section_oob, BiomeContainer,
     BlockStateContainer, Chunk, SECTION_BLOCK_COUNT,
 };
+use super::light::{self, ChunkLight, LightData, LightKind};
 use super::paletted_container::PalettedContainer;
 use super::unloaded::{self, UnloadedChunk};
 use super::{ChunkLayerInfo, ChunkLayerMessages, LocalMsg};
@@ -38,6 +39,11 @@ pub struct LoadedChunk {
     changed_block_entities: BTreeSet<u32>,
     /// If any biomes in this chunk have been modified this tick.
     changed_biomes: bool,
+    /// Sky and block light for the chunk.
+    pub(super) light: ChunkLight,
+    /// If the chunk has been modified since it was last marked clean. Light
+    /// changes are not included.
+    dirty: bool,
     /// Cached bytes of the chunk initialization packet. The cache is considered
     /// invalidated if empty. This should be cleared whenever the chunk is
     /// modified in an observable way, even if the chunk is not viewed.
@@ -90,6 +96,8 @@ impl LoadedChunk {
             block_entities: BTreeMap::new(),
             changed_block_entities: BTreeSet::new(),
             changed_biomes: false,
+            light: ChunkLight::new(height as usize / 16),
+            dirty: false,
             cached_init_packets: Mutex::new(vec![]),
         }
     }
@@ -120,6 +128,8 @@ impl LoadedChunk {
         let old_block_entities = mem::replace(&mut self.block_entities, chunk.block_entities);
         self.changed_block_entities.clear();
         self.changed_biomes = false;
+        self.light = ChunkLight::new(self.sections.len());
+        self.dirty = true;
         self.cached_init_packets.get_mut().clear();
         self.assert_no_changes();
 
@@ -145,6 +155,8 @@ impl LoadedChunk {
         let old_block_entities = mem::take(&mut self.block_entities);
         self.changed_block_entities.clear();
         self.changed_biomes = false;
+        self.light = ChunkLight::new(self.sections.len());
+        self.dirty = false;
         self.cached_init_packets.get_mut().clear();
 
         self.assert_no_changes();
@@ -155,6 +167,35 @@ impl LoadedChunk {
         }
     }
 
+    /// Returns a copy of the blocks, biomes, and block entities in this chunk.
+    pub fn to_unloaded(&self) -> UnloadedChunk {
+        UnloadedChunk {
+            sections: self
+                .sections
+                .iter()
+                .map(|sect| unloaded::Section {
+                    block_states: sect.block_states.clone(),
+                    biomes: sect.biomes.clone(),
+                })
+                .collect(),
+            block_entities: self.block_entities.clone(),
+        }
+    }
+
+    /// Returns whether this chunk has been modified since it was inserted or
+    /// last marked clean with [`Self::set_dirty`]. Changes to light do not make
+    /// a chunk dirty.
+    ///
+    /// This is useful for knowing which chunks need to be saved.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Sets whether this chunk is considered modified. See [`Self::is_dirty`].
+    pub fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
     /// Returns the number of clients in view of this chunk.
     pub fn viewer_count(&self) -> u32 {
         self.viewer_count.load(Ordering::Relaxed)
@@ -177,6 +218,62 @@ impl LoadedChunk {
         debug_assert_ne!(old, 0, "viewer count underflow!");
     }
 
+    /// Returns the sky light level at the given offsets in this chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offsets are outside the bounds of the chunk.
+    #[track_caller]
+    pub fn sky_light(&self, x: u32, y: u32, z: u32) -> u8 {
+        check_block_oob(self, x, y, z);
+
+        self.light.get(LightKind::Sky, x, y, z)
+    }
+
+    /// Returns the block light level at the given offsets in this chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offsets are outside the bounds of the chunk.
+    #[track_caller]
+    pub fn block_light(&self, x: u32, y: u32, z: u32) -> u8 {
+        check_block_oob(self, x, y, z);
+
+        self.light.get(LightKind::Block, x, y, z)
+    }
+
+    /// Sets a light level, recording the change if the chunk is viewed.
+    pub(super) fn set_light(&mut self, kind: LightKind, x: u32, y: u32, z: u32, level: u8) {
+        let track = *self.viewer_count.get_mut() > 0;
+
+        if self.light.set(kind, x, y, z, level, track) {
+            self.cached_init_packets.get_mut().clear();
+        }
+    }
+
+    /// Sets all light levels in this chunk to zero.
+    pub(super) fn reset_light(&mut self) {
+        let track = *self.viewer_count.get_mut() > 0;
+
+        if self.light.reset(track) {
+            self.cached_init_packets.get_mut().clear();
+        }
+    }
+
+    /// Clears the cached chunk initialization packets.
+    pub(super) fn clear_init_packet_cache(&mut self) {
+        self.cached_init_packets.get_mut().clear();
+    }
+
+    /// Returns the block state of every block in the section if they are all
+    /// the same.
+    pub(super) fn uniform_block_state(&self, sect_y: u32) -> Option<BlockState> {
+        match self.sections[sect_y as usize].block_states {
+            PalettedContainer::Single(state) => Some(state),
+            _ => None,
+        }
+    }
+
     /// Performs the changes necessary to prepare this chunk for client updates.
     /// - Chunk change messages are written to the layer.
     /// - Recorded changes are cleared.
@@ -287,6 +384,27 @@ impl LoadedChunk {
             });
         }
 
+        // Light
+        if self.light.has_changes() {
+            let data = self.light.changed_data(info.has_sky_light);
+            self.light.clear_changes();
+
+            messages.send_local_infallible(LocalMsg::PacketAt { pos }, |buf| {
+                let mut writer = PacketWriter::new(buf, info.threshold);
+
+                writer.write_packet(&LightUpdateS2c {
+                    chunk_x: VarInt(pos.x),
+                    chunk_z: VarInt(pos.z),
+                    sky_light_mask: Cow::Borrowed(&data.sky_light_mask),
+                    block_light_mask: Cow::Borrowed(&data.block_light_mask),
+                    empty_sky_light_mask: Cow::Borrowed(&data.empty_sky_light_mask),
+                    empty_block_light_mask: Cow::Borrowed(&data.empty_block_light_mask),
+                    sky_light_arrays: Cow::Borrowed(&data.sky_light_arrays),
+                    block_light_arrays: Cow::Borrowed(&data.block_light_arrays),
+                });
+            });
+        }
+
         // All changes should be cleared.
         self.assert_no_changes();
     }
@@ -438,17 +556,25 @@ impl LoadedChunk {
                 })
                 .collect();
 
+            // Chunks without computed light are sent without any light data so that
+            // clients don't see them as completely dark.
+            let light = if info.lighting_enabled && !self.light.needs_init() {
+                self.light.init_data(info.has_sky_light)
+            } else {
+                LightData::default()
+            };
+
             PacketWriter::new(&mut init_packets, info.threshold).write_packet(&ChunkDataS2c {
                 pos,
                 heightmaps: Cow::Owned(heightmaps),
                 blocks_and_biomes: &blocks_and_biomes,
                 block_entities: Cow::Owned(block_entities),
-                sky_light_mask: Cow::Borrowed(&[]),
-                block_light_mask: Cow::Borrowed(&[]),
-                empty_sky_light_mask: Cow::Borrowed(&[]),
-                empty_block_light_mask: Cow::Borrowed(&[]),
-                sky_light_arrays: Cow::Borrowed(&[]),
-                block_light_arrays: Cow::Borrowed(&[]),
+                sky_light_mask: Cow::Borrowed(&light.sky_light_mask),
+                block_light_mask: Cow::Borrowed(&light.block_light_mask),
+                empty_sky_light_mask: Cow::Borrowed(&light.empty_sky_light_mask),
+                empty_block_light_mask: Cow::Borrowed(&light.empty_block_light_mask),
+                sky_light_arrays: Cow::Borrowed(&light.sky_light_arrays),
+                block_light_arrays: Cow::Borrowed(&light.block_light_arrays),
             })
         }
 
@@ -462,6 +588,7 @@ impl LoadedChunk {
         {
             assert!(!self.changed_biomes);
             assert!(self.changed_block_entities.is_empty());
+            assert!(!self.light.has_changes());
 
             for sect in &self.sections {
                 assert!(sect.updates.is_empty());
@@ -495,6 +622,11 @@ impl Chunk for LoadedChunk {
 
         if block != old_block {
             self.cached_init_packets.get_mut().clear();
+            self.dirty = true;
+
+            if light::light_props_differ(old_block, block) {
+                self.light.push_pending(x + z * 16 + y * 16 * 16);
+            }
 
             if *self.viewer_count.get_mut() > 0 {
                 sect.updates.push(
@@ -518,6 +650,14 @@ impl Chunk for LoadedChunk {
         if let PalettedContainer::Single(b) = &sect.block_states {
             if *b != block {
                 self.cached_init_packets.get_mut().clear();
+                self.dirty = true;
+
+                if light::light_props_differ(*b, block) {
+                    for idx in 0..SECTION_BLOCK_COUNT as u32 {
+                        self.light
+                            .push_pending(sect_y * SECTION_BLOCK_COUNT as u32 + idx);
+                    }
+                }
 
                 if *self.viewer_count.get_mut() > 0 {
                     // The whole section is being modified, so any previous modifications would
@@ -545,10 +685,18 @@ impl Chunk for LoadedChunk {
             for z in 0..16 {
                 for x in 0..16 {
                     for y in 0..16 {
-                        let idx = x + z * 16 + (sect_y * 16 + y) * (16 * 16);
+                        let idx = x + z * 16 + y * 16 * 16;
+
+                        let old_block = sect.block_states.get(idx as usize);
 
-                        if block != sect.block_states.get(idx as usize) {
+                        if block != old_block {
                             self.cached_init_packets.get_mut().clear();
+                            self.dirty = true;
+
+                            if light::light_props_differ(old_block, block) {
+                                self.light
+                                    .push_pending(sect_y * SECTION_BLOCK_COUNT as u32 + idx);
+                            }
 
                             if *self.viewer_count.get_mut() > 0 {
                                 sect.updates.push(
@@ -585,6 +733,7 @@ impl Chunk for LoadedChunk {
                 self.changed_block_entities.insert(idx);
             }
             self.cached_init_packets.get_mut().clear();
+            self.dirty = true;
 
             Some(be)
         } else {
@@ -609,6 +758,7 @@ impl Chunk for LoadedChunk {
                     self.changed_block_entities.insert(idx);
                 }
                 self.cached_init_packets.get_mut().clear();
+                self.dirty = true;
 
                 self.block_entities.insert(idx, nbt)
             }
@@ -617,6 +767,7 @@ impl Chunk for LoadedChunk {
 
                 if res.is_some() {
                     self.cached_init_packets.get_mut().clear();
+                    self.dirty = true;
                 }
 
                 res
@@ -630,6 +781,7 @@ impl Chunk for LoadedChunk {
         }
 
         self.cached_init_packets.get_mut().clear();
+        self.dirty = true;
 
         if *self.viewer_count.get_mut() > 0 {
             self.changed_block_entities
@@ -656,6 +808,7 @@ impl Chunk for LoadedChunk {
 
         if biome != old_biome {
             self.cached_init_packets.get_mut().clear();
+            self.dirty = true;
 
             if *self.viewer_count.get_mut() > 0 {
                 self.changed_biomes = true;
@@ -673,10 +826,12 @@ impl Chunk for LoadedChunk {
         if let PalettedContainer::Single(b) = &sect.biomes {
             if *b != biome {
                 self.cached_init_packets.get_mut().clear();
+                self.dirty = true;
                 self.changed_biomes = *self.viewer_count.get_mut() > 0;
             }
         } else {
             self.cached_init_packets.get_mut().clear();
+            self.dirty = true;
             self.changed_biomes = *self.viewer_count.get_mut() > 0;
         }
 
@@ -691,6 +846,8 @@ impl Chunk for LoadedChunk {
             sect.biomes.shrink_to_fit();
             sect.updates.shrink_to_fit();
         }
+
+        self.light.shrink_to_fit();
     }
 }
 
@@ -717,6 +874,33 @@ mod tests {
         chunk.assert_no_changes();
     }
 
+    #[test]
+    fn loaded_chunk_dirty() {
+        let mut chunk = LoadedChunk::new(512);
+        assert!(!chunk.is_dirty());
+
+        chunk.insert(UnloadedChunk::new());
+        assert!(chunk.is_dirty());
+
+        chunk.set_dirty(false);
+
+        // Setting a block to the same state is not a modification.
+        chunk.set_block_state(0, 0, 0, BlockState::AIR);
+        assert!(!chunk.is_dirty());
+
+        chunk.set_block_state(0, 0, 0, BlockState::STONE);
+        assert!(chunk.is_dirty());
+
+        chunk.set_dirty(false);
+
+        chunk.set_biome(0, 0, 0, BiomeId::from_index(3));
+        assert!(chunk.is_dirty());
+
+        let unloaded = chunk.to_unloaded();
+        assert_eq!(unloaded.block_state(0, 0, 0), BlockState::STONE);
+        assert_eq!(unloaded.biome(0, 0, 0), BiomeId::from_index(3));
+    }
+
     #[test]
     fn loaded_chunk_changes_clear_packet_cache() {
         #[track_caller]
@@ -727,6 +911,8 @@ mod tests {
                 min_y: -16,
                 biome_registry_len: 200,
                 threshold: CompressionThreshold(-1),
+                has_sky_light: true,
+                lighting_enabled: true,
             };
 
             let mut buf = vec![];
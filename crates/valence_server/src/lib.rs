@@ -2,12 +2,15 @@
 
 pub mod abilities;
 pub mod action;
+pub mod block_entity;
 pub mod brand;
 mod chunk_view;
 pub mod client;
 pub mod client_command;
 pub mod client_settings;
+pub mod configuration;
 pub mod custom_payload;
+pub mod difficulty;
 pub mod event_loop;
 pub mod hand_swing;
 pub mod interact_block;
@@ -18,6 +21,8 @@ pub mod layer;
 pub mod message;
 pub mod movement;
 pub mod op_level;
+pub mod physics;
+pub mod raycast;
 pub mod resource_pack;
 pub mod spawn;
 pub mod status;
@@ -30,8 +35,8 @@ pub use event_loop::{EventLoopPostUpdate, EventLoopPreUpdate, EventLoopUpdate};
 pub use layer::{ChunkLayer, EntityLayer, Layer, LayerBundle};
 pub use valence_protocol::{
     block, ident, item, math, text, uuid, BiomePos, BlockPos, BlockState, ChunkPos,
-    CompressionThreshold, Difficulty, Direction, GameMode, Hand, Ident, ItemKind, ItemStack, Text,
-    MINECRAFT_VERSION, PROTOCOL_VERSION,
+    CompressionThreshold, Difficulty, Direction, GameMode, Hand, Ident, ItemComponent, ItemKind,
+    ItemStack, Text, MINECRAFT_VERSION, PROTOCOL_VERSION,
 };
 pub use valence_server_common::*;
 pub use {
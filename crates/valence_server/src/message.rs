@@ -1,22 +1,9 @@
-// TODO: delete this module in favor of valence_chat.
+//! System messages. Player chat is handled by `valence_chat`.
 
-use bevy_app::prelude::*;
-use bevy_ecs::prelude::*;
 use valence_protocol::encode::WritePacket;
-use valence_protocol::packets::play::{ChatMessageC2s, GameMessageS2c};
+use valence_protocol::packets::play::GameMessageS2c;
 use valence_protocol::text::IntoText;
 
-use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
-
-pub struct MessagePlugin;
-
-impl Plugin for MessagePlugin {
-    fn build(&self, app: &mut App) {
-        app.add_event::<ChatMessageEvent>()
-            .add_systems(EventLoopPreUpdate, handle_chat_message);
-    }
-}
-
 pub trait SendMessage {
     /// Sends a system message visible in the chat.
     fn send_chat_message<'a>(&mut self, msg: impl IntoText<'a>);
@@ -39,25 +26,3 @@ impl<T: WritePacket> SendMessage for T {
         });
     }
 }
-
-#[derive(Event, Clone, Debug)]
-pub struct ChatMessageEvent {
-    pub client: Entity,
-    pub message: Box<str>,
-    pub timestamp: u64,
-}
-
-pub fn handle_chat_message(
-    mut packets: EventReader<PacketEvent>,
-    mut events: EventWriter<ChatMessageEvent>,
-) {
-    for packet in packets.read() {
-        if let Some(pkt) = packet.decode::<ChatMessageC2s>() {
-            events.send(ChatMessageEvent {
-                client: packet.client,
-                message: pkt.message.0.into(),
-                timestamp: pkt.timestamp,
-            });
-        }
-    }
-}
@@ -1,13 +1,24 @@
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use valence_entity::active_status_effects::ActiveStatusEffects;
+use valence_entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_entity::entity::Flags;
+use valence_entity::hitbox::HitboxShape;
+use valence_entity::living::LivingFlags;
 use valence_entity::{HeadYaw, Look, OnGround, Position};
-use valence_math::DVec3;
+use valence_math::{Aabb, DVec3};
 use valence_protocol::packets::play::{
     FullC2s, LookAndOnGroundC2s, OnGroundOnlyC2s, PositionAndOnGroundC2s, VehicleMoveC2s,
 };
+use valence_protocol::status_effects::StatusEffect;
+use valence_protocol::{BlockPos, GameMode};
 
+use crate::abilities::{FlyingSpeed, PlayerAbilitiesFlags};
+use crate::client::VisibleChunkLayer;
 use crate::event_loop::{EventLoopPreUpdate, PacketEvent};
 use crate::teleport::TeleportState;
+use crate::ChunkLayer;
 
 pub struct MovementPlugin;
 
@@ -15,13 +26,72 @@ impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<MovementSettings>()
             .add_event::<MovementEvent>()
+            .add_event::<MovementViolationEvent>()
             .add_systems(EventLoopPreUpdate, handle_client_movement);
     }
 }
 
 /// Configuration resource for client movement checks.
-#[derive(Resource, Default)]
-pub struct MovementSettings; // TODO
+///
+/// All checks are disabled by default. When a check fails, the movement is
+/// rejected, the client is teleported back to its previous position, and a
+/// [`MovementViolationEvent`] is sent.
+#[derive(Resource, Clone, Debug)]
+pub struct MovementSettings {
+    /// Whether clients are prevented from moving faster than their movement
+    /// speed allows.
+    pub check_speed: bool,
+    /// Whether clients are prevented from flying when
+    /// [`PlayerAbilitiesFlags::allow_flying`] is not set.
+    pub check_flying: bool,
+    /// Whether clients are prevented from moving into the collision shapes
+    /// of blocks.
+    pub check_clipping: bool,
+    /// The maximum horizontal distance in blocks a client may move in a
+    /// single movement packet when its movement speed attribute has the
+    /// default value of 0.1. The limit is scaled by the actual movement speed
+    /// of the client.
+    ///
+    /// The default is generous enough for sprint jumping on ice.
+    pub max_horizontal_distance: f64,
+    /// Like [`Self::max_horizontal_distance`], but for flying clients with the
+    /// default [`FlyingSpeed`] of 0.05.
+    pub max_flying_distance: f64,
+    /// The maximum height in blocks a client may rise above the last position
+    /// it stood on the ground without the Jump Boost effect.
+    pub max_jump_height: f64,
+}
+
+impl Default for MovementSettings {
+    fn default() -> Self {
+        Self {
+            check_speed: false,
+            check_flying: false,
+            check_clipping: false,
+            max_horizontal_distance: 1.0,
+            max_flying_distance: 1.5,
+            max_jump_height: 1.3,
+        }
+    }
+}
+
+/// Per-client state used to validate movement.
+#[derive(Component, Clone, Debug)]
+pub struct MovementState {
+    /// The last position accepted from the client.
+    last_pos: DVec3,
+    /// The Y coordinate of the last position the client stood on the ground.
+    ground_y: f64,
+}
+
+impl Default for MovementState {
+    fn default() -> Self {
+        Self {
+            last_pos: DVec3::NAN,
+            ground_y: f64::NAN,
+        }
+    }
+}
 
 /// Event sent when a client successfully moves.
 #[derive(Event, Clone, Debug)]
@@ -35,173 +105,406 @@ pub struct MovementEvent {
     pub old_on_ground: bool,
 }
 
+/// Event sent when the movement of a client is rejected by one of the checks
+/// enabled in [`MovementSettings`]. The client has been teleported back to
+/// `old_position`.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct MovementViolationEvent {
+    pub client: Entity,
+    pub violation: MovementViolation,
+    /// The position the client tried to move to.
+    pub position: DVec3,
+    /// The position of the client before the rejected movement.
+    pub old_position: DVec3,
+}
+
+/// The reason a movement was rejected.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MovementViolation {
+    /// The client moved further than its speed allows.
+    Speed {
+        /// The horizontal distance the client tried to move.
+        distance: f64,
+        /// The maximum distance the client was allowed to move.
+        max_distance: f64,
+    },
+    /// The client flew without being allowed to.
+    Flying,
+    /// The client moved into a block.
+    Clipping { block: BlockPos },
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct MovementQuery {
+    pos: &'static mut Position,
+    look: &'static mut Look,
+    head_yaw: &'static mut HeadYaw,
+    on_ground: &'static mut OnGround,
+    teleport_state: &'static mut TeleportState,
+    state: Option<&'static mut MovementState>,
+    game_mode: Option<&'static GameMode>,
+    abilities: Option<&'static PlayerAbilitiesFlags>,
+    flying_speed: Option<&'static FlyingSpeed>,
+    attributes: Option<&'static EntityAttributes>,
+    effects: Option<&'static ActiveStatusEffects>,
+    flags: Option<&'static Flags>,
+    living_flags: Option<&'static LivingFlags>,
+    hitbox: Option<&'static HitboxShape>,
+    chunk_layer: Option<&'static VisibleChunkLayer>,
+}
+
 fn handle_client_movement(
     mut packets: EventReader<PacketEvent>,
-    mut clients: Query<(
-        &mut Position,
-        &mut Look,
-        &mut HeadYaw,
-        &mut OnGround,
-        &mut TeleportState,
-    )>,
+    mut clients: Query<MovementQuery>,
+    layers: Query<&ChunkLayer>,
+    settings: Res<MovementSettings>,
     mut movement_events: EventWriter<MovementEvent>,
+    mut violation_events: EventWriter<MovementViolationEvent>,
 ) {
+    let mut ctx = MovementContext {
+        layers: &layers,
+        settings: &settings,
+        movement_events: &mut movement_events,
+        violation_events: &mut violation_events,
+    };
+
     for packet in packets.read() {
         if let Some(pkt) = packet.decode::<PositionAndOnGroundC2s>() {
-            if let Ok((pos, look, head_yaw, on_ground, teleport_state)) =
-                clients.get_mut(packet.client)
-            {
+            if let Ok(client) = clients.get_mut(packet.client) {
                 let mov = MovementEvent {
                     client: packet.client,
                     position: pkt.position,
-                    old_position: pos.0,
-                    look: *look,
-                    old_look: *look,
+                    old_position: client.pos.0,
+                    look: *client.look,
+                    old_look: *client.look,
                     on_ground: pkt.on_ground,
-                    old_on_ground: on_ground.0,
+                    old_on_ground: client.on_ground.0,
                 };
 
-                handle(
-                    mov,
-                    pos,
-                    look,
-                    head_yaw,
-                    on_ground,
-                    teleport_state,
-                    &mut movement_events,
-                );
+                handle(mov, client, true, &mut ctx);
             }
         } else if let Some(pkt) = packet.decode::<FullC2s>() {
-            if let Ok((pos, look, head_yaw, on_ground, teleport_state)) =
-                clients.get_mut(packet.client)
-            {
+            if let Ok(client) = clients.get_mut(packet.client) {
                 let mov = MovementEvent {
                     client: packet.client,
                     position: pkt.position,
-                    old_position: pos.0,
+                    old_position: client.pos.0,
                     look: Look {
                         yaw: pkt.yaw,
                         pitch: pkt.pitch,
                     },
-                    old_look: *look,
+                    old_look: *client.look,
                     on_ground: pkt.on_ground,
-                    old_on_ground: on_ground.0,
+                    old_on_ground: client.on_ground.0,
                 };
 
-                handle(
-                    mov,
-                    pos,
-                    look,
-                    head_yaw,
-                    on_ground,
-                    teleport_state,
-                    &mut movement_events,
-                );
+                handle(mov, client, true, &mut ctx);
             }
         } else if let Some(pkt) = packet.decode::<LookAndOnGroundC2s>() {
-            if let Ok((pos, look, head_yaw, on_ground, teleport_state)) =
-                clients.get_mut(packet.client)
-            {
+            if let Ok(client) = clients.get_mut(packet.client) {
                 let mov = MovementEvent {
                     client: packet.client,
-                    position: pos.0,
-                    old_position: pos.0,
+                    position: client.pos.0,
+                    old_position: client.pos.0,
                     look: Look {
                         yaw: pkt.yaw,
                         pitch: pkt.pitch,
                     },
-                    old_look: *look,
+                    old_look: *client.look,
                     on_ground: pkt.on_ground,
-                    old_on_ground: on_ground.0,
+                    old_on_ground: client.on_ground.0,
                 };
 
-                handle(
-                    mov,
-                    pos,
-                    look,
-                    head_yaw,
-                    on_ground,
-                    teleport_state,
-                    &mut movement_events,
-                );
+                handle(mov, client, true, &mut ctx);
             }
         } else if let Some(pkt) = packet.decode::<OnGroundOnlyC2s>() {
-            if let Ok((pos, look, head_yaw, on_ground, teleport_state)) =
-                clients.get_mut(packet.client)
-            {
+            if let Ok(client) = clients.get_mut(packet.client) {
                 let mov = MovementEvent {
                     client: packet.client,
-                    position: pos.0,
-                    old_position: pos.0,
-                    look: *look,
-                    old_look: *look,
+                    position: client.pos.0,
+                    old_position: client.pos.0,
+                    look: *client.look,
+                    old_look: *client.look,
                     on_ground: pkt.on_ground,
-                    old_on_ground: on_ground.0,
+                    old_on_ground: client.on_ground.0,
                 };
 
-                handle(
-                    mov,
-                    pos,
-                    look,
-                    head_yaw,
-                    on_ground,
-                    teleport_state,
-                    &mut movement_events,
-                );
+                handle(mov, client, true, &mut ctx);
             }
         } else if let Some(pkt) = packet.decode::<VehicleMoveC2s>() {
-            if let Ok((pos, look, head_yaw, on_ground, teleport_state)) =
-                clients.get_mut(packet.client)
-            {
+            if let Ok(client) = clients.get_mut(packet.client) {
                 let mov = MovementEvent {
                     client: packet.client,
                     position: pkt.position,
-                    old_position: pos.0,
+                    old_position: client.pos.0,
                     look: Look {
                         yaw: pkt.yaw,
                         pitch: pkt.pitch,
                     },
-                    old_look: *look,
-                    on_ground: on_ground.0,
-                    old_on_ground: on_ground.0,
+                    old_look: *client.look,
+                    on_ground: client.on_ground.0,
+                    old_on_ground: client.on_ground.0,
                 };
 
-                handle(
-                    mov,
-                    pos,
-                    look,
-                    head_yaw,
-                    on_ground,
-                    teleport_state,
-                    &mut movement_events,
-                );
+                // Vehicles move by their own rules, so they aren't validated.
+                handle(mov, client, false, &mut ctx);
             }
         }
     }
 }
 
+struct MovementContext<'a, 'w, 's> {
+    layers: &'a Query<'w, 's, &'static ChunkLayer>,
+    settings: &'a MovementSettings,
+    movement_events: &'a mut EventWriter<'w, MovementEvent>,
+    violation_events: &'a mut EventWriter<'w, MovementViolationEvent>,
+}
+
 fn handle(
     mov: MovementEvent,
-    mut pos: Mut<Position>,
-    mut look: Mut<Look>,
-    mut head_yaw: Mut<HeadYaw>,
-    mut on_ground: Mut<OnGround>,
-    mut teleport_state: Mut<TeleportState>,
-    movement_events: &mut EventWriter<MovementEvent>,
+    mut client: MovementQueryItem,
+    validate: bool,
+    ctx: &mut MovementContext,
 ) {
-    if teleport_state.pending_teleports() != 0 {
+    if client.teleport_state.pending_teleports() != 0 {
         return;
     }
 
-    // TODO: check that the client isn't moving too fast / flying.
-    // TODO: check that the client isn't clipping through blocks.
+    if let Some(state) = &mut client.state {
+        // The server moved the client since the last accepted movement, so the
+        // ground position is no longer meaningful.
+        if state.last_pos != client.pos.0 {
+            state.last_pos = client.pos.0;
+            state.ground_y = client.pos.0.y;
+        }
+    }
+
+    if validate {
+        if let Some(violation) = check_movement(&mov, &client, ctx) {
+            // Teleport the client back to where it was.
+            client.teleport_state.synced_pos = DVec3::NAN;
+            client.pos.set_changed();
+
+            ctx.violation_events.send(MovementViolationEvent {
+                client: mov.client,
+                violation,
+                position: mov.position,
+                old_position: mov.old_position,
+            });
+
+            return;
+        }
+    }
+
+    if let Some(state) = &mut client.state {
+        state.last_pos = mov.position;
+
+        if mov.on_ground && is_supported(&mov, &client, ctx) {
+            state.ground_y = mov.position.y;
+        }
+    }
+
+    client.pos.set_if_neq(Position(mov.position));
+    client.teleport_state.synced_pos = mov.position;
+    client.look.set_if_neq(mov.look);
+    client.teleport_state.synced_look = mov.look;
+    client.head_yaw.set_if_neq(HeadYaw(mov.look.yaw));
+    client.on_ground.set_if_neq(OnGround(mov.on_ground));
+
+    ctx.movement_events.send(mov);
+}
+
+/// Runs the checks enabled in the settings and returns the first violation.
+fn check_movement(
+    mov: &MovementEvent,
+    client: &MovementQueryItem,
+    ctx: &MovementContext,
+) -> Option<MovementViolation> {
+    let settings = ctx.settings;
+
+    let (allow_flying, wants_flying) = client
+        .abilities
+        .map_or((false, false), |a| (a.allow_flying(), a.flying()));
+    let flying = wants_flying && allow_flying;
+
+    // Gliding with an elytra and riptide tridents can move players much faster
+    // and higher than normal, so they are exempt from the speed and flying
+    // checks.
+    let gliding = client.flags.is_some_and(|f| f.fall_flying())
+        || client.living_flags.is_some_and(|f| f.using_riptide());
+
+    if settings.check_flying && wants_flying && !allow_flying {
+        return Some(MovementViolation::Flying);
+    }
+
+    if settings.check_speed && !gliding {
+        let delta = mov.position - mov.old_position;
+        let distance = delta.x.hypot(delta.z);
 
-    pos.set_if_neq(Position(mov.position));
-    teleport_state.synced_pos = mov.position;
-    look.set_if_neq(mov.look);
-    teleport_state.synced_look = mov.look;
-    head_yaw.set_if_neq(HeadYaw(mov.look.yaw));
-    on_ground.set_if_neq(OnGround(mov.on_ground));
+        let max_distance = if flying {
+            let flying_speed = client.flying_speed.map_or(0.05, |s| f64::from(s.0));
+            settings.max_flying_distance * flying_speed / 0.05
+        } else {
+            settings.max_horizontal_distance * movement_speed(client) / 0.1
+        };
+
+        if distance > max_distance {
+            return Some(MovementViolation::Speed {
+                distance,
+                max_distance,
+            });
+        }
+    }
+
+    if settings.check_flying && !flying && !gliding {
+        let levitating = client
+            .effects
+            .is_some_and(|e| e.has_effect(StatusEffect::Levitation));
+
+        if let Some(state) = &client.state {
+            let jump_boost = client
+                .effects
+                .and_then(|e| e.get_current_effect(StatusEffect::JumpBoost))
+                .map_or(0.0, |e| f64::from(e.amplifier()) + 1.0);
+
+            // Each level of Jump Boost adds 0.1 blocks per tick to the initial
+            // jump velocity, which is a little over half a block of height.
+            let max_height = settings.max_jump_height + jump_boost * 0.6;
+
+            if !levitating
+                && mov.position.y > mov.old_position.y
+                && mov.position.y - state.ground_y > max_height
+            {
+                return Some(MovementViolation::Flying);
+            }
+        }
+    }
+
+    if settings.check_clipping && client.game_mode != Some(&GameMode::Spectator) {
+        if let Some(layer) = client
+            .chunk_layer
+            .and_then(|layer| ctx.layers.get(layer.0).ok())
+        {
+            let shape = hitbox_shape(client);
+
+            // Only blocks the client wasn't already inside of count, so that players
+            // can always move out of blocks they got stuck in.
+            let old_bounds = shrink(shape + mov.old_position, 1e-5);
+            let new_bounds = shrink(shape + mov.position, 1e-5);
+
+            if let Some(block) =
+                find_collision(layer, new_bounds, |aabb| !aabb.intersects(old_bounds))
+            {
+                return Some(MovementViolation::Clipping { block });
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns whether there is a block below the new position of the client to
+/// support its claim of being on the ground. Clients outside of a chunk
+/// layer are always supported.
+fn is_supported(mov: &MovementEvent, client: &MovementQueryItem, ctx: &MovementContext) -> bool {
+    if !ctx.settings.check_flying {
+        return true;
+    }
+
+    let Some(layer) = client
+        .chunk_layer
+        .and_then(|layer| ctx.layers.get(layer.0).ok())
+    else {
+        return true;
+    };
+
+    let bounds = hitbox_shape(client) + mov.position;
+
+    // A thin box just below the feet of the client.
+    let below = Aabb::new(
+        DVec3::new(bounds.min().x, bounds.min().y - 0.5, bounds.min().z),
+        DVec3::new(bounds.max().x, bounds.min().y + 1e-5, bounds.max().z),
+    );
+
+    find_collision(layer, below, |_| true).is_some()
+}
+
+/// Returns the movement speed of the client, including the effect of Speed.
+///
+/// The Speed modifier is only added here if it isn't already part of the
+/// movement speed attribute, which is the case when another plugin applies
+/// the attribute modifiers of status effects.
+fn movement_speed(client: &MovementQueryItem) -> f64 {
+    let attribute = client
+        .attributes
+        .and_then(|a| a.get(EntityAttribute::GenericMovementSpeed));
+
+    let speed = attribute.map_or(0.1, |a| a.compute_value());
+
+    let has_speed_modifier = attribute.is_some_and(|a| {
+        StatusEffect::Speed
+            .attribute_modifiers()
+            .iter()
+            .any(|m| a.has_modifier(m.uuid))
+    });
+
+    if has_speed_modifier {
+        return speed;
+    }
+
+    let speed_level = client
+        .effects
+        .and_then(|e| e.get_current_effect(StatusEffect::Speed))
+        .map_or(0.0, |e| f64::from(e.amplifier()) + 1.0);
+
+    speed * (1.0 + 0.2 * speed_level)
+}
+
+fn hitbox_shape(client: &MovementQueryItem) -> Aabb {
+    client.hitbox.map_or_else(
+        || Aabb::from_bottom_size(DVec3::ZERO, DVec3::new(0.6, 1.8, 0.6)),
+        |h| h.get(),
+    )
+}
+
+fn shrink(aabb: Aabb, amount: f64) -> Aabb {
+    Aabb::new(aabb.min() + amount, aabb.max() - amount)
+}
+
+/// Returns the position of a block whose collision shapes intersect `bounds`
+/// and pass `filter`.
+fn find_collision<F>(layer: &ChunkLayer, bounds: Aabb, mut filter: F) -> Option<BlockPos>
+where
+    F: FnMut(Aabb) -> bool,
+{
+    let min = BlockPos::from(bounds.min());
+    let max = BlockPos::from(bounds.max());
+
+    // Start one block lower to include blocks taller than a full block, such as
+    // fences and walls.
+    for y in min.y - 1..=max.y {
+        for z in min.z..=max.z {
+            for x in min.x..=max.x {
+                let pos = BlockPos::new(x, y, z);
+
+                let Some(block) = layer.block(pos) else {
+                    continue;
+                };
+
+                let offset = DVec3::new(x.into(), y.into(), z.into());
+
+                if block
+                    .state
+                    .collision_shapes()
+                    .map(|shape| shape + offset)
+                    .any(|shape| shape.intersects(bounds) && filter(shape))
+                {
+                    return Some(pos);
+                }
+            }
+        }
+    }
 
-    movement_events.send(mov);
+    None
 }
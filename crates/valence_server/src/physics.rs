@@ -0,0 +1,400 @@
+//! Simple vanilla-like physics for non-player entities.
+//!
+//! [`PhysicsPlugin`] is not part of the default plugins. Once added, every
+//! non-player entity with a [`Velocity`] is moved by its velocity each tick,
+//! pulled down by gravity, slowed by drag, and stopped by the collision shapes
+//! of the blocks in its chunk layer. The chunk layer of an entity is the
+//! [`ChunkLayer`] on the same Bevy entity as its [`EntityLayerId`].
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use valence_entity::entity::NoGravity;
+use valence_entity::hitbox::HitboxShape;
+use valence_entity::{EntityKind, EntityLayerId, InitEntitiesSet, OnGround, Position, Velocity};
+use valence_generated::block::{BlockKind, BlockState};
+use valence_math::{Aabb, DVec3};
+use valence_protocol::{BlockPos, ChunkPos};
+use valence_server_common::Despawned;
+
+use crate::layer::chunk::Chunk;
+use crate::ChunkLayer;
+
+/// The number of game ticks in a second. [`Velocity`] is measured in meters
+/// per second, while the constants in [`PhysicsProperties`] are per tick.
+const TICKS_PER_SECOND: f64 = 20.0;
+
+/// Velocity components smaller than this (in blocks per tick) are set to zero
+/// so that resting entities stop sending velocity updates.
+const MIN_VELOCITY: f64 = 0.003;
+
+/// Tolerance used when comparing the faces of bounding boxes.
+const EPSILON: f64 = 1e-7;
+
+pub struct PhysicsPlugin;
+
+/// When entity physics is simulated. Systems that change the [`Velocity`] of
+/// entities for the current tick should run _before_ this.
+///
+/// This set lives in [`PostUpdate`].
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct PhysicsSet;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(PostUpdate, PhysicsSet.before(InitEntitiesSet))
+            .add_systems(PostUpdate, step_entities.in_set(PhysicsSet));
+    }
+}
+
+/// The constants used to move an entity each tick.
+///
+/// Entities get the properties of their [`EntityKind`] from
+/// [`PhysicsProperties::of`] unless this component is present, in which case
+/// it is used instead.
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct PhysicsProperties {
+    /// Downward acceleration in blocks per tick squared.
+    pub gravity: f64,
+    /// Multiplier applied to the horizontal velocity every tick. This is
+    /// further multiplied by the slipperiness of the block below when the
+    /// entity is on the ground.
+    pub drag: f64,
+    /// Multiplier applied to the vertical velocity every tick.
+    pub vertical_drag: f64,
+    /// Whether drag is applied before gravity. Projectiles do this, while
+    /// most other entities apply gravity first.
+    pub drag_before_gravity: bool,
+}
+
+impl PhysicsProperties {
+    const LIVING: Self = Self {
+        gravity: 0.08,
+        drag: 0.91,
+        vertical_drag: 0.98,
+        drag_before_gravity: false,
+    };
+
+    const FLYING: Self = Self {
+        gravity: 0.0,
+        drag: 0.91,
+        vertical_drag: 0.91,
+        drag_before_gravity: false,
+    };
+
+    const FALLING: Self = Self {
+        gravity: 0.04,
+        drag: 0.98,
+        vertical_drag: 0.98,
+        drag_before_gravity: false,
+    };
+
+    /// Returns the vanilla physics properties of an entity kind, or `None` if
+    /// entities of the kind are not moved by the physics simulation.
+    ///
+    /// Players are never simulated since their clients are in control of
+    /// their movement.
+    pub const fn of(kind: EntityKind) -> Option<Self> {
+        const fn projectile(gravity: f64, drag: f64) -> PhysicsProperties {
+            PhysicsProperties {
+                gravity,
+                drag,
+                vertical_drag: drag,
+                drag_before_gravity: true,
+            }
+        }
+
+        Some(match kind {
+            EntityKind::PLAYER
+            | EntityKind::AREA_EFFECT_CLOUD
+            | EntityKind::BLOCK_DISPLAY
+            | EntityKind::ITEM_DISPLAY
+            | EntityKind::TEXT_DISPLAY
+            | EntityKind::INTERACTION
+            | EntityKind::MARKER
+            | EntityKind::LIGHTNING
+            | EntityKind::PAINTING
+            | EntityKind::ITEM_FRAME
+            | EntityKind::GLOW_ITEM_FRAME
+            | EntityKind::LEASH_KNOT
+            | EntityKind::END_CRYSTAL
+            | EntityKind::EVOKER_FANGS
+            | EntityKind::OMINOUS_ITEM_SPAWNER
+            | EntityKind::SHULKER => return None,
+            EntityKind::ITEM | EntityKind::TNT | EntityKind::FALLING_BLOCK => Self::FALLING,
+            EntityKind::EXPERIENCE_ORB => Self {
+                gravity: 0.03,
+                ..Self::FALLING
+            },
+            EntityKind::MINECART
+            | EntityKind::CHEST_MINECART
+            | EntityKind::COMMAND_BLOCK_MINECART
+            | EntityKind::FURNACE_MINECART
+            | EntityKind::HOPPER_MINECART
+            | EntityKind::SPAWNER_MINECART
+            | EntityKind::TNT_MINECART => Self {
+                gravity: 0.04,
+                drag: 0.95,
+                vertical_drag: 0.95,
+                drag_before_gravity: false,
+            },
+            EntityKind::BOAT | EntityKind::CHEST_BOAT => Self {
+                gravity: 0.04,
+                drag: 0.9,
+                vertical_drag: 0.98,
+                drag_before_gravity: false,
+            },
+            EntityKind::ARROW | EntityKind::SPECTRAL_ARROW | EntityKind::TRIDENT => {
+                projectile(0.05, 0.99)
+            }
+            EntityKind::SNOWBALL | EntityKind::EGG | EntityKind::ENDER_PEARL => {
+                projectile(0.03, 0.99)
+            }
+            EntityKind::POTION => projectile(0.05, 0.99),
+            EntityKind::EXPERIENCE_BOTTLE => projectile(0.07, 0.99),
+            EntityKind::LLAMA_SPIT => projectile(0.06, 0.99),
+            EntityKind::FISHING_BOBBER => projectile(0.03, 0.92),
+            EntityKind::FIREBALL
+            | EntityKind::SMALL_FIREBALL
+            | EntityKind::DRAGON_FIREBALL
+            | EntityKind::WITHER_SKULL => projectile(0.0, 0.95),
+            EntityKind::WIND_CHARGE
+            | EntityKind::BREEZE_WIND_CHARGE
+            | EntityKind::SHULKER_BULLET
+            | EntityKind::FIREWORK_ROCKET
+            | EntityKind::EYE_OF_ENDER => projectile(0.0, 1.0),
+            EntityKind::ALLAY
+            | EntityKind::BAT
+            | EntityKind::BEE
+            | EntityKind::ENDER_DRAGON
+            | EntityKind::GHAST
+            | EntityKind::PHANTOM
+            | EntityKind::VEX
+            | EntityKind::WITHER => Self::FLYING,
+            _ => Self::LIVING,
+        })
+    }
+}
+
+/// Returns how slippery a block is when entities slide across its top.
+pub fn block_slipperiness(state: BlockState) -> f64 {
+    match state.to_kind() {
+        BlockKind::Ice | BlockKind::PackedIce | BlockKind::FrostedIce => 0.98,
+        BlockKind::BlueIce => 0.989,
+        BlockKind::SlimeBlock => 0.8,
+        _ => 0.6,
+    }
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct PhysicsQuery {
+    kind: &'static EntityKind,
+    layer: &'static EntityLayerId,
+    pos: &'static mut Position,
+    vel: &'static mut Velocity,
+    on_ground: &'static mut OnGround,
+    hitbox: &'static HitboxShape,
+    props: Option<&'static PhysicsProperties>,
+    no_gravity: Option<&'static NoGravity>,
+}
+
+fn step_entities(
+    mut entities: Query<PhysicsQuery, Without<Despawned>>,
+    layers: Query<&ChunkLayer>,
+    mut boxes: Local<Vec<Aabb>>,
+) {
+    for mut entity in &mut entities {
+        let Some(props) = entity
+            .props
+            .copied()
+            .or_else(|| PhysicsProperties::of(*entity.kind))
+        else {
+            continue;
+        };
+
+        let Ok(layer) = layers.get(entity.layer.0) else {
+            continue;
+        };
+
+        // Entities in unloaded chunks are frozen in place.
+        if layer.chunk(ChunkPos::from(entity.pos.0)).is_none() {
+            continue;
+        }
+
+        let gravity = if entity.no_gravity.is_some_and(|g| g.0) {
+            0.0
+        } else {
+            props.gravity
+        };
+
+        let mut vel = entity.vel.0.as_dvec3() / TICKS_PER_SECOND;
+        let bounds = entity.hitbox.get() + entity.pos.0;
+
+        collect_block_boxes(layer, bounds, vel, &mut boxes);
+        let moved = collide(bounds, vel, &boxes);
+
+        let on_ground = moved.y != vel.y && vel.y < 0.0;
+
+        if moved.x != vel.x {
+            vel.x = 0.0;
+        }
+
+        if moved.y != vel.y {
+            vel.y = 0.0;
+        }
+
+        if moved.z != vel.z {
+            vel.z = 0.0;
+        }
+
+        let new_pos = entity.pos.0 + moved;
+
+        let drag = if on_ground {
+            let below = BlockPos::new(
+                new_pos.x.floor() as i32,
+                (new_pos.y - 0.5).floor() as i32,
+                new_pos.z.floor() as i32,
+            );
+
+            props.drag * block_state_at(layer, below).map_or(0.6, block_slipperiness)
+        } else {
+            props.drag
+        };
+
+        let apply_drag = |vel: &mut DVec3| {
+            vel.x *= drag;
+            vel.y *= props.vertical_drag;
+            vel.z *= drag;
+        };
+
+        if props.drag_before_gravity {
+            apply_drag(&mut vel);
+            vel.y -= gravity;
+        } else {
+            vel.y -= gravity;
+            apply_drag(&mut vel);
+        }
+
+        for v in vel.as_mut() {
+            if v.abs() < MIN_VELOCITY {
+                *v = 0.0;
+            }
+        }
+
+        if entity.pos.0 != new_pos {
+            entity.pos.0 = new_pos;
+        }
+
+        let new_vel = (vel * TICKS_PER_SECOND).as_vec3();
+
+        if entity.vel.0 != new_vel {
+            entity.vel.0 = new_vel;
+        }
+
+        entity.on_ground.set_if_neq(OnGround(on_ground));
+    }
+}
+
+/// Returns the block state at a position, or `None` if the chunk containing it
+/// is not loaded. Positions above or below the world are air.
+fn block_state_at(layer: &ChunkLayer, pos: BlockPos) -> Option<BlockState> {
+    let chunk = layer.chunk(ChunkPos::from(pos))?;
+
+    let Some(y) = pos
+        .y
+        .checked_sub(layer.min_y())
+        .and_then(|y| u32::try_from(y).ok())
+        .filter(|&y| y < layer.height())
+    else {
+        return Some(BlockState::AIR);
+    };
+
+    Some(chunk.block_state(pos.x.rem_euclid(16) as u32, y, pos.z.rem_euclid(16) as u32))
+}
+
+/// Collects the collision boxes of all blocks that an entity with the given
+/// bounds could hit while moving by `motion`.
+fn collect_block_boxes(layer: &ChunkLayer, bounds: Aabb, motion: DVec3, boxes: &mut Vec<Aabb>) {
+    boxes.clear();
+
+    let swept = bounds.union(bounds + motion);
+
+    let min = (swept.min() - EPSILON).floor();
+    let max = (swept.max() + EPSILON).floor();
+
+    for y in min.y as i32 - 1..=max.y as i32 {
+        for z in min.z as i32..=max.z as i32 {
+            for x in min.x as i32..=max.x as i32 {
+                let offset = DVec3::new(x.into(), y.into(), z.into());
+
+                match block_state_at(layer, BlockPos::new(x, y, z)) {
+                    Some(state) => {
+                        boxes.extend(state.collision_shapes().map(|shape| shape + offset));
+                    }
+                    // Unloaded chunks are solid so that entities don't leave the
+                    // loaded part of the world.
+                    None => boxes.push(Aabb::new_unchecked(offset, offset + 1.0)),
+                }
+            }
+        }
+    }
+}
+
+/// Moves `bounds` by `motion` as far as possible without entering any of the
+/// `boxes`, and returns the resulting motion. The vertical axis is resolved
+/// first, followed by the horizontal axis with the larger movement.
+fn collide(mut bounds: Aabb, motion: DVec3, boxes: &[Aabb]) -> DVec3 {
+    let mut result = DVec3::ZERO;
+
+    let axes = if motion.x.abs() < motion.z.abs() {
+        [1, 2, 0]
+    } else {
+        [1, 0, 2]
+    };
+
+    for axis in axes {
+        let offset = clip_axis(bounds, axis, motion[axis], boxes);
+
+        let mut delta = DVec3::ZERO;
+        delta[axis] = offset;
+
+        bounds = bounds + delta;
+        result[axis] = offset;
+    }
+
+    result
+}
+
+/// Clips the movement of `bounds` along a single axis so that it does not
+/// enter any of the `boxes`.
+fn clip_axis(bounds: Aabb, axis: usize, mut offset: f64, boxes: &[Aabb]) -> f64 {
+    if offset == 0.0 {
+        return 0.0;
+    }
+
+    let [a, b] = match axis {
+        0 => [1, 2],
+        1 => [0, 2],
+        _ => [0, 1],
+    };
+
+    for shape in boxes {
+        // The box must overlap on the other two axes to block the movement.
+        if shape.max()[a] <= bounds.min()[a] + EPSILON
+            || shape.min()[a] >= bounds.max()[a] - EPSILON
+            || shape.max()[b] <= bounds.min()[b] + EPSILON
+            || shape.min()[b] >= bounds.max()[b] - EPSILON
+        {
+            continue;
+        }
+
+        if offset > 0.0 && shape.min()[axis] >= bounds.max()[axis] - EPSILON {
+            offset = offset.min(shape.min()[axis] - bounds.max()[axis]);
+        } else if offset < 0.0 && shape.max()[axis] <= bounds.min()[axis] + EPSILON {
+            offset = offset.max(shape.max()[axis] - bounds.min()[axis]);
+        }
+    }
+
+    offset
+}
@@ -0,0 +1,260 @@
+//! Raycasting against blocks and entities.
+//!
+//! Block raycasts are performed with [`ChunkLayer::raycast_blocks`]. To include
+//! entities, use [`raycast`] with the hitboxes of the entities that can be
+//! hit.
+
+use bevy_ecs::prelude::*;
+use valence_generated::block::BlockState;
+use valence_math::{Aabb, DVec3};
+use valence_protocol::{BlockPos, Direction};
+
+use crate::ChunkLayer;
+
+/// The set of block shapes a ray is tested against.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum RaycastShape {
+    /// The shapes that entities collide with. Use this for line of sight and
+    /// projectiles.
+    #[default]
+    Collision,
+    /// The outline shapes that players aim at when breaking and placing
+    /// blocks. Use this to find the block a player is looking at.
+    Outline,
+}
+
+impl RaycastShape {
+    /// Returns the shapes of a block state relative to the block's position.
+    pub fn shapes(self, state: BlockState) -> impl Iterator<Item = Aabb> {
+        let (collision, outline) = match self {
+            RaycastShape::Collision => (Some(state.collision_shapes()), None),
+            RaycastShape::Outline => (None, Some(state.outline_shapes())),
+        };
+
+        collision
+            .into_iter()
+            .flatten()
+            .chain(outline.into_iter().flatten())
+    }
+}
+
+/// A block hit by a ray.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BlockRaycastHit {
+    /// The position of the block that was hit.
+    pub block_pos: BlockPos,
+    /// The state of the block that was hit.
+    pub state: BlockState,
+    /// The face of the block's shape that was hit.
+    pub face: Direction,
+    /// The point where the ray hit the block.
+    pub pos: DVec3,
+    /// The distance from the origin of the ray to [`Self::pos`].
+    pub distance: f64,
+}
+
+/// An entity hit by a ray.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct EntityRaycastHit {
+    /// The entity that was hit.
+    pub entity: Entity,
+    /// The face of the entity's hitbox that was hit.
+    pub face: Direction,
+    /// The point where the ray hit the entity.
+    pub pos: DVec3,
+    /// The distance from the origin of the ray to [`Self::pos`].
+    pub distance: f64,
+}
+
+/// The result of [`raycast`].
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RaycastHit {
+    Block(BlockRaycastHit),
+    Entity(EntityRaycastHit),
+}
+
+impl RaycastHit {
+    /// The point where the ray hit.
+    pub fn pos(&self) -> DVec3 {
+        match self {
+            RaycastHit::Block(hit) => hit.pos,
+            RaycastHit::Entity(hit) => hit.pos,
+        }
+    }
+
+    /// The face that was hit.
+    pub fn face(&self) -> Direction {
+        match self {
+            RaycastHit::Block(hit) => hit.face,
+            RaycastHit::Entity(hit) => hit.face,
+        }
+    }
+
+    /// The distance from the origin of the ray to the hit.
+    pub fn distance(&self) -> f64 {
+        match self {
+            RaycastHit::Block(hit) => hit.distance,
+            RaycastHit::Entity(hit) => hit.distance,
+        }
+    }
+}
+
+/// Casts a ray against the blocks of `layer` and the given entity hitboxes,
+/// and returns the closest hit.
+///
+/// `entities` yields the entities that can be hit along with their hitboxes
+/// in world space, such as the [`Hitbox`] of every entity in an entity layer
+/// except the one casting the ray. `direction` does not need to be
+/// normalized. The ray stops after `max_distance` blocks or when it reaches an
+/// unloaded chunk.
+///
+/// [`Hitbox`]: valence_entity::hitbox::Hitbox
+pub fn raycast<I>(
+    layer: &ChunkLayer,
+    entities: I,
+    origin: DVec3,
+    direction: DVec3,
+    max_distance: f64,
+    shape: RaycastShape,
+) -> Option<RaycastHit>
+where
+    I: IntoIterator<Item = (Entity, Aabb)>,
+{
+    let block_hit = layer.raycast_blocks(origin, direction, max_distance, shape);
+
+    // Entities behind the hit block can't be hit.
+    let max_distance = block_hit.map_or(max_distance, |hit| hit.distance);
+
+    match raycast_entities(entities, origin, direction, max_distance) {
+        Some(entity_hit) => Some(RaycastHit::Entity(entity_hit)),
+        None => block_hit.map(RaycastHit::Block),
+    }
+}
+
+/// Casts a ray against the given entity hitboxes and returns the closest hit
+/// within `max_distance`.
+///
+/// See [`raycast`] for more information.
+pub fn raycast_entities<I>(
+    entities: I,
+    origin: DVec3,
+    direction: DVec3,
+    max_distance: f64,
+) -> Option<EntityRaycastHit>
+where
+    I: IntoIterator<Item = (Entity, Aabb)>,
+{
+    let dir = direction.try_normalize()?;
+
+    let mut closest: Option<EntityRaycastHit> = None;
+
+    for (entity, hitbox) in entities {
+        let Some((distance, face)) = ray_box_intersection(hitbox, origin, dir) else {
+            continue;
+        };
+
+        if distance <= max_distance && closest.map_or(true, |c| distance < c.distance) {
+            closest = Some(EntityRaycastHit {
+                entity,
+                face,
+                pos: origin + dir * distance,
+                distance,
+            });
+        }
+    }
+
+    closest
+}
+
+/// Returns the distance along the normalized direction `dir` to the point
+/// where the ray enters `aabb`, along with the face of the box it enters
+/// through.
+///
+/// If the origin is inside the box, the distance is zero and the face is
+/// opposite the direction of the ray.
+pub(crate) fn ray_box_intersection(
+    aabb: Aabb,
+    origin: DVec3,
+    dir: DVec3,
+) -> Option<(f64, Direction)> {
+    const NEGATIVE_FACES: [Direction; 3] = [Direction::West, Direction::Down, Direction::North];
+    const POSITIVE_FACES: [Direction; 3] = [Direction::East, Direction::Up, Direction::South];
+
+    let mut near = 0.0_f64;
+    let mut far = f64::INFINITY;
+    let mut face = None;
+
+    for i in 0..3 {
+        if dir[i] == 0.0 {
+            // Parallel to the slabs of this axis.
+            if origin[i] < aabb.min()[i] || origin[i] > aabb.max()[i] {
+                return None;
+            }
+
+            continue;
+        }
+
+        let t0 = (aabb.min()[i] - origin[i]) / dir[i];
+        let t1 = (aabb.max()[i] - origin[i]) / dir[i];
+
+        let (enter, exit, enter_face) = if dir[i] > 0.0 {
+            (t0, t1, NEGATIVE_FACES[i])
+        } else {
+            (t1, t0, POSITIVE_FACES[i])
+        };
+
+        if enter > near {
+            near = enter;
+            face = Some(enter_face);
+        }
+
+        far = far.min(exit);
+
+        if near > far {
+            return None;
+        }
+    }
+
+    let face = face.unwrap_or_else(|| {
+        // The origin is inside the box. Use the face of the dominant axis.
+        let abs = dir.abs();
+        let axis = if abs.x >= abs.y && abs.x >= abs.z {
+            0
+        } else if abs.y >= abs.z {
+            1
+        } else {
+            2
+        };
+
+        if dir[axis] > 0.0 {
+            NEGATIVE_FACES[axis]
+        } else {
+            POSITIVE_FACES[axis]
+        }
+    });
+
+    Some((near, face))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_box_faces() {
+        let aabb = Aabb::new(DVec3::new(0.0, 0.0, 0.0), DVec3::new(1.0, 1.0, 1.0));
+
+        let (dist, face) =
+            ray_box_intersection(aabb, DVec3::new(-2.0, 0.5, 0.5), DVec3::X).unwrap();
+        assert_eq!(dist, 2.0);
+        assert_eq!(face, Direction::West);
+
+        let (dist, face) =
+            ray_box_intersection(aabb, DVec3::new(0.5, 3.0, 0.5), DVec3::NEG_Y).unwrap();
+        assert_eq!(dist, 2.0);
+        assert_eq!(face, Direction::Up);
+
+        assert!(ray_box_intersection(aabb, DVec3::new(-2.0, 2.0, 0.5), DVec3::X).is_none());
+        assert!(ray_box_intersection(aabb, DVec3::new(2.0, 0.5, 0.5), DVec3::X).is_none());
+    }
+}
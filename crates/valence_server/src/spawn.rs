@@ -9,8 +9,7 @@ use derive_more::{Deref, DerefMut};
 use valence_entity::EntityLayerId;
 use valence_protocol::packets::play::{GameJoinS2c, PlayerRespawnS2c, PlayerSpawnPositionS2c};
 use valence_protocol::{BlockPos, GameMode, GlobalPos, Ident, VarInt, WritePacket};
-use valence_registry::tags::TagsRegistry;
-use valence_registry::{BiomeRegistry, RegistryCodec};
+use valence_registry::{BiomeRegistry, DimensionTypeRegistry, RegistryCodec};
 
 use crate::client::{Client, ViewDistance, VisibleChunkLayer};
 use crate::layer::ChunkLayer;
@@ -85,7 +84,6 @@ pub struct ClientSpawnQuery {
 
 pub(super) fn initial_join(
     codec: Res<RegistryCodec>,
-    tags: Res<TagsRegistry>,
     mut clients: Query<(&mut Client, &VisibleChunkLayer, ClientSpawnQueryReadOnly), Added<Client>>,
     chunk_layers: Query<&ChunkLayer>,
 ) {
@@ -94,54 +92,68 @@ pub(super) fn initial_join(
             continue;
         };
 
-        let dimension_names: BTreeSet<Ident<Cow<str>>> = codec
-            .registry(BiomeRegistry::KEY)
-            .iter()
-            .map(|value| value.name.as_str_ident().into())
-            .collect();
-
-        let dimension_name: Ident<Cow<str>> = chunk_layer.dimension_type_name().into();
-
-        let last_death_location = spawn.death_loc.0.as_ref().map(|(id, pos)| GlobalPos {
-            dimension_name: id.as_str_ident().into(),
-            position: *pos,
-        });
-
         // The login packet is prepended so that it's sent before all the other packets.
         // Some packets don't work correctly when sent before the game join packet.
-        _ = client.enc.prepend_packet(&GameJoinS2c {
-            entity_id: 0, // We reserve ID 0 for clients.
-            is_hardcore: spawn.is_hardcore.0,
-            game_mode: *spawn.game_mode,
-            previous_game_mode: spawn.prev_game_mode.0.into(),
-            dimension_names: Cow::Owned(dimension_names),
-            registry_codec: Cow::Borrowed(codec.cached_codec()),
-            dimension_type_name: dimension_name.clone(),
-            dimension_name,
-            hashed_seed: spawn.hashed_seed.0 as i64,
-            max_players: VarInt(0), // Ignored by clients.
-            view_distance: VarInt(i32::from(spawn.view_distance.get())),
-            simulation_distance: VarInt(16), // TODO.
-            reduced_debug_info: spawn.reduced_debug_info.0,
-            enable_respawn_screen: spawn.has_respawn_screen.0,
-            is_debug: spawn.is_debug.0,
-            is_flat: spawn.is_flat.0,
-            last_death_location,
-            portal_cooldown: VarInt(spawn.portal_cooldown.0),
-        });
-
-        client.write_packet_bytes(tags.sync_tags_packet());
+        _ = client
+            .enc
+            .prepend_packet(&game_join_packet(&codec, chunk_layer, &spawn));
+    }
+}
 
-        /*
-        // TODO: enable all the features?
-        q.client.write_packet(&FeatureFlags {
-            features: vec![Ident::new("vanilla").unwrap()],
-        })?;
-        */
+/// Builds the game join packet for a client spawning into `chunk_layer`. The
+/// registries and tags referenced by this packet are sent during the
+/// configuration state.
+pub(crate) fn game_join_packet<'a>(
+    codec: &'a RegistryCodec,
+    chunk_layer: &'a ChunkLayer,
+    spawn: &'a ClientSpawnQueryReadOnlyItem,
+) -> GameJoinS2c<'a> {
+    let dimension_names: BTreeSet<Ident<Cow<str>>> = codec
+        .registry(BiomeRegistry::KEY)
+        .iter()
+        .map(|value| value.name.as_str_ident().into())
+        .collect();
+
+    let dimension_name = chunk_layer.dimension_type_name();
+
+    let last_death_location = spawn.death_loc.0.as_ref().map(|(id, pos)| GlobalPos {
+        dimension_name: id.as_str_ident().into(),
+        position: *pos,
+    });
+
+    GameJoinS2c {
+        entity_id: 0, // We reserve ID 0 for clients.
+        is_hardcore: spawn.is_hardcore.0,
+        dimension_names: Cow::Owned(dimension_names),
+        max_players: VarInt(0), // Ignored by clients.
+        view_distance: VarInt(i32::from(spawn.view_distance.get())),
+        simulation_distance: VarInt(16), // TODO.
+        reduced_debug_info: spawn.reduced_debug_info.0,
+        enable_respawn_screen: spawn.has_respawn_screen.0,
+        do_limited_crafting: false,
+        dimension_type: dimension_type_index(codec, dimension_name),
+        dimension_name: dimension_name.into(),
+        hashed_seed: spawn.hashed_seed.0 as i64,
+        game_mode: *spawn.game_mode,
+        previous_game_mode: spawn.prev_game_mode.0.into(),
+        is_debug: spawn.is_debug.0,
+        is_flat: spawn.is_flat.0,
+        last_death_location,
+        portal_cooldown: VarInt(spawn.portal_cooldown.0),
+        enforces_secure_chat: false,
     }
 }
 
+fn dimension_type_index(codec: &RegistryCodec, dimension_type_name: Ident<&str>) -> VarInt {
+    let idx = codec
+        .entry_index(DimensionTypeRegistry::KEY, dimension_type_name)
+        .unwrap_or_default();
+
+    VarInt(idx as i32)
+}
+
 pub(super) fn respawn(
+    codec: Res<RegistryCodec>,
     mut clients: Query<
         (
             &mut Client,
@@ -177,16 +189,16 @@ pub(super) fn respawn(
         });
 
         client.write_packet(&PlayerRespawnS2c {
-            dimension_type_name: dimension_name.into(),
+            dimension_type: dimension_type_index(&codec, dimension_name),
             dimension_name: dimension_name.into(),
             hashed_seed: hashed_seed.0,
             game_mode: *game_mode,
             previous_game_mode: prev_game_mode.0.into(),
             is_debug: is_debug.0,
             is_flat: is_flat.0,
-            copy_metadata: true,
             last_death_location,
             portal_cooldown: VarInt(0), // TODO
+            data_kept: 0x02,
         });
     }
 }
@@ -60,6 +60,16 @@ impl TeleportState {
     pub fn pending_teleports(&self) -> u32 {
         self.pending_teleports
     }
+
+    /// Forgets the position and look last sent to the client so the next
+    /// update teleports the client again.
+    pub(crate) fn reset_synced(&mut self) {
+        self.synced_pos = DVec3::NAN;
+        self.synced_look = Look {
+            yaw: f32::NAN,
+            pitch: f32::NAN,
+        };
+    }
 }
 
 /// Syncs the client's position and look with the server.
@@ -1,9 +1,10 @@
 #![allow(clippy::type_complexity)]
 
+use valence::block_entity::{Sign, Skull};
+use valence::chat::ChatMessageEvent;
 use valence::interact_block::InteractBlockEvent;
-use valence::message::ChatMessageEvent;
-use valence::nbt::{compound, List};
 use valence::prelude::*;
+use valence::protocol::item::component::ResolvableProfile;
 
 const FLOOR_Y: i32 = 64;
 const SIGN_POS: [i32; 3] = [3, FLOOR_Y + 1, 2];
@@ -47,21 +48,14 @@ fn setup(
         BlockState::CHEST.set(PropName::Facing, PropValue::West),
     );
 
+    let mut sign = Sign::default();
+    sign.front_text.messages[0] = "Type in chat:".color(Color::RED);
+
     layer.chunk.set_block(
         SIGN_POS,
         Block {
             state: BlockState::OAK_SIGN.set(PropName::Rotation, PropValue::_4),
-            nbt: Some(compound! {
-                "front_text" => compound! {
-                    "messages" => List::String(vec![
-                        // All 4 lines are required, otherwise no text is displayed.
-                        "Type in chat:".color(Color::RED).into(),
-                        "".into_text().into(),
-                        "".into_text().into(),
-                        "".into_text().into(),
-                    ]),
-                }
-            }),
+            nbt: Some(sign.into()),
         },
     );
 
@@ -120,16 +114,9 @@ fn event_handler(
             continue;
         };
 
-        let nbt = layer.block_entity_mut(SIGN_POS).unwrap();
-        nbt.merge(compound! {
-            "front_text" => compound! {
-                "messages" => List::String(vec![
-                    "Type in chat:".color(Color::RED).into(),
-                    message.to_string().color(Color::DARK_GREEN).into(),
-                    format!("~{username}").italic().into(),
-                    "".into_text().into(),
-                ]),
-            },
+        layer.modify_block_entity(SIGN_POS, |sign: &mut Sign| {
+            sign.front_text.messages[1] = message.to_string().color(Color::DARK_GREEN);
+            sign.front_text.messages[2] = format!("~{username}").italic();
         });
     }
 
@@ -149,16 +136,13 @@ fn event_handler(
                 continue;
             };
 
-            *layer.block_entity_mut(SKULL_POS).unwrap() = compound! {
-                "SkullOwner" => compound! {
-                    "Id" => uuid.0,
-                    "Properties" => compound! {
-                        "textures" => List::Compound(vec![compound! {
-                            "Value" => textures.value.clone(),
-                        }])
-                    }
-                }
-            };
+            layer.modify_block_entity(SKULL_POS, |skull: &mut Skull| {
+                skull.profile = Some(ResolvableProfile {
+                    name: None,
+                    uuid: Some(uuid.0),
+                    properties: vec![textures.clone()],
+                });
+            });
         }
     }
 }
@@ -1,14 +1,12 @@
 #![allow(clippy::type_complexity)]
 
-use valence::interact_block::InteractBlockEvent;
-use valence::inventory::HeldItem;
 use valence::prelude::*;
 
 const SPAWN_Y: i32 = 64;
 
 pub fn main() {
     App::new()
-        .add_plugins(DefaultPlugins)
+        .add_plugins((DefaultPlugins, BlockPlacementPlugin))
         .add_systems(Startup, setup)
         .add_systems(
             Update,
@@ -17,7 +15,6 @@ pub fn main() {
                 despawn_disconnected_clients,
                 toggle_gamemode_on_sneak,
                 digging,
-                place_blocks,
             ),
         )
         .run();
@@ -120,54 +117,3 @@ fn digging(
         }
     }
 }
-
-fn place_blocks(
-    mut clients: Query<(&mut Inventory, &GameMode, &HeldItem)>,
-    mut layers: Query<&mut ChunkLayer>,
-    mut events: EventReader<InteractBlockEvent>,
-) {
-    let mut layer = layers.single_mut();
-
-    for event in events.read() {
-        let Ok((mut inventory, game_mode, held)) = clients.get_mut(event.client) else {
-            continue;
-        };
-        if event.hand != Hand::Main {
-            continue;
-        }
-
-        // get the held item
-        let slot_id = held.slot();
-        let stack = inventory.slot(slot_id);
-        if stack.is_empty() {
-            // no item in the slot
-            continue;
-        };
-
-        let Some(block_kind) = BlockKind::from_item_kind(stack.item) else {
-            // can't place this item as a block
-            continue;
-        };
-
-        if *game_mode == GameMode::Survival {
-            // check if the player has the item in their inventory and remove
-            // it.
-            if stack.count > 1 {
-                let amount = stack.count - 1;
-                inventory.set_slot_amount(slot_id, amount);
-            } else {
-                inventory.set_slot(slot_id, ItemStack::EMPTY);
-            }
-        }
-        let real_pos = event.position.get_in_direction(event.face);
-        let state = block_kind.to_state().set(
-            PropName::Axis,
-            match event.face {
-                Direction::Down | Direction::Up => PropValue::Y,
-                Direction::North | Direction::South => PropValue::Z,
-                Direction::West | Direction::East => PropValue::X,
-            },
-        );
-        layer.set_block(real_pos, state);
-    }
-}
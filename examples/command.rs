@@ -4,14 +4,14 @@ use std::ops::DerefMut;
 
 use command::graph::CommandGraphBuilder;
 use command::handler::CommandResultEvent;
-use command::parsers::entity_selector::{EntitySelector, EntitySelectors};
+use command::parsers::entity_selector::EntitySelector;
 use command::parsers::{CommandArg, GreedyString, QuotableString};
 use command::scopes::CommandScopes;
+use command::selector::SelectorQuery;
+use command::source::{CommandReplyEvent, ConsoleCommandPlugin};
 use command::{parsers, AddCommand, Command, CommandScopeRegistry, ModifierValue};
 use command_macros::Command;
 use parsers::{Vec2 as Vec2Parser, Vec3 as Vec3Parser};
-use rand::prelude::IteratorRandom;
-use valence::entity::living::LivingEntity;
 use valence::prelude::*;
 use valence::*;
 use valence_server::op_level::OpLevel;
@@ -173,6 +173,8 @@ impl Command for ComplexRedirectionCommand {
 pub fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
+        // commands can also be entered in the terminal running the server
+        .add_plugins(ConsoleCommandPlugin)
         .add_command::<TestCommand>()
         .add_command::<TeleportCommand>()
         .add_command::<GamemodeCommand>()
@@ -195,10 +197,6 @@ pub fn main() {
         .run();
 }
 
-enum TeleportTarget {
-    Targets(Vec<Entity>),
-}
-
 #[derive(Debug)]
 enum TeleportDestination {
     Location(Vec3Parser),
@@ -207,95 +205,65 @@ enum TeleportDestination {
 
 fn handle_teleport_command(
     mut events: EventReader<CommandResultEvent<TeleportCommand>>,
-    living_entities: Query<Entity, With<LivingEntity>>,
-    mut clients: Query<(Entity, &mut Client)>,
-    entity_layers: Query<&EntityLayerId>,
-    mut positions: Query<&mut Position>,
-    usernames: Query<(Entity, &Username)>,
+    mut replies: EventWriter<CommandReplyEvent>,
+    // the selector query reads positions, so it can't be used at the same time as
+    // the positions we want to change
+    mut params: ParamSet<(SelectorQuery, Query<&mut Position>)>,
 ) {
     for event in events.read() {
-        let compiled_command = match &event.result {
-            TeleportCommand::ExecutorToLocation { location } => (
-                TeleportTarget::Targets(vec![event.executor]),
-                TeleportDestination::Location(*location),
-            ),
-            TeleportCommand::ExecutorToTarget { target } => (
-                TeleportTarget::Targets(vec![event.executor]),
-                TeleportDestination::Target(
-                    find_targets(
-                        &living_entities,
-                        &mut clients,
-                        &positions,
-                        &entity_layers,
-                        &usernames,
-                        event,
-                        target,
-                    )
-                    .first()
-                    .copied(),
+        let (targets, destination) = {
+            let selector = params.p0();
+            let first =
+                |target: &EntitySelector| selector.resolve(target, event.executor).first().copied();
+
+            match &event.result {
+                TeleportCommand::ExecutorToLocation { location } => (
+                    vec![event.executor],
+                    TeleportDestination::Location(*location),
                 ),
-            ),
-            TeleportCommand::TargetToTarget { from, to } => (
-                TeleportTarget::Targets(
-                    find_targets(
-                        &living_entities,
-                        &mut clients,
-                        &positions,
-                        &entity_layers,
-                        &usernames,
-                        event,
-                        from,
-                    )
-                    .clone(),
+                TeleportCommand::ExecutorToTarget { target } => (
+                    vec![event.executor],
+                    TeleportDestination::Target(first(target)),
                 ),
-                TeleportDestination::Target(
-                    find_targets(
-                        &living_entities,
-                        &mut clients,
-                        &positions,
-                        &entity_layers,
-                        &usernames,
-                        event,
-                        to,
-                    )
-                    .first()
-                    .copied(),
+                TeleportCommand::TargetToTarget { from, to } => (
+                    selector.resolve(from, event.executor),
+                    TeleportDestination::Target(first(to)),
                 ),
-            ),
-            TeleportCommand::TargetToLocation { target, location } => (
-                TeleportTarget::Targets(
-                    find_targets(
-                        &living_entities,
-                        &mut clients,
-                        &positions,
-                        &entity_layers,
-                        &usernames,
-                        event,
-                        target,
-                    )
-                    .clone(),
+                TeleportCommand::TargetToLocation { target, location } => (
+                    selector.resolve(target, event.executor),
+                    TeleportDestination::Location(*location),
                 ),
-                TeleportDestination::Location(*location),
-            ),
+            }
         };
 
-        let (TeleportTarget::Targets(targets), destination) = compiled_command;
+        if targets.is_empty() || matches!(destination, TeleportDestination::Target(None)) {
+            replies.send(CommandReplyEvent::new(
+                event.executor,
+                "Could not find target",
+            ));
+            continue;
+        }
 
         println!("executing teleport command {targets:#?} -> {destination:#?}");
+        let mut positions = params.p1();
         match destination {
             TeleportDestination::Location(location) => {
                 for target in targets {
-                    let mut pos = positions.get_mut(target).unwrap();
+                    // the console has no position to change
+                    let Ok(mut pos) = positions.get_mut(target) else {
+                        continue;
+                    };
                     pos.0.x = f64::from(location.x.get(pos.0.x as f32));
                     pos.0.y = f64::from(location.y.get(pos.0.y as f32));
                     pos.0.z = f64::from(location.z.get(pos.0.z as f32));
                 }
             }
             TeleportDestination::Target(target) => {
-                let target = target.unwrap();
-                let target_pos = **positions.get(target).unwrap();
+                let target_pos = **positions.get(target.unwrap()).unwrap();
                 for target in targets {
-                    let mut position = positions.get_mut(target).unwrap();
+                    let Ok(mut position) = positions.get_mut(target) else {
+                        continue;
+                    };
                     position.0 = target_pos;
                 }
             }
@@ -303,301 +271,88 @@ fn handle_teleport_command(
     }
 }
 
-fn find_targets(
-    living_entities: &Query<Entity, With<LivingEntity>>,
-    clients: &mut Query<(Entity, &mut Client)>,
-    positions: &Query<&mut Position>,
-    entity_layers: &Query<&EntityLayerId>,
-    usernames: &Query<(Entity, &Username)>,
-    event: &CommandResultEvent<TeleportCommand>,
-    target: &EntitySelector,
-) -> Vec<Entity> {
-    match target {
-        EntitySelector::SimpleSelector(selector) => match selector {
-            EntitySelectors::AllEntities => {
-                let executor_entity_layer = *entity_layers.get(event.executor).unwrap();
-                living_entities
-                    .iter()
-                    .filter(|entity| {
-                        let entity_layer = entity_layers.get(*entity).unwrap();
-                        entity_layer.0 == executor_entity_layer.0
-                    })
-                    .collect()
-            }
-            EntitySelectors::SinglePlayer(name) => {
-                let target = usernames.iter().find(|(_, username)| username.0 == *name);
-                match target {
-                    None => {
-                        let client = &mut clients.get_mut(event.executor).unwrap().1;
-                        client.send_chat_message(format!("Could not find target: {name}"));
-                        vec![]
-                    }
-                    Some(target_entity) => {
-                        vec![target_entity.0]
-                    }
-                }
-            }
-            EntitySelectors::AllPlayers => {
-                let executor_entity_layer = *entity_layers.get(event.executor).unwrap();
-                clients
-                    .iter_mut()
-                    .filter_map(|(entity, ..)| {
-                        let entity_layer = entity_layers.get(entity).unwrap();
-                        if entity_layer.0 == executor_entity_layer.0 {
-                            Some(entity)
-                        } else {
-                            None
-                        }
-                    })
-                    .collect()
-            }
-            EntitySelectors::SelfPlayer => {
-                vec![event.executor]
-            }
-            EntitySelectors::NearestPlayer => {
-                let executor_entity_layer = *entity_layers.get(event.executor).unwrap();
-                let executor_pos = positions.get(event.executor).unwrap();
-                let target = clients
-                    .iter_mut()
-                    .filter(|(entity, ..)| {
-                        *entity_layers.get(*entity).unwrap() == executor_entity_layer
-                    })
-                    .filter(|(target, ..)| *target != event.executor)
-                    .map(|(target, ..)| target)
-                    .min_by(|target, target2| {
-                        let target_pos = positions.get(*target).unwrap();
-                        let target2_pos = positions.get(*target2).unwrap();
-                        let target_dist = target_pos.distance(**executor_pos);
-                        let target2_dist = target2_pos.distance(**executor_pos);
-                        target_dist.partial_cmp(&target2_dist).unwrap()
-                    });
-                match target {
-                    None => {
-                        let mut client = clients.get_mut(event.executor).unwrap().1;
-                        client.send_chat_message("Could not find target".to_owned());
-                        vec![]
-                    }
-                    Some(target_entity) => {
-                        vec![target_entity]
-                    }
-                }
-            }
-            EntitySelectors::RandomPlayer => {
-                let executor_entity_layer = *entity_layers.get(event.executor).unwrap();
-                let target = clients
-                    .iter_mut()
-                    .filter(|(entity, ..)| {
-                        *entity_layers.get(*entity).unwrap() == executor_entity_layer
-                    })
-                    .choose(&mut rand::thread_rng())
-                    .map(|(target, ..)| target);
-                match target {
-                    None => {
-                        let mut client = clients.get_mut(event.executor).unwrap().1;
-                        client.send_chat_message("Could not find target".to_owned());
-                        vec![]
-                    }
-                    Some(target_entity) => {
-                        vec![target_entity]
-                    }
-                }
-            }
-        },
-        EntitySelector::ComplexSelector(_, _) => {
-            let mut client = clients.get_mut(event.executor).unwrap().1;
-            client.send_chat_message("complex selector not implemented".to_owned());
-            vec![]
-        }
-    }
-}
-
 fn handle_test_command(
     mut events: EventReader<CommandResultEvent<TestCommand>>,
-    mut clients: Query<&mut Client>,
+    mut replies: EventWriter<CommandReplyEvent>,
 ) {
     for event in events.read() {
-        let client = &mut clients.get_mut(event.executor).unwrap();
-        client.send_chat_message(format!(
-            "Test command executed with data:\n {:#?}",
-            &event.result
+        replies.send(CommandReplyEvent::new(
+            event.executor,
+            format!("Test command executed with data:\n {:#?}", &event.result),
         ));
     }
 }
 
 fn handle_complex_command(
     mut events: EventReader<CommandResultEvent<ComplexRedirectionCommand>>,
-    mut clients: Query<&mut Client>,
+    mut replies: EventWriter<CommandReplyEvent>,
 ) {
     for event in events.read() {
-        let client = &mut clients.get_mut(event.executor).unwrap();
-        client.send_chat_message(format!(
-            "complex command executed with data:\n {:#?}\n and with the modifiers:\n {:#?}",
-            &event.result, &event.modifiers
+        replies.send(CommandReplyEvent::new(
+            event.executor,
+            format!(
+                "complex command executed with data:\n {:#?}\n and with the modifiers:\n {:#?}",
+                &event.result, &event.modifiers
+            ),
         ));
     }
 }
 
 fn handle_struct_command(
     mut events: EventReader<CommandResultEvent<StructCommand>>,
-    mut clients: Query<&mut Client>,
+    mut replies: EventWriter<CommandReplyEvent>,
 ) {
     for event in events.read() {
-        let client = &mut clients.get_mut(event.executor).unwrap();
-        client.send_chat_message(format!(
-            "Struct command executed with data:\n {:#?}",
-            &event.result
+        replies.send(CommandReplyEvent::new(
+            event.executor,
+            format!("Struct command executed with data:\n {:#?}", &event.result),
         ));
     }
 }
 
 fn handle_gamemode_command(
     mut events: EventReader<CommandResultEvent<GamemodeCommand>>,
-    mut clients: Query<(&mut Client, &mut GameMode, &Username, Entity)>,
-    positions: Query<&Position>,
+    mut replies: EventWriter<CommandReplyEvent>,
+    // the selector query reads game modes, so it can't be used at the same time as
+    // the game modes we want to change
+    mut params: ParamSet<(SelectorQuery, Query<&mut GameMode>)>,
 ) {
     for event in events.read() {
-        let game_mode_to_set = match &event.result {
-            GamemodeCommand::Survival { .. } => GameMode::Survival,
-            GamemodeCommand::Creative { .. } => GameMode::Creative,
-            GamemodeCommand::Adventure { .. } => GameMode::Adventure,
-            GamemodeCommand::Spectator { .. } => GameMode::Spectator,
+        let (game_mode_to_set, selector) = match &event.result {
+            GamemodeCommand::Survival { target } => (GameMode::Survival, target),
+            GamemodeCommand::Creative { target } => (GameMode::Creative, target),
+            GamemodeCommand::Adventure { target } => (GameMode::Adventure, target),
+            GamemodeCommand::Spectator { target } => (GameMode::Spectator, target),
         };
 
-        let selector = match &event.result {
-            GamemodeCommand::Survival { target } => target.clone(),
-            GamemodeCommand::Creative { target } => target.clone(),
-            GamemodeCommand::Adventure { target } => target.clone(),
-            GamemodeCommand::Spectator { target } => target.clone(),
+        let targets = match selector {
+            None => vec![event.executor],
+            Some(selector) => params.p0().resolve(selector, event.executor),
         };
 
-        match selector {
-            None => {
-                let (mut client, mut game_mode, ..) = clients.get_mut(event.executor).unwrap();
+        if targets.is_empty() {
+            replies.send(CommandReplyEvent::new(
+                event.executor,
+                "Could not find target",
+            ));
+            continue;
+        }
+
+        let mut game_modes = params.p1();
+
+        for target in targets {
+            if let Ok(mut game_mode) = game_modes.get_mut(target) {
                 *game_mode = game_mode_to_set;
-                client.send_chat_message(format!(
-                    "Gamemode command executor -> self executed with data:\n {:#?}",
-                    &event.result
-                ));
             }
-            Some(selector) => match selector {
-                EntitySelector::SimpleSelector(selector) => match selector {
-                    EntitySelectors::AllEntities => {
-                        for (mut client, mut game_mode, ..) in &mut clients.iter_mut() {
-                            *game_mode = game_mode_to_set;
-                            client.send_chat_message(format!(
-                                "Gamemode command executor -> all entities executed with data:\n \
-                                 {:#?}",
-                                &event.result
-                            ));
-                        }
-                    }
-                    EntitySelectors::SinglePlayer(name) => {
-                        let target = clients
-                            .iter_mut()
-                            .find(|(.., username, _)| username.0 == *name)
-                            .map(|(.., target)| target);
-
-                        match target {
-                            None => {
-                                let client = &mut clients.get_mut(event.executor).unwrap().0;
-                                client.send_chat_message(format!("Could not find target: {name}"));
-                            }
-                            Some(target) => {
-                                let mut game_mode = clients.get_mut(target).unwrap().1;
-                                *game_mode = game_mode_to_set;
-
-                                let client = &mut clients.get_mut(event.executor).unwrap().0;
-                                client.send_chat_message(format!(
-                                    "Gamemode command executor -> single player executed with \
-                                     data:\n {:#?}",
-                                    &event.result
-                                ));
-                            }
-                        }
-                    }
-                    EntitySelectors::AllPlayers => {
-                        for (mut client, mut game_mode, ..) in &mut clients.iter_mut() {
-                            *game_mode = game_mode_to_set;
-                            client.send_chat_message(format!(
-                                "Gamemode command executor -> all entities executed with data:\n \
-                                 {:#?}",
-                                &event.result
-                            ));
-                        }
-                    }
-                    EntitySelectors::SelfPlayer => {
-                        let (mut client, mut game_mode, ..) =
-                            clients.get_mut(event.executor).unwrap();
-                        *game_mode = game_mode_to_set;
-                        client.send_chat_message(format!(
-                            "Gamemode command executor -> self executed with data:\n {:#?}",
-                            &event.result
-                        ));
-                    }
-                    EntitySelectors::NearestPlayer => {
-                        let executor_pos = positions.get(event.executor).unwrap();
-                        let target = clients
-                            .iter_mut()
-                            .filter(|(.., target)| *target != event.executor)
-                            .min_by(|(.., target), (.., target2)| {
-                                let target_pos = positions.get(*target).unwrap();
-                                let target2_pos = positions.get(*target2).unwrap();
-                                let target_dist = target_pos.distance(**executor_pos);
-                                let target2_dist = target2_pos.distance(**executor_pos);
-                                target_dist.partial_cmp(&target2_dist).unwrap()
-                            })
-                            .map(|(.., target)| target);
-
-                        match target {
-                            None => {
-                                let client = &mut clients.get_mut(event.executor).unwrap().0;
-                                client.send_chat_message("Could not find target".to_owned());
-                            }
-                            Some(target) => {
-                                let mut game_mode = clients.get_mut(target).unwrap().1;
-                                *game_mode = game_mode_to_set;
-
-                                let client = &mut clients.get_mut(event.executor).unwrap().0;
-                                client.send_chat_message(format!(
-                                    "Gamemode command executor -> single player executed with \
-                                     data:\n {:#?}",
-                                    &event.result
-                                ));
-                            }
-                        }
-                    }
-                    EntitySelectors::RandomPlayer => {
-                        let target = clients
-                            .iter_mut()
-                            .choose(&mut rand::thread_rng())
-                            .map(|(.., target)| target);
-
-                        match target {
-                            None => {
-                                let client = &mut clients.get_mut(event.executor).unwrap().0;
-                                client.send_chat_message("Could not find target".to_owned());
-                            }
-                            Some(target) => {
-                                let mut game_mode = clients.get_mut(target).unwrap().1;
-                                *game_mode = game_mode_to_set;
-
-                                let client = &mut clients.get_mut(event.executor).unwrap().0;
-                                client.send_chat_message(format!(
-                                    "Gamemode command executor -> single player executed with \
-                                     data:\n {:#?}",
-                                    &event.result
-                                ));
-                            }
-                        }
-                    }
-                },
-                EntitySelector::ComplexSelector(_, _) => {
-                    let client = &mut clients.get_mut(event.executor).unwrap().0;
-                    client
-                        .send_chat_message("Complex selectors are not implemented yet".to_owned());
-                }
-            },
         }
+
+        replies.send(CommandReplyEvent::new(
+            event.executor,
+            format!(
+                "Gamemode command executed with data:\n {:#?}",
+                &event.result
+            ),
+        ));
     }
 }
 
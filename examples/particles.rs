@@ -156,7 +156,7 @@ fn create_particle_vec() -> Vec<Particle> {
         Particle::Heart,
         Particle::InstantEffect,
         Particle::Item(ItemStack::EMPTY),
-        Particle::Item(ItemStack::new(ItemKind::IronPickaxe, 1, None)),
+        Particle::Item(ItemStack::new(ItemKind::IronPickaxe, 1)),
         Particle::VibrationBlock {
             block_pos: [0, SPAWN_Y, 0].into(),
             ticks: 50,
@@ -1,23 +1,13 @@
-#![allow(clippy::type_complexity)]
-
-use std::collections::hash_map::Entry;
-use std::collections::HashMap;
-use std::sync::Arc;
-use std::thread;
 use std::time::SystemTime;
 
-use flume::{Receiver, Sender};
 use noise::{NoiseFn, SuperSimplex};
 use tracing::info;
 use valence::prelude::*;
 use valence::spawn::IsFlat;
 
 const SPAWN_POS: DVec3 = DVec3::new(0.0, 200.0, 0.0);
-const HEIGHT: u32 = 384;
 
-struct ChunkWorkerState {
-    sender: Sender<(ChunkPos, UnloadedChunk)>,
-    receiver: Receiver<ChunkPos>,
+struct TerrainGenerator {
     // Noise functions
     density: SuperSimplex,
     hilly: SuperSimplex,
@@ -26,36 +16,11 @@ struct ChunkWorkerState {
     grass: SuperSimplex,
 }
 
-#[derive(Resource)]
-struct GameState {
-    /// Chunks that need to be generated. Chunks without a priority have already
-    /// been sent to the thread pool.
-    pending: HashMap<ChunkPos, Option<Priority>>,
-    sender: Sender<ChunkPos>,
-    receiver: Receiver<(ChunkPos, UnloadedChunk)>,
-}
-
-/// The order in which chunks should be processed by the thread pool. Smaller
-/// values are sent first.
-type Priority = u64;
-
 pub fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_systems(Startup, setup)
-        .add_systems(
-            Update,
-            (
-                (
-                    init_clients,
-                    remove_unviewed_chunks,
-                    update_client_views,
-                    send_recv_chunks,
-                )
-                    .chain(),
-                despawn_disconnected_clients,
-            ),
-        )
+        .add_systems(Update, (init_clients, despawn_disconnected_clients))
         .run();
 }
 
@@ -74,12 +39,10 @@ fn setup(
 
     info!("current seed: {seed}");
 
-    let (finished_sender, finished_receiver) = flume::unbounded();
-    let (pending_sender, pending_receiver) = flume::unbounded();
-
-    let state = Arc::new(ChunkWorkerState {
-        sender: finished_sender,
-        receiver: pending_receiver,
+    // Chunks are generated in a thread pool for parallelism and to avoid blocking
+    // the main tick loop. Chunks in view of clients are generated automatically,
+    // and chunks no longer in view are unloaded.
+    let level = GeneratedLevel::new(TerrainGenerator {
         density: SuperSimplex::new(seed),
         hilly: SuperSimplex::new(seed.wrapping_add(1)),
         stone: SuperSimplex::new(seed.wrapping_add(2)),
@@ -87,27 +50,9 @@ fn setup(
         grass: SuperSimplex::new(seed.wrapping_add(4)),
     });
 
-    // Chunks are generated in a thread pool for parallelism and to avoid blocking
-    // the main tick loop. You can use your thread pool of choice here (rayon,
-    // bevy_tasks, etc). Only the standard library is used in the example for the
-    // sake of simplicity.
-    //
-    // If your chunk generation algorithm is inexpensive then there's no need to do
-    // this.
-    for _ in 0..thread::available_parallelism().unwrap().get() {
-        let state = state.clone();
-        thread::spawn(move || chunk_worker(state));
-    }
-
-    commands.insert_resource(GameState {
-        pending: HashMap::new(),
-        sender: pending_sender,
-        receiver: finished_receiver,
-    });
-
     let layer = LayerBundle::new(ident!("overworld"), &dimensions, &biomes, &server);
 
-    commands.spawn(layer);
+    commands.spawn((layer, level));
 }
 
 fn init_clients(
@@ -144,81 +89,9 @@ fn init_clients(
     }
 }
 
-fn remove_unviewed_chunks(mut layers: Query<&mut ChunkLayer>) {
-    layers
-        .single_mut()
-        .retain_chunks(|_, chunk| chunk.viewer_count_mut() > 0);
-}
-
-fn update_client_views(
-    mut layers: Query<&mut ChunkLayer>,
-    mut clients: Query<(&mut Client, View, OldView)>,
-    mut state: ResMut<GameState>,
-) {
-    let layer = layers.single_mut();
-
-    for (client, view, old_view) in &mut clients {
-        let view = view.get();
-        let queue_pos = |pos: ChunkPos| {
-            if layer.chunk(pos).is_none() {
-                match state.pending.entry(pos) {
-                    Entry::Occupied(mut oe) => {
-                        if let Some(priority) = oe.get_mut() {
-                            let dist = view.pos.distance_squared(pos);
-                            *priority = (*priority).min(dist);
-                        }
-                    }
-                    Entry::Vacant(ve) => {
-                        let dist = view.pos.distance_squared(pos);
-                        ve.insert(Some(dist));
-                    }
-                }
-            }
-        };
-
-        // Queue all the new chunks in the view to be sent to the thread pool.
-        if client.is_added() {
-            view.iter().for_each(queue_pos);
-        } else {
-            let old_view = old_view.get();
-            if old_view != view {
-                view.diff(old_view).for_each(queue_pos);
-            }
-        }
-    }
-}
-
-fn send_recv_chunks(mut layers: Query<&mut ChunkLayer>, state: ResMut<GameState>) {
-    let mut layer = layers.single_mut();
-    let state = state.into_inner();
-
-    // Insert the chunks that are finished generating into the instance.
-    for (pos, chunk) in state.receiver.drain() {
-        layer.insert_chunk(pos, chunk);
-        assert!(state.pending.remove(&pos).is_some());
-    }
-
-    // Collect all the new chunks that need to be loaded this tick.
-    let mut to_send = vec![];
-
-    for (pos, priority) in &mut state.pending {
-        if let Some(pri) = priority.take() {
-            to_send.push((pri, pos));
-        }
-    }
-
-    // Sort chunks by ascending priority.
-    to_send.sort_unstable_by_key(|(pri, _)| *pri);
-
-    // Send the sorted chunks to be loaded.
-    for (_, pos) in to_send {
-        let _ = state.sender.try_send(*pos);
-    }
-}
-
-fn chunk_worker(state: Arc<ChunkWorkerState>) {
-    while let Ok(pos) = state.receiver.recv() {
-        let mut chunk = UnloadedChunk::with_height(HEIGHT);
+impl ChunkGenerator for TerrainGenerator {
+    fn generate(&self, pos: ChunkPos, height: u32, _min_y: i32) -> Option<UnloadedChunk> {
+        let mut chunk = UnloadedChunk::with_height(height);
 
         for offset_z in 0..16 {
             for offset_x in 0..16 {
@@ -234,10 +107,10 @@ fn chunk_worker(state: Arc<ChunkWorkerState>) {
 
                     let p = DVec3::new(f64::from(x), f64::from(y), f64::from(z));
 
-                    let block = if has_terrain_at(&state, p) {
+                    let block = if self.has_terrain_at(p) {
                         let gravel_height = WATER_HEIGHT
                             - 1
-                            - (fbm(&state.gravel, p / 10.0, 3, 2.0, 0.5) * 6.0).floor() as i32;
+                            - (fbm(&self.gravel, p / 10.0, 3, 2.0, 0.5) * 6.0).floor() as i32;
 
                         if in_terrain {
                             if depth > 0 {
@@ -252,7 +125,7 @@ fn chunk_worker(state: Arc<ChunkWorkerState>) {
                             }
                         } else {
                             in_terrain = true;
-                            let n = noise01(&state.stone, p / 15.0);
+                            let n = noise01(&self.stone, p / 15.0);
 
                             depth = (n * 5.0).round() as u32;
 
@@ -283,7 +156,7 @@ fn chunk_worker(state: Arc<ChunkWorkerState>) {
                         && chunk.block_state(offset_x, y - 1, offset_z) == BlockState::GRASS_BLOCK
                     {
                         let p = DVec3::new(f64::from(x), f64::from(y), f64::from(z));
-                        let density = fbm(&state.grass, p / 5.0, 4, 2.0, 0.7);
+                        let density = fbm(&self.grass, p / 5.0, 4, 2.0, 0.7);
 
                         if density > 0.55 {
                             if density > 0.7
@@ -305,27 +178,29 @@ fn chunk_worker(state: Arc<ChunkWorkerState>) {
             }
         }
 
-        let _ = state.sender.try_send((pos, chunk));
+        Some(chunk)
     }
 }
 
-fn has_terrain_at(state: &ChunkWorkerState, p: DVec3) -> bool {
-    let hilly = lerp(0.1, 1.0, noise01(&state.hilly, p / 400.0)).powi(2);
+impl TerrainGenerator {
+    fn has_terrain_at(&self, p: DVec3) -> bool {
+        let hilly = lerp(0.1, 1.0, noise01(&self.hilly, p / 400.0)).powi(2);
 
-    let lower = 15.0 + 100.0 * hilly;
-    let upper = lower + 100.0 * hilly;
+        let lower = 15.0 + 100.0 * hilly;
+        let upper = lower + 100.0 * hilly;
 
-    if p.y <= lower {
-        return true;
-    } else if p.y >= upper {
-        return false;
-    }
+        if p.y <= lower {
+            return true;
+        } else if p.y >= upper {
+            return false;
+        }
 
-    let density = 1.0 - lerpstep(lower, upper, p.y);
+        let density = 1.0 - lerpstep(lower, upper, p.y);
 
-    let n = fbm(&state.density, p / 100.0, 4, 2.0, 0.5);
+        let n = fbm(&self.density, p / 100.0, 4, 2.0, 0.5);
 
-    n < density
+        n < density
+    }
 }
 
 fn lerp(a: f64, b: f64, t: f64) -> f64 {
@@ -1,9 +1,10 @@
 #![allow(clippy::type_complexity)]
 
 use bevy_app::App;
+use valence::chat::ChatMessageEvent;
 use valence::client::despawn_disconnected_clients;
 use valence::inventory::HeldItem;
-use valence::message::{ChatMessageEvent, SendMessage};
+use valence::message::SendMessage;
 use valence::prelude::*;
 use valence::world_border::*;
 
@@ -90,7 +91,7 @@ fn init_clients(
         visible_chunk_layer.0 = layer;
         visible_entity_layers.0.insert(layer);
         pos.set([0.5, f64::from(SPAWN_Y) + 1.0, 0.5]);
-        let pickaxe = ItemStack::new(ItemKind::WoodenPickaxe, 1, None);
+        let pickaxe = ItemStack::new(ItemKind::WoodenPickaxe, 1);
         inv.set_slot(main_slot.slot(), pickaxe);
         client
             .send_chat_message("Use `add` and `center` chat messages to change the world border.");
@@ -44,6 +44,7 @@ public class Main implements ModInitializer {
                new Attributes(),
                new Blocks(),
                new Effects(),
+               new ItemComponents(),
                new Misc(),
                new Packets(),
                new Sounds(),
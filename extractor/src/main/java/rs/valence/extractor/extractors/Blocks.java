@@ -39,6 +39,7 @@ public class Blocks implements Main.Extractor {
             blockJson.addProperty("name", Registries.BLOCK.getId(block).getPath());
             blockJson.addProperty("translation_key", block.getTranslationKey());
             blockJson.addProperty("item_id", Registries.ITEM.getRawId(block.asItem()));
+            blockJson.addProperty("hardness", block.getHardness());
 
             if (block.asItem() instanceof VerticallyAttachableBlockItem wsbItem) {
                 if (wsbItem.getBlock() == block) {
@@ -72,6 +73,7 @@ public class Blocks implements Main.Extractor {
                 stateJson.addProperty("luminance", state.getLuminance());
                 stateJson.addProperty("opaque", state.isOpaque());
                 stateJson.addProperty("replaceable", state.isReplaceable());
+                stateJson.addProperty("tool_required", state.isToolRequired());
 
                 if (block.getDefaultState().equals(state)) {
                     blockJson.addProperty("default_state_id", id);
@@ -87,6 +89,16 @@ public class Blocks implements Main.Extractor {
 
                 stateJson.add("collision_shapes", collisionShapeIdxsJson);
 
+                var outlineShapeIdxsJson = new JsonArray();
+                for (var box : state.getOutlineShape(EmptyBlockView.INSTANCE, BlockPos.ORIGIN).getBoundingBoxes()) {
+                    var outlineShape = new Shape(box.minX, box.minY, box.minZ, box.maxX, box.maxY, box.maxZ);
+
+                    var idx = shapes.putIfAbsent(outlineShape, shapes.size());
+                    outlineShapeIdxsJson.add(Objects.requireNonNullElseGet(idx, () -> shapes.size() - 1));
+                }
+
+                stateJson.add("outline_shapes", outlineShapeIdxsJson);
+
                 for (var blockEntity : Registries.BLOCK_ENTITY_TYPE) {
                     if (blockEntity.supports(state)) {
                         stateJson.addProperty("block_entity_type", Registries.BLOCK_ENTITY_TYPE.getRawId(blockEntity));
@@ -38,7 +38,7 @@ public class Effects implements Main.Extractor {
                 attributeModifierJson.addProperty("attribute", attribute.getIdAsString());
                 attributeModifierJson.addProperty("operation", modifier.operation().getId());
                 attributeModifierJson.addProperty("base_value", modifier.value());
-                attributeModifierJson.addProperty("uuid", modifier.id().toTranslationKey());
+                attributeModifierJson.addProperty("id", modifier.id().toString());
 
                 attributeModifiersJson.add(attributeModifierJson);
             });
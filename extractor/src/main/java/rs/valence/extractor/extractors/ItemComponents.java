@@ -0,0 +1,33 @@
+package rs.valence.extractor.extractors;
+
+import com.google.gson.JsonArray;
+import com.google.gson.JsonElement;
+import com.google.gson.JsonObject;
+import net.minecraft.registry.Registries;
+import rs.valence.extractor.Main;
+
+public class ItemComponents implements Main.Extractor {
+    public ItemComponents() {
+    }
+
+    @Override
+    public String fileName() {
+        return "item_components.json";
+    }
+
+    @Override
+    public JsonElement extract() {
+        var componentsJson = new JsonArray();
+
+        for (var component : Registries.DATA_COMPONENT_TYPE) {
+            var componentJson = new JsonObject();
+
+            componentJson.addProperty("id", Registries.DATA_COMPONENT_TYPE.getRawId(component));
+            componentJson.addProperty("name", Registries.DATA_COMPONENT_TYPE.getId(component).getPath());
+
+            componentsJson.add(componentJson);
+        }
+
+        return componentsJson;
+    }
+}
@@ -6,6 +6,7 @@ import com.google.gson.JsonObject;
 import com.mojang.serialization.JsonOps;
 import net.minecraft.component.ComponentMap;
 import net.minecraft.component.DataComponentTypes;
+import net.minecraft.component.type.AttributeModifiersComponent;
 import net.minecraft.component.type.FoodComponent;
 import net.minecraft.enchantment.Enchantment;
 import net.minecraft.item.Item;
@@ -36,9 +37,9 @@ public class Items implements Main.Extractor {
         for (var item : registryManager.get(RegistryKeys.ITEM).streamEntries().toList()) {
             var itemJson = new JsonObject();
 
-            itemJson.addProperty("id", item.getKey().orElseThrow().getValue().toString());
-            itemJson.addProperty("name", item.getKey().orElseThrow().getValue().toString());
             Item realItem = item.value();
+            itemJson.addProperty("id", Registries.ITEM.getRawId(realItem));
+            itemJson.addProperty("name", item.getKey().orElseThrow().getValue().getPath());
             itemJson.addProperty("translation_key", realItem.getTranslationKey());
             itemJson.addProperty("max_stack", realItem.getMaxCount());
             itemJson.addProperty("max_durability", realItem.getDefaultStack().getMaxDamage());
@@ -47,6 +48,14 @@ public class Items implements Main.Extractor {
 
             itemJson.add("components", ComponentMap.CODEC.encodeStart(RegistryOps.of(JsonOps.INSTANCE, registryManager), realItem.getComponents()).getOrThrow());
 
+            // Armor items don't store their modifiers in the default components, so fall back to the item like
+            // ItemStack does.
+            var attributeModifiers = realItem.getComponents().getOrDefault(DataComponentTypes.ATTRIBUTE_MODIFIERS, AttributeModifiersComponent.DEFAULT);
+            if (attributeModifiers.modifiers().isEmpty()) {
+                attributeModifiers = realItem.getAttributeModifiers();
+            }
+            itemJson.add("attribute_modifiers", AttributeModifiersComponent.CODEC.encodeStart(RegistryOps.of(JsonOps.INSTANCE, registryManager), attributeModifiers).getOrThrow());
+
             itemsJson.add(itemJson);
         }
         return itemsJson;
@@ -43,17 +43,29 @@ pub use valence_advancement as advancement;
 pub use valence_anvil as anvil;
 #[cfg(feature = "boss_bar")]
 pub use valence_boss_bar as boss_bar;
+#[cfg(feature = "chat")]
+pub use valence_chat as chat;
+#[cfg(feature = "combat")]
+pub use valence_combat as combat;
 #[cfg(feature = "command")]
 pub use valence_command as command;
 #[cfg(feature = "command")]
 pub use valence_command_macros as command_macros;
+#[cfg(feature = "effect")]
+pub use valence_effect as effect;
 #[cfg(feature = "equipment")]
 pub use valence_equipment as equipment;
+#[cfg(feature = "generation")]
+pub use valence_generation as generation;
+#[cfg(feature = "hunger")]
+pub use valence_hunger as hunger;
 #[cfg(feature = "inventory")]
 pub use valence_inventory as inventory;
 pub use valence_lang as lang;
 #[cfg(feature = "network")]
 pub use valence_network as network;
+#[cfg(feature = "player_data")]
+pub use valence_player_data as player_data;
 #[cfg(feature = "player_list")]
 pub use valence_player_list as player_list;
 use valence_registry::RegistryPlugin;
@@ -64,7 +76,9 @@ use valence_server::action::ActionPlugin;
 use valence_server::client::ClientPlugin;
 use valence_server::client_command::ClientCommandPlugin;
 use valence_server::client_settings::ClientSettingsPlugin;
+use valence_server::configuration::ConfigurationPlugin;
 use valence_server::custom_payload::CustomPayloadPlugin;
+use valence_server::difficulty::DifficultyPlugin;
 use valence_server::entity::hitbox::HitboxPlugin;
 use valence_server::entity::EntityPlugin;
 use valence_server::event_loop::EventLoopPlugin;
@@ -74,7 +88,6 @@ use valence_server::interact_entity::InteractEntityPlugin;
 use valence_server::interact_item::InteractItemPlugin;
 use valence_server::keepalive::KeepalivePlugin;
 use valence_server::layer::LayerPlugin;
-use valence_server::message::MessagePlugin;
 use valence_server::movement::MovementPlugin;
 use valence_server::op_level::OpLevelPlugin;
 pub use valence_server::protocol::status_effects;
@@ -110,8 +123,24 @@ pub mod prelude {
         event::AdvancementTabChangeEvent, Advancement, AdvancementBundle, AdvancementClientUpdate,
         AdvancementCriteria, AdvancementDisplay, AdvancementFrameType, AdvancementRequirements,
     };
+    #[cfg(feature = "chat")]
+    pub use valence_chat::{ChatMessageEvent, ChatSettings, RelayChatEvent};
+    #[cfg(feature = "combat")]
+    pub use valence_combat::{
+        CombatPlugin, CombatSet, CombatSettings, DamageEvent, DeathEvent, PendingDamage,
+    };
+    #[cfg(feature = "effect")]
+    pub use valence_effect::EffectPlugin;
     #[cfg(feature = "equipment")]
     pub use valence_equipment::Equipment;
+    #[cfg(feature = "generation")]
+    pub use valence_generation::{ChunkGenerator, GeneratedLevel};
+    #[cfg(feature = "hunger")]
+    pub use valence_hunger::{HungerPlugin, HungerSettings};
+    #[cfg(feature = "inventory")]
+    pub use valence_inventory::block_placement::{BlockPlaceEvent, BlockPlacementPlugin};
+    #[cfg(feature = "inventory")]
+    pub use valence_inventory::item_entity::ItemEntityPlugin;
     #[cfg(feature = "inventory")]
     pub use valence_inventory::{
         CursorItem, Inventory, InventoryKind, InventoryWindow, InventoryWindowMut, OpenInventory,
@@ -121,6 +150,8 @@ pub mod prelude {
         ConnectionMode, ErasedNetworkCallbacks, NetworkCallbacks, NetworkSettings, NewClientInfo,
         SharedNetworkState,
     };
+    #[cfg(feature = "player_data")]
+    pub use valence_player_data::{PlayerDataFolder, PlayerDataStorage};
     #[cfg(feature = "player_list")]
     pub use valence_player_list::{PlayerList, PlayerListEntry};
     pub use valence_registry::biome::{Biome, BiomeId, BiomeRegistry};
@@ -135,6 +166,7 @@ pub mod prelude {
         ClientCommand, JumpWithHorseEvent, JumpWithHorseState, LeaveBedEvent, SneakEvent,
         SneakState, SprintEvent, SprintState,
     };
+    pub use valence_server::difficulty::WorldDifficulty;
     pub use valence_server::entity::hitbox::{Hitbox, HitboxShape};
     pub use valence_server::entity::{
         EntityAnimation, EntityKind, EntityLayerId, EntityManager, EntityStatus, HeadYaw, Look,
@@ -157,8 +189,8 @@ pub mod prelude {
     pub use valence_server::spawn::{ClientSpawnQuery, ClientSpawnQueryReadOnly, RespawnPosition};
     pub use valence_server::title::SetTitle as _;
     pub use valence_server::{
-        ident, BlockPos, ChunkPos, ChunkView, Despawned, Direction, GameMode, Hand, ItemKind,
-        ItemStack, Server, UniqueId,
+        ident, BlockPos, ChunkPos, ChunkView, Despawned, Direction, GameMode, Hand, ItemComponent,
+        ItemKind, ItemStack, Server, UniqueId,
     };
 
     pub use super::DefaultPlugins;
@@ -190,14 +222,15 @@ impl PluginGroup for DefaultPlugins {
             .add(KeepalivePlugin)
             .add(InteractEntityPlugin)
             .add(ClientSettingsPlugin)
+            .add(ConfigurationPlugin)
             .add(ActionPlugin)
             .add(TeleportPlugin)
-            .add(MessagePlugin)
             .add(CustomPayloadPlugin)
             .add(HandSwingPlugin)
             .add(InteractBlockPlugin)
             .add(InteractItemPlugin)
             .add(OpLevelPlugin)
+            .add(DifficultyPlugin)
             .add(ResourcePackPlugin)
             .add(StatusPlugin)
             .add(StatusEffectPlugin)
@@ -213,6 +246,11 @@ impl PluginGroup for DefaultPlugins {
             group = group.add(valence_network::NetworkPlugin)
         }
 
+        #[cfg(feature = "player_data")]
+        {
+            group = group.add(valence_player_data::PlayerDataPlugin)
+        }
+
         #[cfg(feature = "player_list")]
         {
             group = group.add(valence_player_list::PlayerListPlugin)
@@ -233,6 +271,11 @@ impl PluginGroup for DefaultPlugins {
             group = group.add(valence_anvil::AnvilPlugin)
         }
 
+        #[cfg(feature = "generation")]
+        {
+            group = group.add(valence_generation::ChunkGenerationPlugin)
+        }
+
         #[cfg(feature = "advancement")]
         {
             group = group.add(valence_advancement::AdvancementPlugin)
@@ -253,6 +296,11 @@ impl PluginGroup for DefaultPlugins {
             group = group.add(valence_boss_bar::BossBarPlugin)
         }
 
+        #[cfg(feature = "chat")]
+        {
+            group = group.add(valence_chat::ChatPlugin)
+        }
+
         #[cfg(feature = "command")]
         {
             group = group.add(valence_command::manager::CommandPlugin)
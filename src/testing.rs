@@ -11,11 +11,15 @@ use valence_ident::ident;
 use valence_network::NetworkPlugin;
 use valence_registry::{BiomeRegistry, DimensionTypeRegistry};
 use valence_server::client::{ClientBundle, ClientBundleArgs, ClientConnection, ReceivedPacket};
+use valence_server::entity::{OnGround, Position};
 use valence_server::keepalive::KeepaliveSettings;
+use valence_server::layer::chunk::UnloadedChunk;
 use valence_server::protocol::decode::PacketFrame;
 use valence_server::protocol::packets::play::{PlayerPositionLookS2c, TeleportConfirmC2s};
-use valence_server::protocol::{Decode, Encode, Packet, PacketDecoder, PacketEncoder, VarInt};
-use valence_server::{ChunkLayer, EntityLayer, Server, ServerSettings};
+use valence_server::protocol::{
+    Decode, Encode, Packet, PacketDecoder, PacketEncoder, PacketState, VarInt,
+};
+use valence_server::{BlockState, ChunkLayer, EntityLayer, Server, ServerSettings};
 
 use crate::DefaultPlugins;
 pub struct ScenarioSingleClient {
@@ -70,6 +74,50 @@ impl ScenarioSingleClient {
             layer,
         }
     }
+
+    /// Inserts a chunk at (0, 0) with a stone floor at y = 63 and puts the
+    /// client on the ground in the middle of it at (8.5, 64, 8.5).
+    ///
+    /// The app isn't updated, so plugins and resources can still be added
+    /// afterwards.
+    pub fn add_floor(&mut self) {
+        let mut layer = self
+            .app
+            .world_mut()
+            .get_mut::<ChunkLayer>(self.layer)
+            .unwrap();
+
+        layer.insert_chunk([0, 0], UnloadedChunk::new());
+
+        for z in 0..16 {
+            for x in 0..16 {
+                layer.set_block([x, 63, z], BlockState::STONE);
+            }
+        }
+
+        let mut client = self.app.world_mut().entity_mut(self.client);
+        client.get_mut::<Position>().unwrap().set([8.5, 64.0, 8.5]);
+        client.get_mut::<OnGround>().unwrap().0 = true;
+    }
+
+    /// Sets up a scenario with the given plugins added and the client
+    /// standing on a floor (see [`Self::add_floor`]).
+    ///
+    /// The app is updated until the client has confirmed its initial
+    /// teleport, and the packets sent so far are cleared.
+    pub fn with_floor<M>(plugins: impl Plugins<M>) -> Self {
+        let mut scenario = Self::new();
+
+        scenario.app.add_plugins(plugins);
+        scenario.add_floor();
+
+        scenario.app.update();
+        scenario.helper.confirm_initial_pending_teleports();
+        scenario.app.update();
+        scenario.helper.clear_received();
+
+        scenario
+    }
 }
 
 impl Default for ScenarioSingleClient {
@@ -125,8 +173,9 @@ impl MockClientConnection {
         }
     }
 
-    /// Injects a (Packet ID + data) frame to be received by the server.
-    fn inject_send(&self, mut bytes: BytesMut) {
+    /// Injects a (Packet ID + data) frame sent in `state` to be received by
+    /// the server.
+    fn inject_send(&self, mut bytes: BytesMut, state: PacketState) {
         let id = VarInt::decode_partial((&mut bytes).reader()).expect("failed to decode packet ID");
 
         self.inner
@@ -135,6 +184,7 @@ impl MockClientConnection {
             .recv_buf
             .push_back(ReceivedPacket {
                 timestamp: Instant::now(),
+                state,
                 id,
                 body: bytes.freeze(),
             });
@@ -198,7 +248,7 @@ impl MockClientHelper {
             .encode_with_id((&mut self.scratch).writer())
             .expect("failed to encode packet");
 
-        self.conn.inject_send(self.scratch.split());
+        self.conn.inject_send(self.scratch.split(), P::STATE);
     }
 
     /// Collect all packets that have been received by the client.
@@ -1,12 +1,25 @@
+mod block_placement;
 mod boss_bar;
+mod chat;
 mod client;
+mod combat;
+mod command;
+mod configuration;
+mod digging;
 mod equipment;
 mod example;
+mod generation;
 mod hunger;
 mod inventory;
+mod item_entity;
 mod layer;
+mod light;
+mod movement;
+mod physics;
+mod player_data;
 mod player_list;
 mod potions;
+mod raycast;
 mod scoreboard;
 mod weather;
 mod world_border;
@@ -0,0 +1,186 @@
+use bevy_app::App;
+use bevy_ecs::prelude::*;
+
+use crate::block::{BlockKind, PropName, PropValue};
+use crate::event_loop::EventLoopUpdate;
+use crate::inventory::block_placement::{
+    BlockPlaceEvent, BlockPlacementPlugin, BlockPlacementSet, PendingBlockPlacements,
+};
+use crate::inventory::Inventory;
+use crate::layer::ChunkLayer;
+use crate::math::Vec3;
+use crate::protocol::packets::play::PlayerInteractBlockC2s;
+use crate::testing::{MockClientHelper, ScenarioSingleClient};
+use crate::{BlockPos, BlockState, Direction, GameMode, Hand, ItemKind, ItemStack};
+
+/// Clicks the top face of the block at `position`.
+fn click_top(helper: &mut MockClientHelper, position: BlockPos) {
+    helper.send(&PlayerInteractBlockC2s {
+        hand: Hand::Main,
+        position,
+        face: Direction::Up,
+        cursor_pos: Vec3::new(0.5, 1.0, 0.5),
+        head_inside_block: false,
+        sequence: 1.into(),
+    });
+}
+
+fn block_at(app: &App, layer: Entity, position: BlockPos) -> BlockState {
+    app.world()
+        .get::<ChunkLayer>(layer)
+        .unwrap()
+        .block(position)
+        .unwrap()
+        .state
+}
+
+fn place_events(app: &App) -> Vec<BlockPlaceEvent> {
+    app.world()
+        .resource::<Events<BlockPlaceEvent>>()
+        .iter_current_update_events()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn place_block_consumes_item() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer,
+    } = ScenarioSingleClient::with_floor(BlockPlacementPlugin);
+
+    app.world_mut()
+        .get_mut::<Inventory>(client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::OakLog, 2));
+
+    click_top(&mut helper, BlockPos::new(10, 63, 8));
+
+    app.update();
+
+    let state = block_at(&app, layer, BlockPos::new(10, 64, 8));
+    assert_eq!(state.to_kind(), BlockKind::OakLog);
+    assert_eq!(state.get(PropName::Axis), Some(PropValue::Y));
+
+    let inventory = app.world().get::<Inventory>(client).unwrap();
+    assert_eq!(inventory.slot(36), &ItemStack::new(ItemKind::OakLog, 1));
+
+    let events = place_events(&app);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].position, BlockPos::new(10, 64, 8));
+    assert_eq!(events[0].against, BlockPos::new(10, 63, 8));
+}
+
+#[test]
+fn creative_placement_keeps_item() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer,
+    } = ScenarioSingleClient::with_floor(BlockPlacementPlugin);
+
+    app.world_mut()
+        .get_mut::<Inventory>(client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::Stone, 1));
+
+    *app.world_mut().get_mut::<GameMode>(client).unwrap() = GameMode::Creative;
+
+    click_top(&mut helper, BlockPos::new(10, 63, 8));
+
+    app.update();
+
+    assert_eq!(
+        block_at(&app, layer, BlockPos::new(10, 64, 8)),
+        BlockState::STONE
+    );
+
+    let inventory = app.world().get::<Inventory>(client).unwrap();
+    assert_eq!(inventory.slot(36), &ItemStack::new(ItemKind::Stone, 1));
+}
+
+#[test]
+fn placement_inside_player_is_rejected() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer,
+    } = ScenarioSingleClient::with_floor(BlockPlacementPlugin);
+
+    app.world_mut()
+        .get_mut::<Inventory>(client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::Stone, 1));
+
+    // The client is standing on this block.
+    click_top(&mut helper, BlockPos::new(8, 63, 8));
+
+    app.update();
+
+    assert!(block_at(&app, layer, BlockPos::new(8, 64, 8)).is_air());
+    assert!(place_events(&app).is_empty());
+
+    let inventory = app.world().get::<Inventory>(client).unwrap();
+    assert_eq!(inventory.slot(36), &ItemStack::new(ItemKind::Stone, 1));
+}
+
+#[test]
+fn cancelled_placement_is_not_applied() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer,
+    } = ScenarioSingleClient::with_floor(BlockPlacementPlugin);
+
+    app.world_mut()
+        .get_mut::<Inventory>(client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::Stone, 1));
+
+    app.add_systems(
+        EventLoopUpdate,
+        (|mut pending: ResMut<PendingBlockPlacements>| pending.clear())
+            .in_set(BlockPlacementSet::Modify),
+    );
+
+    click_top(&mut helper, BlockPos::new(10, 63, 8));
+
+    app.update();
+
+    assert!(block_at(&app, layer, BlockPos::new(10, 64, 8)).is_air());
+    assert!(place_events(&app).is_empty());
+
+    let inventory = app.world().get::<Inventory>(client).unwrap();
+    assert_eq!(inventory.slot(36), &ItemStack::new(ItemKind::Stone, 1));
+}
+
+#[test]
+fn place_door_with_upper_half() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer,
+    } = ScenarioSingleClient::with_floor(BlockPlacementPlugin);
+
+    app.world_mut()
+        .get_mut::<Inventory>(client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::OakDoor, 1));
+
+    click_top(&mut helper, BlockPos::new(10, 63, 8));
+
+    app.update();
+
+    let lower = block_at(&app, layer, BlockPos::new(10, 64, 8));
+    let upper = block_at(&app, layer, BlockPos::new(10, 65, 8));
+
+    assert_eq!(lower.get(PropName::Half), Some(PropValue::Lower));
+    assert_eq!(upper.get(PropName::Half), Some(PropValue::Upper));
+    assert_eq!(lower.get(PropName::Facing), upper.get(PropName::Facing));
+}
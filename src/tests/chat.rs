@@ -0,0 +1,341 @@
+use bevy_app::App;
+use bevy_ecs::event::Events;
+use rsa::pkcs8::EncodePublicKey;
+use rsa::{Pkcs1v15Sign, RsaPrivateKey};
+use sha2::{Digest, Sha256};
+
+use crate::chat::{ChatMessageEvent, ChatSessionEvent, ChatSettings, ChatState};
+use crate::protocol::packets::play::{
+    ChatMessageC2s, ChatMessageS2c, DisconnectS2c, MessageAcknowledgmentC2s, PlayerSessionC2s,
+    ProfilelessChatMessageS2c,
+};
+use crate::protocol::{Bounded, FixedBitSet, VarInt};
+use crate::testing::{MockClientHelper, ScenarioSingleClient};
+use crate::uuid::{Builder, Uuid};
+use crate::UniqueId;
+
+/// Creates a scenario where the client has joined and has the given UUID.
+/// Chat messages are broadcast to all clients.
+fn chat_scenario(uuid: Uuid) -> ScenarioSingleClient {
+    let mut scenario = ScenarioSingleClient::new();
+
+    scenario
+        .app
+        .world_mut()
+        .resource_mut::<ChatSettings>()
+        .broadcast = true;
+
+    scenario
+        .app
+        .world_mut()
+        .get_mut::<UniqueId>(scenario.client)
+        .unwrap()
+        .0 = uuid;
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    scenario
+}
+
+fn online_uuid() -> Uuid {
+    Builder::from_random_bytes([7; 16]).into_uuid()
+}
+
+fn offline_uuid() -> Uuid {
+    Builder::from_md5_bytes([7; 16]).into_uuid()
+}
+
+fn send_session(helper: &mut MockClientHelper) {
+    helper.send(&PlayerSessionC2s {
+        session_id: Uuid::from_u128(1),
+        expires_at: i64::MAX,
+        public_key_data: Bounded(&[1_u8, 2, 3][..]),
+        key_signature: Bounded(&[4_u8, 5, 6][..]),
+    });
+}
+
+const SESSION_ID: Uuid = Uuid::from_u128(1);
+
+/// Sends a chat session with the public key of `key`.
+fn send_signed_session(helper: &mut MockClientHelper, key: &RsaPrivateKey) {
+    let der = key.to_public_key().to_public_key_der().unwrap();
+
+    helper.send(&PlayerSessionC2s {
+        session_id: SESSION_ID,
+        expires_at: i64::MAX,
+        public_key_data: Bounded(der.as_bytes()),
+        key_signature: Bounded(&[][..]),
+    });
+}
+
+/// Signs the first chat message of a session like the client does.
+fn sign_message(
+    key: &RsaPrivateKey,
+    sender: Uuid,
+    salt: u64,
+    timestamp: u64,
+    message: &str,
+) -> [u8; 256] {
+    let mut hasher = Sha256::new();
+
+    hasher.update(1_i32.to_be_bytes());
+    hasher.update(sender.as_bytes());
+    hasher.update(SESSION_ID.as_bytes());
+    hasher.update(0_i32.to_be_bytes());
+    hasher.update(salt.to_be_bytes());
+    hasher.update((timestamp / 1000).to_be_bytes());
+    hasher.update((message.len() as i32).to_be_bytes());
+    hasher.update(message.as_bytes());
+    hasher.update(0_i32.to_be_bytes());
+
+    key.sign(Pkcs1v15Sign::new::<Sha256>(), &hasher.finalize())
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+fn chat_events(app: &App) -> Vec<ChatMessageEvent> {
+    app.world()
+        .resource::<Events<ChatMessageEvent>>()
+        .iter_current_update_events()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn unsigned_message_is_relayed_as_system_chat() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = chat_scenario(offline_uuid());
+
+    helper.send(&ChatMessageC2s {
+        message: Bounded("hello"),
+        timestamp: 1000,
+        salt: 0,
+        signature: None,
+        message_count: VarInt(0),
+        acknowledgement: FixedBitSet([0; 3]),
+    });
+
+    app.update();
+
+    let events = chat_events(&app);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].client, client);
+    assert_eq!(&*events[0].message, "hello");
+    assert!(events[0].signature.is_none());
+
+    let sent_packets = helper.collect_received();
+    sent_packets.assert_count::<ProfilelessChatMessageS2c>(1);
+    sent_packets.assert_count::<ChatMessageS2c>(0);
+}
+
+#[test]
+fn messages_are_not_broadcast_by_default() {
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    helper.send(&ChatMessageC2s {
+        message: Bounded("hello"),
+        timestamp: 1000,
+        salt: 0,
+        signature: None,
+        message_count: VarInt(0),
+        acknowledgement: FixedBitSet([0; 3]),
+    });
+
+    app.update();
+
+    assert_eq!(chat_events(&app).len(), 1);
+
+    let sent_packets = helper.collect_received();
+    sent_packets.assert_count::<ProfilelessChatMessageS2c>(0);
+    sent_packets.assert_count::<ChatMessageS2c>(0);
+}
+
+#[test]
+fn signed_message_is_relayed_as_player_chat() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = chat_scenario(online_uuid());
+
+    let key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+
+    send_signed_session(&mut helper, &key);
+
+    app.update();
+
+    let session = app.world().get::<ChatState>(client).unwrap().session();
+    assert_eq!(session.unwrap().session_id(), SESSION_ID);
+
+    helper.clear_received();
+
+    let signature = sign_message(&key, online_uuid(), 42, 1_000_000, "hello");
+
+    helper.send(&ChatMessageC2s {
+        message: Bounded("hello"),
+        timestamp: 1_000_000,
+        salt: 42,
+        signature: Some(&signature),
+        message_count: VarInt(0),
+        acknowledgement: FixedBitSet([0; 3]),
+    });
+
+    app.update();
+
+    let events = chat_events(&app);
+    assert_eq!(events.len(), 1);
+
+    let signed = events[0].signature.as_deref().unwrap();
+    assert_eq!(signed.sender, online_uuid());
+    assert_eq!(signed.index, 0);
+    assert_eq!(*signed.signature, signature);
+
+    let sent_packets = helper.collect_received();
+    sent_packets.assert_count::<ChatMessageS2c>(1);
+    sent_packets.assert_count::<ProfilelessChatMessageS2c>(0);
+    sent_packets.assert_count::<DisconnectS2c>(0);
+}
+
+#[test]
+fn tampered_signature_disconnects() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = chat_scenario(online_uuid());
+
+    let key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+
+    send_signed_session(&mut helper, &key);
+
+    app.update();
+    helper.clear_received();
+
+    let mut signature = sign_message(&key, online_uuid(), 42, 1_000_000, "hello");
+    signature[100] ^= 1;
+
+    helper.send(&ChatMessageC2s {
+        message: Bounded("hello"),
+        timestamp: 1_000_000,
+        salt: 42,
+        signature: Some(&signature),
+        message_count: VarInt(0),
+        acknowledgement: FixedBitSet([0; 3]),
+    });
+
+    app.update();
+
+    assert!(chat_events(&app).is_empty());
+    assert!(app.world().get_entity(client).is_none());
+
+    let sent_packets = helper.collect_received();
+    sent_packets.assert_count::<ChatMessageS2c>(0);
+    sent_packets.assert_count::<DisconnectS2c>(1);
+}
+
+#[test]
+fn illegal_characters_disconnect() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = chat_scenario(offline_uuid());
+
+    helper.send(&ChatMessageC2s {
+        message: Bounded("§chello"),
+        timestamp: 1000,
+        salt: 0,
+        signature: None,
+        message_count: VarInt(0),
+        acknowledgement: FixedBitSet([0; 3]),
+    });
+
+    app.update();
+
+    assert!(chat_events(&app).is_empty());
+    assert!(app.world().get_entity(client).is_none());
+
+    helper.collect_received().assert_count::<DisconnectS2c>(1);
+}
+
+#[test]
+fn invalid_acknowledgement_disconnects() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = chat_scenario(offline_uuid());
+
+    // No messages were sent to the client, so there is nothing to acknowledge.
+    helper.send(&MessageAcknowledgmentC2s {
+        message_count: VarInt(5),
+    });
+
+    app.update();
+
+    assert!(app.world().get_entity(client).is_none());
+
+    helper.collect_received().assert_count::<DisconnectS2c>(1);
+}
+
+#[test]
+fn offline_session_is_ignored() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = chat_scenario(offline_uuid());
+
+    send_session(&mut helper);
+
+    app.update();
+
+    assert!(app
+        .world()
+        .get::<ChatState>(client)
+        .unwrap()
+        .session()
+        .is_none());
+    assert!(app
+        .world()
+        .resource::<Events<ChatSessionEvent>>()
+        .is_empty());
+
+    helper.collect_received().assert_count::<DisconnectS2c>(0);
+}
+
+#[test]
+fn invalid_public_key_disconnects() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = chat_scenario(online_uuid());
+
+    send_session(&mut helper);
+
+    app.update();
+
+    assert!(app.world().get_entity(client).is_none());
+
+    helper.collect_received().assert_count::<DisconnectS2c>(1);
+}
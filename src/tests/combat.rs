@@ -0,0 +1,126 @@
+use bevy_ecs::entity::Entity;
+
+use crate::combat::{CombatPlugin, DamageEvent, Dead};
+use crate::entity::cow::CowEntityBundle;
+use crate::entity::living::Health;
+use crate::entity::{EntityLayerId, Position};
+use crate::protocol::packets::play::{DeathMessageS2c, EntityDamageS2c};
+use crate::testing::ScenarioSingleClient;
+use crate::{ident, GameMode};
+
+/// Spawns a cow with 10 health next to the client and updates the app so
+/// that the cow is initialized.
+fn spawn_cow(scenario: &mut ScenarioSingleClient) -> Entity {
+    let cow = scenario
+        .app
+        .world_mut()
+        .spawn(CowEntityBundle {
+            position: Position::new([9.5, 64.0, 9.5]),
+            layer: EntityLayerId(scenario.layer),
+            living_health: Health(10.0),
+            ..Default::default()
+        })
+        .id();
+
+    scenario.app.update();
+    scenario.helper.clear_received();
+
+    cow
+}
+
+#[test]
+fn damage_reduces_health() {
+    let mut scenario = ScenarioSingleClient::with_floor(CombatPlugin);
+    let cow = spawn_cow(&mut scenario);
+
+    scenario
+        .app
+        .world_mut()
+        .send_event(DamageEvent::new(cow, ident!("generic"), 4.0));
+
+    scenario.app.update();
+
+    assert_eq!(scenario.app.world().get::<Health>(cow).unwrap().0, 6.0);
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<EntityDamageS2c>(1);
+}
+
+#[test]
+fn invulnerability_blocks_weaker_damage() {
+    let mut scenario = ScenarioSingleClient::with_floor(CombatPlugin);
+    let cow = spawn_cow(&mut scenario);
+
+    scenario
+        .app
+        .world_mut()
+        .send_event(DamageEvent::new(cow, ident!("generic"), 4.0));
+
+    scenario.app.update();
+
+    // Weaker damage during the invulnerability is ignored.
+    scenario
+        .app
+        .world_mut()
+        .send_event(DamageEvent::new(cow, ident!("generic"), 2.0));
+
+    scenario.app.update();
+
+    assert_eq!(scenario.app.world().get::<Health>(cow).unwrap().0, 6.0);
+
+    // Stronger damage only deals the difference.
+    scenario
+        .app
+        .world_mut()
+        .send_event(DamageEvent::new(cow, ident!("generic"), 5.0));
+
+    scenario.app.update();
+
+    assert_eq!(scenario.app.world().get::<Health>(cow).unwrap().0, 5.0);
+}
+
+#[test]
+fn creative_players_are_invulnerable() {
+    let mut scenario = ScenarioSingleClient::with_floor(CombatPlugin);
+    let client = scenario.client;
+
+    *scenario
+        .app
+        .world_mut()
+        .get_mut::<GameMode>(client)
+        .unwrap() = GameMode::Creative;
+
+    let health = scenario.app.world().get::<Health>(client).unwrap().0;
+
+    scenario
+        .app
+        .world_mut()
+        .send_event(DamageEvent::new(client, ident!("generic"), 4.0));
+
+    scenario.app.update();
+
+    assert_eq!(
+        scenario.app.world().get::<Health>(client).unwrap().0,
+        health
+    );
+}
+
+#[test]
+fn lethal_damage_kills_player() {
+    let mut scenario = ScenarioSingleClient::with_floor(CombatPlugin);
+    let client = scenario.client;
+
+    scenario.app.world_mut().send_event(DamageEvent::new(
+        client,
+        ident!("generic_kill"),
+        f32::INFINITY,
+    ));
+
+    scenario.app.update();
+
+    assert_eq!(scenario.app.world().get::<Health>(client).unwrap().0, 0.0);
+    assert!(scenario.app.world().get::<Dead>(client).is_some());
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<DeathMessageS2c>(1);
+}
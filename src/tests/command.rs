@@ -0,0 +1,335 @@
+use std::collections::{BTreeSet, HashMap};
+
+use bevy_app::App;
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::RunSystemOnce;
+
+use crate::command::graph::CommandGraphBuilder;
+use crate::command::parsers::{CommandArg, CommandArgParseError, EntitySelector};
+use crate::command::scopes::CommandScopes;
+use crate::command::selector::{EntityTags, SelectorQuery};
+use crate::command::source::{CommandReplyEvent, CommandSource, ReplyChannel};
+use crate::command::{
+    CommandError, CommandExecutionEvent, CommandFailedEvent, CommandFeedbackSettings,
+    CommandRegistry,
+};
+use crate::entity::zombie::ZombieEntityBundle;
+use crate::entity::{EntityLayerId, Position};
+use crate::protocol::packets::play::GameMessageS2c;
+use crate::scoreboard::{
+    Objective, ObjectiveBundle, ObjectiveScores, Team, TeamBundle, TeamMembers,
+};
+use crate::testing::{create_mock_client, ScenarioSingleClient};
+
+fn resolve(app: &mut App, selector: &str, executor: Entity) -> Vec<Entity> {
+    let selector = EntitySelector::arg_from_str(selector).unwrap();
+
+    app.world_mut().run_system_once_with(
+        (selector, executor),
+        |In((selector, executor)): In<(EntitySelector, Entity)>, query: SelectorQuery| {
+            query.resolve(&selector, executor)
+        },
+    )
+}
+
+fn spawn_zombie(app: &mut App, layer: Entity, pos: [f64; 3]) -> Entity {
+    app.world_mut()
+        .spawn(ZombieEntityBundle {
+            layer: EntityLayerId(layer),
+            position: Position::new(pos),
+            ..Default::default()
+        })
+        .id()
+}
+
+#[test]
+fn selector_filters_by_type_and_distance() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        layer,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let near = spawn_zombie(&mut app, layer, [2.0, 0.0, 0.0]);
+    let far = spawn_zombie(&mut app, layer, [20.0, 0.0, 0.0]);
+
+    app.update();
+
+    assert_eq!(
+        resolve(&mut app, "@e[type=zombie,distance=..5]", client),
+        [near]
+    );
+    assert_eq!(
+        resolve(&mut app, "@e[type=!player,sort=furthest]", client),
+        [far, near]
+    );
+    assert_eq!(
+        resolve(&mut app, "@e[type=zombie,dx=3,dy=1,dz=1]", client),
+        [near]
+    );
+    assert_eq!(resolve(&mut app, "@s", client), [client]);
+    assert_eq!(resolve(&mut app, "@s", near), [near]);
+    assert_eq!(resolve(&mut app, "@a", near), [client]);
+    assert_eq!(resolve(&mut app, "TEST", near), [client]);
+    assert!(resolve(&mut app, "@a[gamemode=!survival,gamemode=!creative]", near).is_empty());
+}
+
+#[test]
+fn nearest_player_is_selected() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        layer,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let (mut other, _) = create_mock_client("other");
+    other.player.layer.0 = layer;
+    other.player.position = Position::new([10.0, 0.0, 0.0]);
+    let other = app.world_mut().spawn(other).id();
+
+    let zombie = spawn_zombie(&mut app, layer, [8.0, 0.0, 0.0]);
+
+    app.update();
+
+    assert_eq!(resolve(&mut app, "@p", zombie), [other]);
+    assert_eq!(
+        resolve(&mut app, "@a[sort=nearest]", zombie),
+        [other, client]
+    );
+    assert_eq!(
+        resolve(&mut app, "@a[x=0,y=0,z=0,limit=1,sort=nearest]", zombie),
+        [client]
+    );
+    assert_eq!(resolve(&mut app, "@r[limit=2]", zombie).len(), 2);
+}
+
+#[test]
+fn selector_filters_by_tags_teams_and_scores() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        layer,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let tagged = spawn_zombie(&mut app, layer, [1.0, 0.0, 0.0]);
+    let untagged = spawn_zombie(&mut app, layer, [2.0, 0.0, 0.0]);
+
+    app.world_mut()
+        .entity_mut(tagged)
+        .insert(EntityTags(BTreeSet::from(["boss".to_owned()])));
+
+    app.world_mut().spawn(TeamBundle {
+        name: Team::new("red"),
+        members: TeamMembers::with_members(["test"]),
+        layer: EntityLayerId(layer),
+        ..Default::default()
+    });
+
+    app.world_mut().spawn(ObjectiveBundle {
+        name: Objective::new("kills"),
+        scores: ObjectiveScores::with_map([("test".to_owned(), 3)]),
+        layer: EntityLayerId(layer),
+        ..Default::default()
+    });
+
+    app.update();
+
+    assert_eq!(resolve(&mut app, "@e[tag=boss]", client), [tagged]);
+    assert_eq!(
+        resolve(&mut app, "@e[type=zombie,tag=]", client),
+        [untagged]
+    );
+    assert_eq!(resolve(&mut app, "@e[team=red]", client), [client]);
+    assert_eq!(
+        resolve(&mut app, "@e[type=zombie,team=!red]", client).len(),
+        2
+    );
+    assert_eq!(
+        resolve(&mut app, "@a[scores={kills=2..}]", client),
+        [client]
+    );
+    assert!(resolve(&mut app, "@a[scores={kills=..2}]", client).is_empty());
+}
+
+struct GiveCommand;
+
+/// Registers the commands `give <count> [<target>]` and `give all`.
+fn add_give_command(app: &mut App) {
+    let mut registry = app.world_mut().resource_mut::<CommandRegistry>();
+    let mut executables = HashMap::new();
+    let mut parsers = HashMap::new();
+    let mut modifiers = HashMap::new();
+
+    {
+        let mut builder = CommandGraphBuilder::<GiveCommand>::new(
+            &mut registry,
+            &mut executables,
+            &mut parsers,
+            &mut modifiers,
+        );
+
+        let give = builder.root().literal("give").id();
+
+        builder
+            .argument("count")
+            .with_parser::<i32>()
+            .with_executable(|_| GiveCommand)
+            .argument("target")
+            .with_parser::<String>()
+            .with_executable(|_| GiveCommand);
+
+        builder
+            .at(give)
+            .literal("all")
+            .with_executable(|_| GiveCommand);
+    }
+
+    registry.parsers.extend(parsers);
+    registry.executables.extend(executables.keys());
+}
+
+fn run_command(app: &mut App, executor: Entity, command: &str) -> Vec<CommandFailedEvent> {
+    app.world_mut().send_event(CommandExecutionEvent {
+        command: command.to_owned(),
+        executor,
+    });
+
+    app.update();
+
+    app.world()
+        .resource::<Events<CommandFailedEvent>>()
+        .iter_current_update_events()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn failed_commands_report_error_and_usage() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::new();
+
+    add_give_command(&mut app);
+
+    assert!(run_command(&mut app, client, "give 3").is_empty());
+    assert!(run_command(&mut app, client, "give 3 test").is_empty());
+    assert!(run_command(&mut app, client, "give all").is_empty());
+
+    let [failure] = &run_command(&mut app, client, "foo bar")[..] else {
+        panic!("expected one failure");
+    };
+    assert_eq!(failure.error, CommandError::UnknownCommand);
+    assert_eq!(failure.cursor, 0);
+    assert!(failure.usage.is_empty());
+
+    let [failure] = &run_command(&mut app, client, "give")[..] else {
+        panic!("expected one failure");
+    };
+    assert_eq!(failure.error, CommandError::UnknownCommand);
+    assert_eq!(failure.cursor, 4);
+    assert_eq!(failure.usage, ["give <count> [<target>]", "give all"]);
+
+    let [failure] = &run_command(&mut app, client, "give abc")[..] else {
+        panic!("expected one failure");
+    };
+    assert_eq!(
+        failure.error,
+        CommandError::InvalidArgument(CommandArgParseError::InvalidArgument {
+            expected: "integer".to_owned(),
+            got: "abc".to_owned(),
+        })
+    );
+    assert_eq!(failure.cursor, 5);
+    assert_eq!(failure.usage, ["give <count> [<target>]", "give all"]);
+
+    let [failure] = &run_command(&mut app, client, "give all now")[..] else {
+        panic!("expected one failure");
+    };
+    assert_eq!(failure.error, CommandError::UnknownArgument);
+    assert_eq!(failure.cursor, 9);
+    assert!(failure.usage.is_empty());
+}
+
+#[test]
+fn command_errors_are_sent_to_client() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    add_give_command(&mut app);
+
+    app.update();
+    helper.clear_received();
+
+    run_command(&mut app, client, "give");
+
+    // the error, the context and the two usages
+    helper.collect_received().assert_count::<GameMessageS2c>(4);
+
+    app.world_mut()
+        .resource_mut::<CommandFeedbackSettings>()
+        .send_errors = false;
+
+    assert_eq!(run_command(&mut app, client, "give").len(), 1);
+
+    helper.collect_received().assert_count::<GameMessageS2c>(0);
+}
+
+#[test]
+fn replies_are_sent_through_reply_channel() {
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    add_give_command(&mut app);
+
+    let (sender, receiver) = flume::unbounded();
+    let executor = app
+        .world_mut()
+        .spawn((
+            CommandSource::Rcon,
+            CommandScopes::new(),
+            ReplyChannel(sender),
+        ))
+        .id();
+
+    app.update();
+    helper.clear_received();
+
+    run_command(&mut app, executor, "give abc");
+
+    let replies: Vec<_> = receiver
+        .try_iter()
+        .map(|reply| reply.to_legacy_lossy())
+        .collect();
+
+    assert_eq!(
+        replies,
+        [
+            "invalid argument, expected integer got abc",
+            "give <--[HERE]",
+            "/give <count> [<target>]",
+            "/give all",
+        ]
+    );
+
+    app.world_mut()
+        .send_event(CommandReplyEvent::new(executor, "Gave 3 items"));
+    app.update();
+
+    assert_eq!(
+        receiver.try_recv().unwrap().to_legacy_lossy(),
+        "Gave 3 items"
+    );
+
+    // nothing is sent to the client
+    helper.collect_received().assert_count::<GameMessageS2c>(0);
+}
@@ -0,0 +1,96 @@
+use std::borrow::Cow;
+
+use bevy_ecs::world::Command;
+
+use crate::client::Client;
+use crate::configuration::{ReconfigureClient, Reconfiguring};
+use crate::layer::chunk::UnloadedChunk;
+use crate::layer::ChunkLayer;
+use crate::protocol::packets::configuration::{
+    FinishConfigurationS2c, SelectKnownPacksC2s, SelectKnownPacksS2c,
+};
+use crate::protocol::packets::play::game_state_change_s2c::GameEventKind;
+use crate::protocol::packets::play::{
+    ChunkDataS2c, ConfigurationAcknowledgedC2s, GameJoinS2c, GameStateChangeS2c,
+    PlayerPositionLookS2c, StartConfigurationS2c,
+};
+use crate::protocol::{PacketState, WritePacket};
+use crate::testing::ScenarioSingleClient;
+use crate::ChunkPos;
+
+#[test]
+fn reconfigure_client_and_rejoin() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer,
+    } = ScenarioSingleClient::new();
+
+    app.world_mut()
+        .get_mut::<ChunkLayer>(layer)
+        .unwrap()
+        .insert_chunk(ChunkPos::new(0, 0), UnloadedChunk::new());
+
+    app.update();
+
+    helper.confirm_initial_pending_teleports();
+    helper.clear_received();
+
+    ReconfigureClient { client }.apply(app.world_mut());
+
+    // Play packets are discarded once the client is sent to configuration.
+    app.world_mut()
+        .get_mut::<Client>(client)
+        .unwrap()
+        .write_packet(&GameStateChangeS2c {
+            kind: GameEventKind::StartRaining,
+            value: 0.0,
+        });
+
+    app.update();
+
+    {
+        let recvd = helper.collect_received();
+
+        recvd.assert_count::<StartConfigurationS2c>(1);
+        recvd.assert_count::<GameStateChangeS2c>(0);
+        recvd.assert_count::<SelectKnownPacksS2c>(0);
+    }
+
+    // The client stays in the play state until it acknowledges the switch.
+    let client_ref = app.world().get::<Client>(client).unwrap();
+    assert_eq!(client_ref.state(), PacketState::Play);
+    assert_eq!(
+        app.world().get::<Reconfiguring>(client),
+        Some(&Reconfiguring::AwaitingAck)
+    );
+
+    helper.send(&ConfigurationAcknowledgedC2s);
+
+    app.update();
+
+    helper.collect_received().first::<SelectKnownPacksS2c>();
+
+    let client_ref = app.world().get::<Client>(client).unwrap();
+    assert_eq!(client_ref.state(), PacketState::Configuration);
+
+    helper.send(&SelectKnownPacksC2s {
+        packs: Cow::Borrowed(&[]),
+    });
+
+    app.update();
+
+    {
+        let recvd = helper.collect_received();
+
+        recvd.first::<FinishConfigurationS2c>();
+        recvd.first::<GameJoinS2c>();
+        recvd.assert_count::<ChunkDataS2c>(1);
+        recvd.assert_count::<PlayerPositionLookS2c>(1);
+    }
+
+    let client_ref = app.world().get::<Client>(client).unwrap();
+    assert_eq!(client_ref.state(), PacketState::Play);
+    assert!(app.world().get::<Reconfiguring>(client).is_none());
+}
@@ -0,0 +1,151 @@
+use bevy_app::App;
+use bevy_ecs::event::Events;
+
+use crate::action::{
+    DiggingEvent, DiggingSettings, DiggingState, DiggingViolation, DiggingViolationEvent,
+};
+use crate::inventory::Inventory;
+use crate::protocol::packets::play::player_action_c2s::PlayerAction;
+use crate::protocol::packets::play::{BlockUpdateS2c, PlayerActionC2s};
+use crate::testing::{MockClientHelper, ScenarioSingleClient};
+use crate::{BlockPos, Direction, ItemKind, ItemStack};
+
+fn send_action(helper: &mut MockClientHelper, action: PlayerAction, position: BlockPos) {
+    helper.send(&PlayerActionC2s {
+        action,
+        position,
+        direction: Direction::Up,
+        sequence: 1.into(),
+    });
+}
+
+fn digging_events(app: &App) -> Vec<DiggingEvent> {
+    app.world()
+        .resource::<Events<DiggingEvent>>()
+        .iter_current_update_events()
+        .copied()
+        .collect()
+}
+
+fn violations(app: &App) -> Vec<DiggingViolationEvent> {
+    app.world()
+        .resource::<Events<DiggingViolationEvent>>()
+        .iter_current_update_events()
+        .copied()
+        .collect()
+}
+
+#[test]
+fn digging_out_of_reach_is_rejected() {
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_floor(());
+
+    app.insert_resource(DiggingSettings {
+        check_reach: true,
+        ..Default::default()
+    });
+
+    let near = BlockPos::new(8, 63, 9);
+    send_action(&mut helper, PlayerAction::StartDestroyBlock, near);
+
+    app.update();
+
+    assert_eq!(digging_events(&app).len(), 1);
+    assert!(violations(&app).is_empty());
+
+    let far = BlockPos::new(8, 63, 15);
+    send_action(&mut helper, PlayerAction::StartDestroyBlock, far);
+
+    app.update();
+
+    assert!(digging_events(&app).is_empty());
+
+    let violations = violations(&app);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].position, far);
+    assert!(matches!(
+        violations[0].violation,
+        DiggingViolation::Reach { .. }
+    ));
+
+    // The block is resent so the client doesn't see it broken.
+    let sent_packets = helper.collect_received();
+    sent_packets.assert_count::<BlockUpdateS2c>(1);
+    assert_eq!(sent_packets.first::<BlockUpdateS2c>().position, far);
+}
+
+#[test]
+fn breaking_too_fast_is_rejected() {
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_floor(());
+
+    app.insert_resource(DiggingSettings {
+        check_break_speed: true,
+        ..Default::default()
+    });
+
+    let pos = BlockPos::new(8, 63, 8);
+
+    send_action(&mut helper, PlayerAction::StartDestroyBlock, pos);
+    app.update();
+    send_action(&mut helper, PlayerAction::StopDestroyBlock, pos);
+    app.update();
+
+    assert!(digging_events(&app).is_empty());
+
+    let violations = violations(&app);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].state, DiggingState::Stop);
+    assert!(matches!(
+        violations[0].violation,
+        DiggingViolation::BreakSpeed { progress } if progress < 0.1
+    ));
+}
+
+#[test]
+fn breaking_with_tool_is_accepted() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_floor(());
+
+    app.insert_resource(DiggingSettings {
+        check_break_speed: true,
+        ..Default::default()
+    });
+
+    app.world_mut()
+        .get_mut::<Inventory>(client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::DiamondPickaxe, 1));
+
+    // Sync the held item.
+    app.update();
+
+    let pos = BlockPos::new(8, 63, 8);
+
+    send_action(&mut helper, PlayerAction::StartDestroyBlock, pos);
+    app.update();
+
+    // A diamond pickaxe breaks stone in 6 ticks.
+    for _ in 0..5 {
+        app.update();
+    }
+
+    send_action(&mut helper, PlayerAction::StopDestroyBlock, pos);
+    app.update();
+
+    assert!(violations(&app).is_empty());
+
+    let events = digging_events(&app);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].state, DiggingState::Stop);
+}
@@ -31,7 +31,7 @@ fn test_only_send_update_to_other_players() {
         .get_mut::<Equipment>(client)
         .expect("could not get player equipment");
 
-    player_equipment.set_chest(ItemStack::new(ItemKind::DiamondChestplate, 1, None));
+    player_equipment.set_chest(ItemStack::new(ItemKind::DiamondChestplate, 1));
 
     app.update();
 
@@ -69,8 +69,8 @@ fn test_multiple_entities() {
         .get_mut::<Equipment>(zombie)
         .expect("could not get entity equipment");
 
-    equipment.set_chest(ItemStack::new(ItemKind::DiamondChestplate, 1, None));
-    equipment.set_head(ItemStack::new(ItemKind::DiamondHelmet, 1, None));
+    equipment.set_chest(ItemStack::new(ItemKind::DiamondChestplate, 1));
+    equipment.set_head(ItemStack::new(ItemKind::DiamondHelmet, 1));
 
     app.update();
 
@@ -86,8 +86,8 @@ fn test_multiple_entities() {
         .expect("could not get entity equipment");
 
     // Set the zombie's equipment to the same items
-    equipment.set_chest(ItemStack::new(ItemKind::DiamondChestplate, 1, None));
-    equipment.set_head(ItemStack::new(ItemKind::DiamondHelmet, 1, None));
+    equipment.set_chest(ItemStack::new(ItemKind::DiamondChestplate, 1));
+    equipment.set_head(ItemStack::new(ItemKind::DiamondHelmet, 1));
 
     app.update();
 
@@ -128,8 +128,8 @@ fn test_update_on_load_entity() {
         .get_mut::<Equipment>(zombie)
         .expect("could not get entity equipment");
 
-    equipment.set_chest(ItemStack::new(ItemKind::DiamondChestplate, 1, None));
-    equipment.set_head(ItemStack::new(ItemKind::DiamondHelmet, 1, None));
+    equipment.set_chest(ItemStack::new(ItemKind::DiamondChestplate, 1));
+    equipment.set_head(ItemStack::new(ItemKind::DiamondHelmet, 1));
 
     app.update();
 
@@ -272,7 +272,7 @@ fn test_inventory_sync_from_equipment() {
         .get_mut::<Equipment>(client)
         .expect("could not get player equipment");
 
-    player_equipment.set_chest(ItemStack::new(ItemKind::DiamondChestplate, 1, None));
+    player_equipment.set_chest(ItemStack::new(ItemKind::DiamondChestplate, 1));
 
     app.update();
 
@@ -290,12 +290,12 @@ fn test_inventory_sync_from_equipment() {
     // after the equipment change
     assert_eq!(
         *player_inventory.slot(PlayerInventory::SLOT_CHEST),
-        ItemStack::new(ItemKind::DiamondChestplate, 1, None)
+        ItemStack::new(ItemKind::DiamondChestplate, 1)
     );
 
     assert_eq!(
         *player_equipment.chest(),
-        ItemStack::new(ItemKind::DiamondChestplate, 1, None)
+        ItemStack::new(ItemKind::DiamondChestplate, 1)
     );
 }
 
@@ -323,7 +323,7 @@ fn test_equipment_sync_from_inventory() {
 
     player_inventory.set_slot(
         PlayerInventory::SLOT_CHEST,
-        ItemStack::new(ItemKind::DiamondChestplate, 1, None),
+        ItemStack::new(ItemKind::DiamondChestplate, 1),
     );
 
     app.update();
@@ -342,12 +342,12 @@ fn test_equipment_sync_from_inventory() {
     // after the inventory change
     assert_eq!(
         *player_inventory.slot(PlayerInventory::SLOT_CHEST),
-        ItemStack::new(ItemKind::DiamondChestplate, 1, None)
+        ItemStack::new(ItemKind::DiamondChestplate, 1)
     );
 
     assert_eq!(
         *player_equipment.chest(),
-        ItemStack::new(ItemKind::DiamondChestplate, 1, None)
+        ItemStack::new(ItemKind::DiamondChestplate, 1)
     );
 }
 
@@ -373,7 +373,7 @@ fn test_equipment_priority_over_inventory() {
 
     player_inventory.set_slot(
         PlayerInventory::SLOT_CHEST,
-        ItemStack::new(ItemKind::DiamondChestplate, 1, None),
+        ItemStack::new(ItemKind::DiamondChestplate, 1),
     );
 
     let mut player_equipment = app
@@ -381,7 +381,7 @@ fn test_equipment_priority_over_inventory() {
         .get_mut::<Equipment>(client)
         .expect("could not get player equipment");
 
-    player_equipment.set_chest(ItemStack::new(ItemKind::GoldenChestplate, 1, None));
+    player_equipment.set_chest(ItemStack::new(ItemKind::GoldenChestplate, 1));
 
     app.update();
 
@@ -399,12 +399,12 @@ fn test_equipment_priority_over_inventory() {
 
     assert_eq!(
         *player_inventory.slot(PlayerInventory::SLOT_CHEST),
-        ItemStack::new(ItemKind::GoldenChestplate, 1, None)
+        ItemStack::new(ItemKind::GoldenChestplate, 1)
     );
 
     assert_eq!(
         *player_equipment.chest(),
-        ItemStack::new(ItemKind::GoldenChestplate, 1, None)
+        ItemStack::new(ItemKind::GoldenChestplate, 1)
     );
 }
 
@@ -430,7 +430,7 @@ fn test_equipment_change_from_player() {
         .get_mut::<Inventory>(client)
         .expect("could not get player equipment");
 
-    player_inventory.set_slot(36, ItemStack::new(ItemKind::DiamondChestplate, 1, None));
+    player_inventory.set_slot(36, ItemStack::new(ItemKind::DiamondChestplate, 1));
     app.update();
     helper.clear_received();
 
@@ -455,7 +455,7 @@ fn test_equipment_change_from_player() {
             },
             SlotChange {
                 idx: PlayerInventory::SLOT_CHEST as i16,
-                stack: ItemStack::new(ItemKind::DiamondChestplate, 1, None),
+                stack: ItemStack::new(ItemKind::DiamondChestplate, 1),
             },
         ]
         .into(),
@@ -477,14 +477,14 @@ fn test_equipment_change_from_player() {
 
     assert_eq!(
         player_inventory.slot(PlayerInventory::SLOT_CHEST),
-        &ItemStack::new(ItemKind::DiamondChestplate, 1, None)
+        &ItemStack::new(ItemKind::DiamondChestplate, 1)
     );
 
     assert_eq!(player_inventory.slot(36), &ItemStack::EMPTY);
 
     assert_eq!(
         player_equipment.chest(),
-        &ItemStack::new(ItemKind::DiamondChestplate, 1, None)
+        &ItemStack::new(ItemKind::DiamondChestplate, 1)
     );
 }
 
@@ -510,8 +510,8 @@ fn test_held_item_change_from_client() {
         .get_mut::<Inventory>(client)
         .expect("could not get player equipment");
 
-    player_inventory.set_slot(36, ItemStack::new(ItemKind::DiamondSword, 1, None));
-    player_inventory.set_slot(37, ItemStack::new(ItemKind::IronSword, 1, None));
+    player_inventory.set_slot(36, ItemStack::new(ItemKind::DiamondSword, 1));
+    player_inventory.set_slot(37, ItemStack::new(ItemKind::IronSword, 1));
 
     app.update();
 
@@ -522,7 +522,7 @@ fn test_held_item_change_from_client() {
 
     assert_eq!(
         player_equipment.main_hand(),
-        &ItemStack::new(ItemKind::DiamondSword, 1, None)
+        &ItemStack::new(ItemKind::DiamondSword, 1)
     );
 
     // Change the held item from the client
@@ -538,6 +538,6 @@ fn test_held_item_change_from_client() {
 
     assert_eq!(
         player_equipment.main_hand(),
-        &ItemStack::new(ItemKind::IronSword, 1, None)
+        &ItemStack::new(ItemKind::IronSword, 1)
     );
 }
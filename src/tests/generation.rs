@@ -0,0 +1,176 @@
+use std::thread;
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+
+use crate::entity::Position;
+use crate::generation::{ChunkGenerateEvent, FlatGenerator, GeneratedLevel};
+use crate::layer::chunk::{Chunk, UnloadedChunk};
+use crate::layer::ChunkLayer;
+use crate::protocol::packets::play::ChunkDataS2c;
+use crate::testing::ScenarioSingleClient;
+use crate::{BlockState, ChunkPos};
+
+#[test]
+fn generate_chunks_in_view() {
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        layer: layer_ent,
+        ..
+    } = ScenarioSingleClient::new();
+
+    app.world_mut()
+        .entity_mut(layer_ent)
+        .insert(GeneratedLevel::new(FlatGenerator {
+            layers: vec![
+                (BlockState::BEDROCK, 1),
+                (BlockState::DIRT, 2),
+                (BlockState::GRASS_BLOCK, 1),
+            ],
+            ..Default::default()
+        }));
+
+    let mut generated = false;
+
+    // Chunks are generated on another thread, so wait for them to arrive.
+    for _ in 0..500 {
+        app.update();
+
+        let events = app.world().resource::<Events<ChunkGenerateEvent>>();
+
+        if events
+            .iter_current_update_events()
+            .any(|e| e.chunk_layer == layer_ent && e.pos == ChunkPos::new(0, 0))
+        {
+            generated = true;
+            break;
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(
+        generated,
+        "chunk at the client's position was not generated"
+    );
+
+    let layer = app.world().get::<ChunkLayer>(layer_ent).unwrap();
+    let min_y = layer.min_y();
+
+    assert_eq!(
+        layer.block([0, min_y, 0]).unwrap().state,
+        BlockState::BEDROCK
+    );
+    assert_eq!(
+        layer.block([5, min_y + 2, 9]).unwrap().state,
+        BlockState::DIRT
+    );
+    assert_eq!(
+        layer.block([15, min_y + 3, 15]).unwrap().state,
+        BlockState::GRASS_BLOCK
+    );
+    assert!(layer.block([0, min_y + 4, 0]).unwrap().state.is_air());
+
+    // The generated chunk is sent to the client in the same tick.
+    helper.collect_received().first::<ChunkDataS2c>();
+}
+
+/// Returns a generator that only finishes a chunk for every message sent on the
+/// returned sender, so tests can control which chunks are in flight.
+fn blocking_generator() -> (
+    flume::Sender<()>,
+    impl Fn(ChunkPos, u32, i32) -> Option<UnloadedChunk>,
+) {
+    let (release_send, release_recv) = flume::unbounded::<()>();
+
+    let generator = move |_, height, _| {
+        release_recv.recv().ok()?;
+        Some(UnloadedChunk::with_height(height))
+    };
+
+    (release_send, generator)
+}
+
+#[test]
+fn generated_chunks_do_not_overwrite_inserted_chunks() {
+    let ScenarioSingleClient {
+        mut app,
+        layer: layer_ent,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let (release, generator) = blocking_generator();
+
+    let mut level = GeneratedLevel::new(generator);
+    level.max_in_flight = 1;
+    level.unload_unviewed = false;
+
+    app.world_mut().entity_mut(layer_ent).insert(level);
+
+    // The chunk at the client's position is generated first.
+    app.update();
+
+    let mut layer = app.world_mut().get_mut::<ChunkLayer>(layer_ent).unwrap();
+    let mut chunk = UnloadedChunk::with_height(layer.height());
+    chunk.set_block_state(0, 0, 0, BlockState::STONE);
+    layer.insert_chunk([0, 0], chunk);
+
+    release.send(()).unwrap();
+
+    for _ in 0..500 {
+        app.update();
+
+        let level = app.world().get::<GeneratedLevel>(layer_ent).unwrap();
+
+        if !level.is_generating(ChunkPos::new(0, 0)) {
+            break;
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let layer = app.world().get::<ChunkLayer>(layer_ent).unwrap();
+
+    assert_eq!(
+        layer.block([0, layer.min_y(), 0]).unwrap().state,
+        BlockState::STONE
+    );
+}
+
+#[test]
+fn pending_chunks_out_of_view_are_dropped() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        layer: layer_ent,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let (_release, generator) = blocking_generator();
+
+    let mut level = GeneratedLevel::new(generator);
+    level.max_in_flight = 1;
+    level.force_chunk_generate(ChunkPos::new(100, 100));
+
+    app.world_mut().entity_mut(layer_ent).insert(level);
+
+    app.update();
+
+    let level = app.world().get::<GeneratedLevel>(layer_ent).unwrap();
+    assert!(level.is_generating(ChunkPos::new(2, 0)));
+
+    // Move the client far away from the chunks it queued.
+    app.world_mut()
+        .get_mut::<Position>(client)
+        .unwrap()
+        .set([10_000.0, 64.0, 0.0]);
+
+    app.update();
+
+    let level = app.world().get::<GeneratedLevel>(layer_ent).unwrap();
+    assert!(!level.is_generating(ChunkPos::new(2, 0)));
+    assert!(level.is_generating(ChunkPos::new(625, 0)));
+    // Forced chunks are kept even though no client can see them.
+    assert!(level.is_generating(ChunkPos::new(100, 100)));
+}
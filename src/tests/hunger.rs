@@ -1,9 +1,20 @@
+use bevy_app::App;
+use bevy_ecs::event::Events;
+use valence_server::difficulty::WorldDifficulty;
+use valence_server::entity::active_status_effects::{ActiveStatusEffect, ActiveStatusEffects};
 use valence_server::entity::living::Health;
 use valence_server::entity::player::{Food, Saturation};
-use valence_server::protocol::packets::play::HealthUpdateS2c;
+use valence_server::protocol::packets::play::{HealthUpdateS2c, PlayerInteractItemC2s};
+use valence_server::protocol::status_effects::StatusEffect;
 use valence_server::protocol::VarInt;
+use valence_server::{Difficulty, Hand, ItemKind, ItemStack};
 
-use crate::testing::ScenarioSingleClient;
+use crate::combat::{CombatPlugin, Dead};
+use crate::effect::EffectPlugin;
+use crate::hunger::{HungerPlugin, HungerState};
+use crate::inventory::player_inventory::PlayerInventory;
+use crate::inventory::{DropItemStackEvent, Inventory};
+use crate::testing::{MockClientHelper, ScenarioSingleClient};
 
 #[test]
 fn test_hunger() {
@@ -36,3 +47,246 @@ fn test_hunger() {
     assert_eq!(packet.food, VarInt(5));
     assert_eq!(packet.food_saturation, og_saturation);
 }
+
+#[test]
+fn exhaustion_drains_saturation_then_food() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, HungerPlugin));
+
+    app.world_mut().get_mut::<Food>(client).unwrap().0 = 20;
+    app.world_mut().get_mut::<Saturation>(client).unwrap().0 = 1.0;
+    app.world_mut()
+        .get_mut::<HungerState>(client)
+        .unwrap()
+        .add_exhaustion(4.5);
+
+    app.update();
+
+    assert_eq!(app.world().get::<Saturation>(client).unwrap().0, 0.0);
+    assert_eq!(app.world().get::<Food>(client).unwrap().0, 20);
+
+    app.world_mut()
+        .get_mut::<HungerState>(client)
+        .unwrap()
+        .add_exhaustion(4.0);
+
+    app.update();
+
+    assert_eq!(app.world().get::<Food>(client).unwrap().0, 19);
+}
+
+#[test]
+fn natural_regeneration() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, HungerPlugin));
+
+    app.world_mut().get_mut::<Food>(client).unwrap().0 = 18;
+    app.world_mut().get_mut::<Saturation>(client).unwrap().0 = 0.0;
+    app.world_mut().get_mut::<Health>(client).unwrap().0 = 10.0;
+
+    for _ in 0..80 {
+        app.update();
+    }
+
+    assert_eq!(app.world().get::<Health>(client).unwrap().0, 11.0);
+    assert_eq!(
+        app.world().get::<HungerState>(client).unwrap().exhaustion(),
+        6.0
+    );
+}
+
+#[test]
+fn starvation_on_normal_difficulty() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, HungerPlugin));
+
+    app.world_mut().get_mut::<Food>(client).unwrap().0 = 0;
+    app.world_mut().get_mut::<Saturation>(client).unwrap().0 = 0.0;
+    app.world_mut().get_mut::<Health>(client).unwrap().0 = 2.0;
+
+    // The starvation damage is applied on the tick after it is dealt.
+    for _ in 0..81 {
+        app.update();
+    }
+
+    assert_eq!(app.world().get::<Health>(client).unwrap().0, 1.0);
+
+    // Players don't starve to death on normal difficulty.
+    for _ in 0..200 {
+        app.update();
+    }
+
+    assert_eq!(app.world().get::<Health>(client).unwrap().0, 1.0);
+}
+
+#[test]
+fn eating_food() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, HungerPlugin));
+
+    app.world_mut().get_mut::<Food>(client).unwrap().0 = 10;
+    app.world_mut()
+        .get_mut::<Inventory>(client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::Bread, 2));
+
+    helper.send(&PlayerInteractItemC2s {
+        hand: Hand::Main,
+        sequence: VarInt(0),
+    });
+
+    // Bread takes 32 ticks to eat.
+    for _ in 0..31 {
+        app.update();
+    }
+
+    assert!(app.world().get::<HungerState>(client).unwrap().is_eating());
+    assert_eq!(app.world().get::<Food>(client).unwrap().0, 10);
+
+    app.update();
+
+    assert!(!app.world().get::<HungerState>(client).unwrap().is_eating());
+    assert_eq!(app.world().get::<Food>(client).unwrap().0, 15);
+
+    let inventory = app.world().get::<Inventory>(client).unwrap();
+    assert_eq!(inventory.slot(36).count, 1);
+}
+
+#[test]
+fn starvation_on_hard_difficulty() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, HungerPlugin));
+
+    app.world_mut().resource_mut::<WorldDifficulty>().difficulty = Difficulty::Hard;
+    app.world_mut().get_mut::<Food>(client).unwrap().0 = 0;
+    app.world_mut().get_mut::<Saturation>(client).unwrap().0 = 0.0;
+    app.world_mut().get_mut::<Health>(client).unwrap().0 = 1.0;
+
+    for _ in 0..81 {
+        app.update();
+    }
+
+    // Unlike on normal difficulty, players starve to death.
+    assert!(app.world().get::<Dead>(client).is_some());
+}
+
+/// Eats a mushroom stew from hotbar slot 0 of the client.
+fn eat_stew(app: &mut App, helper: &mut MockClientHelper) {
+    helper.send(&PlayerInteractItemC2s {
+        hand: Hand::Main,
+        sequence: VarInt(0),
+    });
+
+    // Mushroom stew takes 32 ticks to eat.
+    for _ in 0..32 {
+        app.update();
+    }
+}
+
+#[test]
+fn eating_last_stew_leaves_bowl_in_slot() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, HungerPlugin));
+
+    app.world_mut().get_mut::<Food>(client).unwrap().0 = 10;
+    app.world_mut()
+        .get_mut::<Inventory>(client)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::MushroomStew, 1));
+
+    eat_stew(&mut app, &mut helper);
+
+    assert_eq!(app.world().get::<Food>(client).unwrap().0, 16);
+
+    let inventory = app.world().get::<Inventory>(client).unwrap();
+    assert_eq!(inventory.slot(36), &ItemStack::new(ItemKind::Bowl, 1));
+}
+
+#[test]
+fn bowl_is_added_to_inventory_when_stack_remains() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, HungerPlugin));
+
+    app.world_mut().get_mut::<Food>(client).unwrap().0 = 10;
+
+    let mut inventory = app.world_mut().get_mut::<Inventory>(client).unwrap();
+    inventory.set_slot(36, ItemStack::new(ItemKind::MushroomStew, 2));
+    inventory.set_slot(20, ItemStack::new(ItemKind::Bowl, 3));
+
+    eat_stew(&mut app, &mut helper);
+
+    let inventory = app.world().get::<Inventory>(client).unwrap();
+    assert_eq!(
+        inventory.slot(36),
+        &ItemStack::new(ItemKind::MushroomStew, 1)
+    );
+    assert_eq!(inventory.slot(20), &ItemStack::new(ItemKind::Bowl, 4));
+}
+
+#[test]
+fn bowl_is_dropped_when_inventory_is_full() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, HungerPlugin));
+
+    app.world_mut().get_mut::<Food>(client).unwrap().0 = 10;
+
+    let mut inventory = app.world_mut().get_mut::<Inventory>(client).unwrap();
+
+    for slot in PlayerInventory::SLOTS_MAIN {
+        inventory.set_slot(slot, ItemStack::new(ItemKind::Stone, 64));
+    }
+
+    inventory.set_slot(36, ItemStack::new(ItemKind::MushroomStew, 2));
+
+    eat_stew(&mut app, &mut helper);
+
+    let dropped: Vec<_> = app
+        .world()
+        .resource::<Events<DropItemStackEvent>>()
+        .iter_current_update_events()
+        .cloned()
+        .collect();
+
+    assert_eq!(dropped.len(), 1);
+    assert_eq!(dropped[0].client, client);
+    assert_eq!(dropped[0].from_slot, None);
+    assert_eq!(dropped[0].stack, ItemStack::new(ItemKind::Bowl, 1));
+}
+
+#[test]
+fn hunger_effect_adds_exhaustion() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, HungerPlugin, EffectPlugin));
+
+    app.world_mut()
+        .get_mut::<ActiveStatusEffects>(client)
+        .unwrap()
+        .apply(ActiveStatusEffect::from_effect(StatusEffect::Hunger).with_duration(100));
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    assert!(app.world().get::<HungerState>(client).unwrap().exhaustion() > 0.0);
+}
@@ -4,8 +4,8 @@ use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 
 use crate::inventory::{
-    convert_to_player_slot_id, ClickMode, ClientInventoryState, CursorItem, DropItemStackEvent,
-    HeldItem, Inventory, InventoryKind, OpenInventory, SlotChange,
+    convert_to_player_slot_id, ClickMode, ClickSlotEvent, ClientInventoryState, CursorItem,
+    DropItemStackEvent, HeldItem, Inventory, InventoryKind, OpenInventory, SlotChange,
 };
 use crate::protocol::packets::play::{
     ClickSlotC2s, CloseScreenS2c, CreativeInventoryActionC2s, InventoryS2c, OpenScreenS2c,
@@ -13,7 +13,7 @@ use crate::protocol::packets::play::{
 };
 use crate::protocol::VarInt;
 use crate::testing::ScenarioSingleClient;
-use crate::{GameMode, ItemKind, ItemStack};
+use crate::{GameMode, ItemComponent, ItemKind, ItemStack};
 
 #[test]
 fn test_should_open_inventory() {
@@ -143,7 +143,7 @@ fn test_should_modify_player_inventory_click_slot() {
         .world_mut()
         .get_mut::<Inventory>(client)
         .expect("could not find inventory for client");
-    inventory.set_slot(20, ItemStack::new(ItemKind::Diamond, 2, None));
+    inventory.set_slot(20, ItemStack::new(ItemKind::Diamond, 2));
 
     // Make the client click the slot and pick up the item.
     let state_id = app
@@ -163,7 +163,7 @@ fn test_should_modify_player_inventory_click_slot() {
             stack: ItemStack::EMPTY,
         }]
         .into(),
-        carried_item: ItemStack::new(ItemKind::Diamond, 2, None),
+        carried_item: ItemStack::new(ItemKind::Diamond, 2),
     });
 
     app.update();
@@ -189,7 +189,71 @@ fn test_should_modify_player_inventory_click_slot() {
         .get::<CursorItem>(client)
         .expect("could not find client");
 
-    assert_eq!(cursor_item.0, ItemStack::new(ItemKind::Diamond, 2, None));
+    assert_eq!(cursor_item.0, ItemStack::new(ItemKind::Diamond, 2));
+}
+
+#[test]
+fn test_should_resync_click_with_modified_components() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    helper.clear_received();
+
+    let mut inventory = app
+        .world_mut()
+        .get_mut::<Inventory>(client)
+        .expect("could not find inventory for client");
+    inventory.set_slot(20, ItemStack::new(ItemKind::IronSword, 1));
+
+    let state_id = app
+        .world_mut()
+        .get::<ClientInventoryState>(client)
+        .unwrap()
+        .state_id();
+
+    // The client picks up the sword, but claims it has a custom name now.
+    helper.send(&ClickSlotC2s {
+        window_id: 0,
+        button: 0,
+        mode: ClickMode::Click,
+        state_id: VarInt(state_id.0),
+        slot_idx: 20,
+        slot_changes: vec![SlotChange {
+            idx: 20,
+            stack: ItemStack::EMPTY,
+        }]
+        .into(),
+        carried_item: ItemStack::new(ItemKind::IronSword, 1)
+            .with_component(ItemComponent::CustomName("Excalibur".into())),
+    });
+
+    app.update();
+
+    // The click doesn't match the server's result, so the client is resynced and
+    // nothing is applied.
+    let sent_packets = helper.collect_received();
+    sent_packets.assert_count::<InventoryS2c>(1);
+
+    let events = app.world().resource::<Events<ClickSlotEvent>>();
+    assert!(events.iter_current_update_events().next().is_none());
+
+    let inventory = app
+        .world_mut()
+        .get::<Inventory>(client)
+        .expect("could not find inventory for client");
+    assert_eq!(inventory.slot(20), &ItemStack::new(ItemKind::IronSword, 1));
+
+    let cursor_item = app
+        .world_mut()
+        .get::<CursorItem>(client)
+        .expect("could not find client");
+    assert_eq!(cursor_item.0, ItemStack::EMPTY);
 }
 
 #[test]
@@ -209,7 +273,7 @@ fn test_should_allow_non_modifying_inventory_clicks() {
         .world_mut()
         .get_mut::<Inventory>(client)
         .expect("could not find inventory for client");
-    inventory.set_slot(20, ItemStack::new(ItemKind::Diamond, 2, None));
+    inventory.set_slot(20, ItemStack::new(ItemKind::Diamond, 2));
 
     // Make the client click the slot and pick up the item.
     let state_id = app
@@ -226,7 +290,7 @@ fn test_should_allow_non_modifying_inventory_clicks() {
         state_id: VarInt(state_id.0),
         slot_idx: 0,
         slot_changes: vec![].into(),
-        carried_item: ItemStack::new(ItemKind::Air, 0, None),
+        carried_item: ItemStack::new(ItemKind::Air, 0),
     });
     // Clicked on a real slot, but the slot is empty
     helper.send(&ClickSlotC2s {
@@ -236,7 +300,7 @@ fn test_should_allow_non_modifying_inventory_clicks() {
         state_id: VarInt(state_id.0),
         slot_idx: 1,
         slot_changes: vec![].into(),
-        carried_item: ItemStack::new(ItemKind::Air, 0, None),
+        carried_item: ItemStack::new(ItemKind::Air, 0),
     });
     // Clicked in the margin area of an inventory (in the main ui but not in one of
     // the slots)
@@ -247,7 +311,7 @@ fn test_should_allow_non_modifying_inventory_clicks() {
         state_id: VarInt(state_id.0),
         slot_idx: -1,
         slot_changes: vec![].into(),
-        carried_item: ItemStack::new(ItemKind::Air, 0, None),
+        carried_item: ItemStack::new(ItemKind::Air, 0),
     });
     // Clicked outside the user interface without holding an item (this is a drop
     // key mode from the client for some reason)
@@ -258,7 +322,7 @@ fn test_should_allow_non_modifying_inventory_clicks() {
         state_id: VarInt(state_id.0),
         slot_idx: -999,
         slot_changes: vec![].into(),
-        carried_item: ItemStack::new(ItemKind::Air, 0, None),
+        carried_item: ItemStack::new(ItemKind::Air, 0),
     });
 
     app.update();
@@ -287,7 +351,7 @@ fn test_should_modify_player_inventory_server_side() {
         .world_mut()
         .get_mut::<Inventory>(client)
         .expect("could not find inventory for client");
-    inventory.set_slot(20, ItemStack::new(ItemKind::Diamond, 2, None));
+    inventory.set_slot(20, ItemStack::new(ItemKind::Diamond, 2));
 
     app.update();
     helper.clear_received();
@@ -297,7 +361,7 @@ fn test_should_modify_player_inventory_server_side() {
         .world_mut()
         .get_mut::<Inventory>(client)
         .expect("could not find inventory for client");
-    inventory.set_slot(21, ItemStack::new(ItemKind::IronIngot, 1, None));
+    inventory.set_slot(21, ItemStack::new(ItemKind::IronIngot, 1));
 
     app.update();
 
@@ -364,7 +428,7 @@ fn test_should_modify_open_inventory_click_slot() {
         .get_mut::<Inventory>(inventory_ent)
         .expect("could not find inventory for client");
 
-    inventory.set_slot(20, ItemStack::new(ItemKind::Diamond, 2, None));
+    inventory.set_slot(20, ItemStack::new(ItemKind::Diamond, 2));
 
     // Process a tick to get past the "on join" logic.
     app.update();
@@ -385,7 +449,7 @@ fn test_should_modify_open_inventory_click_slot() {
             stack: ItemStack::EMPTY,
         }]
         .into(),
-        carried_item: ItemStack::new(ItemKind::Diamond, 2, None),
+        carried_item: ItemStack::new(ItemKind::Diamond, 2),
     });
 
     app.update();
@@ -408,7 +472,7 @@ fn test_should_modify_open_inventory_click_slot() {
         .world_mut()
         .get::<CursorItem>(client)
         .expect("could not find client");
-    assert_eq!(cursor_item.0, ItemStack::new(ItemKind::Diamond, 2, None));
+    assert_eq!(cursor_item.0, ItemStack::new(ItemKind::Diamond, 2));
 }
 
 #[test]
@@ -443,7 +507,7 @@ fn test_sync_inventory_change_made_from_valence_while_inventory_is_opened() {
         .get_mut::<Inventory>(client)
         .expect("could not find inventory for client");
 
-    inventory.set_slot(9, ItemStack::new(ItemKind::Diamond, 2, None));
+    inventory.set_slot(9, ItemStack::new(ItemKind::Diamond, 2));
 
     app.update();
 
@@ -460,7 +524,7 @@ fn test_sync_inventory_change_made_from_valence_while_inventory_is_opened() {
 
     assert_eq!(
         received.slot_data,
-        Cow::Borrowed(&ItemStack::new(ItemKind::Diamond, 2, None))
+        Cow::Borrowed(&ItemStack::new(ItemKind::Diamond, 2))
     );
 
     assert_eq!(received.state_id, VarInt(inv_state_state_id.0));
@@ -484,7 +548,7 @@ fn test_prevent_modify_open_inventory_click_slot_readonly_inventory() {
         .expect("could not find inventory for client");
 
     inventory.readonly = true;
-    inventory.set_slot(20, ItemStack::new(ItemKind::Diamond, 2, None));
+    inventory.set_slot(20, ItemStack::new(ItemKind::Diamond, 2));
 
     // Process a tick to get past the "on join" logic.
     app.update();
@@ -509,7 +573,7 @@ fn test_prevent_modify_open_inventory_click_slot_readonly_inventory() {
             stack: ItemStack::EMPTY,
         }]
         .into(),
-        carried_item: ItemStack::new(ItemKind::Diamond, 2, None),
+        carried_item: ItemStack::new(ItemKind::Diamond, 2),
     });
 
     app.update();
@@ -527,10 +591,7 @@ fn test_prevent_modify_open_inventory_click_slot_readonly_inventory() {
         .get::<Inventory>(inventory_ent)
         .expect("could not find inventory");
     // Inventory is read-only, the item is not being moved
-    assert_eq!(
-        inventory.slot(20),
-        &ItemStack::new(ItemKind::Diamond, 2, None)
-    );
+    assert_eq!(inventory.slot(20), &ItemStack::new(ItemKind::Diamond, 2));
     let cursor_item = app
         .world_mut()
         .get::<CursorItem>(client)
@@ -559,7 +620,7 @@ fn test_should_modify_open_inventory_server_side() {
         .world_mut()
         .get_mut::<Inventory>(inventory_ent)
         .expect("could not find inventory for client");
-    inventory.set_slot(5, ItemStack::new(ItemKind::IronIngot, 1, None));
+    inventory.set_slot(5, ItemStack::new(ItemKind::IronIngot, 1));
 
     app.update();
 
@@ -575,10 +636,7 @@ fn test_should_modify_open_inventory_server_side() {
         .get::<Inventory>(inventory_ent)
         .expect("could not find inventory for client");
 
-    assert_eq!(
-        inventory.slot(5),
-        &ItemStack::new(ItemKind::IronIngot, 1, None)
-    );
+    assert_eq!(inventory.slot(5), &ItemStack::new(ItemKind::IronIngot, 1));
 }
 
 #[test]
@@ -599,7 +657,7 @@ fn test_hotbar_item_swap_container() {
         .expect("could not find inventory for client");
 
     // 36 is the first hotbar slot
-    player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 1, None));
+    player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 1));
 
     let open_inv_ent = set_up_open_inventory(&mut app, client);
 
@@ -608,7 +666,7 @@ fn test_hotbar_item_swap_container() {
         .get_mut::<Inventory>(open_inv_ent)
         .expect("could not find inventory for client");
 
-    open_inventory.set_slot(0, ItemStack::new(ItemKind::IronIngot, 10, None));
+    open_inventory.set_slot(0, ItemStack::new(ItemKind::IronIngot, 10));
 
     // This update makes sure we have the items in the inventory by the time the
     // client wants to update these
@@ -632,12 +690,12 @@ fn test_hotbar_item_swap_container() {
             // target slot.
             SlotChange {
                 idx: 0,
-                stack: ItemStack::new(ItemKind::Diamond, 1, None),
+                stack: ItemStack::new(ItemKind::Diamond, 1),
             },
             SlotChange {
                 // 54 is the players hotbar slot 1, when the 9x3 inventory is opnened.
                 idx: 54,
-                stack: ItemStack::new(ItemKind::IronIngot, 10, None),
+                stack: ItemStack::new(ItemKind::IronIngot, 10),
             },
             // The second one is the slot in the open inventory, after the ClickSlot action
             // source slot.
@@ -662,7 +720,7 @@ fn test_hotbar_item_swap_container() {
     // Swapped items successfully
     assert_eq!(
         player_inventory.slot(36),
-        &ItemStack::new(ItemKind::IronIngot, 10, None)
+        &ItemStack::new(ItemKind::IronIngot, 10)
     );
 
     let open_inventory = app
@@ -672,7 +730,7 @@ fn test_hotbar_item_swap_container() {
 
     assert_eq!(
         open_inventory.slot(0),
-        &ItemStack::new(ItemKind::Diamond, 1, None)
+        &ItemStack::new(ItemKind::Diamond, 1)
     );
 }
 
@@ -696,7 +754,7 @@ fn test_prevent_hotbar_item_click_container_readonly_inventory() {
         .expect("could not find inventory for client");
 
     // 36 is the first hotbar slot
-    player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 1, None));
+    player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 1));
 
     let open_inv_ent = set_up_open_inventory(&mut app, client);
 
@@ -707,7 +765,7 @@ fn test_prevent_hotbar_item_click_container_readonly_inventory() {
 
     // Open inventory is read-only
     open_inventory.readonly = true;
-    open_inventory.set_slot(0, ItemStack::new(ItemKind::IronIngot, 10, None));
+    open_inventory.set_slot(0, ItemStack::new(ItemKind::IronIngot, 10));
 
     // This update makes sure we have the items in the inventory by the time the
     // client wants to update these
@@ -733,14 +791,14 @@ fn test_prevent_hotbar_item_click_container_readonly_inventory() {
             // target slot.
             SlotChange {
                 idx: 0,
-                stack: ItemStack::new(ItemKind::Diamond, 1, None),
+                stack: ItemStack::new(ItemKind::Diamond, 1),
             },
             // The second one is the slot in the open inventory, after the ClickSlot action
             // source slot.
             SlotChange {
                 // 54 is the players hotbar slot 1, when the 9x3 inventory is opnened.
                 idx: 54,
-                stack: ItemStack::new(ItemKind::IronIngot, 10, None),
+                stack: ItemStack::new(ItemKind::IronIngot, 10),
             },
         ]
         .into(),
@@ -763,7 +821,7 @@ fn test_prevent_hotbar_item_click_container_readonly_inventory() {
     // Opened inventory is read-only, the items are not swapped.
     assert_eq!(
         player_inventory.slot(36),
-        &ItemStack::new(ItemKind::Diamond, 1, None)
+        &ItemStack::new(ItemKind::Diamond, 1)
     );
 
     let open_inventory = app
@@ -774,7 +832,7 @@ fn test_prevent_hotbar_item_click_container_readonly_inventory() {
     // Opened inventory is read-only, the items are not swapped.
     assert_eq!(
         open_inventory.slot(0),
-        &ItemStack::new(ItemKind::IronIngot, 10, None)
+        &ItemStack::new(ItemKind::IronIngot, 10)
     );
 }
 
@@ -798,7 +856,7 @@ fn test_still_allow_hotbar_item_click_in_own_inventory_if_container_readonly_inv
         .expect("could not find inventory for client");
 
     // 36 is the first hotbar slot
-    player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 10, None));
+    player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 10));
 
     let open_inv_ent = set_up_open_inventory(&mut app, client);
 
@@ -831,7 +889,7 @@ fn test_still_allow_hotbar_item_click_in_own_inventory_if_container_readonly_inv
         slot_changes: vec![
             SlotChange {
                 idx: 27,
-                stack: ItemStack::new(ItemKind::Diamond, 10, None),
+                stack: ItemStack::new(ItemKind::Diamond, 10),
             },
             SlotChange {
                 idx: 54,
@@ -856,7 +914,7 @@ fn test_still_allow_hotbar_item_click_in_own_inventory_if_container_readonly_inv
     assert_eq!(player_inventory.slot(36), &ItemStack::EMPTY);
     assert_eq!(
         player_inventory.slot(9),
-        &ItemStack::new(ItemKind::Diamond, 10, None)
+        &ItemStack::new(ItemKind::Diamond, 10)
     );
 }
 
@@ -879,7 +937,7 @@ fn test_prevent_shift_item_click_container_readonly_inventory() {
         .get_mut::<Inventory>(client)
         .expect("could not find inventory for client");
 
-    player_inventory.set_slot(9, ItemStack::new(ItemKind::Diamond, 64, None));
+    player_inventory.set_slot(9, ItemStack::new(ItemKind::Diamond, 64));
 
     let open_inv_ent = set_up_open_inventory(&mut app, client);
 
@@ -912,7 +970,7 @@ fn test_prevent_shift_item_click_container_readonly_inventory() {
             // target
             SlotChange {
                 idx: 0,
-                stack: ItemStack::new(ItemKind::Diamond, 64, None),
+                stack: ItemStack::new(ItemKind::Diamond, 64),
             },
             // source
             SlotChange {
@@ -938,7 +996,7 @@ fn test_prevent_shift_item_click_container_readonly_inventory() {
 
     assert_eq!(
         player_inventory.slot(9),
-        &ItemStack::new(ItemKind::Diamond, 64, None)
+        &ItemStack::new(ItemKind::Diamond, 64)
     );
 
     let open_inventory = app
@@ -998,7 +1056,7 @@ fn test_set_creative_mode_slot_handling() {
 
     helper.send(&CreativeInventoryActionC2s {
         slot: 36,
-        clicked_item: ItemStack::new(ItemKind::Diamond, 2, None),
+        clicked_item: ItemStack::new(ItemKind::Diamond, 2),
     });
 
     app.update();
@@ -1009,10 +1067,7 @@ fn test_set_creative_mode_slot_handling() {
         .get::<Inventory>(client)
         .expect("could not find inventory for client");
 
-    assert_eq!(
-        inventory.slot(36),
-        &ItemStack::new(ItemKind::Diamond, 2, None)
-    );
+    assert_eq!(inventory.slot(36), &ItemStack::new(ItemKind::Diamond, 2));
 }
 
 #[test]
@@ -1036,7 +1091,7 @@ fn test_ignore_set_creative_mode_slot_if_not_creative() {
 
     helper.send(&CreativeInventoryActionC2s {
         slot: 36,
-        clicked_item: ItemStack::new(ItemKind::Diamond, 2, None),
+        clicked_item: ItemStack::new(ItemKind::Diamond, 2),
     });
 
     app.update();
@@ -1136,7 +1191,7 @@ fn should_not_increment_state_id_on_cursor_item_change() {
     let expected_state_id = inv_state.state_id().0;
 
     let mut cursor_item = app.world_mut().get_mut::<CursorItem>(client).unwrap();
-    cursor_item.0 = ItemStack::new(ItemKind::Diamond, 2, None);
+    cursor_item.0 = ItemStack::new(ItemKind::Diamond, 2);
 
     app.update();
 
@@ -1166,7 +1221,7 @@ fn should_send_cursor_item_change_when_modified_on_the_server() {
     helper.clear_received();
 
     let mut cursor_item = app.world_mut().get_mut::<CursorItem>(client).unwrap();
-    cursor_item.0 = ItemStack::new(ItemKind::Diamond, 2, None);
+    cursor_item.0 = ItemStack::new(ItemKind::Diamond, 2);
 
     app.update();
 
@@ -1198,7 +1253,7 @@ mod dropping_items {
             .world_mut()
             .get_mut::<Inventory>(client)
             .expect("could not find inventory");
-        inventory.set_slot(36, ItemStack::new(ItemKind::IronIngot, 3, None));
+        inventory.set_slot(36, ItemStack::new(ItemKind::IronIngot, 3));
 
         helper.send(&PlayerActionC2s {
             action: PlayerAction::DropItem,
@@ -1215,10 +1270,7 @@ mod dropping_items {
             .get::<Inventory>(client)
             .expect("could not find client");
 
-        assert_eq!(
-            inventory.slot(36),
-            &ItemStack::new(ItemKind::IronIngot, 2, None)
-        );
+        assert_eq!(inventory.slot(36), &ItemStack::new(ItemKind::IronIngot, 2));
 
         let events = app
             .world_mut()
@@ -1230,10 +1282,7 @@ mod dropping_items {
         assert_eq!(events.len(), 1);
         assert_eq!(events[0].client, client);
         assert_eq!(events[0].from_slot, Some(36));
-        assert_eq!(
-            events[0].stack,
-            ItemStack::new(ItemKind::IronIngot, 1, None)
-        );
+        assert_eq!(events[0].stack, ItemStack::new(ItemKind::IronIngot, 1));
 
         let sent_packets = helper.collect_received();
 
@@ -1258,7 +1307,7 @@ mod dropping_items {
             .get_mut::<Inventory>(client)
             .expect("could not find inventory");
         inventory.readonly = true;
-        inventory.set_slot(36, ItemStack::new(ItemKind::IronIngot, 3, None));
+        inventory.set_slot(36, ItemStack::new(ItemKind::IronIngot, 3));
 
         helper.send(&PlayerActionC2s {
             action: PlayerAction::DropItem,
@@ -1278,7 +1327,7 @@ mod dropping_items {
         assert_eq!(
             inventory.slot(36),
             // Inventory is read-only, item is not being dropped
-            &ItemStack::new(ItemKind::IronIngot, 3, None)
+            &ItemStack::new(ItemKind::IronIngot, 3)
         );
 
         let events = app
@@ -1314,7 +1363,7 @@ mod dropping_items {
             .world_mut()
             .get_mut::<Inventory>(client)
             .expect("could not find inventory");
-        inventory.set_slot(36, ItemStack::new(ItemKind::IronIngot, 32, None));
+        inventory.set_slot(36, ItemStack::new(ItemKind::IronIngot, 32));
 
         helper.send(&PlayerActionC2s {
             action: PlayerAction::DropAllItems,
@@ -1344,10 +1393,7 @@ mod dropping_items {
         assert_eq!(events.len(), 1);
         assert_eq!(events[0].client, client);
         assert_eq!(events[0].from_slot, Some(36));
-        assert_eq!(
-            events[0].stack,
-            ItemStack::new(ItemKind::IronIngot, 32, None)
-        );
+        assert_eq!(events[0].stack, ItemStack::new(ItemKind::IronIngot, 32));
     }
 
     #[test]
@@ -1368,7 +1414,7 @@ mod dropping_items {
             .get_mut::<Inventory>(client)
             .expect("could not find inventory");
         inventory.readonly = true;
-        inventory.set_slot(36, ItemStack::new(ItemKind::IronIngot, 32, None));
+        inventory.set_slot(36, ItemStack::new(ItemKind::IronIngot, 32));
 
         helper.send(&PlayerActionC2s {
             action: PlayerAction::DropAllItems,
@@ -1390,10 +1436,7 @@ mod dropping_items {
             .get::<Inventory>(client)
             .expect("could not find inventory");
         // Inventory is read-only, item is not being dropped
-        assert_eq!(
-            inventory.slot(36),
-            &ItemStack::new(ItemKind::IronIngot, 32, None)
-        );
+        assert_eq!(inventory.slot(36), &ItemStack::new(ItemKind::IronIngot, 32));
         let events = app
             .world_mut()
             .get_resource::<Events<DropItemStackEvent>>()
@@ -1423,7 +1466,7 @@ mod dropping_items {
 
         helper.send(&CreativeInventoryActionC2s {
             slot: -1,
-            clicked_item: ItemStack::new(ItemKind::IronIngot, 32, None),
+            clicked_item: ItemStack::new(ItemKind::IronIngot, 32),
         });
 
         app.update();
@@ -1439,10 +1482,7 @@ mod dropping_items {
         assert_eq!(events.len(), 1);
         assert_eq!(events[0].client, client);
         assert_eq!(events[0].from_slot, None);
-        assert_eq!(
-            events[0].stack,
-            ItemStack::new(ItemKind::IronIngot, 32, None)
-        );
+        assert_eq!(events[0].stack, ItemStack::new(ItemKind::IronIngot, 32));
     }
 
     #[test]
@@ -1462,7 +1502,7 @@ mod dropping_items {
             .world_mut()
             .get_mut::<CursorItem>(client)
             .expect("could not find client");
-        cursor_item.0 = ItemStack::new(ItemKind::IronIngot, 32, None);
+        cursor_item.0 = ItemStack::new(ItemKind::IronIngot, 32);
         let inv_state = app
             .world_mut()
             .get_mut::<ClientInventoryState>(client)
@@ -1499,10 +1539,7 @@ mod dropping_items {
         assert_eq!(events.len(), 1);
         assert_eq!(events[0].client, client);
         assert_eq!(events[0].from_slot, None);
-        assert_eq!(
-            events[0].stack,
-            ItemStack::new(ItemKind::IronIngot, 32, None)
-        );
+        assert_eq!(events[0].stack, ItemStack::new(ItemKind::IronIngot, 32));
     }
 
     #[test]
@@ -1530,7 +1567,7 @@ mod dropping_items {
             .get_mut::<Inventory>(client)
             .expect("could not find inventory");
 
-        inventory.set_slot(40, ItemStack::new(ItemKind::IronIngot, 32, None));
+        inventory.set_slot(40, ItemStack::new(ItemKind::IronIngot, 32));
 
         helper.send(&ClickSlotC2s {
             window_id: 0,
@@ -1540,7 +1577,7 @@ mod dropping_items {
             state_id: VarInt(state_id),
             slot_changes: vec![SlotChange {
                 idx: 40,
-                stack: ItemStack::new(ItemKind::IronIngot, 31, None),
+                stack: ItemStack::new(ItemKind::IronIngot, 31),
             }]
             .into(),
             carried_item: ItemStack::EMPTY,
@@ -1559,10 +1596,7 @@ mod dropping_items {
         assert_eq!(events.len(), 1);
         assert_eq!(events[0].client, client);
         assert_eq!(events[0].from_slot, Some(40));
-        assert_eq!(
-            events[0].stack,
-            ItemStack::new(ItemKind::IronIngot, 1, None)
-        );
+        assert_eq!(events[0].stack, ItemStack::new(ItemKind::IronIngot, 1));
     }
 
     #[test]
@@ -1591,7 +1625,7 @@ mod dropping_items {
             .expect("could not find inventory");
 
         inventory.readonly = true;
-        inventory.set_slot(40, ItemStack::new(ItemKind::IronIngot, 32, None));
+        inventory.set_slot(40, ItemStack::new(ItemKind::IronIngot, 32));
 
         helper.send(&ClickSlotC2s {
             window_id: 0,
@@ -1601,7 +1635,7 @@ mod dropping_items {
             state_id: VarInt(state_id),
             slot_changes: vec![SlotChange {
                 idx: 40,
-                stack: ItemStack::new(ItemKind::IronIngot, 31, None),
+                stack: ItemStack::new(ItemKind::IronIngot, 31),
             }]
             .into(),
             carried_item: ItemStack::EMPTY,
@@ -1618,7 +1652,7 @@ mod dropping_items {
         assert_eq!(
             inventory.slot(40),
             // Inventory is read-only, item is not being dropped
-            &ItemStack::new(ItemKind::IronIngot, 32, None)
+            &ItemStack::new(ItemKind::IronIngot, 32)
         );
 
         let events = app
@@ -1657,7 +1691,7 @@ mod dropping_items {
             .get_mut::<Inventory>(client)
             .expect("could not find inventory");
 
-        inventory.set_slot(40, ItemStack::new(ItemKind::IronIngot, 32, None));
+        inventory.set_slot(40, ItemStack::new(ItemKind::IronIngot, 32));
 
         helper.send(&ClickSlotC2s {
             window_id: 0,
@@ -1686,10 +1720,7 @@ mod dropping_items {
         assert_eq!(events.len(), 1);
         assert_eq!(events[0].client, client);
         assert_eq!(events[0].from_slot, Some(40));
-        assert_eq!(
-            events[0].stack,
-            ItemStack::new(ItemKind::IronIngot, 32, None)
-        );
+        assert_eq!(events[0].stack, ItemStack::new(ItemKind::IronIngot, 32));
     }
 
     #[test]
@@ -1718,7 +1749,7 @@ mod dropping_items {
             .expect("could not find inventory");
 
         inventory.readonly = true;
-        inventory.set_slot(40, ItemStack::new(ItemKind::IronIngot, 32, None));
+        inventory.set_slot(40, ItemStack::new(ItemKind::IronIngot, 32));
 
         helper.send(&ClickSlotC2s {
             window_id: 0,
@@ -1745,7 +1776,7 @@ mod dropping_items {
         assert_eq!(
             inventory.slot(40),
             // Inventory is read-only, item is not being dropped
-            &ItemStack::new(ItemKind::IronIngot, 32, None)
+            &ItemStack::new(ItemKind::IronIngot, 32)
         );
 
         let events = app
@@ -1780,7 +1811,7 @@ mod dropping_items {
 
         inventory.set_slot(
             convert_to_player_slot_id(InventoryKind::Generic9x3, 50),
-            ItemStack::new(ItemKind::IronIngot, 32, None),
+            ItemStack::new(ItemKind::IronIngot, 32),
         );
 
         let _inventory_ent = set_up_open_inventory(&mut app, client);
@@ -1805,7 +1836,7 @@ mod dropping_items {
             mode: ClickMode::DropKey,
             slot_changes: vec![SlotChange {
                 idx: 50,
-                stack: ItemStack::new(ItemKind::IronIngot, 31, None),
+                stack: ItemStack::new(ItemKind::IronIngot, 31),
             }]
             .into(),
             carried_item: ItemStack::EMPTY,
@@ -1833,16 +1864,13 @@ mod dropping_items {
             Some(convert_to_player_slot_id(InventoryKind::Generic9x3, 50))
         );
 
-        assert_eq!(
-            events[0].stack,
-            ItemStack::new(ItemKind::IronIngot, 1, None)
-        );
+        assert_eq!(events[0].stack, ItemStack::new(ItemKind::IronIngot, 1));
 
         // Also make sure that the player inventory was updated correctly.
         let expected_player_slot_id = convert_to_player_slot_id(InventoryKind::Generic9x3, 50);
         assert_eq!(
             player_inventory.slot(expected_player_slot_id),
-            &ItemStack::new(ItemKind::IronIngot, 31, None)
+            &ItemStack::new(ItemKind::IronIngot, 31)
         );
     }
 
@@ -1866,7 +1894,7 @@ mod dropping_items {
         inventory.readonly = true;
         inventory.set_slot(
             convert_to_player_slot_id(InventoryKind::Generic9x3, 50),
-            ItemStack::new(ItemKind::IronIngot, 32, None),
+            ItemStack::new(ItemKind::IronIngot, 32),
         );
 
         let _inventory_ent = set_up_open_inventory(&mut app, client);
@@ -1891,7 +1919,7 @@ mod dropping_items {
             mode: ClickMode::DropKey,
             slot_changes: vec![SlotChange {
                 idx: 50,
-                stack: ItemStack::new(ItemKind::IronIngot, 31, None),
+                stack: ItemStack::new(ItemKind::IronIngot, 31),
             }]
             .into(),
             carried_item: ItemStack::EMPTY,
@@ -1920,7 +1948,7 @@ mod dropping_items {
         assert_eq!(
             player_inventory.slot(expected_player_slot_id),
             // Inventory is read-only, item is not being dropped
-            &ItemStack::new(ItemKind::IronIngot, 32, None)
+            &ItemStack::new(ItemKind::IronIngot, 32)
         );
     }
 }
@@ -1946,7 +1974,7 @@ fn should_drop_item_stack_player_open_inventory_with_dropkey() {
 
     inventory.set_slot(
         convert_to_player_slot_id(InventoryKind::Generic9x3, 50),
-        ItemStack::new(ItemKind::IronIngot, 32, None),
+        ItemStack::new(ItemKind::IronIngot, 32),
     );
 
     let _inventory_ent = set_up_open_inventory(&mut app, client);
@@ -1997,10 +2025,7 @@ fn should_drop_item_stack_player_open_inventory_with_dropkey() {
         events[0].from_slot,
         Some(convert_to_player_slot_id(InventoryKind::Generic9x3, 50))
     );
-    assert_eq!(
-        events[0].stack,
-        ItemStack::new(ItemKind::IronIngot, 32, None)
-    );
+    assert_eq!(events[0].stack, ItemStack::new(ItemKind::IronIngot, 32));
 
     // Also make sure that the player inventory was updated correctly.
     let expected_player_slot_id = convert_to_player_slot_id(InventoryKind::Generic9x3, 50);
@@ -2024,12 +2049,26 @@ fn dragging_items() {
     helper.clear_received();
 
     app.world_mut().get_mut::<CursorItem>(client).unwrap().0 =
-        ItemStack::new(ItemKind::Diamond, 64, None);
+        ItemStack::new(ItemKind::Diamond, 64);
 
     let inv_state = app.world_mut().get::<ClientInventoryState>(client).unwrap();
     let window_id = inv_state.window_id();
     let state_id = inv_state.state_id().0;
 
+    // The client starts the drag, drags over three slots and then ends it. Only
+    // the last packet contains the changes.
+    for (slot_idx, button) in [(-999, 0), (9, 1), (10, 1), (11, 1)] {
+        helper.send(&ClickSlotC2s {
+            window_id,
+            state_id: VarInt(state_id),
+            slot_idx,
+            button,
+            mode: ClickMode::Drag,
+            slot_changes: vec![].into(),
+            carried_item: ItemStack::new(ItemKind::Diamond, 64),
+        });
+    }
+
     let drag_packet = ClickSlotC2s {
         window_id,
         state_id: VarInt(state_id),
@@ -2039,19 +2078,19 @@ fn dragging_items() {
         slot_changes: vec![
             SlotChange {
                 idx: 9,
-                stack: ItemStack::new(ItemKind::Diamond, 21, None),
+                stack: ItemStack::new(ItemKind::Diamond, 21),
             },
             SlotChange {
                 idx: 10,
-                stack: ItemStack::new(ItemKind::Diamond, 21, None),
+                stack: ItemStack::new(ItemKind::Diamond, 21),
             },
             SlotChange {
                 idx: 11,
-                stack: ItemStack::new(ItemKind::Diamond, 21, None),
+                stack: ItemStack::new(ItemKind::Diamond, 21),
             },
         ]
         .into(),
-        carried_item: ItemStack::new(ItemKind::Diamond, 1, None),
+        carried_item: ItemStack::new(ItemKind::Diamond, 1),
     };
     helper.send(&drag_packet);
 
@@ -2064,7 +2103,7 @@ fn dragging_items() {
         .get::<CursorItem>(client)
         .expect("could not find client");
 
-    assert_eq!(cursor_item.0, ItemStack::new(ItemKind::Diamond, 1, None));
+    assert_eq!(cursor_item.0, ItemStack::new(ItemKind::Diamond, 1));
 
     let inventory = app
         .world_mut()
@@ -2072,9 +2111,6 @@ fn dragging_items() {
         .expect("could not find inventory");
 
     for i in 9..12 {
-        assert_eq!(
-            inventory.slot(i),
-            &ItemStack::new(ItemKind::Diamond, 21, None)
-        );
+        assert_eq!(inventory.slot(i), &ItemStack::new(ItemKind::Diamond, 21));
     }
 }
@@ -0,0 +1,184 @@
+use bevy_ecs::prelude::*;
+
+use crate::entity::item::{ItemEntityBundle, Stack};
+use crate::entity::{EntityLayerId, Position};
+use crate::inventory::item_entity::{
+    ItemAge, ItemEntityPlugin, PickupDelay, PickupItemEvent, DESPAWN_AGE, DROPPED_PICKUP_DELAY,
+};
+use crate::inventory::{DropItemStackEvent, Inventory};
+use crate::protocol::packets::play::ItemPickupAnimationS2c;
+use crate::testing::ScenarioSingleClient;
+use crate::{ItemKind, ItemStack};
+
+fn spawn_item(scenario: &mut ScenarioSingleClient, pos: [f64; 3], stack: ItemStack) -> Entity {
+    scenario
+        .app
+        .world_mut()
+        .spawn((
+            ItemEntityBundle {
+                position: Position::new(pos),
+                layer: EntityLayerId(scenario.layer),
+                item_stack: Stack(stack),
+                ..Default::default()
+            },
+            PickupDelay(0),
+        ))
+        .id()
+}
+
+#[test]
+fn dropped_stack_spawns_item_entity() {
+    let mut scenario = ScenarioSingleClient::with_floor(ItemEntityPlugin);
+
+    scenario.app.world_mut().send_event(DropItemStackEvent {
+        client: scenario.client,
+        from_slot: Some(36),
+        stack: ItemStack::new(ItemKind::Diamond, 3),
+    });
+
+    scenario.app.update();
+
+    let mut items = scenario
+        .app
+        .world_mut()
+        .query::<(&Stack, &Position, &PickupDelay)>();
+
+    let items = items.iter(scenario.app.world()).collect::<Vec<_>>();
+
+    assert_eq!(items.len(), 1);
+
+    let (stack, pos, delay) = items[0];
+
+    assert_eq!(stack.0, ItemStack::new(ItemKind::Diamond, 3));
+    assert!(pos.0.y > 64.0, "item should be dropped from the eyes");
+    assert!(delay.0 > 0 && delay.0 <= DROPPED_PICKUP_DELAY);
+}
+
+#[test]
+fn player_picks_up_item() {
+    let mut scenario = ScenarioSingleClient::with_floor(ItemEntityPlugin);
+
+    let item = spawn_item(
+        &mut scenario,
+        [9.0, 64.0, 8.5],
+        ItemStack::new(ItemKind::Diamond, 3),
+    );
+
+    scenario.app.update();
+
+    let inventory = scenario
+        .app
+        .world()
+        .get::<Inventory>(scenario.client)
+        .unwrap();
+
+    assert_eq!(inventory.slot(36), &ItemStack::new(ItemKind::Diamond, 3));
+    assert!(scenario.app.world().get_entity(item).is_none());
+
+    let events = scenario
+        .app
+        .world()
+        .resource::<Events<PickupItemEvent>>()
+        .iter_current_update_events()
+        .collect::<Vec<_>>();
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].item_entity, item);
+
+    let frames = scenario.helper.collect_received();
+    frames.assert_count::<ItemPickupAnimationS2c>(1);
+}
+
+#[test]
+fn pickup_delay_prevents_pickup() {
+    let mut scenario = ScenarioSingleClient::with_floor(ItemEntityPlugin);
+
+    let item = spawn_item(
+        &mut scenario,
+        [9.0, 64.0, 8.5],
+        ItemStack::new(ItemKind::Diamond, 3),
+    );
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(item)
+        .insert(PickupDelay(5));
+
+    for _ in 0..4 {
+        scenario.app.update();
+    }
+
+    assert!(scenario.app.world().get_entity(item).is_some());
+
+    scenario.app.update();
+
+    assert!(scenario.app.world().get_entity(item).is_none());
+}
+
+#[test]
+fn nearby_items_merge() {
+    let mut scenario = ScenarioSingleClient::with_floor(ItemEntityPlugin);
+
+    let a = spawn_item(
+        &mut scenario,
+        [12.5, 64.0, 12.5],
+        ItemStack::new(ItemKind::Stone, 40),
+    );
+    let b = spawn_item(
+        &mut scenario,
+        [13.0, 64.0, 12.5],
+        ItemStack::new(ItemKind::Stone, 30),
+    );
+    let c = spawn_item(
+        &mut scenario,
+        [12.5, 64.0, 13.0],
+        ItemStack::new(ItemKind::Dirt, 1),
+    );
+
+    scenario.app.update();
+
+    let world = scenario.app.world();
+
+    // The larger stack takes in as much of the smaller stack as it can.
+    assert_eq!(
+        world.get::<Stack>(a).unwrap().0,
+        ItemStack::new(ItemKind::Stone, 64)
+    );
+    assert_eq!(
+        world.get::<Stack>(b).unwrap().0,
+        ItemStack::new(ItemKind::Stone, 6)
+    );
+    assert!(world.get_entity(b).is_some());
+
+    // Different items are never merged.
+    assert_eq!(
+        world.get::<Stack>(c).unwrap().0,
+        ItemStack::new(ItemKind::Dirt, 1)
+    );
+}
+
+#[test]
+fn old_items_despawn() {
+    let mut scenario = ScenarioSingleClient::with_floor(ItemEntityPlugin);
+
+    let item = spawn_item(
+        &mut scenario,
+        [12.5, 64.0, 12.5],
+        ItemStack::new(ItemKind::Stone, 1),
+    );
+
+    scenario
+        .app
+        .world_mut()
+        .entity_mut(item)
+        .insert(ItemAge(DESPAWN_AGE - 2));
+
+    scenario.app.update();
+
+    assert!(scenario.app.world().get_entity(item).is_some());
+
+    scenario.app.update();
+
+    assert!(scenario.app.world().get_entity(item).is_none());
+}
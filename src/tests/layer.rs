@@ -2,6 +2,7 @@ use std::collections::BTreeSet;
 
 use bevy_ecs::world::EntityWorldMut;
 
+use crate::block_entity::{Container, Sign};
 use crate::client::{ViewDistance, VisibleEntityLayers};
 use crate::entity::cow::CowEntityBundle;
 use crate::entity::{EntityLayerId, Position};
@@ -13,6 +14,7 @@ use crate::protocol::packets::play::{
 };
 use crate::protocol::Packet;
 use crate::testing::ScenarioSingleClient;
+use crate::text::IntoText;
 use crate::{BlockState, ChunkView, Despawned, Server};
 
 #[test]
@@ -64,6 +66,49 @@ fn block_create_destroy() {
     }
 }
 
+#[test]
+fn typed_block_entity() {
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        layer: layer_ent,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world_mut().get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+    layer.set_block([1, 1, 1], BlockState::OAK_SIGN);
+
+    app.update();
+    helper.clear_received();
+
+    let mut layer = app.world_mut().get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    // The block entity kind must match the requested type.
+    assert_eq!(layer.block_entity_as::<Container, _>([1, 1, 1]), None);
+    assert_eq!(
+        layer.modify_block_entity([1, 1, 1], |_: &mut Container| ()),
+        None
+    );
+
+    layer.modify_block_entity([1, 1, 1], |sign: &mut Sign| {
+        sign.front_text.messages[0] = "Hello".into_text();
+        sign.is_waxed = true;
+    });
+
+    let sign = layer.block_entity_as::<Sign, _>([1, 1, 1]).unwrap();
+
+    assert_eq!(sign.front_text.messages[0], "Hello".into_text());
+    assert!(sign.is_waxed);
+
+    app.update();
+
+    helper
+        .collect_received()
+        .assert_count::<BlockEntityUpdateS2c>(1);
+}
+
 #[test]
 fn layer_chunk_view_change() {
     fn view(client: &EntityWorldMut) -> ChunkView {
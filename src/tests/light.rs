@@ -0,0 +1,120 @@
+use crate::layer::chunk::UnloadedChunk;
+use crate::layer::ChunkLayer;
+use crate::protocol::packets::play::{ChunkDataS2c, LightUpdateS2c};
+use crate::testing::ScenarioSingleClient;
+use crate::BlockState;
+
+#[test]
+fn chunk_sent_with_light() {
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        layer: layer_ent,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world_mut().get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    layer.set_lighting_enabled(true);
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+    layer.set_block([8, 64, 8], BlockState::STONE);
+
+    app.update();
+
+    {
+        let recvd = helper.collect_received();
+        let pkt = recvd.first::<ChunkDataS2c>();
+
+        assert!(pkt.sky_light_mask.iter().any(|&bits| bits != 0));
+        assert!(!pkt.sky_light_arrays.is_empty());
+    }
+
+    let layer = app.world().get::<ChunkLayer>(layer_ent).unwrap();
+
+    // Directly under the sky.
+    assert_eq!(layer.sky_light([8, 65, 8]), Some(15));
+    assert_eq!(layer.sky_light([0, 64, 0]), Some(15));
+    // Shadowed by the stone and lit from the side.
+    assert_eq!(layer.sky_light([8, 63, 8]), Some(14));
+    assert_eq!(layer.sky_light([8, 64, 8]), Some(0));
+    // Not loaded.
+    assert_eq!(layer.sky_light([100, 64, 100]), None);
+}
+
+#[test]
+fn light_update_on_block_change() {
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        layer: layer_ent,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world_mut().get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    layer.set_lighting_enabled(true);
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+
+    app.update();
+
+    helper.clear_received();
+
+    let mut layer = app.world_mut().get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    layer.set_block([4, 64, 4], BlockState::GLOWSTONE);
+
+    app.update();
+
+    helper.collect_received().assert_count::<LightUpdateS2c>(1);
+
+    let layer = app.world().get::<ChunkLayer>(layer_ent).unwrap();
+
+    assert_eq!(layer.block_light([4, 64, 4]), Some(15));
+    assert_eq!(layer.block_light([5, 64, 4]), Some(14));
+    assert_eq!(layer.block_light([4, 60, 4]), Some(11));
+
+    let mut layer = app.world_mut().get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    layer.set_block([4, 64, 4], BlockState::AIR);
+
+    app.update();
+
+    helper.collect_received().assert_count::<LightUpdateS2c>(1);
+
+    let layer = app.world().get::<ChunkLayer>(layer_ent).unwrap();
+
+    assert_eq!(layer.block_light([4, 64, 4]), Some(0));
+    assert_eq!(layer.block_light([5, 64, 4]), Some(0));
+}
+
+#[test]
+fn lighting_is_disabled_by_default() {
+    let ScenarioSingleClient {
+        mut app,
+        mut helper,
+        layer: layer_ent,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world_mut().get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    assert!(!layer.lighting_enabled());
+
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+    layer.set_block([4, 64, 4], BlockState::GLOWSTONE);
+
+    app.update();
+
+    {
+        let recvd = helper.collect_received();
+        let pkt = recvd.first::<ChunkDataS2c>();
+
+        assert!(pkt.sky_light_arrays.is_empty());
+        assert!(pkt.block_light_arrays.is_empty());
+        recvd.assert_count::<LightUpdateS2c>(0);
+    }
+
+    let layer = app.world().get::<ChunkLayer>(layer_ent).unwrap();
+
+    assert_eq!(layer.block_light([4, 64, 4]), Some(0));
+}
@@ -0,0 +1,170 @@
+use bevy_app::App;
+use bevy_ecs::event::Events;
+
+use crate::entity::Position;
+use crate::layer::ChunkLayer;
+use crate::math::DVec3;
+use crate::movement::{MovementSettings, MovementViolation, MovementViolationEvent};
+use crate::protocol::packets::play::{PlayerPositionLookS2c, PositionAndOnGroundC2s};
+use crate::testing::ScenarioSingleClient;
+use crate::{BlockPos, BlockState};
+
+fn violations(app: &App) -> Vec<MovementViolationEvent> {
+    app.world()
+        .resource::<Events<MovementViolationEvent>>()
+        .iter_current_update_events()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn movement_within_limits_is_accepted() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_floor(());
+
+    app.insert_resource(MovementSettings {
+        check_speed: true,
+        check_flying: true,
+        check_clipping: true,
+        ..Default::default()
+    });
+
+    helper.send(&PositionAndOnGroundC2s {
+        position: DVec3::new(9.0, 64.0, 8.5),
+        on_ground: true,
+    });
+
+    app.update();
+
+    let pos = app.world().get::<Position>(client).unwrap().0;
+    assert_eq!(pos, DVec3::new(9.0, 64.0, 8.5));
+    assert!(violations(&app).is_empty());
+
+    helper
+        .collect_received()
+        .assert_count::<PlayerPositionLookS2c>(0);
+}
+
+#[test]
+fn moving_too_fast_is_rejected() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_floor(());
+
+    app.insert_resource(MovementSettings {
+        check_speed: true,
+        ..Default::default()
+    });
+
+    helper.send(&PositionAndOnGroundC2s {
+        position: DVec3::new(14.5, 64.0, 8.5),
+        on_ground: true,
+    });
+
+    app.update();
+
+    let pos = app.world().get::<Position>(client).unwrap().0;
+    assert_eq!(pos, DVec3::new(8.5, 64.0, 8.5));
+
+    let violations = violations(&app);
+    assert_eq!(violations.len(), 1);
+    assert!(matches!(
+        violations[0].violation,
+        MovementViolation::Speed { distance, .. } if distance == 6.0
+    ));
+    assert_eq!(violations[0].position, DVec3::new(14.5, 64.0, 8.5));
+
+    // The client is teleported back.
+    let sent_packets = helper.collect_received();
+    sent_packets.assert_count::<PlayerPositionLookS2c>(1);
+    assert_eq!(
+        sent_packets.first::<PlayerPositionLookS2c>().position,
+        DVec3::new(8.5, 64.0, 8.5)
+    );
+}
+
+#[test]
+fn flying_without_permission_is_rejected() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::with_floor(());
+
+    app.insert_resource(MovementSettings {
+        check_flying: true,
+        ..Default::default()
+    });
+
+    // A regular jump is fine.
+    helper.send(&PositionAndOnGroundC2s {
+        position: DVec3::new(8.5, 65.0, 8.5),
+        on_ground: false,
+    });
+
+    app.update();
+
+    assert!(violations(&app).is_empty());
+
+    // Rising further is not.
+    helper.send(&PositionAndOnGroundC2s {
+        position: DVec3::new(8.5, 66.0, 8.5),
+        on_ground: false,
+    });
+
+    app.update();
+
+    let pos = app.world().get::<Position>(client).unwrap().0;
+    assert_eq!(pos, DVec3::new(8.5, 65.0, 8.5));
+
+    let violations = violations(&app);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].violation, MovementViolation::Flying);
+}
+
+#[test]
+fn moving_into_block_is_rejected() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        layer,
+    } = ScenarioSingleClient::with_floor(());
+
+    app.insert_resource(MovementSettings {
+        check_clipping: true,
+        ..Default::default()
+    });
+
+    app.world_mut()
+        .get_mut::<ChunkLayer>(layer)
+        .unwrap()
+        .set_block([8, 64, 9], BlockState::STONE);
+
+    helper.send(&PositionAndOnGroundC2s {
+        position: DVec3::new(8.5, 64.0, 9.5),
+        on_ground: true,
+    });
+
+    app.update();
+
+    let pos = app.world().get::<Position>(client).unwrap().0;
+    assert_eq!(pos, DVec3::new(8.5, 64.0, 8.5));
+
+    let violations = violations(&app);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(
+        violations[0].violation,
+        MovementViolation::Clipping {
+            block: BlockPos::new(8, 64, 9)
+        }
+    );
+}
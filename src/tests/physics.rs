@@ -0,0 +1,81 @@
+use crate::entity::cow::CowEntityBundle;
+use crate::entity::item::ItemEntityBundle;
+use crate::entity::{EntityLayerId, OnGround, Position, Velocity};
+use crate::layer::ChunkLayer;
+use crate::math::Vec3;
+use crate::physics::PhysicsPlugin;
+use crate::testing::ScenarioSingleClient;
+use crate::BlockState;
+
+#[test]
+fn entity_falls_onto_block() {
+    let mut scenario = ScenarioSingleClient::with_floor(PhysicsPlugin);
+
+    let cow = scenario
+        .app
+        .world_mut()
+        .spawn(CowEntityBundle {
+            position: Position::new([8.5, 70.0, 8.5]),
+            layer: EntityLayerId(scenario.layer),
+            ..Default::default()
+        })
+        .id();
+
+    // The hitbox of the cow is added during the first tick.
+    scenario.app.update();
+    scenario.app.update();
+
+    let pos = scenario.app.world().get::<Position>(cow).unwrap().0;
+    assert!(pos.y < 70.0, "cow should start falling");
+    assert!(!scenario.app.world().get::<OnGround>(cow).unwrap().0);
+
+    for _ in 0..40 {
+        scenario.app.update();
+    }
+
+    let pos = scenario.app.world().get::<Position>(cow).unwrap().0;
+    assert!((pos.y - 64.0).abs() < 1e-9, "unexpected position {pos}");
+    assert_eq!(pos.x, 8.5);
+    assert_eq!(pos.z, 8.5);
+    assert!(scenario.app.world().get::<OnGround>(cow).unwrap().0);
+}
+
+#[test]
+fn entity_stopped_by_wall() {
+    let mut scenario = ScenarioSingleClient::with_floor(PhysicsPlugin);
+
+    let mut layer = scenario
+        .app
+        .world_mut()
+        .get_mut::<ChunkLayer>(scenario.layer)
+        .unwrap();
+
+    for z in 0..16 {
+        layer.set_block([12, 64, z], BlockState::STONE);
+        layer.set_block([12, 65, z], BlockState::STONE);
+    }
+
+    let item = scenario
+        .app
+        .world_mut()
+        .spawn(ItemEntityBundle {
+            position: Position::new([4.5, 64.0, 8.5]),
+            velocity: Velocity(Vec3::new(100.0, 0.0, 0.0)),
+            layer: EntityLayerId(scenario.layer),
+            ..Default::default()
+        })
+        .id();
+
+    for _ in 0..20 {
+        scenario.app.update();
+    }
+
+    let pos = scenario.app.world().get::<Position>(item).unwrap().0;
+    let vel = scenario.app.world().get::<Velocity>(item).unwrap().0;
+
+    // The item's hitbox is 0.25 blocks wide, so it stops 0.125 blocks from the
+    // wall.
+    assert!((pos.x - 11.875).abs() < 1e-6, "unexpected position {pos}");
+    assert!((pos.y - 64.0).abs() < 1e-9, "unexpected position {pos}");
+    assert_eq!(vel.x, 0.0);
+}
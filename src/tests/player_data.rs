@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::client::{VisibleChunkLayer, VisibleEntityLayers};
+use crate::entity::living::Health;
+use crate::entity::player::Experience;
+use crate::entity::{EntityLayerId, Position};
+use crate::inventory::Inventory;
+use crate::math::DVec3;
+use crate::nbt::{Compound, List, Value};
+use crate::player_data::{PlayerData, PlayerDataStorage, PlayerDataStore};
+use crate::protocol::anyhow;
+use crate::registry::{BiomeRegistry, DimensionTypeRegistry, RegistryCodec};
+use crate::testing::ScenarioSingleClient;
+use crate::uuid::Uuid;
+use crate::{
+    ident, ChunkLayer, Despawned, EntityLayer, GameMode, ItemKind, ItemStack, Server, UniqueId,
+};
+
+/// A player data store keeping everything in memory.
+#[derive(Clone, Default)]
+struct MemoryStore(Arc<Mutex<HashMap<Uuid, Compound>>>);
+
+impl PlayerDataStore for MemoryStore {
+    fn load(&self, uuid: Uuid) -> anyhow::Result<Option<Compound>> {
+        Ok(self.0.lock().unwrap().get(&uuid).cloned())
+    }
+
+    fn save(&self, uuid: Uuid, data: &Compound) -> anyhow::Result<()> {
+        self.0.lock().unwrap().insert(uuid, data.clone());
+        Ok(())
+    }
+}
+
+#[test]
+fn saved_data_is_applied_on_join() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::new();
+
+    let uuid = app.world().get::<UniqueId>(client).unwrap().0;
+
+    let data = PlayerData {
+        position: Some(DVec3::new(10.5, 80.0, -4.5)),
+        game_mode: Some(GameMode::Creative),
+        inventory: vec![(36, ItemStack::new(ItemKind::Diamond, 3))],
+        experience: Some(Experience {
+            level: 3,
+            progress: 0.25,
+            total: 30,
+        }),
+        health: Some(7.0),
+        ..Default::default()
+    };
+
+    let store = MemoryStore::default();
+    store
+        .0
+        .lock()
+        .unwrap()
+        .insert(uuid, data.to_nbt(app.world().resource::<RegistryCodec>()));
+
+    app.insert_resource(PlayerDataStorage::new(store));
+    app.update();
+
+    // Wait for the worker thread to load the data, which is applied on the next
+    // update.
+    app.world().resource::<PlayerDataStorage>().flush();
+    app.update();
+
+    let world = app.world();
+
+    assert_eq!(
+        world.get::<Position>(client).unwrap().0,
+        DVec3::new(10.5, 80.0, -4.5)
+    );
+    assert_eq!(*world.get::<GameMode>(client).unwrap(), GameMode::Creative);
+    assert_eq!(
+        *world.get::<Inventory>(client).unwrap().slot(36),
+        ItemStack::new(ItemKind::Diamond, 3)
+    );
+    assert_eq!(world.get::<Experience>(client).unwrap().level, 3);
+    assert_eq!(world.get::<Health>(client).unwrap().0, 7.0);
+}
+
+#[test]
+fn data_is_saved_on_despawn() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::new();
+
+    let uuid = app.world().get::<UniqueId>(client).unwrap().0;
+
+    let store = MemoryStore::default();
+
+    // Unknown keys written by other programs are kept.
+    let mut existing = Compound::new();
+    existing.insert("Custom", 42);
+    store.0.lock().unwrap().insert(uuid, existing);
+
+    app.insert_resource(PlayerDataStorage::new(store.clone()));
+    app.update();
+    app.world().resource::<PlayerDataStorage>().flush();
+    app.update();
+
+    let mut entity = app.world_mut().entity_mut(client);
+    entity.get_mut::<Position>().unwrap().0 = DVec3::new(1.0, 2.0, 3.0);
+    entity.get_mut::<Health>().unwrap().0 = 5.0;
+    entity
+        .get_mut::<Inventory>()
+        .unwrap()
+        .set_slot(45, ItemStack::new(ItemKind::Shield, 1));
+
+    app.update();
+    app.world().resource::<PlayerDataStorage>().flush();
+
+    // Nothing is saved while the client is connected.
+    assert!(!store.0.lock().unwrap()[&uuid].contains_key("Pos"));
+
+    app.world_mut().entity_mut(client).insert(Despawned);
+    app.update();
+    app.world().resource::<PlayerDataStorage>().flush();
+
+    assert!(app.world().get_entity(client).is_none());
+
+    let saved = store.0.lock().unwrap()[&uuid].clone();
+
+    assert_eq!(saved.get("Custom"), Some(&Value::Int(42)));
+    assert_eq!(
+        saved.get("Pos"),
+        Some(&Value::List(List::Double(vec![1.0, 2.0, 3.0])))
+    );
+    assert_eq!(saved.get("Health"), Some(&Value::Float(5.0)));
+    assert_eq!(
+        saved.get("Dimension"),
+        Some(&Value::String("minecraft:overworld".into()))
+    );
+
+    let data = PlayerData::from_nbt(&saved, app.world().resource::<RegistryCodec>());
+    assert_eq!(
+        data.inventory,
+        vec![(45, ItemStack::new(ItemKind::Shield, 1))]
+    );
+}
+
+#[test]
+fn saved_dimension_moves_client_to_layer() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        layer,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let nether = ChunkLayer::new(
+        ident!("the_nether"),
+        app.world().resource::<DimensionTypeRegistry>(),
+        app.world().resource::<BiomeRegistry>(),
+        app.world().resource::<Server>(),
+    );
+    let entity_layer = EntityLayer::new(app.world().resource::<Server>());
+    let nether = app.world_mut().spawn((nether, entity_layer)).id();
+
+    let uuid = app.world().get::<UniqueId>(client).unwrap().0;
+
+    let data = PlayerData {
+        dimension: Some(ident!("the_nether").into()),
+        ..Default::default()
+    };
+
+    let store = MemoryStore::default();
+    store
+        .0
+        .lock()
+        .unwrap()
+        .insert(uuid, data.to_nbt(app.world().resource::<RegistryCodec>()));
+
+    app.insert_resource(PlayerDataStorage::new(store));
+    app.update();
+    app.world().resource::<PlayerDataStorage>().flush();
+    app.update();
+
+    let world = app.world();
+
+    assert_eq!(world.get::<EntityLayerId>(client).unwrap().0, nether);
+    assert_eq!(world.get::<VisibleChunkLayer>(client).unwrap().0, nether);
+
+    let visible = &world.get::<VisibleEntityLayers>(client).unwrap().0;
+    assert!(visible.contains(&nether));
+    assert!(!visible.contains(&layer));
+}
@@ -1,8 +1,14 @@
+use bevy_app::App;
 use valence_server::entity::active_status_effects::{ActiveStatusEffect, ActiveStatusEffects};
+use valence_server::entity::attributes::{EntityAttribute, EntityAttributes};
+use valence_server::entity::entity::Flags;
+use valence_server::entity::living::Health;
 use valence_server::protocol::packets::play::{EntityStatusEffectS2c, RemoveEntityStatusEffectS2c};
 use valence_server::protocol::status_effects::StatusEffect;
 use valence_server::protocol::VarInt;
 
+use crate::combat::CombatPlugin;
+use crate::effect::{EffectFlags, EffectPlugin};
 use crate::testing::ScenarioSingleClient;
 
 #[test]
@@ -69,3 +75,99 @@ fn test_status_effects_packets() {
     assert_eq!(packet.entity_id, VarInt(0)); // Client entity ID is always 0
     assert_eq!(packet.effect_id, VarInt(31)); // Bad Omen
 }
+
+#[test]
+fn speed_modifies_movement_speed() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, EffectPlugin));
+
+    app.world_mut()
+        .get_mut::<ActiveStatusEffects>(client)
+        .unwrap()
+        .apply(
+            ActiveStatusEffect::from_effect(StatusEffect::Speed)
+                .with_duration(20)
+                .with_amplifier(1),
+        );
+
+    let movement_speed = |app: &App| {
+        app.world()
+            .get::<EntityAttributes>(client)
+            .unwrap()
+            .get_compute_value(EntityAttribute::GenericMovementSpeed)
+            .unwrap()
+    };
+
+    app.update();
+
+    assert!((movement_speed(&app) - 0.14).abs() < 1e-6);
+
+    for _ in 0..20 {
+        app.update();
+    }
+
+    assert!((movement_speed(&app) - 0.1).abs() < 1e-6);
+}
+
+#[test]
+fn poison_does_not_kill() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, EffectPlugin));
+
+    app.world_mut()
+        .get_mut::<ActiveStatusEffects>(client)
+        .unwrap()
+        .apply(ActiveStatusEffect::from_effect(StatusEffect::Poison).with_duration(200));
+
+    app.world_mut().get_mut::<Health>(client).unwrap().0 = 2.0;
+
+    for _ in 0..100 {
+        app.update();
+    }
+
+    assert_eq!(app.world().get::<Health>(client).unwrap().0, 1.0);
+}
+
+#[test]
+fn instant_health_heals() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, EffectPlugin));
+
+    app.world_mut()
+        .get_mut::<ActiveStatusEffects>(client)
+        .unwrap()
+        .apply(ActiveStatusEffect::from_effect(StatusEffect::InstantHealth));
+
+    app.world_mut().get_mut::<Health>(client).unwrap().0 = 10.0;
+
+    app.update();
+
+    assert_eq!(app.world().get::<Health>(client).unwrap().0, 14.0);
+}
+
+#[test]
+fn invisibility_sets_flags() {
+    let ScenarioSingleClient {
+        mut app, client, ..
+    } = ScenarioSingleClient::with_floor((CombatPlugin, EffectPlugin));
+
+    app.world_mut()
+        .get_mut::<ActiveStatusEffects>(client)
+        .unwrap()
+        .apply(ActiveStatusEffect::from_effect(StatusEffect::Invisibility).with_duration(10));
+
+    app.update();
+
+    assert!(app.world().get::<Flags>(client).unwrap().invisible());
+    assert!(app.world().get::<EffectFlags>(client).unwrap().invisible());
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    assert!(!app.world().get::<Flags>(client).unwrap().invisible());
+    assert!(!app.world().get::<EffectFlags>(client).unwrap().invisible());
+}
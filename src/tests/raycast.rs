@@ -0,0 +1,128 @@
+use crate::entity::cow::CowEntityBundle;
+use crate::entity::hitbox::Hitbox;
+use crate::entity::{EntityLayerId, Position};
+use crate::layer::chunk::UnloadedChunk;
+use crate::layer::ChunkLayer;
+use crate::math::DVec3;
+use crate::protocol::Direction;
+use crate::raycast::{raycast, RaycastHit, RaycastShape};
+use crate::testing::ScenarioSingleClient;
+use crate::{BlockPos, BlockState};
+
+#[test]
+fn raycast_blocks_hits_first_block() {
+    let ScenarioSingleClient {
+        mut app,
+        layer: layer_ent,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world_mut().get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+    layer.set_block([5, 64, 0], BlockState::STONE);
+    layer.set_block([8, 64, 0], BlockState::STONE);
+    // A bottom slab only fills the lower half of the block.
+    layer.set_block([2, 65, 0], BlockState::SMOOTH_STONE_SLAB);
+
+    let layer = app.world().get::<ChunkLayer>(layer_ent).unwrap();
+
+    let origin = DVec3::new(0.5, 64.5, 0.5);
+
+    let hit = layer
+        .raycast_blocks(origin, DVec3::X, 10.0, RaycastShape::Collision)
+        .unwrap();
+
+    assert_eq!(hit.block_pos, BlockPos::new(5, 64, 0));
+    assert_eq!(hit.state, BlockState::STONE);
+    assert_eq!(hit.face, Direction::West);
+    assert_eq!(hit.distance, 4.5);
+    assert_eq!(hit.pos, DVec3::new(5.0, 64.5, 0.5));
+
+    // Out of range.
+    assert!(layer
+        .raycast_blocks(origin, DVec3::X, 4.0, RaycastShape::Collision)
+        .is_none());
+
+    // Passes over the slab.
+    let hit = layer
+        .raycast_blocks(
+            DVec3::new(0.5, 65.75, 0.5),
+            DVec3::X,
+            10.0,
+            RaycastShape::Collision,
+        )
+        .map(|hit| hit.block_pos);
+    assert_eq!(hit, None);
+
+    // Hits the top of the slab.
+    let hit = layer
+        .raycast_blocks(
+            DVec3::new(2.5, 67.0, 0.5),
+            DVec3::NEG_Y,
+            10.0,
+            RaycastShape::Collision,
+        )
+        .unwrap();
+    assert_eq!(hit.block_pos, BlockPos::new(2, 65, 0));
+    assert_eq!(hit.face, Direction::Up);
+    assert_eq!(hit.distance, 1.5);
+}
+
+#[test]
+fn raycast_hits_closest_entity_or_block() {
+    let ScenarioSingleClient {
+        mut app,
+        layer: layer_ent,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let mut layer = app.world_mut().get_mut::<ChunkLayer>(layer_ent).unwrap();
+
+    layer.insert_chunk([0, 0], UnloadedChunk::new());
+    layer.set_block([8, 64, 8], BlockState::STONE);
+
+    let cow = app
+        .world_mut()
+        .spawn(CowEntityBundle {
+            position: Position::new([4.5, 64.0, 8.5]),
+            layer: EntityLayerId(layer_ent),
+            ..Default::default()
+        })
+        .id();
+
+    // Let the hitbox be added and sized.
+    app.update();
+    app.update();
+
+    let hitbox = app.world().get::<Hitbox>(cow).unwrap().get();
+    let layer = app.world().get::<ChunkLayer>(layer_ent).unwrap();
+
+    let origin = DVec3::new(0.5, 64.5, 8.5);
+
+    let hit = raycast(
+        layer,
+        [(cow, hitbox)],
+        origin,
+        DVec3::X,
+        10.0,
+        RaycastShape::Collision,
+    );
+
+    match hit {
+        Some(RaycastHit::Entity(hit)) => {
+            assert_eq!(hit.entity, cow);
+            assert_eq!(hit.face, Direction::West);
+            assert!((hit.distance - (hitbox.min().x - origin.x)).abs() < 1e-9);
+        }
+        other => panic!("expected to hit the cow, got {other:?}"),
+    }
+
+    // Without the cow, the block behind it is hit.
+    let hit = raycast(layer, [], origin, DVec3::X, 10.0, RaycastShape::Collision);
+
+    match hit {
+        Some(RaycastHit::Block(hit)) => assert_eq!(hit.block_pos, BlockPos::new(8, 64, 8)),
+        other => panic!("expected to hit the block, got {other:?}"),
+    }
+}
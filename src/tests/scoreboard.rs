@@ -1,10 +1,15 @@
+use bevy_app::App;
+use bevy_ecs::entity::Entity;
 use valence_scoreboard::*;
 
 use crate::client::VisibleEntityLayers;
 use crate::entity::EntityLayerId;
 use crate::layer::EntityLayer;
+use crate::protocol::packets::play::scoreboard_objective_update_s2c::ObjectiveMode;
+use crate::protocol::packets::play::team_s2c::Mode as TeamMode;
 use crate::protocol::packets::play::{
     ScoreboardDisplayS2c, ScoreboardObjectiveUpdateS2c, ScoreboardPlayerUpdateS2c,
+    ScoreboardScoreResetS2c, TeamS2c,
 };
 use crate::testing::ScenarioSingleClient;
 use crate::text::IntoText;
@@ -191,3 +196,252 @@ fn should_only_update_score_diff() {
         recvd.assert_count::<ScoreboardPlayerUpdateS2c>(1);
     }
 }
+
+/// Spawns a team in a new entity layer visible to the client.
+fn spawn_team(app: &mut App, client: Entity) -> Entity {
+    let server = app.world().get_resource::<Server>().unwrap().clone();
+    let team_layer = app.world_mut().spawn(EntityLayer::new(&server)).id();
+
+    app.world_mut()
+        .entity_mut(client)
+        .get_mut::<VisibleEntityLayers>()
+        .unwrap()
+        .0
+        .insert(team_layer);
+
+    app.world_mut()
+        .spawn(TeamBundle {
+            name: Team::new("red"),
+            display: TeamDisplay("Red".into_text()),
+            settings: TeamSettings {
+                color: TeamColor::Red,
+                friendly_fire: false,
+                ..Default::default()
+            },
+            members: TeamMembers::with_members(["test"]),
+            layer: EntityLayerId(team_layer),
+            ..Default::default()
+        })
+        .id()
+}
+
+#[test]
+fn show_team_when_client_join() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    spawn_team(&mut app, client);
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<TeamS2c>(1);
+
+    let pkt = recvd.first::<TeamS2c>();
+    assert_eq!(pkt.team_name, "red");
+    assert!(matches!(
+        pkt.mode,
+        TeamMode::CreateTeam {
+            team_color: TeamColor::Red,
+            ref entities,
+            ..
+        } if entities == &["test"]
+    ));
+}
+
+#[test]
+fn should_update_team_members() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let team = spawn_team(&mut app, client);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    helper.clear_received();
+
+    let mut members = app.world_mut().get_mut::<TeamMembers>(team).unwrap();
+    members.insert("foo");
+    members.remove("test");
+
+    app.update();
+
+    {
+        let recvd = helper.collect_received();
+        recvd.assert_count::<TeamS2c>(2);
+    }
+
+    // Changing the settings updates the team info without resending members.
+    app.world_mut()
+        .get_mut::<TeamSettings>(team)
+        .unwrap()
+        .collision_rule = CollisionRule::Never;
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<TeamS2c>(1);
+    assert!(matches!(
+        recvd.first::<TeamS2c>().mode,
+        TeamMode::UpdateTeamInfo {
+            collision_rule: CollisionRule::Never,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn remove_team_when_layer_hidden() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    let team = spawn_team(&mut app, client);
+
+    app.update();
+    helper.clear_received();
+
+    let team_layer = app.world().get::<EntityLayerId>(team).unwrap().0;
+
+    app.world_mut()
+        .get_mut::<VisibleEntityLayers>(client)
+        .unwrap()
+        .0
+        .remove(&team_layer);
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<TeamS2c>(1);
+    assert!(matches!(
+        recvd.first::<TeamS2c>().mode,
+        TeamMode::RemoveTeam
+    ));
+}
+
+#[test]
+fn show_sidebar_to_client() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    app.update();
+    helper.clear_received();
+
+    app.world_mut()
+        .entity_mut(client)
+        .insert(Sidebar::new("Stats").with_lines(["Kills: 0", "Deaths: 0"]));
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<ScoreboardObjectiveUpdateS2c>(1);
+    recvd.assert_count::<ScoreboardDisplayS2c>(1);
+    recvd.assert_count::<ScoreboardPlayerUpdateS2c>(2);
+    recvd.assert_order::<(
+        ScoreboardObjectiveUpdateS2c,
+        ScoreboardDisplayS2c,
+        ScoreboardPlayerUpdateS2c,
+    )>();
+
+    let pkt = recvd.first::<ScoreboardObjectiveUpdateS2c>();
+    assert_eq!(pkt.objective_name, SIDEBAR_OBJECTIVE_NAME);
+    assert!(matches!(
+        pkt.mode,
+        ObjectiveMode::Create {
+            number_format: Some(NumberFormat::Blank),
+            ..
+        }
+    ));
+
+    // The first line is at the top of the sidebar.
+    let pkt = recvd.first::<ScoreboardPlayerUpdateS2c>();
+    assert_eq!(pkt.objective_score.0, 0);
+    assert_eq!(pkt.display_name.as_deref(), Some(&"Kills: 0".into_text()));
+}
+
+#[test]
+fn should_only_update_sidebar_diff() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    app.world_mut()
+        .entity_mut(client)
+        .insert(Sidebar::new("Stats").with_lines(["Kills: 0", "Deaths: 0", "Score: 0"]));
+
+    app.update();
+    helper.clear_received();
+
+    {
+        let mut sidebar = app.world_mut().get_mut::<Sidebar>(client).unwrap();
+        sidebar.set_line(0, "Kills: 1");
+        sidebar.lines.truncate(2);
+    }
+
+    app.update();
+
+    {
+        let recvd = helper.collect_received();
+        recvd.assert_count::<ScoreboardObjectiveUpdateS2c>(0);
+        recvd.assert_count::<ScoreboardPlayerUpdateS2c>(1);
+        recvd.assert_count::<ScoreboardScoreResetS2c>(1);
+
+        let pkt = recvd.first::<ScoreboardPlayerUpdateS2c>();
+        assert_eq!(pkt.display_name.as_deref(), Some(&"Kills: 1".into_text()));
+    }
+
+    // Changing only the title updates the objective.
+    app.world_mut().get_mut::<Sidebar>(client).unwrap().title = "New Stats".into_text();
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<ScoreboardObjectiveUpdateS2c>(1);
+    recvd.assert_count::<ScoreboardPlayerUpdateS2c>(0);
+}
+
+#[test]
+fn remove_sidebar() {
+    let ScenarioSingleClient {
+        mut app,
+        client,
+        mut helper,
+        ..
+    } = ScenarioSingleClient::new();
+
+    app.world_mut()
+        .entity_mut(client)
+        .insert(Sidebar::new("Stats").with_lines(["Kills: 0"]));
+
+    app.update();
+    helper.clear_received();
+
+    app.world_mut().entity_mut(client).remove::<Sidebar>();
+
+    app.update();
+
+    let recvd = helper.collect_received();
+    recvd.assert_count::<ScoreboardObjectiveUpdateS2c>(1);
+    assert!(matches!(
+        recvd.first::<ScoreboardObjectiveUpdateS2c>().mode,
+        ObjectiveMode::Remove
+    ));
+}
@@ -69,6 +69,7 @@ fn write_packets(packets: &Vec<Packet>) -> anyhow::Result<()> {
             "handshaking" => quote! { valence_protocol::PacketState::Handshaking },
             "status" => quote! { valence_protocol::PacketState::Status },
             "login" => quote! { valence_protocol::PacketState::Login },
+            "configuration" => quote! { valence_protocol::PacketState::Configuration },
             "play" => quote! { valence_protocol::PacketState::Play },
             _ => unreachable!(),
         };
@@ -113,6 +114,7 @@ fn write_transformer(packets: &[Packet]) -> anyhow::Result<()> {
             "handshaking" => "Handshaking".to_owned(),
             "status" => "Status".to_owned(),
             "login" => "Login".to_owned(),
+            "configuration" => "Configuration".to_owned(),
             "play" => "Play".to_owned(),
             _ => panic!("Invalid state"),
         };
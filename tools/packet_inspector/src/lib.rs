@@ -11,11 +11,13 @@ use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
 use valence_protocol::decode::PacketFrame;
+use valence_protocol::packets::configuration::FinishConfigurationC2s;
 use valence_protocol::packets::handshaking::handshake_c2s::HandshakeNextState;
 use valence_protocol::packets::handshaking::HandshakeC2s;
 use valence_protocol::packets::login::{
-    LoginCompressionS2c, LoginDisconnectS2c, LoginHelloS2c, LoginSuccessS2c,
+    LoginAcknowledgedC2s, LoginCompressionS2c, LoginDisconnectS2c, LoginHelloS2c,
 };
+use valence_protocol::packets::play::ConfigurationAcknowledgedC2s;
 use valence_protocol::text::color::NamedColor;
 use valence_protocol::text::{Color, IntoText};
 use valence_protocol::{
@@ -210,6 +212,26 @@ impl Proxy {
                     }
                 }
 
+                // The client acknowledges every switch into and out of the configuration
+                // state, so those transitions are tracked on this side of the proxy.
+                if state == PacketState::Login
+                    && extrapolate_packet::<LoginAcknowledgedC2s>(&packet).is_some()
+                {
+                    *state_lock.write().await = PacketState::Configuration;
+                }
+
+                if state == PacketState::Configuration
+                    && extrapolate_packet::<FinishConfigurationC2s>(&packet).is_some()
+                {
+                    *state_lock.write().await = PacketState::Play;
+                }
+
+                if state == PacketState::Play
+                    && extrapolate_packet::<ConfigurationAcknowledgedC2s>(&packet).is_some()
+                {
+                    *state_lock.write().await = PacketState::Configuration;
+                }
+
                 server_writer.send_packet_raw(&packet).await?;
             }
         });
@@ -239,10 +261,6 @@ impl Proxy {
                     if let Some(LoginCompressionS2c { threshold }) = extrapolate_packet(&packet) {
                         *threshold_lock.write().await = CompressionThreshold(threshold.0);
                     }
-
-                    if extrapolate_packet::<LoginSuccessS2c>(&packet).is_some() {
-                        *state_lock.write().await = PacketState::Play;
-                    }
                 }
 
                 registry
@@ -5,10 +5,13 @@ use anyhow::bail;
 use tokio::io::AsyncWriteExt;
 use tokio::net::TcpStream;
 use uuid::Uuid;
+use valence_protocol::packets::configuration::{
+    self, FinishConfigurationC2s, FinishConfigurationS2c, SelectKnownPacksC2s, SelectKnownPacksS2c,
+};
 use valence_protocol::packets::handshaking::handshake_c2s::HandshakeNextState;
 use valence_protocol::packets::handshaking::HandshakeC2s;
 use valence_protocol::packets::login::{
-    LoginCompressionS2c, LoginHelloC2s, LoginHelloS2c, LoginSuccessS2c,
+    LoginAcknowledgedC2s, LoginCompressionS2c, LoginHelloC2s, LoginHelloS2c, LoginSuccessS2c,
 };
 use valence_protocol::packets::play::{
     KeepAliveC2s, KeepAliveS2c, PlayerPositionLookS2c, PositionAndOnGroundC2s, TeleportConfirmC2s,
@@ -91,6 +94,9 @@ pub async fn make_session(params: &SessionParams<'_>) -> anyhow::Result<()> {
                 }
 
                 LoginSuccessS2c::ID => {
+                    enc.append_packet(&LoginAcknowledgedC2s)?;
+                    conn.write_all(&enc.take()).await?;
+
                     break;
                 }
 
@@ -105,6 +111,57 @@ pub async fn make_session(params: &SessionParams<'_>) -> anyhow::Result<()> {
 
     println!("{sess_name} logged in");
 
+    'configuration: loop {
+        while let Some(frame) = dec.try_next_packet()? {
+            match frame.id {
+                configuration::KeepAliveS2c::ID => {
+                    let packet: configuration::KeepAliveS2c = frame.decode()?;
+                    enc.clear();
+
+                    enc.append_packet(&configuration::KeepAliveC2s { id: packet.id })?;
+                    conn.write_all(&enc.take()).await?;
+                }
+
+                SelectKnownPacksS2c::ID => {
+                    let packet: SelectKnownPacksS2c = frame.decode()?;
+                    enc.clear();
+
+                    enc.append_packet(&SelectKnownPacksC2s {
+                        packs: packet.packs,
+                    })?;
+                    conn.write_all(&enc.take()).await?;
+                }
+
+                FinishConfigurationS2c::ID => {
+                    enc.clear();
+
+                    enc.append_packet(&FinishConfigurationC2s)?;
+                    conn.write_all(&enc.take()).await?;
+
+                    break 'configuration;
+                }
+                _ => (),
+            }
+        }
+
+        dec.reserve(rb_size);
+
+        let mut read_buf = dec.take_capacity();
+
+        conn.readable().await?;
+
+        match conn.try_read_buf(&mut read_buf) {
+            Ok(0) => return Err(io::Error::from(ErrorKind::UnexpectedEof).into()),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+            Err(e) => return Err(e.into()),
+            Ok(_) => (),
+        };
+
+        dec.queue_bytes(read_buf);
+    }
+
+    println!("{sess_name} configured");
+
     loop {
         while let Some(frame) = dec.try_next_packet()? {
             match frame.id {